@@ -0,0 +1,42 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rw_parser_rs::renderware::txd::dxt;
+
+const SIZE: usize = 1024;
+
+/// A decoder writing a raster of the given size into an RGBA buffer.
+type Decoder = fn(&[u8], usize, usize, &mut [u8]);
+
+/// Returns pseudo-random blocks, which exercise every palette mode.
+fn raster(format: texpresso::Format) -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    (0..format.compressed_size(SIZE, SIZE))
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+fn dxt_benchmark(c: &mut Criterion) {
+    let decoders: [(&str, texpresso::Format, Decoder); 3] = [
+        ("DXT1", texpresso::Format::Bc1, dxt::decode_bc1),
+        ("DXT3", texpresso::Format::Bc2, dxt::decode_bc2),
+        ("DXT5", texpresso::Format::Bc3, dxt::decode_bc3),
+    ];
+
+    for (name, format, decode) in decoders {
+        let data = raster(format);
+        let mut output = vec![0; SIZE * SIZE * 4];
+        let mut group = c.benchmark_group(name);
+        group.bench_function("texpresso", |b| {
+            b.iter(|| format.decompress(black_box(&data), SIZE, SIZE, &mut output))
+        });
+        group.bench_function("fast-dxt", |b| b.iter(|| decode(black_box(&data), SIZE, SIZE, &mut output)));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, dxt_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,313 @@
+//! Synthetic DFF, TXD and IFP files for the benchmarks, generated in memory
+//! so that they run anywhere without game assets.
+//!
+//! Every generator takes a `SizeClass`, which scales the file from a prop to
+//! a whole vehicle, texture dictionary or animation package.
+
+use rw_parser_rs::renderware::rw_file::RwFileWriter;
+use rw_parser_rs::renderware::rw_sections::RwSections;
+use rw_parser_rs::renderware::txd::txd_parser::{RwTextureDictionary, RwTextureNative, RwTxd};
+use rw_parser_rs::renderware::txd::txd_writer::{TxdTextureFormat, TxdWriter};
+use rw_parser_rs::utils::byte_stream::ByteWriter;
+
+/// RenderWare 3.6.0.3, as used by San Andreas.
+const VERSION: u32 = 0x1803FFFF;
+
+#[derive(Debug, Clone, Copy)]
+pub enum SizeClass {
+    Small,
+    Medium,
+    Large,
+}
+
+impl SizeClass {
+    pub const ALL: [SizeClass; 3] = [SizeClass::Small, SizeClass::Medium, SizeClass::Large];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SizeClass::Small => "small",
+            SizeClass::Medium => "medium",
+            SizeClass::Large => "large",
+        }
+    }
+
+    /// Returns the number of geometries and of vertices per geometry.
+    fn dff_size(self) -> (u32, u32) {
+        match self {
+            SizeClass::Small => (1, 500),
+            SizeClass::Medium => (4, 5_000),
+            SizeClass::Large => (16, 20_000),
+        }
+    }
+
+    /// Returns the number of textures and their width and height.
+    fn txd_size(self) -> (usize, u16) {
+        match self {
+            SizeClass::Small => (4, 64),
+            SizeClass::Medium => (16, 256),
+            SizeClass::Large => (8, 1024),
+        }
+    }
+
+    /// Returns the number of animations, bones per animation and keyframes
+    /// per bone.
+    fn ifp_size(self) -> (u32, u32, u32) {
+        match self {
+            SizeClass::Small => (4, 16, 10),
+            SizeClass::Medium => (32, 32, 30),
+            SizeClass::Large => (128, 64, 60),
+        }
+    }
+}
+
+/// Returns a clump with textured, prelit geometries of a grid of vertices,
+/// each with a bin mesh and an atomic.
+pub fn dff(size: SizeClass) -> Vec<u8> {
+    let (geometry_count, vertex_count) = size.dff_size();
+    let mut writer = RwFileWriter::new();
+
+    writer.begin_section(RwSections::RwClump as u32, VERSION);
+    writer.begin_section(RwSections::RwStruct as u32, VERSION);
+    for value in [geometry_count, 0, 0] {
+        writer.get_stream().write_u32(value);
+    }
+    writer.end_section();
+
+    writer.begin_section(RwSections::RwFrameList as u32, VERSION);
+    writer.begin_section(RwSections::RwStruct as u32, VERSION);
+    writer.get_stream().write_u32(1);
+    for value in [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0] {
+        writer.get_stream().write_f32(value);
+    }
+    writer.get_stream().write_i32(-1);
+    writer.get_stream().write_u32(0);
+    writer.end_section();
+    writer.end_section();
+
+    writer.begin_section(RwSections::RwGeometryList as u32, VERSION);
+    writer.begin_section(RwSections::RwStruct as u32, VERSION);
+    writer.get_stream().write_u32(geometry_count);
+    writer.end_section();
+    for _ in 0..geometry_count {
+        write_geometry(&mut writer, vertex_count);
+    }
+    writer.end_section();
+
+    for geometry_index in 0..geometry_count {
+        writer.begin_section(RwSections::RwAtomic as u32, VERSION);
+        writer.begin_section(RwSections::RwStruct as u32, VERSION);
+        for value in [0, geometry_index, 5, 0] {
+            writer.get_stream().write_u32(value);
+        }
+        writer.end_section();
+        writer.write_section(RwSections::RwExtension as u32, VERSION, &[]);
+        writer.end_section();
+    }
+
+    writer.write_section(RwSections::RwExtension as u32, VERSION, &[]);
+    writer.end_section();
+    writer.into_bytes()
+}
+
+fn write_geometry(writer: &mut RwFileWriter, vertex_count: u32) {
+    let columns = 100;
+    let triangles: Vec<[u32; 3]> = (0..vertex_count - columns - 1)
+        .filter(|vertex| vertex % columns != columns - 1)
+        .flat_map(|vertex| [[vertex, vertex + 1, vertex + columns], [vertex + 1, vertex + columns + 1, vertex + columns]])
+        .collect();
+
+    writer.begin_section(RwSections::RwGeometry as u32, VERSION);
+    writer.begin_section(RwSections::RwStruct as u32, VERSION);
+    let stream = writer.get_stream();
+    stream.write_u16(0x02 | 0x04 | 0x08 | 0x10 | 0x20);
+    stream.write_u8(1);
+    stream.write_u8(0);
+    stream.write_u32(triangles.len() as u32);
+    stream.write_u32(vertex_count);
+    stream.write_u32(1);
+    for vertex in 0..vertex_count {
+        stream.write(&[vertex as u8, (vertex >> 8) as u8, 128, 255]);
+    }
+    for vertex in 0..vertex_count {
+        stream.write_f32((vertex % columns) as f32 / columns as f32);
+        stream.write_f32((vertex / columns) as f32 / columns as f32);
+    }
+    for (index, [vertex1, vertex2, vertex3]) in triangles.iter().enumerate() {
+        stream.write_u16(*vertex2 as u16);
+        stream.write_u16(*vertex1 as u16);
+        stream.write_u16((index % 2) as u16);
+        stream.write_u16(*vertex3 as u16);
+    }
+    for value in [0.5, 0.5, 0.0, 100.0] {
+        stream.write_f32(value);
+    }
+    stream.write_u32(1);
+    stream.write_u32(1);
+    for vertex in 0..vertex_count {
+        stream.write_f32((vertex % columns) as f32);
+        stream.write_f32((vertex / columns) as f32);
+        stream.write_f32(((vertex % 7) as f32).sin());
+    }
+    for _ in 0..vertex_count {
+        for value in [0.0, 0.0, 1.0] {
+            stream.write_f32(value);
+        }
+    }
+    writer.end_section();
+
+    writer.begin_section(RwSections::RwMaterialList as u32, VERSION);
+    writer.begin_section(RwSections::RwStruct as u32, VERSION);
+    writer.get_stream().write_u32(2);
+    writer.get_stream().write_i32(-1);
+    writer.get_stream().write_i32(-1);
+    writer.end_section();
+    for name in ["body", "glass"] {
+        write_material(writer, name);
+    }
+    writer.end_section();
+
+    writer.begin_section(RwSections::RwExtension as u32, VERSION);
+    writer.begin_section(RwSections::RwBinMeshPLG as u32, VERSION);
+    let stream = writer.get_stream();
+    stream.write_u32(0);
+    stream.write_u32(2);
+    stream.write_u32(triangles.len() as u32 * 3);
+    for material in 0..2 {
+        let mesh: Vec<&[u32; 3]> = triangles.iter().skip(material).step_by(2).collect();
+        stream.write_u32(mesh.len() as u32 * 3);
+        stream.write_u32(material as u32);
+        for vertex in mesh.into_iter().flatten() {
+            stream.write_u32(*vertex);
+        }
+    }
+    writer.end_section();
+    writer.end_section();
+    writer.end_section();
+}
+
+fn write_material(writer: &mut RwFileWriter, texture_name: &str) {
+    writer.begin_section(RwSections::RwMaterial as u32, VERSION);
+    writer.begin_section(RwSections::RwStruct as u32, VERSION);
+    let stream = writer.get_stream();
+    stream.write_u32(0);
+    stream.write(&[255, 255, 255, 255]);
+    stream.write_u32(0);
+    stream.write_u32(1);
+    for value in [1.0, 1.0, 1.0] {
+        stream.write_f32(value);
+    }
+    writer.end_section();
+
+    writer.begin_section(RwSections::RwTexture as u32, VERSION);
+    writer.write_section(RwSections::RwStruct as u32, VERSION, &0x1106u32.to_le_bytes());
+    let mut name = texture_name.as_bytes().to_vec();
+    name.resize((name.len() + 4) & !3, 0);
+    writer.write_section(RwSections::RwString as u32, VERSION, &name);
+    writer.write_section(RwSections::RwString as u32, VERSION, &[0; 4]);
+    writer.write_section(RwSections::RwExtension as u32, VERSION, &[]);
+    writer.end_section();
+
+    writer.write_section(RwSections::RwExtension as u32, VERSION, &[]);
+    writer.end_section();
+}
+
+/// Returns a dictionary of DXT1 textures with gradient pixels.
+pub fn txd(size: SizeClass) -> Vec<u8> {
+    let (texture_count, side) = size.txd_size();
+    let pixels: Vec<u8> = (0..side as usize * side as usize)
+        .flat_map(|pixel| {
+            let (x, y) = (pixel % side as usize, pixel / side as usize);
+            [(x * 255 / side as usize) as u8, (y * 255 / side as usize) as u8, 128, 255]
+        })
+        .collect();
+
+    let texture_natives = (0..texture_count)
+        .map(|index| RwTextureNative {
+            filter_mode: 6,
+            u_addressing: 1,
+            v_addressing: 1,
+            texture_name: format!("texture{}", index),
+            width: side,
+            height: side,
+            mipmaps: vec![pixels.clone()],
+            ..Default::default()
+        })
+        .collect();
+    let txd = RwTxd {
+        texture_dictionary: RwTextureDictionary::new(texture_natives),
+        parent_name: None,
+    };
+
+    TxdWriter::new()
+        .with_format(TxdTextureFormat::Dxt1)
+        .write(&txd)
+        .expect("Failed to write the TXD fixture")
+}
+
+/// Returns an `ANPK` package of animations whose bones all have rotation
+/// and translation keyframes.
+pub fn ifp(size: SizeClass) -> Vec<u8> {
+    let (animation_count, bone_count, keyframe_count) = size.ifp_size();
+    let mut stream = ByteWriter::new();
+
+    let package = begin_block(&mut stream, b"ANPK");
+    let info = begin_block(&mut stream, b"INFO");
+    stream.write_u32(animation_count);
+    stream.write_string("benchmark", 12);
+    end_block(&mut stream, info);
+
+    for animation in 0..animation_count {
+        let name = format!("animation{}", animation);
+        stream.write(b"NAME");
+        stream.write_u32(name.len() as u32 + 1);
+        stream.write_string(&name, (name.len() + 4) & !3);
+
+        let dgan = begin_block(&mut stream, b"DGAN");
+        let info = begin_block(&mut stream, b"INFO");
+        stream.write_u32(bone_count);
+        stream.write_u32(0);
+        end_block(&mut stream, info);
+
+        for bone in 0..bone_count {
+            let cpan = begin_block(&mut stream, b"CPAN");
+            let anim = begin_block(&mut stream, b"ANIM");
+            stream.write_string(&format!("bone{}", bone), 28);
+            stream.write_u32(keyframe_count);
+            stream.write(&[0; 8]);
+            stream.write_i32(bone as i32);
+            end_block(&mut stream, anim);
+
+            let keyframes = begin_block(&mut stream, b"KRT0");
+            for keyframe in 0..keyframe_count {
+                let angle = keyframe as f32 * 0.1;
+                for value in [0.0, (angle / 2.0).sin(), 0.0, (angle / 2.0).cos()] {
+                    stream.write_f32(value);
+                }
+                for value in [angle, 0.0, bone as f32 * 0.1] {
+                    stream.write_f32(value);
+                }
+                stream.write_f32(keyframe as f32 / 30.0);
+            }
+            end_block(&mut stream, keyframes);
+            end_block(&mut stream, cpan);
+        }
+        end_block(&mut stream, dgan);
+    }
+
+    end_block(&mut stream, package);
+    stream.into_inner()
+}
+
+/// Writes the tag of an IFP block and a placeholder size, and returns the
+/// position of the size.
+fn begin_block(stream: &mut ByteWriter, tag: &[u8; 4]) -> u64 {
+    stream.write(tag);
+    let position = stream.get_position();
+    stream.write_u32(0);
+    position
+}
+
+fn end_block(stream: &mut ByteWriter, position: u64) {
+    let size = stream.get_position() - position - 4;
+    stream.patch_u32(position, size as u32);
+}
@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+
+mod fixtures;
+
+use fixtures::SizeClass;
+
+fn parse_dff_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DFF Parser");
+    for size in SizeClass::ALL {
+        let buffer = fixtures::dff(size);
+        group.throughput(Throughput::Bytes(buffer.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size.name()), &buffer, |b, buffer| {
+            b.iter(|| DffParser::new(black_box(buffer)).parse().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn parse_txd_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("TXD Parser");
+    for size in SizeClass::ALL {
+        let buffer = fixtures::txd(size);
+        group.throughput(Throughput::Bytes(buffer.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size.name()), &buffer, |b, buffer| {
+            b.iter(|| TxdParser::new(black_box(buffer)).parse().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn parse_ifp_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("IFP Parser");
+    for size in SizeClass::ALL {
+        let buffer = fixtures::ifp(size);
+        group.throughput(Throughput::Bytes(buffer.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size.name()), &buffer, |b, buffer| {
+            b.iter(|| IfpParser::new(black_box(buffer)).parse().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_dff_benchmark, parse_txd_benchmark, parse_ifp_benchmark);
+criterion_main!(benches);
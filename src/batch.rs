@@ -0,0 +1,326 @@
+//! # Batch Scanning
+//!
+//! Parses every DFF, TXD and IFP file under a set of directories and IMG
+//! archives, for auditing a whole game install without hand-rolling the
+//! orchestration.
+//!
+//! ## Features
+//!
+//! - Walks directories, optionally recursively, and the entries of IMG
+//!   archives found along the way.
+//! - Parses files on the rayon thread pool with the `parallel` feature, and
+//!   one after the other otherwise.
+//! - Streams every result and error through a channel as soon as it is
+//!   ready, together with the parse report of the file.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::batch::{scan, ScanOptions};
+//!
+//! for scanned in scan(["path/to/gta-sa/models"], ScanOptions::new()) {
+//!     if let Err(error) = &scanned.result {
+//!         println!("{}: {}", scanned.source, error);
+//!     }
+//! }
+//! ```
+
+use crate::renderware::dff::dff_parser::{DffParser, RwDff};
+use crate::renderware::ifp::ifp_parser::{IfpParser, RwIfp};
+use crate::renderware::img::img_archive::ImgArchive;
+use crate::renderware::rw_error::RwResult;
+use crate::renderware::rw_report::{ParseMode, ParseReport};
+use crate::renderware::txd::txd_parser::{RwTxd, TxdParser};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use serde::Serialize;
+
+/// The number of archive entries read before they are parsed together.
+const ARCHIVE_BATCH_SIZE: usize = 64;
+
+/// The kinds of files a scan parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ScanKind {
+    Dff,
+    Txd,
+    Ifp,
+}
+
+impl ScanKind {
+    /// Returns the kind of a file from its extension, in any case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "dff" => Some(ScanKind::Dff),
+            "txd" => Some(ScanKind::Txd),
+            "ifp" => Some(ScanKind::Ifp),
+            _ => None,
+        }
+    }
+}
+
+/// Options of a `scan`.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    recursive: bool,
+    archives: bool,
+    mode: ParseMode,
+    kinds: Vec<ScanKind>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScanOptions {
+    /// Creates options that walk directories recursively, look inside IMG
+    /// archives and parse DFF, TXD and IFP files in `ParseMode::Standard`.
+    pub fn new() -> Self {
+        ScanOptions {
+            recursive: true,
+            archives: true,
+            mode: ParseMode::Standard,
+            kinds: vec![ScanKind::Dff, ScanKind::Txd, ScanKind::Ifp],
+        }
+    }
+
+    /// Sets whether subdirectories are walked.
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Sets whether the entries of `.img` archives are parsed.
+    pub fn with_archives(mut self, archives: bool) -> Self {
+        self.archives = archives;
+        self
+    }
+
+    /// Sets the parse mode used for every file.
+    pub fn with_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Restricts the scan to the given kinds of files.
+    pub fn with_kinds(mut self, kinds: &[ScanKind]) -> Self {
+        self.kinds = kinds.to_vec();
+        self
+    }
+
+    fn kind_of(&self, extension: Option<&str>) -> Option<ScanKind> {
+        extension.and_then(ScanKind::from_extension).filter(|kind| self.kinds.contains(kind))
+    }
+}
+
+/// Where a scanned file comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanSource {
+    /// The file on disk, or the archive holding the file.
+    pub path: PathBuf,
+    /// The name of the file inside the archive, if it comes from one.
+    pub entry: Option<String>,
+}
+
+impl fmt::Display for ScanSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.entry {
+            Some(entry) => write!(f, "{}/{}", self.path.display(), entry),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+/// A parsed file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScannedAsset {
+    Dff(RwDff),
+    Txd(RwTxd),
+    Ifp(RwIfp),
+}
+
+/// The outcome of parsing a single file, or an error met while walking the
+/// directories and archives.
+#[derive(Debug)]
+pub struct ScanResult {
+    pub source: ScanSource,
+    pub result: RwResult<(ScannedAsset, ParseReport)>,
+}
+
+/// The data of a file waiting to be parsed.
+enum ScanData {
+    Path,
+    Bytes(Vec<u8>),
+}
+
+struct ScanJob {
+    source: ScanSource,
+    kind: ScanKind,
+    data: ScanData,
+}
+
+/// The receiver of a scan was dropped, so the scan stops.
+struct Stopped;
+
+/// Scans `paths`, which may be directories, IMG archives or single files,
+/// on a background thread.
+///
+/// Results are sent through the returned channel in the order they are
+/// ready, which is not the order of the files when parsing in parallel. The
+/// channel is closed once every file was parsed; dropping the receiver stops
+/// the scan early.
+pub fn scan<I, P>(paths: I, options: ScanOptions) -> Receiver<ScanResult>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let roots: Vec<PathBuf> = paths.into_iter().map(|path| path.as_ref().to_path_buf()).collect();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = run_scan(roots, &options, &sender);
+    });
+    receiver
+}
+
+fn run_scan(roots: Vec<PathBuf>, options: &ScanOptions, sender: &Sender<ScanResult>) -> Result<(), Stopped> {
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    for root in roots {
+        if root.is_dir() {
+            walk_directory(&root, options, &mut files, &mut visited, sender)?;
+        } else {
+            files.push(root);
+        }
+    }
+
+    let mut jobs = Vec::new();
+    for path in files {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        if options.archives && extension.is_some_and(|extension| extension.eq_ignore_ascii_case("img")) {
+            scan_archive(&path, options, sender)?;
+        } else if let Some(kind) = options.kind_of(extension) {
+            jobs.push(ScanJob {
+                source: ScanSource { path, entry: None },
+                kind,
+                data: ScanData::Path,
+            });
+        }
+    }
+    parse_jobs(jobs, options.mode, sender)
+}
+
+/// Collects the files of a directory. Directories are identified by their
+/// canonical path, so symbolic links looping back up the tree or pointing
+/// at a directory already walked are skipped.
+fn walk_directory(
+    directory: &Path,
+    options: &ScanOptions,
+    files: &mut Vec<PathBuf>,
+    visited: &mut HashSet<PathBuf>,
+    sender: &Sender<ScanResult>,
+) -> Result<(), Stopped> {
+    let canonical = match fs::canonicalize(directory) {
+        Ok(canonical) => canonical,
+        Err(error) => return send_error(sender, directory, None, error),
+    };
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) => return send_error(sender, directory, None, error),
+    };
+
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            if options.recursive {
+                walk_directory(&path, options, files, visited, sender)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the entries of an archive in batches and parses each batch.
+fn scan_archive(path: &Path, options: &ScanOptions, sender: &Sender<ScanResult>) -> Result<(), Stopped> {
+    let mut archive = match ImgArchive::open_path(path) {
+        Ok(archive) => archive,
+        Err(error) => return send_error(sender, path, None, error),
+    };
+
+    let entries: Vec<_> = archive
+        .entries()
+        .iter()
+        .filter_map(|entry| options.kind_of(entry.extension().as_deref()).map(|kind| (entry.clone(), kind)))
+        .collect();
+
+    for batch in entries.chunks(ARCHIVE_BATCH_SIZE) {
+        let mut jobs = Vec::with_capacity(batch.len());
+        for (entry, kind) in batch {
+            match archive.read_entry(entry) {
+                Ok(data) => jobs.push(ScanJob {
+                    source: ScanSource {
+                        path: path.to_path_buf(),
+                        entry: Some(entry.name.clone()),
+                    },
+                    kind: *kind,
+                    data: ScanData::Bytes(data),
+                }),
+                Err(error) => send_error(sender, path, Some(entry.name.clone()), error)?,
+            }
+        }
+        parse_jobs(jobs, options.mode, sender)?;
+    }
+    Ok(())
+}
+
+fn send_error(sender: &Sender<ScanResult>, path: &Path, entry: Option<String>, error: std::io::Error) -> Result<(), Stopped> {
+    let result = ScanResult {
+        source: ScanSource {
+            path: path.to_path_buf(),
+            entry,
+        },
+        result: Err(error.into()),
+    };
+    sender.send(result).map_err(|_| Stopped)
+}
+
+fn parse_job(job: ScanJob, mode: ParseMode) -> ScanResult {
+    let result = match job.data {
+        ScanData::Path => fs::read(&job.source.path).map_err(Into::into),
+        ScanData::Bytes(data) => Ok(data),
+    }
+    .and_then(|data| match job.kind {
+        ScanKind::Dff => DffParser::new(&data).with_mode(mode).parse_with_report().map(|(dff, report)| (ScannedAsset::Dff(dff), report)),
+        ScanKind::Txd => TxdParser::new(&data).with_mode(mode).parse_with_report().map(|(txd, report)| (ScannedAsset::Txd(txd), report)),
+        ScanKind::Ifp => IfpParser::new(&data).with_mode(mode).parse_with_report().map(|(ifp, report)| (ScannedAsset::Ifp(ifp), report)),
+    });
+    ScanResult { source: job.source, result }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn parse_jobs(jobs: Vec<ScanJob>, mode: ParseMode, sender: &Sender<ScanResult>) -> Result<(), Stopped> {
+    for job in jobs {
+        sender.send(parse_job(job, mode)).map_err(|_| Stopped)?;
+    }
+    Ok(())
+}
+
+/// Parses the jobs on the rayon thread pool. Jobs not yet started are
+/// dropped once a send fails.
+#[cfg(feature = "parallel")]
+fn parse_jobs(jobs: Vec<ScanJob>, mode: ParseMode, sender: &Sender<ScanResult>) -> Result<(), Stopped> {
+    use rayon::prelude::*;
+    jobs.into_par_iter()
+        .try_for_each_with(sender.clone(), |sender, job| sender.send(parse_job(job, mode)).map_err(|_| Stopped))
+}
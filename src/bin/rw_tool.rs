@@ -0,0 +1,316 @@
+//! # rw-tool
+//!
+//! A command line front end for the parsers, built with the `cli` feature.
+//!
+//! ```text
+//! rw-tool dump <file> [-o <output.json>]
+//! rw-tool info <file>...
+//! rw-tool validate <file>...
+//! rw-tool extract-textures <file.txd> [-o <directory>] [--dds]
+//! rw-tool convert <input> <output> [--format <format>] [--platform d3d8|d3d9] [--mipmaps]
+//! ```
+//!
+//! The kind of a file is taken from its extension, or detected from its
+//! header when the extension is unknown.
+
+use rw_parser_rs::renderware::dff::dff_parser::{DffParser, RwDff};
+use rw_parser_rs::renderware::ifp::ifp_parser::{IfpParser, RwIfp};
+use rw_parser_rs::renderware::rw_report::{ParseMode, ParseReport};
+use rw_parser_rs::renderware::txd::txd_parser::{RwTxd, TxdParser};
+use rw_parser_rs::renderware::txd::txd_writer::{TxdTextureFormat, TxdWriter};
+use rw_parser_rs::renderware::utils::image_format_enums::PlatformType;
+use rw_parser_rs::utils::file_name::UniqueFileNames;
+use rw_parser_rs::{RwFileKind, detect};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage:
+  rw-tool dump <file> [-o <output.json>]
+  rw-tool info <file>...
+  rw-tool validate <file>...
+  rw-tool extract-textures <file.txd> [-o <directory>] [--dds]
+  rw-tool convert <input> <output> [--format raw|palette8|dxt1|dxt3|dxt5|preserve] [--platform d3d8|d3d9] [--mipmaps]
+
+Files are DFF, TXD or IFP files. `convert` writes JSON when the output ends
+in .json, and otherwise writes a TXD from a TXD or a JSON dump of one.";
+
+type CliResult<T> = Result<T, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    Dff,
+    Txd,
+    Ifp,
+}
+
+impl FileKind {
+    fn detect(path: &Path, data: &[u8]) -> CliResult<Self> {
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        match extension.to_ascii_lowercase().as_str() {
+            "dff" => return Ok(FileKind::Dff),
+            "txd" => return Ok(FileKind::Txd),
+            "ifp" => return Ok(FileKind::Ifp),
+            _ => {}
+        }
+
+        match detect(data) {
+            RwFileKind::Dff => Ok(FileKind::Dff),
+            RwFileKind::Txd => Ok(FileKind::Txd),
+            RwFileKind::Ifp(_) => Ok(FileKind::Ifp),
+            _ => Err(format!("{}: not a DFF, TXD or IFP file", path.display())),
+        }
+    }
+}
+
+/// A parsed file of any supported kind.
+enum Parsed {
+    Dff(RwDff),
+    Txd(RwTxd),
+    Ifp(RwIfp),
+}
+
+impl Parsed {
+    fn to_json(&self) -> CliResult<String> {
+        match self {
+            Parsed::Dff(dff) => serde_json::to_string_pretty(dff),
+            Parsed::Txd(txd) => serde_json::to_string_pretty(txd),
+            Parsed::Ifp(ifp) => serde_json::to_string_pretty(ifp),
+        }
+        .map_err(|error| error.to_string())
+    }
+}
+
+fn read(path: &Path) -> CliResult<Vec<u8>> {
+    fs::read(path).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+fn write(path: &Path, data: &[u8]) -> CliResult<()> {
+    fs::write(path, data).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+fn parse(path: &Path, mode: ParseMode) -> CliResult<(Parsed, ParseReport)> {
+    let data = read(path)?;
+    let result = match FileKind::detect(path, &data)? {
+        FileKind::Dff => DffParser::new(&data).with_mode(mode).parse_with_report().map(|(dff, report)| (Parsed::Dff(dff), report)),
+        FileKind::Txd => TxdParser::new(&data).with_mode(mode).parse_with_report().map(|(txd, report)| (Parsed::Txd(txd), report)),
+        FileKind::Ifp => IfpParser::new(&data).with_mode(mode).parse_with_report().map(|(ifp, report)| (Parsed::Ifp(ifp), report)),
+    };
+    result.map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+/// Splits the arguments of a subcommand into positional values and the
+/// values of the given options. Options listed in `flags` take no value.
+struct Arguments {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Arguments {
+    fn parse(args: &[String], with_value: &[&str], flags: &[&str]) -> CliResult<Self> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if with_value.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                options.push((arg.clone(), Some(value.clone())));
+            } else if flags.contains(&arg.as_str()) {
+                options.push((arg.clone(), None));
+            } else if arg.starts_with('-') {
+                return Err(format!("Unknown option {}", arg));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Arguments { positional, options })
+    }
+
+    fn value(&self, names: &[&str]) -> Option<&str> {
+        self.options.iter().rev().find(|(name, _)| names.contains(&name.as_str())).and_then(|(_, value)| value.as_deref())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    fn files(&self, count: usize) -> CliResult<Vec<PathBuf>> {
+        if self.positional.len() < count {
+            return Err(format!("Expected {} file argument(s)\n\n{}", count, USAGE));
+        }
+        Ok(self.positional.iter().map(PathBuf::from).collect())
+    }
+}
+
+fn dump(args: &[String]) -> CliResult<ExitCode> {
+    let args = Arguments::parse(args, &["-o", "--output"], &[])?;
+    let file = &args.files(1)?[0];
+    let json = parse(file, ParseMode::Standard)?.0.to_json()?;
+    match args.value(&["-o", "--output"]) {
+        Some(output) => write(Path::new(output), json.as_bytes())?,
+        None => println!("{}", json),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn info(args: &[String]) -> CliResult<ExitCode> {
+    let args = Arguments::parse(args, &[], &[])?;
+    for file in args.files(1)? {
+        let data = read(&file)?;
+        let summary = match FileKind::detect(&file, &data)? {
+            FileKind::Dff => {
+                let dff = DffParser::new(&data).parse().map_err(|error| format!("{}: {}", file.display(), error))?;
+                let geometries = dff.geometry_list.as_ref().map_or(&[][..], |list| &list.geometries[..]);
+                format!(
+                    "DFF {} ({:?}): {} frames, {} geometries, {} vertices, {} triangles, {} atomics",
+                    dff.version,
+                    dff.model_type,
+                    dff.frame_list.as_ref().map_or(0, |list| list.frames.len()),
+                    geometries.len(),
+                    geometries.iter().map(|geometry| geometry.vertex_information.len()).sum::<usize>(),
+                    geometries.iter().map(|geometry| geometry.triangle_information.len()).sum::<usize>(),
+                    dff.atomics.len(),
+                )
+            }
+            FileKind::Txd => {
+                let txd = TxdParser::new(&data).parse_metadata().map_err(|error| format!("{}: {}", file.display(), error))?;
+                let mut summary = format!("TXD: {} textures", txd.texture_dictionary.texture_natives.len());
+                for texture in txd.texture_dictionary.iter() {
+                    summary += &format!(
+                        "\n  {} {}x{} {:?}, {} mipmaps",
+                        texture.texture_name, texture.width, texture.height, texture.raster_encoding, texture.mipmap_count
+                    );
+                }
+                summary
+            }
+            FileKind::Ifp => {
+                let ifp = IfpParser::new(&data).parse().map_err(|error| format!("{}: {}", file.display(), error))?;
+                let mut summary = format!("IFP {:?} {:?}: {} animations", ifp.version, ifp.name, ifp.animations.len());
+                for animation in &ifp.animations {
+                    summary += &format!("\n  {} ({} bones)", animation.name, animation.bones.len());
+                }
+                summary
+            }
+        };
+        println!("{}: {}", file.display(), summary);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(args: &[String]) -> CliResult<ExitCode> {
+    let args = Arguments::parse(args, &[], &[])?;
+    let mut failed = false;
+    for file in args.files(1)? {
+        match parse(&file, ParseMode::Strict) {
+            Ok((_, report)) => {
+                println!("{}: ok", file.display());
+                for warning in &report.warnings {
+                    println!("  warning at {:#x}: {}", warning.offset, warning.message);
+                }
+            }
+            Err(message) => {
+                eprintln!("{}", message);
+                failed = true;
+            }
+        }
+    }
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+fn extract_textures(args: &[String]) -> CliResult<ExitCode> {
+    let args = Arguments::parse(args, &["-o", "--output"], &["--dds"])?;
+    let file = &args.files(1)?[0];
+    let directory = PathBuf::from(args.value(&["-o", "--output"]).unwrap_or("."));
+    fs::create_dir_all(&directory).map_err(|error| format!("{}: {}", directory.display(), error))?;
+
+    let data = read(file)?;
+    let txd = TxdParser::new(&data).parse_metadata().map_err(|error| format!("{}: {}", file.display(), error))?;
+    let dds = args.flag("--dds");
+    // Texture names come from the file, so they are sanitized before being
+    // joined to the output directory, and duplicates get a suffix
+    let mut names = UniqueFileNames::new();
+    for texture in txd.texture_dictionary.iter() {
+        let (encoded, extension) = if dds { (texture.to_dds(), "dds") } else { (texture.to_png(), "png") };
+        match encoded {
+            Ok(encoded) => {
+                let output = directory.join(names.claim(&texture.texture_name, extension));
+                write(&output, &encoded)?;
+                println!("{}", output.display());
+            }
+            Err(error) => eprintln!("{}: skipped {}: {}", file.display(), texture.texture_name, error),
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn convert(args: &[String]) -> CliResult<ExitCode> {
+    let args = Arguments::parse(args, &["--format", "--platform"], &["--mipmaps"])?;
+    let files = args.files(2)?;
+    let (input, output) = (&files[0], &files[1]);
+
+    if output.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json")) {
+        let json = parse(input, ParseMode::Standard)?.0.to_json()?;
+        write(output, json.as_bytes())?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let data = read(input)?;
+    let txd: RwTxd = if input.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json")) {
+        serde_json::from_slice(&data).map_err(|error| format!("{}: {}", input.display(), error))?
+    } else {
+        match FileKind::detect(input, &data)? {
+            FileKind::Txd => TxdParser::new(&data).parse().map_err(|error| format!("{}: {}", input.display(), error))?,
+            kind => return Err(format!("{}: cannot convert a {:?} file to TXD", input.display(), kind)),
+        }
+    };
+
+    let format = match args.value(&["--format"]).unwrap_or("preserve") {
+        "raw" => TxdTextureFormat::Raw8888,
+        "palette8" => TxdTextureFormat::Palette8,
+        "dxt1" => TxdTextureFormat::Dxt1,
+        "dxt3" => TxdTextureFormat::Dxt3,
+        "dxt5" => TxdTextureFormat::Dxt5,
+        "preserve" => TxdTextureFormat::Preserve,
+        other => return Err(format!("Unknown texture format {}", other)),
+    };
+    let platform = match args.value(&["--platform"]).unwrap_or("d3d9") {
+        "d3d8" => PlatformType::D3d8,
+        "d3d9" => PlatformType::D3d9,
+        other => return Err(format!("Unknown platform {}", other)),
+    };
+
+    let encoded = TxdWriter::new()
+        .with_platform(platform)
+        .with_format(format)
+        .with_mipmaps(args.flag("--mipmaps"))
+        .write(&txd)
+        .map_err(|error| format!("{}: {}", output.display(), error))?;
+    write(output, &encoded)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let result = match command.as_str() {
+        "dump" => dump(rest),
+        "info" => info(rest),
+        "validate" => validate(rest),
+        "extract-textures" => extract_textures(rest),
+        "convert" => convert(rest),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+        other => Err(format!("Unknown command {}\n\n{}", other, USAGE)),
+    };
+
+    result.unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        ExitCode::FAILURE
+    })
+}
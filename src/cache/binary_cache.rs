@@ -0,0 +1,167 @@
+//! # Binary Cache
+//!
+//! A compact binary encoding of parsed results, so batch pipelines can parse
+//! an archive once and reload its models and textures much faster on later
+//! runs. Requires the `cache` feature.
+//!
+//! ## Features
+//!
+//! - Encodes `RwDff`, `RwTxd` and `RwIfp` with bincode.
+//! - Prefixes every blob with a header holding the cache format version,
+//!   the kind of result and the crate version that wrote it.
+//! - Rejects blobs from another format or crate version, whose layout may
+//!   differ, so stale caches are rebuilt instead of misread.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::cache::binary_cache::{decode_cache, encode_cache};
+//! use rw_parser_rs::renderware::dff::dff_parser::{DffParser, RwDff};
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/model.dff").unwrap();
+//! let dff = DffParser::new(&file_data).parse().unwrap();
+//! fs::write("model.dff.cache", encode_cache(&dff).unwrap()).unwrap();
+//!
+//! let cached = fs::read("model.dff.cache").unwrap();
+//! let dff: RwDff = decode_cache(&cached).unwrap();
+//! ```
+
+use crate::renderware::dff::dff_parser::RwDff;
+use crate::renderware::ifp::ifp_parser::RwIfp;
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::txd::txd_parser::RwTxd;
+use std::io::{Error, ErrorKind};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// The signature at the start of every cache blob.
+pub const CACHE_MAGIC: [u8; 4] = *b"RWPC";
+
+/// The version of the header layout and encoding. It is bumped whenever
+/// blobs written by older versions cannot be read anymore.
+pub const CACHE_FORMAT_VERSION: u16 = 1;
+
+/// The kind of parsed result stored in a cache blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CacheKind {
+    Dff = 1,
+    Txd = 2,
+    Ifp = 3,
+}
+
+impl CacheKind {
+    fn from_u16(value: u16) -> Option<Self> {
+        match value {
+            1 => Some(CacheKind::Dff),
+            2 => Some(CacheKind::Txd),
+            3 => Some(CacheKind::Ifp),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed result that can be stored in the binary cache.
+pub trait Cacheable: Serialize + DeserializeOwned {
+    const KIND: CacheKind;
+}
+
+impl Cacheable for RwDff {
+    const KIND: CacheKind = CacheKind::Dff;
+}
+
+impl Cacheable for RwTxd {
+    const KIND: CacheKind = CacheKind::Txd;
+}
+
+impl Cacheable for RwIfp {
+    const KIND: CacheKind = CacheKind::Ifp;
+}
+
+/// The header of a cache blob.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheHeader {
+    pub format_version: u16,
+    pub kind: CacheKind,
+    /// The version of this crate that wrote the blob.
+    pub crate_version: String,
+    /// The size of the header, after which the encoded result starts.
+    pub size: usize,
+}
+
+impl CacheHeader {
+    /// Returns whether a blob with this header can be decoded by this build.
+    pub fn is_current(&self) -> bool {
+        self.format_version == CACHE_FORMAT_VERSION && self.crate_version == env!("CARGO_PKG_VERSION")
+    }
+}
+
+/// Encodes a parsed result into a cache blob.
+pub fn encode_cache<T: Cacheable>(value: &T) -> RwResult<Vec<u8>> {
+    let crate_version = env!("CARGO_PKG_VERSION").as_bytes();
+
+    let mut data = Vec::with_capacity(9 + crate_version.len());
+    data.extend_from_slice(&CACHE_MAGIC);
+    data.extend_from_slice(&CACHE_FORMAT_VERSION.to_le_bytes());
+    data.extend_from_slice(&(T::KIND as u16).to_le_bytes());
+    data.push(crate_version.len() as u8);
+    data.extend_from_slice(crate_version);
+
+    let payload = bincode::serialize(value).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+    data.extend_from_slice(&payload);
+    Ok(data)
+}
+
+/// Reads the header of a cache blob without decoding the result.
+pub fn read_cache_header(data: &[u8]) -> RwResult<CacheHeader> {
+    if data.len() < 9 || data[0..4] != CACHE_MAGIC {
+        return Err(Error::new(ErrorKind::InvalidData, "Not a rw-parser-rs cache blob").into());
+    }
+
+    let format_version = u16::from_le_bytes([data[4], data[5]]);
+    let kind_value = u16::from_le_bytes([data[6], data[7]]);
+    let kind = CacheKind::from_u16(kind_value).ok_or_else(|| RwParseError::InconsistentData {
+        offset: 6,
+        message: format!("Unknown cache kind {}", kind_value),
+    })?;
+
+    let size = 9 + data[8] as usize;
+    let crate_version = data.get(9..size).ok_or_else(|| RwParseError::InconsistentData {
+        offset: 9,
+        message: format!("Crate version of {} bytes extends past the end of the cache blob", data[8]),
+    })?;
+
+    Ok(CacheHeader {
+        format_version,
+        kind,
+        crate_version: String::from_utf8_lossy(crate_version).to_string(),
+        size,
+    })
+}
+
+/// Decodes a cache blob written by `encode_cache`.
+///
+/// # Returns
+///
+/// A `Result` containing the decoded value, or a `RwParseError` if the
+/// blob holds another kind of result or was written by another format or
+/// crate version.
+pub fn decode_cache<T: Cacheable>(data: &[u8]) -> RwResult<T> {
+    let header = read_cache_header(data)?;
+    if !header.is_current() {
+        return Err(RwParseError::UnsupportedVersion {
+            format: "cache",
+            version: format!("{} ({})", header.format_version, header.crate_version),
+        });
+    }
+    if header.kind != T::KIND {
+        return Err(RwParseError::InconsistentData {
+            offset: 6,
+            message: format!("Cache holds a {:?}, not a {:?}", header.kind, T::KIND),
+        });
+    }
+
+    let value = bincode::deserialize(&data[header.size..]).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+    Ok(value)
+}
@@ -0,0 +1,2 @@
+pub mod binary_cache;
+pub mod parse_cache;
\ No newline at end of file
@@ -0,0 +1,159 @@
+//! # Parse Cache
+//!
+//! An on-disk cache of parsed results keyed by a hash of the input file, so
+//! tools running again over an unchanged game install load every model,
+//! texture dictionary and animation from the cache instead of parsing it.
+//! Requires the `cache` feature.
+//!
+//! ## Features
+//!
+//! - Stores one `binary_cache` blob per input under a cache directory.
+//! - Names blobs after the kind of result, a 64-bit FNV-1a hash and the size
+//!   of the input, so changed files miss the cache.
+//! - Optionally ignores blobs older than a maximum age.
+//! - Treats stale or damaged blobs as misses and replaces them.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::cache::parse_cache::ParseCache;
+//! use std::fs;
+//! use std::time::Duration;
+//!
+//! let cache = ParseCache::new("target/rw-cache").with_max_age(Duration::from_secs(7 * 24 * 60 * 60));
+//! let file_data = fs::read("path/to/your/model.dff").unwrap();
+//! let dff = cache.parse_dff(&file_data).unwrap();
+//! println!("Geometries: {}", dff.geometry_list.map_or(0, |list| list.geometries.len()));
+//! ```
+
+use super::binary_cache::{CacheKind, Cacheable, decode_cache, encode_cache};
+use crate::renderware::dff::dff_parser::{DffParser, RwDff};
+use crate::renderware::ifp::ifp_parser::{IfpParser, RwIfp};
+use crate::renderware::rw_error::RwResult;
+use crate::renderware::txd::txd_parser::{RwTxd, TxdParser};
+use std::fs;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// A directory of cached parse results keyed by the content of their input.
+#[derive(Debug, Clone)]
+pub struct ParseCache {
+    directory: PathBuf,
+    max_age: Option<Duration>,
+}
+
+impl ParseCache {
+    /// Creates a cache storing its blobs in `directory`, which is created
+    /// on the first write.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        ParseCache {
+            directory: directory.into(),
+            max_age: None,
+        }
+    }
+
+    /// Ignores blobs written longer than `max_age` ago.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Returns the directory of the cache.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the 64-bit FNV-1a hash of `data`.
+    pub fn content_hash(data: &[u8]) -> u64 {
+        data.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+    }
+
+    /// Returns the path of the blob caching the result of type `T` parsed
+    /// from `data`.
+    pub fn path_for<T: Cacheable>(&self, data: &[u8]) -> PathBuf {
+        let kind = match T::KIND {
+            CacheKind::Dff => "dff",
+            CacheKind::Txd => "txd",
+            CacheKind::Ifp => "ifp",
+        };
+        self.directory.join(format!("{:016x}-{:x}.{}.cache", Self::content_hash(data), data.len(), kind))
+    }
+
+    /// Returns the cached result parsed from `data`, or `None` if there is
+    /// none, it expired, or it was written by another crate version.
+    pub fn get<T: Cacheable>(&self, data: &[u8]) -> Option<T> {
+        let path = self.path_for::<T>(data);
+        if let Some(max_age) = self.max_age {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+            let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+            if age > max_age {
+                return None;
+            }
+        }
+        decode_cache(&fs::read(&path).ok()?).ok()
+    }
+
+    /// Stores the result parsed from `data`, replacing any older blob.
+    pub fn insert<T: Cacheable>(&self, data: &[u8], value: &T) -> RwResult<()> {
+        let blob = encode_cache(value)?;
+        fs::create_dir_all(&self.directory)?;
+
+        // Writing to a temporary file first keeps concurrent readers from
+        // seeing half-written blobs
+        let path = self.path_for::<T>(data);
+        let temporary = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&temporary, blob)?;
+        fs::rename(&temporary, &path)?;
+        Ok(())
+    }
+
+    /// Returns the cached result for `data`, or parses it with `parse` and
+    /// caches the result. Failing to write the cache does not fail the parse.
+    pub fn get_or_parse<T, F>(&self, data: &[u8], parse: F) -> RwResult<T>
+    where
+        T: Cacheable,
+        F: FnOnce(&[u8]) -> RwResult<T>,
+    {
+        if let Some(value) = self.get(data) {
+            return Ok(value);
+        }
+        let value = parse(data)?;
+        let _ = self.insert(data, &value);
+        Ok(value)
+    }
+
+    /// Parses a DFF file through the cache.
+    pub fn parse_dff(&self, data: &[u8]) -> RwResult<RwDff> {
+        self.get_or_parse(data, |data| DffParser::new(data).parse())
+    }
+
+    /// Parses a TXD file through the cache.
+    pub fn parse_txd(&self, data: &[u8]) -> RwResult<RwTxd> {
+        self.get_or_parse(data, |data| TxdParser::new(data).parse())
+    }
+
+    /// Parses an IFP file through the cache.
+    pub fn parse_ifp(&self, data: &[u8]) -> RwResult<RwIfp> {
+        self.get_or_parse(data, |data| IfpParser::new(data).parse())
+    }
+
+    /// Removes every blob of the cache.
+    pub fn clear(&self) -> Result<()> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "cache") {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
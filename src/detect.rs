@@ -0,0 +1,146 @@
+//! # File Detection
+//!
+//! Works out the format of a file from its content, for tools scanning
+//! archives and mod folders whose file names cannot be trusted.
+//!
+//! ## Features
+//!
+//! - Recognizes DFF and TXD RenderWare streams in either byte order by their
+//!   first section, its version and its struct child.
+//! - Recognizes IFP, COL and version 2 IMG files by their signature.
+//! - Parses a file of any detected format into an `RwAsset` with a single
+//!   call and error type.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::{RwAsset, RwFileKind, detect};
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/unknown.bin").unwrap();
+//! println!("{:?}", detect(&file_data));
+//! match RwAsset::parse(&file_data).unwrap() {
+//!     RwAsset::Dff(dff) => println!("Model with {} frames", dff.frames().len()),
+//!     RwAsset::Txd(txd) => println!("{} textures", txd.texture_dictionary.texture_natives.len()),
+//!     asset => println!("{:?}", asset.kind() == RwFileKind::Img),
+//! }
+//! ```
+
+use crate::renderware::col::col_parser::{ColParser, ColVersion, RwCol};
+use crate::renderware::dff::dff_parser::{DffParser, RwDff};
+use crate::renderware::ifp::ifp_parser::{IfpParser, IfpVersion, RwIfp};
+use crate::renderware::img::img_archive::{ImgArchive, ImgEntry};
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_sections::RwSections;
+use crate::renderware::txd::txd_parser::{RwTxd, TxdParser};
+use crate::utils::byte_stream::Endianness;
+use crate::utils::rw_version::unpack_version;
+use std::io::Cursor;
+
+use num_traits::FromPrimitive;
+use serde::Serialize;
+
+/// The format of a file, as found by `detect`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RwFileKind {
+    /// A RenderWare clump stream.
+    Dff,
+    /// A RenderWare texture dictionary stream.
+    Txd,
+    /// An animation package.
+    Ifp(IfpVersion),
+    /// A collision file, named after the version of its first model.
+    Col(ColVersion),
+    /// A version 2 IMG archive. Version 1 archives have no header and are
+    /// reported as `Unknown`.
+    Img,
+    Unknown,
+}
+
+/// Detects the format of a file from its first bytes.
+pub fn detect(buffer: &[u8]) -> RwFileKind {
+    let Some(signature) = buffer.get(0..4) else {
+        return RwFileKind::Unknown;
+    };
+    match signature {
+        b"ANP3" => return RwFileKind::Ifp(IfpVersion::ANP3),
+        b"ANPK" => return RwFileKind::Ifp(IfpVersion::ANPK),
+        b"VER2" => return RwFileKind::Img,
+        _ => {}
+    }
+    if let Some(version) = ColVersion::from_signature(signature) {
+        return RwFileKind::Col(version);
+    }
+
+    stream_kind(buffer, Endianness::Little)
+        .or_else(|| stream_kind(buffer, Endianness::Big))
+        .unwrap_or(RwFileKind::Unknown)
+}
+
+/// Returns the kind of a RenderWare stream read with the given byte order.
+fn stream_kind(buffer: &[u8], endianness: Endianness) -> Option<RwFileKind> {
+    let field = |index: usize| {
+        let bytes: [u8; 4] = buffer.get(index..index + 4)?.try_into().unwrap();
+        Some(endianness.u32_from_bytes(bytes))
+    };
+    let (section_type, size, version) = (field(0)?, field(4)?, field(8)?);
+    // A real stream starts with a container holding a struct, stamped with a
+    // RenderWare 3 version, and fits in the buffer
+    let version = unpack_version(version);
+    if !(0x30000..0x40000).contains(&version) || size as u64 + 12 > buffer.len() as u64 {
+        return None;
+    }
+    if field(12)? != RwSections::RwStruct as u32 {
+        return None;
+    }
+    match RwSections::from_u32(section_type) {
+        Some(RwSections::RwClump) => Some(RwFileKind::Dff),
+        Some(RwSections::RwTextureDictionary) => Some(RwFileKind::Txd),
+        _ => None,
+    }
+}
+
+/// A parsed file of any format `detect` recognizes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RwAsset {
+    Dff(RwDff),
+    Txd(RwTxd),
+    Ifp(RwIfp),
+    Col(RwCol),
+    /// The entries of an IMG archive, whose data stays in the buffer.
+    Img(Vec<ImgEntry>),
+}
+
+impl RwAsset {
+    /// Detects the format of a file and parses it with the matching parser.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed asset, or a `RwParseError` if the
+    /// format is unknown (`RwParseError::UnknownFormat`) or parsing fails.
+    pub fn parse(buffer: &[u8]) -> RwResult<RwAsset> {
+        let stream_endianness = || match stream_kind(buffer, Endianness::Little) {
+            Some(_) => Endianness::Little,
+            None => Endianness::Big,
+        };
+        match detect(buffer) {
+            RwFileKind::Dff => Ok(RwAsset::Dff(DffParser::new(buffer).with_endianness(stream_endianness()).parse()?)),
+            RwFileKind::Txd => Ok(RwAsset::Txd(TxdParser::new(buffer).with_endianness(stream_endianness()).parse()?)),
+            RwFileKind::Ifp(_) => Ok(RwAsset::Ifp(IfpParser::new(buffer).parse()?)),
+            RwFileKind::Col(_) => Ok(RwAsset::Col(ColParser::new(buffer).parse()?)),
+            RwFileKind::Img => Ok(RwAsset::Img(ImgArchive::open_v2(Cursor::new(buffer))?.entries().to_vec())),
+            RwFileKind::Unknown => Err(RwParseError::UnknownFormat),
+        }
+    }
+
+    /// Returns the format of the asset.
+    pub fn kind(&self) -> RwFileKind {
+        match self {
+            RwAsset::Dff(_) => RwFileKind::Dff,
+            RwAsset::Txd(_) => RwFileKind::Txd,
+            RwAsset::Ifp(ifp) => RwFileKind::Ifp(ifp.version),
+            RwAsset::Col(col) => RwFileKind::Col(col.models.first().map_or(ColVersion::COL1, |model| model.version)),
+            RwAsset::Img(_) => RwFileKind::Img,
+        }
+    }
+}
@@ -0,0 +1,463 @@
+//! # Collada Export
+//!
+//! Converts a parsed clump into a Collada 1.4.1 document (`.dae`), available
+//! with the `dae` feature, for 3ds Max pipelines and other tools built
+//! around Collada.
+//!
+//! ## Features
+//!
+//! - One node per frame with its matrix, keeping the frame hierarchy, and
+//!   skin bones marked as joints.
+//! - Every geometry instanced in the node of its atomic's frame, with
+//!   positions, normals, prelighting colors, the first UV channel and one
+//!   triangle list per material.
+//! - Lambert materials with their color, and their texture referenced as an
+//!   image file named after it.
+//! - Skin controllers with the bones, inverse bind matrices and weights of
+//!   `RwSkin`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::export::dae::DaeExporter;
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/bmyst.dff").unwrap()).parse().unwrap();
+//! fs::write("path/to/bmyst.dae", DaeExporter::new(&dff).to_dae()).unwrap();
+//! ```
+
+use crate::renderware::common::types::{RwMatrix3, RwMatrix4, RwVector3};
+use crate::renderware::dff::dff_parser::{RwDff, RwGeometry, RwSkin};
+use std::fmt::Write;
+
+/// The main exporter for Collada files.
+pub struct DaeExporter<'a> {
+    dff: &'a RwDff,
+    texture_extension: String,
+}
+
+impl<'a> DaeExporter<'a> {
+    /// Creates an exporter for a clump, referencing textures as `.png` files.
+    pub fn new(dff: &'a RwDff) -> Self {
+        DaeExporter { dff, texture_extension: "png".to_string() }
+    }
+
+    /// Sets the extension of the texture files referenced by the materials.
+    pub fn with_texture_extension(mut self, texture_extension: &str) -> Self {
+        self.texture_extension = texture_extension.trim_start_matches('.').to_string();
+        self
+    }
+
+    /// Builds the `.dae` file.
+    pub fn to_dae(&self) -> String {
+        let geometries = self.dff.geometry_list.as_ref().map_or(&[][..], |list| &list.geometries[..]);
+        let frames = self.dff.frames();
+
+        let mut joints = vec![false; frames.len()];
+        for geometry in geometries {
+            if let Some(skin) = &geometry.skin {
+                for frame in self.dff.skin_bone_frames(skin.bone_count as usize) {
+                    joints[frame] = true;
+                }
+            }
+        }
+
+        let mut output = String::new();
+        output.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        output.push_str("<COLLADA xmlns=\"http://www.collada.org/2005/11/COLLADASchema\" version=\"1.4.1\">\n");
+        let _ = writeln!(
+            output,
+            "  <asset>\n    <contributor><authoring_tool>rw-parser-rs {}</authoring_tool></contributor>\n    <unit name=\"meter\" meter=\"1\"/>\n    <up_axis>Z_UP</up_axis>\n  </asset>",
+            env!("CARGO_PKG_VERSION")
+        );
+
+        self.write_images(&mut output, geometries);
+        self.write_materials(&mut output, geometries);
+
+        output.push_str("  <library_geometries>\n");
+        for (index, geometry) in geometries.iter().enumerate() {
+            write_geometry(&mut output, index, geometry);
+        }
+        output.push_str("  </library_geometries>\n");
+
+        if geometries.iter().any(|geometry| geometry.skin.is_some()) {
+            output.push_str("  <library_controllers>\n");
+            for (index, geometry) in geometries.iter().enumerate() {
+                if let Some(skin) = &geometry.skin {
+                    write_controller(&mut output, index, skin, &self.dff.skin_bone_frames(skin.bone_count as usize));
+                }
+            }
+            output.push_str("  </library_controllers>\n");
+        }
+
+        output.push_str("  <library_visual_scenes>\n    <visual_scene id=\"scene\" name=\"scene\">\n");
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); frames.len()];
+        let mut roots = Vec::new();
+        for (index, frame) in frames.iter().enumerate() {
+            match usize::try_from(frame.parent_frame).ok().filter(|&parent| parent < index) {
+                Some(parent) => children[parent].push(index),
+                None => roots.push(index),
+            }
+        }
+        let mut instances: Vec<Vec<usize>> = vec![Vec::new(); frames.len()];
+        let mut unplaced = Vec::new();
+        for index in 0..geometries.len() {
+            match self.dff.atomics.get(index).map(|&frame| frame as usize).filter(|&frame| frame < frames.len()) {
+                Some(frame) => instances[frame].push(index),
+                None => unplaced.push(index),
+            }
+        }
+        let skeleton = roots.first().copied();
+        for root in roots {
+            self.write_node(&mut output, root, 3, &children, &instances, &joints, geometries, skeleton);
+        }
+        if !unplaced.is_empty() {
+            output.push_str("      <node id=\"unplaced\" name=\"unplaced\" type=\"NODE\">\n");
+            for index in unplaced {
+                write_instance(&mut output, index, &geometries[index], 4, skeleton);
+            }
+            output.push_str("      </node>\n");
+        }
+        output.push_str("    </visual_scene>\n  </library_visual_scenes>\n");
+        output.push_str("  <scene>\n    <instance_visual_scene url=\"#scene\"/>\n  </scene>\n</COLLADA>\n");
+        output
+    }
+
+    fn write_images(&self, output: &mut String, geometries: &[RwGeometry]) {
+        let mut names: Vec<&str> = Vec::new();
+        for material in geometries.iter().flat_map(|geometry| &geometry.material_list.material_data) {
+            if let Some(name) = material.texture_name().filter(|name| !names.contains(name)) {
+                names.push(name);
+            }
+        }
+        if names.is_empty() {
+            return;
+        }
+        output.push_str("  <library_images>\n");
+        for name in names {
+            let _ = writeln!(
+                output,
+                "    <image id=\"{}\" name=\"{}\"><init_from>{}</init_from></image>",
+                image_id(name),
+                escape(name),
+                uri_escape(&format!("{}.{}", name, self.texture_extension))
+            );
+        }
+        output.push_str("  </library_images>\n");
+    }
+
+    fn write_materials(&self, output: &mut String, geometries: &[RwGeometry]) {
+        let mut effects = String::new();
+        let mut materials = String::new();
+        for (geometry_index, geometry) in geometries.iter().enumerate() {
+            for (material_index, material) in geometry.material_list.material_data.iter().enumerate() {
+                let id = material_id(geometry_index, material_index);
+                let color = material.color;
+                let _ = writeln!(effects, "    <effect id=\"{}-effect\">\n      <profile_COMMON>", id);
+                let diffuse = match material.texture_name() {
+                    Some(name) => {
+                        let image = image_id(name);
+                        let _ = writeln!(
+                            effects,
+                            "        <newparam sid=\"{0}-surface\"><surface type=\"2D\"><init_from>{0}</init_from></surface></newparam>\n        <newparam sid=\"{0}-sampler\"><sampler2D><source>{0}-surface</source></sampler2D></newparam>",
+                            image
+                        );
+                        format!("<texture texture=\"{}-sampler\" texcoord=\"UVMap\"/>", image)
+                    }
+                    None => format!(
+                        "<color>{} {} {} {}</color>",
+                        color.r as f32 / 255.0,
+                        color.g as f32 / 255.0,
+                        color.b as f32 / 255.0,
+                        color.a as f32 / 255.0
+                    ),
+                };
+                let _ = writeln!(effects, "        <technique sid=\"common\">\n          <lambert>\n            <diffuse>{}</diffuse>", diffuse);
+                if color.a < 255 {
+                    let _ = writeln!(effects, "            <transparency><float>{}</float></transparency>", color.a as f32 / 255.0);
+                }
+                effects.push_str("          </lambert>\n        </technique>\n      </profile_COMMON>\n    </effect>\n");
+
+                let name = material.texture_name().map_or_else(|| id.clone(), escape);
+                let _ = writeln!(materials, "    <material id=\"{0}\" name=\"{1}\"><instance_effect url=\"#{0}-effect\"/></material>", id, name);
+            }
+        }
+        if !effects.is_empty() {
+            let _ = write!(
+                output,
+                "  <library_effects>\n{}  </library_effects>\n  <library_materials>\n{}  </library_materials>\n",
+                effects, materials
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn write_node(
+        &self,
+        output: &mut String,
+        frame_index: usize,
+        depth: usize,
+        children: &[Vec<usize>],
+        instances: &[Vec<usize>],
+        joints: &[bool],
+        geometries: &[RwGeometry],
+        skeleton: Option<usize>,
+    ) {
+        let indent = "  ".repeat(depth);
+        let frame = &self.dff.frames()[frame_index];
+        let name = self.dff.frame_name(frame_index).map_or_else(|| format!("Frame {}", frame_index), escape);
+        let _ = writeln!(
+            output,
+            "{0}<node id=\"frame{1}\" sid=\"frame{1}\" name=\"{2}\" type=\"{3}\">\n{0}  <matrix sid=\"transform\">{4}</matrix>",
+            indent,
+            frame_index,
+            name,
+            if joints[frame_index] { "JOINT" } else { "NODE" },
+            frame_matrix(&frame.rotation_matrix, frame.coordinates_offset)
+        );
+        for &index in &instances[frame_index] {
+            write_instance(output, index, &geometries[index], depth + 1, skeleton);
+        }
+        for &child in &children[frame_index] {
+            self.write_node(output, child, depth + 1, children, instances, joints, geometries, skeleton);
+        }
+        let _ = writeln!(output, "{}</node>", indent);
+    }
+}
+
+fn write_geometry(output: &mut String, index: usize, geometry: &RwGeometry) {
+    let vertex_count = geometry.vertex_information.len();
+    let id = format!("geometry{}", index);
+    let _ = writeln!(output, "    <geometry id=\"{0}\" name=\"Geometry {1}\">\n      <mesh>", id, index);
+
+    let positions: Vec<f32> = geometry.vertex_information.iter().flat_map(|v| [v.x, v.y, v.z]).collect();
+    write_source(output, &format!("{}-positions", id), &positions, &["X", "Y", "Z"]);
+    let mut inputs = vec![format!("<input semantic=\"VERTEX\" source=\"#{}-vertices\" offset=\"0\"/>", id)];
+
+    if geometry.normal_information.len() == vertex_count {
+        let normals: Vec<f32> = geometry.normal_information.iter().flat_map(|n| [n.x, n.y, n.z]).collect();
+        write_source(output, &format!("{}-normals", id), &normals, &["X", "Y", "Z"]);
+        inputs.push(format!("<input semantic=\"NORMAL\" source=\"#{}-normals\" offset=\"0\"/>", id));
+    }
+    if let Some(uvs) = geometry.texture_mapping_information.first().filter(|uvs| uvs.len() == vertex_count) {
+        // Collada puts the texture origin at the bottom left
+        let uvs: Vec<f32> = uvs.iter().flat_map(|uv| [uv.u, 1.0 - uv.v]).collect();
+        write_source(output, &format!("{}-uvs", id), &uvs, &["S", "T"]);
+        inputs.push(format!("<input semantic=\"TEXCOORD\" source=\"#{}-uvs\" offset=\"0\" set=\"0\"/>", id));
+    }
+    if geometry.vertex_color_information.len() == vertex_count {
+        let colors: Vec<f32> = geometry
+            .vertex_color_information
+            .iter()
+            .flat_map(|c| [c.r, c.g, c.b, c.a].map(|channel| channel as f32 / 255.0))
+            .collect();
+        write_source(output, &format!("{}-colors", id), &colors, &["R", "G", "B", "A"]);
+        inputs.push(format!("<input semantic=\"COLOR\" source=\"#{}-colors\" offset=\"0\"/>", id));
+    }
+    let _ = writeln!(
+        output,
+        "        <vertices id=\"{0}-vertices\"><input semantic=\"POSITION\" source=\"#{0}-positions\"/></vertices>",
+        id
+    );
+
+    for material_index in 0..geometry.material_list.material_data.len().max(1) {
+        let triangles: Vec<[usize; 3]> = geometry
+            .triangle_information
+            .iter()
+            .filter(|triangle| triangle.material_id as usize == material_index)
+            .map(|triangle| [triangle.vector.x as usize, triangle.vector.y as usize, triangle.vector.z as usize])
+            .filter(|corners| corners.iter().all(|&corner| corner < vertex_count))
+            .collect();
+        if triangles.is_empty() {
+            continue;
+        }
+        let _ = writeln!(
+            output,
+            "        <triangles material=\"{}\" count=\"{}\">",
+            material_id(index, material_index),
+            triangles.len()
+        );
+        for input in &inputs {
+            let _ = writeln!(output, "          {}", input);
+        }
+        let _ = writeln!(output, "          <p>{}</p>\n        </triangles>", join(triangles.iter().flatten()));
+    }
+    output.push_str("      </mesh>\n    </geometry>\n");
+}
+
+fn write_controller(output: &mut String, index: usize, skin: &RwSkin, bone_frames: &[usize]) {
+    let id = format!("skin{}", index);
+    let bone_count = bone_frames.len();
+    let _ = writeln!(
+        output,
+        "    <controller id=\"{0}\">\n      <skin source=\"#geometry{1}\">\n        <bind_shape_matrix>1 0 0 0 0 1 0 0 0 0 1 0 0 0 0 1</bind_shape_matrix>",
+        id, index
+    );
+
+    let names: Vec<String> = bone_frames.iter().map(|frame| format!("frame{}", frame)).collect();
+    let _ = writeln!(
+        output,
+        "        <source id=\"{0}-joints\">\n          <Name_array id=\"{0}-joints-array\" count=\"{1}\">{2}</Name_array>\n          <technique_common><accessor source=\"#{0}-joints-array\" count=\"{1}\" stride=\"1\"><param name=\"JOINT\" type=\"name\"/></accessor></technique_common>\n        </source>",
+        id,
+        bone_count,
+        names.join(" ")
+    );
+
+    let identity = RwMatrix3 {
+        right: RwVector3 { x: 1.0, y: 0.0, z: 0.0 },
+        up: RwVector3 { x: 0.0, y: 1.0, z: 0.0 },
+        at: RwVector3 { x: 0.0, y: 0.0, z: 1.0 },
+    };
+    let matrices: Vec<String> = (0..bone_count)
+        .map(|bone| match skin.inverse_bone_matrices.get(bone) {
+            Some(RwMatrix4 { right, up, at, transform }) => frame_matrix(
+                &RwMatrix3 {
+                    right: RwVector3 { x: right.x, y: right.y, z: right.z },
+                    up: RwVector3 { x: up.x, y: up.y, z: up.z },
+                    at: RwVector3 { x: at.x, y: at.y, z: at.z },
+                },
+                RwVector3 { x: transform.x, y: transform.y, z: transform.z },
+            ),
+            None => frame_matrix(&identity, RwVector3 { x: 0.0, y: 0.0, z: 0.0 }),
+        })
+        .collect();
+    let _ = writeln!(
+        output,
+        "        <source id=\"{0}-bind-poses\">\n          <float_array id=\"{0}-bind-poses-array\" count=\"{1}\">{2}</float_array>\n          <technique_common><accessor source=\"#{0}-bind-poses-array\" count=\"{3}\" stride=\"16\"><param name=\"TRANSFORM\" type=\"float4x4\"/></accessor></technique_common>\n        </source>",
+        id,
+        bone_count * 16,
+        matrices.join(" "),
+        bone_count
+    );
+
+    let mut weights = Vec::new();
+    let mut counts = Vec::new();
+    let mut pairs = Vec::new();
+    for (vertex, vertex_weights) in skin.vertex_weights.iter().enumerate() {
+        let indices = skin.bone_vertex_indices.get(vertex);
+        let mut count = 0;
+        for (slot, &weight) in vertex_weights.iter().enumerate() {
+            let bone = indices.and_then(|indices| indices.get(slot)).copied().unwrap_or(0) as usize;
+            if weight > 0.0 && bone < bone_count {
+                pairs.extend([bone, weights.len()]);
+                weights.push(weight);
+                count += 1;
+            }
+        }
+        counts.push(count);
+    }
+    let _ = writeln!(
+        output,
+        "        <source id=\"{0}-weights\">\n          <float_array id=\"{0}-weights-array\" count=\"{1}\">{2}</float_array>\n          <technique_common><accessor source=\"#{0}-weights-array\" count=\"{1}\" stride=\"1\"><param name=\"WEIGHT\" type=\"float\"/></accessor></technique_common>\n        </source>",
+        id,
+        weights.len(),
+        join(&weights)
+    );
+    let _ = writeln!(
+        output,
+        "        <joints>\n          <input semantic=\"JOINT\" source=\"#{0}-joints\"/>\n          <input semantic=\"INV_BIND_MATRIX\" source=\"#{0}-bind-poses\"/>\n        </joints>",
+        id
+    );
+    let _ = writeln!(
+        output,
+        "        <vertex_weights count=\"{1}\">\n          <input semantic=\"JOINT\" source=\"#{0}-joints\" offset=\"0\"/>\n          <input semantic=\"WEIGHT\" source=\"#{0}-weights\" offset=\"1\"/>\n          <vcount>{2}</vcount>\n          <v>{3}</v>\n        </vertex_weights>",
+        id,
+        counts.len(),
+        join(&counts),
+        join(&pairs)
+    );
+    output.push_str("      </skin>\n    </controller>\n");
+}
+
+/// Writes the instance of a geometry, or of its skin controller if it has
+/// one, binding its materials.
+fn write_instance(output: &mut String, index: usize, geometry: &RwGeometry, depth: usize, skeleton: Option<usize>) {
+    let indent = "  ".repeat(depth);
+    let element = match (&geometry.skin, skeleton) {
+        (Some(_), Some(skeleton)) => {
+            let _ = writeln!(output, "{}<instance_controller url=\"#skin{}\">\n{}  <skeleton>#frame{}</skeleton>", indent, index, indent, skeleton);
+            "instance_controller"
+        }
+        _ => {
+            let _ = writeln!(output, "{}<instance_geometry url=\"#geometry{}\">", indent, index);
+            "instance_geometry"
+        }
+    };
+    let _ = writeln!(output, "{}  <bind_material><technique_common>", indent);
+    for material_index in 0..geometry.material_list.material_data.len() {
+        let _ = writeln!(
+            output,
+            "{0}    <instance_material symbol=\"{1}\" target=\"#{1}\"><bind_vertex_input semantic=\"UVMap\" input_semantic=\"TEXCOORD\" input_set=\"0\"/></instance_material>",
+            indent,
+            material_id(index, material_index)
+        );
+    }
+    let _ = writeln!(output, "{0}  </technique_common></bind_material>\n{0}</{1}>", indent, element);
+}
+
+fn write_source(output: &mut String, id: &str, values: &[f32], params: &[&str]) {
+    let stride = params.len();
+    let params: String = params.iter().map(|param| format!("<param name=\"{}\" type=\"float\"/>", param)).collect();
+    let _ = writeln!(
+        output,
+        "        <source id=\"{0}\">\n          <float_array id=\"{0}-array\" count=\"{1}\">{2}</float_array>\n          <technique_common><accessor source=\"#{0}-array\" count=\"{3}\" stride=\"{4}\">{5}</accessor></technique_common>\n        </source>",
+        id,
+        values.len(),
+        join(values),
+        values.len() / stride,
+        stride,
+        params
+    );
+}
+
+/// Returns the row-major matrix of a frame, whose rotation rows are the
+/// columns of the Collada matrix.
+fn frame_matrix(rotation: &RwMatrix3, offset: RwVector3) -> String {
+    let RwMatrix3 { right, up, at } = rotation;
+    join([
+        right.x, up.x, at.x, offset.x,
+        right.y, up.y, at.y, offset.y,
+        right.z, up.z, at.z, offset.z,
+        0.0, 0.0, 0.0, 1.0,
+    ])
+}
+
+fn material_id(geometry_index: usize, material_index: usize) -> String {
+    format!("geometry{}-material{}", geometry_index, material_index)
+}
+
+/// Returns an XML id for a texture, whose name may contain any character.
+fn image_id(name: &str) -> String {
+    let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect();
+    format!("image-{}", name.to_lowercase())
+}
+
+fn join<T: std::fmt::Display>(values: impl IntoIterator<Item = T>) -> String {
+    let mut output = String::new();
+    for value in values {
+        if !output.is_empty() {
+            output.push(' ');
+        }
+        let _ = write!(output, "{}", value);
+    }
+    output
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+/// Percent-encodes a file name for `init_from`, which holds a URI relative
+/// to the document. The result needs no further XML escaping.
+fn uri_escape(file_name: &str) -> String {
+    let mut output = String::with_capacity(file_name.len());
+    for byte in file_name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            output.push(byte as char);
+        } else {
+            let _ = write!(output, "%{:02X}", byte);
+        }
+    }
+    output
+}
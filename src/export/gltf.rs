@@ -0,0 +1,560 @@
+//! # glTF Export
+//!
+//! Converts a parsed clump into a binary glTF 2.0 file (`.glb`), available
+//! with the `gltf` feature, so that models can be opened in Blender or any
+//! other glTF-aware tool.
+//!
+//! ## Features
+//!
+//! - One node per frame, keeping the frame hierarchy, with the geometry of
+//!   each atomic attached to the node of its frame.
+//! - One mesh per geometry and one primitive per material, with positions,
+//!   normals, prelighting colors and the first UV channel.
+//! - Materials with their color, and their texture embedded as a PNG when
+//!   it is found in the texture dictionary.
+//! - Skins built from `RwSkin`, with the HAnim bones as joints.
+//! - One animation per IFP animation, retargeted onto the frames and sampled
+//!   at a fixed rate.
+//! - A root node turning the Z-up RenderWare space into the Y-up glTF space.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::export::gltf::GltfExporter;
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/bmyst.dff").unwrap()).parse().unwrap();
+//! let txd = TxdParser::new(&fs::read("path/to/bmyst.txd").unwrap()).parse().unwrap();
+//! let ifp = IfpParser::new(&fs::read("path/to/ped.ifp").unwrap()).parse().unwrap();
+//!
+//! let glb = GltfExporter::new(&dff).with_textures(&txd).with_animations(&ifp).to_glb().unwrap();
+//! fs::write("path/to/bmyst.glb", glb).unwrap();
+//! ```
+
+use crate::renderware::dff::dff_material::AlphaMode;
+use crate::renderware::dff::dff_parser::{RwDff, RwGeometry, RwMaterial, RwSkin, RwTexture};
+use crate::renderware::ifp::ifp_parser::{RwIfp, RwIfpAnimation};
+use crate::renderware::txd::txd_parser::RwTxd;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
+
+use serde_json::{json, Value};
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+const UNSIGNED_BYTE: u32 = 5121;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+/// The rotation of the root node from Z-up to Y-up, as x, y, z and w.
+const Z_UP_TO_Y_UP: [f32; 4] = [-std::f32::consts::FRAC_1_SQRT_2, 0.0, 0.0, std::f32::consts::FRAC_1_SQRT_2];
+
+/// The main exporter for glTF files.
+pub struct GltfExporter<'a> {
+    dff: &'a RwDff,
+    txd: Option<&'a RwTxd>,
+    animations: Vec<&'a RwIfpAnimation>,
+    frame_rate: f32,
+    y_up: bool,
+}
+
+impl<'a> GltfExporter<'a> {
+    /// Creates an exporter for a clump, without textures or animations,
+    /// converting it to the Y-up space and sampling animations at 30 frames
+    /// per second.
+    pub fn new(dff: &'a RwDff) -> Self {
+        GltfExporter {
+            dff,
+            txd: None,
+            animations: Vec::new(),
+            frame_rate: 30.0,
+            y_up: true,
+        }
+    }
+
+    /// Embeds the textures of the materials found in `txd`.
+    pub fn with_textures(mut self, txd: &'a RwTxd) -> Self {
+        self.txd = Some(txd);
+        self
+    }
+
+    /// Adds every animation of an IFP package.
+    pub fn with_animations(mut self, ifp: &'a RwIfp) -> Self {
+        self.animations.extend(&ifp.animations);
+        self
+    }
+
+    /// Adds a single animation.
+    pub fn with_animation(mut self, animation: &'a RwIfpAnimation) -> Self {
+        self.animations.push(animation);
+        self
+    }
+
+    /// Sets the number of samples per second of the exported animations.
+    pub fn with_frame_rate(mut self, frame_rate: f32) -> Self {
+        self.frame_rate = frame_rate.max(1.0);
+        self
+    }
+
+    /// Sets whether the model is rotated from the Z-up RenderWare space into
+    /// the Y-up glTF space.
+    pub fn with_y_up(mut self, y_up: bool) -> Self {
+        self.y_up = y_up;
+        self
+    }
+
+    /// Builds the `.glb` file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the file bytes or an `std::io::Error` if the
+    /// binary data exceeds the 4 GiB limit of the format.
+    pub fn to_glb(&self) -> Result<Vec<u8>> {
+        let mut builder = Builder::default();
+        let frames = self.dff.frames();
+
+        let mut nodes: Vec<Value> = frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let rotation = frame.rotation_matrix.to_quaternion();
+                let offset = frame.coordinates_offset;
+                json!({
+                    "name": self.dff.frame_name(index).map_or_else(|| format!("Frame {}", index), str::to_string),
+                    "translation": [offset.x, offset.y, offset.z],
+                    "rotation": [rotation.x, rotation.y, rotation.z, rotation.w],
+                })
+            })
+            .collect();
+
+        let mut roots = Vec::new();
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); frames.len()];
+        for (index, frame) in frames.iter().enumerate() {
+            match usize::try_from(frame.parent_frame).ok().filter(|&parent| parent < frames.len() && parent != index) {
+                Some(parent) => children[parent].push(index),
+                None => roots.push(index),
+            }
+        }
+        for (node, children) in nodes.iter_mut().zip(children) {
+            if !children.is_empty() {
+                node["children"] = json!(children);
+            }
+        }
+
+        let mut materials = MaterialCache::default();
+        let mut skins = Vec::new();
+        if let Some(geometry_list) = &self.dff.geometry_list {
+            for (index, geometry) in geometry_list.geometries.iter().enumerate() {
+                let Some(mesh) = self.write_mesh(&mut builder, &mut materials, geometry) else {
+                    continue;
+                };
+                let mut node = json!({ "name": format!("Geometry {}", index), "mesh": mesh });
+                if let Some(skin) = geometry.skin.as_ref().filter(|_| !frames.is_empty()) {
+                    node["skin"] = json!(skins.len());
+                    skins.push(self.write_skin(&mut builder, skin));
+                }
+
+                let node_index = nodes.len();
+                nodes.push(node);
+                match self.dff.atomics.get(index).map(|&frame| frame as usize).filter(|&frame| frame < frames.len()) {
+                    Some(frame) => push_child(&mut nodes[frame], node_index),
+                    None => roots.push(node_index),
+                }
+            }
+        }
+
+        let animations: Vec<Value> =
+            self.animations.iter().filter_map(|animation| self.write_animation(&mut builder, animation)).collect();
+
+        let scene_nodes = if self.y_up {
+            nodes.push(json!({ "name": "Root", "rotation": Z_UP_TO_Y_UP, "children": roots }));
+            vec![nodes.len() - 1]
+        } else {
+            roots
+        };
+
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": concat!("rw-parser-rs ", env!("CARGO_PKG_VERSION")) },
+            "scene": 0,
+            "scenes": [{ "nodes": scene_nodes }],
+            "nodes": nodes,
+        });
+        for (key, values) in [
+            ("meshes", std::mem::take(&mut builder.meshes)),
+            ("materials", materials.materials),
+            ("textures", materials.textures),
+            ("images", materials.images),
+            ("samplers", materials.samplers),
+            ("skins", skins),
+            ("animations", animations),
+        ] {
+            if !values.is_empty() {
+                root[key] = Value::Array(values);
+            }
+        }
+        builder.finish(root)
+    }
+
+    /// Writes a geometry with one primitive per material, and returns the
+    /// index of its mesh, or `None` if it has no triangles.
+    fn write_mesh(&self, builder: &mut Builder, materials: &mut MaterialCache, geometry: &RwGeometry) -> Option<usize> {
+        let vertex_count = geometry.vertex_information.len();
+        let mut groups: Vec<(u16, Vec<u32>)> = Vec::new();
+        for triangle in &geometry.triangle_information {
+            let indices = [triangle.vector.x as u32, triangle.vector.y as u32, triangle.vector.z as u32];
+            if indices.iter().any(|&index| index as usize >= vertex_count) {
+                continue;
+            }
+            match groups.iter_mut().find(|(material, _)| *material == triangle.material_id) {
+                Some((_, group)) => group.extend(indices),
+                None => groups.push((triangle.material_id, indices.to_vec())),
+            }
+        }
+        if groups.is_empty() {
+            return None;
+        }
+
+        let mut attributes = serde_json::Map::new();
+        let positions: Vec<[f32; 3]> = geometry.vertex_information.iter().map(|v| [v.x, v.y, v.z]).collect();
+        attributes.insert("POSITION".into(), json!(builder.vec3_accessor(&positions, true)));
+        if geometry.normal_information.len() == vertex_count {
+            let normals: Vec<[f32; 3]> = geometry.normal_information.iter().map(|n| normalized([n.x, n.y, n.z])).collect();
+            attributes.insert("NORMAL".into(), json!(builder.vec3_accessor(&normals, false)));
+        }
+        if let Some(uvs) = geometry.texture_mapping_information.first().filter(|uvs| uvs.len() == vertex_count) {
+            let data: Vec<f32> = uvs.iter().flat_map(|uv| [uv.u, uv.v]).collect();
+            attributes.insert("TEXCOORD_0".into(), json!(builder.vertex_accessor(&floats(&data), FLOAT, "VEC2", vertex_count, None)));
+        }
+        if geometry.vertex_color_information.len() == vertex_count {
+            let data: Vec<u8> = geometry.vertex_color_information.iter().flat_map(|c| [c.r, c.g, c.b, c.a]).collect();
+            let accessor = builder.vertex_accessor(&data, UNSIGNED_BYTE, "VEC4", vertex_count, None);
+            builder.accessors[accessor]["normalized"] = json!(true);
+            attributes.insert("COLOR_0".into(), json!(accessor));
+        }
+        if let Some(skin) = geometry.skin.as_ref().filter(|skin| skin.vertex_weights.len() == vertex_count) {
+            let (joints, weights) = skin_attributes(skin);
+            attributes.insert("JOINTS_0".into(), json!(builder.vertex_accessor(&joints, UNSIGNED_BYTE, "VEC4", vertex_count, None)));
+            attributes.insert("WEIGHTS_0".into(), json!(builder.vertex_accessor(&floats(&weights), FLOAT, "VEC4", vertex_count, None)));
+        }
+
+        let primitives: Vec<Value> = groups
+            .into_iter()
+            .map(|(material_id, indices)| {
+                let count = indices.len();
+                let (data, component_type) = if vertex_count <= u16::MAX as usize {
+                    (indices.iter().flat_map(|&index| (index as u16).to_le_bytes()).collect::<Vec<u8>>(), UNSIGNED_SHORT)
+                } else {
+                    (indices.iter().flat_map(|&index| index.to_le_bytes()).collect(), UNSIGNED_INT)
+                };
+                let accessor = builder.index_accessor(&data, component_type, count);
+                let mut primitive = json!({ "attributes": attributes, "indices": accessor });
+                if let Some(material) = geometry.material_list.material_data.get(material_id as usize) {
+                    primitive["material"] = json!(materials.get(builder, self.txd, material));
+                }
+                primitive
+            })
+            .collect();
+
+        builder.meshes.push(json!({ "primitives": primitives }));
+        Some(builder.meshes.len() - 1)
+    }
+
+    /// Writes a skin whose joints are the nodes of the skin's bones.
+    fn write_skin(&self, builder: &mut Builder, skin: &RwSkin) -> Value {
+        let bone_count = skin.bone_count as usize;
+        let joints = self.dff.skin_bone_frames(bone_count);
+
+        let matrices: Vec<f32> = (0..bone_count)
+            .flat_map(|bone| match skin.inverse_bone_matrices.get(bone) {
+                Some(matrix) => [
+                    matrix.right.x, matrix.right.y, matrix.right.z, 0.0,
+                    matrix.up.x, matrix.up.y, matrix.up.z, 0.0,
+                    matrix.at.x, matrix.at.y, matrix.at.z, 0.0,
+                    matrix.transform.x, matrix.transform.y, matrix.transform.z, 1.0,
+                ],
+                None => [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            })
+            .collect();
+        let inverse_bind_matrices = builder.accessor(&floats(&matrices), FLOAT, "MAT4", bone_count, None);
+
+        json!({ "joints": joints, "inverseBindMatrices": inverse_bind_matrices })
+    }
+
+    /// Writes the translation and rotation of every frame an animation
+    /// drives, or returns `None` if it drives none.
+    fn write_animation(&self, builder: &mut Builder, animation: &RwIfpAnimation) -> Option<Value> {
+        let seconds_per_unit = animation.seconds_per_unit();
+        let duration = animation.duration();
+        let sample_count = (duration * seconds_per_unit * self.frame_rate).ceil() as usize + 1;
+        let times: Vec<f32> = (0..sample_count)
+            .map(|sample| duration * sample as f32 / (sample_count - 1).max(1) as f32)
+            .collect();
+
+        let retargeted = animation.retarget(self.dff, &times);
+        let tracks: Vec<_> = retargeted.tracks.iter().filter(|track| track.animated).collect();
+        if tracks.is_empty() {
+            return None;
+        }
+
+        let seconds: Vec<f32> = times.iter().map(|time| time * seconds_per_unit).collect();
+        let input = builder.accessor(&floats(&seconds), FLOAT, "SCALAR", seconds.len(), Some((&seconds[..1], &seconds[seconds.len() - 1..])));
+
+        let mut samplers = Vec::new();
+        let mut channels = Vec::new();
+        for track in tracks {
+            let translations: Vec<f32> =
+                track.transforms.iter().flat_map(|t| [t.position.x, t.position.y, t.position.z]).collect();
+            let rotations: Vec<f32> =
+                track.transforms.iter().flat_map(|t| [t.rotation.x, t.rotation.y, t.rotation.z, t.rotation.w]).collect();
+            for (path, data, kind) in [("translation", translations, "VEC3"), ("rotation", rotations, "VEC4")] {
+                let output = builder.accessor(&floats(&data), FLOAT, kind, times.len(), None);
+                channels.push(json!({ "sampler": samplers.len(), "target": { "node": track.frame_index, "path": path } }));
+                samplers.push(json!({ "input": input, "output": output, "interpolation": "LINEAR" }));
+            }
+        }
+
+        Some(json!({ "name": animation.name, "samplers": samplers, "channels": channels }))
+    }
+}
+
+fn push_child(node: &mut Value, child: usize) {
+    match node.get_mut("children").and_then(Value::as_array_mut) {
+        Some(children) => children.push(json!(child)),
+        None => node["children"] = json!([child]),
+    }
+}
+
+/// Returns the joints and weights of every vertex, with the weights scaled
+/// to add up to one as glTF requires.
+fn skin_attributes(skin: &RwSkin) -> (Vec<u8>, Vec<f32>) {
+    let mut joints = Vec::with_capacity(skin.vertex_weights.len() * 4);
+    let mut weights = Vec::with_capacity(skin.vertex_weights.len() * 4);
+    for (vertex, vertex_weights) in skin.vertex_weights.iter().enumerate() {
+        let indices = skin.bone_vertex_indices.get(vertex);
+        let mut vertex_joints = [0u8; 4];
+        let mut values = [0.0f32; 4];
+        for slot in 0..4 {
+            let weight = vertex_weights.get(slot).copied().unwrap_or(0.0);
+            let joint = indices.and_then(|indices| indices.get(slot)).copied().unwrap_or(0);
+            if weight > 0.0 && joint < skin.bone_count {
+                vertex_joints[slot] = joint;
+                values[slot] = weight;
+            }
+        }
+        let total: f32 = values.iter().sum();
+        if total > 0.0 {
+            values.iter_mut().for_each(|value| *value /= total);
+        } else {
+            values[0] = 1.0;
+        }
+        joints.extend(vertex_joints);
+        weights.extend(values);
+    }
+    (joints, weights)
+}
+
+fn normalized([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let length = (x * x + y * y + z * z).sqrt();
+    if length > f32::EPSILON { [x / length, y / length, z / length] } else { [0.0, 0.0, 1.0] }
+}
+
+fn floats(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// The binary chunk and the buffer views, accessors and meshes written so
+/// far.
+#[derive(Default)]
+struct Builder {
+    binary: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+}
+
+impl Builder {
+    /// Appends data to the binary chunk, aligned to four bytes, and returns
+    /// the index of its buffer view.
+    fn buffer_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        self.binary.resize(self.binary.len().next_multiple_of(4), 0);
+        let mut view = json!({ "buffer": 0, "byteOffset": self.binary.len(), "byteLength": data.len() });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.binary.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    /// Writes an accessor for animation or skin data, whose buffer view has
+    /// no target.
+    fn accessor(&mut self, data: &[u8], component_type: u32, kind: &str, count: usize, bounds: Option<(&[f32], &[f32])>) -> usize {
+        let view = self.buffer_view(data, None);
+        self.push_accessor(view, component_type, kind, count, bounds)
+    }
+
+    fn vertex_accessor(&mut self, data: &[u8], component_type: u32, kind: &str, count: usize, bounds: Option<(&[f32], &[f32])>) -> usize {
+        let view = self.buffer_view(data, Some(ARRAY_BUFFER));
+        self.push_accessor(view, component_type, kind, count, bounds)
+    }
+
+    fn index_accessor(&mut self, data: &[u8], component_type: u32, count: usize) -> usize {
+        let view = self.buffer_view(data, Some(ELEMENT_ARRAY_BUFFER));
+        self.push_accessor(view, component_type, "SCALAR", count, None)
+    }
+
+    fn push_accessor(&mut self, view: usize, component_type: u32, kind: &str, count: usize, bounds: Option<(&[f32], &[f32])>) -> usize {
+        let mut accessor = json!({ "bufferView": view, "componentType": component_type, "count": count, "type": kind });
+        if let Some((min, max)) = bounds {
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    /// Writes three-component vectors, with their bounds if `bounded`, as
+    /// glTF requires for positions.
+    fn vec3_accessor(&mut self, values: &[[f32; 3]], bounded: bool) -> usize {
+        let data: Vec<f32> = values.iter().flatten().copied().collect();
+        if !bounded {
+            return self.vertex_accessor(&floats(&data), FLOAT, "VEC3", values.len(), None);
+        }
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for value in values {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(value[axis]);
+                max[axis] = max[axis].max(value[axis]);
+            }
+        }
+        self.vertex_accessor(&floats(&data), FLOAT, "VEC3", values.len(), Some((&min, &max)))
+    }
+
+    /// Adds the buffer to `root` and lays out the `.glb` file.
+    fn finish(mut self, mut root: Value) -> Result<Vec<u8>> {
+        if !self.binary.is_empty() {
+            self.binary.resize(self.binary.len().next_multiple_of(4), 0);
+            root["buffers"] = json!([{ "byteLength": self.binary.len() }]);
+            root["bufferViews"] = Value::Array(self.buffer_views);
+            root["accessors"] = Value::Array(self.accessors);
+        }
+
+        let mut json = serde_json::to_vec(&root).map_err(Error::other)?;
+        json.resize(json.len().next_multiple_of(4), b' ');
+
+        let binary_chunk = if self.binary.is_empty() { 0 } else { 8 + self.binary.len() };
+        let length = u32::try_from(12 + 8 + json.len() + binary_chunk)
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "The glTF file would exceed 4 GiB"))?;
+
+        let mut output = Vec::with_capacity(length as usize);
+        output.extend_from_slice(b"glTF");
+        output.extend_from_slice(&2u32.to_le_bytes());
+        output.extend_from_slice(&length.to_le_bytes());
+        output.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        output.extend_from_slice(b"JSON");
+        output.extend_from_slice(&json);
+        if !self.binary.is_empty() {
+            output.extend_from_slice(&(self.binary.len() as u32).to_le_bytes());
+            output.extend_from_slice(b"BIN\0");
+            output.extend_from_slice(&self.binary);
+        }
+        Ok(output)
+    }
+}
+
+/// The glTF materials, textures, images and samplers, shared between the
+/// primitives that use the same RenderWare material or texture.
+#[derive(Default)]
+struct MaterialCache {
+    materials: Vec<Value>,
+    material_index: HashMap<String, usize>,
+    textures: Vec<Value>,
+    texture_index: HashMap<String, Option<usize>>,
+    images: Vec<Value>,
+    samplers: Vec<Value>,
+}
+
+impl MaterialCache {
+    /// Returns the index of the glTF material of `material`.
+    fn get(&mut self, builder: &mut Builder, txd: Option<&RwTxd>, material: &RwMaterial) -> usize {
+        let key = serde_json::to_string(material).unwrap_or_default();
+        if let Some(&index) = self.material_index.get(&key) {
+            return index;
+        }
+
+        let color = material.color;
+        let mut gltf_material = json!({
+            "pbrMetallicRoughness": {
+                "baseColorFactor": [color.r as f32 / 255.0, color.g as f32 / 255.0, color.b as f32 / 255.0, color.a as f32 / 255.0],
+                "metallicFactor": 0.0,
+                "roughnessFactor": 1.0,
+            },
+            "doubleSided": true,
+        });
+        if let Some(name) = material.texture_name() {
+            gltf_material["name"] = json!(name);
+            if let Some(index) = material.texture.as_ref().and_then(|texture| self.texture(builder, txd, texture)) {
+                gltf_material["pbrMetallicRoughness"]["baseColorTexture"] = json!({ "index": index });
+            }
+        }
+        match material.alpha_mode(txd) {
+            AlphaMode::Opaque => {}
+            AlphaMode::Mask => gltf_material["alphaMode"] = json!("MASK"),
+            AlphaMode::Blend => gltf_material["alphaMode"] = json!("BLEND"),
+        }
+
+        self.materials.push(gltf_material);
+        self.material_index.insert(key, self.materials.len() - 1);
+        self.materials.len() - 1
+    }
+
+    /// Returns the index of the glTF texture of `texture`, or `None` if it is
+    /// not in the dictionary or cannot be decoded.
+    fn texture(&mut self, builder: &mut Builder, txd: Option<&RwTxd>, texture: &RwTexture) -> Option<usize> {
+        let native = txd?.texture_dictionary.get_by_name(&texture.texture_name)?;
+        let key = format!("{}/{}/{}/{}", native.texture_name, texture.texture_filtering, texture.u_addressing, texture.v_addressing);
+        match self.texture_index.get(&key) {
+            Some(&index) => index,
+            None => {
+                let index = native.to_png().ok().map(|png| {
+                    let view = builder.buffer_view(&png, None);
+                    self.images.push(json!({ "name": native.texture_name, "bufferView": view, "mimeType": "image/png" }));
+                    self.samplers.push(sampler(texture));
+                    self.textures.push(json!({ "source": self.images.len() - 1, "sampler": self.samplers.len() - 1 }));
+                    self.textures.len() - 1
+                });
+                self.texture_index.insert(key, index);
+                index
+            }
+        }
+    }
+}
+
+/// Returns the glTF sampler of the filtering and addressing of a texture.
+fn sampler(texture: &RwTexture) -> Value {
+    let (mag_filter, min_filter) = match texture.texture_filtering {
+        1 => (9728, 9728), // Nearest
+        3 => (9728, 9984), // Nearest, nearest mipmap
+        4 => (9728, 9986), // Nearest, linear mipmap
+        5 => (9729, 9985), // Linear, nearest mipmap
+        6 => (9729, 9987), // Linear, linear mipmap
+        _ => (9729, 9729), // Linear
+    };
+    let wrap = |addressing: u8| match addressing {
+        2 => 33648, // Mirror
+        3 | 4 => 33071, // Clamp, border
+        _ => 10497, // Repeat
+    };
+    json!({
+        "magFilter": mag_filter,
+        "minFilter": min_filter,
+        "wrapS": wrap(texture.u_addressing),
+        "wrapT": wrap(texture.v_addressing),
+    })
+}
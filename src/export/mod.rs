@@ -0,0 +1,6 @@
+#[cfg(feature = "dae")]
+pub mod dae;
+#[cfg(feature = "gltf")]
+pub mod gltf;
+pub mod obj;
+pub mod smd;
\ No newline at end of file
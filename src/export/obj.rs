@@ -0,0 +1,268 @@
+//! # OBJ Export
+//!
+//! Converts the static geometry of a parsed clump into a Wavefront `.obj`
+//! file and its `.mtl` material library, for tools that do not read glTF.
+//!
+//! ## Features
+//!
+//! - One object per geometry, placed in world space by the frames of its
+//!   atomic.
+//! - Vertices, normals and the first UV channel, flipped to the bottom-left
+//!   origin OBJ expects.
+//! - One group of faces per material, with materials shared by name.
+//! - An `.mtl` file whose diffuse maps reference the textures by name, which
+//!   can be extracted next to it with `extract_textures` when the `image`
+//!   feature is enabled.
+//!
+//! Skins, frames and animations are not represented in OBJ and are dropped.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::export::obj::ObjExporter;
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/lamppost.dff").unwrap()).parse().unwrap();
+//!
+//! let exporter = ObjExporter::new(&dff).with_mtl_file_name("lamppost.mtl");
+//! fs::write("path/to/lamppost.obj", exporter.to_obj()).unwrap();
+//! fs::write("path/to/lamppost.mtl", exporter.to_mtl()).unwrap();
+//! ```
+
+use crate::renderware::dff::dff_parser::{RwDff, RwMaterial};
+use crate::renderware::dff::dff_transform::{world_transform, IDENTITY};
+use crate::utils::file_name::UniqueFileNames;
+use std::fmt::Write;
+
+#[cfg(feature = "image")]
+use crate::renderware::txd::txd_parser::RwTxd;
+#[cfg(feature = "image")]
+use std::io::Result;
+#[cfg(feature = "image")]
+use std::path::{Path, PathBuf};
+
+/// The main exporter for OBJ files.
+pub struct ObjExporter<'a> {
+    dff: &'a RwDff,
+    mtl_file_name: Option<String>,
+    texture_extension: String,
+    world_space: bool,
+}
+
+/// A material of the `.mtl` file.
+struct ObjMaterial<'a> {
+    name: String,
+    material: &'a RwMaterial,
+}
+
+impl<'a> ObjExporter<'a> {
+    /// Creates an exporter for a clump, placing geometries in world space and
+    /// referencing textures as `.png` files.
+    pub fn new(dff: &'a RwDff) -> Self {
+        ObjExporter {
+            dff,
+            mtl_file_name: None,
+            texture_extension: "png".to_string(),
+            world_space: true,
+        }
+    }
+
+    /// Sets the file name of the material library referenced by the `.obj`
+    /// file. Without it, the file has no `mtllib` line.
+    pub fn with_mtl_file_name(mut self, mtl_file_name: &str) -> Self {
+        self.mtl_file_name = Some(mtl_file_name.to_string());
+        self
+    }
+
+    /// Sets the extension of the texture files referenced by the `.mtl` file.
+    pub fn with_texture_extension(mut self, texture_extension: &str) -> Self {
+        self.texture_extension = texture_extension.trim_start_matches('.').to_string();
+        self
+    }
+
+    /// Sets whether geometries are moved by the frames of their atomics, or
+    /// kept in their own model space.
+    pub fn with_world_space(mut self, world_space: bool) -> Self {
+        self.world_space = world_space;
+        self
+    }
+
+    /// Builds the `.obj` file.
+    pub fn to_obj(&self) -> String {
+        let mut output = String::new();
+        if let Some(mtl_file_name) = &self.mtl_file_name {
+            let _ = writeln!(output, "mtllib {}", mtl_file_name);
+        }
+
+        let (materials, material_indices) = self.materials();
+        let frames = self.dff.frames();
+        let Some(geometry_list) = &self.dff.geometry_list else {
+            return output;
+        };
+
+        // OBJ indices are 1-based and shared by every object of the file
+        let (mut vertex_base, mut uv_base, mut normal_base) = (1, 1, 1);
+        for (index, geometry) in geometry_list.geometries.iter().enumerate() {
+            let vertex_count = geometry.vertex_information.len();
+            if vertex_count == 0 {
+                continue;
+            }
+            let transform = match self.dff.atomics.get(index) {
+                Some(&frame) if self.world_space => world_transform(frames, frame as usize),
+                _ => IDENTITY,
+            };
+            let _ = writeln!(output, "o Geometry {}", index);
+
+            for &vertex in &geometry.vertex_information {
+                let [x, y, z] = transform.apply(vertex);
+                let _ = writeln!(output, "v {} {} {}", x, y, z);
+            }
+            let uvs = geometry.texture_mapping_information.first().filter(|uvs| uvs.len() == vertex_count);
+            for uv in uvs.into_iter().flatten() {
+                let _ = writeln!(output, "vt {} {}", uv.u, 1.0 - uv.v);
+            }
+            let has_normals = geometry.normal_information.len() == vertex_count;
+            if has_normals {
+                for &normal in &geometry.normal_information {
+                    let [x, y, z] = transform.rotate(normal);
+                    let _ = writeln!(output, "vn {} {} {}", x, y, z);
+                }
+            }
+
+            let mut material_ids: Vec<u16> = geometry.triangle_information.iter().map(|triangle| triangle.material_id).collect();
+            material_ids.sort_unstable();
+            material_ids.dedup();
+            for material_id in material_ids {
+                match material_indices[index].get(material_id as usize) {
+                    Some(&material) => {
+                        let _ = writeln!(output, "usemtl {}", materials[material].name);
+                    }
+                    None => {
+                        let _ = writeln!(output, "usemtl default");
+                    }
+                }
+                for triangle in geometry.triangle_information.iter().filter(|triangle| triangle.material_id == material_id) {
+                    let corners = [triangle.vector.x as usize, triangle.vector.y as usize, triangle.vector.z as usize];
+                    if corners.iter().any(|&corner| corner >= vertex_count) {
+                        continue;
+                    }
+                    output.push('f');
+                    for corner in corners {
+                        let _ = match (uvs.is_some(), has_normals) {
+                            (true, true) => write!(output, " {}/{}/{}", vertex_base + corner, uv_base + corner, normal_base + corner),
+                            (true, false) => write!(output, " {}/{}", vertex_base + corner, uv_base + corner),
+                            (false, true) => write!(output, " {}//{}", vertex_base + corner, normal_base + corner),
+                            (false, false) => write!(output, " {}", vertex_base + corner),
+                        };
+                    }
+                    output.push('\n');
+                }
+            }
+
+            vertex_base += vertex_count;
+            uv_base += if uvs.is_some() { vertex_count } else { 0 };
+            normal_base += if has_normals { vertex_count } else { 0 };
+        }
+        output
+    }
+
+    /// Builds the `.mtl` file.
+    pub fn to_mtl(&self) -> String {
+        let texture_files = self.texture_files();
+        let mut output = String::new();
+        for ObjMaterial { name, material } in self.materials().0 {
+            let color = material.color;
+            let _ = writeln!(output, "newmtl {}", name);
+            let _ = writeln!(
+                output,
+                "Kd {} {} {}",
+                color.r as f32 / 255.0,
+                color.g as f32 / 255.0,
+                color.b as f32 / 255.0
+            );
+            if color.a < 255 {
+                let _ = writeln!(output, "d {}", color.a as f32 / 255.0);
+            }
+            if let Some((_, file_name)) = material.texture_name().and_then(|name| texture_files.iter().find(|(known, _)| *known == name)) {
+                let _ = writeln!(output, "map_Kd {}", file_name);
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Returns the names of the textures referenced by the `.mtl` file.
+    pub fn texture_names(&self) -> Vec<&'a str> {
+        let mut names: Vec<&str> = Vec::new();
+        for ObjMaterial { material, .. } in self.materials().0 {
+            if let Some(name) = material.texture_name().filter(|name| !names.contains(name)) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Writes the textures referenced by the `.mtl` file as PNG files into
+    /// `directory`, skipping the ones missing from `txd`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the paths of the written files or an
+    /// `std::io::Error` if a texture cannot be decoded or written.
+    #[cfg(feature = "image")]
+    pub fn extract_textures(&self, txd: &RwTxd, directory: &Path) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for (name, file_name) in self.texture_files() {
+            let Some(texture) = txd.texture_dictionary.get_by_name(name) else {
+                continue;
+            };
+            let path = directory.join(file_name);
+            std::fs::write(&path, texture.to_png()?)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Returns the texture names with the file names the `.mtl` file refers
+    /// to them by. Names come from the model, so they are sanitized into
+    /// distinct file names that stay inside the output directory.
+    fn texture_files(&self) -> Vec<(&'a str, String)> {
+        let mut file_names = UniqueFileNames::new();
+        self.texture_names()
+            .into_iter()
+            .map(|name| (name, file_names.claim(name, &self.texture_extension)))
+            .collect()
+    }
+
+    /// Returns the distinct materials of the clump, and the index of the
+    /// material each geometry's material ids refer to.
+    fn materials(&self) -> (Vec<ObjMaterial<'a>>, Vec<Vec<usize>>) {
+        let mut materials: Vec<ObjMaterial<'a>> = Vec::new();
+        let geometries = self.dff.geometry_list.as_ref().map_or(&[][..], |list| &list.geometries[..]);
+        let indices = geometries
+            .iter()
+            .map(|geometry| {
+                geometry
+                    .material_list
+                    .material_data
+                    .iter()
+                    .map(|material| match materials.iter().position(|known| known.material == material) {
+                        Some(index) => index,
+                        None => {
+                            let base = material.texture_name().unwrap_or("material");
+                            let name = if materials.iter().any(|known| known.name == base) {
+                                format!("{}_{}", base, materials.len())
+                            } else {
+                                base.to_string()
+                            };
+                            materials.push(ObjMaterial { name, material });
+                            materials.len() - 1
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        (materials, indices)
+    }
+}
@@ -0,0 +1,192 @@
+//! # SMD Export
+//!
+//! Converts a parsed clump and its IFP animations into Valve SMD files, the
+//! text format of Source-style toolchains. SMD is easy to read by eye, which
+//! makes it a good way to check a skeleton or an animation.
+//!
+//! ## Features
+//!
+//! - A reference file with one node per frame, the bind pose, and every
+//!   triangle in model space with its texture and bone weights.
+//! - One animation file per IFP animation, retargeted onto the frames and
+//!   sampled at a fixed rate.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::export::smd::SmdExporter;
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/bmyst.dff").unwrap()).parse().unwrap();
+//! let ifp = IfpParser::new(&fs::read("path/to/ped.ifp").unwrap()).parse().unwrap();
+//!
+//! let exporter = SmdExporter::new(&dff);
+//! fs::write("path/to/bmyst.smd", exporter.to_reference()).unwrap();
+//! for animation in &ifp.animations {
+//!     fs::write(format!("path/to/{}.smd", animation.name), exporter.to_animation(animation)).unwrap();
+//! }
+//! ```
+
+use crate::renderware::common::types::{RwQuaternion, RwVector3};
+use crate::renderware::dff::dff_parser::RwDff;
+use crate::renderware::dff::dff_transform::{world_transform, IDENTITY};
+use crate::renderware::ifp::ifp_parser::RwIfpAnimation;
+use std::fmt::Write;
+
+/// The main exporter for SMD files.
+pub struct SmdExporter<'a> {
+    dff: &'a RwDff,
+    frame_rate: f32,
+    texture_extension: String,
+}
+
+impl<'a> SmdExporter<'a> {
+    /// Creates an exporter for a clump, sampling animations at 30 frames per
+    /// second and naming materials after `.png` textures.
+    pub fn new(dff: &'a RwDff) -> Self {
+        SmdExporter {
+            dff,
+            frame_rate: 30.0,
+            texture_extension: "png".to_string(),
+        }
+    }
+
+    /// Sets the number of frames per second of the exported animations.
+    pub fn with_frame_rate(mut self, frame_rate: f32) -> Self {
+        self.frame_rate = frame_rate.max(1.0);
+        self
+    }
+
+    /// Sets the extension of the texture files that name the materials.
+    pub fn with_texture_extension(mut self, texture_extension: &str) -> Self {
+        self.texture_extension = texture_extension.trim_start_matches('.').to_string();
+        self
+    }
+
+    /// Builds the reference file, with the bind pose and the triangles.
+    pub fn to_reference(&self) -> String {
+        let mut output = self.header();
+        output.push_str("skeleton\ntime 0\n");
+        self.write_pose(&mut output, |frame| {
+            let frame = &self.dff.frames()[frame];
+            (frame.coordinates_offset, frame.rotation_matrix.to_quaternion())
+        });
+        output.push_str("end\ntriangles\n");
+
+        let frames = self.dff.frames();
+        let geometries = self.dff.geometry_list.as_ref().map_or(&[][..], |list| &list.geometries[..]);
+        for (index, geometry) in geometries.iter().enumerate() {
+            let vertex_count = geometry.vertex_information.len();
+            let frame = self.dff.atomics.get(index).map_or(0, |&frame| frame as usize);
+            let transform = if frame < frames.len() { world_transform(frames, frame) } else { IDENTITY };
+            let parent = frame.min(frames.len().saturating_sub(1));
+            let uvs = geometry.texture_mapping_information.first().filter(|uvs| uvs.len() == vertex_count);
+            let bone_frames = geometry.skin.as_ref().map(|skin| self.dff.skin_bone_frames(skin.bone_count as usize));
+
+            for triangle in &geometry.triangle_information {
+                let corners = [triangle.vector.x as usize, triangle.vector.y as usize, triangle.vector.z as usize];
+                if corners.iter().any(|&corner| corner >= vertex_count) {
+                    continue;
+                }
+                let material = geometry.material_list.material_data.get(triangle.material_id as usize);
+                match material.and_then(|material| material.texture_name()) {
+                    Some(name) => {
+                        let _ = writeln!(output, "{}.{}", name, self.texture_extension);
+                    }
+                    None => output.push_str("default\n"),
+                }
+
+                for corner in corners {
+                    let [x, y, z] = transform.apply(geometry.vertex_information[corner]);
+                    let normal = geometry.normal_information.get(corner).copied().unwrap_or(RwVector3 { x: 0.0, y: 0.0, z: 1.0 });
+                    let [nx, ny, nz] = transform.rotate(normal);
+                    // SMD puts the texture origin at the bottom left
+                    let (u, v) = uvs.map_or((0.0, 0.0), |uvs| (uvs[corner].u, 1.0 - uvs[corner].v));
+                    let _ = write!(output, "{} {} {} {} {} {} {} {} {}", parent, x, y, z, nx, ny, nz, u, v);
+
+                    if let (Some(skin), Some(bone_frames)) = (&geometry.skin, &bone_frames) {
+                        let links: Vec<(usize, f32)> = skin
+                            .vertex_weights
+                            .get(corner)
+                            .into_iter()
+                            .flatten()
+                            .enumerate()
+                            .filter(|&(_, &weight)| weight > 0.0)
+                            .filter_map(|(slot, &weight)| {
+                                let bone = *skin.bone_vertex_indices.get(corner)?.get(slot)? as usize;
+                                Some((*bone_frames.get(bone)?, weight))
+                            })
+                            .collect();
+                        let _ = write!(output, " {}", links.len());
+                        for (bone, weight) in links {
+                            let _ = write!(output, " {} {}", bone, weight);
+                        }
+                    }
+                    output.push('\n');
+                }
+            }
+        }
+        output.push_str("end\n");
+        output
+    }
+
+    /// Builds the animation file of an IFP animation, with one pose per
+    /// frame of the animation.
+    pub fn to_animation(&self, animation: &RwIfpAnimation) -> String {
+        let units_per_frame = 1.0 / (self.frame_rate * animation.seconds_per_unit());
+        let frame_count = (animation.duration() / units_per_frame).ceil() as usize + 1;
+        let times: Vec<f32> = (0..frame_count).map(|frame| (frame as f32 * units_per_frame).min(animation.duration())).collect();
+        let retargeted = animation.retarget(self.dff, &times);
+
+        let mut output = self.header();
+        output.push_str("skeleton\n");
+        for time in 0..frame_count {
+            let _ = writeln!(output, "time {}", time);
+            self.write_pose(&mut output, |frame| {
+                let transform = retargeted.tracks[frame].transforms[time];
+                (transform.position, transform.rotation)
+            });
+        }
+        output.push_str("end\n");
+        output
+    }
+
+    /// Writes the version and the node list. A model without frames gets a
+    /// single root node, as SMD needs at least one.
+    fn header(&self) -> String {
+        let mut output = String::from("version 1\nnodes\n");
+        let frames = self.dff.frames();
+        if frames.is_empty() {
+            output.push_str("0 \"root\" -1\n");
+        }
+        let mut names: Vec<String> = Vec::with_capacity(frames.len());
+        for (index, frame) in frames.iter().enumerate() {
+            let name = self.dff.frame_name(index).filter(|name| !name.is_empty() && !names.iter().any(|known| known == name));
+            let name = name.map_or_else(|| format!("Frame {}", index), |name| name.replace('"', "'"));
+            let parent = if (frame.parent_frame as i64) < index as i64 { frame.parent_frame } else { -1 };
+            let _ = writeln!(output, "{} \"{}\" {}", index, name, parent);
+            names.push(name);
+        }
+        output.push_str("end\n");
+        output
+    }
+
+    /// Writes the local position and rotation of every node.
+    fn write_pose(&self, output: &mut String, pose: impl Fn(usize) -> (RwVector3, RwQuaternion)) {
+        let frame_count = self.dff.frames().len();
+        if frame_count == 0 {
+            output.push_str("0 0 0 0 0 0 0\n");
+        }
+        for frame in 0..frame_count {
+            let (position, rotation) = pose(frame);
+            let angles = RwQuaternion::to_euler(rotation);
+            let _ = writeln!(
+                output,
+                "{} {} {} {} {} {} {}",
+                frame, position.x, position.y, position.z, angles.x, angles.y, angles.z
+            );
+        }
+    }
+}
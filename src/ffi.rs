@@ -0,0 +1,361 @@
+//! # C Bindings
+//!
+//! `extern "C"` functions for embedding the parser in C and C++ tools.
+//! Requires the `ffi` feature; build the shared library with `cargo rustc
+//! --release --features ffi --crate-type cdylib`.
+//!
+//! A file is parsed into an opaque `RwHandle`, which is queried through the
+//! `rw_dff_*`, `rw_txd_*` and `rw_ifp_*` functions or exported as JSON, and
+//! released with `rw_free`. When a function fails, `rw_last_error` returns a
+//! description of the failure. Files are parsed with `parse_untrusted`, so
+//! a panic in a parser is reported there instead of unwinding into C.
+//!
+//! ## Example
+//!
+//! ```c
+//! RwHandle *handle = rw_parse_txd(data, size);
+//! if (!handle) {
+//!     fprintf(stderr, "%s\n", rw_last_error());
+//!     return;
+//! }
+//! for (size_t i = 0; i < rw_txd_texture_count(handle); i++) {
+//!     uint16_t width, height;
+//!     const uint8_t *rgba = rw_txd_texture_rgba(handle, i, &width, &height);
+//! }
+//! char *json = rw_to_json(handle);
+//! rw_string_free(json);
+//! rw_free(handle);
+//! ```
+
+use crate::renderware::dff::dff_parser::{DffParser, RwDff};
+use crate::renderware::ifp::ifp_parser::{IfpParser, RwIfp};
+use crate::renderware::rw_error::RwResult;
+use crate::renderware::txd::txd_parser::{RwTxd, TxdParser};
+use std::cell::RefCell;
+use std::ffi::{CString, c_char};
+use std::ptr;
+use std::slice;
+
+/// The kind of file held by an `RwHandle`, as returned by `rw_handle_kind`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RwHandleKind {
+    Dff = 1,
+    Txd = 2,
+    Ifp = 3,
+}
+
+/// An opaque parsed file.
+pub enum RwHandle {
+    Dff(RwDff),
+    Txd(RwTxd),
+    Ifp(RwIfp),
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+fn into_handle<T>(result: RwResult<T>, wrap: fn(T) -> RwHandle) -> *mut RwHandle {
+    match result {
+        Ok(value) => Box::into_raw(Box::new(wrap(value))),
+        Err(error) => {
+            set_last_error(error.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `data` must point to `size` readable bytes, or be null with a `size` of 0.
+unsafe fn bytes<'a>(data: *const u8, size: usize) -> &'a [u8] {
+    if data.is_null() {
+        return &[];
+    }
+    unsafe { slice::from_raw_parts(data, size) }
+}
+
+/// Returns the description of the last failure on this thread, or null if
+/// nothing has failed. The string stays valid until the next failure.
+#[unsafe(no_mangle)]
+pub extern "C" fn rw_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Parses a DFF file. Returns null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `size` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_parse_dff(data: *const u8, size: usize) -> *mut RwHandle {
+    let data = unsafe { bytes(data, size) };
+    into_handle(DffParser::parse_untrusted(data), RwHandle::Dff)
+}
+
+/// Parses a TXD file, decoding every texture to RGBA. Returns null on
+/// failure.
+///
+/// # Safety
+///
+/// `data` must point to `size` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_parse_txd(data: *const u8, size: usize) -> *mut RwHandle {
+    let data = unsafe { bytes(data, size) };
+    into_handle(TxdParser::parse_untrusted(data), RwHandle::Txd)
+}
+
+/// Parses an IFP file. Returns null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `size` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_parse_ifp(data: *const u8, size: usize) -> *mut RwHandle {
+    let data = unsafe { bytes(data, size) };
+    into_handle(IfpParser::parse_untrusted(data), RwHandle::Ifp)
+}
+
+/// Releases a handle. Null is ignored.
+///
+/// # Safety
+///
+/// `handle` must come from one of the `rw_parse_*` functions and must not
+/// be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_free(handle: *mut RwHandle) {
+    if !handle.is_null() {
+        drop(unsafe { Box::from_raw(handle) });
+    }
+}
+
+/// Returns the kind of file held by `handle`.
+///
+/// # Safety
+///
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_handle_kind(handle: *const RwHandle) -> RwHandleKind {
+    match unsafe { &*handle } {
+        RwHandle::Dff(_) => RwHandleKind::Dff,
+        RwHandle::Txd(_) => RwHandleKind::Txd,
+        RwHandle::Ifp(_) => RwHandleKind::Ifp,
+    }
+}
+
+/// Exports the parsed file as JSON. Returns null on failure. The string must
+/// be released with `rw_string_free`.
+///
+/// # Safety
+///
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_to_json(handle: *const RwHandle) -> *mut c_char {
+    let json = match unsafe { &*handle } {
+        RwHandle::Dff(dff) => serde_json::to_string(dff),
+        RwHandle::Txd(txd) => serde_json::to_string(txd),
+        RwHandle::Ifp(ifp) => serde_json::to_string(ifp),
+    };
+    match json.map_err(|error| error.to_string()).and_then(|json| CString::new(json).map_err(|error| error.to_string())) {
+        Ok(json) => json.into_raw(),
+        Err(message) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a string returned by `rw_to_json`. Null is ignored.
+///
+/// # Safety
+///
+/// `text` must come from `rw_to_json` and must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(unsafe { CString::from_raw(text) });
+    }
+}
+
+/// Copies `text` into `buffer` as a null-terminated string, truncating it to
+/// fit. Returns the full length of `text`, without the terminator.
+///
+/// # Safety
+///
+/// `buffer` must point to `capacity` writable bytes, or be null.
+unsafe fn copy_string(text: &str, buffer: *mut c_char, capacity: usize) -> usize {
+    if !buffer.is_null() && capacity > 0 {
+        let length = text.len().min(capacity - 1);
+        unsafe {
+            ptr::copy_nonoverlapping(text.as_ptr(), buffer as *mut u8, length);
+            *buffer.add(length) = 0;
+        }
+    }
+    text.len()
+}
+
+/// Returns the number of geometries of a DFF handle, or 0 for other kinds.
+///
+/// # Safety
+///
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_dff_geometry_count(handle: *const RwHandle) -> usize {
+    match unsafe { &*handle } {
+        RwHandle::Dff(dff) => dff.geometry_list.as_ref().map_or(0, |list| list.geometries.len()),
+        _ => 0,
+    }
+}
+
+/// Returns the number of vertices of a geometry, or 0 if it does not exist.
+///
+/// # Safety
+///
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_dff_vertex_count(handle: *const RwHandle, geometry: usize) -> usize {
+    match unsafe { &*handle } {
+        RwHandle::Dff(dff) => dff
+            .geometry_list
+            .as_ref()
+            .and_then(|list| list.geometries.get(geometry))
+            .map_or(0, |geometry| geometry.vertex_information.len()),
+        _ => 0,
+    }
+}
+
+/// Returns the number of triangles of a geometry, or 0 if it does not exist.
+///
+/// # Safety
+///
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_dff_triangle_count(handle: *const RwHandle, geometry: usize) -> usize {
+    match unsafe { &*handle } {
+        RwHandle::Dff(dff) => dff
+            .geometry_list
+            .as_ref()
+            .and_then(|list| list.geometries.get(geometry))
+            .map_or(0, |geometry| geometry.triangle_information.len()),
+        _ => 0,
+    }
+}
+
+/// Returns the number of textures of a TXD handle, or 0 for other kinds.
+///
+/// # Safety
+///
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_txd_texture_count(handle: *const RwHandle) -> usize {
+    match unsafe { &*handle } {
+        RwHandle::Txd(txd) => txd.texture_dictionary.texture_natives.len(),
+        _ => 0,
+    }
+}
+
+/// Copies the name of a texture into `buffer`. Returns the length of the
+/// name, which is larger than `capacity - 1` if it was truncated, or 0 if
+/// the texture does not exist.
+///
+/// # Safety
+///
+/// `handle` must be a live handle and `buffer` must point to `capacity`
+/// writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_txd_texture_name(
+    handle: *const RwHandle,
+    index: usize,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    match unsafe { &*handle } {
+        RwHandle::Txd(txd) => txd
+            .texture_dictionary
+            .texture_natives
+            .get(index)
+            .map_or(0, |native| unsafe { copy_string(&native.texture_name, buffer, capacity) }),
+        _ => 0,
+    }
+}
+
+/// Returns the decoded RGBA pixels of a texture and writes its size to
+/// `width` and `height`. Returns null if the texture does not exist, its
+/// format has no decoder or its raster is truncated. The pixels live as long
+/// as the handle.
+///
+/// # Safety
+///
+/// `handle` must be a live handle, and `width` and `height` must be
+/// writable or null. A non-null result always points to exactly
+/// `width * height * 4` bytes; textures whose decoded bitmap has another
+/// length are reported as null, so the size can be trusted.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_txd_texture_rgba(
+    handle: *const RwHandle,
+    index: usize,
+    width: *mut u16,
+    height: *mut u16,
+) -> *const u8 {
+    let RwHandle::Txd(txd) = (unsafe { &*handle }) else {
+        return ptr::null();
+    };
+    let Some(native) = txd.texture_dictionary.texture_natives.get(index) else {
+        return ptr::null();
+    };
+    let pixel_bytes = native.width as usize * native.height as usize * 4;
+    let Some(rgba) = native.mipmaps.first().filter(|rgba| !rgba.is_empty() && rgba.len() == pixel_bytes) else {
+        return ptr::null();
+    };
+    unsafe {
+        if !width.is_null() {
+            *width = native.width;
+        }
+        if !height.is_null() {
+            *height = native.height;
+        }
+    }
+    rgba.as_ptr()
+}
+
+/// Returns the number of animations of an IFP handle, or 0 for other kinds.
+///
+/// # Safety
+///
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_ifp_animation_count(handle: *const RwHandle) -> usize {
+    match unsafe { &*handle } {
+        RwHandle::Ifp(ifp) => ifp.animations.len(),
+        _ => 0,
+    }
+}
+
+/// Copies the name of an animation into `buffer`, like
+/// `rw_txd_texture_name`.
+///
+/// # Safety
+///
+/// `handle` must be a live handle and `buffer` must point to `capacity`
+/// writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rw_ifp_animation_name(
+    handle: *const RwHandle,
+    index: usize,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    match unsafe { &*handle } {
+        RwHandle::Ifp(ifp) => ifp
+            .animations
+            .get(index)
+            .map_or(0, |animation| unsafe { copy_string(&animation.name, buffer, capacity) }),
+        _ => 0,
+    }
+}
@@ -0,0 +1,770 @@
+//! # glTF Import
+//!
+//! Converts a glTF 2.0 scene into an `RwDff`, available with the `gltf`
+//! feature, so that models authored in Blender or other tools can be brought
+//! into the game's format.
+//!
+//! ## Features
+//!
+//! - Reads binary `.glb` files and `.gltf` files whose buffers are embedded
+//!   as base64 data URIs.
+//! - Turns the nodes of the scene into frames, keeping their hierarchy and
+//!   names, and the Y-up glTF space into the Z-up RenderWare space.
+//! - Turns every mesh instance into a geometry and an atomic, merging its
+//!   primitives and triangulating strips and fans.
+//! - Turns materials into RenderWare materials named after their base color
+//!   texture, with the filtering and addressing of its sampler.
+//! - Turns the first skin into an HAnim hierarchy and skinned geometries with
+//!   up to four weights per vertex.
+//! - Rejects what RenderWare cannot store: geometries of more than 65535
+//!   vertices and skins of more bones than the configured limit.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::import::gltf::GltfImporter;
+//! use std::fs;
+//!
+//! let data = fs::read("path/to/model.glb").unwrap();
+//! let dff = GltfImporter::new(&data).with_max_bones(64).import().unwrap();
+//!
+//! println!("Geometries: {}", dff.geometry_list.map_or(0, |list| list.geometries.len()));
+//! ```
+
+use crate::renderware::common::types::{
+    RwColor, RwMatrix3, RwMatrix4, RwQuaternion, RwSphere, RwTextureCoordinate, RwTriangle, RwVector3, RwVector4,
+};
+use crate::renderware::dff::dff_model_type::DffModelType;
+use crate::renderware::dff::dff_parser::{
+    RwAnimNode, RwBinMesh, RwBone, RwDff, RwFrame, RwFrameList, RwGeometry, RwGeometryList, RwMaterial, RwMaterialList,
+    RwMesh, RwSkin, RwTexture,
+};
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::utils::rw_version::version_string;
+
+use serde_json::Value;
+
+/// The version given to imported models, RenderWare 3.6.0.3 (San Andreas).
+const VERSION: u32 = 0x36003;
+
+/// The HAnim flag of a bone without children.
+const POP_PARENT_MATRIX: i32 = 1;
+/// The HAnim flag of a bone followed by a sibling.
+const PUSH_PARENT_MATRIX: i32 = 2;
+/// The most elements of an accessor without a buffer view, which reads as
+/// zeros. Enough for the indices of the largest geometry RenderWare stores.
+const MAX_ZEROED_ELEMENTS: usize = 1 << 20;
+
+/// The main importer for glTF files.
+pub struct GltfImporter<'a> {
+    data: &'a [u8],
+    z_up: bool,
+    flip_v: bool,
+    max_bones: usize,
+}
+
+impl<'a> GltfImporter<'a> {
+    /// Creates an importer for a `.glb` or `.gltf` file, converting it to the
+    /// Z-up space and limiting skins to the 64 bones San Andreas can render.
+    pub fn new(data: &'a [u8]) -> Self {
+        GltfImporter {
+            data,
+            z_up: true,
+            flip_v: false,
+            max_bones: 64,
+        }
+    }
+
+    /// Sets whether the scene is rotated from the Y-up glTF space into the
+    /// Z-up RenderWare space.
+    pub fn with_z_up(mut self, z_up: bool) -> Self {
+        self.z_up = z_up;
+        self
+    }
+
+    /// Sets whether texture coordinates are flipped vertically. glTF and
+    /// RenderWare both put the origin at the top left, so this is only
+    /// needed for files from exporters that write bottom-left coordinates.
+    pub fn with_flip_v(mut self, flip_v: bool) -> Self {
+        self.flip_v = flip_v;
+        self
+    }
+
+    /// Sets the maximum number of bones of a skin. Skinned geometries also
+    /// never exceed 256 bones, the range of their bone indices.
+    pub fn with_max_bones(mut self, max_bones: usize) -> Self {
+        self.max_bones = max_bones.min(256);
+        self
+    }
+
+    /// Imports the default scene of the file.
+    ///
+    /// # Returns
+    ///
+    /// A `RwResult` containing the model or a `RwParseError` if the file is
+    /// not valid glTF, uses external buffers, or exceeds a RenderWare limit.
+    pub fn import(&self) -> RwResult<RwDff> {
+        let document = Document::read(self.data)?;
+        let nodes = document.array("nodes");
+
+        // Frames list parents before children, so the scene is walked depth first
+        let scene_index = document.root["scene"].as_u64().unwrap_or(0) as usize;
+        let scene = document.array("scenes").get(scene_index);
+        let roots: Vec<usize> = scene.map_or_else(
+            || (0..nodes.len()).filter(|&node| !nodes.iter().any(|parent| children(parent).contains(&node))).collect(),
+            |scene| indices(&scene["nodes"]),
+        );
+
+        let mut frames = Vec::new();
+        let mut names = Vec::new();
+        let mut frame_of_node = vec![None; nodes.len()];
+        let synthetic_root = roots.len() != 1;
+        if synthetic_root {
+            frames.push(RwFrame {
+                rotation_matrix: RwQuaternion::IDENTITY.to_matrix3(),
+                coordinates_offset: RwVector3 { x: 0.0, y: 0.0, z: 0.0 },
+                parent_frame: -1,
+                user_data: Vec::new(),
+            });
+            names.push("root".to_string());
+        }
+        let mut stack: Vec<(usize, i32)> = roots.iter().rev().map(|&node| (node, if synthetic_root { 0 } else { -1 })).collect();
+        while let Some((node_index, parent_frame)) = stack.pop() {
+            let node = nodes.get(node_index).ok_or_else(|| invalid(format!("Scene references missing node {}", node_index)))?;
+            if frame_of_node[node_index].is_some() {
+                return Err(invalid(format!("Node {} appears twice in the scene", node_index)));
+            }
+            frame_of_node[node_index] = Some(frames.len());
+            let parent = frames.len() as i32;
+            let (rotation_matrix, coordinates_offset) = node_transform(node);
+            frames.push(RwFrame { rotation_matrix, coordinates_offset, parent_frame, user_data: Vec::new() });
+            names.push(node["name"].as_str().unwrap_or_default().to_string());
+            stack.extend(children(node).into_iter().rev().map(|child| (child, parent)));
+        }
+        if self.z_up {
+            rotate_to_z_up(&mut frames[0]);
+        }
+
+        let skin = document.array("skins").first();
+        let joints = skin.map(|skin| indices(&skin["joints"])).unwrap_or_default();
+        if joints.len() > self.max_bones {
+            return Err(invalid(format!("The skin has {} bones, but at most {} are allowed", joints.len(), self.max_bones)));
+        }
+        let joint_frames: Vec<usize> = joints
+            .iter()
+            .map(|&joint| frame_of_node.get(joint).copied().flatten())
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid("A joint of the skin is not in the scene".to_string()))?;
+
+        let materials = document.array("materials").iter().map(|material| self.material(&document, material)).collect::<Vec<_>>();
+        let mut geometries = Vec::new();
+        let mut atomics = Vec::new();
+        for (node_index, node) in nodes.iter().enumerate() {
+            let (Some(mesh), Some(frame)) = (node["mesh"].as_u64(), frame_of_node[node_index]) else {
+                continue;
+            };
+            let mesh = document.array("meshes").get(mesh as usize).ok_or_else(|| invalid(format!("Node {} references missing mesh {}", node_index, mesh)))?;
+            let skinned = node["skin"].as_u64().is_some();
+            if node["skin"].as_u64().is_some_and(|skin| skin != 0) {
+                return Err(invalid(format!("Node {} uses a skin other than the first, which is not supported", node_index)));
+            }
+            if skinned && joints.is_empty() {
+                return Err(invalid(format!("Node {} is skinned, but the skin is missing or has no joints", node_index)));
+            }
+            geometries.push(self.geometry(&document, mesh, &materials, skinned.then_some((skin, joints.len())))?);
+            atomics.push(frame as u32);
+        }
+
+        let anim_nodes = match skin {
+            Some(_) => hanim_hierarchy(&frames, &joint_frames),
+            None => Vec::new(),
+        };
+        let model_type = if geometries.iter().any(|geometry: &RwGeometry| geometry.skin.is_some()) {
+            DffModelType::Skin
+        } else if names.iter().any(|name| name.to_lowercase().contains("wheel") || name.to_lowercase().contains("chassis")) {
+            DffModelType::Vehicle
+        } else {
+            DffModelType::Generic
+        };
+
+        Ok(RwDff {
+            model_type,
+            version: version_string(VERSION).unwrap_or_default().to_string(),
+            version_number: VERSION,
+            geometry_list: Some(RwGeometryList { geometric_object_count: geometries.len() as u32, geometries }),
+            frame_list: Some(RwFrameList { frame_count: frames.len() as u32, frames }),
+            atomic_pipelines: vec![None; atomics.len()],
+            atomics,
+            dummies: names,
+            anim_nodes,
+        })
+    }
+
+    /// Converts a glTF material, naming its texture after the image of its
+    /// base color texture.
+    fn material(&self, document: &Document, material: &Value) -> RwMaterial {
+        let pbr = &material["pbrMetallicRoughness"];
+        let factor: Vec<f32> = floats(&pbr["baseColorFactor"]);
+        let channel = |index: usize| (factor.get(index).copied().unwrap_or(1.0).clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        let texture = pbr["baseColorTexture"]["index"].as_u64().and_then(|index| document.array("textures").get(index as usize));
+        let texture = texture.map(|texture| {
+            let image = texture["source"].as_u64().and_then(|index| document.array("images").get(index as usize));
+            let texture_name = image
+                .and_then(|image| {
+                    image["name"].as_str().filter(|name| !name.is_empty()).or_else(|| {
+                        let uri = image["uri"].as_str().filter(|uri| !uri.starts_with("data:"))?;
+                        let file_name = uri.rsplit(['/', '\\']).next()?;
+                        Some(file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem))
+                    })
+                })
+                .unwrap_or("texture")
+                .to_string();
+            let sampler = texture["sampler"].as_u64().and_then(|index| document.array("samplers").get(index as usize));
+            let (texture_filtering, u_addressing, v_addressing, uses_mip_levels) = sampler.map_or((2, 1, 1, false), sampler_modes);
+            RwTexture { texture_filtering, u_addressing, v_addressing, uses_mip_levels, texture_name }
+        });
+
+        RwMaterial {
+            color: RwColor { r: channel(0), g: channel(1), b: channel(2), a: channel(3) },
+            is_textured: texture.is_some(),
+            ambient: Some(1.0),
+            specular: Some(1.0),
+            diffuse: Some(1.0),
+            texture,
+            normal_map: None,
+            right_to_render: None,
+        }
+    }
+
+    /// Converts a glTF mesh, merging its primitives into one geometry with a
+    /// material per primitive.
+    fn geometry(
+        &self,
+        document: &Document,
+        mesh: &Value,
+        materials: &[RwMaterial],
+        skin: Option<(Option<&Value>, usize)>,
+    ) -> RwResult<RwGeometry> {
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut colors = Vec::new();
+        let mut joints = Vec::new();
+        let mut weights = Vec::new();
+        let (mut has_normals, mut has_uvs, mut has_colors) = (false, false, false);
+        let mut triangles = Vec::new();
+        let mut geometry_materials: Vec<RwMaterial> = Vec::new();
+
+        for primitive in mesh["primitives"].as_array().map_or(&[][..], Vec::as_slice) {
+            let attributes = &primitive["attributes"];
+            let base = vertices.len();
+            let positions = document.accessor(&attributes["POSITION"], 3)?.ok_or_else(|| invalid("A primitive has no positions".to_string()))?;
+            let count = positions.len() / 3;
+            vertices.extend(positions.chunks_exact(3).map(|p| RwVector3 { x: p[0], y: p[1], z: p[2] }));
+
+            let primitive_normals = document.accessor(&attributes["NORMAL"], 3)?.filter(|values| values.len() == count * 3);
+            has_normals |= primitive_normals.is_some();
+            match primitive_normals {
+                Some(values) => normals.extend(values.chunks_exact(3).map(|n| RwVector3 { x: n[0], y: n[1], z: n[2] })),
+                None => normals.resize(vertices.len(), RwVector3 { x: 0.0, y: 0.0, z: 1.0 }),
+            }
+
+            let primitive_uvs = document.accessor(&attributes["TEXCOORD_0"], 2)?.filter(|values| values.len() == count * 2);
+            has_uvs |= primitive_uvs.is_some();
+            match primitive_uvs {
+                Some(values) => uvs.extend(values.chunks_exact(2).map(|uv| RwTextureCoordinate {
+                    u: uv[0],
+                    v: if self.flip_v { 1.0 - uv[1] } else { uv[1] },
+                })),
+                None => uvs.resize(vertices.len(), RwTextureCoordinate { u: 0.0, v: 0.0 }),
+            }
+
+            let components = document.accessor_components(&attributes["COLOR_0"]);
+            let primitive_colors = document.accessor(&attributes["COLOR_0"], components)?.filter(|values| values.len() == count * components);
+            has_colors |= primitive_colors.is_some();
+            match primitive_colors {
+                Some(values) => colors.extend(values.chunks_exact(components).map(|c| {
+                    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    RwColor { r: channel(c[0]), g: channel(c[1]), b: channel(c[2]), a: c.get(3).map_or(255, |&a| channel(a)) }
+                })),
+                None => colors.resize(vertices.len(), RwColor { r: 255, g: 255, b: 255, a: 255 }),
+            }
+
+            if let Some((_, bone_count)) = skin {
+                let primitive_joints = document.accessor(&attributes["JOINTS_0"], 4)?.filter(|values| values.len() == count * 4);
+                let primitive_weights = document.accessor(&attributes["WEIGHTS_0"], 4)?.filter(|values| values.len() == count * 4);
+                match (primitive_joints, primitive_weights) {
+                    (Some(primitive_joints), Some(primitive_weights)) => {
+                        for (vertex_joints, vertex_weights) in primitive_joints.chunks_exact(4).zip(primitive_weights.chunks_exact(4)) {
+                            let mut indices = [0u8; 4];
+                            let mut values = [0.0f32; 4];
+                            for slot in 0..4 {
+                                if vertex_weights[slot] <= 0.0 {
+                                    continue;
+                                }
+                                let joint = vertex_joints[slot] as usize;
+                                if joint >= bone_count {
+                                    return Err(invalid(format!("A vertex uses joint {}, but the skin has {} bones", joint, bone_count)));
+                                }
+                                indices[slot] = joint as u8;
+                                values[slot] = vertex_weights[slot];
+                            }
+                            joints.push(indices.to_vec());
+                            weights.push(values.to_vec());
+                        }
+                    }
+                    _ => {
+                        joints.resize(vertices.len(), vec![0; 4]);
+                        weights.resize(vertices.len(), vec![1.0, 0.0, 0.0, 0.0]);
+                    }
+                }
+            }
+
+            let material = match primitive["material"].as_u64() {
+                Some(index) => materials.get(index as usize).cloned().ok_or_else(|| invalid(format!("A primitive references missing material {}", index)))?,
+                None => default_material(),
+            };
+            let material_id = match geometry_materials.iter().position(|known| *known == material) {
+                Some(index) => index,
+                None => {
+                    geometry_materials.push(material);
+                    geometry_materials.len() - 1
+                }
+            } as u16;
+
+            let indices: Vec<usize> = match document.accessor(&primitive["indices"], 1)? {
+                Some(values) => values.into_iter().map(|index| index as usize).collect(),
+                None => (0..count).collect(),
+            };
+            if indices.iter().any(|&index| index >= count) {
+                return Err(invalid("A primitive has indices past its vertices".to_string()));
+            }
+            for [a, b, c] in triangulate(primitive["mode"].as_u64().unwrap_or(4), &indices)? {
+                triangles.push(RwTriangle {
+                    vector: RwVector3 { x: (base + a) as f32, y: (base + b) as f32, z: (base + c) as f32 },
+                    material_id,
+                });
+            }
+        }
+
+        if vertices.len() > u16::MAX as usize {
+            return Err(invalid(format!("A geometry has {} vertices, but at most 65535 are allowed", vertices.len())));
+        }
+
+        let skin = match skin {
+            Some((skin, bone_count)) => Some(self.skin(document, skin, bone_count, joints, weights)?),
+            None => None,
+        };
+        let bin_mesh = bin_mesh(&triangles, geometry_materials.len());
+        Ok(RwGeometry {
+            vertex_color_information: if has_colors { colors } else { Vec::new() },
+            texture_coordinates_count: has_uvs as u8,
+            texture_mapping_information: if has_uvs { vec![uvs] } else { Vec::new() },
+            has_vertices: true,
+            has_normals,
+            triangle_information: triangles,
+            bounding_sphere: Some(bounding_sphere(&vertices)),
+            normal_information: if has_normals { normals } else { Vec::new() },
+            vertex_information: vertices,
+            material_list: RwMaterialList { material_instance_count: geometry_materials.len() as u32, material_data: geometry_materials },
+            bin_mesh,
+            skin,
+            user_data: Vec::new(),
+        })
+    }
+
+    fn skin(&self, document: &Document, skin: Option<&Value>, bone_count: usize, joints: Vec<Vec<u8>>, weights: Vec<Vec<f32>>) -> RwResult<RwSkin> {
+        let matrices = match skin {
+            Some(skin) => document.accessor(&skin["inverseBindMatrices"], 16)?.unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let inverse_bone_matrices = (0..bone_count)
+            .map(|bone| match matrices.get(bone * 16..bone * 16 + 16) {
+                Some(m) => RwMatrix4 {
+                    right: RwVector4 { x: m[0], y: m[1], z: m[2], t: 0.0 },
+                    up: RwVector4 { x: m[4], y: m[5], z: m[6], t: 0.0 },
+                    at: RwVector4 { x: m[8], y: m[9], z: m[10], t: 0.0 },
+                    transform: RwVector4 { x: m[12], y: m[13], z: m[14], t: 1.0 },
+                },
+                None => RwMatrix4 {
+                    right: RwVector4 { x: 1.0, y: 0.0, z: 0.0, t: 0.0 },
+                    up: RwVector4 { x: 0.0, y: 1.0, z: 0.0, t: 0.0 },
+                    at: RwVector4 { x: 0.0, y: 0.0, z: 1.0, t: 0.0 },
+                    transform: RwVector4 { x: 0.0, y: 0.0, z: 0.0, t: 1.0 },
+                },
+            })
+            .collect();
+
+        let mut used = vec![false; bone_count];
+        let mut max_weights_per_vertex = 0;
+        for (vertex_joints, vertex_weights) in joints.iter().zip(&weights) {
+            let mut count = 0;
+            for (&joint, &weight) in vertex_joints.iter().zip(vertex_weights) {
+                if weight > 0.0 {
+                    let slot = used
+                        .get_mut(joint as usize)
+                        .ok_or_else(|| invalid(format!("A vertex uses joint {}, but the skin has {} bones", joint, bone_count)))?;
+                    *slot = true;
+                    count += 1;
+                }
+            }
+            max_weights_per_vertex = max_weights_per_vertex.max(count);
+        }
+
+        Ok(RwSkin {
+            bone_count: bone_count as u8,
+            used_bone_count: used.iter().filter(|&&used| used).count() as u8,
+            max_weights_per_vertex,
+            bone_vertex_indices: joints,
+            vertex_weights: weights,
+            inverse_bone_matrices,
+            used_bones: (0..bone_count).filter(|&bone| used[bone]).map(|bone| bone as u8).collect(),
+            split_data: None,
+        })
+    }
+}
+
+/// A parsed glTF document and its buffers.
+struct Document {
+    root: Value,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Document {
+    fn read(data: &[u8]) -> RwResult<Document> {
+        let (json, binary) = if data.starts_with(b"glTF") {
+            let mut json = None;
+            let mut binary = None;
+            let mut offset = 12;
+            while offset + 8 <= data.len() {
+                let length = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+                let chunk = data.get(offset + 8..offset + 8 + length).ok_or_else(|| invalid("A GLB chunk is truncated".to_string()))?;
+                match &data[offset + 4..offset + 8] {
+                    b"JSON" => json = json.or(Some(chunk)),
+                    b"BIN\0" => binary = binary.or(Some(chunk)),
+                    _ => {}
+                }
+                offset += 8 + length;
+            }
+            (json.ok_or_else(|| invalid("The GLB file has no JSON chunk".to_string()))?, binary)
+        } else {
+            (data, None)
+        };
+
+        let root: Value = serde_json::from_slice(json).map_err(|error| invalid(format!("Invalid glTF JSON: {}", error)))?;
+        if !root["asset"]["version"].as_str().is_some_and(|version| version.starts_with("2.")) {
+            return Err(RwParseError::UnsupportedVersion {
+                format: "glTF",
+                version: root["asset"]["version"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+
+        let buffers = root["buffers"]
+            .as_array()
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .enumerate()
+            .map(|(index, buffer)| match buffer["uri"].as_str() {
+                None if index == 0 => binary.map(<[u8]>::to_vec).ok_or_else(|| invalid("Buffer 0 has no data".to_string())),
+                Some(uri) if uri.starts_with("data:") => {
+                    let (_, encoded) = uri.split_once(";base64,").ok_or_else(|| invalid("A data URI is not base64".to_string()))?;
+                    decode_base64(encoded).ok_or_else(|| invalid("A data URI has invalid base64".to_string()))
+                }
+                Some(uri) => Err(invalid(format!("External buffer '{}' is not supported; embed it or convert to .glb", uri))),
+                None => Err(invalid(format!("Buffer {} has no data", index))),
+            })
+            .collect::<RwResult<_>>()?;
+
+        Ok(Document { root, buffers })
+    }
+
+    fn array(&self, key: &str) -> &[Value] {
+        self.root[key].as_array().map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the number of components of an accessor's elements.
+    fn accessor_components(&self, index: &Value) -> usize {
+        let accessor = index.as_u64().and_then(|index| self.array("accessors").get(index as usize));
+        accessor.map_or(4, |accessor| type_components(accessor["type"].as_str().unwrap_or_default()))
+    }
+
+    /// Reads an accessor as floats, scaling normalized integers into `0..=1`
+    /// or `-1..=1`, or returns `None` if `index` is not set.
+    fn accessor(&self, index: &Value, components: usize) -> RwResult<Option<Vec<f32>>> {
+        let Some(index) = index.as_u64() else {
+            return Ok(None);
+        };
+        let accessor = self.array("accessors").get(index as usize).ok_or_else(|| invalid(format!("Missing accessor {}", index)))?;
+        if accessor.get("sparse").is_some() {
+            return Err(invalid(format!("Accessor {} is sparse, which is not supported", index)));
+        }
+        let kind = accessor["type"].as_str().unwrap_or_default();
+        if type_components(kind) != components {
+            return Err(invalid(format!("Accessor {} has type {}, but {} components are needed", index, kind, components)));
+        }
+
+        let count = accessor["count"].as_u64().unwrap_or(0) as usize;
+        let component_type = accessor["componentType"].as_u64().unwrap_or(0);
+        let normalized = accessor["normalized"].as_bool().unwrap_or(false);
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(invalid(format!("Accessor {} has unknown component type {}", index, component_type))),
+        };
+        let Some(view_index) = accessor["bufferView"].as_u64() else {
+            // Without a buffer view nothing bounds the count, so zero-filled
+            // accessors are limited to keep a bogus count from allocating
+            if count > MAX_ZEROED_ELEMENTS {
+                return Err(invalid(format!("Accessor {} has {} elements but no buffer view", index, count)));
+            }
+            return Ok(Some(vec![0.0; count * components]));
+        };
+
+        let view = self.array("bufferViews").get(view_index as usize).ok_or_else(|| invalid(format!("Missing buffer view {}", view_index)))?;
+        let buffer = view["buffer"].as_u64().and_then(|buffer| self.buffers.get(buffer as usize));
+        let element_size = component_size * components;
+        let stride = view["byteStride"].as_u64().map_or(element_size, |stride| stride as usize);
+        if stride < element_size {
+            return Err(invalid(format!("Accessor {} has a stride of {} bytes, smaller than its elements", index, stride)));
+        }
+        let past_buffer = || invalid(format!("Accessor {} reads past its buffer", index));
+        let start = (view["byteOffset"].as_u64().unwrap_or(0) as usize)
+            .checked_add(accessor["byteOffset"].as_u64().unwrap_or(0) as usize)
+            .ok_or_else(past_buffer)?;
+        let end = match count.checked_sub(1) {
+            None => start,
+            Some(last) => stride
+                .checked_mul(last)
+                .and_then(|offset| offset.checked_add(start))
+                .and_then(|offset| offset.checked_add(element_size))
+                .ok_or_else(past_buffer)?,
+        };
+        let data = buffer.and_then(|buffer| buffer.get(start..end)).ok_or_else(past_buffer)?;
+
+        let mut values = Vec::with_capacity(count * components);
+        for element in 0..count {
+            for component in 0..components {
+                let at = element * stride + component * component_size;
+                let bytes = &data[at..at + component_size];
+                let value = match component_type {
+                    5120 => (bytes[0] as i8) as f32 / if normalized { 127.0 } else { 1.0 },
+                    5121 => bytes[0] as f32 / if normalized { 255.0 } else { 1.0 },
+                    5122 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / if normalized { 32767.0 } else { 1.0 },
+                    5123 => u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / if normalized { 65535.0 } else { 1.0 },
+                    5125 => u32::from_le_bytes(bytes.try_into().unwrap()) as f32,
+                    _ => f32::from_le_bytes(bytes.try_into().unwrap()),
+                };
+                values.push(if normalized { value.max(-1.0) } else { value });
+            }
+        }
+        Ok(Some(values))
+    }
+}
+
+fn invalid(message: String) -> RwParseError {
+    RwParseError::InconsistentData { offset: 0, message }
+}
+
+fn type_components(kind: &str) -> usize {
+    match kind {
+        "SCALAR" => 1,
+        "VEC2" => 2,
+        "VEC3" => 3,
+        "VEC4" | "MAT2" => 4,
+        "MAT3" => 9,
+        "MAT4" => 16,
+        _ => 0,
+    }
+}
+
+fn indices(value: &Value) -> Vec<usize> {
+    value.as_array().map_or_else(Vec::new, |values| values.iter().filter_map(Value::as_u64).map(|index| index as usize).collect())
+}
+
+fn floats(value: &Value) -> Vec<f32> {
+    value.as_array().map_or_else(Vec::new, |values| values.iter().filter_map(Value::as_f64).map(|value| value as f32).collect())
+}
+
+fn children(node: &Value) -> Vec<usize> {
+    indices(&node["children"])
+}
+
+/// Returns the rotation and translation of a node, with its scale baked into
+/// the rotation's axes.
+fn node_transform(node: &Value) -> (RwMatrix3, RwVector3) {
+    let matrix = floats(&node["matrix"]);
+    if matrix.len() == 16 {
+        let axis = |column: usize| RwVector3 { x: matrix[column * 4], y: matrix[column * 4 + 1], z: matrix[column * 4 + 2] };
+        return (RwMatrix3 { right: axis(0), up: axis(1), at: axis(2) }, axis(3));
+    }
+
+    let translation = floats(&node["translation"]);
+    let rotation = floats(&node["rotation"]);
+    let scale = floats(&node["scale"]);
+    let rotation = match rotation[..] {
+        [x, y, z, w] => RwQuaternion { x, y, z, w },
+        _ => RwQuaternion::IDENTITY,
+    };
+    let mut matrix = rotation.to_matrix3();
+    if let [x, y, z] = scale[..] {
+        let scaled = |axis: RwVector3, factor: f32| RwVector3 { x: axis.x * factor, y: axis.y * factor, z: axis.z * factor };
+        matrix = RwMatrix3 { right: scaled(matrix.right, x), up: scaled(matrix.up, y), at: scaled(matrix.at, z) };
+    }
+    let offset = match translation[..] {
+        [x, y, z] => RwVector3 { x, y, z },
+        _ => RwVector3 { x: 0.0, y: 0.0, z: 0.0 },
+    };
+    (matrix, offset)
+}
+
+/// Rotates a root frame by 90 degrees about X, which turns Y-up into Z-up.
+fn rotate_to_z_up(frame: &mut RwFrame) {
+    let rotate = |v: RwVector3| RwVector3 { x: v.x, y: -v.z, z: v.y };
+    let matrix = &mut frame.rotation_matrix;
+    *matrix = RwMatrix3 { right: rotate(matrix.right), up: rotate(matrix.up), at: rotate(matrix.at) };
+    frame.coordinates_offset = rotate(frame.coordinates_offset);
+}
+
+/// Returns the filtering, addressing and mipmap use of a glTF sampler.
+fn sampler_modes(sampler: &Value) -> (u8, u8, u8, bool) {
+    let texture_filtering = match (sampler["magFilter"].as_u64(), sampler["minFilter"].as_u64()) {
+        (_, Some(9984)) => 3,
+        (_, Some(9986)) => 4,
+        (_, Some(9985)) => 5,
+        (_, Some(9987)) => 6,
+        (Some(9728), _) | (_, Some(9728)) => 1,
+        _ => 2,
+    };
+    let addressing = |wrap: &Value| match wrap.as_u64() {
+        Some(33648) => 2,
+        Some(33071) => 3,
+        _ => 1,
+    };
+    (texture_filtering, addressing(&sampler["wrapS"]), addressing(&sampler["wrapT"]), texture_filtering >= 3)
+}
+
+fn default_material() -> RwMaterial {
+    RwMaterial {
+        color: RwColor { r: 255, g: 255, b: 255, a: 255 },
+        is_textured: false,
+        ambient: Some(1.0),
+        specular: Some(1.0),
+        diffuse: Some(1.0),
+        texture: None,
+        normal_map: None,
+        right_to_render: None,
+    }
+}
+
+/// Splits the indices of a primitive into triangles.
+fn triangulate(mode: u64, indices: &[usize]) -> RwResult<Vec<[usize; 3]>> {
+    let triangles = match mode {
+        4 => indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[1], triangle[2]]).collect(),
+        // Every other triangle of a strip is flipped to keep the winding
+        5 => (2..indices.len())
+            .map(|i| if i % 2 == 0 { [indices[i - 2], indices[i - 1], indices[i]] } else { [indices[i - 1], indices[i - 2], indices[i]] })
+            .collect(),
+        6 => (2..indices.len()).map(|i| [indices[0], indices[i - 1], indices[i]]).collect(),
+        _ => return Err(invalid(format!("Primitive mode {} has no triangles", mode))),
+    };
+    Ok(triangles)
+}
+
+fn bin_mesh(triangles: &[RwTriangle], material_count: usize) -> RwBinMesh {
+    let meshes: Vec<RwMesh> = (0..material_count)
+        .map(|material| {
+            let indices: Vec<u32> = triangles
+                .iter()
+                .filter(|triangle| triangle.material_id as usize == material)
+                .flat_map(|triangle| [triangle.vector.x as u32, triangle.vector.y as u32, triangle.vector.z as u32])
+                .collect();
+            RwMesh { material_index: material as u32, index_count: indices.len() as u32, indices }
+        })
+        .filter(|mesh| mesh.index_count > 0)
+        .collect();
+    RwBinMesh { mesh_count: meshes.len() as u32, meshes }
+}
+
+fn bounding_sphere(vertices: &[RwVector3]) -> RwSphere {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for vertex in vertices {
+        for (axis, value) in [vertex.x, vertex.y, vertex.z].into_iter().enumerate() {
+            min[axis] = min[axis].min(value);
+            max[axis] = max[axis].max(value);
+        }
+    }
+    if vertices.is_empty() {
+        return RwSphere { vector: RwVector3 { x: 0.0, y: 0.0, z: 0.0 }, radius: 0.0 };
+    }
+    let center = RwVector3 { x: (min[0] + max[0]) / 2.0, y: (min[1] + max[1]) / 2.0, z: (min[2] + max[2]) / 2.0 };
+    let radius = vertices
+        .iter()
+        .map(|v| ((v.x - center.x).powi(2) + (v.y - center.y).powi(2) + (v.z - center.z).powi(2)).sqrt())
+        .fold(0.0, f32::max);
+    RwSphere { vector: center, radius }
+}
+
+/// Builds the HAnim nodes of a skeleton. Every frame gets a node whose bone
+/// id is its index, so that the nodes line up with the frames as the parser
+/// expects, and the first frame lists the bones in the order of the joints.
+fn hanim_hierarchy(frames: &[RwFrame], joint_frames: &[usize]) -> Vec<RwAnimNode> {
+    let is_joint = |frame: usize| joint_frames.contains(&frame);
+    let joint_parent = |frame: usize| {
+        let mut parent = frames[frame].parent_frame;
+        while parent >= 0 && !is_joint(parent as usize) {
+            parent = frames[parent as usize].parent_frame;
+        }
+        parent
+    };
+
+    let bones = joint_frames
+        .iter()
+        .enumerate()
+        .map(|(bone_index, &frame)| {
+            let parent = joint_parent(frame);
+            let has_children = joint_frames.iter().any(|&other| joint_parent(other) == frame as i32);
+            let has_next_sibling = joint_frames[bone_index + 1..].iter().any(|&other| joint_parent(other) == parent);
+            let mut flags = 0;
+            if !has_children {
+                flags |= POP_PARENT_MATRIX;
+            }
+            if has_next_sibling {
+                flags |= PUSH_PARENT_MATRIX;
+            }
+            RwBone { bone_id: frame as i32, bone_index: bone_index as i32, flags }
+        })
+        .collect::<Vec<_>>();
+
+    (0..frames.len())
+        .map(|frame| {
+            let bones = if frame == 0 { bones.clone() } else { Vec::new() };
+            RwAnimNode { bone_id: frame as i32, bones_count: bones.len() as i32, bones }
+        })
+        .collect()
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    for byte in encoded.bytes().filter(|&byte| byte != b'=') {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        accumulator = accumulator << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((accumulator >> bits) as u8);
+        }
+    }
+    Some(output)
+}
@@ -1,19 +1,93 @@
 pub mod renderware;
 pub mod utils;
+pub mod batch;
+pub mod export;
+pub mod import;
+pub mod scene;
+pub mod detect;
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
 
-pub use utils::byte_stream::ByteStream;
-pub use renderware::rw_file::{RwFile, RwSectionHeader};
-pub use renderware::rw_sections::RwSections;
+pub use detect::{RwAsset, RwFileKind, detect};
+pub use utils::byte_stream::{ByteStream, ByteWriter, Endianness};
+pub use utils::rw_version::{RwGame, RwRelease, RwVersion};
+#[cfg(feature = "mmap")]
+pub use utils::mapped_file::MappedFile;
+pub use renderware::rw_file::{RwFile, RwFileWriter, RwSectionHeader};
+pub use renderware::rw_error::{RwParseError, RwResult};
+pub use renderware::rw_report::{ParseMode, ParseReport, ParseWarning, ParseWarningKind};
+pub use renderware::rw_source_map::{SourceEntry, SourceItem, SourceMap, SourceSpan};
+pub use renderware::rw_sections::{RwSectionId, RwSections, RwVendor};
+pub use renderware::rw_chunk_tree::{RwChunkTree, RwChunk};
+pub use renderware::rw_chunk_extractor::{RwChunkExtractor, SectionPath, SectionSelector, SectionStep};
+pub use renderware::rw_visitor::{SectionVisitor, SectionContext, VisitAction};
 
 pub use renderware::dff::dff_parser::{
     DffParser, RwDff, RwClump, RwAnimNode, RwBone, RwFrame, RwFrameList, RwTexture, RwMaterial,
-    RwMaterialList, RwGeometry, RwGeometryList, RwAtomic, RwBinMesh, RwSkin, RwMesh,
+    RwMaterialList, RwGeometry, RwGeometryList, RwAtomic, RwBinMesh, RwSkin, RwMesh, RwNormalMap,
+    RwRightToRender, RwPipeline, RwSkinSplitData, RwSkinRunRange, RwSkinBoneRun, UserDataEntry, UserDataValues,
 };
 pub use renderware::dff::dff_model_type::DffModelType;
+pub use renderware::dff::dff_flat::{ParserScratch, RwFlatDff, RwFlatGeometry};
+pub use renderware::dff::dff_material::{AlphaMode, MaterialHints};
+pub use renderware::dff::dff_validate::{TextureIssue, TextureIssueKind};
 
-pub use renderware::txd::txd_parser::{TxdParser, RwTxd, RwTextureDictionary, RwTextureNative};
+pub use renderware::txd::txd_parser::{
+    TxdParser, RwTxd, RwTextureDictionary, RwTextureNative, RwRasterEncoding, RwRasterFormatInfo, RwRasterView,
+    RwTxdView,
+};
+pub use renderware::txd::txd_writer::{TxdTextureFormat, TxdWriter};
+pub use renderware::txd::txd_merge::{ConflictPolicy, TextureChange, TxdDiff};
+pub use renderware::txd::txd_audit::{AlphaAuditIssue, AlphaIssueKind};
+pub use renderware::txd::txd_stats::{FormatStats, TextureSize, TxdStats};
+pub use renderware::txd::txd_atlas::{AtlasPacker, AtlasRegion, TextureAtlas};
 pub use renderware::utils::image_format_enums::{D3dFormat, PaletteType, PlatformType, RasterFormat};
 
 pub use renderware::ifp::ifp_parser::{
-    IfpParser, IfpVersion, RwIfp, RwIfpAnimation, RwIfpBone, RwIfpKeyframe,
+    IfpParser, IfpVersion, RwIfp, RwIfpAnimation, RwIfpBone, RwIfpKeyframe, AnpkAnimationInfo, AnpkBoneInfo,
+    IfpTimeBase, IfpIndex, IfpIndexEntry, KeyframeType,
+};
+pub use renderware::ifp::ifp_sample::BonePose;
+pub use renderware::ifp::ifp_retarget::{LocalTransform, RetargetedAnimation, RetargetedTrack};
+
+pub use renderware::col::col_parser::{
+    ColParser, ColVersion, RwCol, ColModel, ColBounds, ColSurface, ColSphere, ColBox, ColFace, ColFaceGroup,
+};
+pub use renderware::col::col_writer::ColWriter;
+
+pub use renderware::img::img_archive::{ImgArchive, ImgEntry, ImgVersion};
+pub use renderware::img::img_writer::ImgWriter;
+
+pub use renderware::ipl::ipl_parser::{IplParser, RwIpl, IplInstance, IplZone, IplOccluder, IplPickup, IplRawEntry};
+pub use renderware::ide::ide_parser::{
+    IdeParser, RwIde, IdeObject, IdeObjectKind, IdePed, IdeVehicle, IdeTextureParent, IdeRawEntry,
 };
+
+pub use renderware::gxt::gxt_parser::{GxtParser, RwGxt, GxtVersion, GxtTable, GxtEntry};
+
+pub use renderware::world::world_parser::{WorldParser, RwWorld, RwWorldSector, RwPlaneSector, RwAtomicSector};
+
+pub use renderware::anm::anm_parser::{AnmParser, RwAnm, RwAnmTrack, AnmKeyframeFormat};
+
+pub use renderware::dat::gta_dat::{GtaDatParser, RwGtaDat, GtaDatEntry, GtaDatEntryKind};
+pub use renderware::dat::water_dat::{WaterDatParser, RwWaterDat, WaterPolygon, WaterVertex, WaterRectangle};
+pub use renderware::dat::timecyc_dat::{TimecycParser, RwTimecyc, TimecycEntry, TimecycVersion};
+pub use renderware::dat::handling_cfg::{HandlingParser, RwHandling, HandlingEntry, HandlingExtraEntry, HandlingVersion};
+pub use renderware::dat::carcols_dat::{CarcolsParser, RwCarcols, CarColorAssignment, CarColorCombination};
+
+pub use renderware::fxp::fxp_parser::{
+    FxpParser, RwFxp, FxSystem, FxPrimitive, FxInfo, FxInterpolator, FxKeyframe, FxProperty,
+};
+
+pub use renderware::rrr::rrr_parser::{RrrParser, RwRrr, RrrFrame};
+pub use renderware::rrr::rrr_writer::RrrWriter;
+
+pub use renderware::nodes::nodes_parser::{NodesParser, RwNodes, PathNode, NaviNode, PathLink};
+
+pub use renderware::assets::asset_database::{AssetDatabase, ModelDefinition, ModelKind, ResolvedAsset};
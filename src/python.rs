@@ -0,0 +1,123 @@
+//! # Python Bindings
+//!
+//! `pyo3` classes for parsing RenderWare files from Python and Blender
+//! scripts. Requires the `python` feature; build the extension module with
+//! `maturin build --features python,pyo3/extension-module`.
+//!
+//! `DffParser`, `TxdParser` and `IfpParser` take the file bytes, and their
+//! `parse` method returns the parsed structure as nested dictionaries and
+//! lists with the same keys as the JSON output. Files are parsed with
+//! `parse_untrusted`, and parse errors are raised as `ValueError`.
+//!
+//! ## Example
+//!
+//! ```python
+//! from rw_parser_rs import DffParser
+//!
+//! dff = DffParser.from_file("infernus.dff").parse()
+//! print(len(dff["geometry_list"]["geometries"]))
+//! ```
+
+use crate::renderware::dff::dff_parser::DffParser;
+use crate::renderware::ifp::ifp_parser::IfpParser;
+use crate::renderware::rw_error::{RwResult, catch_panic};
+use crate::renderware::txd::txd_parser::TxdParser;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Converts a parse result into Python objects by way of JSON, which keeps
+/// the dictionaries identical to the JSON output.
+fn to_python<'py, T: Serialize>(py: Python<'py>, result: RwResult<T>) -> PyResult<Bound<'py, PyAny>> {
+    let value = result.map_err(|error| PyValueError::new_err(error.to_string()))?;
+    let json = serde_json::to_string(&value).map_err(|error| PyValueError::new_err(error.to_string()))?;
+    PyModule::import(py, "json")?.call_method1("loads", (json,))
+}
+
+fn read_file(path: PathBuf) -> PyResult<Vec<u8>> {
+    std::fs::read(path).map_err(|error| PyIOError::new_err(error.to_string()))
+}
+
+/// Parses DFF models.
+#[pyclass(name = "DffParser")]
+pub struct PyDffParser {
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl PyDffParser {
+    #[new]
+    fn new(data: &[u8]) -> Self {
+        PyDffParser { data: data.to_vec() }
+    }
+
+    #[staticmethod]
+    fn from_file(path: PathBuf) -> PyResult<Self> {
+        Ok(PyDffParser { data: read_file(path)? })
+    }
+
+    fn parse<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_python(py, DffParser::parse_untrusted(&self.data))
+    }
+}
+
+/// Parses TXD texture dictionaries, decoding every texture to RGBA.
+#[pyclass(name = "TxdParser")]
+pub struct PyTxdParser {
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl PyTxdParser {
+    #[new]
+    fn new(data: &[u8]) -> Self {
+        PyTxdParser { data: data.to_vec() }
+    }
+
+    #[staticmethod]
+    fn from_file(path: PathBuf) -> PyResult<Self> {
+        Ok(PyTxdParser { data: read_file(path)? })
+    }
+
+    fn parse<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_python(py, TxdParser::parse_untrusted(&self.data))
+    }
+
+    /// Parses the dictionary without decoding pixel data, which is much
+    /// faster when only names and formats are needed.
+    fn parse_metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_python(py, catch_panic(|| TxdParser::new(&self.data).parse_metadata()))
+    }
+}
+
+/// Parses IFP animation packages.
+#[pyclass(name = "IfpParser")]
+pub struct PyIfpParser {
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl PyIfpParser {
+    #[new]
+    fn new(data: &[u8]) -> Self {
+        PyIfpParser { data: data.to_vec() }
+    }
+
+    #[staticmethod]
+    fn from_file(path: PathBuf) -> PyResult<Self> {
+        Ok(PyIfpParser { data: read_file(path)? })
+    }
+
+    fn parse<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_python(py, IfpParser::parse_untrusted(&self.data))
+    }
+}
+
+#[pymodule]
+fn rw_parser_rs(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyDffParser>()?;
+    module.add_class::<PyTxdParser>()?;
+    module.add_class::<PyIfpParser>()?;
+    Ok(())
+}
@@ -0,0 +1,279 @@
+//! # ANM Parser
+//!
+//! A parser for standalone RenderWare animation files (`.anm`), which hold a
+//! single hierarchical animation (`RpHAnimAnimation`) in an animation chunk.
+//! They are used for cutscenes and by other RenderWare games.
+//!
+//! ## Features
+//!
+//! - Parses standard keyframes (rotation and translation as floats).
+//! - Decodes compressed keyframes, whose components are packed into 16-bit
+//!   floats and whose translations are scaled by the animation's custom data.
+//! - Splits the interleaved keyframes into one track per bone and converts
+//!   them into IFP animations for use with the IFP tools.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::anm::anm_parser::AnmParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/cutscene.anm").unwrap();
+//! let anm_data = AnmParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Duration: {}s, bones: {}", anm_data.duration, anm_data.bones.len());
+//! ```
+
+use crate::renderware::common::types::{RwQuaternion, RwVector3};
+use crate::renderware::ifp::ifp_parser::{IfpTimeBase, KeyframeType, RwIfpAnimation, RwIfpBone, RwIfpKeyframe};
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_file::RwFile;
+use crate::renderware::rw_sections::RwSections;
+use crate::utils::rw_version::{unpack_version, version_string};
+
+use serde::Serialize;
+
+/// The keyframe scheme of an animation, as registered with `RtAnim`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AnmKeyframeFormat {
+    /// `RpHAnimKeyFrame`: a time, a float quaternion and a float translation.
+    Standard,
+    /// `RpHAnimCompressedKeyFrame`: a time and 16-bit float components.
+    Compressed,
+}
+
+impl AnmKeyframeFormat {
+    pub fn from_type_id(type_id: u32) -> Option<Self> {
+        match type_id {
+            1 => Some(AnmKeyframeFormat::Standard),
+            2 => Some(AnmKeyframeFormat::Compressed),
+            _ => None,
+        }
+    }
+
+    pub fn type_id(self) -> u32 {
+        match self {
+            AnmKeyframeFormat::Standard => 1,
+            AnmKeyframeFormat::Compressed => 2,
+        }
+    }
+
+    /// The size of a keyframe in memory, which the links between keyframes
+    /// are stored as multiples of.
+    fn keyframe_size(self) -> usize {
+        match self {
+            AnmKeyframeFormat::Standard => 0x24,
+            AnmKeyframeFormat::Compressed => 0x18,
+        }
+    }
+}
+
+/// Represents a parsed ANM file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwAnm {
+    pub version: String,
+    pub version_number: u32,
+    /// The version of the animation data, `0x100` in known files.
+    pub animation_version: u32,
+    pub keyframe_format: AnmKeyframeFormat,
+    pub flags: u32,
+    /// The length of the animation, in seconds.
+    pub duration: f32,
+    /// The keyframes of each bone, in the order of the nodes of the
+    /// `RpHAnimHierarchy` the animation is played on.
+    pub bones: Vec<RwAnmTrack>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwAnmTrack {
+    /// The keyframes of the bone, with times in seconds. Scales are always
+    /// one, as HAnim keyframes do not store them.
+    pub keyframes: Vec<RwIfpKeyframe>,
+}
+
+impl RwAnm {
+    /// Converts the animation into an IFP animation with rotation and
+    /// translation keyframes. Bones are unnamed and identified by their node
+    /// index in `bone_id`.
+    pub fn to_ifp_animation(&self, name: &str) -> RwIfpAnimation {
+        let bones = self
+            .bones
+            .iter()
+            .enumerate()
+            .map(|(index, track)| RwIfpBone {
+                name: String::new(),
+                keyframe_type: KeyframeType::KRT0,
+                use_bone_id: true,
+                bone_id: index as i32,
+                keyframes: track.keyframes.clone(),
+                anpk_info: None,
+            })
+            .collect();
+
+        RwIfpAnimation {
+            name: name.to_string(),
+            bones,
+            anpk_info: None,
+            time_base: IfpTimeBase::Seconds,
+            original_time_base: IfpTimeBase::Seconds,
+        }
+    }
+}
+
+/// Decodes a 16-bit float of compressed keyframes: a sign bit, a 4-bit
+/// exponent and an 11-bit mantissa.
+pub fn decompress_anm_float(value: u16) -> f32 {
+    let value = value as u32;
+    let sign = (value & 0x8000) << 16;
+    if value & 0x7FFF == 0 {
+        return f32::from_bits(sign);
+    }
+    f32::from_bits(sign | (((value & 0x7800) << 12) + 0x38000000) | ((value & 0x07FF) << 12))
+}
+
+/// A keyframe as stored in the file, before it is assigned to a bone.
+struct StoredKeyframe {
+    time: f32,
+    rotation: RwQuaternion,
+    translation: RwVector3,
+    previous: i32,
+}
+
+/// The main parser for ANM files.
+pub struct AnmParser<'a> {
+    file: RwFile<'a>,
+}
+
+impl<'a> AnmParser<'a> {
+    /// Creates a new `AnmParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw ANM file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        AnmParser {
+            file: RwFile::new(buffer),
+        }
+    }
+
+    /// Parses the animation chunk at the start of the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwAnm` data or a `RwParseError`
+    /// if the file is not an animation or uses an unknown keyframe scheme.
+    pub fn parse(&mut self) -> RwResult<RwAnm> {
+        let header = self.file.expect_section(RwSections::RwAnimAnimation)?;
+        let version_number = unpack_version(header.version_number);
+        let version = version_string(version_number).unwrap_or_default().to_string();
+
+        let stream = self.file.get_stream();
+        let animation_version = stream.read_u32()?;
+        let type_offset = stream.get_position();
+        let type_id = stream.read_u32()?;
+        let keyframe_count = stream.read_u32()?;
+        let flags = stream.read_u32()?;
+        let duration = stream.read_f32()?;
+
+        let keyframe_format = AnmKeyframeFormat::from_type_id(type_id).ok_or_else(|| RwParseError::InconsistentData {
+            offset: type_offset,
+            message: format!("Unsupported animation keyframe type {}", type_id),
+        })?;
+
+        let keyframes = match keyframe_format {
+            AnmKeyframeFormat::Standard => self.read_standard_keyframes(keyframe_count)?,
+            AnmKeyframeFormat::Compressed => self.read_compressed_keyframes(keyframe_count)?,
+        };
+
+        Ok(RwAnm {
+            version,
+            version_number,
+            animation_version,
+            keyframe_format,
+            flags,
+            duration,
+            bones: split_tracks(keyframes, keyframe_format.keyframe_size()),
+        })
+    }
+
+    fn read_standard_keyframes(&mut self, keyframe_count: u32) -> RwResult<Vec<StoredKeyframe>> {
+        let mut keyframes = Vec::with_capacity(self.file.checked_capacity("keyframes", keyframe_count as u64, 36)?);
+        let stream = self.file.get_stream();
+
+        for _ in 0..keyframe_count {
+            let time = stream.read_f32()?;
+            let rotation = RwQuaternion { x: stream.read_f32()?, y: stream.read_f32()?, z: stream.read_f32()?, w: stream.read_f32()? };
+            let translation = RwVector3 { x: stream.read_f32()?, y: stream.read_f32()?, z: stream.read_f32()? };
+            let previous = stream.read_i32()?;
+            keyframes.push(StoredKeyframe { time, rotation, translation, previous });
+        }
+
+        Ok(keyframes)
+    }
+
+    fn read_compressed_keyframes(&mut self, keyframe_count: u32) -> RwResult<Vec<StoredKeyframe>> {
+        let mut keyframes = Vec::with_capacity(self.file.checked_capacity("keyframes", keyframe_count as u64, 22)?);
+        let stream = self.file.get_stream();
+
+        for _ in 0..keyframe_count {
+            let time = stream.read_f32()?;
+            let mut components = [0.0; 7];
+            for component in &mut components {
+                *component = decompress_anm_float(stream.read_u16()?);
+            }
+            let previous = stream.read_i32()?;
+            keyframes.push(StoredKeyframe {
+                time,
+                rotation: RwQuaternion { x: components[0], y: components[1], z: components[2], w: components[3] },
+                translation: RwVector3 { x: components[4], y: components[5], z: components[6] },
+                previous,
+            });
+        }
+
+        // The custom data maps the packed translations back to their range
+        let offset = RwVector3 { x: stream.read_f32()?, y: stream.read_f32()?, z: stream.read_f32()? };
+        let scalar = RwVector3 { x: stream.read_f32()?, y: stream.read_f32()?, z: stream.read_f32()? };
+        for keyframe in &mut keyframes {
+            let translation = &mut keyframe.translation;
+            translation.x = translation.x * scalar.x + offset.x;
+            translation.y = translation.y * scalar.y + offset.y;
+            translation.z = translation.z * scalar.z + offset.z;
+        }
+
+        Ok(keyframes)
+    }
+}
+
+/// Groups the keyframes into bone tracks. The file interleaves the bones,
+/// starting with two keyframes for each of them, and links every keyframe to
+/// the previous keyframe of its bone by its byte offset in the keyframe array.
+fn split_tracks(keyframes: Vec<StoredKeyframe>, keyframe_size: usize) -> Vec<RwAnmTrack> {
+    let mut tracks: Vec<RwAnmTrack> = Vec::new();
+    let mut track_of: Vec<usize> = Vec::with_capacity(keyframes.len());
+
+    for (index, keyframe) in keyframes.iter().enumerate() {
+        // The first keyframe of a bone has no valid link, and times always
+        // increase along a track
+        let previous = usize::try_from(keyframe.previous)
+            .ok()
+            .map(|offset| offset / keyframe_size)
+            .filter(|&previous| previous < index && keyframes[previous].time < keyframe.time);
+
+        let track = match previous {
+            Some(previous) => track_of[previous],
+            None => {
+                tracks.push(RwAnmTrack { keyframes: Vec::new() });
+                tracks.len() - 1
+            }
+        };
+        track_of.push(track);
+        tracks[track].keyframes.push(RwIfpKeyframe {
+            time: keyframe.time,
+            position: keyframe.translation,
+            rotation: keyframe.rotation,
+            scale: RwVector3 { x: 1.0, y: 1.0, z: 1.0 },
+        });
+    }
+
+    tracks
+}
@@ -0,0 +1 @@
+pub mod anm_parser;
\ No newline at end of file
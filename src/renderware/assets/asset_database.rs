@@ -0,0 +1,222 @@
+//! # Asset Database
+//!
+//! An index over the definition, placement and archive files of a game,
+//! which resolves a model name to its parsed model, textures and placements.
+//!
+//! ## Features
+//!
+//! - Ingests IDE and IPL files and any number of IMG archives.
+//! - Looks up model definitions by name or id.
+//! - Loads and parses the DFF and TXD of a model from the archives, and
+//!   follows the texture dictionary parents declared by `txdp`.
+//! - Lists the placements of a model across all IPL files, including binary
+//!   IPL files that only store model ids.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::assets::asset_database::AssetDatabase;
+//! use rw_parser_rs::renderware::ide::ide_parser::IdeParser;
+//! use rw_parser_rs::renderware::img::img_archive::ImgArchive;
+//! use std::fs;
+//!
+//! let mut database = AssetDatabase::new();
+//! database.add_ide(IdeParser::new(&fs::read("path/to/your/vegepart.ide").unwrap()).parse().unwrap());
+//! database.add_archive(ImgArchive::open_path("path/to/your/gta3.img").unwrap());
+//!
+//! let asset = database.resolve("sm_veg_tree3").unwrap().unwrap();
+//! println!("Placements: {}", asset.placements.len());
+//! ```
+
+use crate::renderware::dff::dff_parser::{DffParser, RwDff};
+use crate::renderware::ide::ide_parser::{IdeObjectKind, RwIde};
+use crate::renderware::img::img_archive::ImgArchive;
+use crate::renderware::ipl::ipl_parser::{IplInstance, RwIpl};
+use crate::renderware::txd::txd_parser::{RwTxd, TxdParser};
+use std::io::{Read, Result, Seek};
+
+use serde::Serialize;
+
+/// The section a model was defined in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ModelKind {
+    Object(IdeObjectKind),
+    Ped,
+    Vehicle,
+}
+
+/// The definition of a model, from any IDE section.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelDefinition {
+    pub kind: ModelKind,
+    pub id: i32,
+    pub model_name: String,
+    pub txd_name: String,
+}
+
+impl ModelDefinition {
+    /// Returns the definitions of every object, ped and vehicle of an IDE
+    /// file.
+    pub fn from_ide(ide: &RwIde) -> Vec<ModelDefinition> {
+        let objects = ide.objects.iter().map(|object| ModelDefinition {
+            kind: ModelKind::Object(object.kind),
+            id: object.id,
+            model_name: object.model_name.clone(),
+            txd_name: object.txd_name.clone(),
+        });
+        let peds = ide.peds.iter().map(|ped| ModelDefinition {
+            kind: ModelKind::Ped,
+            id: ped.id,
+            model_name: ped.model_name.clone(),
+            txd_name: ped.txd_name.clone(),
+        });
+        let vehicles = ide.vehicles.iter().map(|vehicle| ModelDefinition {
+            kind: ModelKind::Vehicle,
+            id: vehicle.id,
+            model_name: vehicle.model_name.clone(),
+            txd_name: vehicle.txd_name.clone(),
+        });
+        objects.chain(peds).chain(vehicles).collect()
+    }
+}
+
+/// A model with its parsed files and placements.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedAsset {
+    pub definition: ModelDefinition,
+    /// The parsed model, if an archive contains it.
+    pub dff: Option<RwDff>,
+    /// The parsed texture dictionary, if an archive contains it.
+    pub txd: Option<RwTxd>,
+    pub placements: Vec<IplInstance>,
+}
+
+/// An index over IDE, IPL and IMG files.
+pub struct AssetDatabase<R> {
+    definitions: Vec<ModelDefinition>,
+    ides: Vec<RwIde>,
+    ipls: Vec<RwIpl>,
+    archives: Vec<ImgArchive<R>>,
+}
+
+impl<R> Default for AssetDatabase<R> {
+    fn default() -> Self {
+        AssetDatabase {
+            definitions: Vec::new(),
+            ides: Vec::new(),
+            ipls: Vec::new(),
+            archives: Vec::new(),
+        }
+    }
+}
+
+impl<R> AssetDatabase<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the definitions of an IDE file. Later definitions of the same
+    /// model name take precedence.
+    pub fn add_ide(&mut self, ide: RwIde) {
+        self.definitions.extend(ModelDefinition::from_ide(&ide));
+        self.ides.push(ide);
+    }
+
+    pub fn add_ipl(&mut self, ipl: RwIpl) {
+        self.ipls.push(ipl);
+    }
+
+    /// Adds an archive. Files are looked up in the archives in the order
+    /// they were added.
+    pub fn add_archive(&mut self, archive: ImgArchive<R>) {
+        self.archives.push(archive);
+    }
+
+    pub fn definitions(&self) -> &[ModelDefinition] {
+        &self.definitions
+    }
+
+    /// Returns the definition of a model, compared case-insensitively.
+    pub fn model(&self, model_name: &str) -> Option<&ModelDefinition> {
+        self.definitions.iter().rev().find(|definition| definition.model_name.eq_ignore_ascii_case(model_name))
+    }
+
+    pub fn model_by_id(&self, id: i32) -> Option<&ModelDefinition> {
+        self.definitions.iter().rev().find(|definition| definition.id == id)
+    }
+
+    /// Returns the texture dictionary of a model followed by its `txdp`
+    /// ancestors, from the nearest to the farthest.
+    pub fn txd_chain(&self, model_name: &str) -> Vec<String> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut next = self.model(model_name).map(|definition| definition.txd_name.clone());
+
+        while let Some(txd_name) = next.take() {
+            // Guard against parent cycles
+            if chain.iter().any(|existing| existing.eq_ignore_ascii_case(&txd_name)) {
+                break;
+            }
+            next = self.ides.iter().rev().find_map(|ide| ide.txd_parent(&txd_name)).map(|parent| parent.to_string());
+            chain.push(txd_name);
+        }
+
+        chain
+    }
+
+    /// Returns every placement of a model. Instances are matched by id, or
+    /// by name for text IPL files placing models this database does not
+    /// define.
+    pub fn placements(&self, model_name: &str) -> Vec<&IplInstance> {
+        let id = self.model(model_name).map(|definition| definition.id);
+        self.ipls
+            .iter()
+            .flat_map(|ipl| &ipl.instances)
+            .filter(|instance| match id {
+                Some(id) => instance.id == id,
+                None => instance.model_name.as_ref().is_some_and(|name| name.eq_ignore_ascii_case(model_name)),
+            })
+            .collect()
+    }
+}
+
+impl<R: Read + Seek> AssetDatabase<R> {
+    /// Reads a file from the first archive containing it.
+    pub fn read_file(&mut self, file_name: &str) -> Result<Option<Vec<u8>>> {
+        for archive in &mut self.archives {
+            if let Some(data) = archive.read(file_name)? {
+                return Ok(Some(data));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Loads and parses the DFF of a model.
+    pub fn read_dff(&mut self, model_name: &str) -> Result<Option<RwDff>> {
+        match self.read_file(&format!("{}.dff", model_name))? {
+            Some(data) => Ok(Some(DffParser::new(&data).parse()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads and parses a texture dictionary by name.
+    pub fn read_txd(&mut self, txd_name: &str) -> Result<Option<RwTxd>> {
+        match self.read_file(&format!("{}.txd", txd_name))? {
+            Some(data) => Ok(Some(TxdParser::new(&data).parse()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolves a model to its definition, parsed DFF and TXD, and
+    /// placements. Returns `None` if no IDE file defines the model.
+    pub fn resolve(&mut self, model_name: &str) -> Result<Option<ResolvedAsset>> {
+        let Some(definition) = self.model(model_name).cloned() else {
+            return Ok(None);
+        };
+
+        let dff = self.read_dff(&definition.model_name)?;
+        let txd = self.read_txd(&definition.txd_name)?;
+        let placements = self.placements(&definition.model_name).into_iter().cloned().collect();
+
+        Ok(Some(ResolvedAsset { definition, dff, txd, placements }))
+    }
+}
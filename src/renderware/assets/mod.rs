@@ -0,0 +1 @@
+pub mod asset_database;
\ No newline at end of file
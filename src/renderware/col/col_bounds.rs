@@ -0,0 +1,159 @@
+//! Bounding volume helpers for collision models, used to keep bounds and
+//! face groups consistent after editing a model's primitives.
+
+use super::col_parser::{ColBounds, ColFace, ColFaceGroup, ColModel, COL_FLAG_FACE_GROUPS};
+use crate::renderware::common::types::RwVector3;
+
+/// A typical `max_faces` for `ColModel::rebuild_face_groups`.
+pub const DEFAULT_FACES_PER_GROUP: usize = 50;
+
+impl ColBounds {
+    /// Returns empty bounds centered on the origin.
+    pub fn zero() -> Self {
+        let origin = RwVector3 { x: 0.0, y: 0.0, z: 0.0 };
+        ColBounds { min: origin, max: origin, center: origin, radius: 0.0 }
+    }
+}
+
+impl ColModel {
+    /// Recomputes the bounding box and sphere from the model's spheres,
+    /// boxes and mesh vertices. The sphere is centered on the box.
+    pub fn recompute_bounds(&mut self) {
+        let mut points = Vec::new();
+        for sphere in &self.spheres {
+            let r = sphere.radius;
+            points.push(offset(sphere.center, -r, -r, -r));
+            points.push(offset(sphere.center, r, r, r));
+        }
+        for col_box in &self.boxes {
+            points.push(col_box.min);
+            points.push(col_box.max);
+        }
+        points.extend(mesh_vertices(&self.vertices, &self.faces));
+
+        let Some((min, max)) = aabb(points.iter().copied()) else {
+            self.bounds = ColBounds::zero();
+            return;
+        };
+        let center = RwVector3 { x: (min.x + max.x) * 0.5, y: (min.y + max.y) * 0.5, z: (min.z + max.z) * 0.5 };
+
+        let mut radius: f32 = 0.0;
+        for sphere in &self.spheres {
+            radius = radius.max(distance(center, sphere.center) + sphere.radius);
+        }
+        for col_box in &self.boxes {
+            for corner in box_corners(col_box.min, col_box.max) {
+                radius = radius.max(distance(center, corner));
+            }
+        }
+        for vertex in mesh_vertices(&self.vertices, &self.faces) {
+            radius = radius.max(distance(center, vertex));
+        }
+
+        self.bounds = ColBounds { min, max, center, radius };
+    }
+
+    /// Rebuilds the face groups by splitting the mesh along its longest axis
+    /// until each group holds at most `max_faces` faces. Faces are reordered
+    /// so every group covers a contiguous range.
+    ///
+    /// Meshes with `max_faces` faces or fewer get no face groups, like the
+    /// original game files.
+    pub fn rebuild_face_groups(&mut self, max_faces: usize) {
+        self.face_groups.clear();
+        self.flags &= !COL_FLAG_FACE_GROUPS;
+        if self.faces.len() <= max_faces.max(1) {
+            return;
+        }
+
+        let vertices = &self.vertices;
+        let mut ordered = Vec::with_capacity(self.faces.len());
+        let mut ranges = Vec::new();
+        split_faces(vertices, self.faces.clone(), max_faces.max(1), &mut ordered, &mut ranges);
+
+        for (start, end) in ranges {
+            let points = ordered[start..end].iter().flat_map(|face| face_vertices(vertices, face));
+            let (min, max) = aabb(points).unwrap_or((ColBounds::zero().min, ColBounds::zero().max));
+            self.face_groups.push(ColFaceGroup { min, max, start_face: start as u16, end_face: (end - 1) as u16 });
+        }
+        self.faces = ordered;
+        self.flags |= COL_FLAG_FACE_GROUPS;
+    }
+}
+
+/// Recursively splits `faces` at the median centroid along the longest axis,
+/// appending each leaf to `ordered` and its range to `ranges`.
+fn split_faces(
+    vertices: &[RwVector3],
+    mut faces: Vec<ColFace>,
+    max_faces: usize,
+    ordered: &mut Vec<ColFace>,
+    ranges: &mut Vec<(usize, usize)>,
+) {
+    if faces.len() <= max_faces {
+        let start = ordered.len();
+        ordered.extend(faces);
+        ranges.push((start, ordered.len()));
+        return;
+    }
+
+    let centroids = faces.iter().map(|face| centroid(vertices, face));
+    let (min, max) = aabb(centroids).unwrap_or((ColBounds::zero().min, ColBounds::zero().max));
+    let extent = [max.x - min.x, max.y - min.y, max.z - min.z];
+    let axis = (0..3).max_by(|&a, &b| extent[a].total_cmp(&extent[b])).unwrap_or(0);
+
+    let key = |face: &ColFace| {
+        let c = centroid(vertices, face);
+        [c.x, c.y, c.z][axis]
+    };
+    faces.sort_by(|a, b| key(a).total_cmp(&key(b)));
+
+    let upper = faces.split_off(faces.len() / 2);
+    split_faces(vertices, faces, max_faces, ordered, ranges);
+    split_faces(vertices, upper, max_faces, ordered, ranges);
+}
+
+fn mesh_vertices<'a>(vertices: &'a [RwVector3], faces: &'a [ColFace]) -> impl Iterator<Item = RwVector3> + 'a {
+    faces.iter().flat_map(move |face| face_vertices(vertices, face))
+}
+
+fn face_vertices(vertices: &[RwVector3], face: &ColFace) -> Vec<RwVector3> {
+    [face.a, face.b, face.c].iter().filter_map(|&index| vertices.get(index as usize).copied()).collect()
+}
+
+fn centroid(vertices: &[RwVector3], face: &ColFace) -> RwVector3 {
+    let points = face_vertices(vertices, face);
+    let count = points.len().max(1) as f32;
+    let sum = points.iter().fold(RwVector3 { x: 0.0, y: 0.0, z: 0.0 }, |sum, p| offset(sum, p.x, p.y, p.z));
+    RwVector3 { x: sum.x / count, y: sum.y / count, z: sum.z / count }
+}
+
+fn aabb(points: impl Iterator<Item = RwVector3>) -> Option<(RwVector3, RwVector3)> {
+    points.fold(None, |bounds, p| {
+        Some(match bounds {
+            None => (p, p),
+            Some((min, max)) => (
+                RwVector3 { x: min.x.min(p.x), y: min.y.min(p.y), z: min.z.min(p.z) },
+                RwVector3 { x: max.x.max(p.x), y: max.y.max(p.y), z: max.z.max(p.z) },
+            ),
+        })
+    })
+}
+
+fn box_corners(min: RwVector3, max: RwVector3) -> [RwVector3; 8] {
+    let mut corners = [min; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+        corner.x = if i & 1 == 0 { min.x } else { max.x };
+        corner.y = if i & 2 == 0 { min.y } else { max.y };
+        corner.z = if i & 4 == 0 { min.z } else { max.z };
+    }
+    corners
+}
+
+fn offset(v: RwVector3, x: f32, y: f32, z: f32) -> RwVector3 {
+    RwVector3 { x: v.x + x, y: v.y + y, z: v.z + z }
+}
+
+fn distance(a: RwVector3, b: RwVector3) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
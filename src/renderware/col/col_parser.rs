@@ -0,0 +1,424 @@
+//! # COL Parser
+//!
+//! A parser for GTA collision files (`.col`), used alongside RenderWare
+//! models in Grand Theft Auto 3, Vice City, and San Andreas. Collision files
+//! are not chunk-based; each model starts with a `COLL`, `COL2`, `COL3` or
+//! `COL4` header.
+//!
+//! ## Features
+//!
+//! - Parses all collision versions, from `COL1` (GTA3/VC) to `COL4`.
+//! - Reads spheres, boxes, triangle meshes and their surface materials.
+//! - Decompresses the fixed-point vertices of `COL2` and later.
+//! - Reads face groups and the shadow meshes of `COL3`/`COL4`.
+//! - Reads every model of multi-model collision archives.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::col::col_parser::ColParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/collision.col").unwrap();
+//! let mut parser = ColParser::new(&file_data);
+//! let col_data = parser.parse().unwrap();
+//!
+//! println!("Collision model count: {}", col_data.models.len());
+//! ```
+
+use crate::renderware::common::types::RwVector3;
+use crate::renderware::rw_file::RwFile;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// The `flags` bit set when a `COL2+` model has any collision primitive.
+pub const COL_FLAG_NOT_EMPTY: u32 = 0x02;
+/// The `flags` bit set when a `COL2+` model has face groups.
+pub const COL_FLAG_FACE_GROUPS: u32 = 0x08;
+/// The `flags` bit set when a `COL3+` model has a shadow mesh.
+pub const COL_FLAG_SHADOW_MESH: u32 = 0x10;
+
+/// The scale of the fixed-point vertices of `COL2` and later.
+const COMPRESSED_VERTEX_SCALE: f32 = 128.0;
+
+/// Represents the version of a collision model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ColVersion {
+    /// GTA3 / Vice City format, signature `COLL`.
+    COL1,
+    /// San Andreas PS2 format.
+    COL2,
+    /// San Andreas PC/Xbox format, adding shadow meshes.
+    COL3,
+    /// San Andreas format with an extra header field.
+    COL4,
+}
+
+impl ColVersion {
+    pub fn from_signature(signature: &[u8]) -> Option<Self> {
+        match signature {
+            b"COLL" => Some(ColVersion::COL1),
+            b"COL2" => Some(ColVersion::COL2),
+            b"COL3" => Some(ColVersion::COL3),
+            b"COL4" => Some(ColVersion::COL4),
+            _ => None,
+        }
+    }
+
+    pub fn signature(self) -> &'static [u8; 4] {
+        match self {
+            ColVersion::COL1 => b"COLL",
+            ColVersion::COL2 => b"COL2",
+            ColVersion::COL3 => b"COL3",
+            ColVersion::COL4 => b"COL4",
+        }
+    }
+}
+
+/// Represents a parsed collision file, which holds one or more models.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwCol {
+    pub models: Vec<ColModel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColModel {
+    pub version: ColVersion,
+    pub name: String,
+    /// The model id, unused by the games when loading from `.col` files.
+    pub model_id: u16,
+    pub bounds: ColBounds,
+    pub spheres: Vec<ColSphere>,
+    pub boxes: Vec<ColBox>,
+    pub vertices: Vec<RwVector3>,
+    pub faces: Vec<ColFace>,
+    pub face_groups: Vec<ColFaceGroup>,
+    pub shadow_vertices: Vec<RwVector3>,
+    pub shadow_faces: Vec<ColFace>,
+    /// The raw `COL2+` header flags, zero for `COL1`.
+    pub flags: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ColBounds {
+    pub min: RwVector3,
+    pub max: RwVector3,
+    pub center: RwVector3,
+    pub radius: f32,
+}
+
+/// The surface properties of a collision primitive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ColSurface {
+    /// The surface material id, as listed in `surface.dat`.
+    pub material: u8,
+    pub flag: u8,
+    pub brightness: u8,
+    pub light: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ColSphere {
+    pub center: RwVector3,
+    pub radius: f32,
+    pub surface: ColSurface,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ColBox {
+    pub min: RwVector3,
+    pub max: RwVector3,
+    pub surface: ColSurface,
+}
+
+/// A triangle of a collision mesh. `COL2+` faces only store the material
+/// and light of their surface.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ColFace {
+    pub a: u32,
+    pub b: u32,
+    pub c: u32,
+    pub surface: ColSurface,
+}
+
+/// A bounding box over a range of faces, used to speed up collision tests.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ColFaceGroup {
+    pub min: RwVector3,
+    pub max: RwVector3,
+    pub start_face: u16,
+    pub end_face: u16,
+}
+
+/// The main parser for collision files.
+pub struct ColParser<'a> {
+    file: RwFile<'a>,
+}
+
+impl<'a> ColParser<'a> {
+    /// Creates a new `ColParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw collision file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        ColParser {
+            file: RwFile::new(buffer),
+        }
+    }
+
+    /// Parses every collision model in the file buffer.
+    ///
+    /// Reading stops at the end of the buffer or at the first block without
+    /// a collision signature, which skips the zero padding found at the end
+    /// of some archives.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwCol` data or an `std::io::Error`
+    /// if the file contains no collision model or a parsing error occurs.
+    pub fn parse(&mut self) -> Result<RwCol> {
+        let mut models = Vec::new();
+
+        while self.file.get_stream().remaining() >= 8 {
+            let start = self.file.get_stream().get_position();
+            let signature = self.file.get_stream().read(4)?;
+            let Some(version) = ColVersion::from_signature(&signature) else {
+                break;
+            };
+            let size = self.file.get_stream().read_u32()?;
+
+            models.push(self.read_model(version, start)?);
+            self.file.get_stream().set_position(start + 8 + size as u64);
+        }
+
+        if models.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "No collision model found"));
+        }
+
+        Ok(RwCol { models })
+    }
+
+    fn read_model(&mut self, version: ColVersion, start: u64) -> Result<ColModel> {
+        let name = self.file.get_stream().read_string(22)?;
+        let model_id = self.file.get_stream().read_u16()?;
+
+        match version {
+            ColVersion::COL1 => self.read_col1_model(name, model_id),
+            _ => self.read_col2_model(version, name, model_id, start + 4),
+        }
+    }
+
+    fn read_col1_model(&mut self, name: String, model_id: u16) -> Result<ColModel> {
+        let radius = self.file.get_stream().read_f32()?;
+        let center = self.read_vector3()?;
+        let min = self.read_vector3()?;
+        let max = self.read_vector3()?;
+
+        let spheres_count = self.file.get_stream().read_u32()?;
+        let mut spheres = Vec::with_capacity(self.file.checked_capacity("spheres", spheres_count as u64, 20)?);
+        for _ in 0..spheres_count {
+            let radius = self.file.get_stream().read_f32()?;
+            let center = self.read_vector3()?;
+            let surface = self.read_surface()?;
+            spheres.push(ColSphere { center, radius, surface });
+        }
+
+        let lines_count = self.file.get_stream().read_u32()?;
+        self.file.get_stream().skip(lines_count as u64 * 24)?;
+
+        let boxes_count = self.file.get_stream().read_u32()?;
+        let mut boxes = Vec::with_capacity(self.file.checked_capacity("boxes", boxes_count as u64, 28)?);
+        for _ in 0..boxes_count {
+            boxes.push(self.read_box()?);
+        }
+
+        let vertices_count = self.file.get_stream().read_u32()?;
+        let mut vertices = Vec::with_capacity(self.file.checked_capacity("vertices", vertices_count as u64, 12)?);
+        for _ in 0..vertices_count {
+            vertices.push(self.read_vector3()?);
+        }
+
+        let faces_count = self.file.get_stream().read_u32()?;
+        let mut faces = Vec::with_capacity(self.file.checked_capacity("faces", faces_count as u64, 16)?);
+        for _ in 0..faces_count {
+            let a = self.file.get_stream().read_u32()?;
+            let b = self.file.get_stream().read_u32()?;
+            let c = self.file.get_stream().read_u32()?;
+            let surface = self.read_surface()?;
+            faces.push(ColFace { a, b, c, surface });
+        }
+
+        Ok(ColModel {
+            version: ColVersion::COL1,
+            name,
+            model_id,
+            bounds: ColBounds { min, max, center, radius },
+            spheres,
+            boxes,
+            vertices,
+            faces,
+            face_groups: Vec::new(),
+            shadow_vertices: Vec::new(),
+            shadow_faces: Vec::new(),
+            flags: 0,
+        })
+    }
+
+    /// Reads a `COL2+` model. Its sections are located through offsets
+    /// relative to `base`, the position following the signature.
+    fn read_col2_model(&mut self, version: ColVersion, name: String, model_id: u16, base: u64) -> Result<ColModel> {
+        let min = self.read_vector3()?;
+        let max = self.read_vector3()?;
+        let center = self.read_vector3()?;
+        let radius = self.file.get_stream().read_f32()?;
+
+        let spheres_count = self.file.get_stream().read_u16()?;
+        let boxes_count = self.file.get_stream().read_u16()?;
+        let faces_count = self.file.get_stream().read_u16()?;
+        let _lines_count = self.file.get_stream().read_u8()?;
+        self.file.get_stream().skip(1)?; // padding
+        let flags = self.file.get_stream().read_u32()?;
+        let spheres_offset = self.file.get_stream().read_u32()?;
+        let boxes_offset = self.file.get_stream().read_u32()?;
+        let _lines_offset = self.file.get_stream().read_u32()?;
+        let vertices_offset = self.file.get_stream().read_u32()?;
+        let faces_offset = self.file.get_stream().read_u32()?;
+        let _planes_offset = self.file.get_stream().read_u32()?;
+
+        let (shadow_faces_count, shadow_vertices_offset, shadow_faces_offset) = if version != ColVersion::COL2 {
+            (
+                self.file.get_stream().read_u32()?,
+                self.file.get_stream().read_u32()?,
+                self.file.get_stream().read_u32()?,
+            )
+        } else {
+            (0, 0, 0)
+        };
+
+        self.file.get_stream().set_position(base + spheres_offset as u64);
+        let mut spheres = Vec::with_capacity(spheres_count as usize);
+        for _ in 0..spheres_count {
+            let center = self.read_vector3()?;
+            let radius = self.file.get_stream().read_f32()?;
+            let surface = self.read_surface()?;
+            spheres.push(ColSphere { center, radius, surface });
+        }
+
+        self.file.get_stream().set_position(base + boxes_offset as u64);
+        let mut boxes = Vec::with_capacity(boxes_count as usize);
+        for _ in 0..boxes_count {
+            boxes.push(self.read_box()?);
+        }
+
+        self.file.get_stream().set_position(base + faces_offset as u64);
+        let faces = self.read_compressed_faces(faces_count as u32)?;
+        let vertices = self.read_compressed_vertices(base + vertices_offset as u64, &faces)?;
+
+        let mut face_groups = Vec::new();
+        if flags & COL_FLAG_FACE_GROUPS != 0 && faces_count > 0 {
+            // The group count precedes the faces, and the groups precede it
+            let count_position = (base + faces_offset as u64)
+                .checked_sub(4)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Invalid collision face group offset"))?;
+            self.file.get_stream().set_position(count_position);
+            let groups_count = self.file.get_stream().read_u32()?;
+            let groups_position = count_position
+                .checked_sub(groups_count as u64 * 28)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Invalid collision face group count"))?;
+            self.file.get_stream().set_position(groups_position);
+
+            face_groups.reserve(groups_count as usize);
+            for _ in 0..groups_count {
+                let min = self.read_vector3()?;
+                let max = self.read_vector3()?;
+                let start_face = self.file.get_stream().read_u16()?;
+                let end_face = self.file.get_stream().read_u16()?;
+                face_groups.push(ColFaceGroup { min, max, start_face, end_face });
+            }
+        }
+
+        let mut shadow_faces = Vec::new();
+        let mut shadow_vertices = Vec::new();
+        if shadow_faces_count > 0 {
+            self.file.get_stream().set_position(base + shadow_faces_offset as u64);
+            shadow_faces = self.read_compressed_faces(shadow_faces_count)?;
+            shadow_vertices = self.read_compressed_vertices(base + shadow_vertices_offset as u64, &shadow_faces)?;
+        }
+
+        Ok(ColModel {
+            version,
+            name,
+            model_id,
+            bounds: ColBounds { min, max, center, radius },
+            spheres,
+            boxes,
+            vertices,
+            faces,
+            face_groups,
+            shadow_vertices,
+            shadow_faces,
+            flags,
+        })
+    }
+
+    fn read_compressed_faces(&mut self, count: u32) -> Result<Vec<ColFace>> {
+        let mut faces = Vec::with_capacity(self.file.checked_capacity("faces", count as u64, 8)?);
+        for _ in 0..count {
+            let a = self.file.get_stream().read_u16()? as u32;
+            let b = self.file.get_stream().read_u16()? as u32;
+            let c = self.file.get_stream().read_u16()? as u32;
+            let material = self.file.get_stream().read_u8()?;
+            let light = self.file.get_stream().read_u8()?;
+            faces.push(ColFace {
+                a,
+                b,
+                c,
+                surface: ColSurface { material, light, ..Default::default() },
+            });
+        }
+        Ok(faces)
+    }
+
+    /// Reads fixed-point vertices. Their count is not stored, so it is
+    /// derived from the highest index used by `faces`.
+    fn read_compressed_vertices(&mut self, offset: u64, faces: &[ColFace]) -> Result<Vec<RwVector3>> {
+        let count = faces.iter().map(|face| face.a.max(face.b).max(face.c) + 1).max().unwrap_or(0);
+
+        self.file.get_stream().set_position(offset);
+        let mut vertices = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            vertices.push(RwVector3 {
+                x: self.file.get_stream().read_i16()? as f32 / COMPRESSED_VERTEX_SCALE,
+                y: self.file.get_stream().read_i16()? as f32 / COMPRESSED_VERTEX_SCALE,
+                z: self.file.get_stream().read_i16()? as f32 / COMPRESSED_VERTEX_SCALE,
+            });
+        }
+        Ok(vertices)
+    }
+
+    fn read_box(&mut self) -> Result<ColBox> {
+        let min = self.read_vector3()?;
+        let max = self.read_vector3()?;
+        let surface = self.read_surface()?;
+        Ok(ColBox { min, max, surface })
+    }
+
+    fn read_surface(&mut self) -> Result<ColSurface> {
+        Ok(ColSurface {
+            material: self.file.get_stream().read_u8()?,
+            flag: self.file.get_stream().read_u8()?,
+            brightness: self.file.get_stream().read_u8()?,
+            light: self.file.get_stream().read_u8()?,
+        })
+    }
+
+    fn read_vector3(&mut self) -> Result<RwVector3> {
+        Ok(RwVector3 {
+            x: self.file.get_stream().read_f32()?,
+            y: self.file.get_stream().read_f32()?,
+            z: self.file.get_stream().read_f32()?,
+        })
+    }
+}
@@ -0,0 +1,287 @@
+//! # COL Writer
+//!
+//! A serializer that turns a parsed `RwCol` back into a collision file.
+//!
+//! ## Features
+//!
+//! - Writes `COL1` to `COL4` models, keeping each model's version or
+//!   converting all of them to a single version.
+//! - Compresses mesh vertices to the fixed-point format of `COL2` and later.
+//! - Writes face groups and `COL3`/`COL4` shadow meshes.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::col::col_parser::{ColParser, ColVersion};
+//! use rw_parser_rs::renderware::col::col_writer::ColWriter;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/collision.col").unwrap();
+//! let mut col_data = ColParser::new(&file_data).parse().unwrap();
+//! for model in &mut col_data.models {
+//!     model.recompute_bounds();
+//! }
+//!
+//! let output = ColWriter::new().with_version(ColVersion::COL3).write(&col_data).unwrap();
+//! fs::write("path/to/your/output.col", output).unwrap();
+//! ```
+
+use super::col_parser::{
+    ColFace, ColModel, ColSurface, ColVersion, RwCol, COL_FLAG_FACE_GROUPS, COL_FLAG_NOT_EMPTY, COL_FLAG_SHADOW_MESH,
+};
+use crate::renderware::common::types::RwVector3;
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::utils::byte_stream::ByteWriter;
+
+/// The main writer for collision files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColWriter {
+    version: Option<ColVersion>,
+}
+
+impl ColWriter {
+    /// Creates a new `ColWriter` that writes each model in its own version.
+    pub fn new() -> Self {
+        ColWriter { version: None }
+    }
+
+    /// Converts every model to the given version. Face groups and shadow
+    /// meshes are dropped when the version cannot store them.
+    pub fn with_version(mut self, version: ColVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Serializes every model of the collision file.
+    ///
+    /// # Returns
+    ///
+    /// A `RwResult` containing the collision file bytes, or an
+    /// `UnwritableData` error if a `COL2+` mesh exceeds the 16-bit face or
+    /// vertex limits.
+    pub fn write(&self, col: &RwCol) -> RwResult<Vec<u8>> {
+        let mut stream = ByteWriter::new();
+        for model in &col.models {
+            self.write_model(&mut stream, model)?;
+        }
+        Ok(stream.into_inner())
+    }
+
+    /// Serializes a single collision model, including its header.
+    pub fn write_model(&self, stream: &mut ByteWriter, model: &ColModel) -> RwResult<()> {
+        let version = self.version.unwrap_or(model.version);
+
+        stream.write(version.signature());
+        let size_position = stream.get_position();
+        stream.write_u32(0);
+
+        let body_start = stream.get_position();
+        write_name(stream, &model.name);
+        stream.write_u16(model.model_id);
+        match version {
+            ColVersion::COL1 => write_col1_body(stream, model),
+            _ => write_col2_body(stream, model, version)?,
+        }
+
+        stream.patch_u32(size_position, (stream.get_position() - body_start) as u32);
+        Ok(())
+    }
+}
+
+fn write_col1_body(stream: &mut ByteWriter, model: &ColModel) {
+    let bounds = &model.bounds;
+    stream.write_f32(bounds.radius);
+    write_vector3(stream, bounds.center);
+    write_vector3(stream, bounds.min);
+    write_vector3(stream, bounds.max);
+
+    stream.write_u32(model.spheres.len() as u32);
+    for sphere in &model.spheres {
+        stream.write_f32(sphere.radius);
+        write_vector3(stream, sphere.center);
+        write_surface(stream, sphere.surface);
+    }
+
+    stream.write_u32(0); // lines
+
+    stream.write_u32(model.boxes.len() as u32);
+    for col_box in &model.boxes {
+        write_vector3(stream, col_box.min);
+        write_vector3(stream, col_box.max);
+        write_surface(stream, col_box.surface);
+    }
+
+    stream.write_u32(model.vertices.len() as u32);
+    for vertex in &model.vertices {
+        write_vector3(stream, *vertex);
+    }
+
+    stream.write_u32(model.faces.len() as u32);
+    for face in &model.faces {
+        stream.write_u32(face.a);
+        stream.write_u32(face.b);
+        stream.write_u32(face.c);
+        write_surface(stream, face.surface);
+    }
+}
+
+/// Writes a `COL2+` model following its name. The header holds offsets to
+/// the sections that follow it, relative to the size field written before
+/// the name.
+fn write_col2_body(stream: &mut ByteWriter, model: &ColModel, version: ColVersion) -> RwResult<()> {
+    let has_shadow = version != ColVersion::COL2 && !model.shadow_faces.is_empty();
+    let has_face_groups = !model.face_groups.is_empty() && !model.faces.is_empty();
+
+    check_mesh_limits(&model.vertices, &model.faces)?;
+    if model.spheres.len() > u16::MAX as usize || model.boxes.len() > u16::MAX as usize {
+        return Err(RwParseError::UnwritableData { message: "Too many collision spheres or boxes for COL2+".to_string() });
+    }
+    if has_shadow {
+        check_mesh_limits(&model.shadow_vertices, &model.shadow_faces)?;
+    }
+
+    let mut flags = model.flags & !(COL_FLAG_NOT_EMPTY | COL_FLAG_FACE_GROUPS | COL_FLAG_SHADOW_MESH);
+    if !model.spheres.is_empty() || !model.boxes.is_empty() || !model.faces.is_empty() {
+        flags |= COL_FLAG_NOT_EMPTY;
+    }
+    if has_face_groups {
+        flags |= COL_FLAG_FACE_GROUPS;
+    }
+    if has_shadow {
+        flags |= COL_FLAG_SHADOW_MESH;
+    }
+
+    // Offsets are relative to the size field, which sits 28 bytes before
+    // the bounds that start this body.
+    let base = stream.get_position() - 28;
+    let offset = |stream: &ByteWriter| (stream.get_position() - base) as u32;
+
+    let bounds = &model.bounds;
+    write_vector3(stream, bounds.min);
+    write_vector3(stream, bounds.max);
+    write_vector3(stream, bounds.center);
+    stream.write_f32(bounds.radius);
+
+    stream.write_u16(model.spheres.len() as u16);
+    stream.write_u16(model.boxes.len() as u16);
+    stream.write_u16(model.faces.len() as u16);
+    stream.write(&[0, 0]); // lines, padding
+    stream.write_u32(flags);
+    let offsets_position = stream.get_position();
+    for _ in 0..6 {
+        stream.write_u32(0);
+    }
+
+    let shadow_position = stream.get_position();
+    if version != ColVersion::COL2 {
+        let shadow_faces_count = if has_shadow { model.shadow_faces.len() as u32 } else { 0 };
+        stream.write_u32(shadow_faces_count);
+        stream.write_u32(0);
+        stream.write_u32(0);
+    }
+    if version == ColVersion::COL4 {
+        stream.write_u32(0);
+    }
+
+    let spheres_offset = offset(stream);
+    for sphere in &model.spheres {
+        write_vector3(stream, sphere.center);
+        stream.write_f32(sphere.radius);
+        write_surface(stream, sphere.surface);
+    }
+
+    let boxes_offset = offset(stream);
+    for col_box in &model.boxes {
+        write_vector3(stream, col_box.min);
+        write_vector3(stream, col_box.max);
+        write_surface(stream, col_box.surface);
+    }
+
+    let vertices_offset = offset(stream);
+    write_compressed_vertices(stream, &model.vertices);
+
+    if has_face_groups {
+        for group in &model.face_groups {
+            write_vector3(stream, group.min);
+            write_vector3(stream, group.max);
+            stream.write_u16(group.start_face);
+            stream.write_u16(group.end_face);
+        }
+        stream.write_u32(model.face_groups.len() as u32);
+    }
+
+    let faces_offset = offset(stream);
+    write_compressed_faces(stream, &model.faces);
+
+    for (index, section_offset) in [spheres_offset, boxes_offset, 0, vertices_offset, faces_offset, 0].into_iter().enumerate() {
+        stream.patch_u32(offsets_position + index as u64 * 4, section_offset);
+    }
+
+    if has_shadow {
+        let shadow_vertices_offset = offset(stream);
+        write_compressed_vertices(stream, &model.shadow_vertices);
+        let shadow_faces_offset = offset(stream);
+        write_compressed_faces(stream, &model.shadow_faces);
+        stream.patch_u32(shadow_position + 4, shadow_vertices_offset);
+        stream.patch_u32(shadow_position + 8, shadow_faces_offset);
+    }
+    Ok(())
+}
+
+fn check_mesh_limits(vertices: &[RwVector3], faces: &[ColFace]) -> RwResult<()> {
+    if faces.len() > u16::MAX as usize || vertices.len() > u16::MAX as usize + 1 {
+        return Err(RwParseError::UnwritableData { message: "Collision mesh exceeds the COL2+ 16-bit limits".to_string() });
+    }
+    let in_range = |value: f32| (i16::MIN as f32..=i16::MAX as f32).contains(&(value * 128.0).round());
+    if let Some(vertex) = vertices.iter().find(|vertex| ![vertex.x, vertex.y, vertex.z].into_iter().all(in_range)) {
+        return Err(RwParseError::UnwritableData {
+            message: format!(
+                "Collision vertex ({}, {}, {}) is outside the COL2+ range of -256 to 256",
+                vertex.x, vertex.y, vertex.z
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Writes vertices as 16-bit fixed-point values, padded to four bytes.
+/// `check_mesh_limits` has made sure every coordinate fits.
+fn write_compressed_vertices(stream: &mut ByteWriter, vertices: &[RwVector3]) {
+    let compress = |value: f32| (value * 128.0).round() as i16;
+    for vertex in vertices {
+        stream.write_i16(compress(vertex.x));
+        stream.write_i16(compress(vertex.y));
+        stream.write_i16(compress(vertex.z));
+    }
+    if vertices.len() % 2 == 1 {
+        stream.write(&[0, 0]);
+    }
+}
+
+fn write_compressed_faces(stream: &mut ByteWriter, faces: &[ColFace]) {
+    for face in faces {
+        stream.write_u16(face.a as u16);
+        stream.write_u16(face.b as u16);
+        stream.write_u16(face.c as u16);
+        stream.write_u8(face.surface.material);
+        stream.write_u8(face.surface.light);
+    }
+}
+
+fn write_surface(stream: &mut ByteWriter, surface: ColSurface) {
+    stream.write(&[surface.material, surface.flag, surface.brightness, surface.light]);
+}
+
+fn write_vector3(stream: &mut ByteWriter, vector: RwVector3) {
+    stream.write_f32(vector.x);
+    stream.write_f32(vector.y);
+    stream.write_f32(vector.z);
+}
+
+/// Writes the 22-byte model name, keeping its last byte as a terminator.
+fn write_name(stream: &mut ByteWriter, name: &str) {
+    let bytes = name.as_bytes();
+    let length = bytes.len().min(21);
+    stream.write(&bytes[..length]);
+    stream.write(&[0; 22][length..]);
+}
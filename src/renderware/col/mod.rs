@@ -0,0 +1,3 @@
+pub mod col_parser;
+pub mod col_writer;
+pub mod col_bounds;
\ No newline at end of file
@@ -1 +1,2 @@
-pub mod types;
\ No newline at end of file
+pub mod types;
+pub(crate) mod text;
\ No newline at end of file
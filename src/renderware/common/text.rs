@@ -0,0 +1,48 @@
+//! Helpers for the line-based text formats of the games (IPL, IDE, ...),
+//! which group comma or whitespace separated lines into sections closed by
+//! `end`.
+
+/// A data line of a sectioned text file.
+pub(crate) struct SectionLine<'a> {
+    /// The 1-based line number, for error messages.
+    pub line_number: usize,
+    /// The lowercase name of the enclosing section.
+    pub section: String,
+    pub fields: Vec<&'a str>,
+}
+
+/// Splits a line into fields separated by commas and/or whitespace.
+pub(crate) fn split_fields(line: &str) -> Vec<&str> {
+    line.split(|c: char| c == ',' || c.is_whitespace()).filter(|field| !field.is_empty()).collect()
+}
+
+/// Strips `#` comments and surrounding whitespace from a line.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap_or("").trim()
+}
+
+/// Returns the data lines of a sectioned text file. A line outside of any
+/// section opens a new section named after it, and `end` closes it.
+pub(crate) fn section_lines(text: &str) -> Vec<SectionLine<'_>> {
+    let mut lines = Vec::new();
+    let mut section: Option<String> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = strip_comment(line);
+        if line.is_empty() {
+            continue;
+        }
+
+        match &section {
+            None => section = Some(line.to_ascii_lowercase()),
+            Some(_) if line.eq_ignore_ascii_case("end") => section = None,
+            Some(current) => lines.push(SectionLine {
+                line_number: index + 1,
+                section: current.clone(),
+                fields: split_fields(line),
+            }),
+        }
+    }
+
+    lines
+}
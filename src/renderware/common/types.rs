@@ -1,19 +1,19 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwVector2 {
     pub x: f32,
     pub y: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwVector3 {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwVector4 {
     pub x: f32,
     pub y: f32,
@@ -21,7 +21,7 @@ pub struct RwVector4 {
     pub t: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwQuaternion {
     pub x: f32,
     pub y: f32,
@@ -29,14 +29,139 @@ pub struct RwQuaternion {
     pub w: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+impl RwQuaternion {
+    /// The quaternion representing no rotation.
+    pub const IDENTITY: RwQuaternion = RwQuaternion { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+
+    pub fn dot(self, other: RwQuaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the quaternion scaled to unit length, or the identity if its
+    /// length is zero.
+    pub fn normalize(self) -> RwQuaternion {
+        let length = self.length();
+        if length > 0.0 {
+            RwQuaternion { x: self.x / length, y: self.y / length, z: self.z / length, w: self.w / length }
+        } else {
+            RwQuaternion::IDENTITY
+        }
+    }
+
+    /// Returns the angle in radians of the rotation from `self` to `other`.
+    pub fn angle_to(self, other: RwQuaternion) -> f32 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+
+    /// Spherically interpolates between two rotations along the shortest arc.
+    pub fn slerp(self, other: RwQuaternion, t: f32) -> RwQuaternion {
+        let mut dot = self.dot(other);
+        let other = if dot < 0.0 {
+            dot = -dot;
+            RwQuaternion { x: -other.x, y: -other.y, z: -other.z, w: -other.w }
+        } else {
+            other
+        };
+
+        let (wa, wb) = if dot > 0.9995 {
+            // Nearly parallel: fall back to linear interpolation
+            (1.0 - t, t)
+        } else {
+            let theta = dot.acos();
+            let sin_theta = theta.sin();
+            (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+        };
+
+        RwQuaternion {
+            x: self.x * wa + other.x * wb,
+            y: self.y * wa + other.y * wb,
+            z: self.z * wa + other.z * wb,
+            w: self.w * wa + other.w * wb,
+        }
+        .normalize()
+    }
+
+    /// Converts the rotation into a matrix whose rows are the rotated axes,
+    /// the layout used by RenderWare frames.
+    pub fn to_matrix3(self) -> RwMatrix3 {
+        let RwQuaternion { x, y, z, w } = self.normalize();
+        RwMatrix3 {
+            right: RwVector3 { x: 1.0 - 2.0 * (y * y + z * z), y: 2.0 * (x * y + w * z), z: 2.0 * (x * z - w * y) },
+            up: RwVector3 { x: 2.0 * (x * y - w * z), y: 1.0 - 2.0 * (x * x + z * z), z: 2.0 * (y * z + w * x) },
+            at: RwVector3 { x: 2.0 * (x * z + w * y), y: 2.0 * (y * z - w * x), z: 1.0 - 2.0 * (x * x + y * y) },
+        }
+    }
+
+    /// Converts a rotation matrix whose rows are the rotated axes into a
+    /// unit quaternion.
+    pub fn from_matrix3(matrix: &RwMatrix3) -> RwQuaternion {
+        let (right, up, at) = (matrix.right, matrix.up, matrix.at);
+        let trace = right.x + up.y + at.z;
+
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            RwQuaternion { w: 0.25 * s, x: (up.z - at.y) / s, y: (at.x - right.z) / s, z: (right.y - up.x) / s }
+        } else if right.x > up.y && right.x > at.z {
+            let s = (1.0 + right.x - up.y - at.z).sqrt() * 2.0;
+            RwQuaternion { w: (up.z - at.y) / s, x: 0.25 * s, y: (up.x + right.y) / s, z: (at.x + right.z) / s }
+        } else if up.y > at.z {
+            let s = (1.0 + up.y - right.x - at.z).sqrt() * 2.0;
+            RwQuaternion { w: (at.x - right.z) / s, x: (up.x + right.y) / s, y: 0.25 * s, z: (at.y + up.z) / s }
+        } else {
+            let s = (1.0 + at.z - right.x - up.y).sqrt() * 2.0;
+            RwQuaternion { w: (right.y - up.x) / s, x: (at.x + right.z) / s, y: (at.y + up.z) / s, z: 0.25 * s }
+        };
+        q.normalize()
+    }
+
+    /// Converts Euler angles in radians into a rotation. The angles rotate
+    /// about X, then Y, then Z.
+    pub fn from_euler(angles: RwVector3) -> RwQuaternion {
+        let (sr, cr) = (angles.x * 0.5).sin_cos();
+        let (sp, cp) = (angles.y * 0.5).sin_cos();
+        let (sy, cy) = (angles.z * 0.5).sin_cos();
+        RwQuaternion {
+            x: sr * cp * cy - cr * sp * sy,
+            y: cr * sp * cy + sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+            w: cr * cp * cy + sr * sp * sy,
+        }
+    }
+
+    /// Converts the rotation into Euler angles in radians, in the convention
+    /// of `from_euler`.
+    pub fn to_euler(self) -> RwVector3 {
+        let RwQuaternion { x, y, z, w } = self.normalize();
+        RwVector3 {
+            x: (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y)),
+            y: (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin(),
+            z: (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwMatrix3 {
     pub right: RwVector3,
     pub up: RwVector3,
     pub at: RwVector3,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+impl RwMatrix3 {
+    pub fn to_quaternion(&self) -> RwQuaternion {
+        RwQuaternion::from_matrix3(self)
+    }
+
+    pub fn from_quaternion(rotation: RwQuaternion) -> RwMatrix3 {
+        rotation.to_matrix3()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwMatrix4 {
     pub right: RwVector4,
     pub up: RwVector4,
@@ -44,7 +169,7 @@ pub struct RwMatrix4 {
     pub transform: RwVector4,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RwColor {
     pub r: u8,
     pub g: u8,
@@ -52,19 +177,19 @@ pub struct RwColor {
     pub a: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwTextureCoordinate {
     pub u: f32,
     pub v: f32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwTriangle {
     pub vector: RwVector3,
     pub material_id: u16,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwSphere {
     pub vector: RwVector3,
     pub radius: f32,
@@ -0,0 +1,147 @@
+//! # carcols.dat Parser
+//!
+//! A parser for the vehicle color files (`carcols.dat`) of Grand Theft Auto
+//! 3, Vice City, and San Andreas.
+//!
+//! ## Features
+//!
+//! - Parses the color palette of the `col` section.
+//! - Parses the two-color combinations of the `car` section and the
+//!   four-color combinations of the San Andreas `car4` section.
+//! - Resolves the palette colors of a model's combinations.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dat::carcols_dat::CarcolsParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/carcols.dat").unwrap();
+//! let carcols = CarcolsParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Infernus colors: {:?}", carcols.colors_for_model("infernus"));
+//! ```
+
+use crate::renderware::common::text::section_lines;
+use crate::renderware::common::types::RwColor;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// Represents a parsed car color file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwCarcols {
+    /// The palette, indexed by the color ids of the combinations.
+    pub palette: Vec<RwColor>,
+    pub cars: Vec<CarColorAssignment>,
+}
+
+/// The color combinations a model may spawn with.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CarColorAssignment {
+    pub model_name: String,
+    pub combinations: Vec<CarColorCombination>,
+}
+
+/// Palette indices of a combination. The third and fourth colors are only
+/// set by the San Andreas `car4` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CarColorCombination {
+    pub primary: u8,
+    pub secondary: u8,
+    pub tertiary: Option<u8>,
+    pub quaternary: Option<u8>,
+}
+
+impl CarColorCombination {
+    pub fn indices(&self) -> Vec<u8> {
+        [Some(self.primary), Some(self.secondary), self.tertiary, self.quaternary].into_iter().flatten().collect()
+    }
+}
+
+impl RwCarcols {
+    pub fn palette_color(&self, index: u8) -> Option<RwColor> {
+        self.palette.get(index as usize).copied()
+    }
+
+    /// Returns the palette colors of each combination of a model, compared
+    /// case-insensitively. Combinations of both `car` and `car4` are
+    /// included.
+    pub fn colors_for_model(&self, model_name: &str) -> Vec<Vec<RwColor>> {
+        self.cars
+            .iter()
+            .filter(|car| car.model_name.eq_ignore_ascii_case(model_name))
+            .flat_map(|car| &car.combinations)
+            .map(|combination| combination.indices().into_iter().filter_map(|index| self.palette_color(index)).collect())
+            .collect()
+    }
+}
+
+/// The main parser for car color files.
+pub struct CarcolsParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> CarcolsParser<'a> {
+    /// Creates a new `CarcolsParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw car color data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        CarcolsParser { buffer }
+    }
+
+    /// Parses the car color file. Lines of unknown sections are skipped.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwCarcols` data or an
+    /// `std::io::Error` if a line is malformed.
+    pub fn parse(&mut self) -> Result<RwCarcols> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut carcols = RwCarcols::default();
+
+        for line in section_lines(&text) {
+            let fields = &line.fields;
+            let error = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("carcols.dat line {}: invalid entry in {} section", line.line_number, line.section),
+                )
+            };
+
+            match line.section.as_str() {
+                "col" => carcols.palette.push(parse_color(fields).ok_or_else(error)?),
+                "car" => carcols.cars.push(parse_assignment(fields, 2).ok_or_else(error)?),
+                "car4" => carcols.cars.push(parse_assignment(fields, 4).ok_or_else(error)?),
+                _ => {}
+            }
+        }
+
+        Ok(carcols)
+    }
+}
+
+fn parse_color(fields: &[&str]) -> Option<RwColor> {
+    let component = |index: usize| fields.get(index)?.parse::<u8>().ok();
+
+    Some(RwColor { r: component(0)?, g: component(1)?, b: component(2)?, a: 255 })
+}
+
+fn parse_assignment(fields: &[&str], colors_per_combination: usize) -> Option<CarColorAssignment> {
+    let (model_name, indices) = fields.split_first()?;
+    let indices = indices.iter().map(|index| index.parse::<u8>().ok()).collect::<Option<Vec<_>>>()?;
+
+    let combinations = indices
+        .chunks_exact(colors_per_combination)
+        .map(|chunk| CarColorCombination {
+            primary: chunk[0],
+            secondary: chunk[1],
+            tertiary: chunk.get(2).copied(),
+            quaternary: chunk.get(3).copied(),
+        })
+        .collect();
+
+    Some(CarColorAssignment { model_name: model_name.to_string(), combinations })
+}
@@ -0,0 +1,158 @@
+//! # gta.dat Parser
+//!
+//! A parser for the level load lists of Grand Theft Auto 3, Vice City, and
+//! San Andreas (`gta.dat`, `gta_vc.dat`, `default.dat`), which name the
+//! archives, definitions, placements and collision files the game loads.
+//!
+//! ## Features
+//!
+//! - Parses every load line into a typed entry with its path.
+//! - Keeps the level of `COLFILE` entries and unknown keywords as-is.
+//! - Lists the paths of a given kind in load order.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dat::gta_dat::{GtaDatEntryKind, GtaDatParser};
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/gta.dat").unwrap();
+//! let gta_dat = GtaDatParser::new(&file_data).parse().unwrap();
+//!
+//! println!("IPL files: {:?}", gta_dat.paths(&GtaDatEntryKind::Ipl));
+//! ```
+
+use crate::renderware::common::text::strip_comment;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// Represents a parsed load list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwGtaDat {
+    /// The entries, in load order.
+    pub entries: Vec<GtaDatEntry>,
+}
+
+/// The keyword of a load line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum GtaDatEntryKind {
+    /// An item definition file (`IDE`).
+    Ide,
+    /// An item placement file (`IPL`).
+    Ipl,
+    /// An additional IMG archive (`IMG`).
+    Img,
+    /// An additional IMG archive of Vice City (`CDIMAGE`).
+    CdImage,
+    /// A collision archive (`COLFILE`).
+    ColFile,
+    /// A texture dictionary (`TEXDICTION`).
+    TexDictionary,
+    /// A model loaded outside of the IMG archives (`MODELFILE`).
+    ModelFile,
+    /// A cutscene hierarchy model (`HIERFILE`).
+    HierFile,
+    /// A map zone file of GTA3 (`MAPZONE`).
+    MapZone,
+    /// A loading screen texture (`SPLASH`).
+    Splash,
+    /// Any other keyword, in uppercase.
+    Other(String),
+}
+
+impl GtaDatEntryKind {
+    pub fn from_keyword(keyword: &str) -> Self {
+        match keyword.to_ascii_uppercase().as_str() {
+            "IDE" => GtaDatEntryKind::Ide,
+            "IPL" => GtaDatEntryKind::Ipl,
+            "IMG" => GtaDatEntryKind::Img,
+            "CDIMAGE" => GtaDatEntryKind::CdImage,
+            "COLFILE" => GtaDatEntryKind::ColFile,
+            "TEXDICTION" => GtaDatEntryKind::TexDictionary,
+            "MODELFILE" => GtaDatEntryKind::ModelFile,
+            "HIERFILE" => GtaDatEntryKind::HierFile,
+            "MAPZONE" => GtaDatEntryKind::MapZone,
+            "SPLASH" => GtaDatEntryKind::Splash,
+            other => GtaDatEntryKind::Other(other.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GtaDatEntry {
+    pub kind: GtaDatEntryKind,
+    /// The path as written, relative to the game directory and usually with
+    /// backslashes.
+    pub path: String,
+    /// The level of `COLFILE` entries, zero for the generic collisions.
+    pub level: Option<i32>,
+}
+
+impl RwGtaDat {
+    /// Returns the paths of the entries of the given kind, in load order.
+    pub fn paths(&self, kind: &GtaDatEntryKind) -> Vec<&str> {
+        self.entries.iter().filter(|entry| &entry.kind == kind).map(|entry| entry.path.as_str()).collect()
+    }
+}
+
+/// The main parser for load lists.
+pub struct GtaDatParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> GtaDatParser<'a> {
+    /// Creates a new `GtaDatParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw load list data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        GtaDatParser { buffer }
+    }
+
+    /// Parses the load list.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwGtaDat` data or an `std::io::Error`
+    /// if a line is missing its path.
+    pub fn parse(&mut self) -> Result<RwGtaDat> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut gta_dat = RwGtaDat::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = strip_comment(line);
+            let Some((keyword, rest)) = line.split_once(char::is_whitespace) else {
+                if !line.is_empty() {
+                    gta_dat.entries.push(GtaDatEntry {
+                        kind: GtaDatEntryKind::from_keyword(line),
+                        path: String::new(),
+                        level: None,
+                    });
+                }
+                continue;
+            };
+
+            let kind = GtaDatEntryKind::from_keyword(keyword);
+            let rest = rest.trim();
+            let (level, path) = if kind == GtaDatEntryKind::ColFile {
+                let (level, path) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                (level.parse().ok(), path.trim())
+            } else {
+                (None, rest)
+            };
+
+            if path.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("gta.dat line {}: missing path for {}", index + 1, keyword),
+                ));
+            }
+
+            gta_dat.entries.push(GtaDatEntry { kind, path: path.to_string(), level });
+        }
+
+        Ok(gta_dat)
+    }
+}
@@ -0,0 +1,298 @@
+//! # handling.cfg Parser
+//!
+//! A parser for the vehicle handling files (`handling.cfg`) of Grand Theft
+//! Auto 3, Vice City, and San Andreas.
+//!
+//! ## Features
+//!
+//! - Detects the game from the number of values of the vehicle lines.
+//! - Parses the vehicle lines into typed physics values.
+//! - Keeps the boat (`%`), bike (`!`), flying (`$`) and animation (`^`)
+//!   lines with their identifier and raw values.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dat::handling_cfg::HandlingParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/handling.cfg").unwrap();
+//! let handling = HandlingParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Infernus mass: {:?}", handling.get("INFERNUS").map(|entry| entry.mass));
+//! ```
+
+use crate::renderware::common::text::split_fields;
+use crate::renderware::common::types::RwVector3;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// The game a handling file belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HandlingVersion {
+    /// GTA3: 32 values per vehicle line.
+    Gta3,
+    /// Vice City: 33 values per vehicle line.
+    ViceCity,
+    /// San Andreas: 36 values per vehicle line.
+    SanAndreas,
+}
+
+impl HandlingVersion {
+    fn from_field_count(field_count: usize) -> Option<Self> {
+        match field_count {
+            32 => Some(HandlingVersion::Gta3),
+            33 => Some(HandlingVersion::ViceCity),
+            36 => Some(HandlingVersion::SanAndreas),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a parsed handling file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwHandling {
+    pub version: HandlingVersion,
+    pub vehicles: Vec<HandlingEntry>,
+    pub boats: Vec<HandlingExtraEntry>,
+    pub bikes: Vec<HandlingExtraEntry>,
+    pub flying: Vec<HandlingExtraEntry>,
+    /// The animation groups of San Andreas vehicles.
+    pub animations: Vec<HandlingExtraEntry>,
+}
+
+/// The handling of a vehicle. Fields that only some games store are
+/// `None` for the others.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HandlingEntry {
+    /// The handling id referenced by IDE `cars` lines.
+    pub id: String,
+    pub mass: f32,
+    /// The turning mass, San Andreas only.
+    pub turn_mass: Option<f32>,
+    /// The drag multiplier, San Andreas only.
+    pub drag_multiplier: Option<f32>,
+    /// The bounding box size, GTA3 and Vice City only.
+    pub dimensions: Option<RwVector3>,
+    pub centre_of_mass: RwVector3,
+    pub percent_submerged: i32,
+    pub traction_multiplier: f32,
+    pub traction_loss: f32,
+    pub traction_bias: f32,
+    pub gear_count: u8,
+    pub max_velocity: f32,
+    pub engine_acceleration: f32,
+    /// The engine inertia, San Andreas only.
+    pub engine_inertia: Option<f32>,
+    /// `F`ront, `R`ear or `4` wheel drive.
+    pub drive_type: char,
+    /// `P`etrol, `D`iesel or `E`lectric.
+    pub engine_type: char,
+    pub brake_deceleration: f32,
+    pub brake_bias: f32,
+    pub abs: bool,
+    pub steering_lock: f32,
+    pub suspension_force: f32,
+    pub suspension_damping: f32,
+    /// The high speed damping, San Andreas only.
+    pub suspension_high_speed_damping: Option<f32>,
+    pub suspension_upper_limit: f32,
+    pub suspension_lower_limit: f32,
+    pub suspension_bias: f32,
+    /// The anti-dive multiplier, absent in GTA3.
+    pub suspension_anti_dive: Option<f32>,
+    pub seat_offset: f32,
+    pub collision_damage_multiplier: f32,
+    pub monetary_value: i32,
+    /// The model flags, San Andreas only.
+    pub model_flags: Option<u32>,
+    pub handling_flags: u32,
+    pub front_lights: u8,
+    pub rear_lights: u8,
+    /// The animation group, San Andreas only.
+    pub anim_group: Option<u8>,
+}
+
+/// A boat, bike, flying or animation line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HandlingExtraEntry {
+    pub id: String,
+    pub values: Vec<String>,
+}
+
+impl RwHandling {
+    /// Returns the handling of a vehicle, compared case-insensitively.
+    pub fn get(&self, id: &str) -> Option<&HandlingEntry> {
+        self.vehicles.iter().find(|entry| entry.id.eq_ignore_ascii_case(id))
+    }
+}
+
+/// The main parser for handling files.
+pub struct HandlingParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> HandlingParser<'a> {
+    /// Creates a new `HandlingParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw handling data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        HandlingParser { buffer }
+    }
+
+    /// Parses the handling file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwHandling` data or an
+    /// `std::io::Error` if the game cannot be detected or a line is
+    /// malformed.
+    pub fn parse(&mut self) -> Result<RwHandling> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut version = None;
+        let mut vehicles = Vec::new();
+        let (mut boats, mut bikes, mut flying, mut animations) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or("").trim();
+            // The San Andreas file ends with a `#` terminator
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| Error::new(ErrorKind::InvalidData, format!("handling.cfg line {}: {}", index + 1, message));
+
+            let extras = match line.chars().next() {
+                Some('%') => Some(&mut boats),
+                Some('!') => Some(&mut bikes),
+                Some('$') => Some(&mut flying),
+                Some('^') => Some(&mut animations),
+                _ => None,
+            };
+            if let Some(extras) = extras {
+                let fields = split_fields(&line[1..]);
+                let (id, values) = fields.split_first().ok_or_else(|| error("missing identifier"))?;
+                extras.push(HandlingExtraEntry {
+                    id: id.to_string(),
+                    values: values.iter().map(|value| value.to_string()).collect(),
+                });
+                continue;
+            }
+
+            let fields = split_fields(line);
+            let line_version = HandlingVersion::from_field_count(fields.len())
+                .ok_or_else(|| error(&format!("unexpected value count {}", fields.len())))?;
+            if *version.get_or_insert(line_version) != line_version {
+                return Err(error("vehicle line from a different game"));
+            }
+            vehicles.push(parse_vehicle(line_version, &fields).ok_or_else(|| error("invalid vehicle line"))?);
+        }
+
+        let version = version.ok_or_else(|| Error::new(ErrorKind::InvalidData, "handling.cfg has no vehicle lines"))?;
+        Ok(RwHandling { version, vehicles, boats, bikes, flying, animations })
+    }
+}
+
+fn parse_vehicle(version: HandlingVersion, fields: &[&str]) -> Option<HandlingEntry> {
+    let f = |index: usize| fields.get(index)?.parse::<f32>().ok();
+    let i = |index: usize| fields.get(index)?.parse::<f32>().ok().map(|value| value as i32);
+    let c = |index: usize| fields.get(index)?.chars().next();
+    let hex = |index: usize| u32::from_str_radix(fields.get(index)?, 16).ok();
+    let vector = |start: usize| Some(RwVector3 { x: f(start)?, y: f(start + 1)?, z: f(start + 2)? });
+
+    // Fields are read in file order; `next` tracks the index of the next one
+    let mut next = 1;
+    let mut take = |count: usize| {
+        next += count;
+        next - count
+    };
+
+    let mass = f(take(1))?;
+    let (turn_mass, drag_multiplier, dimensions) = match version {
+        HandlingVersion::SanAndreas => (Some(f(take(1))?), Some(f(take(1))?), None),
+        _ => (None, None, Some(vector(take(3))?)),
+    };
+    let centre_of_mass = vector(take(3))?;
+    let percent_submerged = i(take(1))?;
+    let traction_multiplier = f(take(1))?;
+    let traction_loss = f(take(1))?;
+    let traction_bias = f(take(1))?;
+    let gear_count = i(take(1))? as u8;
+    let max_velocity = f(take(1))?;
+    let engine_acceleration = f(take(1))?;
+    let engine_inertia = if version == HandlingVersion::SanAndreas { Some(f(take(1))?) } else { None };
+    let drive_type = c(take(1))?;
+    let engine_type = c(take(1))?;
+    let brake_deceleration = f(take(1))?;
+    let brake_bias = f(take(1))?;
+    let abs = i(take(1))? != 0;
+    let steering_lock = f(take(1))?;
+    let suspension_force = f(take(1))?;
+    let suspension_damping = f(take(1))?;
+    let suspension_high_speed_damping = if version == HandlingVersion::SanAndreas { Some(f(take(1))?) } else { None };
+
+    // GTA3 and Vice City store the seat offset and value before the
+    // suspension limits, San Andreas after them
+    let mut seat_offset = 0.0;
+    let mut collision_damage_multiplier = 0.0;
+    let mut monetary_value = 0;
+    if version != HandlingVersion::SanAndreas {
+        seat_offset = f(take(1))?;
+        collision_damage_multiplier = f(take(1))?;
+        monetary_value = i(take(1))?;
+    }
+    let suspension_upper_limit = f(take(1))?;
+    let suspension_lower_limit = f(take(1))?;
+    let suspension_bias = f(take(1))?;
+    let suspension_anti_dive = if version == HandlingVersion::Gta3 { None } else { Some(f(take(1))?) };
+    if version == HandlingVersion::SanAndreas {
+        seat_offset = f(take(1))?;
+        collision_damage_multiplier = f(take(1))?;
+        monetary_value = i(take(1))?;
+    }
+    let model_flags = if version == HandlingVersion::SanAndreas { Some(hex(take(1))?) } else { None };
+    let handling_flags = hex(take(1))?;
+    let front_lights = i(take(1))? as u8;
+    let rear_lights = i(take(1))? as u8;
+    let anim_group = if version == HandlingVersion::SanAndreas { Some(i(take(1))? as u8) } else { None };
+
+    Some(HandlingEntry {
+        id: fields[0].to_string(),
+        mass,
+        turn_mass,
+        drag_multiplier,
+        dimensions,
+        centre_of_mass,
+        percent_submerged,
+        traction_multiplier,
+        traction_loss,
+        traction_bias,
+        gear_count,
+        max_velocity,
+        engine_acceleration,
+        engine_inertia,
+        drive_type,
+        engine_type,
+        brake_deceleration,
+        brake_bias,
+        abs,
+        steering_lock,
+        suspension_force,
+        suspension_damping,
+        suspension_high_speed_damping,
+        suspension_upper_limit,
+        suspension_lower_limit,
+        suspension_bias,
+        suspension_anti_dive,
+        seat_offset,
+        collision_damage_multiplier,
+        monetary_value,
+        model_flags,
+        handling_flags,
+        front_lights,
+        rear_lights,
+        anim_group,
+    })
+}
@@ -0,0 +1,5 @@
+pub mod gta_dat;
+pub mod water_dat;
+pub mod timecyc_dat;
+pub mod handling_cfg;
+pub mod carcols_dat;
\ No newline at end of file
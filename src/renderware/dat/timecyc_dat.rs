@@ -0,0 +1,268 @@
+//! # timecyc.dat Parser
+//!
+//! A parser for the time cycle files (`timecyc.dat`) of Grand Theft Auto 3,
+//! Vice City, and San Andreas, which define the lighting, sky and fog colors
+//! of every weather at key hours of the day.
+//!
+//! ## Features
+//!
+//! - Detects the game from the layout of the file.
+//! - Decodes the colors and values shared by all three games into typed
+//!   fields, and keeps every value of the line.
+//! - Looks up the entry of a weather and hour.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dat::timecyc_dat::TimecycParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/timecyc.dat").unwrap();
+//! let timecyc = TimecycParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Noon sky: {:?}", timecyc.get(0, 12).map(|entry| entry.sky_top));
+//! ```
+
+use crate::renderware::common::text::{split_fields, strip_comment};
+use crate::renderware::common::types::RwColor;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// The game a time cycle file belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TimecycVersion {
+    /// GTA3: 4 weathers, 24 hours, 40 values per line.
+    Gta3,
+    /// Vice City: 7 weathers, 24 hours, 52 values per line.
+    ViceCity,
+    /// San Andreas: 23 weathers, 8 hours, 52 values per line.
+    SanAndreas,
+}
+
+const GTA3_WEATHERS: [&str; 4] = ["SUNNY", "CLOUDY", "RAINY", "FOGGY"];
+
+const VICE_CITY_WEATHERS: [&str; 7] = ["SUNNY", "CLOUDY", "RAINY", "FOGGY", "EXTRASUNNY", "HURRICANE", "EXTRACOLOURS"];
+
+const SAN_ANDREAS_WEATHERS: [&str; 23] = [
+    "EXTRASUNNY_LA", "SUNNY_LA", "EXTRASUNNY_SMOG_LA", "SUNNY_SMOG_LA", "CLOUDY_LA",
+    "SUNNY_SF", "EXTRASUNNY_SF", "CLOUDY_SF", "RAINY_SF", "FOGGY_SF",
+    "SUNNY_VEGAS", "EXTRASUNNY_VEGAS", "CLOUDY_VEGAS",
+    "EXTRASUNNY_COUNTRYSIDE", "SUNNY_COUNTRYSIDE", "CLOUDY_COUNTRYSIDE", "RAINY_COUNTRYSIDE",
+    "EXTRASUNNY_DESERT", "SUNNY_DESERT", "SANDSTORM_DESERT",
+    "UNDERWATER", "EXTRACOLOURS_1", "EXTRACOLOURS_2",
+];
+
+/// The hours of the eight lines of each San Andreas weather.
+const SAN_ANDREAS_HOURS: [u8; 8] = [0, 5, 6, 7, 12, 19, 20, 22];
+
+impl TimecycVersion {
+    /// The weather names, in file order.
+    pub fn weathers(self) -> &'static [&'static str] {
+        match self {
+            TimecycVersion::Gta3 => &GTA3_WEATHERS,
+            TimecycVersion::ViceCity => &VICE_CITY_WEATHERS,
+            TimecycVersion::SanAndreas => &SAN_ANDREAS_WEATHERS,
+        }
+    }
+
+    /// The hour of each line of a weather, in file order.
+    pub fn hours(self) -> Vec<u8> {
+        match self {
+            TimecycVersion::SanAndreas => SAN_ANDREAS_HOURS.to_vec(),
+            _ => (0..24).collect(),
+        }
+    }
+
+    fn layout(self) -> &'static TimecycLayout {
+        match self {
+            TimecycVersion::Gta3 => &GTA3_LAYOUT,
+            TimecycVersion::ViceCity => &VICE_CITY_LAYOUT,
+            TimecycVersion::SanAndreas => &SAN_ANDREAS_LAYOUT,
+        }
+    }
+}
+
+/// The index of the first value of each typed field in a line.
+struct TimecycLayout {
+    ambient_object: Option<usize>,
+    directional: usize,
+    sky_top: usize,
+    /// The nine values from the sun size to the light on ground.
+    scalars: usize,
+    low_clouds: usize,
+    bottom_clouds: usize,
+    water: Option<usize>,
+}
+
+const GTA3_LAYOUT: TimecycLayout = TimecycLayout {
+    ambient_object: None,
+    directional: 3,
+    sky_top: 6,
+    scalars: 18,
+    low_clouds: 27,
+    bottom_clouds: 33,
+    water: None,
+};
+
+const VICE_CITY_LAYOUT: TimecycLayout = TimecycLayout {
+    ambient_object: Some(3),
+    directional: 12,
+    sky_top: 15,
+    scalars: 27,
+    low_clouds: 36,
+    bottom_clouds: 42,
+    water: Some(48),
+};
+
+const SAN_ANDREAS_LAYOUT: TimecycLayout = TimecycLayout {
+    ambient_object: Some(3),
+    directional: 6,
+    sky_top: 9,
+    scalars: 21,
+    low_clouds: 30,
+    bottom_clouds: 33,
+    water: Some(36),
+};
+
+/// Represents a parsed time cycle file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwTimecyc {
+    pub version: TimecycVersion,
+    /// The entries, grouped by weather in file order.
+    pub entries: Vec<TimecycEntry>,
+}
+
+/// The values of a weather at one hour. Colors are opaque unless noted.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimecycEntry {
+    /// The index of the weather in `TimecycVersion::weathers()`.
+    pub weather: usize,
+    pub hour: u8,
+    pub ambient: RwColor,
+    /// The ambient color of objects, absent in GTA3.
+    pub ambient_object: Option<RwColor>,
+    pub directional: RwColor,
+    pub sky_top: RwColor,
+    pub sky_bottom: RwColor,
+    pub sun_core: RwColor,
+    pub sun_corona: RwColor,
+    pub sun_size: f32,
+    pub sprite_size: f32,
+    pub sprite_brightness: f32,
+    pub shadow_intensity: f32,
+    pub light_shadow: f32,
+    /// The shadow intensity of poles, or of trees in GTA3.
+    pub pole_shadow: f32,
+    pub far_clip: f32,
+    pub fog_start: f32,
+    pub light_on_ground: f32,
+    pub low_clouds: RwColor,
+    pub bottom_clouds: RwColor,
+    /// The water color with its alpha, absent in GTA3.
+    pub water: Option<RwColor>,
+    /// Every value of the line, including those without a typed field.
+    pub values: Vec<f32>,
+}
+
+impl RwTimecyc {
+    /// Returns the entry of a weather that applies at the given hour: the
+    /// last entry at or before it.
+    pub fn get(&self, weather: usize, hour: u8) -> Option<&TimecycEntry> {
+        self.entries.iter().rev().find(|entry| entry.weather == weather && entry.hour <= hour)
+    }
+
+    pub fn weather_name(&self, weather: usize) -> Option<&'static str> {
+        self.version.weathers().get(weather).copied()
+    }
+}
+
+/// The main parser for time cycle files.
+pub struct TimecycParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> TimecycParser<'a> {
+    /// Creates a new `TimecycParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw time cycle data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        TimecycParser { buffer }
+    }
+
+    /// Parses the time cycle file. GTA3 is detected by its 40 values per
+    /// line, and San Andreas by its line count not being a whole number of
+    /// days.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwTimecyc` data or an
+    /// `std::io::Error` if the layout is not recognized.
+    pub fn parse(&mut self) -> Result<RwTimecyc> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut lines = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = strip_comment(line);
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let values = split_fields(line)
+                .iter()
+                .map(|field| field.parse::<f32>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| Error::new(ErrorKind::InvalidData, format!("timecyc.dat line {}: invalid value", index + 1)))?;
+            lines.push(values);
+        }
+
+        let field_count = lines.iter().map(|values| values.len()).min().unwrap_or(0);
+        let version = match field_count {
+            40..=51 => TimecycVersion::Gta3,
+            52.. if lines.len().is_multiple_of(24) => TimecycVersion::ViceCity,
+            52.. => TimecycVersion::SanAndreas,
+            _ => return Err(Error::new(ErrorKind::InvalidData, format!("Unsupported timecyc.dat layout with {} values per line", field_count))),
+        };
+
+        let hours = version.hours();
+        let entries = lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, values)| read_entry(version, index / hours.len(), hours[index % hours.len()], values))
+            .collect();
+
+        Ok(RwTimecyc { version, entries })
+    }
+}
+
+fn read_entry(version: TimecycVersion, weather: usize, hour: u8, values: Vec<f32>) -> TimecycEntry {
+    let layout = version.layout();
+    let byte = |index: usize| values[index].clamp(0.0, 255.0) as u8;
+    let color = |index: usize| RwColor { r: byte(index), g: byte(index + 1), b: byte(index + 2), a: 255 };
+    let scalar = |offset: usize| values[layout.scalars + offset];
+
+    TimecycEntry {
+        weather,
+        hour,
+        ambient: color(0),
+        ambient_object: layout.ambient_object.map(color),
+        directional: color(layout.directional),
+        sky_top: color(layout.sky_top),
+        sky_bottom: color(layout.sky_top + 3),
+        sun_core: color(layout.sky_top + 6),
+        sun_corona: color(layout.sky_top + 9),
+        sun_size: scalar(0),
+        sprite_size: scalar(1),
+        sprite_brightness: scalar(2),
+        shadow_intensity: scalar(3),
+        light_shadow: scalar(4),
+        pole_shadow: scalar(5),
+        far_clip: scalar(6),
+        fog_start: scalar(7),
+        light_on_ground: scalar(8),
+        low_clouds: color(layout.low_clouds),
+        bottom_clouds: color(layout.bottom_clouds),
+        water: layout.water.map(|index| RwColor { a: byte(index + 3), ..color(index) }),
+        values,
+    }
+}
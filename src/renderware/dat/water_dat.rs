@@ -0,0 +1,144 @@
+//! # water.dat Parser
+//!
+//! A parser for the water definitions of Grand Theft Auto 3, Vice City, and
+//! San Andreas (`water.dat`).
+//!
+//! ## Features
+//!
+//! - Parses the water quads and triangles of San Andreas, with the flow
+//!   speed and wave height of each vertex.
+//! - Parses the water level rectangles of GTA3 and Vice City.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dat::water_dat::WaterDatParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/water.dat").unwrap();
+//! let water = WaterDatParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Water polygons: {}", water.polygons.len());
+//! ```
+
+use crate::renderware::common::text::{split_fields, strip_comment};
+use crate::renderware::common::types::RwVector3;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// Represents a parsed water file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwWaterDat {
+    /// The quads and triangles of San Andreas.
+    pub polygons: Vec<WaterPolygon>,
+    /// The water level rectangles of GTA3 and Vice City.
+    pub rectangles: Vec<WaterRectangle>,
+}
+
+/// A water quad or triangle.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WaterPolygon {
+    /// Three or four vertices.
+    pub vertices: Vec<WaterVertex>,
+    /// 1 for visible water, 0 for invisible water.
+    pub flags: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WaterVertex {
+    pub position: RwVector3,
+    /// The flow speed along the X and Y axes.
+    pub speed: [f32; 2],
+    pub unknown: f32,
+    pub wave_height: f32,
+}
+
+/// An axis-aligned area of water at a fixed height.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WaterRectangle {
+    pub height: f32,
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl WaterPolygon {
+    pub fn is_quad(&self) -> bool {
+        self.vertices.len() == 4
+    }
+}
+
+/// The main parser for water files.
+pub struct WaterDatParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> WaterDatParser<'a> {
+    /// Creates a new `WaterDatParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw water file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        WaterDatParser { buffer }
+    }
+
+    /// Parses the water file. Lines that do not start with a number, such as
+    /// the `processed` header of San Andreas, are skipped.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwWaterDat` data or an
+    /// `std::io::Error` if a line has an unexpected number of fields.
+    pub fn parse(&mut self) -> Result<RwWaterDat> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut water = RwWaterDat::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let fields = split_fields(strip_comment(line));
+            if fields.first().is_none_or(|field| field.parse::<f32>().is_err()) {
+                continue;
+            }
+
+            let error = || Error::new(ErrorKind::InvalidData, format!("water.dat line {}: invalid water entry", index + 1));
+            match fields.len() {
+                5 => water.rectangles.push(parse_rectangle(&fields).ok_or_else(error)?),
+                22 | 29 => water.polygons.push(parse_polygon(&fields).ok_or_else(error)?),
+                _ => return Err(error()),
+            }
+        }
+
+        Ok(water)
+    }
+}
+
+fn parse_polygon(fields: &[&str]) -> Option<WaterPolygon> {
+    let values = fields.iter().map(|field| field.parse::<f32>().ok()).collect::<Option<Vec<_>>>()?;
+    let (flags, vertex_values) = values.split_last()?;
+
+    let vertices = vertex_values
+        .chunks_exact(7)
+        .map(|vertex| WaterVertex {
+            position: RwVector3 { x: vertex[0], y: vertex[1], z: vertex[2] },
+            speed: [vertex[3], vertex[4]],
+            unknown: vertex[5],
+            wave_height: vertex[6],
+        })
+        .collect();
+
+    Some(WaterPolygon { vertices, flags: *flags as u32 })
+}
+
+fn parse_rectangle(fields: &[&str]) -> Option<WaterRectangle> {
+    let f = |index: usize| fields.get(index)?.parse::<f32>().ok();
+
+    Some(WaterRectangle {
+        height: f(0)?,
+        min_x: f(1)?,
+        min_y: f(2)?,
+        max_x: f(3)?,
+        max_y: f(4)?,
+    })
+}
@@ -0,0 +1,118 @@
+//! # Flat Geometry
+//!
+//! An alternative output layout of `DffParser::parse_flat`, which stores the
+//! vertex attributes of each geometry in flat buffers ready to be uploaded to
+//! the GPU, instead of one `Vec` of structs per attribute, UV channel and
+//! skinned vertex.
+//!
+//! The buffers come from a `ParserScratch`, so parsing many models in a row,
+//! such as every vehicle of a game, reuses the same allocations once the
+//! previous results are handed back with `ParserScratch::recycle`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_flat::ParserScratch;
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use std::fs;
+//!
+//! let mut scratch = ParserScratch::new();
+//! for path in ["path/to/infernus.dff", "path/to/cheetah.dff"] {
+//!     let buffer = fs::read(path).unwrap();
+//!     let flat = DffParser::new(&buffer).parse_flat(&mut scratch).unwrap();
+//!     for geometry in &flat.geometries {
+//!         println!("{} vertices, {} triangles", geometry.vertex_count, geometry.triangle_count());
+//!     }
+//!     scratch.recycle(flat);
+//! }
+//! ```
+
+use crate::renderware::dff::dff_parser::RwDff;
+
+use serde::Serialize;
+
+/// The vertex attributes and triangles of a geometry in flat buffers. Every
+/// buffer is empty when the geometry does not have the attribute.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwFlatGeometry {
+    pub vertex_count: usize,
+    /// The x, y and z of every vertex.
+    pub positions: Vec<f32>,
+    /// The x, y and z of every vertex.
+    pub normals: Vec<f32>,
+    /// The r, g, b and a of every vertex.
+    pub colors: Vec<u8>,
+    /// The u and v of every vertex, one UV channel after the other.
+    pub uvs: Vec<f32>,
+    pub uv_channels: usize,
+    /// The three vertex indices of every triangle, in the order of
+    /// `RwTriangle::vector`.
+    pub indices: Vec<u16>,
+    /// The material of every triangle.
+    pub material_ids: Vec<u16>,
+    /// The four bone indices of every vertex, for skinned geometries.
+    pub bone_indices: Vec<u8>,
+    /// The four bone weights of every vertex, for skinned geometries.
+    pub bone_weights: Vec<f32>,
+}
+
+impl RwFlatGeometry {
+    pub fn triangle_count(&self) -> usize {
+        self.material_ids.len()
+    }
+
+    /// Returns the u and v of every vertex for a UV channel.
+    pub fn uv_channel(&self, channel: usize) -> Option<&[f32]> {
+        let size = self.vertex_count * 2;
+        self.uvs.get(channel * size..(channel + 1) * size)
+    }
+
+    /// Empties every buffer, keeping the allocations.
+    pub fn clear(&mut self) {
+        self.vertex_count = 0;
+        self.positions.clear();
+        self.normals.clear();
+        self.colors.clear();
+        self.uvs.clear();
+        self.uv_channels = 0;
+        self.indices.clear();
+        self.material_ids.clear();
+        self.bone_indices.clear();
+        self.bone_weights.clear();
+    }
+}
+
+/// A DFF parsed by `DffParser::parse_flat`.
+///
+/// The geometries of `dff` keep their material list, bin mesh, bounding
+/// sphere and skin bones, but their vertex attributes, triangles and skin
+/// weights are empty. They are in `geometries`, at the same index.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwFlatDff {
+    pub dff: RwDff,
+    pub geometries: Vec<RwFlatGeometry>,
+}
+
+/// Buffers reused between calls to `DffParser::parse_flat`.
+#[derive(Debug, Default)]
+pub struct ParserScratch {
+    pub(crate) spare: Vec<RwFlatGeometry>,
+}
+
+impl ParserScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes back the buffers of a parsed DFF for the next parse.
+    pub fn recycle(&mut self, flat: RwFlatDff) {
+        self.spare.extend(flat.geometries);
+    }
+
+    /// Returns empty buffers for a geometry, reusing spare ones if any.
+    pub(crate) fn take(&mut self) -> RwFlatGeometry {
+        let mut geometry = self.spare.pop().unwrap_or_default();
+        geometry.clear();
+        geometry
+    }
+}
@@ -0,0 +1,254 @@
+//! # Geometry Editing
+//!
+//! Helpers for changing the vertices and triangles of a parsed geometry
+//! while keeping its per-vertex data and bin mesh consistent.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use std::fs;
+//!
+//! let mut dff = DffParser::new(&fs::read("path/to/bmyst.dff").unwrap()).parse().unwrap();
+//! for geometry in &mut dff.geometry_list.as_mut().unwrap().geometries {
+//!     geometry.optimize_for_gpu();
+//! }
+//! ```
+
+use super::dff_parser::{RwBinMesh, RwGeometry, RwMesh};
+use crate::renderware::common::types::RwTriangle;
+
+/// The number of vertices of the simulated post-transform cache.
+const CACHE_SIZE: usize = 32;
+/// Forsyth's scoring constants, tuned for the caches of current GPUs.
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+impl RwGeometry {
+    /// Returns the number of vertices of the geometry.
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_information.len()
+    }
+
+    /// Appends a copy of a vertex with all its per-vertex data: normal,
+    /// prelit color, texture coordinates and skin weights.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new vertex.
+    pub fn duplicate_vertex(&mut self, index: usize) -> usize {
+        let vertex_count = self.vertex_count();
+        fn push_copy<T: Clone>(values: &mut Vec<T>, index: usize, vertex_count: usize) {
+            // Arrays that do not cover every vertex are left alone
+            if values.len() == vertex_count {
+                let value = values[index].clone();
+                values.push(value);
+            }
+        }
+
+        push_copy(&mut self.vertex_information, index, vertex_count);
+        push_copy(&mut self.normal_information, index, vertex_count);
+        push_copy(&mut self.vertex_color_information, index, vertex_count);
+        for uvs in &mut self.texture_mapping_information {
+            push_copy(uvs, index, vertex_count);
+        }
+        if let Some(skin) = &mut self.skin {
+            push_copy(&mut skin.bone_vertex_indices, index, vertex_count);
+            push_copy(&mut skin.vertex_weights, index, vertex_count);
+        }
+        vertex_count
+    }
+
+    /// Rebuilds the bin mesh from the triangles, as one triangle list per
+    /// material in material order.
+    pub fn rebuild_bin_mesh(&mut self) {
+        let meshes: Vec<RwMesh> = (0..self.material_list.material_data.len())
+            .map(|material| {
+                let indices: Vec<u32> = self
+                    .triangle_information
+                    .iter()
+                    .filter(|triangle| triangle.material_id as usize == material)
+                    .flat_map(|triangle| [triangle.vector.x as u32, triangle.vector.y as u32, triangle.vector.z as u32])
+                    .collect();
+                RwMesh { material_index: material as u32, index_count: indices.len() as u32, indices }
+            })
+            .filter(|mesh| mesh.index_count > 0)
+            .collect();
+        self.bin_mesh = RwBinMesh { mesh_count: meshes.len() as u32, meshes };
+    }
+
+    /// Reorders the triangles and vertices of the geometry for the vertex
+    /// caches of current GPUs.
+    ///
+    /// The triangles of each material are reordered with Tom Forsyth's
+    /// linear-speed vertex cache optimisation, then the vertices are
+    /// renumbered in the order they are first used, so they are also read
+    /// sequentially. All per-vertex data, including skin weights, follows
+    /// the vertices, and the bin mesh is rebuilt as triangle lists. The
+    /// rendered result is unchanged.
+    pub fn optimize_for_gpu(&mut self) {
+        let vertex_count = self.vertex_count();
+        let (valid, invalid): (Vec<RwTriangle>, Vec<RwTriangle>) = self
+            .triangle_information
+            .iter()
+            .partition(|triangle| corners(triangle).iter().all(|&corner| corner < vertex_count));
+
+        let mut material_ids: Vec<u16> = valid.iter().map(|triangle| triangle.material_id).collect();
+        material_ids.sort_unstable();
+        material_ids.dedup();
+        let mut triangles: Vec<RwTriangle> = Vec::with_capacity(self.triangle_information.len());
+        for material_id in material_ids {
+            let group: Vec<RwTriangle> = valid.iter().copied().filter(|triangle| triangle.material_id == material_id).collect();
+            triangles.extend(forsyth_order(&group, vertex_count).into_iter().map(|index| group[index]));
+        }
+
+        // Number the vertices by first use, keeping unused ones at the end
+        let mut remap = vec![usize::MAX; vertex_count];
+        let mut order: Vec<usize> = Vec::with_capacity(vertex_count);
+        for triangle in &triangles {
+            for corner in corners(triangle) {
+                if remap[corner] == usize::MAX {
+                    remap[corner] = order.len();
+                    order.push(corner);
+                }
+            }
+        }
+        for (vertex, slot) in remap.iter_mut().enumerate() {
+            if *slot == usize::MAX {
+                *slot = order.len();
+                order.push(vertex);
+            }
+        }
+        self.reorder_vertices(&order);
+
+        for triangle in &mut triangles {
+            let [a, b, c] = corners(triangle);
+            triangle.vector.x = remap[a] as f32;
+            triangle.vector.y = remap[b] as f32;
+            triangle.vector.z = remap[c] as f32;
+        }
+        // Triangles with out of range indices cannot be placed, so they are
+        // kept as they are after the others
+        triangles.extend(invalid);
+        self.triangle_information = triangles;
+        self.rebuild_bin_mesh();
+    }
+
+    /// Reorders the vertices and their per-vertex data, where `order` lists
+    /// the old index of each new vertex. Vertices left out of `order` are
+    /// removed. Triangles are not updated.
+    pub(crate) fn reorder_vertices(&mut self, order: &[usize]) {
+        let vertex_count = self.vertex_count();
+        fn permute<T: Clone>(values: &mut Vec<T>, order: &[usize], vertex_count: usize) {
+            if values.len() == vertex_count {
+                *values = order.iter().map(|&index| values[index].clone()).collect();
+            }
+        }
+
+        permute(&mut self.vertex_information, order, vertex_count);
+        permute(&mut self.normal_information, order, vertex_count);
+        permute(&mut self.vertex_color_information, order, vertex_count);
+        for uvs in &mut self.texture_mapping_information {
+            permute(uvs, order, vertex_count);
+        }
+        if let Some(skin) = &mut self.skin {
+            permute(&mut skin.bone_vertex_indices, order, vertex_count);
+            permute(&mut skin.vertex_weights, order, vertex_count);
+        }
+    }
+}
+
+fn corners(triangle: &RwTriangle) -> [usize; 3] {
+    [triangle.vector.x as usize, triangle.vector.y as usize, triangle.vector.z as usize]
+}
+
+/// Scores a vertex by its position in the simulated cache and the number of
+/// triangles still using it.
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        // The last triangle's vertices get a fixed score, so the next
+        // triangle does not simply reuse the same edge
+        Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+        Some(position) => (1.0 - (position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(CACHE_DECAY_POWER),
+        None => 0.0,
+    };
+    cache_score + VALENCE_BOOST_SCALE * (remaining as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+/// Returns the order in which to draw triangles, whose indices must all be
+/// below `vertex_count`, for the fewest vertex cache misses.
+fn forsyth_order(triangles: &[RwTriangle], vertex_count: usize) -> Vec<usize> {
+    let mut vertex_triangles: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    for (index, triangle) in triangles.iter().enumerate() {
+        for corner in corners(triangle) {
+            vertex_triangles[corner].push(index);
+        }
+    }
+    let mut remaining: Vec<usize> = vertex_triangles.iter().map(Vec::len).collect();
+    let mut cache_positions: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut vertex_scores: Vec<f32> = remaining.iter().map(|&count| vertex_score(None, count)).collect();
+    let triangle_score = |scores: &[f32], triangle: &RwTriangle| corners(triangle).iter().map(|&corner| scores[corner]).sum::<f32>();
+    let mut triangle_scores: Vec<f32> = triangles.iter().map(|triangle| triangle_score(&vertex_scores, triangle)).collect();
+    let mut added = vec![false; triangles.len()];
+
+    let mut order = Vec::with_capacity(triangles.len());
+    let mut cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut next_unadded = 0;
+    while order.len() < triangles.len() {
+        // Prefer the best triangle using a cached vertex, and fall back to
+        // the next triangle not drawn yet
+        let best = cache
+            .iter()
+            .flat_map(|&vertex| &vertex_triangles[vertex])
+            .copied()
+            .filter(|&triangle| !added[triangle])
+            .max_by(|&a, &b| triangle_scores[a].total_cmp(&triangle_scores[b]));
+        let best = best.unwrap_or_else(|| {
+            while added[next_unadded] {
+                next_unadded += 1;
+            }
+            next_unadded
+        });
+
+        added[best] = true;
+        order.push(best);
+        let triangle_corners = corners(&triangles[best]);
+        for corner in triangle_corners {
+            remaining[corner] -= 1;
+            if let Some(position) = vertex_triangles[corner].iter().position(|&triangle| triangle == best) {
+                vertex_triangles[corner].swap_remove(position);
+            }
+        }
+
+        // Move the triangle's vertices to the front of the cache
+        let mut new_cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+        for corner in triangle_corners {
+            if !new_cache.contains(&corner) {
+                new_cache.push(corner);
+            }
+        }
+        new_cache.extend(cache.iter().copied().filter(|vertex| !triangle_corners.contains(vertex)));
+        for (position, &vertex) in new_cache.iter().enumerate() {
+            cache_positions[vertex] = (position < CACHE_SIZE).then_some(position);
+        }
+        new_cache.truncate(CACHE_SIZE);
+
+        // Rescore the vertices whose cache position changed, including the
+        // ones pushed out, and the triangles using them
+        for &vertex in cache.iter().chain(&new_cache) {
+            vertex_scores[vertex] = vertex_score(cache_positions[vertex], remaining[vertex]);
+        }
+        for &vertex in cache.iter().chain(&new_cache) {
+            for &triangle in &vertex_triangles[vertex] {
+                triangle_scores[triangle] = triangle_score(&vertex_scores, &triangles[triangle]);
+            }
+        }
+        cache = new_cache;
+    }
+    order
+}
@@ -0,0 +1,69 @@
+//! # Material Hints
+//!
+//! Guesses how a material should be rendered by engines that need to know up
+//! front, such as glTF viewers: whether it is opaque, a cutout to alpha test
+//! or translucent, and whether its back faces should be drawn. RenderWare
+//! leaves these decisions to the game, so every converter would otherwise
+//! make them on its own.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/tree.dff").unwrap()).parse().unwrap();
+//! let txd = TxdParser::new(&fs::read("path/to/tree.txd").unwrap()).parse().unwrap();
+//!
+//! for geometry in &dff.geometry_list.as_ref().unwrap().geometries {
+//!     for material in &geometry.material_list.material_data {
+//!         println!("{:?}", material.hints(Some(&txd)));
+//!     }
+//! }
+//! ```
+
+use super::dff_parser::RwMaterial;
+use crate::renderware::txd::txd_parser::RwTxd;
+use serde::{Deserialize, Serialize};
+
+/// How a material's transparency should be rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlphaMode {
+    /// Alpha is ignored.
+    Opaque,
+    /// Texels below half alpha are discarded and the rest drawn opaque, as
+    /// for foliage and fences.
+    Mask,
+    /// The material is blended over what is behind it, as for glass.
+    Blend,
+}
+
+/// Rendering hints for a material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaterialHints {
+    pub alpha_mode: AlphaMode,
+    /// Whether back faces should be drawn. Transparent materials are mostly
+    /// single planes meant to be seen from both sides.
+    pub double_sided: bool,
+}
+
+impl RwMaterial {
+    /// Classifies the material's transparency from its color alpha and the
+    /// alpha content of its texture, looked up in `txd`. A material whose
+    /// texture is missing from `txd` is judged by its color alone.
+    pub fn alpha_mode(&self, txd: Option<&RwTxd>) -> AlphaMode {
+        if self.color.a < 255 {
+            return AlphaMode::Blend;
+        }
+        self.texture_name()
+            .and_then(|name| txd?.texture_dictionary.get_by_name(name))
+            .map_or(AlphaMode::Opaque, |native| native.alpha_mode())
+    }
+
+    /// Returns the rendering hints of the material.
+    pub fn hints(&self, txd: Option<&RwTxd>) -> MaterialHints {
+        let alpha_mode = self.alpha_mode(txd);
+        MaterialHints { alpha_mode, double_sided: alpha_mode != AlphaMode::Opaque }
+    }
+}
@@ -28,25 +28,35 @@ use super::dff_model_type::DffModelType;
 use crate::renderware::common::types::{
     RwColor, RwMatrix3, RwMatrix4, RwSphere, RwTextureCoordinate, RwTriangle, RwVector3, RwVector4,
 };
-use crate::renderware::rw_file::RwFile;
+use crate::renderware::rw_file::{RwFile, RwSectionHeader};
+use crate::utils::byte_stream::{ByteStream, Endianness};
 use crate::renderware::rw_sections::RwSections;
-use crate::utils::rw_version::{unpack_version, RwVersion};
-use std::io::Result;
+use crate::utils::rw_version::{unpack_version, version_string};
+use crate::renderware::rw_error::{catch_panic, RwParseError, RwResult};
+use crate::renderware::rw_report::{ParseMode, ParseReport};
+use crate::renderware::rw_source_map::{SourceItem, SourceMap};
+use crate::renderware::dff::dff_flat::{ParserScratch, RwFlatDff, RwFlatGeometry};
 use num::FromPrimitive;
+use std::io::{Read, Seek};
+#[cfg(feature = "mmap")]
+use crate::utils::mapped_file::MappedFile;
+#[cfg(feature = "mmap")]
+use std::path::Path;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Represents the top-level structure of a parsed DFF file.
 ///
 /// This struct contains all the deserialized data from a RenderWare Clump,
 /// including geometry, frame hierarchy, and metadata.
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwDff {
     /// The determined type of the model (e.g., Skin, Vehicle).
     pub model_type: DffModelType,
     /// The RenderWare version string (e.g., "3.6.0.3").
     pub version: String,
-    /// The packed integer representation of the RenderWare version.
+    /// The unpacked RenderWare version, such as `0x36003`. Files older than
+    /// 3.1.0.1 are reported the same way, as `0x31000` for a `0x310` stamp.
     pub version_number: u32,
     /// A list of geometries contained within the DFF file.
     pub geometry_list: Option<RwGeometryList>,
@@ -54,47 +64,114 @@ pub struct RwDff {
     pub frame_list: Option<RwFrameList>,
     /// Atomic data mapping geometries to frames.
     pub atomics: Vec<u32>,
+    /// The Right-To-Render data of the atomic of each geometry, indexed
+    /// like `atomics`.
+    pub atomic_pipelines: Vec<Option<RwRightToRender>>,
     /// A list of dummy object names.
     pub dummies: Vec<String>,
     /// Animation node data, typically for skinned models.
     pub anim_nodes: Vec<RwAnimNode>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+impl RwDff {
+    /// Returns the frames of the frame list, if any.
+    pub fn frames(&self) -> &[RwFrame] {
+        self.frame_list.as_ref().map_or(&[], |list| &list.frames)
+    }
+
+    /// Returns the HAnim bone id of a frame.
+    ///
+    /// The parser does not record which frame each HAnim node belongs to, so
+    /// the nodes are assumed to describe the trailing frames of the frame
+    /// list in order, which is how the game's skinned models are laid out.
+    pub fn frame_bone_id(&self, frame_index: usize) -> Option<i32> {
+        let offset = self.frames().len().saturating_sub(self.anim_nodes.len());
+        frame_index.checked_sub(offset).and_then(|index| self.anim_nodes.get(index)).map(|node| node.bone_id)
+    }
+
+    /// Returns the node name of a frame, assuming the names describe the
+    /// trailing frames like `frame_bone_id`.
+    pub fn frame_name(&self, frame_index: usize) -> Option<&str> {
+        let offset = self.frames().len().saturating_sub(self.dummies.len());
+        frame_index.checked_sub(offset).and_then(|index| self.dummies.get(index)).map(String::as_str)
+    }
+
+    /// Returns the frame of each of the `bone_count` bones of a skin.
+    ///
+    /// Skin bones follow the HAnim hierarchy, whose bone ids are matched
+    /// against `frame_bone_id`. Without a hierarchy, the bones are assumed to
+    /// be the trailing frames in order. The result is empty if there are no
+    /// frames.
+    pub fn skin_bone_frames(&self, bone_count: usize) -> Vec<usize> {
+        let frame_count = self.frames().len();
+        if frame_count == 0 {
+            return Vec::new();
+        }
+        let hierarchy = self.anim_nodes.iter().find(|node| !node.bones.is_empty());
+        (0..bone_count)
+            .map(|bone| {
+                let by_id = hierarchy
+                    .and_then(|node| node.bones.get(bone))
+                    .and_then(|bone| (0..frame_count).find(|&frame| self.frame_bone_id(frame) == Some(bone.bone_id)));
+                by_id.unwrap_or_else(|| (frame_count.saturating_sub(bone_count) + bone).min(frame_count - 1))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwClump {
     pub atomic_count: u32,
     pub light_count: Option<u32>,
     pub camera_count: Option<u32>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwAnimNode {
     pub bone_id: i32,
     pub bones_count: i32,
     pub bones: Vec<RwBone>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RwBone {
     pub bone_id: i32,
     pub bone_index: i32,
     pub flags: i32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwFrame {
     pub rotation_matrix: RwMatrix3,
     pub coordinates_offset: RwVector3,
     pub parent_frame: i32,
+    /// The entries of the frame's user data plugin.
+    pub user_data: Vec<UserDataEntry>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwFrameList {
     pub frame_count: u32,
     pub frames: Vec<RwFrame>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+/// A named array of the user data plugin, as written by exporters to keep
+/// their own metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserDataEntry {
+    pub name: String,
+    pub values: UserDataValues,
+}
+
+/// The values of a user data array.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UserDataValues {
+    Int(Vec<i32>),
+    Float(Vec<f32>),
+    String(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RwTexture {
     pub texture_filtering: u8,
     pub u_addressing: u8,
@@ -103,7 +180,7 @@ pub struct RwTexture {
     pub texture_name: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwMaterial {
     pub color: RwColor,
     pub is_textured: bool,
@@ -111,15 +188,41 @@ pub struct RwMaterial {
     pub specular: Option<f32>,
     pub diffuse: Option<f32>,
     pub texture: Option<RwTexture>,
+    /// Bump mapping from the normal map plugin, used by the mobile releases.
+    pub normal_map: Option<RwNormalMap>,
+    /// The rendering pipeline from the Right-To-Render plugin.
+    pub right_to_render: Option<RwRightToRender>,
+}
+
+impl RwMaterial {
+    /// Returns the name of the material's texture, or `None` if it is
+    /// untextured or the name is empty.
+    pub fn texture_name(&self) -> Option<&str> {
+        self.texture
+            .as_ref()
+            .filter(|texture| self.is_textured && !texture.texture_name.is_empty())
+            .map(|texture| texture.texture_name.as_str())
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+/// The textures of the RenderWare normal map plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RwNormalMap {
+    /// The tangent space normal map.
+    pub normal_texture: Option<RwTexture>,
+    /// The environment map reflected by the bumped surface.
+    pub environment_texture: Option<RwTexture>,
+    /// How strongly the environment map is reflected.
+    pub environment_coefficient: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwMaterialList {
     pub material_instance_count: u32,
     pub material_data: Vec<RwMaterial>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwGeometry {
     pub vertex_color_information: Vec<RwColor>,
     pub texture_coordinates_count: u8,
@@ -133,28 +236,67 @@ pub struct RwGeometry {
     pub material_list: RwMaterialList,
     pub bin_mesh: RwBinMesh,
     pub skin: Option<RwSkin>,
+    /// Entries of the user data plugin.
+    pub user_data: Vec<UserDataEntry>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwGeometryList {
     pub geometric_object_count: u32,
     pub geometries: Vec<RwGeometry>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RwAtomic {
     pub frame_index: u32,
     pub geometry_index: u32,
     pub flags: u32,
+    /// The rendering pipeline from the Right-To-Render plugin.
+    pub right_to_render: Option<RwRightToRender>,
+}
+
+/// The Right-To-Render plugin, which names the plugin whose pipeline
+/// renders an atomic or material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RwRightToRender {
+    /// The id of the plugin owning the pipeline, or the pipeline id itself
+    /// for the custom San Andreas pipelines.
+    pub plugin_id: u32,
+    /// Data passed to the pipeline, such as the pipeline id for the skin
+    /// and material effects plugins.
+    pub extra_data: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+/// The custom rendering pipelines of San Andreas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RwPipeline {
+    /// `CCustomBuildingPipeline`, for buildings without night colors.
+    Building = 0x53F2_0098,
+    /// `CCustomBuildingDNPipeline`, for buildings with night vertex colors.
+    BuildingNight = 0x53F2_009A,
+    /// `CCustomCarEnvMapPipeline`, for vehicles.
+    Vehicle = 0x53F2_009C,
+}
+
+impl RwRightToRender {
+    /// Returns the custom San Andreas pipeline, if the plugin id names one.
+    pub fn pipeline(&self) -> Option<RwPipeline> {
+        match self.plugin_id {
+            0x53F2_0098 => Some(RwPipeline::Building),
+            0x53F2_009A => Some(RwPipeline::BuildingNight),
+            0x53F2_009C => Some(RwPipeline::Vehicle),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwBinMesh {
     pub mesh_count: u32,
     pub meshes: Vec<RwMesh>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwSkin {
     pub bone_count: u8,
     pub used_bone_count: u8,
@@ -162,9 +304,42 @@ pub struct RwSkin {
     pub bone_vertex_indices: Vec<Vec<u8>>,
     pub vertex_weights: Vec<Vec<f32>>,
     pub inverse_bone_matrices: Vec<RwMatrix4>,
+    /// The bones that influence at least one vertex.
+    pub used_bones: Vec<u8>,
+    /// The hardware skinning split data stored after the matrices since
+    /// RenderWare 3.4.0.3, `None` for older files.
+    pub split_data: Option<RwSkinSplitData>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+/// The data San Andreas and later skins store to split a geometry into
+/// meshes whose bones fit the bone palette of a hardware skinning shader.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RwSkinSplitData {
+    /// The most bones a split mesh may use, or 0 if the skin is not split.
+    pub bone_limit: u32,
+    /// The palette index of each bone.
+    pub bone_remap_indices: Vec<u8>,
+    /// The runs of `bone_runs` used by each mesh.
+    pub mesh_bone_runs: Vec<RwSkinRunRange>,
+    /// Runs of consecutive bones, shared by the meshes.
+    pub bone_runs: Vec<RwSkinBoneRun>,
+}
+
+/// The runs of bones a split mesh uses, as a range of `bone_runs`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RwSkinRunRange {
+    pub start: u8,
+    pub count: u8,
+}
+
+/// A run of consecutive bones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RwSkinBoneRun {
+    pub first_bone: u8,
+    pub bone_count: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwMesh {
     pub material_index: u32,
     pub index_count: u32,
@@ -177,6 +352,31 @@ pub struct RwMesh {
 /// deserialize the DFF data.
 pub struct DffParser<'a> {
     file: RwFile<'a>,
+    mode: ParseMode,
+    report: ParseReport,
+    source_map: SourceMap,
+    /// The index of the geometry being read, for the source map.
+    geometry_index: usize,
+    flat: Option<FlatOutput>,
+}
+
+/// The parts of a clump gathered from its top-level sections.
+#[derive(Default)]
+struct ClumpSections {
+    version_number: Option<u32>,
+    atomics: Vec<u32>,
+    atomic_pipelines: Vec<Option<RwRightToRender>>,
+    dummies: Vec<String>,
+    anim_nodes: Vec<RwAnimNode>,
+    geometry_list: Option<RwGeometryList>,
+    frame_list: Option<RwFrameList>,
+    atomic_count: usize,
+}
+
+/// The flat geometries of a `parse_flat` in progress.
+struct FlatOutput {
+    scratch: ParserScratch,
+    geometries: Vec<RwFlatGeometry>,
 }
 
 impl<'a> DffParser<'a> {
@@ -188,9 +388,28 @@ impl<'a> DffParser<'a> {
     pub fn new(buffer: &'a [u8]) -> Self {
         DffParser {
             file: RwFile::new(buffer),
+            mode: ParseMode::Standard,
+            report: ParseReport::default(),
+            source_map: SourceMap::default(),
+            geometry_index: 0,
+            flat: None,
         }
     }
 
+    /// Sets the byte order of the clump, for big-endian GameCube, Wii and
+    /// PS3 assets.
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.file.get_stream().set_endianness(endianness);
+        self
+    }
+
+    /// Sets how inconsistent data is handled. In lenient mode, parsing stops
+    /// at the first damaged top-level section and keeps what was read before.
+    pub fn with_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     /// Parses the entire DFF file buffer.
     ///
     /// This method iterates through the RenderWare sections in the file,
@@ -198,76 +417,38 @@ impl<'a> DffParser<'a> {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the parsed `RwDff` data or an `std::io::Error`
+    /// A `Result` containing the parsed `RwDff` data or a `RwParseError`
     /// if the parsing fails.
-    pub fn parse(&mut self) -> Result<RwDff> {
-        let mut version: Option<String> = None;
-        let mut version_number: Option<u32> = None;
-        let mut atomics: Vec<u32> = Vec::new();
-        let mut dummies: Vec<String> = Vec::new();
-        let mut anim_nodes: Vec<RwAnimNode> = Vec::new();
-        let mut geometry_list: Option<RwGeometryList> = None;
-        let mut frame_list: Option<RwFrameList> = None;
+    pub fn parse(&mut self) -> RwResult<RwDff> {
+        self.report = ParseReport::default();
+        self.source_map = SourceMap::default();
+        let mut clump = ClumpSections::default();
 
         while self.file.get_stream().get_position() < self.file.get_stream().get_size() {
-            let header = self.file.read_section_header()?;
-
-            if header.section_type == 0 {
-                break;
-            }
-
-            if header.section_size == 0 {
-                continue;
+            let offset = self.file.get_stream().get_position();
+            match self.read_top_level_section(offset, &mut clump) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(error) if self.mode == ParseMode::Lenient => {
+                    self.report.recovered(offset, format!("Stopped at a damaged section: {}", error));
+                    break;
+                }
+                Err(error) => return Err(error),
             }
+        }
 
-            let section_type_enum: Option<RwSections> = FromPrimitive::from_u32(header.section_type);
+        let ClumpSections { version_number, atomics, atomic_pipelines, dummies, anim_nodes, geometry_list, frame_list, .. } = clump;
 
-            match section_type_enum {
-                Some(RwSections::RwClump) => {
-                    version_number = Some(unpack_version(header.version_number));
-                    version = Some(RwVersion::new().get_version_string(version_number.unwrap()).unwrap_or_default());
-                }
-                Some(RwSections::RwFrameList) => {
-                    frame_list = Some(self.read_frame_list()?);
-                }
-                Some(RwSections::RwExtension) => {
-                    let extension_header = self.file.read_section_header()?;
-                    let extension_type_enum: Option<RwSections> = FromPrimitive::from_u32(extension_header.section_type);
-                    match extension_type_enum {
-                        Some(RwSections::RwNodeName) => {
-                            dummies.push(self.file.get_stream().read_string(extension_header.section_size as usize)?);
-                        }
-                        Some(RwSections::RwAnim) => {
-                            anim_nodes.push(self.read_anim_node()?);
-                        }
-                        _ => {
-                            self.file.get_stream().skip(extension_header.section_size as u64)?;
-                        }
-                    }
-                }
-                Some(RwSections::RwGeometryList) => {
-                    geometry_list = Some(self.read_geometry_list()?);
-                }
-                Some(RwSections::RwAtomic) => {
-                    let atomic = self.read_atomic()?;
-                    if atomics.len() <= atomic.geometry_index as usize {
-                        atomics.resize(atomic.geometry_index as usize + 1, 0);
-                    }
-                    atomics[atomic.geometry_index as usize] = atomic.frame_index;
-                }
-                Some(RwSections::RwNodeName) => {
-                    dummies.push(self.file.get_stream().read_string(header.section_size as usize)?);
-                }
-                Some(RwSections::RwAnim) => {
-                    anim_nodes.push(self.read_anim_node()?);
-                }
-                _ => {
-                    self.file.get_stream().skip(header.section_size as u64)?;
-                }
-            }
+        let geometry_count = geometry_list.as_ref().map_or(0, |list| list.geometries.len());
+        if atomics.len() > geometry_count {
+            self.report.suspicious(
+                self.mode,
+                0,
+                format!("Atomics reference {} geometries, but the clump has {}", atomics.len(), geometry_count),
+            )?;
         }
 
-        let model_type = if geometry_list.as_ref().map_or(false, |g| g.geometries.iter().any(|geo| geo.skin.is_some())) {
+        let model_type = if geometry_list.as_ref().is_some_and(|g| g.geometries.iter().any(|geo| geo.skin.is_some())) {
             DffModelType::Skin
         } else if dummies.iter().any(|d| d.to_lowercase().contains("wheel") || d.to_lowercase().contains("chassis")) {
             DffModelType::Vehicle
@@ -277,23 +458,202 @@ impl<'a> DffParser<'a> {
 
         Ok(RwDff {
             model_type,
-            version: version.unwrap_or_default(),
+            version: version_number.map(|number| version_string(number).unwrap_or_default().to_string()).unwrap_or_default(),
             version_number: version_number.unwrap_or_default(),
             geometry_list,
             frame_list,
             atomics,
+            atomic_pipelines,
             dummies,
             anim_nodes,
         })
     }
 
-    fn read_frame_list(&mut self) -> Result<RwFrameList> {
-        self.file.read_section_header()?; // Struct
+    /// Reads the top-level section at `offset` into `clump`. Returns
+    /// `false` at the zero section type that ends some files.
+    fn read_top_level_section(&mut self, offset: u64, clump: &mut ClumpSections) -> RwResult<bool> {
+        let header = self.file.read_section_header()?;
+
+        if header.section_type == 0 {
+            return Ok(false);
+        }
+
+        if header.section_size == 0 {
+            return Ok(true);
+        }
+
+        let section_type_enum: Option<RwSections> = FromPrimitive::from_u32(header.section_type);
+
+        match section_type_enum {
+            Some(RwSections::RwClump) => {
+                clump.version_number = Some(unpack_version(header.version_number));
+            }
+            Some(RwSections::RwFrameList) => {
+                let end = offset + 12 + header.section_size as u64;
+                let (list, names, nodes) = self.read_frame_list(end)?;
+                clump.frame_list = Some(list);
+                clump.dummies.extend(names);
+                clump.anim_nodes.extend(nodes);
+            }
+            Some(RwSections::RwExtension) => {
+                let extension_offset = self.file.get_stream().get_position();
+                let extension_header = self.file.read_section_header()?;
+                let extension_type_enum: Option<RwSections> = FromPrimitive::from_u32(extension_header.section_type);
+                match extension_type_enum {
+                    Some(RwSections::RwNodeName) => {
+                        clump.dummies.push(self.file.get_stream().read_string(extension_header.section_size as usize)?);
+                    }
+                    Some(RwSections::RwAnim) => {
+                        clump.anim_nodes.push(self.read_anim_node()?);
+                    }
+                    _ => {
+                        self.report.skipped_section(extension_header.section_type, extension_offset);
+                        self.file.get_stream().skip(extension_header.section_size as u64)?;
+                    }
+                }
+            }
+            Some(RwSections::RwGeometryList) => {
+                clump.geometry_list = Some(self.read_geometry_list()?);
+            }
+            Some(RwSections::RwAtomic) => {
+                let atomic = self.read_atomic(offset + 12 + header.section_size as u64)?;
+                // `atomics` is indexed by geometry, so the index is bounded by
+                // the geometries read so far, or by the bytes left for the
+                // geometries still to come, before it sizes the vectors
+                match &clump.geometry_list {
+                    Some(list) if atomic.geometry_index as usize >= list.geometries.len() => {
+                        return Err(RwParseError::InconsistentData {
+                            offset,
+                            message: format!(
+                                "Atomic references geometry {} of {}",
+                                atomic.geometry_index,
+                                list.geometries.len()
+                            ),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.file.checked_capacity("atomics", atomic.geometry_index as u64 + 1, 16)?;
+                    }
+                }
+                let geometry_index = atomic.geometry_index as usize;
+                if clump.atomics.len() <= geometry_index {
+                    clump.atomics.resize(geometry_index + 1, 0);
+                }
+                clump.atomics[geometry_index] = atomic.frame_index;
+                if clump.atomic_pipelines.len() <= geometry_index {
+                    clump.atomic_pipelines.resize(geometry_index + 1, None);
+                }
+                clump.atomic_pipelines[geometry_index] = atomic.right_to_render;
+                self.source_map.record(SourceItem::Atomic(clump.atomic_count), offset, offset + 12 + header.section_size as u64);
+                clump.atomic_count += 1;
+            }
+            Some(RwSections::RwNodeName) => {
+                clump.dummies.push(self.file.get_stream().read_string(header.section_size as usize)?);
+            }
+            Some(RwSections::RwAnim) => {
+                clump.anim_nodes.push(self.read_anim_node()?);
+            }
+            _ => {
+                // The structs of containers such as the clump are not interesting
+                if header.section_type != RwSections::RwStruct as u32 {
+                    self.report.skipped_section(header.section_type, offset);
+                }
+                self.file.get_stream().skip(header.section_size as u64)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Parses the DFF file like `parse`, also returning the warnings met on
+    /// the way: unknown sections, skipped plugins and out of range indices.
+    pub fn parse_with_report(&mut self) -> RwResult<(RwDff, ParseReport)> {
+        let dff = self.parse()?;
+        Ok((dff, std::mem::take(&mut self.report)))
+    }
+
+    /// Parses the DFF file like `parse`, but stores the vertex attributes,
+    /// triangles and skin weights of the geometries in flat buffers taken
+    /// from `scratch`. See `dff_flat`.
+    pub fn parse_flat(&mut self, scratch: &mut ParserScratch) -> RwResult<RwFlatDff> {
+        self.flat = Some(FlatOutput {
+            scratch: std::mem::take(scratch),
+            geometries: Vec::new(),
+        });
+        let result = self.parse();
+        let (used, mut geometries) = self.flat.take().map_or_else(Default::default, |flat| (flat.scratch, flat.geometries));
+        *scratch = used;
+
+        // Geometries of a list dropped in lenient mode have no counterpart
+        let count = result.as_ref().map_or(0, |dff| dff.geometry_list.as_ref().map_or(0, |list| list.geometries.len()));
+        scratch.spare.extend(geometries.drain(count.min(geometries.len())..));
+        Ok(RwFlatDff { dff: result?, geometries })
+    }
+
+    /// Parses the DFF file like `parse`, also returning where each frame,
+    /// geometry, material, texture and atomic was read from.
+    pub fn parse_with_source_map(&mut self) -> RwResult<(RwDff, SourceMap)> {
+        let dff = self.parse()?;
+        Ok((dff, std::mem::take(&mut self.source_map)))
+    }
+
+    /// Parses DFF data from an untrusted source, such as an upload or a
+    /// fuzzer, in `ParseMode::Standard`. Counts that need more data than
+    /// remains are rejected before anything is allocated, and a panic is
+    /// returned as an error instead of unwinding into the caller.
+    pub fn parse_untrusted(data: &[u8]) -> RwResult<RwDff> {
+        catch_panic(|| DffParser::new(data).with_mode(ParseMode::Standard).parse())
+    }
+
+    /// Memory-maps the DFF file at `path` and parses it in place, without
+    /// reading it into a buffer first. Requires the `mmap` feature.
+    #[cfg(feature = "mmap")]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> RwResult<RwDff> {
+        let file = MappedFile::open(path)?;
+        DffParser::new(&file).parse()
+    }
+
+    /// Parses a DFF file from `reader`, starting at its current position.
+    ///
+    /// Only the sections up to and including the clump are read, so
+    /// `reader` may be an IMG archive positioned at the start of an entry.
+    ///
+    /// Unlike `TxdParser::parse_from_reader`, this does not avoid holding
+    /// the file in memory: a clump is parsed as a whole, so it is read into
+    /// one buffer with the sections before it. It only saves reading the
+    /// rest of the source.
+    pub fn parse_from_reader<R: Read + Seek>(reader: &mut R) -> RwResult<RwDff> {
+        let mut data = Vec::new();
+        loop {
+            let (header, section) = RwFile::read_section_from(reader)?;
+            if data.is_empty() {
+                data = section;
+            } else {
+                data.extend_from_slice(&section);
+            }
+            if header.section_type == RwSections::RwClump as u32 {
+                break;
+            }
+        }
+        DffParser::new(&data).parse()
+    }
+
+    /// Gives parsers of related formats access to the underlying file, so
+    /// they can reuse the material and mesh readers.
+    pub(crate) fn file(&mut self) -> &mut RwFile<'a> {
+        &mut self.file
+    }
+
+    /// Reads a frame list ending at `end` and the extensions of its frames,
+    /// also returning the node names and HAnim nodes found in them.
+    fn read_frame_list(&mut self, end: u64) -> RwResult<(RwFrameList, Vec<String>, Vec<RwAnimNode>)> {
+        self.file.expect_section(RwSections::RwStruct)?;
 
         let frame_count = self.file.get_stream().read_u32()?;
-        let mut frames = Vec::with_capacity(frame_count as usize);
+        let mut frames = Vec::with_capacity(self.file.checked_capacity("frames", frame_count as u64, 56)?);
 
-        for _ in 0..frame_count {
+        for index in 0..frame_count as usize {
+            let start = self.file.get_stream().get_position();
             let rotation_matrix = RwMatrix3 {
                 right: RwVector3 {
                     x: self.file.get_stream().read_f32()?,
@@ -325,17 +685,80 @@ impl<'a> DffParser<'a> {
                 rotation_matrix,
                 coordinates_offset,
                 parent_frame,
+                user_data: Vec::new(),
             });
+            self.source_map.record(SourceItem::Frame(index), start, self.file.get_stream().get_position());
+        }
+
+        let mut names = Vec::new();
+        let mut anim_nodes = Vec::new();
+        let limit = end.min(self.file.get_stream().get_size());
+        for frame in &mut frames {
+            let extension_start = self.file.get_stream().get_position();
+            if extension_start + 12 > limit {
+                break;
+            }
+            let header = self.file.read_section_header()?;
+            if header.section_type != RwSections::RwExtension as u32 {
+                self.file.get_stream().set_position(extension_start);
+                break;
+            }
+
+            let extension_end = (self.file.get_stream().get_position() + header.section_size as u64).min(limit);
+            self.read_plugins(extension_end, |parser, plugin, offset| {
+                match RwSections::from_u32(plugin.section_type) {
+                    Some(RwSections::RwNodeName) => names.push(parser.file.get_stream().read_string(plugin.section_size as usize)?),
+                    Some(RwSections::RwAnim) => anim_nodes.push(parser.read_anim_node()?),
+                    Some(RwSections::RwUserDataPLG) => frame.user_data = parser.read_user_data(offset)?,
+                    _ => return Ok(false),
+                }
+                Ok(true)
+            })?;
         }
 
-        Ok(RwFrameList {
+        Ok((RwFrameList {
             frame_count,
             frames,
-        })
+        }, names, anim_nodes))
     }
 
-    fn read_atomic(&mut self) -> Result<RwAtomic> {
-        self.file.read_section_header()?; // Struct
+    /// Reads the arrays of a user data plugin starting at `offset`.
+    fn read_user_data(&mut self, offset: u64) -> RwResult<Vec<UserDataEntry>> {
+        let array_count = self.file.get_stream().read_u32()?;
+        let mut entries = Vec::with_capacity(self.file.checked_capacity("user data arrays", array_count as u64, 12)?);
+        for _ in 0..array_count {
+            let name = self.read_user_data_string()?;
+            let data_type = self.file.get_stream().read_u32()?;
+            let count = self.file.get_stream().read_u32()?;
+            let capacity = self.file.checked_capacity("user data values", count as u64, 4)?;
+            let values = match data_type {
+                1 => UserDataValues::Int((0..capacity).map(|_| self.file.get_stream().read_i32()).collect::<std::io::Result<_>>()?),
+                2 => UserDataValues::Float((0..capacity).map(|_| self.file.get_stream().read_f32()).collect::<std::io::Result<_>>()?),
+                3 => UserDataValues::String((0..capacity).map(|_| self.read_user_data_string()).collect::<RwResult<_>>()?),
+                _ => {
+                    // The size of the values depends on the type, so the
+                    // arrays after this one cannot be found
+                    self.report.suspicious(self.mode, offset, format!("User data array {:?} has unknown type {}", name, data_type))?;
+                    break;
+                }
+            };
+            entries.push(UserDataEntry { name, values });
+        }
+        Ok(entries)
+    }
+
+    /// Reads a user data string, stored as its length including the
+    /// terminating zero and its characters.
+    fn read_user_data_string(&mut self) -> RwResult<String> {
+        let length = self.file.get_stream().read_u32()?;
+        self.file.checked_capacity("user data string bytes", length as u64, 1)?;
+        Ok(self.file.get_stream().read_string(length as usize)?)
+    }
+
+    /// Reads an atomic ending at `end`, with its extension if it has one.
+    fn read_atomic(&mut self, end: u64) -> RwResult<RwAtomic> {
+        let struct_size = self.file.expect_section(RwSections::RwStruct)?.section_size;
+        let struct_end = self.file.get_stream().get_position() + struct_size as u64;
 
         let frame_index = self.file.get_stream().read_u32()?;
         let geometry_index = self.file.get_stream().read_u32()?;
@@ -343,24 +766,63 @@ impl<'a> DffParser<'a> {
 
         self.file.get_stream().skip(4)?; // Skip unused bytes
 
+        let mut right_to_render = None;
+        self.file.get_stream().set_position(struct_end);
+        if struct_end + 12 <= end.min(self.file.get_stream().get_size()) {
+            let header = self.file.read_section_header()?;
+            if header.section_type == RwSections::RwExtension as u32 {
+                right_to_render = self.read_atomic_extension(header.section_size)?;
+            } else {
+                self.file.get_stream().set_position(struct_end);
+            }
+        }
+
         Ok(RwAtomic {
             frame_index,
             geometry_index,
             flags,
+            right_to_render,
+        })
+    }
+
+    /// Reads the plugins of an atomic extension of `section_size` bytes,
+    /// keeping the Right-To-Render plugin.
+    fn read_atomic_extension(&mut self, section_size: u32) -> RwResult<Option<RwRightToRender>> {
+        let end = self.file.get_stream().get_position() + section_size as u64;
+
+        let mut right_to_render = None;
+        self.read_plugins(end, |parser, plugin, _| {
+            if plugin.section_type != RwSections::RwRightToRender as u32 {
+                return Ok(false);
+            }
+            right_to_render = Some(parser.read_right_to_render()?);
+            Ok(true)
+        })?;
+        Ok(right_to_render)
+    }
+
+    fn read_right_to_render(&mut self) -> RwResult<RwRightToRender> {
+        Ok(RwRightToRender {
+            plugin_id: self.file.get_stream().read_u32()?,
+            extra_data: self.file.get_stream().read_u32()?,
         })
     }
     
-    fn read_geometry_list(&mut self) -> Result<RwGeometryList> {
-        let header = self.file.read_section_header()?; // Struct
+    fn read_geometry_list(&mut self) -> RwResult<RwGeometryList> {
+        let header = self.file.expect_section(RwSections::RwStruct)?;
 
         let geometric_object_count = self.file.get_stream().read_u32()?;
-        let mut geometries = Vec::with_capacity(geometric_object_count as usize);
+        let mut geometries = Vec::with_capacity(self.file.checked_capacity("geometries", geometric_object_count as u64, 24)?);
 
-        for _ in 0..geometric_object_count {
-            self.file.read_section_header()?; // Geometry
-            self.file.read_section_header()?; // Struct
+        for index in 0..geometric_object_count as usize {
+            let offset = self.file.get_stream().get_position();
+            self.geometry_index = index;
+            let geometry_header = self.file.expect_section(RwSections::RwGeometry)?;
             let version_number = unpack_version(header.version_number);
-            geometries.push(self.read_geometry(version_number)?);
+            geometries.push(self.read_within(&geometry_header, offset, "Geometry", |parser| {
+                parser.read_geometry(version_number)
+            })?);
+            self.source_map.record(SourceItem::Geometry(index), offset, self.file.get_stream().get_position());
         }
 
         Ok(RwGeometryList {
@@ -369,7 +831,40 @@ impl<'a> DffParser<'a> {
         })
     }
 
-    fn read_geometry(&mut self, version_number: u32) -> Result<RwGeometry> {
+    /// Reads the content of a section with `read`, keeping reads of
+    /// element counts within it. Reading past its end is suspicious, and the
+    /// parse continues at its end either way.
+    fn read_within<T>(
+        &mut self,
+        header: &RwSectionHeader,
+        offset: u64,
+        what: &str,
+        read: impl FnOnce(&mut Self) -> RwResult<T>,
+    ) -> RwResult<T> {
+        let scope = self.file.enter_section(header);
+        let result = read(self);
+        let overrun = self.file.leave_section(scope);
+        let value = result?;
+        self.report_overrun(overrun, offset, what)?;
+        Ok(value)
+    }
+
+    fn report_overrun(&mut self, overrun: u64, offset: u64, what: &str) -> RwResult<()> {
+        if overrun > 0 {
+            self.report.suspicious(
+                self.mode,
+                offset,
+                format!("{} section was read {} bytes past its end", what, overrun),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn read_geometry(&mut self, version_number: u32) -> RwResult<RwGeometry> {
+        let struct_offset = self.file.get_stream().get_position();
+        let struct_header = self.file.expect_section(RwSections::RwStruct)?;
+        let struct_scope = self.file.enter_section(&struct_header);
+
         let flags = self.file.get_stream().read_u16()?;
         let texture_coordinates_count = self.file.get_stream().read_u8()?;
         let _native_geometry_flags = self.file.get_stream().read_u8()?;
@@ -385,46 +880,97 @@ impl<'a> DffParser<'a> {
         let is_geometry_prelit = (flags & (1 << 3)) != 0;
         let is_textured_uv2 = (flags & (1 << 7)) != 0;
 
+        // In flat mode, the attributes go to these buffers instead of the geometry
+        let mut flat = self.flat.as_mut().map(|flat| flat.scratch.take());
+        if let Some(flat) = flat.as_mut() {
+            flat.vertex_count = vertex_count as usize;
+        }
+
         let mut vertex_color_information = Vec::new();
         if is_geometry_prelit {
-            for _ in 0..vertex_count {
-                vertex_color_information.push(RwColor {
-                    r: self.file.get_stream().read_u8()?,
-                    g: self.file.get_stream().read_u8()?,
-                    b: self.file.get_stream().read_u8()?,
-                    a: self.file.get_stream().read_u8()?,
-                });
+            let count = self.file.checked_capacity("vertex colors", vertex_count as u64, 4)?;
+            let colors = self.file.get_stream().read_slice(count * 4)?;
+            match flat.as_mut() {
+                Some(flat) => flat.colors.extend_from_slice(colors),
+                None => {
+                    vertex_color_information = colors
+                        .chunks_exact(4)
+                        .map(|color| RwColor { r: color[0], g: color[1], b: color[2], a: color[3] })
+                        .collect();
+                }
             }
         }
 
         let mut texture_mapping_information = Vec::new();
         if is_textured_uv1 || is_textured_uv2 {
             for _ in 0..texture_coordinates_count {
-                let mut tex_coords = Vec::new();
-                for _ in 0..vertex_count {
-                    tex_coords.push(RwTextureCoordinate {
-                        u: self.file.get_stream().read_f32()?,
-                        v: self.file.get_stream().read_f32()?,
-                    });
+                let count = self.file.checked_capacity("texture coordinates", vertex_count as u64, 8)?;
+                match flat.as_mut() {
+                    Some(flat) => {
+                        read_f32s_onto(self.file.get_stream(), count * 2, &mut flat.uvs)?;
+                        flat.uv_channels += 1;
+                    }
+                    None => {
+                        let coordinates = self.file.get_stream().read_f32_slice(count * 2)?;
+                        texture_mapping_information.push(
+                            coordinates
+                                .chunks_exact(2)
+                                .map(|coordinate| RwTextureCoordinate { u: coordinate[0], v: coordinate[1] })
+                                .collect(),
+                        );
+                    }
                 }
-                texture_mapping_information.push(tex_coords);
             }
         }
 
+        let triangles_offset = self.file.get_stream().get_position();
         let mut triangle_information = Vec::new();
-        for _ in 0..triangle_count {
-            let vertex2 = self.file.get_stream().read_u16()?;
-            let vertex1 = self.file.get_stream().read_u16()?;
-            let material_id = self.file.get_stream().read_u16()?;
-            let vertex3 = self.file.get_stream().read_u16()?;
-            triangle_information.push(RwTriangle {
-                vector: RwVector3 {
-                    x: vertex1 as f32,
-                    y: vertex2 as f32,
-                    z: vertex3 as f32,
-                },
-                material_id,
-            });
+        let mut out_of_range = 0;
+        let count = self.file.checked_capacity("triangles", triangle_count as u64, 8)?;
+        let triangles = self.file.get_stream().read_u16_slice(count * 4)?;
+        for triangle in triangles.chunks_exact(4) {
+            let [vertex2, vertex1, material_id, vertex3] = [triangle[0], triangle[1], triangle[2], triangle[3]];
+            if [vertex1, vertex2, vertex3].iter().any(|&vertex| vertex as u32 >= vertex_count) {
+                out_of_range += 1;
+            }
+            match flat.as_mut() {
+                Some(flat) => {
+                    flat.indices.extend_from_slice(&[vertex1, vertex2, vertex3]);
+                    flat.material_ids.push(material_id);
+                }
+                None => triangle_information.push(RwTriangle {
+                    vector: RwVector3 {
+                        x: vertex1 as f32,
+                        y: vertex2 as f32,
+                        z: vertex3 as f32,
+                    },
+                    material_id,
+                }),
+            }
+        }
+
+        if out_of_range > 0 && self.mode == ParseMode::Lenient && vertex_count > 0 {
+            let last = (vertex_count - 1) as f32;
+            for triangle in &mut triangle_information {
+                triangle.vector.x = triangle.vector.x.min(last);
+                triangle.vector.y = triangle.vector.y.min(last);
+                triangle.vector.z = triangle.vector.z.min(last);
+            }
+            if let Some(flat) = flat.as_mut() {
+                for index in &mut flat.indices {
+                    *index = (*index).min(last as u16);
+                }
+            }
+            self.report.recovered(
+                triangles_offset,
+                format!("Clamped {} triangles to the {} vertices of the geometry", out_of_range, vertex_count),
+            );
+        } else if out_of_range > 0 {
+            self.report.suspicious(
+                self.mode,
+                triangles_offset,
+                format!("{} triangles reference vertices past the {} of the geometry", out_of_range, vertex_count),
+            )?;
         }
 
         let bounding_sphere = Some(RwSphere {
@@ -441,38 +987,54 @@ impl<'a> DffParser<'a> {
 
         let mut vertex_information = Vec::new();
         if has_vertices {
-            for _ in 0..vertex_count {
-                vertex_information.push(RwVector3 {
-                    x: self.file.get_stream().read_f32()?,
-                    y: self.file.get_stream().read_f32()?,
-                    z: self.file.get_stream().read_f32()?,
-                });
+            let count = self.file.checked_capacity("vertices", vertex_count as u64, 12)?;
+            match flat.as_mut() {
+                Some(flat) => read_f32s_onto(self.file.get_stream(), count * 3, &mut flat.positions)?,
+                None => vertex_information = self.file.get_stream().read_vec3s(count)?,
             }
         }
 
         let mut normal_information = Vec::new();
         if has_normals {
-            for _ in 0..vertex_count {
-                normal_information.push(RwVector3 {
-                    x: self.file.get_stream().read_f32()?,
-                    y: self.file.get_stream().read_f32()?,
-                    z: self.file.get_stream().read_f32()?,
-                });
+            let count = self.file.checked_capacity("normals", vertex_count as u64, 12)?;
+            match flat.as_mut() {
+                Some(flat) => read_f32s_onto(self.file.get_stream(), count * 3, &mut flat.normals)?,
+                None => normal_information = self.file.get_stream().read_vec3s(count)?,
             }
         }
 
+        let overrun = self.file.leave_section(struct_scope);
+        self.report_overrun(overrun, struct_offset, "Geometry struct")?;
+
         let material_list = self.read_material_list()?;
         let section_size = self.file.read_section_header()?.section_size;
-        let position = self.file.get_stream().get_position();
-        let bin_mesh = self.read_bin_mesh()?;
-        
+        let end = self.file.get_stream().get_position() + section_size as u64;
+
+        let mut bin_mesh = None;
         let mut skin = None;
-        let next_header = self.file.read_section_header()?;
-        if next_header.section_type == RwSections::RwSkin as u32 {
-            skin = Some(self.read_skin(vertex_count)?);
-        }
+        let mut user_data = Vec::new();
+        self.read_plugins(end, |parser, plugin, offset| {
+            let plugin_end = offset + 12 + plugin.section_size as u64;
+            match RwSections::from_u32(plugin.section_type) {
+                Some(RwSections::RwBinMeshPLG) => bin_mesh = Some(parser.read_bin_mesh_data()?),
+                Some(RwSections::RwSkin) => skin = Some(parser.read_skin(vertex_count, plugin_end, flat.as_mut())?),
+                Some(RwSections::RwUserDataPLG) => user_data = parser.read_user_data(offset)?,
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+
+        let bin_mesh = match bin_mesh {
+            Some(bin_mesh) => bin_mesh,
+            None => {
+                self.report.suspicious(self.mode, end - section_size as u64, "Geometry has no bin mesh")?;
+                RwBinMesh { mesh_count: 0, meshes: Vec::new() }
+            }
+        };
 
-        self.file.get_stream().set_position(position + section_size as u64);
+        if let (Some(output), Some(flat)) = (self.flat.as_mut(), flat) {
+            output.geometries.push(flat);
+        }
 
         Ok(RwGeometry {
             vertex_color_information,
@@ -487,26 +1049,48 @@ impl<'a> DffParser<'a> {
             material_list,
             bin_mesh,
             skin,
+            user_data,
         })
     }
 
-    fn read_material_list(&mut self) -> Result<RwMaterialList> {
-        self.file.read_section_header()?; // Struct
-        self.file.read_section_header()?; // MaterialList
+    pub(crate) fn read_material_list(&mut self) -> RwResult<RwMaterialList> {
+        self.file.expect_section(RwSections::RwMaterialList)?;
+        self.file.expect_section(RwSections::RwStruct)?;
 
         let material_instance_count = self.file.get_stream().read_u32()?;
-        let mut material_indices = Vec::with_capacity(material_instance_count as usize);
+        let capacity = self.file.checked_capacity("materials", material_instance_count as u64, 4)?;
+        let mut material_indices = Vec::with_capacity(capacity);
         for _ in 0..material_instance_count {
             material_indices.push(self.file.get_stream().read_i32()?);
         }
 
-        let mut material_data = Vec::with_capacity(material_instance_count as usize);
+        let mut material_data = Vec::with_capacity(capacity);
         for i in 0..material_instance_count {
             let material_index = material_indices[i as usize];
             if material_index == -1 {
-                material_data.push(self.read_material()?);
+                let start = self.file.get_stream().get_position();
+                material_data.push(self.read_material(i as usize)?);
+                let item = SourceItem::Material { geometry: self.geometry_index, material: i as usize };
+                self.source_map.record(item, start, self.file.get_stream().get_position());
+            } else if let Some(material) = material_data.get(material_index as usize) {
+                material_data.push(material.clone());
             } else {
-                material_data.push(material_data[material_index as usize].clone());
+                let offset = self.file.get_stream().get_position();
+                let message = format!("Material {} is an instance of missing material {}", i, material_index);
+                if self.mode != ParseMode::Lenient {
+                    return Err(RwParseError::InconsistentData { offset, message });
+                }
+                self.report.recovered(offset, message);
+                material_data.push(RwMaterial {
+                    color: RwColor { r: 255, g: 255, b: 255, a: 255 },
+                    is_textured: false,
+                    ambient: None,
+                    specular: None,
+                    diffuse: None,
+                    texture: None,
+                    normal_map: None,
+                    right_to_render: None,
+                });
             }
         }
 
@@ -516,9 +1100,16 @@ impl<'a> DffParser<'a> {
         })
     }
 
-    fn read_material(&mut self) -> Result<RwMaterial> {
-        self.file.read_section_header()?; // Struct
-        let header = self.file.read_section_header()?; // Material
+    fn read_material(&mut self, index: usize) -> RwResult<RwMaterial> {
+        let offset = self.file.get_stream().get_position();
+        let material_header = self.file.expect_section(RwSections::RwMaterial)?;
+        self.read_within(&material_header, offset, "Material", |parser| parser.read_material_content(index))
+    }
+
+    fn read_material_content(&mut self, index: usize) -> RwResult<RwMaterial> {
+        let struct_offset = self.file.get_stream().get_position();
+        let header = self.file.expect_section(RwSections::RwStruct)?;
+        let struct_scope = self.file.enter_section(&header);
 
         self.file.get_stream().skip(4)?; // Flags
 
@@ -543,13 +1134,18 @@ impl<'a> DffParser<'a> {
             diffuse = Some(self.file.get_stream().read_f32()?);
         }
 
+        let overrun = self.file.leave_section(struct_scope);
+        self.report_overrun(overrun, struct_offset, "Material struct")?;
+
         let mut texture = None;
         if is_textured {
+            let start = self.file.get_stream().get_position();
             texture = Some(self.read_texture()?);
+            let item = SourceItem::Texture { geometry: self.geometry_index, material: index };
+            self.source_map.record(item, start, self.file.get_stream().get_position());
         }
 
-        let size = self.file.read_section_header()?.section_size;
-        self.file.get_stream().skip(size as u64)?;
+        let (normal_map, right_to_render) = self.read_material_extension()?;
 
         Ok(RwMaterial {
             color,
@@ -558,12 +1154,51 @@ impl<'a> DffParser<'a> {
             specular,
             diffuse,
             texture,
+            normal_map,
+            right_to_render,
         })
     }
 
-    fn read_texture(&mut self) -> Result<RwTexture> {
-        self.file.read_section_header()?; // Struct
-        self.file.read_section_header()?; // Texture
+    /// Reads the extension of a material, returning its normal map and
+    /// reporting the other plugins.
+    fn read_material_extension(&mut self) -> RwResult<(Option<RwNormalMap>, Option<RwRightToRender>)> {
+        let section_size = self.file.read_section_header()?.section_size;
+        let end = self.file.get_stream().get_position() + section_size as u64;
+
+        let mut normal_map = None;
+        let mut right_to_render = None;
+        self.read_plugins(end, |parser, plugin, _| {
+            match RwSections::from_u32(plugin.section_type) {
+                Some(RwSections::RwNormalMapPLG) => normal_map = Some(parser.read_normal_map()?),
+                Some(RwSections::RwRightToRender) => right_to_render = Some(parser.read_right_to_render()?),
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok((normal_map, right_to_render))
+    }
+
+    /// Reads a normal map plugin: flags telling which textures follow, the
+    /// normal map, then the environment map with its coefficient.
+    fn read_normal_map(&mut self) -> RwResult<RwNormalMap> {
+        let flags = self.file.get_stream().read_u32()?;
+        let normal_texture = if flags & 0x1 != 0 { Some(self.read_texture()?) } else { None };
+        let (environment_texture, environment_coefficient) = if flags & 0x2 != 0 {
+            let coefficient = self.file.get_stream().read_f32()?;
+            (Some(self.read_texture()?), coefficient)
+        } else {
+            (None, 0.0)
+        };
+        Ok(RwNormalMap {
+            normal_texture,
+            environment_texture,
+            environment_coefficient,
+        })
+    }
+
+    fn read_texture(&mut self) -> RwResult<RwTexture> {
+        self.file.expect_section(RwSections::RwTexture)?;
+        self.file.expect_section(RwSections::RwStruct)?;
 
         let texture_data = self.file.get_stream().read_u32()?;
         let texture_filtering = (texture_data & 0xFF) as u8;
@@ -576,8 +1211,7 @@ impl<'a> DffParser<'a> {
 
         let size1 = self.file.read_section_header()?.section_size;
         self.file.get_stream().skip(size1 as u64)?;
-        let size2 = self.file.read_section_header()?.section_size;
-        self.file.get_stream().skip(size2 as u64)?;
+        self.skip_extension()?;
 
         Ok(RwTexture {
             texture_filtering,
@@ -588,14 +1222,49 @@ impl<'a> DffParser<'a> {
         })
     }
 
-    fn read_bin_mesh(&mut self) -> Result<RwBinMesh> {
-        self.file.read_section_header()?; // Struct
+    /// Skips an extension section, reporting each plugin in it.
+    fn skip_extension(&mut self) -> RwResult<()> {
+        let size = self.file.read_section_header()?.section_size;
+        let end = self.file.get_stream().get_position() + size as u64;
+        self.read_plugins(end, |_, _, _| Ok(false))
+    }
+
+    /// Walks the plugins of an extension from the current position to
+    /// `end`, passing each plugin's header and offset to `read`, which
+    /// returns whether it handled the plugin. Unhandled plugins are
+    /// reported as skipped. Each plugin is left at its end whatever `read`
+    /// consumed, and the walk finishes at `end`.
+    fn read_plugins(
+        &mut self,
+        end: u64,
+        mut read: impl FnMut(&mut Self, &RwSectionHeader, u64) -> RwResult<bool>,
+    ) -> RwResult<()> {
+        let limit = end.min(self.file.section_end());
+        while self.file.get_stream().get_position() + 12 <= limit {
+            let offset = self.file.get_stream().get_position();
+            let header = self.file.read_section_header()?;
+            let plugin_end = self.file.get_stream().get_position() + header.section_size as u64;
+            if !read(self, &header, offset)? {
+                self.report.skipped_section(header.section_type, offset);
+            }
+            self.file.get_stream().set_position(plugin_end);
+        }
+        self.file.get_stream().set_position(end);
+        Ok(())
+    }
+
+    pub(crate) fn read_bin_mesh(&mut self) -> RwResult<RwBinMesh> {
+        self.file.expect_section(RwSections::RwBinMeshPLG)?;
+        self.read_bin_mesh_data()
+    }
 
+    /// Reads the payload of a bin mesh plugin, after its header.
+    fn read_bin_mesh_data(&mut self) -> RwResult<RwBinMesh> {
         self.file.get_stream().skip(4)?; // Flags
         let mesh_count = self.file.get_stream().read_u32()?;
         self.file.get_stream().skip(4)?; // Total number of indices
 
-        let mut meshes = Vec::with_capacity(mesh_count as usize);
+        let mut meshes = Vec::with_capacity(self.file.checked_capacity("meshes", mesh_count as u64, 8)?);
         for _ in 0..mesh_count {
             meshes.push(self.read_mesh()?);
         }
@@ -606,11 +1275,11 @@ impl<'a> DffParser<'a> {
         })
     }
 
-    fn read_mesh(&mut self) -> Result<RwMesh> {
+    fn read_mesh(&mut self) -> RwResult<RwMesh> {
         let index_count = self.file.get_stream().read_u32()?;
         let material_index = self.file.get_stream().read_u32()?;
 
-        let mut indices = Vec::with_capacity(index_count as usize);
+        let mut indices = Vec::with_capacity(self.file.checked_capacity("indices", index_count as u64, 4)?);
         for _ in 0..index_count {
             indices.push(self.file.get_stream().read_u32()?);
         }
@@ -622,34 +1291,51 @@ impl<'a> DffParser<'a> {
         })
     }
 
-    fn read_skin(&mut self, vertex_count: u32) -> Result<RwSkin> {
+    /// Reads a skin plugin ending at `end`. With a `flat` geometry, the bone
+    /// indices and weights of the vertices go to its buffers instead.
+    fn read_skin(&mut self, vertex_count: u32, end: u64, flat: Option<&mut RwFlatGeometry>) -> RwResult<RwSkin> {
         let bone_count = self.file.get_stream().read_u8()?;
         let used_bone_count = self.file.get_stream().read_u8()?;
         let max_weights_per_vertex = self.file.get_stream().read_u8()?;
+        // Skins without a used bone list precede every matrix with a
+        // 0xDEADDEAD marker and have no split data, whatever their version
+        let old_format = used_bone_count == 0;
 
         self.file.get_stream().skip(1)?; // Padding
-        self.file.get_stream().skip(used_bone_count as u64)?; // Skipping special indices
-
-        let mut bone_vertex_indices = Vec::with_capacity(vertex_count as usize);
-        for _ in 0..vertex_count {
-            let mut indices = Vec::with_capacity(4);
-            for _ in 0..4 {
-                indices.push(self.file.get_stream().read_u8()?);
+        let used_bones = self.file.get_stream().read(used_bone_count as usize)?;
+
+        // Every vertex has four bone indices and four weights
+        let capacity = self.file.checked_capacity("skinned vertices", vertex_count as u64, 20)?;
+        let mut bone_vertex_indices = Vec::new();
+        let mut vertex_weights = Vec::new();
+        if let Some(flat) = flat {
+            flat.bone_indices.extend_from_slice(self.file.get_stream().read_slice(capacity * 4)?);
+            read_f32s_onto(self.file.get_stream(), capacity * 4, &mut flat.bone_weights)?;
+        } else {
+            bone_vertex_indices.reserve(capacity);
+            for _ in 0..vertex_count {
+                let mut indices = Vec::with_capacity(4);
+                for _ in 0..4 {
+                    indices.push(self.file.get_stream().read_u8()?);
+                }
+                bone_vertex_indices.push(indices);
             }
-            bone_vertex_indices.push(indices);
-        }
 
-        let mut vertex_weights = Vec::with_capacity(vertex_count as usize);
-        for _ in 0..vertex_count {
-            let mut weights = Vec::with_capacity(4);
-            for _ in 0..4 {
-                weights.push(self.file.get_stream().read_f32()?);
+            vertex_weights.reserve(capacity);
+            for _ in 0..vertex_count {
+                let mut weights = Vec::with_capacity(4);
+                for _ in 0..4 {
+                    weights.push(self.file.get_stream().read_f32()?);
+                }
+                vertex_weights.push(weights);
             }
-            vertex_weights.push(weights);
         }
 
         let mut inverse_bone_matrices = Vec::with_capacity(bone_count as usize);
         for _ in 0..bone_count {
+            if old_format {
+                self.file.get_stream().skip(4)?;
+            }
             inverse_bone_matrices.push(RwMatrix4 {
                 right: RwVector4 {
                     x: self.file.get_stream().read_f32()?,
@@ -677,7 +1363,13 @@ impl<'a> DffParser<'a> {
                 },
             });
         }
-        
+
+        let split_data = if !old_format && self.file.get_stream().get_position() + 12 <= end {
+            Some(self.read_skin_split_data(bone_count)?)
+        } else {
+            None
+        };
+
         Ok(RwSkin {
             bone_count,
             used_bone_count,
@@ -685,14 +1377,45 @@ impl<'a> DffParser<'a> {
             bone_vertex_indices,
             vertex_weights,
             inverse_bone_matrices,
+            used_bones,
+            split_data,
         })
     }
 
-    fn read_anim_node(&mut self) -> Result<RwAnimNode> {
+    fn read_skin_split_data(&mut self, bone_count: u8) -> RwResult<RwSkinSplitData> {
+        let bone_limit = self.file.get_stream().read_u32()?;
+        let mesh_count = self.file.get_stream().read_u32()?;
+        let run_count = self.file.get_stream().read_u32()?;
+        if mesh_count == 0 {
+            return Ok(RwSkinSplitData { bone_limit, ..Default::default() });
+        }
+
+        let bone_remap_indices = self.file.get_stream().read(bone_count as usize)?;
+        let capacity = self.file.checked_capacity("skin split meshes", mesh_count as u64, 2)?;
+        let mut mesh_bone_runs = Vec::with_capacity(capacity);
+        for _ in 0..mesh_count {
+            mesh_bone_runs.push(RwSkinRunRange {
+                start: self.file.get_stream().read_u8()?,
+                count: self.file.get_stream().read_u8()?,
+            });
+        }
+        let capacity = self.file.checked_capacity("skin bone runs", run_count as u64, 2)?;
+        let mut bone_runs = Vec::with_capacity(capacity);
+        for _ in 0..run_count {
+            bone_runs.push(RwSkinBoneRun {
+                first_bone: self.file.get_stream().read_u8()?,
+                bone_count: self.file.get_stream().read_u8()?,
+            });
+        }
+
+        Ok(RwSkinSplitData { bone_limit, bone_remap_indices, mesh_bone_runs, bone_runs })
+    }
+
+    fn read_anim_node(&mut self) -> RwResult<RwAnimNode> {
         self.file.get_stream().skip(4)?; // Skipping AnimVersion property (0x100)
         let bone_id = self.file.get_stream().read_i32()?;
         let bone_count = self.file.get_stream().read_i32()?;
-        let mut bones = Vec::with_capacity(bone_count as usize);
+        let mut bones = Vec::with_capacity(self.file.checked_capacity("bones", bone_count.max(0) as u64, 12)?);
 
         if bone_id == 0 {
             self.file.get_stream().skip(8)?; // Skipping flags and keyFrameSize properties
@@ -714,4 +1437,14 @@ impl<'a> DffParser<'a> {
             bones,
         })
     }
-}
\ No newline at end of file
+}
+
+/// Appends `count` floats to `output`.
+fn read_f32s_onto(stream: &mut ByteStream, count: usize, output: &mut Vec<f32>) -> std::io::Result<()> {
+    if count as u64 * 4 > stream.remaining() {
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"));
+    }
+    let start = output.len();
+    output.resize(start + count, 0.0);
+    stream.read_f32_into(&mut output[start..])
+}
@@ -0,0 +1,406 @@
+//! # Model Preview
+//!
+//! A software renderer that draws a parsed `RwDff` with the textures of its
+//! TXD into a thumbnail, available with the `preview` feature. It is meant
+//! for asset browsers, which otherwise render thumbnails through Blender.
+//!
+//! ## Features
+//!
+//! - Places every atomic with the world transform of its frame.
+//! - Frames the whole model from an orbiting orthographic camera.
+//! - Samples the first UV channel, tinted by the material color and shaded
+//!   with a single directional light.
+//! - Cuts out texels below half alpha and supersamples the edges.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::dff::dff_preview::PreviewRenderer;
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/infernus.dff").unwrap()).parse().unwrap();
+//! let txd = TxdParser::new(&fs::read("path/to/infernus.txd").unwrap()).parse().unwrap();
+//!
+//! let png = PreviewRenderer::new(256, 256).with_angles(135.0, 25.0).render_png(&dff, Some(&txd)).unwrap();
+//! fs::write("path/to/infernus.png", png).unwrap();
+//! ```
+
+use super::dff_parser::{RwDff, RwGeometry};
+use super::dff_transform::{world_transform, IDENTITY};
+use crate::renderware::txd::txd_parser::RwTxd;
+use image::codecs::png::PngEncoder;
+use image::{ExtendedColorType, ImageEncoder, RgbaImage};
+use std::collections::HashMap;
+use std::io::{Error, Result};
+
+type Vec3 = [f32; 3];
+
+/// The fraction of the light that reaches faces turned away from it.
+const AMBIENT: f32 = 0.45;
+
+/// Renders thumbnails of DFF models.
+#[derive(Debug, Clone, Copy)]
+pub struct PreviewRenderer {
+    width: u32,
+    height: u32,
+    yaw: f32,
+    pitch: f32,
+    background: [u8; 4],
+    supersampling: u32,
+}
+
+impl PreviewRenderer {
+    /// Creates a renderer of `width` by `height` pixels that looks at the
+    /// model from its front left and slightly above, over a transparent
+    /// background.
+    pub fn new(width: u32, height: u32) -> Self {
+        PreviewRenderer {
+            width: width.max(1),
+            height: height.max(1),
+            yaw: 135.0,
+            pitch: 20.0,
+            background: [0, 0, 0, 0],
+            supersampling: 2,
+        }
+    }
+
+    /// Sets the camera direction in degrees. The yaw turns the camera
+    /// around the vertical Z axis from the +X side, and the pitch raises it
+    /// above the ground.
+    pub fn with_angles(mut self, yaw: f32, pitch: f32) -> Self {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-89.0, 89.0);
+        self
+    }
+
+    /// Sets the RGBA color of the pixels the model does not cover.
+    pub fn with_background(mut self, background: [u8; 4]) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Sets how many samples per pixel are rendered along each axis, from 1
+    /// for aliased edges to 4.
+    pub fn with_supersampling(mut self, supersampling: u32) -> Self {
+        self.supersampling = supersampling.clamp(1, 4);
+        self
+    }
+
+    /// Renders the model. Materials whose texture is missing from `txd`, or
+    /// cannot be decoded, are drawn in their material color.
+    pub fn render(&self, dff: &RwDff, txd: Option<&RwTxd>) -> RgbaImage {
+        let (width, height) = (self.width * self.supersampling, self.height * self.supersampling);
+        let mut target = Target {
+            width,
+            height,
+            color: self.background.repeat(width as usize * height as usize),
+            depth: vec![f32::INFINITY; width as usize * height as usize],
+        };
+
+        let meshes = world_meshes(dff);
+        if let Some(camera) = self.camera(&meshes) {
+            let mut textures = TextureCache { txd, images: HashMap::new() };
+            for (geometry, positions, normals) in &meshes {
+                draw_geometry(&mut target, &camera, &mut textures, geometry, positions, normals);
+            }
+        }
+
+        let color = downsample(&target.color, self.width, self.height, self.supersampling);
+        RgbaImage::from_raw(self.width, self.height, color).expect("The preview buffer matches its size")
+    }
+
+    /// Renders the model and encodes it as a PNG file.
+    pub fn render_png(&self, dff: &RwDff, txd: Option<&RwTxd>) -> Result<Vec<u8>> {
+        let image = self.render(dff, txd);
+        let mut output = Vec::new();
+        PngEncoder::new(&mut output)
+            .write_image(image.as_raw(), image.width(), image.height(), ExtendedColorType::Rgba8)
+            .map_err(Error::other)?;
+        Ok(output)
+    }
+
+    /// Returns a camera framing every vertex, or `None` if there is none.
+    fn camera(&self, meshes: &[WorldMesh]) -> Option<Camera> {
+        let mut vertices = meshes.iter().flat_map(|(_, positions, _)| positions.iter());
+        let first = *vertices.next()?;
+        let (mut min, mut max) = (first, first);
+        for vertex in vertices {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertex[axis]);
+                max[axis] = max[axis].max(vertex[axis]);
+            }
+        }
+        let center = scale(add(min, max), 0.5);
+        let radius = length(sub(max, center)).max(f32::EPSILON);
+
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        // Points from the camera towards the model
+        let forward = [-yaw.cos() * pitch.cos(), -yaw.sin() * pitch.cos(), -pitch.sin()];
+        let right = normalize(cross(forward, [0.0, 0.0, 1.0]));
+        let up = cross(right, forward);
+
+        let (width, height) = ((self.width * self.supersampling) as f32, (self.height * self.supersampling) as f32);
+        Some(Camera {
+            center,
+            right,
+            up,
+            forward,
+            scale: width.min(height) / (2.1 * radius),
+            half_size: [width / 2.0, height / 2.0],
+            light: normalize(sub(scale(up, 0.6), add(forward, scale(right, 0.4)))),
+        })
+    }
+}
+
+/// A geometry with its positions and normals in world space.
+type WorldMesh<'a> = (&'a RwGeometry, Vec<Vec3>, Vec<Vec3>);
+
+/// Transforms the geometry of every atomic by its frame, and every geometry
+/// without an atomic by nothing.
+fn world_meshes(dff: &RwDff) -> Vec<WorldMesh<'_>> {
+    let frames = dff.frame_list.as_ref().map_or(&[][..], |list| &list.frames[..]);
+    let Some(geometry_list) = &dff.geometry_list else {
+        return Vec::new();
+    };
+
+    geometry_list
+        .geometries
+        .iter()
+        .enumerate()
+        .filter(|(_, geometry)| !geometry.vertex_information.is_empty())
+        .map(|(index, geometry)| {
+            let transform = dff.atomics.get(index).map_or(IDENTITY, |&frame| world_transform(frames, frame as usize));
+            let positions = geometry.vertex_information.iter().map(|&vertex| transform.apply(vertex)).collect();
+            let normals = geometry
+                .normal_information
+                .iter()
+                .map(|&normal| normalize(transform.rotate(normal)))
+                .collect();
+            (geometry, positions, normals)
+        })
+        .collect()
+}
+
+/// An orthographic camera.
+struct Camera {
+    center: Vec3,
+    right: Vec3,
+    up: Vec3,
+    forward: Vec3,
+    /// Pixels per world unit.
+    scale: f32,
+    half_size: [f32; 2],
+    /// Points towards the light.
+    light: Vec3,
+}
+
+impl Camera {
+    /// Returns the pixel coordinates and the depth of a point.
+    fn project(&self, point: Vec3) -> Vec3 {
+        let relative = sub(point, self.center);
+        [
+            self.half_size[0] + dot(relative, self.right) * self.scale,
+            self.half_size[1] - dot(relative, self.up) * self.scale,
+            dot(relative, self.forward),
+        ]
+    }
+}
+
+struct Target {
+    width: u32,
+    height: u32,
+    /// RGBA pixels.
+    color: Vec<u8>,
+    depth: Vec<f32>,
+}
+
+/// Decodes each texture of the dictionary once, when first used.
+struct TextureCache<'a> {
+    txd: Option<&'a RwTxd>,
+    images: HashMap<String, Option<RgbaImage>>,
+}
+
+impl TextureCache<'_> {
+    fn get(&mut self, name: &str) -> Option<&RgbaImage> {
+        let txd = self.txd?;
+        self.images
+            .entry(name.to_ascii_lowercase())
+            .or_insert_with(|| txd.texture_dictionary.get_by_name(name).and_then(|texture| texture.to_image().ok()))
+            .as_ref()
+    }
+}
+
+fn draw_geometry(
+    target: &mut Target,
+    camera: &Camera,
+    textures: &mut TextureCache,
+    geometry: &RwGeometry,
+    positions: &[Vec3],
+    normals: &[Vec3],
+) {
+    let projected: Vec<Vec3> = positions.iter().map(|&position| camera.project(position)).collect();
+    let uvs = geometry.texture_mapping_information.first().map_or(&[][..], |uvs| &uvs[..]);
+    let materials = &geometry.material_list.material_data;
+
+    for triangle in &geometry.triangle_information {
+        let indices = [triangle.vector.x as usize, triangle.vector.y as usize, triangle.vector.z as usize];
+        let (Some(&a), Some(&b), Some(&c)) = (projected.get(indices[0]), projected.get(indices[1]), projected.get(indices[2]))
+        else {
+            continue;
+        };
+
+        let material = materials.get(triangle.material_id as usize);
+        let tint = material.map_or([255; 4], |material| {
+            [material.color.r, material.color.g, material.color.b, material.color.a]
+        });
+        let texture = material
+            .and_then(|material| material.texture_name())
+            .filter(|_| uvs.len() == positions.len())
+            .and_then(|name| textures.get(name));
+
+        let face_normal = normalize(cross(
+            sub(positions[indices[1]], positions[indices[0]]),
+            sub(positions[indices[2]], positions[indices[0]]),
+        ));
+        let shading = indices.map(|index| {
+            let normal = if normals.len() == positions.len() { normals[index] } else { face_normal };
+            // Models are rarely closed, so faces are lit from both sides
+            AMBIENT + (1.0 - AMBIENT) * dot(normal, camera.light).abs()
+        });
+        let texcoords = indices.map(|index| uvs.get(index).map_or([0.0; 2], |uv| [uv.u, uv.v]));
+
+        rasterize(target, [a, b, c], |weights| {
+            let light = weights[0] * shading[0] + weights[1] * shading[1] + weights[2] * shading[2];
+            let texel = match texture {
+                Some(image) => {
+                    let u = weights[0] * texcoords[0][0] + weights[1] * texcoords[1][0] + weights[2] * texcoords[2][0];
+                    let v = weights[0] * texcoords[0][1] + weights[1] * texcoords[1][1] + weights[2] * texcoords[2][1];
+                    sample(image, u, v)
+                }
+                None => [255; 4],
+            };
+            if texel[3] < 128 {
+                return None;
+            }
+            let channel = |index: usize| (texel[index] as f32 * tint[index] as f32 / 255.0 * light).min(255.0) as u8;
+            Some([channel(0), channel(1), channel(2), 255])
+        });
+    }
+}
+
+/// Fills the pixels whose centers are inside a projected triangle and
+/// closer than what was drawn before, with the color `shade` returns for
+/// their barycentric weights. Pixels it returns `None` for are left as is.
+fn rasterize(target: &mut Target, [a, b, c]: [Vec3; 3], mut shade: impl FnMut([f32; 3]) -> Option<[u8; 4]>) {
+    let area = edge(a, b, c);
+    if area.abs() < f32::EPSILON || !area.is_finite() {
+        return;
+    }
+
+    let min_x = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+    let min_y = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+    let max_x = (a[0].max(b[0]).max(c[0]).ceil().max(0.0) as u32).min(target.width);
+    let max_y = (a[1].max(b[1]).max(c[1]).ceil().max(0.0) as u32).min(target.height);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let point = [x as f32 + 0.5, y as f32 + 0.5, 0.0];
+            let weights = [edge(b, c, point) / area, edge(c, a, point) / area, edge(a, b, point) / area];
+            if weights.iter().any(|&weight| weight < 0.0) {
+                continue;
+            }
+
+            let depth = weights[0] * a[2] + weights[1] * b[2] + weights[2] * c[2];
+            let index = y as usize * target.width as usize + x as usize;
+            if depth >= target.depth[index] {
+                continue;
+            }
+            if let Some(color) = shade(weights) {
+                target.depth[index] = depth;
+                target.color[index * 4..index * 4 + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Returns twice the signed area of the triangle `a`, `b`, `point` on screen.
+fn edge(a: Vec3, b: Vec3, point: Vec3) -> f32 {
+    (b[0] - a[0]) * (point[1] - a[1]) - (b[1] - a[1]) * (point[0] - a[0])
+}
+
+/// Returns the texel at a UV coordinate, repeating the texture.
+fn sample(image: &RgbaImage, u: f32, v: f32) -> [u8; 4] {
+    let (width, height) = (image.width(), image.height());
+    let x = ((u.rem_euclid(1.0) * width as f32) as u32).min(width - 1);
+    let y = ((v.rem_euclid(1.0) * height as f32) as u32).min(height - 1);
+    let index = (y as usize * width as usize + x as usize) * 4;
+    image.as_raw().get(index..index + 4).map_or([255; 4], |texel| [texel[0], texel[1], texel[2], texel[3]])
+}
+
+/// Averages each block of `factor` by `factor` pixels, weighting colors by
+/// their alpha so that edges do not darken over a transparent background.
+fn downsample(color: &[u8], width: u32, height: u32, factor: u32) -> Vec<u8> {
+    if factor == 1 {
+        return color.to_vec();
+    }
+    let source_width = width as usize * factor as usize;
+    let mut output = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height as usize {
+        for x in 0..width as usize {
+            let mut sum = [0u32; 4];
+            for sample_y in y * factor as usize..(y + 1) * factor as usize {
+                for sample_x in x * factor as usize..(x + 1) * factor as usize {
+                    let pixel = &color[(sample_y * source_width + sample_x) * 4..][..4];
+                    let alpha = pixel[3] as u32;
+                    for channel in 0..3 {
+                        sum[channel] += pixel[channel] as u32 * alpha;
+                    }
+                    sum[3] += alpha;
+                }
+            }
+            let samples = factor * factor;
+            match sum[3] {
+                0 => output.extend_from_slice(&[0; 4]),
+                alpha => output.extend_from_slice(&[
+                    (sum[0] / alpha) as u8,
+                    (sum[1] / alpha) as u8,
+                    (sum[2] / alpha) as u8,
+                    (alpha / samples) as u8,
+                ]),
+            }
+        }
+    }
+    output
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: Vec3, factor: f32) -> Vec3 {
+    [a[0] * factor, a[1] * factor, a[2] * factor]
+}
+
+fn dot(a: Vec3, b: Vec3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn length(a: Vec3) -> f32 {
+    dot(a, a).sqrt()
+}
+
+/// Returns `a` scaled to a length of 1, or zero if it has no length.
+fn normalize(a: Vec3) -> Vec3 {
+    let length = length(a);
+    if length > f32::EPSILON { scale(a, 1.0 / length) } else { [0.0; 3] }
+}
@@ -0,0 +1,398 @@
+//! # Mesh Simplification
+//!
+//! Quadric error decimation of parsed geometries, to generate the low detail
+//! models of San Andreas' LOD system or lighter models for web viewers.
+//!
+//! ## Features
+//!
+//! - Edge collapses ordered by Garland and Heckbert's quadric error metric.
+//! - Collapses only remove vertices, so the remaining vertices keep their
+//!   exact texture coordinates, colors, normals and bone weights.
+//! - UV seams, material boundaries and open borders are preserved, and
+//!   collapses that would flip a triangle are skipped.
+//! - In skinned geometries, vertices bound mostly to different bones are not
+//!   merged, so joints keep their shape.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::dff::dff_simplify::GeometrySimplifier;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/lamppost.dff").unwrap()).parse().unwrap();
+//!
+//! let lod = GeometrySimplifier::new().with_target_ratio(0.25).simplify_dff(&dff);
+//! for geometry in &lod.geometry_list.as_ref().unwrap().geometries {
+//!     println!("{} triangles", geometry.triangle_information.len());
+//! }
+//! ```
+
+use super::dff_parser::{RwDff, RwGeometry};
+use crate::renderware::common::types::RwVector3;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Simplifies geometries by collapsing edges.
+pub struct GeometrySimplifier {
+    target_ratio: f32,
+    max_error: f32,
+}
+
+/// A symmetric 4x4 error quadric, stored as its upper triangle.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+/// A candidate collapse of a vertex onto a neighbour, ordered by lowest
+/// cost first.
+struct Collapse {
+    cost: f64,
+    vertex: usize,
+    version: u32,
+}
+
+impl Default for GeometrySimplifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeometrySimplifier {
+    /// Creates a simplifier that keeps half of the triangles, whatever the
+    /// error.
+    pub fn new() -> Self {
+        GeometrySimplifier {
+            target_ratio: 0.5,
+            max_error: f32::INFINITY,
+        }
+    }
+
+    /// Sets the fraction of triangles to keep, between 0 and 1.
+    pub fn with_target_ratio(mut self, target_ratio: f32) -> Self {
+        self.target_ratio = target_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the largest error a collapse may introduce, as a squared distance
+    /// in model units. Simplification stops before reaching the target ratio
+    /// if every remaining collapse costs more.
+    pub fn with_max_error(mut self, max_error: f32) -> Self {
+        self.max_error = max_error.max(0.0);
+        self
+    }
+
+    /// Returns a copy of a clump with every geometry simplified, which keeps
+    /// the frames, atomics and skeleton of the original.
+    pub fn simplify_dff(&self, dff: &RwDff) -> RwDff {
+        let mut lod = dff.clone();
+        if let Some(geometry_list) = &mut lod.geometry_list {
+            for geometry in &mut geometry_list.geometries {
+                *geometry = self.simplify(geometry);
+            }
+        }
+        lod
+    }
+
+    /// Returns a simplified copy of a geometry. Unused vertices are removed
+    /// and the bin mesh is rebuilt as triangle lists.
+    pub fn simplify(&self, geometry: &RwGeometry) -> RwGeometry {
+        let mut geometry = geometry.clone();
+        let vertex_count = geometry.vertex_count();
+        let mut triangles: Vec<Option<[usize; 3]>> = geometry
+            .triangle_information
+            .iter()
+            .map(|triangle| {
+                let corners = [triangle.vector.x as usize, triangle.vector.y as usize, triangle.vector.z as usize];
+                corners.iter().all(|&corner| corner < vertex_count).then_some(corners)
+            })
+            .collect();
+        let target = (triangles.iter().flatten().count() as f32 * self.target_ratio).round() as usize;
+
+        let mut mesh = Mesh::new(&geometry, &triangles);
+        let mut live = triangles.iter().flatten().count();
+        let mut versions = vec![0u32; vertex_count];
+        let mut heap = BinaryHeap::new();
+        for vertex in 0..vertex_count {
+            if let Some((cost, _)) = mesh.best_collapse(vertex, &triangles) {
+                heap.push(Collapse { cost, vertex, version: 0 });
+            }
+        }
+
+        while live > target {
+            let Some(Collapse { cost, vertex, version }) = heap.pop() else {
+                break;
+            };
+            if version != versions[vertex] {
+                continue;
+            }
+            if cost > self.max_error as f64 {
+                break;
+            }
+            // The neighbourhood may have changed since the entry was pushed
+            let Some((_, target_vertex)) = mesh.best_collapse(vertex, &triangles) else {
+                versions[vertex] += 1;
+                continue;
+            };
+
+            live -= mesh.collapse(vertex, target_vertex, &mut triangles);
+            versions[vertex] += 1;
+            for neighbour in mesh.neighbours(target_vertex, &triangles).into_iter().chain([target_vertex]) {
+                versions[neighbour] += 1;
+                if let Some((cost, _)) = mesh.best_collapse(neighbour, &triangles) {
+                    heap.push(Collapse { cost, vertex: neighbour, version: versions[neighbour] });
+                }
+            }
+        }
+
+        // Drop the removed triangles and the vertices nothing uses anymore.
+        // Triangles with out of range indices are dropped too, as removing
+        // vertices could bring them back in range.
+        let kept: Vec<usize> = (0..geometry.triangle_information.len()).filter(|&index| triangles[index].is_some()).collect();
+        let mut used = vec![false; vertex_count];
+        for corners in triangles.iter().flatten() {
+            for &corner in corners {
+                used[corner] = true;
+            }
+        }
+        let order: Vec<usize> = (0..vertex_count).filter(|&vertex| used[vertex]).collect();
+        let mut remap = vec![0; vertex_count];
+        for (new, &old) in order.iter().enumerate() {
+            remap[old] = new;
+        }
+
+        let mut result = Vec::with_capacity(kept.len());
+        for index in kept {
+            let mut triangle = geometry.triangle_information[index];
+            let [a, b, c] = triangles[index].unwrap_or_default();
+            (triangle.vector.x, triangle.vector.y, triangle.vector.z) = (remap[a] as f32, remap[b] as f32, remap[c] as f32);
+            result.push(triangle);
+        }
+        geometry.reorder_vertices(&order);
+        geometry.triangle_information = result;
+        geometry.rebuild_bin_mesh();
+        geometry
+    }
+}
+
+/// The connectivity and error quadrics of a geometry being simplified.
+struct Mesh {
+    positions: Vec<RwVector3>,
+    /// The quadric of each vertex, shared by vertices at the same position.
+    quadrics: Vec<Quadric>,
+    vertex_triangles: Vec<Vec<usize>>,
+    /// Whether a vertex is on a seam, border or material boundary.
+    locked: Vec<bool>,
+    /// The bone with the largest weight of each vertex, if skinned.
+    dominant_bones: Vec<Option<u8>>,
+}
+
+impl Mesh {
+    fn new(geometry: &RwGeometry, triangles: &[Option<[usize; 3]>]) -> Self {
+        let vertex_count = geometry.vertex_count();
+        let positions = geometry.vertex_information.clone();
+
+        // Vertices at the same position are split by a UV or normal seam
+        let key = |vertex: usize| {
+            let position = positions[vertex];
+            [position.x.to_bits(), position.y.to_bits(), position.z.to_bits()]
+        };
+        let mut position_ids: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut position_counts: Vec<usize> = Vec::new();
+        let welded: Vec<usize> = (0..vertex_count)
+            .map(|vertex| {
+                let id = *position_ids.entry(key(vertex)).or_insert_with(|| {
+                    position_counts.push(0);
+                    position_counts.len() - 1
+                });
+                position_counts[id] += 1;
+                id
+            })
+            .collect();
+
+        let mut vertex_triangles = vec![Vec::new(); vertex_count];
+        let mut materials: Vec<Option<u16>> = vec![None; vertex_count];
+        let mut locked: Vec<bool> = welded.iter().map(|&id| position_counts[id] > 1).collect();
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut position_quadrics = vec![Quadric::default(); position_counts.len()];
+        for (index, corners) in triangles.iter().enumerate() {
+            let Some(corners) = corners else {
+                continue;
+            };
+            let material_id = geometry.triangle_information[index].material_id;
+            let quadric = Quadric::from_triangle(corners.map(|corner| positions[corner]));
+            for (slot, &corner) in corners.iter().enumerate() {
+                vertex_triangles[corner].push(index);
+                position_quadrics[welded[corner]].add(&quadric);
+                match materials[corner] {
+                    Some(known) if known != material_id => locked[corner] = true,
+                    _ => materials[corner] = Some(material_id),
+                }
+                let next = welded[corners[(slot + 1) % 3]];
+                let edge = (welded[corner].min(next), welded[corner].max(next));
+                *edges.entry(edge).or_default() += 1;
+            }
+        }
+        // Edges used by a single triangle are open borders
+        for corners in triangles.iter().flatten() {
+            for slot in 0..3 {
+                let (a, b) = (welded[corners[slot]], welded[corners[(slot + 1) % 3]]);
+                if edges.get(&(a.min(b), a.max(b))) == Some(&1) {
+                    locked[corners[slot]] = true;
+                    locked[corners[(slot + 1) % 3]] = true;
+                }
+            }
+        }
+
+        let dominant_bones = (0..vertex_count)
+            .map(|vertex| {
+                let skin = geometry.skin.as_ref()?;
+                let weights = skin.vertex_weights.get(vertex)?;
+                let slot = (0..weights.len()).max_by(|&a, &b| weights[a].total_cmp(&weights[b]))?;
+                skin.bone_vertex_indices.get(vertex)?.get(slot).copied()
+            })
+            .collect();
+
+        Mesh {
+            positions,
+            quadrics: welded.iter().map(|&id| position_quadrics[id]).collect(),
+            vertex_triangles,
+            locked,
+            dominant_bones,
+        }
+    }
+
+    /// Returns the vertices sharing a live triangle with a vertex.
+    fn neighbours(&self, vertex: usize, triangles: &[Option<[usize; 3]>]) -> Vec<usize> {
+        let mut neighbours: Vec<usize> = self.vertex_triangles[vertex]
+            .iter()
+            .filter_map(|&triangle| triangles[triangle])
+            .flatten()
+            .filter(|&other| other != vertex)
+            .collect();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        neighbours
+    }
+
+    /// Returns the cost and target of the cheapest valid collapse of a
+    /// vertex onto one of its neighbours.
+    fn best_collapse(&self, vertex: usize, triangles: &[Option<[usize; 3]>]) -> Option<(f64, usize)> {
+        if self.locked[vertex] {
+            return None;
+        }
+        let quadric = self.quadrics[vertex];
+        self.neighbours(vertex, triangles)
+            .into_iter()
+            .filter(|&target| self.dominant_bones[vertex] == self.dominant_bones[target])
+            .filter(|&target| !self.flips(vertex, target, triangles))
+            .map(|target| {
+                let mut combined = quadric;
+                combined.add(&self.quadrics[target]);
+                (combined.error(self.positions[target]).max(0.0), target)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    /// Returns whether moving a vertex onto a target would flip or collapse
+    /// one of the triangles that remain.
+    fn flips(&self, vertex: usize, target: usize, triangles: &[Option<[usize; 3]>]) -> bool {
+        self.vertex_triangles[vertex].iter().filter_map(|&triangle| triangles[triangle]).any(|corners| {
+            if corners.contains(&target) {
+                return false;
+            }
+            let before = corners.map(|corner| self.positions[corner]);
+            let after = corners.map(|corner| self.positions[if corner == vertex { target } else { corner }]);
+            let (old_normal, new_normal) = (normal(before), normal(after));
+            let new_length = dot(new_normal, new_normal).sqrt();
+            new_length < 1e-12 || dot(old_normal, new_normal) <= 0.2 * dot(old_normal, old_normal).sqrt() * new_length
+        })
+    }
+
+    /// Moves the triangles of a vertex onto a target, returning the number
+    /// of triangles that became degenerate and were removed.
+    fn collapse(&mut self, vertex: usize, target: usize, triangles: &mut [Option<[usize; 3]>]) -> usize {
+        let mut removed = 0;
+        for triangle in std::mem::take(&mut self.vertex_triangles[vertex]) {
+            let Some(corners) = &mut triangles[triangle] else {
+                continue;
+            };
+            if corners.contains(&target) {
+                triangles[triangle] = None;
+                removed += 1;
+                continue;
+            }
+            for corner in corners.iter_mut() {
+                if *corner == vertex {
+                    *corner = target;
+                }
+            }
+            self.vertex_triangles[target].push(triangle);
+        }
+        let quadric = self.quadrics[vertex];
+        self.quadrics[target].add(&quadric);
+        removed
+    }
+}
+
+impl Quadric {
+    /// Builds the area-weighted quadric of a triangle's plane.
+    fn from_triangle(corners: [RwVector3; 3]) -> Self {
+        let n = normal(corners);
+        let length = dot(n, n).sqrt();
+        if length < 1e-12 {
+            return Quadric::default();
+        }
+        let [a, b, c] = n.map(|component| component / length);
+        let d = -(a * corners[0].x as f64 + b * corners[0].y as f64 + c * corners[0].z as f64);
+        let weight = length * 0.5;
+        Quadric([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|value| value * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, other) in self.0.iter_mut().zip(other.0) {
+            *value += other;
+        }
+    }
+
+    /// Returns the sum of squared distances of a point to the planes.
+    fn error(&self, point: RwVector3) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let (x, y, z) = (point.x as f64, point.y as f64, point.z as f64);
+        aa * x * x + 2.0 * ab * x * y + 2.0 * ac * x * z + 2.0 * ad * x + bb * y * y + 2.0 * bc * y * z + 2.0 * bd * y + cc * z * z
+            + 2.0 * cd * z
+            + dd
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, so the cheapest collapse must compare
+        // greatest
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Returns the unnormalised normal of a triangle, in double precision.
+fn normal(corners: [RwVector3; 3]) -> [f64; 3] {
+    let [a, b, c] = corners.map(|corner| [corner.x as f64, corner.y as f64, corner.z as f64]);
+    let (u, v) = ([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+    [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
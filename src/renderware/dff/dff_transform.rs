@@ -0,0 +1,62 @@
+//! World transforms of DFF frames, shared by the exporters and the preview
+//! renderer that place geometry in model space.
+
+use crate::renderware::common::types::RwVector3;
+use crate::renderware::dff::dff_parser::RwFrame;
+
+/// A rotation, given by its columns, followed by a translation.
+pub(crate) struct Transform {
+    columns: [RwVector3; 3],
+    offset: RwVector3,
+}
+
+pub(crate) const IDENTITY: Transform = Transform {
+    columns: [
+        RwVector3 { x: 1.0, y: 0.0, z: 0.0 },
+        RwVector3 { x: 0.0, y: 1.0, z: 0.0 },
+        RwVector3 { x: 0.0, y: 0.0, z: 1.0 },
+    ],
+    offset: RwVector3 { x: 0.0, y: 0.0, z: 0.0 },
+};
+
+impl Transform {
+    pub(crate) fn rotate(&self, point: RwVector3) -> [f32; 3] {
+        let [x, y, z] = &self.columns;
+        [
+            x.x * point.x + y.x * point.y + z.x * point.z,
+            x.y * point.x + y.y * point.y + z.y * point.z,
+            x.z * point.x + y.z * point.y + z.z * point.z,
+        ]
+    }
+
+    pub(crate) fn apply(&self, point: RwVector3) -> [f32; 3] {
+        let [x, y, z] = self.rotate(point);
+        [x + self.offset.x, y + self.offset.y, z + self.offset.z]
+    }
+}
+
+/// Combines the transforms of a frame and its parents. A damaged hierarchy
+/// that loops stops after visiting as many frames as there are.
+pub(crate) fn world_transform(frames: &[RwFrame], frame: usize) -> Transform {
+    let mut transform = IDENTITY;
+    let mut current = frame;
+    for _ in 0..frames.len() {
+        let Some(frame) = frames.get(current) else {
+            break;
+        };
+        let parent = Transform {
+            columns: [frame.rotation_matrix.right, frame.rotation_matrix.up, frame.rotation_matrix.at],
+            offset: frame.coordinates_offset,
+        };
+        let vector = |[x, y, z]: [f32; 3]| RwVector3 { x, y, z };
+        transform = Transform {
+            columns: transform.columns.map(|column| vector(parent.rotate(column))),
+            offset: vector(parent.apply(transform.offset)),
+        };
+        if frame.parent_frame < 0 {
+            break;
+        }
+        current = frame.parent_frame as usize;
+    }
+    transform
+}
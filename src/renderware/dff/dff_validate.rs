@@ -0,0 +1,93 @@
+//! # DFF Texture Validation
+//!
+//! Cross-checks the textures a model's materials use against the texture
+//! dictionaries shipped with it. A texture missing from every dictionary is
+//! the classic cause of models rendering plain white in game, and textures
+//! nothing uses only waste memory.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let dff = DffParser::new(&fs::read("path/to/infernus.dff").unwrap()).parse().unwrap();
+//! let txd = TxdParser::new(&fs::read("path/to/infernus.txd").unwrap()).parse().unwrap();
+//! let vehicle = TxdParser::new(&fs::read("path/to/vehicle.txd").unwrap()).parse().unwrap();
+//!
+//! for issue in dff.validate_textures(&[&txd, &vehicle]) {
+//!     println!("{}: {:?}", issue.texture_name, issue.kind);
+//! }
+//! ```
+
+use super::dff_parser::RwDff;
+use crate::renderware::txd::txd_parser::RwTxd;
+use serde::Serialize;
+
+/// A mismatch between a model's materials and its texture dictionaries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextureIssue {
+    pub texture_name: String,
+    pub kind: TextureIssueKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TextureIssueKind {
+    /// A material uses the texture, but none of the dictionaries has it.
+    Missing,
+    /// The dictionary at this index of the list has the texture, but no
+    /// material uses it.
+    Unused { dictionary: usize },
+}
+
+impl RwDff {
+    /// Returns the names of the textures used by the model's materials, in
+    /// order of first use, without duplicates. Names are compared ignoring
+    /// ASCII case, like the game does.
+    pub fn texture_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        let geometries = self.geometry_list.as_ref().map_or(&[][..], |list| &list.geometries[..]);
+        for material in geometries.iter().flat_map(|geometry| &geometry.material_list.material_data) {
+            let Some(name) = material.texture_name() else {
+                continue;
+            };
+            if !names.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Checks the textures of the model's materials against the given
+    /// dictionaries, such as its own TXD and the shared ones it inherits
+    /// from.
+    ///
+    /// Every texture a material uses that no dictionary has is reported as
+    /// missing, followed by every texture of each dictionary that no
+    /// material uses.
+    pub fn validate_textures(&self, txds: &[&RwTxd]) -> Vec<TextureIssue> {
+        let names = self.texture_names();
+        let mut issues: Vec<TextureIssue> = names
+            .iter()
+            .filter(|name| txds.iter().all(|txd| txd.texture_dictionary.get_by_name(name).is_none()))
+            .map(|name| TextureIssue {
+                texture_name: name.to_string(),
+                kind: TextureIssueKind::Missing,
+            })
+            .collect();
+
+        for (dictionary, txd) in txds.iter().enumerate() {
+            issues.extend(
+                txd.texture_dictionary
+                    .iter()
+                    .filter(|native| !names.iter().any(|name| name.eq_ignore_ascii_case(&native.texture_name)))
+                    .map(|native| TextureIssue {
+                        texture_name: native.texture_name.clone(),
+                        kind: TextureIssueKind::Unused { dictionary },
+                    }),
+            );
+        }
+        issues
+    }
+}
@@ -1,2 +1,11 @@
 pub mod dff_parser;
-pub mod dff_model_type;
\ No newline at end of file
+pub mod dff_flat;
+pub mod dff_geometry;
+pub mod dff_material;
+pub mod dff_model_type;
+pub(crate) mod dff_transform;
+pub mod dff_validate;
+#[cfg(feature = "preview")]
+pub mod dff_preview;
+#[cfg(feature = "simplify")]
+pub mod dff_simplify;
\ No newline at end of file
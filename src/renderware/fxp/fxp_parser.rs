@@ -0,0 +1,320 @@
+//! # FXP Parser
+//!
+//! A parser for the particle effect projects of San Andreas
+//! (`effects.fxp`), which define every particle system of the game as a set
+//! of emitters driven by keyframed values.
+//!
+//! ## Features
+//!
+//! - Parses the particle systems with their length, play mode, culling
+//!   distance and bounding sphere.
+//! - Parses the emitters of each system, including their matrix, blending
+//!   and textures.
+//! - Parses the infos of each emitter into their settings and keyframe
+//!   interpolators.
+//! - Lists every texture referenced by the project.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::fxp::fxp_parser::FxpParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/effects.fxp").unwrap();
+//! let fxp_data = FxpParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Systems: {}, textures: {:?}", fxp_data.systems.len(), fxp_data.textures());
+//! ```
+
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// Represents a parsed FXP project.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwFxp {
+    pub systems: Vec<FxSystem>,
+}
+
+/// A particle system, from an `FX_SYSTEM_DATA` block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FxSystem {
+    /// The version number following the block header, 109 in the PC game.
+    pub version: Option<u32>,
+    pub name: String,
+    /// The length of the effect, in seconds.
+    pub length: f32,
+    pub play_mode: i32,
+    pub cull_distance: f32,
+    /// The center and radius of the bounding sphere.
+    pub bounding_sphere: [f32; 4],
+    pub primitives: Vec<FxPrimitive>,
+    /// The remaining settings of the system, such as `FILENAME`.
+    pub properties: Vec<FxProperty>,
+}
+
+/// An emitter of a system, from an `FX_PRIM_*_DATA` block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FxPrimitive {
+    /// The primitive type, `EMITTER` for all primitives of the game.
+    pub kind: String,
+    pub name: String,
+    /// The emitter transform relative to the system, as the right, up, at
+    /// and position vectors.
+    pub matrix: [f32; 12],
+    /// The texture names of the primitive, without the `NULL` placeholders.
+    pub textures: Vec<String>,
+    pub alpha_on: bool,
+    pub src_blend: i32,
+    pub dst_blend: i32,
+    pub infos: Vec<FxInfo>,
+    pub properties: Vec<FxProperty>,
+}
+
+/// A behavior of an emitter, from an `FX_INFO_*_DATA` block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FxInfo {
+    /// The info type, such as `EMRATE` or `COLOUR`.
+    pub kind: String,
+    pub properties: Vec<FxProperty>,
+    pub interpolators: Vec<FxInterpolator>,
+}
+
+/// A keyframed value of an info, from an `FX_INTERP_DATA` block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FxInterpolator {
+    /// The name of the value, such as `RED` or `EMRATE`.
+    pub name: String,
+    pub looped: bool,
+    pub keys: Vec<FxKeyframe>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FxKeyframe {
+    pub time: f32,
+    pub value: f32,
+}
+
+/// A setting without a dedicated field, with its value as written.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FxProperty {
+    pub key: String,
+    pub value: String,
+}
+
+impl RwFxp {
+    /// Returns the particle system with the given name, compared
+    /// case-insensitively.
+    pub fn system(&self, name: &str) -> Option<&FxSystem> {
+        self.systems.iter().find(|system| system.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the names of all textures referenced by the project, sorted
+    /// and without duplicates.
+    pub fn textures(&self) -> Vec<&str> {
+        let mut textures: Vec<&str> = self
+            .systems
+            .iter()
+            .flat_map(|system| &system.primitives)
+            .flat_map(|primitive| &primitive.textures)
+            .map(|texture| texture.as_str())
+            .collect();
+        textures.sort_unstable();
+        textures.dedup();
+        textures
+    }
+}
+
+impl FxInfo {
+    pub fn interpolator(&self, name: &str) -> Option<&FxInterpolator> {
+        self.interpolators.iter().find(|interpolator| interpolator.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl FxInterpolator {
+    /// Returns the value at the given time, interpolated linearly between
+    /// keys and wrapped around the last key if the interpolator loops.
+    pub fn value_at(&self, time: f32) -> Option<f32> {
+        let (first, last) = (self.keys.first()?, self.keys.last()?);
+        let time = if self.looped && last.time > 0.0 { time.rem_euclid(last.time) } else { time };
+
+        if time <= first.time {
+            return Some(first.value);
+        }
+        for pair in self.keys.windows(2) {
+            if time <= pair[1].time {
+                let span = pair[1].time - pair[0].time;
+                let t = if span > 0.0 { (time - pair[0].time) / span } else { 1.0 };
+                return Some(pair[0].value + (pair[1].value - pair[0].value) * t);
+            }
+        }
+        Some(last.value)
+    }
+}
+
+/// The main parser for FXP files.
+pub struct FxpParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> FxpParser<'a> {
+    /// Creates a new `FxpParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw FXP file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        FxpParser { buffer }
+    }
+
+    /// Parses the FXP project.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwFxp` data or an `std::io::Error`
+    /// if a block appears outside of its parent or a value is malformed.
+    pub fn parse(&mut self) -> Result<RwFxp> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut fxp = RwFxp::default();
+        // The name of the last key without a value, which names the
+        // interpolator that follows it
+        let mut pending_name: Option<String> = None;
+        let mut in_interpolator = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| Error::new(ErrorKind::InvalidData, format!("FXP line {}: {}", index + 1, message));
+
+            let Some((key, value)) = line.split_once(':') else {
+                // The version number following FX_SYSTEM_DATA
+                if let Some(system) = fxp.systems.last_mut()
+                    && system.version.is_none()
+                    && system.primitives.is_empty()
+                {
+                    system.version = line.parse().ok();
+                }
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "FX_PROJECT_DATA" | "FX_PRIM_BASE_DATA" => continue,
+                "FX_PROJECT_DATA_END" => break,
+                "FX_SYSTEM_DATA" => {
+                    fxp.systems.push(FxSystem::default());
+                    in_interpolator = false;
+                    continue;
+                }
+                "FX_INTERP_DATA" => {
+                    let info = current_info(&mut fxp).ok_or_else(|| error("interpolator outside of an info"))?;
+                    info.interpolators.push(FxInterpolator {
+                        name: pending_name.take().unwrap_or_default(),
+                        ..Default::default()
+                    });
+                    in_interpolator = true;
+                    continue;
+                }
+                _ => {}
+            }
+
+            if let Some(kind) = key.strip_prefix("FX_PRIM_").and_then(|kind| kind.strip_suffix("_DATA")) {
+                let system = fxp.systems.last_mut().ok_or_else(|| error("primitive outside of a system"))?;
+                system.primitives.push(FxPrimitive { kind: kind.to_string(), ..Default::default() });
+                in_interpolator = false;
+                continue;
+            }
+            if let Some(kind) = key.strip_prefix("FX_INFO_").and_then(|kind| kind.strip_suffix("_DATA")) {
+                let primitive = current_primitive(&mut fxp).ok_or_else(|| error("info outside of a primitive"))?;
+                primitive.infos.push(FxInfo { kind: kind.to_string(), ..Default::default() });
+                in_interpolator = false;
+                continue;
+            }
+
+            if value.is_empty() {
+                pending_name = Some(key.to_string());
+                continue;
+            }
+
+            if in_interpolator {
+                let interpolator = current_info(&mut fxp)
+                    .and_then(|info| info.interpolators.last_mut())
+                    .ok_or_else(|| error("interpolator value outside of an interpolator"))?;
+                match key {
+                    "LOOPED" => interpolator.looped = value != "0",
+                    "NUM_KEYS" => {}
+                    "TIME" => interpolator.keys.push(FxKeyframe { time: parse_number(value).ok_or_else(|| error("invalid time"))?, value: 0.0 }),
+                    "VAL" => {
+                        let keyframe = interpolator.keys.last_mut().ok_or_else(|| error("value without a time"))?;
+                        keyframe.value = parse_number(value).ok_or_else(|| error("invalid value"))?;
+                    }
+                    _ => in_interpolator = false,
+                }
+                if in_interpolator {
+                    continue;
+                }
+            }
+
+            let property = FxProperty { key: key.to_string(), value: value.to_string() };
+            if let Some(info) = current_info(&mut fxp) {
+                info.properties.push(property);
+            } else if let Some(primitive) = current_primitive(&mut fxp) {
+                read_primitive_property(primitive, property).ok_or_else(|| error("invalid primitive value"))?;
+            } else if let Some(system) = fxp.systems.last_mut() {
+                read_system_property(system, property).ok_or_else(|| error("invalid system value"))?;
+            }
+        }
+
+        Ok(fxp)
+    }
+}
+
+fn current_primitive(fxp: &mut RwFxp) -> Option<&mut FxPrimitive> {
+    fxp.systems.last_mut()?.primitives.last_mut()
+}
+
+fn current_info(fxp: &mut RwFxp) -> Option<&mut FxInfo> {
+    current_primitive(fxp)?.infos.last_mut()
+}
+
+fn read_system_property(system: &mut FxSystem, property: FxProperty) -> Option<()> {
+    match property.key.as_str() {
+        "NAME" => system.name = property.value,
+        "LENGTH" => system.length = parse_number(&property.value)?,
+        "PLAYMODE" => system.play_mode = parse_number(&property.value)? as i32,
+        "CULLDIST" => system.cull_distance = parse_number(&property.value)?,
+        "BOUNDINGSPHERE" => system.bounding_sphere = parse_numbers(&property.value)?,
+        "NUM_PRIMS" => {}
+        _ => system.properties.push(property),
+    }
+    Some(())
+}
+
+fn read_primitive_property(primitive: &mut FxPrimitive, property: FxProperty) -> Option<()> {
+    match property.key.as_str() {
+        "NAME" => primitive.name = property.value,
+        "MATRIX" => primitive.matrix = parse_numbers(&property.value)?,
+        "TEXTURE" | "TEXTURE2" | "TEXTURE3" | "TEXTURE4" => {
+            if !property.value.eq_ignore_ascii_case("NULL") {
+                primitive.textures.push(property.value);
+            }
+        }
+        "ALPHAON" => primitive.alpha_on = property.value != "0",
+        "SRCBLENDID" => primitive.src_blend = parse_number(&property.value)? as i32,
+        "DSTBLENDID" => primitive.dst_blend = parse_number(&property.value)? as i32,
+        "NUM_INFOS" => {}
+        _ => primitive.properties.push(property),
+    }
+    Some(())
+}
+
+fn parse_number(value: &str) -> Option<f32> {
+    value.parse().ok()
+}
+
+fn parse_numbers<const N: usize>(value: &str) -> Option<[f32; N]> {
+    let numbers = value.split_whitespace().map(parse_number).collect::<Option<Vec<_>>>()?;
+    numbers.try_into().ok()
+}
@@ -0,0 +1 @@
+pub mod fxp_parser;
\ No newline at end of file
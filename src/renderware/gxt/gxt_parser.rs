@@ -0,0 +1,246 @@
+//! # GXT Parser
+//!
+//! A parser for the text tables (`.gxt`) of Grand Theft Auto 3, Vice City,
+//! and San Andreas, which hold the subtitles, mission text and interface
+//! strings of each language.
+//!
+//! ## Features
+//!
+//! - Parses the single-table GTA3 format and the multi-table (`TABL`)
+//!   formats of Vice City and San Andreas.
+//! - Decodes the 16-bit characters of GTA3/VC and the 8-bit characters of
+//!   San Andreas through the game's font charset.
+//! - Looks up San Andreas entries, whose keys are stored as CRC32 hashes.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::gxt::gxt_parser::GxtParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/american.gxt").unwrap();
+//! let gxt_data = GxtParser::new(&file_data).parse().unwrap();
+//!
+//! println!("{:?}", gxt_data.get("FEM_OK"));
+//! ```
+
+use crate::renderware::rw_file::RwFile;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// Represents the version of a GXT file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum GxtVersion {
+    /// GTA3 format: a single table with 16-bit characters.
+    Gta3,
+    /// Vice City format: several tables with 16-bit characters.
+    ViceCity,
+    /// San Andreas format: several tables with hashed keys.
+    SanAndreas,
+}
+
+/// Represents a parsed GXT file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwGxt {
+    pub version: GxtVersion,
+    /// The tables of the file. `MAIN` holds the text that is always loaded;
+    /// the others hold mission text. GTA3 files have a single `MAIN` table.
+    pub tables: Vec<GxtTable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GxtTable {
+    pub name: String,
+    pub entries: Vec<GxtEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GxtEntry {
+    /// The key name. San Andreas only stores its hash.
+    pub key: Option<String>,
+    /// The key hash, as computed by `gxt_key_hash`.
+    pub key_hash: u32,
+    pub text: String,
+}
+
+impl RwGxt {
+    /// Returns the text of a key from any table.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tables.iter().find_map(|table| table.get(key))
+    }
+
+    pub fn table(&self, name: &str) -> Option<&GxtTable> {
+        self.tables.iter().find(|table| table.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl GxtTable {
+    /// Returns the text of a key, compared case-insensitively as the key
+    /// hashes are.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key_hash = gxt_key_hash(key);
+        self.entries.iter().find(|entry| entry.key_hash == key_hash).map(|entry| entry.text.as_str())
+    }
+}
+
+/// Computes the San Andreas hash of a key: the CRC32 of its uppercase form,
+/// without the final inversion.
+pub fn gxt_key_hash(key: &str) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for byte in key.bytes().map(|byte| byte.to_ascii_uppercase()) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+/// The characters drawn by the game's font for codes 0x80 to 0xAF, which
+/// cover the accented letters of the European releases.
+const FONT_CHARSET: [char; 48] = [
+    'À', 'Á', 'Â', 'Ä', 'Æ', 'Ç', 'È', 'É', 'Ê', 'Ë', 'Ì', 'Í', 'Î', 'Ï', 'Ò', 'Ó', //
+    'Ô', 'Ö', 'Ù', 'Ú', 'Û', 'Ü', 'ß', 'à', 'á', 'â', 'ä', 'æ', 'ç', 'è', 'é', 'ê', //
+    'ë', 'ì', 'í', 'î', 'ï', 'ò', 'ó', 'ô', 'ö', 'ù', 'ú', 'û', 'ü', 'Ñ', 'ñ', '¿',
+];
+
+/// Decodes a character code of the game's font. Codes outside the accented
+/// range map to the Unicode code point of the same value.
+pub fn decode_gxt_char(code: u16) -> char {
+    match code {
+        0x80..=0xAF => FONT_CHARSET[(code - 0x80) as usize],
+        _ => char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+    }
+}
+
+/// The main parser for GXT files.
+pub struct GxtParser<'a> {
+    file: RwFile<'a>,
+}
+
+impl<'a> GxtParser<'a> {
+    /// Creates a new `GxtParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw GXT file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        GxtParser {
+            file: RwFile::new(buffer),
+        }
+    }
+
+    /// Parses the GXT file, detecting its version from the first section.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwGxt` data or an `std::io::Error`
+    /// if the format is not recognized or a parsing error occurs.
+    pub fn parse(&mut self) -> Result<RwGxt> {
+        let signature = self.file.get_stream().read(8)?;
+        self.file.get_stream().set_position(0);
+
+        match (&signature[0..4], &signature[4..8]) {
+            (b"TKEY", _) => Ok(RwGxt {
+                version: GxtVersion::Gta3,
+                tables: vec![self.read_table("MAIN".to_string(), 0, GxtVersion::Gta3, 16)?],
+            }),
+            (b"TABL", _) => self.read_tables(0, GxtVersion::ViceCity, 16),
+            (_, b"TABL") => {
+                self.file.get_stream().skip(2)?; // version
+                let bits_per_char = self.file.get_stream().read_u16()?;
+                if bits_per_char != 8 && bits_per_char != 16 {
+                    return Err(Error::new(ErrorKind::InvalidData, format!("Unsupported GXT character size {}", bits_per_char)));
+                }
+                self.read_tables(4, GxtVersion::SanAndreas, bits_per_char)
+            }
+            _ => Err(Error::new(ErrorKind::InvalidData, "Unsupported GXT format")),
+        }
+    }
+
+    fn read_tables(&mut self, position: u64, version: GxtVersion, bits_per_char: u16) -> Result<RwGxt> {
+        self.file.get_stream().set_position(position);
+        self.expect_section("TABL")?;
+        let size = self.file.get_stream().read_u32()?;
+
+        let capacity = (size as u64 / 12).min(self.file.get_stream().remaining() / 12);
+        let mut headers = Vec::with_capacity(capacity as usize);
+        for _ in 0..size / 12 {
+            let name = self.file.get_stream().read_string(8)?;
+            let offset = self.file.get_stream().read_u32()?;
+            headers.push((name, offset));
+        }
+
+        let mut tables = Vec::with_capacity(headers.len());
+        for (name, offset) in headers {
+            // Tables other than MAIN repeat their name before their keys
+            let keys_offset = if name == "MAIN" { offset } else { offset + 8 };
+            tables.push(self.read_table(name, keys_offset as u64, version, bits_per_char)?);
+        }
+
+        Ok(RwGxt { version, tables })
+    }
+
+    fn read_table(&mut self, name: String, position: u64, version: GxtVersion, bits_per_char: u16) -> Result<GxtTable> {
+        self.file.get_stream().set_position(position);
+        self.expect_section("TKEY")?;
+        let keys_size = self.file.get_stream().read_u32()?;
+
+        let mut keys = Vec::new();
+        if version == GxtVersion::SanAndreas {
+            for _ in 0..keys_size / 8 {
+                let offset = self.file.get_stream().read_u32()?;
+                let key_hash = self.file.get_stream().read_u32()?;
+                keys.push((offset, None, key_hash));
+            }
+        } else {
+            for _ in 0..keys_size / 12 {
+                let offset = self.file.get_stream().read_u32()?;
+                let key = self.file.get_stream().read_string(8)?;
+                let key_hash = gxt_key_hash(&key);
+                keys.push((offset, Some(key), key_hash));
+            }
+        }
+
+        self.file.get_stream().set_position(position + 8 + keys_size as u64);
+        self.expect_section("TDAT")?;
+        let _data_size = self.file.get_stream().read_u32()?;
+        let data_position = self.file.get_stream().get_position();
+
+        let mut entries = Vec::with_capacity(keys.len());
+        for (offset, key, key_hash) in keys {
+            self.file.get_stream().set_position(data_position + offset as u64);
+            let text = self.read_text(bits_per_char)?;
+            entries.push(GxtEntry { key, key_hash, text });
+        }
+
+        Ok(GxtTable { name, entries })
+    }
+
+    fn read_text(&mut self, bits_per_char: u16) -> Result<String> {
+        let mut text = String::new();
+        loop {
+            let code = if bits_per_char == 8 {
+                self.file.get_stream().read_u8()? as u16
+            } else {
+                self.file.get_stream().read_u16()?
+            };
+            if code == 0 {
+                return Ok(text);
+            }
+            text.push(decode_gxt_char(code));
+        }
+    }
+
+    fn expect_section(&mut self, name: &str) -> Result<()> {
+        let signature = self.file.get_stream().read_string(4)?;
+        if signature != name {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Expected GXT {} section, found {:?}", name, signature),
+            ));
+        }
+        Ok(())
+    }
+}
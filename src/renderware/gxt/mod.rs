@@ -0,0 +1 @@
+pub mod gxt_parser;
\ No newline at end of file
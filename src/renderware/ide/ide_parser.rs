@@ -0,0 +1,305 @@
+//! # IDE Parser
+//!
+//! A parser for item definition files (`.ide`), which declare the models of
+//! Grand Theft Auto 3, Vice City, and San Andreas along with the texture
+//! dictionaries they use.
+//!
+//! ## Features
+//!
+//! - Parses the `objs`, `tobj`, `anim`, `weap` and `hier` sections into
+//!   object definitions with draw distances and flags.
+//! - Parses `peds` and `cars` definitions, including vehicle types,
+//!   handling ids and game names.
+//! - Parses the `txdp` texture dictionary parent links of San Andreas.
+//! - Resolves the texture dictionary of a model by name.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ide::ide_parser::IdeParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/definitions.ide").unwrap();
+//! let ide_data = IdeParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Infernus TXD: {:?}", ide_data.txd_for_model("infernus"));
+//! ```
+
+use crate::renderware::common::text::section_lines;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// Represents a parsed IDE file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwIde {
+    pub objects: Vec<IdeObject>,
+    pub peds: Vec<IdePed>,
+    pub vehicles: Vec<IdeVehicle>,
+    pub texture_parents: Vec<IdeTextureParent>,
+    /// The entries of sections without a dedicated type, such as `2dfx` or
+    /// `path`.
+    pub other: Vec<IdeRawEntry>,
+}
+
+/// The section an object definition was declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IdeObjectKind {
+    /// A static object (`objs`).
+    Object,
+    /// An object only visible between two hours (`tobj`).
+    TimedObject,
+    /// An object with a skeletal animation (`anim`).
+    AnimatedObject,
+    /// A weapon model (`weap`).
+    Weapon,
+    /// A cutscene hierarchy (`hier`).
+    Hierarchy,
+}
+
+/// A model definition from one of the object sections.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdeObject {
+    pub kind: IdeObjectKind,
+    pub id: i32,
+    pub model_name: String,
+    pub txd_name: String,
+    /// The draw distance of each mesh. GTA3 and Vice City objects may have
+    /// up to three meshes; San Andreas objects have one.
+    pub draw_distances: Vec<f32>,
+    pub flags: u32,
+    /// The hours between which a `tobj` object is visible.
+    pub time_on: Option<u8>,
+    pub time_off: Option<u8>,
+    /// The IFP file or animation name of `anim` and `weap` objects.
+    pub animation: Option<String>,
+}
+
+/// A pedestrian definition from a `peds` line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdePed {
+    pub id: i32,
+    pub model_name: String,
+    pub txd_name: String,
+    pub ped_type: String,
+    pub behavior: String,
+    pub anim_group: String,
+    /// A bit mask of the vehicle classes the ped drives.
+    pub cars_can_drive: u32,
+    /// The remaining fields, which differ between games.
+    pub extra: Vec<String>,
+}
+
+/// A vehicle definition from a `cars` line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdeVehicle {
+    pub id: i32,
+    pub model_name: String,
+    pub txd_name: String,
+    /// The vehicle type, such as `car`, `bike` or `boat`.
+    pub vehicle_type: String,
+    /// The name of the `handling.cfg` entry.
+    pub handling_id: String,
+    /// The GXT key of the vehicle name.
+    pub game_name: String,
+    /// The remaining fields, which differ between games and vehicle types.
+    pub extra: Vec<String>,
+}
+
+/// A texture dictionary parent link from a `txdp` line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IdeTextureParent {
+    pub txd_name: String,
+    pub parent_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdeRawEntry {
+    pub section: String,
+    pub fields: Vec<String>,
+}
+
+impl RwIde {
+    /// Returns the texture dictionary name of the model with the given name,
+    /// compared case-insensitively.
+    pub fn txd_for_model(&self, model_name: &str) -> Option<&str> {
+        let objects = self.objects.iter().map(|object| (&object.model_name, &object.txd_name));
+        let peds = self.peds.iter().map(|ped| (&ped.model_name, &ped.txd_name));
+        let vehicles = self.vehicles.iter().map(|vehicle| (&vehicle.model_name, &vehicle.txd_name));
+
+        objects
+            .chain(peds)
+            .chain(vehicles)
+            .find(|(name, _)| name.eq_ignore_ascii_case(model_name))
+            .map(|(_, txd_name)| txd_name.as_str())
+    }
+
+    /// Returns the parent of a texture dictionary declared by `txdp`.
+    pub fn txd_parent(&self, txd_name: &str) -> Option<&str> {
+        self.texture_parents
+            .iter()
+            .find(|link| link.txd_name.eq_ignore_ascii_case(txd_name))
+            .map(|link| link.parent_name.as_str())
+    }
+}
+
+/// The main parser for IDE files.
+pub struct IdeParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> IdeParser<'a> {
+    /// Creates a new `IdeParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw IDE file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        IdeParser { buffer }
+    }
+
+    /// Parses the IDE file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwIde` data or an `std::io::Error`
+    /// if a line is malformed.
+    pub fn parse(&mut self) -> Result<RwIde> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut ide = RwIde::default();
+
+        for line in section_lines(&text) {
+            let fields = &line.fields;
+            let error = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("IDE line {}: invalid entry in {} section", line.line_number, line.section),
+                )
+            };
+
+            match line.section.as_str() {
+                "objs" => ide.objects.push(parse_object(IdeObjectKind::Object, fields).ok_or_else(error)?),
+                "tobj" => ide.objects.push(parse_object(IdeObjectKind::TimedObject, fields).ok_or_else(error)?),
+                "anim" => ide.objects.push(parse_object(IdeObjectKind::AnimatedObject, fields).ok_or_else(error)?),
+                "weap" => ide.objects.push(parse_object(IdeObjectKind::Weapon, fields).ok_or_else(error)?),
+                "hier" => ide.objects.push(parse_object(IdeObjectKind::Hierarchy, fields).ok_or_else(error)?),
+                "peds" => ide.peds.push(parse_ped(fields).ok_or_else(error)?),
+                "cars" => ide.vehicles.push(parse_vehicle(fields).ok_or_else(error)?),
+                "txdp" => ide.texture_parents.push(parse_texture_parent(fields).ok_or_else(error)?),
+                section => ide.other.push(IdeRawEntry {
+                    section: section.to_string(),
+                    fields: fields.iter().map(|field| field.to_string()).collect(),
+                }),
+            }
+        }
+
+        Ok(ide)
+    }
+}
+
+fn parse_object(kind: IdeObjectKind, fields: &[&str]) -> Option<IdeObject> {
+    let id = fields.first()?.parse().ok()?;
+    let model_name = fields.get(1)?.to_string();
+    let txd_name = fields.get(2)?.to_string();
+
+    let mut object = IdeObject {
+        kind,
+        id,
+        model_name,
+        txd_name,
+        draw_distances: Vec::new(),
+        flags: 0,
+        time_on: None,
+        time_off: None,
+        animation: None,
+    };
+
+    let mut rest = &fields[3..];
+    if kind == IdeObjectKind::Hierarchy {
+        return Some(object);
+    }
+    if matches!(kind, IdeObjectKind::AnimatedObject | IdeObjectKind::Weapon) {
+        object.animation = Some(rest.first()?.to_string());
+        rest = &rest[1..];
+    }
+    if kind == IdeObjectKind::TimedObject {
+        let (times, data) = rest.split_at(rest.len().checked_sub(2)?);
+        object.time_off = Some(data[1].parse().ok()?);
+        object.time_on = Some(data[0].parse().ok()?);
+        rest = times;
+    }
+
+    // Either `draw distance, flags` (San Andreas), or `mesh count, one draw
+    // distance per mesh, flags`. Weapons always store the mesh count, and
+    // only San Andreas adds their flags.
+    let has_mesh_count = kind == IdeObjectKind::Weapon || rest.len() > 2;
+    if has_mesh_count {
+        let mesh_count: usize = rest.first()?.parse().ok()?;
+        let has_flags = rest.len() == mesh_count + 2;
+        if !(1..=3).contains(&mesh_count) || !(has_flags || kind == IdeObjectKind::Weapon && rest.len() == mesh_count + 1) {
+            return None;
+        }
+        for distance in &rest[1..=mesh_count] {
+            object.draw_distances.push(distance.parse().ok()?);
+        }
+        if !has_flags {
+            return Some(object);
+        }
+    } else if rest.len() == 2 {
+        object.draw_distances.push(rest[0].parse().ok()?);
+    } else {
+        return None;
+    }
+    object.flags = parse_flags(rest.last()?)?;
+
+    Some(object)
+}
+
+fn parse_ped(fields: &[&str]) -> Option<IdePed> {
+    if fields.len() < 7 {
+        return None;
+    }
+
+    Some(IdePed {
+        id: fields[0].parse().ok()?,
+        model_name: fields[1].to_string(),
+        txd_name: fields[2].to_string(),
+        ped_type: fields[3].to_string(),
+        behavior: fields[4].to_string(),
+        anim_group: fields[5].to_string(),
+        cars_can_drive: parse_flags(fields[6])?,
+        extra: fields[7..].iter().map(|field| field.to_string()).collect(),
+    })
+}
+
+fn parse_vehicle(fields: &[&str]) -> Option<IdeVehicle> {
+    if fields.len() < 6 {
+        return None;
+    }
+
+    Some(IdeVehicle {
+        id: fields[0].parse().ok()?,
+        model_name: fields[1].to_string(),
+        txd_name: fields[2].to_string(),
+        vehicle_type: fields[3].to_string(),
+        handling_id: fields[4].to_string(),
+        game_name: fields[5].to_string(),
+        extra: fields[6..].iter().map(|field| field.to_string()).collect(),
+    })
+}
+
+fn parse_texture_parent(fields: &[&str]) -> Option<IdeTextureParent> {
+    Some(IdeTextureParent {
+        txd_name: fields.first()?.to_string(),
+        parent_name: fields.get(1)?.to_string(),
+    })
+}
+
+/// Parses a flags field, which is decimal or hexadecimal with a `0x` prefix
+/// or without one when it is clearly hexadecimal.
+fn parse_flags(field: &str) -> Option<u32> {
+    if let Some(hex) = field.strip_prefix("0x").or_else(|| field.strip_prefix("0X")) {
+        return u32::from_str_radix(hex, 16).ok();
+    }
+    field.parse().ok().or_else(|| u32::from_str_radix(field, 16).ok())
+}
@@ -0,0 +1 @@
+pub mod ide_parser;
\ No newline at end of file
@@ -0,0 +1,136 @@
+//! # Ped Bone Tables
+//!
+//! The standard bone ids and names of the pedestrian skeletons used by Vice
+//! City and San Andreas. `ANP3` animations identify bones by id only and
+//! `ANPK` animations often by name only, so these tables are needed to match
+//! bones between the two or against a model.
+//!
+//! ## Example
+//!
+//! ```
+//! use rw_parser_rs::renderware::ifp::ifp_bones::{bone_id, bone_name, Skeleton};
+//!
+//! assert_eq!(bone_name(Skeleton::SanAndreas, 5), Some("Head"));
+//! assert_eq!(bone_id(Skeleton::ViceCity, "r forearm"), Some(23));
+//! ```
+
+use super::ifp_parser::RwIfpBone;
+use serde::Serialize;
+
+/// A ped skeleton naming convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Skeleton {
+    ViceCity,
+    SanAndreas,
+}
+
+/// A bone of a standard ped skeleton.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BoneInfo {
+    pub id: i32,
+    pub name: &'static str,
+}
+
+const fn bone(id: i32, name: &'static str) -> BoneInfo {
+    BoneInfo { id, name }
+}
+
+/// The Vice City ped skeleton.
+pub const VC_PED_BONES: &[BoneInfo] = &[
+    bone(0, "Root"),
+    bone(1, "Pelvis"),
+    bone(2, "Spine"),
+    bone(3, "Spine1"),
+    bone(4, "Neck"),
+    bone(5, "Head"),
+    bone(21, "Bip01 R Clavicle"),
+    bone(22, "R UpperArm"),
+    bone(23, "R Forearm"),
+    bone(24, "R Hand"),
+    bone(25, "R Fingers"),
+    bone(31, "Bip01 L Clavicle"),
+    bone(32, "L UpperArm"),
+    bone(33, "L Forearm"),
+    bone(34, "L Hand"),
+    bone(35, "L Fingers"),
+    bone(41, "L Thigh"),
+    bone(42, "L Calf"),
+    bone(43, "L Foot"),
+    bone(51, "R Thigh"),
+    bone(52, "R Calf"),
+    bone(53, "R Foot"),
+];
+
+/// The San Andreas ped skeleton.
+pub const SA_PED_BONES: &[BoneInfo] = &[
+    bone(0, "Root"),
+    bone(1, "Pelvis"),
+    bone(2, "Spine"),
+    bone(3, "Spine1"),
+    bone(4, "Neck"),
+    bone(5, "Head"),
+    bone(6, "L Brow"),
+    bone(7, "R Brow"),
+    bone(8, "Jaw"),
+    bone(21, "Bip01 R Clavicle"),
+    bone(22, "R UpperArm"),
+    bone(23, "R ForeArm"),
+    bone(24, "R Hand"),
+    bone(25, "R Finger"),
+    bone(26, "R Finger01"),
+    bone(31, "Bip01 L Clavicle"),
+    bone(32, "L UpperArm"),
+    bone(33, "L ForeArm"),
+    bone(34, "L Hand"),
+    bone(35, "L Finger"),
+    bone(36, "L Finger01"),
+    bone(41, "L Thigh"),
+    bone(42, "L Calf"),
+    bone(43, "L Foot"),
+    bone(44, "L Toe0"),
+    bone(51, "R Thigh"),
+    bone(52, "R Calf"),
+    bone(53, "R Foot"),
+    bone(54, "R Toe0"),
+    bone(201, "Belly"),
+    bone(301, "R Breast"),
+    bone(302, "L Breast"),
+];
+
+impl Skeleton {
+    /// Returns the bones of this skeleton.
+    pub fn bones(self) -> &'static [BoneInfo] {
+        match self {
+            Skeleton::ViceCity => VC_PED_BONES,
+            Skeleton::SanAndreas => SA_PED_BONES,
+        }
+    }
+}
+
+/// Looks up the name of a bone id.
+pub fn bone_name(skeleton: Skeleton, id: i32) -> Option<&'static str> {
+    skeleton.bones().iter().find(|bone| bone.id == id).map(|bone| bone.name)
+}
+
+/// Looks up the id of a bone name, ignoring ASCII case.
+pub fn bone_id(skeleton: Skeleton, name: &str) -> Option<i32> {
+    skeleton.bones().iter().find(|bone| bone.name.eq_ignore_ascii_case(name)).map(|bone| bone.id)
+}
+
+impl RwIfpBone {
+    /// Returns the standard bone id of this bone: its own id when it has
+    /// one, otherwise the id its name has in either skeleton.
+    pub fn canonical_id(&self) -> Option<i32> {
+        if self.use_bone_id {
+            return Some(self.bone_id);
+        }
+        bone_id(Skeleton::SanAndreas, &self.name).or_else(|| bone_id(Skeleton::ViceCity, &self.name))
+    }
+
+    /// Returns the San Andreas name of this bone, resolved through its id or
+    /// its name.
+    pub fn canonical_name(&self) -> Option<&'static str> {
+        self.canonical_id()
+            .and_then(|id| bone_name(Skeleton::SanAndreas, id).or_else(|| bone_name(Skeleton::ViceCity, id)))
+    }
+}
@@ -0,0 +1,125 @@
+//! # IFP Conversion
+//!
+//! Converts parsed animation packages between the `ANP3` and `ANPK` layouts.
+//!
+//! The two formats differ in how keyframes are stored:
+//!
+//! - `ANP3` keyframes are fixed point: rotations in units of 1/4096,
+//!   translations in units of 1/1024 and times in 1/60 second ticks. Every
+//!   bone is identified by its bone id and scale keyframes are not supported.
+//! - `ANPK` keyframes are floats with times in seconds, bones may be
+//!   identified by name only, and `KRTS` keyframes carry a scale.
+//!
+//! Bones identified by name only are given their standard ped bone id when
+//! converting to `ANP3`.
+//!
+//! Converting to `ANP3` quantizes the keyframes to the precision the format
+//! can store, so the result matches what the game would load.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ifp::ifp_parser::{IfpParser, IfpVersion};
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/animation.ifp").unwrap();
+//! let ifp = IfpParser::new(&file_data).parse().unwrap();
+//!
+//! let converted = ifp.convert(IfpVersion::ANPK).unwrap();
+//! println!("{} animations converted", converted.animations.len());
+//! ```
+
+use super::ifp_parser::{IfpTimeBase, IfpVersion, KeyframeType, RwIfp, RwIfpBone, RwIfpKeyframe, ANP3_TICKS_PER_SECOND};
+use crate::renderware::common::types::{RwQuaternion, RwVector3};
+use std::io::{Error, ErrorKind, Result};
+
+impl RwIfp {
+    /// Returns a copy of this package converted to `version`.
+    ///
+    /// Converting to the package's own version returns an unchanged copy.
+    pub fn convert(&self, version: IfpVersion) -> Result<RwIfp> {
+        let convert_bone: fn(&RwIfpBone, f32) -> RwIfpBone = match (self.version, version) {
+            (from, to) if from == to && to != IfpVersion::UNSUPPORTED => return Ok(self.clone()),
+            (IfpVersion::ANP3, IfpVersion::ANPK) => anp3_bone_to_anpk,
+            (IfpVersion::ANPK, IfpVersion::ANP3) => anpk_bone_to_anp3,
+            (from, to) => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!("Cannot convert IFP from {:?} to {:?}", from, to),
+                ));
+            }
+        };
+
+        let mut converted = self.clone();
+        converted.version = version;
+        for animation in &mut converted.animations {
+            let time_scale = match (version, animation.time_base) {
+                (IfpVersion::ANPK, IfpTimeBase::Ticks) => 1.0 / ANP3_TICKS_PER_SECOND,
+                (IfpVersion::ANP3, IfpTimeBase::Seconds) => ANP3_TICKS_PER_SECOND,
+                _ => 1.0,
+            };
+            animation.time_base = if version == IfpVersion::ANP3 { IfpTimeBase::Ticks } else { IfpTimeBase::Seconds };
+            if version == IfpVersion::ANP3 {
+                animation.anpk_info = None;
+            }
+            for bone in &mut animation.bones {
+                *bone = convert_bone(bone, time_scale);
+            }
+        }
+        Ok(converted)
+    }
+}
+
+fn anp3_bone_to_anpk(bone: &RwIfpBone, time_scale: f32) -> RwIfpBone {
+    let keyframes = bone
+        .keyframes
+        .iter()
+        .map(|keyframe| RwIfpKeyframe {
+            time: keyframe.time * time_scale,
+            ..*keyframe
+        })
+        .collect();
+
+    RwIfpBone {
+        keyframes,
+        ..bone.clone()
+    }
+}
+
+fn anpk_bone_to_anp3(bone: &RwIfpBone, time_scale: f32) -> RwIfpBone {
+    let has_translation = bone.has_translation();
+    let quantize = |value: f32, scale: f32| (value * scale).round().clamp(i16::MIN as f32, i16::MAX as f32) / scale;
+
+    let keyframes = bone
+        .keyframes
+        .iter()
+        .map(|keyframe| RwIfpKeyframe {
+            time: (keyframe.time * time_scale).round(),
+            position: if has_translation {
+                RwVector3 {
+                    x: quantize(keyframe.position.x, 1024.0),
+                    y: quantize(keyframe.position.y, 1024.0),
+                    z: quantize(keyframe.position.z, 1024.0),
+                }
+            } else {
+                RwVector3 { x: 0.0, y: 0.0, z: 0.0 }
+            },
+            rotation: RwQuaternion {
+                x: quantize(keyframe.rotation.x, 4096.0),
+                y: quantize(keyframe.rotation.y, 4096.0),
+                z: quantize(keyframe.rotation.z, 4096.0),
+                w: quantize(keyframe.rotation.w, 4096.0),
+            },
+            scale: RwVector3 { x: 1.0, y: 1.0, z: 1.0 },
+        })
+        .collect();
+
+    RwIfpBone {
+        keyframe_type: if has_translation { KeyframeType::KRT0 } else { KeyframeType::KR00 },
+        use_bone_id: true,
+        bone_id: bone.canonical_id().unwrap_or(bone.bone_id),
+        keyframes,
+        anpk_info: None,
+        ..bone.clone()
+    }
+}
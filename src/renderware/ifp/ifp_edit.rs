@@ -0,0 +1,136 @@
+//! # IFP Editing
+//!
+//! Trimming, splitting and joining of animations. Cut points that fall
+//! between keyframes are resampled, so the edited animation plays exactly
+//! like the corresponding part of the original. All times are in the units
+//! of the animation's keyframes.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/animation.ifp").unwrap();
+//! let ifp = IfpParser::new(&file_data).parse().unwrap();
+//!
+//! let animation = &ifp.animations[0];
+//! let idle = animation.slice(0.5, 1.5);
+//! let looped = idle.concat(&idle);
+//! println!("{} -> {}", idle.duration(), looped.duration());
+//! ```
+
+use super::ifp_parser::{RwIfpAnimation, RwIfpBone, RwIfpKeyframe};
+
+impl RwIfpAnimation {
+    /// Returns the part of the animation between `start` and `end`, with
+    /// times rebased to start at zero.
+    pub fn slice(&self, start: f32, end: f32) -> RwIfpAnimation {
+        let (start, end) = (start.min(end), start.max(end));
+        RwIfpAnimation {
+            name: self.name.clone(),
+            bones: self.bones.iter().map(|bone| slice_bone(bone, start, end)).collect(),
+            anpk_info: self.anpk_info,
+            time_base: self.time_base,
+            original_time_base: self.original_time_base,
+        }
+    }
+
+    /// Cuts the animation at each of `times`, returning one animation per
+    /// segment. Segments are named after the original with a numeric suffix.
+    pub fn split_at(&self, times: &[f32]) -> Vec<RwIfpAnimation> {
+        let duration = self.duration();
+        let mut cuts: Vec<f32> = times.iter().copied().filter(|&time| time > 0.0 && time < duration).collect();
+        cuts.sort_by(f32::total_cmp);
+        cuts.dedup();
+
+        let mut boundaries = vec![0.0];
+        boundaries.extend(cuts);
+        boundaries.push(duration);
+
+        boundaries
+            .windows(2)
+            .enumerate()
+            .map(|(index, window)| RwIfpAnimation {
+                name: format!("{}_{}", self.name, index),
+                ..self.slice(window[0], window[1])
+            })
+            .collect()
+    }
+
+    /// Appends `other` to this animation, shifting its keyframes to start
+    /// where this animation ends.
+    ///
+    /// Bones are matched by bone id when both use ids and by name otherwise.
+    /// Bones present in only one of the animations are kept as they are.
+    pub fn concat(&self, other: &RwIfpAnimation) -> RwIfpAnimation {
+        let offset = self.duration();
+        let mut bones = self.bones.clone();
+
+        for other_bone in &other.bones {
+            let shifted = other_bone.keyframes.iter().map(|keyframe| RwIfpKeyframe {
+                time: keyframe.time + offset,
+                ..*keyframe
+            });
+
+            let existing = bones.iter_mut().find(|bone| {
+                if bone.use_bone_id && other_bone.use_bone_id {
+                    bone.bone_id == other_bone.bone_id
+                } else {
+                    bone.name.eq_ignore_ascii_case(&other_bone.name)
+                }
+            });
+
+            match existing {
+                Some(bone) => bone.keyframes.extend(shifted),
+                None => bones.push(RwIfpBone {
+                    keyframes: shifted.collect(),
+                    ..other_bone.clone()
+                }),
+            }
+        }
+
+        RwIfpAnimation {
+            name: self.name.clone(),
+            bones,
+            anpk_info: self.anpk_info,
+            time_base: self.time_base,
+            original_time_base: self.original_time_base,
+        }
+    }
+}
+
+fn slice_bone(bone: &RwIfpBone, start: f32, end: f32) -> RwIfpBone {
+    if bone.keyframes.is_empty() {
+        return bone.clone();
+    }
+
+    let sample = |time: f32| {
+        let pose = bone.sample(time);
+        RwIfpKeyframe {
+            time: time - start,
+            position: pose.position,
+            rotation: pose.rotation,
+            scale: pose.scale,
+        }
+    };
+
+    let mut keyframes = vec![sample(start)];
+    keyframes.extend(
+        bone.keyframes
+            .iter()
+            .filter(|keyframe| keyframe.time > start && keyframe.time < end)
+            .map(|keyframe| RwIfpKeyframe {
+                time: keyframe.time - start,
+                ..*keyframe
+            }),
+    );
+    if end > start {
+        keyframes.push(sample(end));
+    }
+
+    RwIfpBone {
+        keyframes,
+        ..bone.clone()
+    }
+}
@@ -0,0 +1,99 @@
+//! # IFP Keyframe Optimization
+//!
+//! Removes keyframes that can be reconstructed by interpolating their
+//! neighbours, shrinking animations built from motion capture data without
+//! visibly changing them.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/animation.ifp").unwrap();
+//! let mut ifp = IfpParser::new(&file_data).parse().unwrap();
+//!
+//! for animation in &mut ifp.animations {
+//!     let report = animation.optimize(0.001);
+//!     println!("{}: {:.1}% removed", animation.name, report.reduction() * 100.0);
+//! }
+//! ```
+
+use super::ifp_parser::{RwIfpAnimation, RwIfpKeyframe};
+use super::ifp_sample::interpolate_keyframes;
+use crate::renderware::common::types::RwVector3;
+use serde::Serialize;
+
+/// The effect of an optimization pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OptimizationReport {
+    pub keyframes_before: usize,
+    pub keyframes_after: usize,
+}
+
+impl OptimizationReport {
+    /// Returns the fraction of keyframes removed, from 0 to 1.
+    pub fn reduction(&self) -> f32 {
+        if self.keyframes_before == 0 {
+            0.0
+        } else {
+            1.0 - self.keyframes_after as f32 / self.keyframes_before as f32
+        }
+    }
+}
+
+impl RwIfpAnimation {
+    /// Removes keyframes that interpolation between the remaining keyframes
+    /// reproduces within `tolerance`.
+    ///
+    /// The tolerance applies to translation and scale in model units and to
+    /// rotation in radians. The first and last keyframe of every bone are
+    /// always kept.
+    pub fn optimize(&mut self, tolerance: f32) -> OptimizationReport {
+        let keyframes_before = self.bones.iter().map(|bone| bone.keyframes.len()).sum();
+        for bone in &mut self.bones {
+            bone.keyframes = reduce_keyframes(&bone.keyframes, tolerance);
+        }
+        let keyframes_after = self.bones.iter().map(|bone| bone.keyframes.len()).sum();
+
+        OptimizationReport {
+            keyframes_before,
+            keyframes_after,
+        }
+    }
+}
+
+fn reduce_keyframes(keyframes: &[RwIfpKeyframe], tolerance: f32) -> Vec<RwIfpKeyframe> {
+    if keyframes.len() <= 2 {
+        return keyframes.to_vec();
+    }
+
+    let mut kept = vec![keyframes[0]];
+    let mut anchor = 0;
+    for candidate in 2..keyframes.len() {
+        // Check whether every keyframe between the anchor and the candidate
+        // can be dropped in favour of interpolating between the two
+        let (start, end) = (&keyframes[anchor], &keyframes[candidate]);
+        let reducible = keyframes[anchor + 1..candidate].iter().all(|keyframe| {
+            let span = end.time - start.time;
+            let t = if span > 0.0 { (keyframe.time - start.time) / span } else { 0.0 };
+            keyframe_error(keyframe, &interpolate_keyframes(start, end, t)) <= tolerance
+        });
+
+        if !reducible {
+            anchor = candidate - 1;
+            kept.push(keyframes[anchor]);
+        }
+    }
+    kept.push(keyframes[keyframes.len() - 1]);
+    kept
+}
+
+/// Returns the largest difference between two keyframes across translation,
+/// scale and rotation angle.
+fn keyframe_error(a: &RwIfpKeyframe, b: &RwIfpKeyframe) -> f32 {
+    let distance = |p: RwVector3, q: RwVector3| {
+        ((p.x - q.x).powi(2) + (p.y - q.y).powi(2) + (p.z - q.z).powi(2)).sqrt()
+    };
+    distance(a.position, b.position).max(distance(a.scale, b.scale)).max(a.rotation.angle_to(b.rotation))
+}
@@ -8,7 +8,11 @@
 //!
 //! - Parses animation names, bone keyframes, and hierarchy.
 //! - Automatically detects and handles `ANP3` and `ANPK` versions.
+//! - Tolerates `ANP3` variants with delta-encoded times or missing
+//!   translations, and can normalize keyframe times to seconds.
 //! - Deserializes animation data into a structured `RwIfp` format.
+//! - Indexes animations without decoding them, so a single animation can be
+//!   loaded by name from large packages.
 //!
 //! ## Example
 //!
@@ -23,14 +27,21 @@
 //! println!("Animation package name: {}", ifp_data.name);
 //! ```
 
+use crate::renderware::rw_error::{catch_panic, RwParseError, RwResult};
 use crate::renderware::rw_file::RwFile;
+use crate::renderware::rw_report::{ParseMode, ParseReport};
+use crate::renderware::rw_source_map::{SourceItem, SourceMap, SourceSpan};
 use crate::renderware::common::types::{RwVector3, RwQuaternion};
-use std::io::Result;
+use std::io::{Read, Seek, SeekFrom};
+#[cfg(feature = "mmap")]
+use crate::utils::mapped_file::MappedFile;
+#[cfg(feature = "mmap")]
+use std::path::Path;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Represents the version of the IFP file.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IfpVersion {
     /// GTA3 / Vice City format.
     ANP3,
@@ -40,11 +51,27 @@ pub enum IfpVersion {
     UNSUPPORTED,
 }
 
+/// The number of `ANP3` time ticks per second.
+pub(crate) const ANP3_TICKS_PER_SECOND: f32 = 60.0;
+
+/// The unit of keyframe times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IfpTimeBase {
+    /// Seconds, as stored by `ANPK` files.
+    Seconds,
+    /// 1/60 second ticks, as stored by `ANP3` files.
+    Ticks,
+    /// Ticks relative to the previous keyframe, written by some third-party
+    /// tools. Times are always converted to absolute values when parsed, so
+    /// this only appears as `RwIfpAnimation::original_time_base`.
+    DeltaTicks,
+}
+
 /// Represents the top-level structure of a parsed IFP file.
 ///
 /// This struct contains the package name and a list of all animations
 /// included in the file.
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwIfp {
     /// The format version of the IFP file (`ANP3` or `ANPK`).
     pub version: IfpVersion,
@@ -54,22 +81,148 @@ pub struct RwIfp {
     pub animations: Vec<RwIfpAnimation>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwIfpAnimation {
     pub name: String,
     pub bones: Vec<RwIfpBone>,
+    /// Raw `DGAN`/`INFO` header fields, for `ANPK` animations.
+    pub anpk_info: Option<AnpkAnimationInfo>,
+    /// The unit of the keyframe times.
+    pub time_base: IfpTimeBase,
+    /// How the keyframe times were stored in the file.
+    pub original_time_base: IfpTimeBase,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+/// The components stored in each keyframe of a bone, named after the `ANPK`
+/// type tags. Every keyframe also stores a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyframeType {
+    /// No keyframes, used for bones without animation data.
+    K000,
+    /// Rotation only.
+    KR00,
+    /// Rotation and translation.
+    KRT0,
+    /// Rotation, translation and scale.
+    KRTS,
+    /// Rotation and scale.
+    KR0S,
+    /// Scale only.
+    K00S,
+}
+
+impl KeyframeType {
+    /// Parses an `ANPK` type tag, returning `None` for unknown tags.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "K000" => Some(KeyframeType::K000),
+            "KR00" => Some(KeyframeType::KR00),
+            "KRT0" => Some(KeyframeType::KRT0),
+            "KRTS" => Some(KeyframeType::KRTS),
+            "KR0S" => Some(KeyframeType::KR0S),
+            "K00S" => Some(KeyframeType::K00S),
+            _ => None,
+        }
+    }
+
+    /// Maps an `ANP3` frame type, which only distinguishes rotation-only (3)
+    /// and rotation with translation (4).
+    pub fn from_anp3(frame_type: u32) -> Option<Self> {
+        match frame_type {
+            3 => Some(KeyframeType::KR00),
+            4 => Some(KeyframeType::KRT0),
+            _ => None,
+        }
+    }
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            KeyframeType::K000 => "K000",
+            KeyframeType::KR00 => "KR00",
+            KeyframeType::KRT0 => "KRT0",
+            KeyframeType::KRTS => "KRTS",
+            KeyframeType::KR0S => "KR0S",
+            KeyframeType::K00S => "K00S",
+        }
+    }
+
+    pub fn has_rotation(self) -> bool {
+        matches!(self, KeyframeType::KR00 | KeyframeType::KRT0 | KeyframeType::KRTS | KeyframeType::KR0S)
+    }
+
+    pub fn has_translation(self) -> bool {
+        matches!(self, KeyframeType::KRT0 | KeyframeType::KRTS)
+    }
+
+    pub fn has_scale(self) -> bool {
+        matches!(self, KeyframeType::KRTS | KeyframeType::KR0S | KeyframeType::K00S)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwIfpBone {
     pub name: String,
-    pub keyframe_type: String,
+    pub keyframe_type: KeyframeType,
     pub use_bone_id: bool,
     pub bone_id: i32,
     pub keyframes: Vec<RwIfpKeyframe>,
+    /// Raw `CPAN`/`ANIM` header fields, for `ANPK` bones.
+    pub anpk_info: Option<AnpkBoneInfo>,
+}
+
+/// The `ANPK` animation header fields that playback does not need.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnpkAnimationInfo {
+    /// The size of the `DGAN` chunk holding the bones.
+    pub dgan_size: u32,
+    /// The size of the `INFO` chunk.
+    pub info_size: u32,
+    /// The field following the bone count in `INFO`, usually zero.
+    pub unknown: u32,
+}
+
+/// The `ANPK` bone header fields that playback does not need.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnpkBoneInfo {
+    /// The size of the `CPAN` chunk.
+    pub cpan_size: u32,
+    /// The size of the `ANIM` chunk: 44 when a bone id follows the header,
+    /// 48 otherwise.
+    pub anim_size: u32,
+    /// The two fields following the keyframe count. Tools describe them as
+    /// a frame type and sibling link.
+    pub unknown: [u32; 2],
+    /// The two trailing fields of 48-byte `ANIM` chunks, zero otherwise.
+    pub extra: [u32; 2],
+    /// The size of the keyframe chunk, zero for bones without keyframes.
+    pub keyframes_size: u32,
+}
+
+impl RwIfpBone {
+    /// Returns `true` if the keyframes carry translations (`KRT0`/`KRTS`).
+    pub fn has_translation(&self) -> bool {
+        self.keyframe_type.has_translation()
+    }
+}
+
+/// The animation names and offsets of an IFP file, read without decoding
+/// any keyframes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IfpIndex {
+    pub version: IfpVersion,
+    /// The name of the animation package.
+    pub name: String,
+    pub animations: Vec<IfpIndexEntry>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IfpIndexEntry {
+    pub name: String,
+    /// The offset of the animation header from the start of the file.
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct RwIfpKeyframe {
     pub time: f32,
     pub position: RwVector3,
@@ -83,6 +236,12 @@ pub struct RwIfpKeyframe {
 /// deserialize the IFP animation data.
 pub struct IfpParser<'a> {
     file: RwFile<'a>,
+    normalize_times: bool,
+    mode: ParseMode,
+    report: ParseReport,
+    source_map: SourceMap,
+    /// The spans of the bones of the animation being read.
+    bone_spans: Vec<SourceSpan>,
 }
 
 impl<'a> IfpParser<'a> {
@@ -94,9 +253,28 @@ impl<'a> IfpParser<'a> {
     pub fn new(buffer: &'a [u8]) -> Self {
         IfpParser {
             file: RwFile::new(buffer),
+            normalize_times: false,
+            mode: ParseMode::Standard,
+            report: ParseReport::default(),
+            source_map: SourceMap::default(),
+            bone_spans: Vec::new(),
         }
     }
 
+    /// Converts `ANP3` keyframe times from ticks to seconds while parsing, so
+    /// every animation uses `IfpTimeBase::Seconds`.
+    pub fn with_normalized_times(mut self, enabled: bool) -> Self {
+        self.normalize_times = enabled;
+        self
+    }
+
+    /// Sets how inconsistent data is handled. In lenient mode, parsing stops
+    /// at the first damaged animation and keeps the ones read before.
+    pub fn with_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     /// Parses the entire IFP file buffer.
     ///
     /// This method detects the IFP version (`ANP3` or `ANPK`) based on the
@@ -104,28 +282,163 @@ impl<'a> IfpParser<'a> {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the parsed `RwIfp` data or an `std::io::Error`
+    /// A `Result` containing the parsed `RwIfp` data or a `RwParseError`
     /// if the file format is not supported or a parsing error occurs.
-    pub fn parse(&mut self) -> Result<RwIfp> {
+    pub fn parse(&mut self) -> RwResult<RwIfp> {
+        self.report = ParseReport::default();
+        self.source_map = SourceMap::default();
         let file_signature = self.file.get_stream().read_string(4)?;
         self.file.get_stream().set_position(0);
 
         match file_signature.as_str() {
             "ANP3" => self.read_anp3(),
             "ANPK" => self.read_anpk(),
-            _ => Err(std::io::Error::new(std::io::ErrorKind::Other, "Unsupported IFP version")),
+            _ => Err(RwParseError::UnsupportedVersion { format: "IFP", version: file_signature }),
+        }
+    }
+
+    /// Parses the entire IFP file buffer like `parse`, also returning the
+    /// warnings met on the way: keyframe data not matching its declared size
+    /// and animations or bones without keyframes.
+    pub fn parse_with_report(&mut self) -> RwResult<(RwIfp, ParseReport)> {
+        let ifp = self.parse()?;
+        Ok((ifp, std::mem::take(&mut self.report)))
+    }
+
+    /// Parses the IFP file like `parse`, also returning where each animation
+    /// and bone was read from.
+    pub fn parse_with_source_map(&mut self) -> RwResult<(RwIfp, SourceMap)> {
+        let ifp = self.parse()?;
+        Ok((ifp, std::mem::take(&mut self.source_map)))
+    }
+
+    /// Parses IFP data from an untrusted source, such as an upload or a
+    /// fuzzer, in `ParseMode::Standard`. Counts that need more data than
+    /// remains are rejected before anything is allocated, and a panic is
+    /// returned as an error instead of unwinding into the caller.
+    pub fn parse_untrusted(data: &[u8]) -> RwResult<RwIfp> {
+        catch_panic(|| IfpParser::new(data).with_mode(ParseMode::Standard).parse())
+    }
+
+    /// Memory-maps the IFP file at `path` and parses it in place, without
+    /// reading it into a buffer first. Requires the `mmap` feature.
+    #[cfg(feature = "mmap")]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> RwResult<RwIfp> {
+        let file = MappedFile::open(path)?;
+        IfpParser::new(&file).parse()
+    }
+
+    /// Parses an IFP file from `reader`, starting at its current position,
+    /// and leaves the reader after the package.
+    ///
+    /// Only the package is buffered, as given by the size in its header, so
+    /// `reader` may be an IMG archive positioned at the start of an entry.
+    pub fn parse_from_reader<R: Read + Seek>(reader: &mut R) -> RwResult<RwIfp> {
+        let start = reader.stream_position()?;
+        let available = reader.seek(SeekFrom::End(0))?.saturating_sub(start);
+        reader.seek(SeekFrom::Start(start))?;
+
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        // Some tools write a wrong package size, so the rest of the data is
+        // read if it is too large
+        let package_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+        let size = (8 + package_size).min(available);
+
+        let mut data = Vec::with_capacity(size as usize);
+        data.extend_from_slice(&header);
+        reader.by_ref().take(size - 8).read_to_end(&mut data)?;
+        IfpParser::new(&data).parse()
+    }
+
+    /// Reads the package name and the name and offset of every animation,
+    /// skipping over the bone and keyframe data.
+    pub fn parse_index(&mut self) -> RwResult<IfpIndex> {
+        let version = self.read_version()?;
+        let (name, animations_count) = match version {
+            IfpVersion::ANP3 => self.read_anp3_header()?,
+            IfpVersion::ANPK => self.read_anpk_header()?,
+            IfpVersion::UNSUPPORTED => {
+                let signature = self.file.get_stream().read_string(4)?;
+                return Err(RwParseError::UnsupportedVersion { format: "IFP", version: signature });
+            }
+        };
+
+        let mut animations = Vec::with_capacity(self.file.bounded_capacity(animations_count as u64, 8));
+        for _ in 0..animations_count {
+            let offset = self.file.get_stream().get_position();
+            let name = match version {
+                IfpVersion::ANP3 => self.skip_anp3_animation()?,
+                _ => self.skip_anpk_animation()?,
+            };
+            animations.push(IfpIndexEntry { name, offset });
         }
+
+        Ok(IfpIndex { version, name, animations })
+    }
+
+    /// Decodes only the animation with the given name, compared
+    /// case-insensitively as the games do. Returns `None` if the package
+    /// does not contain it.
+    pub fn parse_animation(&mut self, name: &str) -> RwResult<Option<RwIfpAnimation>> {
+        let index = self.parse_index()?;
+        let Some(entry) = index.animations.iter().find(|entry| entry.name.eq_ignore_ascii_case(name)) else {
+            return Ok(None);
+        };
+
+        self.file.get_stream().set_position(entry.offset);
+        let animation = match index.version {
+            IfpVersion::ANP3 => self.read_anp3_animation()?,
+            _ => self.read_anpk_animation()?,
+        };
+        Ok(Some(animation))
     }
 
-    fn read_anp3(&mut self) -> Result<RwIfp> {
+    fn read_version(&mut self) -> RwResult<IfpVersion> {
+        let file_signature = self.file.get_stream().read_string(4)?;
+        self.file.get_stream().set_position(0);
+
+        Ok(match file_signature.as_str() {
+            "ANP3" => IfpVersion::ANP3,
+            "ANPK" => IfpVersion::ANPK,
+            _ => IfpVersion::UNSUPPORTED,
+        })
+    }
+
+    fn read_anp3_header(&mut self) -> RwResult<(String, u32)> {
         self.file.get_stream().skip(4)?; // ANP3
         let _size = self.file.get_stream().read_u32()?;
         let name = self.file.get_stream().read_string(24)?;
         let animations_count = self.file.get_stream().read_u32()?;
-        let mut animations = Vec::with_capacity(animations_count as usize);
+        Ok((name, animations_count))
+    }
+
+    fn skip_anp3_animation(&mut self) -> RwResult<String> {
+        let name = self.file.get_stream().read_string(24)?;
+        let bones_count = self.file.get_stream().read_u32()?;
+        let keyframes_size = self.file.get_stream().read_u32()?;
+        self.file.get_stream().skip(4)?; // unk
+        self.file.get_stream().skip(bones_count as u64 * 36 + keyframes_size as u64)?;
+        Ok(name)
+    }
+
+    fn read_anp3(&mut self) -> RwResult<RwIfp> {
+        let (name, animations_count) = self.read_anp3_header()?;
+        let mut animations = Vec::with_capacity(self.file.bounded_capacity(animations_count as u64, 8));
 
         for _ in 0..animations_count {
-            animations.push(self.read_anp3_animation()?);
+            let offset = self.file.get_stream().get_position();
+            match self.read_anp3_animation() {
+                Ok(animation) => {
+                    self.record_animation(animations.len(), offset);
+                    animations.push(animation);
+                }
+                Err(error) if self.mode == ParseMode::Lenient => {
+                    self.report.recovered(offset, format!("Stopped at a damaged animation: {}", error));
+                    break;
+                }
+                Err(error) => return Err(error),
+            }
         }
 
         Ok(RwIfp {
@@ -135,26 +448,132 @@ impl<'a> IfpParser<'a> {
         })
     }
 
-    fn read_anp3_animation(&mut self) -> Result<RwIfpAnimation> {
+    fn read_anp3_animation(&mut self) -> RwResult<RwIfpAnimation> {
         let name = self.file.get_stream().read_string(24)?;
         let bones_count = self.file.get_stream().read_u32()?;
-        self.file.get_stream().skip(8)?; // keyframes_size, unk
-        let mut bones = Vec::with_capacity(bones_count as usize);
+        let keyframes_size = self.file.get_stream().read_u32()?;
+        self.file.get_stream().skip(4)?; // unk
+
+        // Some tools declare KRT0 bones but omit the translations. Detect this
+        // by checking the keyframe data against the declared size.
+        let start = self.file.get_stream().get_position();
+        let mut bones = self.read_anp3_bones(bones_count, start, keyframes_size, true);
+        if !matches!(bones, Ok((_, true))) {
+            let end = self.file.get_stream().get_position();
+            self.file.get_stream().set_position(start);
+            match self.read_anp3_bones(bones_count, start, keyframes_size, false) {
+                Ok((stripped, true)) => {
+                    self.report.suspicious(
+                        self.mode,
+                        start,
+                        format!("Animation {:?} declares KRT0 bones without translations", name),
+                    )?;
+                    bones = Ok((stripped, true));
+                }
+                _ => self.file.get_stream().set_position(end),
+            }
+        }
+        let (mut bones, size_matches) = bones?;
+        if !size_matches {
+            self.report.suspicious(
+                self.mode,
+                start,
+                format!("Keyframe data of animation {:?} does not match its declared size of {}", name, keyframes_size),
+            )?;
+        }
+        self.report_empty_bones(&name, &bones, start)?;
+
+        // All bones of an animation share one encoding, so a single bone
+        // whose times can only be deltas decides for the others
+        let mut original_time_base = IfpTimeBase::Ticks;
+        if bones.iter().any(|bone| has_delta_times(&bone.keyframes)) {
+            for bone in &mut bones {
+                accumulate_delta_times(&mut bone.keyframes);
+            }
+            original_time_base = IfpTimeBase::DeltaTicks;
+        }
+
+        let mut time_base = IfpTimeBase::Ticks;
+        if self.normalize_times {
+            for keyframe in bones.iter_mut().flat_map(|bone| bone.keyframes.iter_mut()) {
+                keyframe.time /= ANP3_TICKS_PER_SECOND;
+            }
+            time_base = IfpTimeBase::Seconds;
+        }
+
+        Ok(RwIfpAnimation {
+            name,
+            bones,
+            anpk_info: None,
+            time_base,
+            original_time_base,
+        })
+    }
+
+    /// Records the span of an animation that starts at `offset` and ends at
+    /// the position, along with the spans of its bones.
+    fn record_animation(&mut self, index: usize, offset: u64) {
+        let end = self.file.get_stream().get_position();
+        self.source_map.record(SourceItem::Animation(index), offset, end);
+        for (bone, span) in std::mem::take(&mut self.bone_spans).into_iter().enumerate() {
+            self.source_map.record(SourceItem::Bone { animation: index, bone }, span.offset, span.end());
+        }
+    }
+
+    /// Warns about an animation without bones and about bones without
+    /// keyframes.
+    fn report_empty_bones(&mut self, animation: &str, bones: &[RwIfpBone], offset: u64) -> RwResult<()> {
+        if bones.is_empty() {
+            self.report.suspicious(self.mode, offset, format!("Animation {:?} has no bones", animation))?;
+        }
+        for bone in bones.iter().filter(|bone| bone.keyframes.is_empty()) {
+            self.report.suspicious(
+                self.mode,
+                offset,
+                format!("Bone {:?} of animation {:?} has no keyframes", bone.name, animation),
+            )?;
+        }
+        Ok(())
+    }
 
+    /// Reads the bones of an `ANP3` animation, returning them along with
+    /// whether their keyframe data matched `keyframes_size`.
+    fn read_anp3_bones(
+        &mut self,
+        bones_count: u32,
+        start: u64,
+        keyframes_size: u32,
+        read_translations: bool,
+    ) -> RwResult<(Vec<RwIfpBone>, bool)> {
+        let mut bones = Vec::with_capacity(self.file.checked_capacity("bones", bones_count as u64, 36)?);
+        self.bone_spans.clear();
         for _ in 0..bones_count {
-            bones.push(self.read_anp3_bone()?);
+            let bone_start = self.file.get_stream().get_position();
+            bones.push(self.read_anp3_bone(read_translations)?);
+            self.bone_spans.push(SourceSpan::between(bone_start, self.file.get_stream().get_position()));
         }
 
-        Ok(RwIfpAnimation { name, bones })
+        let headers_size = bones_count as u64 * 36;
+        let consumed = self.file.get_stream().get_position() - start;
+        Ok((bones, consumed == headers_size + keyframes_size as u64))
     }
 
-    fn read_anp3_bone(&mut self) -> Result<RwIfpBone> {
+    fn read_anp3_bone(&mut self, read_translations: bool) -> RwResult<RwIfpBone> {
         let name = self.file.get_stream().read_string(24)?;
         let keyframe_type_num = self.file.get_stream().read_u32()?;
         let keyframes_count = self.file.get_stream().read_u32()?;
-        let keyframe_type = if keyframe_type_num == 4 { "KRT0" } else { "KR00" }.to_string();
+        let keyframe_type = match KeyframeType::from_anp3(keyframe_type_num) {
+            Some(KeyframeType::KRT0) if !read_translations => KeyframeType::KR00,
+            Some(keyframe_type) => keyframe_type,
+            None => {
+                return Err(RwParseError::InconsistentData {
+                    offset: self.file.get_stream().get_position() - 8,
+                    message: format!("Unknown ANP3 keyframe type {} for bone {}", keyframe_type_num, name),
+                })
+            }
+        };
         let bone_id = self.file.get_stream().read_i32()?;
-        let mut keyframes = Vec::with_capacity(keyframes_count as usize);
+        let mut keyframes = Vec::with_capacity(self.file.checked_capacity("keyframes", keyframes_count as u64, 10)?);
 
         for _ in 0..keyframes_count {
             let qx = self.file.get_stream().read_i16()? as f32 / 4096.0;
@@ -163,7 +582,7 @@ impl<'a> IfpParser<'a> {
             let qw = self.file.get_stream().read_i16()? as f32 / 4096.0;
             let time = self.file.get_stream().read_i16()? as f32;
 
-            let (px, py, pz) = if keyframe_type.as_bytes()[2] == b'T' {
+            let (px, py, pz) = if keyframe_type.has_translation() {
                 (
                     self.file.get_stream().read_i16()? as f32 / 1024.0,
                     self.file.get_stream().read_i16()? as f32 / 1024.0,
@@ -187,22 +606,55 @@ impl<'a> IfpParser<'a> {
             use_bone_id: true,
             bone_id,
             keyframes,
+            anpk_info: None,
         })
     }
 
-    fn read_anpk(&mut self) -> Result<RwIfp> {
+    fn read_anpk_header(&mut self) -> RwResult<(String, u32)> {
         self.file.get_stream().skip(4)?; // ANPK
         let _size = self.file.get_stream().read_u32()?;
         self.file.get_stream().skip(4)?; // INFO
+        let info_offset = self.file.get_stream().get_position();
         let info_len = self.file.get_stream().read_u32()?;
+        let name_len = info_len.checked_sub(4).ok_or_else(|| RwParseError::InconsistentData {
+            offset: info_offset,
+            message: format!("ANPK INFO size {} is too small for the animation count", info_len),
+        })?;
         let animations_count = self.file.get_stream().read_u32()?;
-        let name = self.file.get_stream().read_string((info_len - 4) as usize)?;
+        let name = self.file.get_stream().read_string(name_len as usize)?;
         let name_align_len = (4 - info_len % 4) % 4;
         self.file.get_stream().skip(name_align_len as u64)?;
+        Ok((name, animations_count))
+    }
+
+    fn skip_anpk_animation(&mut self) -> RwResult<String> {
+        self.file.get_stream().skip(4)?; // NAME
+        let name_len = self.file.get_stream().read_u32()?;
+        let name = self.file.get_stream().read_string(name_len as usize)?;
+        self.file.get_stream().skip(((4 - name_len % 4) % 4) as u64)?;
+        self.file.get_stream().skip(4)?; // DGAN
+        let dgan_size = self.file.get_stream().read_u32()?;
+        self.file.get_stream().skip(dgan_size as u64)?;
+        Ok(name)
+    }
 
-        let mut animations = Vec::with_capacity(animations_count as usize);
+    fn read_anpk(&mut self) -> RwResult<RwIfp> {
+        let (name, animations_count) = self.read_anpk_header()?;
+
+        let mut animations = Vec::with_capacity(self.file.bounded_capacity(animations_count as u64, 8));
         for _ in 0..animations_count {
-            animations.push(self.read_anpk_animation()?);
+            let offset = self.file.get_stream().get_position();
+            match self.read_anpk_animation() {
+                Ok(animation) => {
+                    self.record_animation(animations.len(), offset);
+                    animations.push(animation);
+                }
+                Err(error) if self.mode == ParseMode::Lenient => {
+                    self.report.recovered(offset, format!("Stopped at a damaged animation: {}", error));
+                    break;
+                }
+                Err(error) => return Err(error),
+            }
         }
 
         Ok(RwIfp {
@@ -212,59 +664,119 @@ impl<'a> IfpParser<'a> {
         })
     }
 
-    fn read_anpk_animation(&mut self) -> Result<RwIfpAnimation> {
+    fn read_anpk_animation(&mut self) -> RwResult<RwIfpAnimation> {
         self.file.get_stream().skip(4)?; // NAME
         let name_len = self.file.get_stream().read_u32()?;
         let name = self.file.get_stream().read_string(name_len as usize)?;
         self.file.get_stream().skip(((4 - name_len % 4) % 4) as u64)?;
-        self.file.get_stream().skip(16)?; // DGAN, animation_size, INFO, unk_size
+        self.file.get_stream().skip(4)?; // DGAN
+        let dgan_size = self.file.get_stream().read_u32()?;
+        let dgan_offset = self.file.get_stream().get_position() - 8;
+        let (info_size, unknown, bones) =
+            self.read_within(dgan_size, dgan_offset, "DGAN", |parser| parser.read_anpk_bones(&name))?;
+
+        Ok(RwIfpAnimation {
+            name,
+            bones,
+            anpk_info: Some(AnpkAnimationInfo {
+                dgan_size,
+                info_size,
+                unknown,
+            }),
+            time_base: IfpTimeBase::Seconds,
+            original_time_base: IfpTimeBase::Seconds,
+        })
+    }
+
+    /// Reads the `INFO` chunk and bones of an `ANPK` animation, returning
+    /// the chunk's size and unknown value along with the bones.
+    fn read_anpk_bones(&mut self, name: &str) -> RwResult<(u32, u32, Vec<RwIfpBone>)> {
+        self.file.get_stream().skip(4)?; // INFO
+        let info_size = self.file.get_stream().read_u32()?;
         let bones_count = self.file.get_stream().read_u32()?;
-        self.file.get_stream().skip(4)?; // unk
-        
-        let mut bones = Vec::with_capacity(bones_count as usize);
+        let unknown = self.file.get_stream().read_u32()?;
+
+        let start = self.file.get_stream().get_position();
+        let mut bones = Vec::with_capacity(self.file.checked_capacity("bones", bones_count as u64, 16)?);
+        self.bone_spans.clear();
         for _ in 0..bones_count {
-            bones.push(self.read_anpk_bone()?);
+            let bone_start = self.file.get_stream().get_position();
+            self.file.get_stream().skip(4)?; // CPAN
+            let cpan_size = self.file.get_stream().read_u32()?;
+            bones.push(self.read_within(cpan_size, bone_start, "CPAN", |parser| parser.read_anpk_bone(cpan_size))?);
+            self.bone_spans.push(SourceSpan::between(bone_start, self.file.get_stream().get_position()));
         }
+        self.report_empty_bones(name, &bones, start)?;
+        Ok((info_size, unknown, bones))
+    }
 
-        Ok(RwIfpAnimation { name, bones })
+    /// Reads a chunk of `size` bytes within its own scope, so nothing in it
+    /// is read from the chunks that follow.
+    fn read_within<T>(
+        &mut self,
+        size: u32,
+        offset: u64,
+        what: &str,
+        read: impl FnOnce(&mut Self) -> RwResult<T>,
+    ) -> RwResult<T> {
+        let scope = self.file.enter_chunk(size as u64);
+        let result = read(self);
+        let overrun = self.file.leave_section(scope);
+        let value = result?;
+        if overrun > 0 {
+            self.report.suspicious(self.mode, offset, format!("{} chunk was read {} bytes past its end", what, overrun))?;
+        }
+        Ok(value)
     }
 
-    fn read_anpk_bone(&mut self) -> Result<RwIfpBone> {
-        self.file.get_stream().skip(8)?; // CPAN, bone_len
+    fn read_anpk_bone(&mut self, cpan_size: u32) -> RwResult<RwIfpBone> {
         self.file.get_stream().skip(4)?; // ANIM
         let anim_len = self.file.get_stream().read_u32()?;
         let name = self.file.get_stream().read_string(28)?;
         let keyframes_count = self.file.get_stream().read_u32()?;
-        self.file.get_stream().skip(8)?; // unk
+        let unknown = [self.file.get_stream().read_u32()?, self.file.get_stream().read_u32()?];
 
         let use_bone_id = anim_len == 44;
+        let mut extra = [0; 2];
         let bone_id = if use_bone_id {
             self.file.get_stream().read_i32()?
         } else {
-            self.file.get_stream().skip(8)?;
+            extra = [self.file.get_stream().read_u32()?, self.file.get_stream().read_u32()?];
             0
         };
 
-        let mut keyframe_type = "K000".to_string();
+        let mut keyframe_type = KeyframeType::K000;
         let mut keyframes = Vec::new();
+        let mut keyframes_size = 0;
 
         if keyframes_count > 0 {
-            keyframe_type = self.file.get_stream().read_string(4)?;
-            self.file.get_stream().skip(4)?; // keyframes_len
+            let tag_offset = self.file.get_stream().get_position();
+            let tag = self.file.get_stream().read_string(4)?;
+            keyframe_type = KeyframeType::from_tag(&tag).ok_or_else(|| RwParseError::InconsistentData {
+                offset: tag_offset,
+                message: format!("Unknown ANPK keyframe type {:?} for bone {}", tag, name),
+            })?;
+            keyframes_size = self.file.get_stream().read_u32()?;
 
             for _ in 0..keyframes_count {
-                let qx = self.file.get_stream().read_f32()?;
-                let qy = self.file.get_stream().read_f32()?;
-                let qz = self.file.get_stream().read_f32()?;
-                let qw = self.file.get_stream().read_f32()?;
+                let (qx, qy, qz, qw) = if keyframe_type.has_rotation() {
+                    (
+                        self.file.get_stream().read_f32()?,
+                        self.file.get_stream().read_f32()?,
+                        self.file.get_stream().read_f32()?,
+                        self.file.get_stream().read_f32()?,
+                    )
+                } else {
+                    (0.0, 0.0, 0.0, 1.0)
+                };
 
-                let (px, py, pz) = if keyframe_type.as_bytes()[2] == b'T' {
+                let (px, py, pz) = if keyframe_type.has_translation() {
                     (self.file.get_stream().read_f32()?, self.file.get_stream().read_f32()?, self.file.get_stream().read_f32()?)
                 } else {
                     (0.0, 0.0, 0.0)
                 };
 
-                let (sx, sy, sz) = if keyframe_type.as_bytes()[3] == b'S' {
+                let (sx, sy, sz) = if keyframe_type.has_scale() {
                     (self.file.get_stream().read_f32()?, self.file.get_stream().read_f32()?, self.file.get_stream().read_f32()?)
                 } else {
                     (1.0, 1.0, 1.0)
@@ -287,6 +799,29 @@ impl<'a> IfpParser<'a> {
             use_bone_id,
             bone_id,
             keyframes,
+            anpk_info: Some(AnpkBoneInfo {
+                cpan_size,
+                anim_size: anim_len,
+                unknown,
+                extra,
+                keyframes_size,
+            }),
         })
     }
-}
\ No newline at end of file
+}
+
+/// Returns whether keyframe times can only be deltas: absolute times never
+/// decrease, and never repeat except at the start, while frame deltas are
+/// usually the same interval over and over.
+fn has_delta_times(keyframes: &[RwIfpKeyframe]) -> bool {
+    keyframes.windows(2).any(|pair| pair[1].time < pair[0].time || (pair[1].time == pair[0].time && pair[0].time > 0.0))
+}
+
+/// Turns delta-encoded keyframe times into absolute times.
+fn accumulate_delta_times(keyframes: &mut [RwIfpKeyframe]) {
+    let mut time = 0.0;
+    for keyframe in keyframes {
+        time += keyframe.time;
+        keyframe.time = time;
+    }
+}
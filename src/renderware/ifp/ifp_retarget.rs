@@ -0,0 +1,131 @@
+//! # IFP Retargeting
+//!
+//! Maps the bones of an IFP animation onto the frame hierarchy of a skinned
+//! DFF model, producing local transforms for every frame of the model at a
+//! series of sample times. Frames that the animation does not drive keep
+//! their bind pose.
+//!
+//! Bones are matched by the HAnim bone id first and by frame name second,
+//! as returned by `RwDff::frame_bone_id` and `RwDff::frame_name`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use std::fs;
+//!
+//! let dff_data = fs::read("path/to/your/model.dff").unwrap();
+//! let ifp_data = fs::read("path/to/your/animation.ifp").unwrap();
+//! let dff = DffParser::new(&dff_data).parse().unwrap();
+//! let ifp = IfpParser::new(&ifp_data).parse().unwrap();
+//!
+//! let animation = &ifp.animations[0];
+//! let times: Vec<f32> = (0..30).map(|i| animation.duration() * i as f32 / 29.0).collect();
+//! let retargeted = animation.retarget(&dff, &times);
+//! println!("{} bones not found in the model", retargeted.unmatched_bones.len());
+//! ```
+
+use super::ifp_parser::{RwIfpAnimation, RwIfpBone};
+use crate::renderware::common::types::{RwQuaternion, RwVector3};
+use crate::renderware::dff::dff_parser::RwDff;
+use serde::Serialize;
+
+/// An animation evaluated on the frames of a DFF model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetargetedAnimation {
+    pub name: String,
+    /// The times at which the animation was sampled.
+    pub times: Vec<f32>,
+    /// One track per frame in the model's frame list, in the same order.
+    pub tracks: Vec<RetargetedTrack>,
+    /// Names of animation bones that matched no frame of the model.
+    pub unmatched_bones: Vec<String>,
+}
+
+/// The local transforms of a single DFF frame over time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetargetedTrack {
+    pub frame_index: usize,
+    /// The HAnim bone id of the frame, if it has one.
+    pub bone_id: Option<i32>,
+    /// The node name of the frame, if it has one.
+    pub name: Option<String>,
+    /// Whether an animation bone drives this frame. Undriven frames repeat
+    /// their bind pose.
+    pub animated: bool,
+    /// One transform per sample time, relative to the parent frame.
+    pub transforms: Vec<LocalTransform>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LocalTransform {
+    pub position: RwVector3,
+    pub rotation: RwQuaternion,
+    pub scale: RwVector3,
+}
+
+impl RwIfpAnimation {
+    /// Samples this animation on the frames of `dff` at each of `times`.
+    ///
+    /// Bones without translation keyframes keep the bind pose translation of
+    /// their frame.
+    pub fn retarget(&self, dff: &RwDff, times: &[f32]) -> RetargetedAnimation {
+        let frames = dff.frames();
+        let bone_id_of = |frame_index: usize| dff.frame_bone_id(frame_index);
+        let name_of = |frame_index: usize| dff.frame_name(frame_index);
+
+        let mut frame_bones: Vec<Option<&RwIfpBone>> = vec![None; frames.len()];
+        let mut unmatched_bones = Vec::new();
+        for bone in &self.bones {
+            let by_id = (0..frames.len()).find(|&i| bone.use_bone_id && bone_id_of(i) == Some(bone.bone_id));
+            let by_name = || (0..frames.len()).find(|&i| name_of(i).is_some_and(|name| name.eq_ignore_ascii_case(&bone.name)));
+            match by_id.or_else(by_name) {
+                Some(frame_index) if frame_bones[frame_index].is_none() => frame_bones[frame_index] = Some(bone),
+                _ => unmatched_bones.push(bone.name.clone()),
+            }
+        }
+
+        let tracks = frames
+            .iter()
+            .enumerate()
+            .map(|(frame_index, frame)| {
+                let bind = LocalTransform {
+                    position: frame.coordinates_offset,
+                    rotation: frame.rotation_matrix.to_quaternion(),
+                    scale: RwVector3 { x: 1.0, y: 1.0, z: 1.0 },
+                };
+                let bone = frame_bones[frame_index];
+                let transforms = times
+                    .iter()
+                    .map(|&time| match bone {
+                        Some(bone) => {
+                            let pose = bone.sample(time);
+                            LocalTransform {
+                                position: if bone.has_translation() { pose.position } else { bind.position },
+                                rotation: pose.rotation,
+                                scale: pose.scale,
+                            }
+                        }
+                        None => bind,
+                    })
+                    .collect();
+
+                RetargetedTrack {
+                    frame_index,
+                    bone_id: bone_id_of(frame_index),
+                    name: name_of(frame_index).map(str::to_string),
+                    animated: bone.is_some(),
+                    transforms,
+                }
+            })
+            .collect();
+
+        RetargetedAnimation {
+            name: self.name.clone(),
+            times: times.to_vec(),
+            tracks,
+            unmatched_bones,
+        }
+    }
+}
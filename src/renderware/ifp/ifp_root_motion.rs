@@ -0,0 +1,103 @@
+//! # IFP Root Motion
+//!
+//! Separates the movement of an animation's root bone from the animation
+//! itself. The extracted motion curve can drive a character controller while
+//! the animation is played in place.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/animation.ifp").unwrap();
+//! let mut ifp = IfpParser::new(&file_data).parse().unwrap();
+//!
+//! if let Some(motion) = ifp.animations[0].make_in_place(true) {
+//!     println!("root moves {} keyframes", motion.keyframes.len());
+//! }
+//! ```
+
+use super::ifp_parser::RwIfpAnimation;
+use crate::renderware::common::types::RwVector3;
+use serde::Serialize;
+
+/// The translation of an animation's root bone over time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RootMotion {
+    pub bone_name: String,
+    /// Root displacement relative to its first keyframe.
+    pub keyframes: Vec<RootMotionKeyframe>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RootMotionKeyframe {
+    pub time: f32,
+    pub translation: RwVector3,
+}
+
+impl RwIfpAnimation {
+    /// Returns the index of the root bone: the bone with id 0, else a bone
+    /// named "Root", else the first bone.
+    pub fn root_bone_index(&self) -> Option<usize> {
+        self.bones
+            .iter()
+            .position(|bone| bone.use_bone_id && bone.bone_id == 0)
+            .or_else(|| self.bones.iter().position(|bone| bone.name.eq_ignore_ascii_case("root")))
+            .or_else(|| (!self.bones.is_empty()).then_some(0))
+    }
+
+    /// Extracts the root bone's translation track as a motion curve.
+    ///
+    /// Returns `None` if the root bone has no translation keyframes.
+    pub fn extract_root_motion(&self) -> Option<RootMotion> {
+        let root = &self.bones[self.root_bone_index()?];
+        if !root.has_translation() {
+            return None;
+        }
+
+        let origin = root.keyframes.first()?.position;
+        Some(RootMotion {
+            bone_name: root.name.clone(),
+            keyframes: root
+                .keyframes
+                .iter()
+                .map(|keyframe| RootMotionKeyframe {
+                    time: keyframe.time,
+                    translation: RwVector3 {
+                        x: keyframe.position.x - origin.x,
+                        y: keyframe.position.y - origin.y,
+                        z: keyframe.position.z - origin.z,
+                    },
+                })
+                .collect(),
+        })
+    }
+
+    /// Extracts the root motion and removes it from the animation, leaving
+    /// the root bone at its starting position.
+    ///
+    /// With `keep_vertical` the root keeps its movement along the Z axis, so
+    /// jumps and crouches stay part of the animation.
+    pub fn make_in_place(&mut self, keep_vertical: bool) -> Option<RootMotion> {
+        let mut motion = self.extract_root_motion()?;
+        let root_index = self.root_bone_index()?;
+
+        let root = &mut self.bones[root_index];
+        let origin = root.keyframes[0].position;
+        for keyframe in &mut root.keyframes {
+            keyframe.position.x = origin.x;
+            keyframe.position.y = origin.y;
+            if !keep_vertical {
+                keyframe.position.z = origin.z;
+            }
+        }
+
+        if keep_vertical {
+            for keyframe in &mut motion.keyframes {
+                keyframe.translation.z = 0.0;
+            }
+        }
+        Some(motion)
+    }
+}
@@ -0,0 +1,109 @@
+//! # IFP Sampling
+//!
+//! Evaluates animations at arbitrary points in time. Rotations are
+//! interpolated with spherical linear interpolation, translations and scales
+//! linearly. Times are in the same units as the keyframes of the animation.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/animation.ifp").unwrap();
+//! let ifp = IfpParser::new(&file_data).parse().unwrap();
+//!
+//! let animation = &ifp.animations[0];
+//! for pose in animation.sample(animation.duration() / 2.0) {
+//!     println!("{}: {:?}", pose.name, pose.rotation);
+//! }
+//! ```
+
+use super::ifp_parser::{IfpTimeBase, RwIfpAnimation, RwIfpBone, RwIfpKeyframe, ANP3_TICKS_PER_SECOND};
+use crate::renderware::common::types::{RwQuaternion, RwVector3};
+use serde::Serialize;
+
+/// The local transform of a single bone at a point in time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BonePose {
+    pub name: String,
+    pub bone_id: i32,
+    pub position: RwVector3,
+    pub rotation: RwQuaternion,
+    pub scale: RwVector3,
+}
+
+impl RwIfpAnimation {
+    /// Returns the time of the last keyframe of any bone.
+    pub fn duration(&self) -> f32 {
+        self.bones
+            .iter()
+            .filter_map(|bone| bone.keyframes.last())
+            .map(|keyframe| keyframe.time)
+            .fold(0.0, f32::max)
+    }
+
+    /// Returns the length in seconds of one unit of keyframe time.
+    pub fn seconds_per_unit(&self) -> f32 {
+        match self.time_base {
+            IfpTimeBase::Seconds => 1.0,
+            IfpTimeBase::Ticks | IfpTimeBase::DeltaTicks => 1.0 / ANP3_TICKS_PER_SECOND,
+        }
+    }
+
+    /// Evaluates every bone at `time`.
+    ///
+    /// Times before the first or after the last keyframe of a bone clamp to
+    /// that keyframe. Bones without keyframes yield the identity transform.
+    pub fn sample(&self, time: f32) -> Vec<BonePose> {
+        self.bones.iter().map(|bone| bone.sample(time)).collect()
+    }
+}
+
+impl RwIfpBone {
+    /// Evaluates this bone at `time`.
+    pub fn sample(&self, time: f32) -> BonePose {
+        let keyframe = match self.keyframes.iter().position(|keyframe| keyframe.time >= time) {
+            None => self.keyframes.last().copied(),
+            Some(0) => self.keyframes.first().copied(),
+            Some(index) => {
+                let (previous, next) = (&self.keyframes[index - 1], &self.keyframes[index]);
+                let span = next.time - previous.time;
+                let t = if span > 0.0 { (time - previous.time) / span } else { 0.0 };
+                Some(interpolate_keyframes(previous, next, t))
+            }
+        };
+
+        let keyframe = keyframe.unwrap_or(RwIfpKeyframe {
+            time,
+            position: RwVector3 { x: 0.0, y: 0.0, z: 0.0 },
+            rotation: RwQuaternion::IDENTITY,
+            scale: RwVector3 { x: 1.0, y: 1.0, z: 1.0 },
+        });
+
+        BonePose {
+            name: self.name.clone(),
+            bone_id: self.bone_id,
+            position: keyframe.position,
+            rotation: keyframe.rotation,
+            scale: keyframe.scale,
+        }
+    }
+}
+
+pub(crate) fn interpolate_keyframes(a: &RwIfpKeyframe, b: &RwIfpKeyframe, t: f32) -> RwIfpKeyframe {
+    RwIfpKeyframe {
+        time: a.time + (b.time - a.time) * t,
+        position: lerp(a.position, b.position, t),
+        rotation: a.rotation.slerp(b.rotation, t),
+        scale: lerp(a.scale, b.scale, t),
+    }
+}
+
+fn lerp(a: RwVector3, b: RwVector3, t: f32) -> RwVector3 {
+    RwVector3 {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+        z: a.z + (b.z - a.z) * t,
+    }
+}
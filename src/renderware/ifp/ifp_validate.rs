@@ -0,0 +1,132 @@
+//! # IFP Validation
+//!
+//! Pre-flight checks for animation packages. Animations with broken
+//! keyframes can crash the game, so these checks catch the common problems
+//! before a package is shipped.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ifp::ifp_parser::IfpParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/animation.ifp").unwrap();
+//! let ifp = IfpParser::new(&file_data).parse().unwrap();
+//!
+//! let stats = ifp.stats();
+//! println!("{} animations, {} keyframes", stats.animation_count, stats.keyframe_count);
+//! for issue in ifp.validate() {
+//!     println!("{} {:?}: {:?}", issue.animation, issue.bone, issue.kind);
+//! }
+//! ```
+
+use super::ifp_bones::{bone_name, Skeleton};
+use super::ifp_parser::{RwIfp, RwIfpBone};
+use serde::Serialize;
+
+/// How far a quaternion's length may be from 1 before it is reported.
+const QUATERNION_LENGTH_TOLERANCE: f32 = 0.01;
+
+/// A problem found in an animation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IfpIssue {
+    pub animation: String,
+    /// The bone the issue is in, if it is specific to one bone.
+    pub bone: Option<String>,
+    pub kind: IfpIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum IfpIssueKind {
+    /// The keyframe's time is earlier than the previous keyframe's.
+    NonMonotonicTime { keyframe: usize },
+    /// The keyframe's rotation is not a unit quaternion.
+    UnnormalizedQuaternion { keyframe: usize, length: f32 },
+    /// The bone is not part of the Vice City or San Andreas ped skeleton.
+    UnknownBone,
+    /// The bone has no keyframes.
+    EmptyTrack,
+    /// The animation has no bones.
+    EmptyAnimation,
+}
+
+/// A size summary of an animation package.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IfpStats {
+    pub animation_count: usize,
+    pub bone_count: usize,
+    pub keyframe_count: usize,
+    /// The name and duration of the longest animation.
+    pub longest_animation: Option<(String, f32)>,
+}
+
+impl RwIfp {
+    /// Computes animation, bone and keyframe counts for the package.
+    pub fn stats(&self) -> IfpStats {
+        let mut stats = IfpStats {
+            animation_count: self.animations.len(),
+            ..Default::default()
+        };
+
+        for animation in &self.animations {
+            stats.bone_count += animation.bones.len();
+            stats.keyframe_count += animation.bones.iter().map(|bone| bone.keyframes.len()).sum::<usize>();
+
+            let duration = animation.duration();
+            if stats.longest_animation.as_ref().is_none_or(|(_, longest)| duration > *longest) {
+                stats.longest_animation = Some((animation.name.clone(), duration));
+            }
+        }
+        stats
+    }
+
+    /// Checks every animation for keyframe and skeleton problems.
+    pub fn validate(&self) -> Vec<IfpIssue> {
+        let mut issues = Vec::new();
+        for animation in &self.animations {
+            if animation.bones.is_empty() {
+                issues.push(IfpIssue {
+                    animation: animation.name.clone(),
+                    bone: None,
+                    kind: IfpIssueKind::EmptyAnimation,
+                });
+            }
+
+            for bone in &animation.bones {
+                issues.extend(validate_bone(bone).into_iter().map(|kind| IfpIssue {
+                    animation: animation.name.clone(),
+                    bone: Some(bone.name.clone()),
+                    kind,
+                }));
+            }
+        }
+        issues
+    }
+}
+
+fn validate_bone(bone: &RwIfpBone) -> Vec<IfpIssueKind> {
+    let mut issues = Vec::new();
+
+    let is_standard = bone
+        .canonical_id()
+        .is_some_and(|id| bone_name(Skeleton::SanAndreas, id).or_else(|| bone_name(Skeleton::ViceCity, id)).is_some());
+    if !is_standard {
+        issues.push(IfpIssueKind::UnknownBone);
+    }
+
+    if bone.keyframes.is_empty() {
+        issues.push(IfpIssueKind::EmptyTrack);
+    }
+
+    for (index, keyframe) in bone.keyframes.iter().enumerate() {
+        if index > 0 && keyframe.time < bone.keyframes[index - 1].time {
+            issues.push(IfpIssueKind::NonMonotonicTime { keyframe: index });
+        }
+
+        let length = keyframe.rotation.length();
+        if (length - 1.0).abs() > QUATERNION_LENGTH_TOLERANCE {
+            issues.push(IfpIssueKind::UnnormalizedQuaternion { keyframe: index, length });
+        }
+    }
+    issues
+}
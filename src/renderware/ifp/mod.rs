@@ -1 +1,9 @@
-pub mod ifp_parser;
\ No newline at end of file
+pub mod ifp_parser;
+pub mod ifp_sample;
+pub mod ifp_retarget;
+pub mod ifp_convert;
+pub mod ifp_root_motion;
+pub mod ifp_edit;
+pub mod ifp_optimize;
+pub mod ifp_bones;
+pub mod ifp_validate;
\ No newline at end of file
@@ -0,0 +1,221 @@
+//! # IMG Archive
+//!
+//! A reader for the IMG archives (`gta3.img`, `gta_int.img`, ...) that store
+//! the streamed models, textures, collision and animations of Grand Theft
+//! Auto 3, Vice City, and San Andreas.
+//!
+//! ## Features
+//!
+//! - Reads version 1 archives, whose entry table is a separate `.dir` file.
+//! - Reads version 2 archives, which start with a `VER2` header.
+//! - Looks up entries by name and reads or streams them without extracting
+//!   the archive, so their data can be handed straight to the DFF, TXD, IFP
+//!   and COL parsers.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::img::img_archive::ImgArchive;
+//!
+//! let mut archive = ImgArchive::open_path("path/to/gta3.img").unwrap();
+//! println!("Entry count: {}", archive.entries().len());
+//!
+//! if let Some(data) = archive.read("infernus.dff").unwrap() {
+//!     let dff = DffParser::new(&data).parse().unwrap();
+//!     println!("Model type: {:?}", dff.model_type);
+//! }
+//! ```
+
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind, Read, Result, Seek, SeekFrom};
+use std::path::Path;
+
+use serde::Serialize;
+
+/// The size of an IMG sector. Entry offsets and sizes are in sectors.
+pub const IMG_SECTOR_SIZE: u64 = 2048;
+
+/// The size of an entry in the `.dir` file or the `VER2` table.
+pub(crate) const ENTRY_SIZE: usize = 32;
+
+/// Represents the version of an IMG archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ImgVersion {
+    /// GTA3 / Vice City format, with the entry table in a `.dir` file.
+    V1,
+    /// San Andreas format, with the entry table at the start of the archive.
+    V2,
+}
+
+/// An entry of the archive's table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImgEntry {
+    pub name: String,
+    /// The offset of the entry data, in sectors.
+    pub offset: u32,
+    /// The size of the entry data, in sectors.
+    pub size: u32,
+}
+
+impl ImgEntry {
+    /// Returns the offset of the entry data in bytes.
+    pub fn byte_offset(&self) -> u64 {
+        self.offset as u64 * IMG_SECTOR_SIZE
+    }
+
+    /// Returns the size of the entry data in bytes. Entries are padded to a
+    /// whole number of sectors, so this may exceed the size of the file that
+    /// was stored.
+    pub fn byte_size(&self) -> u64 {
+        self.size as u64 * IMG_SECTOR_SIZE
+    }
+
+    /// Returns the lowercase file extension, such as `dff` or `txd`.
+    pub fn extension(&self) -> Option<String> {
+        self.name.rsplit_once('.').map(|(_, extension)| extension.to_ascii_lowercase())
+    }
+}
+
+/// An IMG archive open for reading.
+pub struct ImgArchive<R> {
+    pub(crate) reader: R,
+    pub(crate) version: ImgVersion,
+    pub(crate) entries: Vec<ImgEntry>,
+}
+
+impl ImgArchive<BufReader<File>> {
+    /// Opens an archive from disk. Version 2 archives are detected by their
+    /// header; otherwise the entry table is read from the `.dir` file next
+    /// to the archive.
+    pub fn open_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut signature = [0; 4];
+        let is_v2 = reader.read_exact(&mut signature).is_ok() && &signature == b"VER2";
+        reader.seek(SeekFrom::Start(0))?;
+
+        if is_v2 {
+            ImgArchive::open_v2(reader)
+        } else {
+            let directory = std::fs::read(path.with_extension("dir"))?;
+            ImgArchive::open_v1(&directory, reader)
+        }
+    }
+}
+
+impl<R: Read + Seek> ImgArchive<R> {
+    /// Opens a version 1 archive from its `.dir` table and `.img` data.
+    pub fn open_v1(directory: &[u8], reader: R) -> Result<Self> {
+        if !directory.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(Error::new(ErrorKind::InvalidData, "IMG directory size is not a multiple of 32"));
+        }
+
+        let entries = directory
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| ImgEntry {
+                offset: u32::from_le_bytes(entry[0..4].try_into().unwrap()),
+                size: u32::from_le_bytes(entry[4..8].try_into().unwrap()),
+                name: read_name(&entry[8..32]),
+            })
+            .collect();
+
+        Ok(ImgArchive {
+            reader,
+            version: ImgVersion::V1,
+            entries,
+        })
+    }
+
+    /// Opens a version 2 archive, reading the entry table that follows the
+    /// `VER2` header.
+    pub fn open_v2(mut reader: R) -> Result<Self> {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        if &header[0..4] != b"VER2" {
+            return Err(Error::new(ErrorKind::InvalidData, "Missing VER2 IMG header"));
+        }
+        let entries_count = u32::from_le_bytes(header[4..8].try_into().unwrap());
+
+        let mut entries = Vec::with_capacity(entries_count.min(0x10000) as usize);
+        let mut entry = [0; ENTRY_SIZE];
+        for _ in 0..entries_count {
+            reader.read_exact(&mut entry)?;
+            let streaming_size = u16::from_le_bytes(entry[4..6].try_into().unwrap());
+            let archive_size = u16::from_le_bytes(entry[6..8].try_into().unwrap());
+            entries.push(ImgEntry {
+                offset: u32::from_le_bytes(entry[0..4].try_into().unwrap()),
+                // The archive size is usually left zero by tools
+                size: if streaming_size != 0 { streaming_size } else { archive_size } as u32,
+                name: read_name(&entry[8..32]),
+            });
+        }
+
+        Ok(ImgArchive {
+            reader,
+            version: ImgVersion::V2,
+            entries,
+        })
+    }
+
+    pub fn version(&self) -> ImgVersion {
+        self.version
+    }
+
+    pub fn entries(&self) -> &[ImgEntry] {
+        &self.entries
+    }
+
+    /// Finds an entry by name, compared case-insensitively as the games do.
+    pub fn find(&self, name: &str) -> Option<&ImgEntry> {
+        self.entries.iter().find(|entry| entry.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Reads the data of the entry with the given name, or returns `None` if
+    /// the archive does not contain it.
+    pub fn read(&mut self, name: &str) -> Result<Option<Vec<u8>>> {
+        match self.find(name).cloned() {
+            Some(entry) => self.read_entry(&entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the data of an entry, including its sector padding. The entry
+    /// size comes from the table, so it is checked against the length of
+    /// the archive before anything is allocated.
+    pub fn read_entry(&mut self, entry: &ImgEntry) -> Result<Vec<u8>> {
+        let length = self.reader.seek(SeekFrom::End(0))?;
+        if length.saturating_sub(entry.byte_offset()) < entry.byte_size() {
+            return Err(truncated(entry));
+        }
+
+        let mut data = Vec::with_capacity(entry.byte_size() as usize);
+        self.entry_reader(entry)?.read_to_end(&mut data)?;
+        if (data.len() as u64) < entry.byte_size() {
+            return Err(truncated(entry));
+        }
+        Ok(data)
+    }
+
+    /// Returns a reader over the data of an entry, for streaming large
+    /// entries without loading them into memory.
+    pub fn entry_reader(&mut self, entry: &ImgEntry) -> Result<std::io::Take<&mut R>> {
+        self.reader.seek(SeekFrom::Start(entry.byte_offset()))?;
+        Ok((&mut self.reader).take(entry.byte_size()))
+    }
+
+    /// Consumes the archive, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn truncated(entry: &ImgEntry) -> Error {
+    Error::new(ErrorKind::UnexpectedEof, format!("IMG entry {} is truncated", entry.name))
+}
+
+fn read_name(field: &[u8]) -> String {
+    let length = field.iter().position(|&c| c == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..length]).to_string()
+}
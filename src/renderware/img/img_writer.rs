@@ -0,0 +1,318 @@
+//! # IMG Writer
+//!
+//! Builds IMG archives and edits existing ones in place.
+//!
+//! ## Features
+//!
+//! - Builds version 1 (`.dir` + `.img`) and version 2 archives from memory.
+//! - Appends, replaces and removes entries of an open archive. Replacements
+//!   that fit in the entry's sectors are written in place.
+//! - Defragments archives, closing the gaps left by replaced and removed
+//!   entries.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::img::img_archive::ImgArchive;
+//! use std::fs;
+//!
+//! let mut archive = ImgArchive::open_path_writable("path/to/gta3.img").unwrap();
+//! archive.replace("infernus.dff", &fs::read("path/to/infernus.dff").unwrap()).unwrap();
+//! let size = archive.defragment().unwrap();
+//! archive.into_inner().set_len(size).unwrap();
+//! ```
+
+use super::img_archive::{ImgArchive, ImgEntry, ImgVersion, ENTRY_SIZE, IMG_SECTOR_SIZE};
+use crate::utils::byte_stream::ByteWriter;
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Builds an IMG archive from entries held in memory.
+#[derive(Debug, Clone, Default)]
+pub struct ImgWriter {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl ImgWriter {
+    pub fn new() -> Self {
+        ImgWriter { entries: Vec::new() }
+    }
+
+    /// Adds an entry, replacing any entry with the same name.
+    pub fn add(&mut self, name: &str, data: Vec<u8>) -> Result<()> {
+        check_name(name)?;
+        match self.entries.iter_mut().find(|(existing, _)| existing.eq_ignore_ascii_case(name)) {
+            Some(entry) => entry.1 = data,
+            None => self.entries.push((name.to_string(), data)),
+        }
+        Ok(())
+    }
+
+    /// Writes a version 2 archive.
+    pub fn write_v2<W: Write>(&self, output: &mut W) -> Result<()> {
+        let first_sector = sectors_for(8 + (self.entries.len() * ENTRY_SIZE) as u64);
+        let entries = self.layout(first_sector)?;
+
+        let mut header = encode_v2_table(&entries)?;
+        header.resize((first_sector * IMG_SECTOR_SIZE) as usize, 0);
+        output.write_all(&header)?;
+        self.write_data(output)
+    }
+
+    /// Writes a version 1 archive, with its entry table to `directory` and
+    /// its data to `output`.
+    pub fn write_v1<D: Write, W: Write>(&self, directory: &mut D, output: &mut W) -> Result<()> {
+        let entries = self.layout(0)?;
+        directory.write_all(&encode_directory(&entries))?;
+        self.write_data(output)
+    }
+
+    fn layout(&self, first_sector: u64) -> Result<Vec<ImgEntry>> {
+        let mut offset = first_sector;
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for (name, data) in &self.entries {
+            let size = sectors_for(data.len() as u64);
+            entries.push(ImgEntry {
+                name: name.clone(),
+                offset: to_u32(offset)?,
+                size: to_u32(size)?,
+            });
+            offset += size;
+        }
+        Ok(entries)
+    }
+
+    fn write_data<W: Write>(&self, output: &mut W) -> Result<()> {
+        for (_, data) in &self.entries {
+            output.write_all(data)?;
+            output.write_all(&vec![0; padding_for(data.len() as u64) as usize])?;
+        }
+        Ok(())
+    }
+}
+
+impl ImgArchive<File> {
+    /// Opens an archive from disk for reading and editing. Version 1 entry
+    /// tables are read from the `.dir` file next to the archive and must be
+    /// saved back with `directory()` after editing.
+    pub fn open_path_writable<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        let mut signature = [0; 4];
+        let is_v2 = file.read_exact(&mut signature).is_ok() && &signature == b"VER2";
+        file.seek(SeekFrom::Start(0))?;
+
+        if is_v2 {
+            ImgArchive::open_v2(file)
+        } else {
+            let directory = std::fs::read(path.with_extension("dir"))?;
+            ImgArchive::open_v1(&directory, file)
+        }
+    }
+}
+
+impl<R> ImgArchive<R> {
+    /// Encodes the entry table in the `.dir` format of version 1 archives.
+    pub fn directory(&self) -> Vec<u8> {
+        encode_directory(&self.entries)
+    }
+}
+
+impl<R: Read + Write + Seek> ImgArchive<R> {
+    /// Adds an entry at the end of the archive. Fails if an entry with the
+    /// same name already exists.
+    pub fn append(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        check_name(name)?;
+        if self.find(name).is_some() {
+            return Err(Error::new(ErrorKind::AlreadyExists, format!("IMG entry {} already exists", name)));
+        }
+        let size = self.entry_size(name, sectors_for(data.len() as u64))?;
+
+        self.reserve_table(self.entries.len() + 1)?;
+        let offset = to_u32(self.end_sector())?;
+        self.write_sectors(offset as u64, data)?;
+        self.entries.push(ImgEntry {
+            name: name.to_string(),
+            offset,
+            size,
+        });
+        self.write_table()
+    }
+
+    /// Replaces the data of an entry. The data is written in place if it
+    /// needs no more sectors than the entry already has, and moved to the
+    /// end of the archive otherwise. Nothing is written if the table cannot
+    /// store the new size.
+    pub fn replace(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let index = self.index_of(name)?;
+        let size = self.entry_size(name, sectors_for(data.len() as u64))?;
+
+        let offset = if size <= self.entries[index].size {
+            self.entries[index].offset
+        } else {
+            to_u32(self.end_sector())?
+        };
+        self.write_sectors(offset as u64, data)?;
+
+        let entry = &mut self.entries[index];
+        entry.offset = offset;
+        entry.size = size;
+        self.write_table()
+    }
+
+    /// Removes an entry from the table. Its sectors stay in the archive
+    /// until it is defragmented.
+    pub fn remove(&mut self, name: &str) -> Result<ImgEntry> {
+        let index = self.index_of(name)?;
+        let entry = self.entries.remove(index);
+        self.write_table()?;
+        Ok(entry)
+    }
+
+    /// Moves every entry down to close the gaps between them, keeping their
+    /// order in the archive.
+    ///
+    /// # Returns
+    ///
+    /// The new size of the archive in bytes. The underlying file is not
+    /// truncated; use `into_inner()` and `File::set_len` to do so.
+    pub fn defragment(&mut self) -> Result<u64> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        order.sort_by_key(|&index| self.entries[index].offset);
+
+        let mut next = self.table_sectors(self.entries.len());
+        for index in order {
+            let entry = self.entries[index].clone();
+            if entry.offset as u64 != next {
+                let data = self.read_entry(&entry)?;
+                self.write_sectors(next, &data)?;
+                self.entries[index].offset = to_u32(next)?;
+            }
+            next += entry.size as u64;
+        }
+
+        self.write_table()?;
+        Ok(next * IMG_SECTOR_SIZE)
+    }
+
+    /// Writes the entry table of a version 2 archive. Version 1 tables live
+    /// in a separate file, see `directory()`.
+    pub fn write_table(&mut self) -> Result<()> {
+        if self.version == ImgVersion::V2 {
+            let table = encode_v2_table(&self.entries)?;
+            self.reader.seek(SeekFrom::Start(0))?;
+            self.reader.write_all(&table)?;
+        }
+        Ok(())
+    }
+
+    /// Makes room for a version 2 table of `count` entries by moving the
+    /// entries it would overlap to the end of the archive.
+    fn reserve_table(&mut self, count: usize) -> Result<()> {
+        let table_end = self.table_sectors(count);
+        loop {
+            let Some(index) = self.entries.iter().position(|entry| (entry.offset as u64) < table_end) else {
+                return Ok(());
+            };
+            let entry = self.entries[index].clone();
+            let data = self.read_entry(&entry)?;
+            let offset = self.end_sector().max(table_end);
+            self.write_sectors(offset, &data)?;
+            self.entries[index].offset = to_u32(offset)?;
+        }
+    }
+
+    /// Checks that the entry table can store a size of `sectors`, before
+    /// anything is written. `VER2` tables store sizes in 16 bits.
+    fn entry_size(&self, name: &str, sectors: u64) -> Result<u32> {
+        if self.version == ImgVersion::V2 && sectors > u16::MAX as u64 {
+            return Err(too_large_for_v2(name));
+        }
+        to_u32(sectors)
+    }
+
+    fn table_sectors(&self, count: usize) -> u64 {
+        match self.version {
+            ImgVersion::V1 => 0,
+            ImgVersion::V2 => sectors_for(8 + (count * ENTRY_SIZE) as u64),
+        }
+    }
+
+    fn end_sector(&self) -> u64 {
+        let data_end = self.entries.iter().map(|entry| entry.offset as u64 + entry.size as u64).max().unwrap_or(0);
+        data_end.max(self.table_sectors(self.entries.len()))
+    }
+
+    fn index_of(&self, name: &str) -> Result<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("IMG entry {} not found", name)))
+    }
+
+    /// Writes `data` at the given sector, padding it to a whole sector.
+    fn write_sectors(&mut self, sector: u64, data: &[u8]) -> Result<()> {
+        self.reader.seek(SeekFrom::Start(sector * IMG_SECTOR_SIZE))?;
+        self.reader.write_all(data)?;
+        self.reader.write_all(&vec![0; padding_for(data.len() as u64) as usize])
+    }
+}
+
+/// Encodes an entry table in the `.dir` format of version 1 archives.
+pub fn encode_directory(entries: &[ImgEntry]) -> Vec<u8> {
+    let mut stream = ByteWriter::with_capacity(entries.len() * ENTRY_SIZE);
+    for entry in entries {
+        stream.write_u32(entry.offset);
+        stream.write_u32(entry.size);
+        write_name(&mut stream, &entry.name);
+    }
+    stream.into_inner()
+}
+
+/// Encodes the `VER2` header and entry table of version 2 archives.
+pub fn encode_v2_table(entries: &[ImgEntry]) -> Result<Vec<u8>> {
+    let mut stream = ByteWriter::with_capacity(8 + entries.len() * ENTRY_SIZE);
+    stream.write(b"VER2");
+    stream.write_u32(entries.len() as u32);
+    for entry in entries {
+        let size = u16::try_from(entry.size).map_err(|_| too_large_for_v2(&entry.name))?;
+        stream.write_u32(entry.offset);
+        stream.write_u16(size);
+        stream.write_u16(0);
+        write_name(&mut stream, &entry.name);
+    }
+    Ok(stream.into_inner())
+}
+
+fn too_large_for_v2(name: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("IMG entry {} is too large for VER2", name))
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 23 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("Invalid IMG entry name {:?}", name)));
+    }
+    Ok(())
+}
+
+/// Writes the 24-byte entry name, keeping its last byte as a terminator.
+fn write_name(stream: &mut ByteWriter, name: &str) {
+    let bytes = name.as_bytes();
+    let length = bytes.len().min(23);
+    stream.write(&bytes[..length]);
+    stream.write(&[0; 24][length..]);
+}
+
+fn sectors_for(size: u64) -> u64 {
+    size.div_ceil(IMG_SECTOR_SIZE)
+}
+
+fn padding_for(size: u64) -> u64 {
+    sectors_for(size) * IMG_SECTOR_SIZE - size
+}
+
+fn to_u32(value: u64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| Error::new(ErrorKind::InvalidInput, "IMG archive exceeds 2^32 sectors"))
+}
@@ -0,0 +1,2 @@
+pub mod img_archive;
+pub mod img_writer;
\ No newline at end of file
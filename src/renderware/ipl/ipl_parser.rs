@@ -0,0 +1,306 @@
+//! # IPL Parser
+//!
+//! A parser for item placement files (`.ipl`), which place map objects and
+//! define zones in Grand Theft Auto 3, Vice City, and San Andreas.
+//!
+//! ## Features
+//!
+//! - Parses the `inst`, `cull`, `zone`, `occl`, `pick` and `path` sections
+//!   of text IPL files from all three games.
+//! - Parses the binary streamed IPL files of San Andreas (`bnry`), which
+//!   store instances with quaternion rotations and LOD indices, and parked
+//!   cars.
+//! - Keeps the lines of other sections as raw fields. Binary parked cars
+//!   are kept the same way, with the fields of the matching text line.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::ipl::ipl_parser::IplParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/placement.ipl").unwrap();
+//! let ipl_data = IplParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Instance count: {}", ipl_data.instances.len());
+//! ```
+
+use crate::renderware::common::text::section_lines;
+use crate::renderware::common::types::{RwQuaternion, RwVector3};
+use crate::renderware::rw_file::RwFile;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// Represents a parsed IPL file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwIpl {
+    /// `true` if the file is a binary streamed IPL.
+    pub binary: bool,
+    pub instances: Vec<IplInstance>,
+    pub culls: Vec<IplRawEntry>,
+    pub zones: Vec<IplZone>,
+    pub occluders: Vec<IplOccluder>,
+    pub pickups: Vec<IplPickup>,
+    pub paths: Vec<IplRawEntry>,
+    /// The entries of sections without a dedicated type, such as `grge`,
+    /// `enex` or `cars`. Parked cars of binary files are stored here too,
+    /// formatted as in a text file.
+    pub other: Vec<IplRawEntry>,
+}
+
+/// A placed object, from an `inst` line or a binary instance.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IplInstance {
+    /// The model id, as defined in an IDE file.
+    pub id: i32,
+    /// The model name. Binary IPL files only store the id.
+    pub model_name: Option<String>,
+    /// The interior id, zero for the outside world and for GTA3.
+    pub interior: i32,
+    pub position: RwVector3,
+    /// The scale, only stored by GTA3 and Vice City.
+    pub scale: RwVector3,
+    /// The rotation, as stored in the file.
+    pub rotation: RwQuaternion,
+    /// The index of the LOD instance within the same file, or -1. Only
+    /// stored by San Andreas.
+    pub lod: i32,
+}
+
+/// A map zone, from a `zone` line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IplZone {
+    pub name: String,
+    pub zone_type: i32,
+    pub min: RwVector3,
+    pub max: RwVector3,
+    /// The island the zone belongs to.
+    pub level: i32,
+    /// The GXT key of the zone name, only stored by San Andreas.
+    pub text_key: Option<String>,
+}
+
+/// A visibility occluder, from an `occl` line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IplOccluder {
+    pub mid_x: f32,
+    pub mid_y: f32,
+    pub bottom_z: f32,
+    pub width_x: f32,
+    pub width_y: f32,
+    pub height: f32,
+    /// The rotation about the Z axis, in degrees.
+    pub rotation: f32,
+    /// The rotations about the other axes, only stored by San Andreas.
+    pub extra_rotations: [f32; 2],
+}
+
+/// A weapon pickup, from a `pick` line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IplPickup {
+    pub weapon_id: i32,
+    pub position: RwVector3,
+}
+
+/// The fields of a line whose layout differs between games, such as `cull`
+/// and `path` lines.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IplRawEntry {
+    pub section: String,
+    pub fields: Vec<String>,
+}
+
+/// The main parser for IPL files.
+pub struct IplParser<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> IplParser<'a> {
+    /// Creates a new `IplParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw IPL file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        IplParser { buffer }
+    }
+
+    /// Parses the IPL file, detecting binary files by their `bnry` header.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwIpl` data or an `std::io::Error`
+    /// if a line or binary record is malformed.
+    pub fn parse(&mut self) -> Result<RwIpl> {
+        if self.buffer.starts_with(b"bnry") {
+            self.read_binary()
+        } else {
+            self.read_text()
+        }
+    }
+
+    fn read_text(&self) -> Result<RwIpl> {
+        let text = String::from_utf8_lossy(self.buffer);
+        let mut ipl = RwIpl::default();
+
+        for line in section_lines(&text) {
+            let fields = &line.fields;
+            let error = |message: &str| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("IPL line {}: {} in {} section", line.line_number, message, line.section),
+                )
+            };
+
+            match line.section.as_str() {
+                "inst" => ipl.instances.push(parse_instance(fields).ok_or_else(|| error("invalid instance"))?),
+                "zone" => ipl.zones.push(parse_zone(fields).ok_or_else(|| error("invalid zone"))?),
+                "occl" => ipl.occluders.push(parse_occluder(fields).ok_or_else(|| error("invalid occluder"))?),
+                "pick" => ipl.pickups.push(parse_pickup(fields).ok_or_else(|| error("invalid pickup"))?),
+                section => {
+                    let entry = IplRawEntry {
+                        section: section.to_string(),
+                        fields: fields.iter().map(|field| field.to_string()).collect(),
+                    };
+                    match section {
+                        "cull" => ipl.culls.push(entry),
+                        "path" => ipl.paths.push(entry),
+                        _ => ipl.other.push(entry),
+                    }
+                }
+            }
+        }
+
+        Ok(ipl)
+    }
+
+    fn read_binary(&self) -> Result<RwIpl> {
+        let mut file = RwFile::new(self.buffer);
+        let stream = file.get_stream();
+
+        stream.skip(4)?; // bnry
+        let instances_count = stream.read_i32()?;
+        stream.skip(12)?; // unused section counts
+        let cars_count = stream.read_i32()?;
+        stream.skip(4)?; // unused section count
+        let instances_offset = stream.read_u32()?;
+        stream.skip(28)?; // unused section offsets
+        let cars_offset = stream.read_u32()?;
+
+        if instances_count < 0 || cars_count < 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "Negative binary IPL section count"));
+        }
+
+        stream.set_position(instances_offset as u64);
+        let mut instances = Vec::with_capacity(instances_count.min(0x10000) as usize);
+        for _ in 0..instances_count {
+            let position = RwVector3 { x: stream.read_f32()?, y: stream.read_f32()?, z: stream.read_f32()? };
+            let rotation = RwQuaternion { x: stream.read_f32()?, y: stream.read_f32()?, z: stream.read_f32()?, w: stream.read_f32()? };
+            let id = stream.read_i32()?;
+            let interior = stream.read_i32()?;
+            let lod = stream.read_i32()?;
+
+            instances.push(IplInstance {
+                id,
+                model_name: None,
+                interior,
+                position,
+                scale: RwVector3 { x: 1.0, y: 1.0, z: 1.0 },
+                rotation,
+                lod,
+            });
+        }
+
+        // Parked cars hold the fields of a text `cars` line: the position
+        // and angle as floats, then eight integers
+        stream.set_position(cars_offset as u64);
+        let mut other = Vec::with_capacity(cars_count.min(0x10000) as usize);
+        for _ in 0..cars_count {
+            let mut fields = Vec::with_capacity(12);
+            for _ in 0..4 {
+                fields.push(stream.read_f32()?.to_string());
+            }
+            for _ in 0..8 {
+                fields.push(stream.read_i32()?.to_string());
+            }
+            other.push(IplRawEntry { section: "cars".to_string(), fields });
+        }
+
+        Ok(RwIpl {
+            binary: true,
+            instances,
+            other,
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_instance(fields: &[&str]) -> Option<IplInstance> {
+    let f = |index: usize| fields.get(index)?.parse::<f32>().ok();
+    let i = |index: usize| fields.get(index)?.parse::<i32>().ok();
+    let vector = |start: usize| Some(RwVector3 { x: f(start)?, y: f(start + 1)?, z: f(start + 2)? });
+    let quaternion = |start: usize| Some(RwQuaternion { x: f(start)?, y: f(start + 1)?, z: f(start + 2)?, w: f(start + 3)? });
+    let unit = RwVector3 { x: 1.0, y: 1.0, z: 1.0 };
+
+    let (interior, position, scale, rotation, lod) = match fields.len() {
+        // GTA3: id, name, position, scale, rotation
+        12 => (0, vector(2)?, vector(5)?, quaternion(8)?, -1),
+        // Vice City: id, name, interior, position, scale, rotation
+        13 => (i(2)?, vector(3)?, vector(6)?, quaternion(9)?, -1),
+        // San Andreas: id, name, interior, position, rotation, lod
+        11 => (i(2)?, vector(3)?, unit, quaternion(6)?, i(10)?),
+        _ => return None,
+    };
+
+    Some(IplInstance {
+        id: i(0)?,
+        model_name: Some(fields[1].to_string()),
+        interior,
+        position,
+        scale,
+        rotation,
+        lod,
+    })
+}
+
+fn parse_zone(fields: &[&str]) -> Option<IplZone> {
+    let f = |index: usize| fields.get(index)?.parse::<f32>().ok();
+    let i = |index: usize| fields.get(index)?.parse::<i32>().ok();
+    if fields.len() < 9 {
+        return None;
+    }
+
+    Some(IplZone {
+        name: fields[0].to_string(),
+        zone_type: i(1)?,
+        min: RwVector3 { x: f(2)?, y: f(3)?, z: f(4)? },
+        max: RwVector3 { x: f(5)?, y: f(6)?, z: f(7)? },
+        level: i(8)?,
+        text_key: fields.get(9).map(|key| key.to_string()),
+    })
+}
+
+fn parse_occluder(fields: &[&str]) -> Option<IplOccluder> {
+    let f = |index: usize| fields.get(index)?.parse::<f32>().ok();
+
+    Some(IplOccluder {
+        mid_x: f(0)?,
+        mid_y: f(1)?,
+        bottom_z: f(2)?,
+        width_x: f(3)?,
+        width_y: f(4)?,
+        height: f(5)?,
+        rotation: f(6)?,
+        extra_rotations: [f(7).unwrap_or(0.0), f(8).unwrap_or(0.0)],
+    })
+}
+
+fn parse_pickup(fields: &[&str]) -> Option<IplPickup> {
+    let f = |index: usize| fields.get(index)?.parse::<f32>().ok();
+
+    Some(IplPickup {
+        weapon_id: fields.first()?.parse().ok()?,
+        position: RwVector3 { x: f(1)?, y: f(2)?, z: f(3)? },
+    })
+}
@@ -0,0 +1 @@
+pub mod ipl_parser;
\ No newline at end of file
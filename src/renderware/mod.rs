@@ -1,7 +1,25 @@
 pub mod rw_file;
+pub mod rw_error;
+pub mod rw_report;
+pub mod rw_source_map;
 pub mod rw_sections;
+pub mod rw_chunk_tree;
+pub mod rw_chunk_extractor;
+pub mod rw_visitor;
 pub mod dff;
 pub mod txd;
 pub mod ifp;
+pub mod col;
+pub mod img;
+pub mod ipl;
+pub mod ide;
+pub mod gxt;
+pub mod world;
+pub mod anm;
+pub mod dat;
+pub mod fxp;
+pub mod rrr;
+pub mod nodes;
+pub mod assets;
 pub mod common;
 pub mod utils;
\ No newline at end of file
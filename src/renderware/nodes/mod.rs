@@ -0,0 +1 @@
+pub mod nodes_parser;
\ No newline at end of file
@@ -0,0 +1,258 @@
+//! # Nodes Parser
+//!
+//! A parser for the path node files of San Andreas (`nodes0.dat` to
+//! `nodes63.dat`), each of which holds the vehicle and pedestrian navigation
+//! graph of one 750x750 unit area of the map.
+//!
+//! ## Features
+//!
+//! - Parses vehicle and pedestrian path nodes with their positions and
+//!   traffic flags.
+//! - Parses the navi nodes that describe the lanes between vehicle nodes.
+//! - Parses the links between nodes, including their navi node, length and
+//!   intersection flags, and lists the neighbors of each node.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::nodes::nodes_parser::NodesParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/nodes0.dat").unwrap();
+//! let nodes_data = NodesParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Vehicle nodes: {}", nodes_data.vehicle_nodes().len());
+//! ```
+
+use crate::renderware::common::types::RwVector3;
+use crate::renderware::rw_file::RwFile;
+use std::io::Result;
+
+use serde::Serialize;
+
+/// The size of the unused block between the links and the navi links.
+const FILLER_SIZE: u64 = 768;
+
+/// Represents a parsed path node file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwNodes {
+    /// The vehicle nodes followed by the pedestrian nodes.
+    pub nodes: Vec<PathNode>,
+    pub vehicle_node_count: u32,
+    pub ped_node_count: u32,
+    pub navi_nodes: Vec<NaviNode>,
+    pub links: Vec<PathLink>,
+}
+
+/// A node of the navigation graph.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PathNode {
+    pub position: RwVector3,
+    /// The index of the node's first link in `RwNodes::links`.
+    pub link_index: u16,
+    /// The area (file number) and index of the node.
+    pub area_id: u16,
+    pub node_id: u16,
+    /// The path width, in 1/8 units.
+    pub width: u8,
+    /// The id of the connected group of nodes the node belongs to.
+    pub flood_fill: u8,
+    pub flags: u32,
+}
+
+/// The lane description between two vehicle nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NaviNode {
+    /// The position on the X and Y axes.
+    pub position: [f32; 2],
+    /// The area and index of the vehicle node the navi node is attached to.
+    pub area_id: u16,
+    pub node_id: u16,
+    /// The normalized direction on the X and Y axes.
+    pub direction: [f32; 2],
+    pub flags: u32,
+}
+
+/// A link from a node to one of its neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PathLink {
+    /// The area and index of the neighbor.
+    pub area_id: u16,
+    pub node_id: u16,
+    /// The area and index of the navi node between the two nodes, only set
+    /// for links between vehicle nodes.
+    pub navi_area_id: u16,
+    pub navi_node_id: u16,
+    /// The distance between the two nodes.
+    pub length: u8,
+    /// Bit 0: road crossing, bit 1: traffic light.
+    pub intersection_flags: u8,
+}
+
+impl RwNodes {
+    pub fn vehicle_nodes(&self) -> &[PathNode] {
+        &self.nodes[..self.vehicle_node_count as usize]
+    }
+
+    pub fn ped_nodes(&self) -> &[PathNode] {
+        &self.nodes[self.vehicle_node_count as usize..]
+    }
+
+    /// Returns the links of the node at the given index.
+    pub fn links_of(&self, index: usize) -> &[PathLink] {
+        let Some(node) = self.nodes.get(index) else {
+            return &[];
+        };
+        let start = (node.link_index as usize).min(self.links.len());
+        let end = (start + node.link_count() as usize).min(self.links.len());
+        &self.links[start..end]
+    }
+}
+
+impl PathNode {
+    pub fn link_count(&self) -> u8 {
+        (self.flags & 0xF) as u8
+    }
+
+    /// The traffic density, from 0 (full) to 3 (none).
+    pub fn traffic_level(&self) -> u8 {
+        ((self.flags >> 4) & 0x3) as u8
+    }
+
+    pub fn has_roadblocks(&self) -> bool {
+        self.flags & (1 << 6) != 0
+    }
+
+    pub fn is_water(&self) -> bool {
+        self.flags & (1 << 7) != 0
+    }
+
+    pub fn is_emergency_only(&self) -> bool {
+        self.flags & (1 << 8) != 0
+    }
+
+    pub fn is_highway(&self) -> bool {
+        self.flags & (1 << 13) != 0
+    }
+
+    /// The spawn probability, from 0 to 15.
+    pub fn spawn_probability(&self) -> u8 {
+        ((self.flags >> 16) & 0xF) as u8
+    }
+}
+
+impl NaviNode {
+    /// The road width, in 1/8 units.
+    pub fn width(&self) -> u8 {
+        (self.flags & 0xFF) as u8
+    }
+
+    pub fn left_lanes(&self) -> u8 {
+        ((self.flags >> 8) & 0x7) as u8
+    }
+
+    pub fn right_lanes(&self) -> u8 {
+        ((self.flags >> 11) & 0x7) as u8
+    }
+}
+
+/// The main parser for path node files.
+pub struct NodesParser<'a> {
+    file: RwFile<'a>,
+}
+
+impl<'a> NodesParser<'a> {
+    /// Creates a new `NodesParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw path node data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        NodesParser {
+            file: RwFile::new(buffer),
+        }
+    }
+
+    /// Parses the path node file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwNodes` data or an `std::io::Error`
+    /// if the file is truncated.
+    pub fn parse(&mut self) -> Result<RwNodes> {
+        let stream = self.file.get_stream();
+        let node_count = stream.read_u32()?;
+        let vehicle_node_count = stream.read_u32()?;
+        let ped_node_count = stream.read_u32()?;
+        let navi_node_count = stream.read_u32()?;
+        let link_count = stream.read_u32()?;
+
+        // Every record is at least one byte, so counts larger than the file
+        // are caught by the reads below without allocating for them
+        let size = stream.get_size();
+        let capacity = |count: u32| (count as u64).min(size) as usize;
+
+        let mut nodes = Vec::with_capacity(capacity(node_count));
+        for _ in 0..node_count {
+            nodes.push(self.read_node()?);
+        }
+
+        let mut navi_nodes = Vec::with_capacity(capacity(navi_node_count));
+        for _ in 0..navi_node_count {
+            navi_nodes.push(self.read_navi_node()?);
+        }
+
+        let mut links = Vec::with_capacity(capacity(link_count));
+        for _ in 0..link_count {
+            let stream = self.file.get_stream();
+            let area_id = stream.read_u16()?;
+            let node_id = stream.read_u16()?;
+            links.push(PathLink { area_id, node_id, navi_area_id: 0, navi_node_id: 0, length: 0, intersection_flags: 0 });
+        }
+
+        self.file.get_stream().skip(FILLER_SIZE)?;
+        for link in &mut links {
+            let navi_link = self.file.get_stream().read_u16()?;
+            link.navi_node_id = navi_link & 0x3FF;
+            link.navi_area_id = navi_link >> 10;
+        }
+        for link in &mut links {
+            link.length = self.file.get_stream().read_u8()?;
+        }
+        for link in &mut links {
+            link.intersection_flags = self.file.get_stream().read_u8()?;
+        }
+
+        Ok(RwNodes { nodes, vehicle_node_count: vehicle_node_count.min(node_count), ped_node_count, navi_nodes, links })
+    }
+
+    fn read_node(&mut self) -> Result<PathNode> {
+        let stream = self.file.get_stream();
+        stream.skip(8)?; // runtime pointer and padding
+        let position = RwVector3 {
+            x: stream.read_i16()? as f32 / 8.0,
+            y: stream.read_i16()? as f32 / 8.0,
+            z: stream.read_i16()? as f32 / 8.0,
+        };
+        stream.skip(2)?; // runtime heuristic cost
+        let link_index = stream.read_u16()?;
+        let area_id = stream.read_u16()?;
+        let node_id = stream.read_u16()?;
+        let width = stream.read_u8()?;
+        let flood_fill = stream.read_u8()?;
+        let flags = stream.read_u32()?;
+
+        Ok(PathNode { position, link_index, area_id, node_id, width, flood_fill, flags })
+    }
+
+    fn read_navi_node(&mut self) -> Result<NaviNode> {
+        let stream = self.file.get_stream();
+        let position = [stream.read_i16()? as f32 / 8.0, stream.read_i16()? as f32 / 8.0];
+        let area_id = stream.read_u16()?;
+        let node_id = stream.read_u16()?;
+        let direction = [stream.read_u8()? as i8 as f32 / 100.0, stream.read_u8()? as i8 as f32 / 100.0];
+        let flags = stream.read_u32()?;
+
+        Ok(NaviNode { position, area_id, node_id, direction, flags })
+    }
+}
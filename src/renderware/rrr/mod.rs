@@ -0,0 +1,2 @@
+pub mod rrr_parser;
+pub mod rrr_writer;
\ No newline at end of file
@@ -0,0 +1,154 @@
+//! # RRR Parser
+//!
+//! A parser for the vehicle path recordings of San Andreas (`.rrr`), which
+//! are stored in `carrec.img` and replayed by missions to drive vehicles
+//! along a recorded route.
+//!
+//! ## Features
+//!
+//! - Decodes every recorded frame: time, position, velocity, orientation
+//!   and driver inputs.
+//! - Converts the fixed-point velocity, orientation and pedal values into
+//!   floats.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::img::img_archive::ImgArchive;
+//! use rw_parser_rs::renderware::rrr::rrr_parser::RrrParser;
+//!
+//! let mut archive = ImgArchive::open_path("path/to/your/carrec.img").unwrap();
+//! let file_data = archive.read("carrec401.rrr").unwrap().unwrap();
+//! let rrr_data = RrrParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Recording length: {} ms", rrr_data.duration());
+//! ```
+
+use crate::renderware::common::types::RwVector3;
+use crate::renderware::rw_file::RwFile;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// The size of a recorded frame in bytes.
+pub const RRR_FRAME_SIZE: usize = 32;
+
+/// The fixed-point scales of the recorded values.
+pub(crate) const VELOCITY_SCALE: f32 = 16383.5;
+pub(crate) const ORIENTATION_SCALE: f32 = 127.0;
+pub(crate) const STEERING_SCALE: f32 = 20.0;
+pub(crate) const PEDAL_SCALE: f32 = 100.0;
+
+/// Represents a parsed path recording.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwRrr {
+    /// The frames, in recording order.
+    pub frames: Vec<RrrFrame>,
+}
+
+/// The state of the vehicle at one point of the recording.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RrrFrame {
+    /// The time since the start of the recording, in milliseconds.
+    pub time: u32,
+    /// The move speed, in units per frame at 50 frames per second.
+    pub velocity: RwVector3,
+    /// The right vector of the vehicle matrix.
+    pub right: RwVector3,
+    /// The forward vector of the vehicle matrix.
+    pub top: RwVector3,
+    /// The steering angle, in radians.
+    pub steering_angle: f32,
+    /// The gas pedal, from 0 to 1.
+    pub gas_pedal: f32,
+    /// The brake pedal, from 0 to 1.
+    pub brake_pedal: f32,
+    pub handbrake: bool,
+    pub position: RwVector3,
+}
+
+impl RwRrr {
+    /// Returns the time of the last frame, in milliseconds.
+    pub fn duration(&self) -> u32 {
+        self.frames.last().map_or(0, |frame| frame.time)
+    }
+}
+
+impl RrrFrame {
+    /// Returns the up vector of the vehicle matrix, which recordings do not
+    /// store.
+    pub fn up(&self) -> RwVector3 {
+        let (right, top) = (self.right, self.top);
+        RwVector3 {
+            x: right.y * top.z - right.z * top.y,
+            y: right.z * top.x - right.x * top.z,
+            z: right.x * top.y - right.y * top.x,
+        }
+    }
+}
+
+/// The main parser for RRR files.
+pub struct RrrParser<'a> {
+    file: RwFile<'a>,
+}
+
+impl<'a> RrrParser<'a> {
+    /// Creates a new `RrrParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw RRR file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        RrrParser {
+            file: RwFile::new(buffer),
+        }
+    }
+
+    /// Parses every frame of the recording.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwRrr` data or an `std::io::Error`
+    /// if the file size is not a whole number of frames.
+    pub fn parse(&mut self) -> Result<RwRrr> {
+        let size = self.file.get_stream().get_size() as usize;
+        if !size.is_multiple_of(RRR_FRAME_SIZE) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("RRR size {} is not a multiple of the {} byte frame size", size, RRR_FRAME_SIZE),
+            ));
+        }
+
+        let mut frames = Vec::with_capacity(size / RRR_FRAME_SIZE);
+        for _ in 0..size / RRR_FRAME_SIZE {
+            frames.push(self.read_frame()?);
+        }
+
+        Ok(RwRrr { frames })
+    }
+
+    fn read_frame(&mut self) -> Result<RrrFrame> {
+        let stream = self.file.get_stream();
+        let time = stream.read_u32()?;
+        let velocity = RwVector3 {
+            x: stream.read_i16()? as f32 / VELOCITY_SCALE,
+            y: stream.read_i16()? as f32 / VELOCITY_SCALE,
+            z: stream.read_i16()? as f32 / VELOCITY_SCALE,
+        };
+        let orientation = stream.read(6)?;
+        let right = decode_orientation(&orientation[0..3]);
+        let top = decode_orientation(&orientation[3..6]);
+        let steering_angle = stream.read_u8()? as i8 as f32 / STEERING_SCALE;
+        let gas_pedal = stream.read_u8()? as f32 / PEDAL_SCALE;
+        let brake_pedal = stream.read_u8()? as f32 / PEDAL_SCALE;
+        let handbrake = stream.read_u8()? != 0;
+        let position = RwVector3 { x: stream.read_f32()?, y: stream.read_f32()?, z: stream.read_f32()? };
+
+        Ok(RrrFrame { time, velocity, right, top, steering_angle, gas_pedal, brake_pedal, handbrake, position })
+    }
+}
+
+fn decode_orientation(bytes: &[u8]) -> RwVector3 {
+    let component = |index: usize| bytes[index] as i8 as f32 / ORIENTATION_SCALE;
+    RwVector3 { x: component(0), y: component(1), z: component(2) }
+}
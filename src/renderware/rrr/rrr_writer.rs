@@ -0,0 +1,70 @@
+//! # RRR Writer
+//!
+//! A serializer that turns a `RwRrr` path recording back into an `.rrr`
+//! file.
+//!
+//! ## Features
+//!
+//! - Encodes velocities, orientations and driver inputs into the fixed-point
+//!   format of the game, clamping values outside of its range.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::rrr::rrr_parser::RrrParser;
+//! use rw_parser_rs::renderware::rrr::rrr_writer::RrrWriter;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/carrec401.rrr").unwrap();
+//! let mut rrr_data = RrrParser::new(&file_data).parse().unwrap();
+//! for frame in &mut rrr_data.frames {
+//!     frame.position.z += 1.0;
+//! }
+//!
+//! fs::write("path/to/your/output.rrr", RrrWriter::new().write(&rrr_data)).unwrap();
+//! ```
+
+use super::rrr_parser::{RrrFrame, RwRrr, ORIENTATION_SCALE, PEDAL_SCALE, RRR_FRAME_SIZE, STEERING_SCALE, VELOCITY_SCALE};
+use crate::renderware::common::types::RwVector3;
+use crate::utils::byte_stream::ByteWriter;
+
+/// The main writer for RRR files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RrrWriter;
+
+impl RrrWriter {
+    pub fn new() -> Self {
+        RrrWriter
+    }
+
+    /// Serializes every frame of the recording.
+    pub fn write(&self, rrr: &RwRrr) -> Vec<u8> {
+        let mut stream = ByteWriter::with_capacity(rrr.frames.len() * RRR_FRAME_SIZE);
+        for frame in &rrr.frames {
+            write_frame(&mut stream, frame);
+        }
+        stream.into_inner()
+    }
+}
+
+fn write_frame(stream: &mut ByteWriter, frame: &RrrFrame) {
+    stream.write_u32(frame.time);
+    for component in [frame.velocity.x, frame.velocity.y, frame.velocity.z] {
+        stream.write_i16((component * VELOCITY_SCALE).round() as i16);
+    }
+    write_orientation(stream, &frame.right);
+    write_orientation(stream, &frame.top);
+    stream.write_u8((frame.steering_angle * STEERING_SCALE).round() as i8 as u8);
+    stream.write_u8((frame.gas_pedal * PEDAL_SCALE).round() as u8);
+    stream.write_u8((frame.brake_pedal * PEDAL_SCALE).round() as u8);
+    stream.write_u8(frame.handbrake as u8);
+    for component in [frame.position.x, frame.position.y, frame.position.z] {
+        stream.write_f32(component);
+    }
+}
+
+fn write_orientation(stream: &mut ByteWriter, vector: &RwVector3) {
+    for component in [vector.x, vector.y, vector.z] {
+        stream.write_u8((component * ORIENTATION_SCALE).round() as i8 as u8);
+    }
+}
@@ -0,0 +1,236 @@
+//! # Chunk Extraction
+//!
+//! Reads and replaces the raw payload of a single section, addressed by its
+//! path in the section tree, for surgical edits such as swapping the skin
+//! plugin of a model without decoding and encoding every other section.
+//!
+//! ## Features
+//!
+//! - Addresses sections by name or id, with an index among siblings of the
+//!   same type: `Clump/Geometry List/Geometry[1]/Extension/Skin PLG`.
+//! - Injects a payload of any size, fixing the sizes of the section and of
+//!   all of its ancestors.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::rw_chunk_extractor::{RwChunkExtractor, SectionPath};
+//! use std::fs;
+//!
+//! let model = fs::read("path/to/your/model.dff").unwrap();
+//! let donor = fs::read("path/to/another/model.dff").unwrap();
+//! let path: SectionPath = "Clump/Geometry List/Geometry/Extension/Skin PLG".parse().unwrap();
+//!
+//! let skin = RwChunkExtractor::new(&donor).unwrap().extract(&path).unwrap();
+//! let patched = RwChunkExtractor::new(&model).unwrap().inject(&path, &skin).unwrap();
+//! fs::write("path/to/your/patched.dff", patched).unwrap();
+//! ```
+
+use crate::renderware::rw_chunk_tree::{RwChunk, RwChunkTree};
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_sections::RwSections;
+use crate::utils::byte_stream::Endianness;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+use num_traits::FromPrimitive;
+
+/// Selects sections by id, or by the name of a known section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionSelector {
+    Id(u32),
+    /// Compared with `RwSections::name` ignoring case and spaces.
+    Name(String),
+}
+
+impl SectionSelector {
+    fn matches(&self, chunk: &RwChunk) -> bool {
+        match self {
+            SectionSelector::Id(id) => chunk.section_type == *id,
+            SectionSelector::Name(name) => {
+                RwSections::from_u32(chunk.section_type).is_some_and(|section| normalize(section.name()) == normalize(name))
+            }
+        }
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect()
+}
+
+/// A step of a `SectionPath`: the `index`-th child matching `selector`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionStep {
+    pub selector: SectionSelector,
+    pub index: usize,
+}
+
+/// The path of a section from the top of the stream.
+///
+/// Parsed from steps separated by `/`, each being a section name or a hex
+/// id such as `0x116`, optionally followed by an index in brackets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionPath {
+    pub steps: Vec<SectionStep>,
+}
+
+impl SectionPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a step to the `index`-th child of a known type.
+    pub fn child(self, section: RwSections, index: usize) -> Self {
+        self.child_id(section as u32, index)
+    }
+
+    /// Appends a step to the `index`-th child with the given id.
+    pub fn child_id(mut self, section_type: u32, index: usize) -> Self {
+        self.steps.push(SectionStep {
+            selector: SectionSelector::Id(section_type),
+            index,
+        });
+        self
+    }
+}
+
+impl FromStr for SectionPath {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<Self, Error> {
+        let invalid = |step: &str| Error::new(ErrorKind::InvalidInput, format!("Invalid section path step {:?}", step));
+        let mut steps = Vec::new();
+        for step in path.split('/').map(str::trim).filter(|step| !step.is_empty()) {
+            let (name, index) = match step.strip_suffix(']').and_then(|step| step.split_once('[')) {
+                Some((name, index)) => (name.trim(), index.trim().parse().map_err(|_| invalid(step))?),
+                None => (step, 0),
+            };
+            let selector = match name.strip_prefix("0x").or_else(|| name.strip_prefix("0X")) {
+                Some(hex) => SectionSelector::Id(u32::from_str_radix(hex, 16).map_err(|_| invalid(step))?),
+                None if !name.is_empty() => SectionSelector::Name(name.to_string()),
+                None => return Err(invalid(step)),
+            };
+            steps.push(SectionStep { selector, index });
+        }
+        Ok(SectionPath { steps })
+    }
+}
+
+impl fmt::Display for SectionPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, step) in self.steps.iter().enumerate() {
+            if position > 0 {
+                f.write_str("/")?;
+            }
+            match &step.selector {
+                SectionSelector::Id(id) => write!(f, "{:#x}", id)?,
+                SectionSelector::Name(name) => f.write_str(name)?,
+            }
+            if step.index > 0 {
+                write!(f, "[{}]", step.index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extracts and injects the payloads of the sections of a buffer.
+pub struct RwChunkExtractor<'a> {
+    buffer: &'a [u8],
+    tree: RwChunkTree,
+    endianness: Endianness,
+}
+
+impl<'a> RwChunkExtractor<'a> {
+    /// Builds the section tree of `buffer`.
+    pub fn new(buffer: &'a [u8]) -> RwResult<Self> {
+        RwChunkExtractor::with_endianness(buffer, Endianness::Little)
+    }
+
+    /// Builds the section tree of a buffer whose headers are stored in the
+    /// given byte order. Injected sizes are written in the same order.
+    pub fn with_endianness(buffer: &'a [u8], endianness: Endianness) -> RwResult<Self> {
+        Ok(RwChunkExtractor {
+            buffer,
+            tree: RwChunkTree::parse_with_endianness(buffer, endianness)?,
+            endianness,
+        })
+    }
+
+    pub fn tree(&self) -> &RwChunkTree {
+        &self.tree
+    }
+
+    /// Returns the section at `path`.
+    pub fn find(&self, path: &SectionPath) -> Option<&RwChunk> {
+        self.ancestry(path).ok().and_then(|chunks| chunks.last().copied())
+    }
+
+    /// Returns a copy of the payload of the section at `path`, without its
+    /// header.
+    pub fn extract(&self, path: &SectionPath) -> RwResult<Vec<u8>> {
+        let chunks = self.ancestry(path)?;
+        let chunk = chunks[chunks.len() - 1];
+        chunk.data(self.buffer).map(<[u8]>::to_vec).ok_or_else(|| self.truncated(chunk))
+    }
+
+    /// Returns a copy of the buffer in which the payload of the section at
+    /// `path` is replaced with `payload`, and the sizes of the section and of
+    /// its ancestors are adjusted to the new length.
+    pub fn inject(&self, path: &SectionPath, payload: &[u8]) -> RwResult<Vec<u8>> {
+        let chunks = self.ancestry(path)?;
+        let chunk = chunks[chunks.len() - 1];
+        let (start, end) = (chunk.data_offset() as usize, chunk.end_offset() as usize);
+        if end > self.buffer.len() {
+            return Err(self.truncated(chunk));
+        }
+
+        let delta = payload.len() as i64 - chunk.size as i64;
+        let mut output = Vec::with_capacity(self.buffer.len() - (end - start) + payload.len());
+        output.extend_from_slice(&self.buffer[..start]);
+        output.extend_from_slice(payload);
+        output.extend_from_slice(&self.buffer[end..]);
+
+        // Ancestors start before the section, so their headers did not move
+        for ancestor in &chunks {
+            let size = u32::try_from(ancestor.size as i64 + delta).map_err(|_| RwParseError::UnwritableData {
+                message: format!("Section {} would exceed 4 GiB", path),
+            })?;
+            let field = ancestor.offset as usize + 4;
+            output[field..field + 4].copy_from_slice(&self.endianness.u32_to_bytes(size));
+        }
+        Ok(output)
+    }
+
+    /// Returns the sections along `path`, from the top-level one to the
+    /// target. A missing section is reported at the payload of the deepest
+    /// section found.
+    fn ancestry(&self, path: &SectionPath) -> RwResult<Vec<&RwChunk>> {
+        let mut chunks: Vec<&RwChunk> = Vec::with_capacity(path.steps.len());
+        let mut siblings = &self.tree.chunks;
+        for step in &path.steps {
+            let Some(chunk) = siblings.iter().filter(|chunk| step.selector.matches(chunk)).nth(step.index) else {
+                break;
+            };
+            chunks.push(chunk);
+            siblings = &chunk.children;
+        }
+        if chunks.is_empty() || chunks.len() < path.steps.len() {
+            return Err(RwParseError::InconsistentData {
+                offset: chunks.last().map_or(0, |chunk| chunk.data_offset()),
+                message: format!("No section at {}", path),
+            });
+        }
+        Ok(chunks)
+    }
+
+    fn truncated(&self, chunk: &RwChunk) -> RwParseError {
+        RwParseError::TruncatedSection {
+            section_type: chunk.section_type,
+            offset: chunk.offset,
+            size: chunk.end_offset() - chunk.offset,
+            available: (self.buffer.len() as u64).saturating_sub(chunk.offset),
+        }
+    }
+}
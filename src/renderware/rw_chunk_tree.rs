@@ -0,0 +1,180 @@
+//! # Chunk Tree
+//!
+//! A format-independent view of RenderWare binary streams as a tree of
+//! sections, for inspecting files whose sections no parser understands.
+//!
+//! ## Features
+//!
+//! - Records the offset, size, type and version of every section, known or
+//!   unknown.
+//! - Descends into container sections, detecting unknown containers by
+//!   checking that their payload is a valid sequence of sections.
+//! - Limits the nesting to `rw_visitor::MAX_SECTION_DEPTH`, and walks and searches the
+//!   tree without recursion.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::rw_chunk_tree::RwChunkTree;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/model.dff").unwrap();
+//! let tree = RwChunkTree::parse(&file_data).unwrap();
+//!
+//! for (depth, chunk) in tree.walk() {
+//!     println!("{}{:#x} at {:#x}, {} bytes", "  ".repeat(depth), chunk.section_type, chunk.offset, chunk.size);
+//! }
+//! ```
+
+use crate::renderware::rw_error::RwResult;
+use crate::renderware::rw_file::RwFile;
+use crate::renderware::rw_sections::RwSections;
+use crate::renderware::rw_visitor::{SectionContext, SectionVisitor, VisitAction};
+use crate::utils::byte_stream::Endianness;
+use crate::utils::rw_version::unpack_version;
+
+use num_traits::FromPrimitive;
+use serde::Serialize;
+
+/// The size of a section header: type, size and version.
+pub const RW_SECTION_HEADER_SIZE: u64 = 12;
+
+/// The sections of a RenderWare binary stream.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RwChunkTree {
+    /// The top-level sections, in file order.
+    pub chunks: Vec<RwChunk>,
+}
+
+/// A section and, for containers, its child sections.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwChunk {
+    pub section_type: u32,
+    /// The offset of the section header from the start of the buffer.
+    pub offset: u64,
+    /// The size of the payload, excluding the header.
+    pub size: u32,
+    /// The packed library id, as stored in the header.
+    pub version_number: u32,
+    /// The child sections. Empty for sections whose payload is raw data.
+    pub children: Vec<RwChunk>,
+}
+
+impl RwChunk {
+    /// The known section type, if any.
+    pub fn section(&self) -> Option<RwSections> {
+        RwSections::from_u32(self.section_type)
+    }
+
+    /// The unpacked RenderWare version, such as `0x36003`.
+    pub fn version(&self) -> u32 {
+        unpack_version(self.version_number)
+    }
+
+    /// The offset of the payload from the start of the buffer.
+    pub fn data_offset(&self) -> u64 {
+        self.offset + RW_SECTION_HEADER_SIZE
+    }
+
+    /// The offset of the end of the section from the start of the buffer.
+    pub fn end_offset(&self) -> u64 {
+        self.data_offset() + self.size as u64
+    }
+
+    pub fn is_container(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    /// Returns the payload of the section within the buffer it was parsed
+    /// from.
+    pub fn data<'a>(&self, buffer: &'a [u8]) -> Option<&'a [u8]> {
+        buffer.get(self.data_offset() as usize..self.end_offset() as usize)
+    }
+
+    /// Returns the first descendant of the given type, depth-first.
+    pub fn find(&self, section_type: u32) -> Option<&RwChunk> {
+        find_in(&self.children, section_type)
+    }
+}
+
+impl RwChunkTree {
+    /// Builds the section tree of a buffer. Trailing bytes too short to hold
+    /// a section header, such as padding, are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the tree or a `RwParseError` if a top-level
+    /// section extends past the end of the buffer, or if sections are nested
+    /// deeper than `MAX_SECTION_DEPTH`.
+    pub fn parse(buffer: &[u8]) -> RwResult<RwChunkTree> {
+        RwChunkTree::parse_with_endianness(buffer, Endianness::Little)
+    }
+
+    /// Builds the section tree of a buffer whose headers are stored in the
+    /// given byte order, such as a big-endian GameCube or PS3 stream.
+    pub fn parse_with_endianness(buffer: &[u8], endianness: Endianness) -> RwResult<RwChunkTree> {
+        let mut builder = ChunkTreeBuilder::default();
+        RwFile::with_endianness(buffer, endianness).visit(&mut builder)?;
+        Ok(RwChunkTree { chunks: builder.chunks })
+    }
+
+    /// Returns the first section of the given type, depth-first.
+    pub fn find(&self, section_type: u32) -> Option<&RwChunk> {
+        find_in(&self.chunks, section_type)
+    }
+
+    /// Returns every section with its depth, in file order.
+    pub fn walk(&self) -> Vec<(usize, &RwChunk)> {
+        let mut output = Vec::new();
+        let mut pending: Vec<(usize, &RwChunk)> = self.chunks.iter().rev().map(|chunk| (0, chunk)).collect();
+        while let Some((depth, chunk)) = pending.pop() {
+            output.push((depth, chunk));
+            pending.extend(chunk.children.iter().rev().map(|child| (depth + 1, child)));
+        }
+        output
+    }
+}
+
+/// Returns the first section of the given type among `chunks` and their
+/// descendants, depth-first, keeping the pending sections on a stack.
+fn find_in(chunks: &[RwChunk], section_type: u32) -> Option<&RwChunk> {
+    let mut pending: Vec<&RwChunk> = chunks.iter().rev().collect();
+    while let Some(chunk) = pending.pop() {
+        if chunk.section_type == section_type {
+            return Some(chunk);
+        }
+        pending.extend(chunk.children.iter().rev());
+    }
+    None
+}
+
+/// Builds the tree from the sections of a walk, keeping the sections that
+/// are still open on a stack.
+#[derive(Default)]
+struct ChunkTreeBuilder {
+    open: Vec<RwChunk>,
+    chunks: Vec<RwChunk>,
+}
+
+impl SectionVisitor for ChunkTreeBuilder {
+    fn enter_section(&mut self, section: &SectionContext) -> VisitAction {
+        self.open.push(RwChunk {
+            section_type: section.section_type,
+            offset: section.offset,
+            size: section.size,
+            version_number: section.version_number,
+            children: Vec::new(),
+        });
+        VisitAction::Descend
+    }
+
+    fn leave_section(&mut self, _section: &SectionContext) {
+        let Some(chunk) = self.open.pop() else {
+            return;
+        };
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(chunk),
+            None => self.chunks.push(chunk),
+        }
+    }
+}
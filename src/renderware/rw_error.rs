@@ -0,0 +1,76 @@
+//! # Parse Errors
+//!
+//! The error type of the RenderWare stream parsers, which keeps the section
+//! and byte offset of a failure instead of a bare I/O error. The writers use
+//! it too, for data that cannot be stored in the format being written.
+
+use std::io::{Error, ErrorKind};
+
+use thiserror::Error;
+
+/// An error raised while parsing a RenderWare file.
+#[derive(Debug, Error)]
+pub enum RwParseError {
+    /// A section header has a different type than the format requires at
+    /// this point.
+    #[error("expected section {expected:#x}, found {found:#x} at offset {offset:#x}")]
+    UnexpectedSection { expected: u32, found: u32, offset: u64 },
+    /// A section or its header extends past the end of the data.
+    #[error("section {section_type:#x} at offset {offset:#x} needs {size} bytes, but only {available} remain")]
+    TruncatedSection { section_type: u32, offset: u64, size: u64, available: u64 },
+    /// A count read from the file needs more data than remains, which would
+    /// otherwise lead to a huge allocation.
+    #[error("{count} {what} of at least {element_size} bytes at offset {offset:#x} need more than the {available} remaining bytes")]
+    ExcessiveCount { what: &'static str, count: u64, element_size: u64, offset: u64, available: u64 },
+    /// A texture native uses a platform without a decoder.
+    #[error("unsupported platform {platform:#x} at offset {offset:#x}")]
+    UnsupportedPlatform { platform: u32, offset: u64 },
+    /// The data is readable but inconsistent, and the parser runs in
+    /// `ParseMode::Strict` or cannot recover from it.
+    #[error("inconsistent data at offset {offset:#x}: {message}")]
+    InconsistentData { offset: u64, message: String },
+    /// A file declares a format version the parser does not know.
+    #[error("unsupported {format} version {version:?}")]
+    UnsupportedVersion { format: &'static str, version: String },
+    /// The data is not in any format the parsers know.
+    #[error("unrecognized file format")]
+    UnknownFormat,
+    /// A writer was given data that the format it writes cannot store.
+    #[error("cannot write data: {message}")]
+    UnwritableData { message: String },
+    #[error(transparent)]
+    Io(#[from] Error),
+}
+
+/// The result type of the RenderWare stream parsers.
+pub type RwResult<T> = std::result::Result<T, RwParseError>;
+
+/// Runs a parser on untrusted data, turning a panic into an error. With
+/// `panic = "abort"`, as under fuzzers, the panic still aborts so that it
+/// gets reported.
+pub(crate) fn catch_panic<T>(parse: impl FnOnce() -> RwResult<T>) -> RwResult<T> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(parse)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(RwParseError::InconsistentData {
+            offset: 0,
+            message: format!("The parser panicked: {}", message),
+        })
+    })
+}
+
+impl From<RwParseError> for Error {
+    /// Keeps the parsers and writers usable from code returning
+    /// `std::io::Result`. I/O errors are passed through, data a writer cannot
+    /// store becomes `InvalidInput` and the others become `InvalidData`.
+    fn from(error: RwParseError) -> Self {
+        match error {
+            RwParseError::Io(error) => error,
+            error @ RwParseError::UnwritableData { .. } => Error::new(ErrorKind::InvalidInput, error),
+            error => Error::new(ErrorKind::InvalidData, error),
+        }
+    }
+}
@@ -1,5 +1,12 @@
-use crate::utils::byte_stream::ByteStream;
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_sections::RwSections;
+use crate::utils::byte_stream::{ByteStream, ByteWriter, Endianness};
+use crate::utils::rw_version::unpack_version;
+use std::io::{Read, Seek, SeekFrom};
 
+use num_traits::FromPrimitive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RwSectionHeader {
     pub section_type: u32,
     pub section_size: u32,
@@ -8,12 +15,24 @@ pub struct RwSectionHeader {
 
 pub struct RwFile<'a> {
     stream: ByteStream<'a>,
+    /// The end offsets of the sections being read, innermost last.
+    scopes: Vec<u64>,
 }
 
 impl<'a> RwFile<'a> {
     pub fn new(buffer: &'a [u8]) -> Self {
         RwFile {
             stream: ByteStream::new(buffer),
+            scopes: Vec::new(),
+        }
+    }
+
+    /// Creates a file whose section headers and payloads are read with the
+    /// given byte order.
+    pub fn with_endianness(buffer: &'a [u8], endianness: Endianness) -> Self {
+        RwFile {
+            stream: ByteStream::with_endianness(buffer, endianness),
+            scopes: Vec::new(),
         }
     }
 
@@ -29,7 +48,347 @@ impl<'a> RwFile<'a> {
         })
     }
 
+    /// Reads a section header, checking that it has the expected type and
+    /// that the whole section fits in the innermost section being read.
+    pub fn expect_section(&mut self, expected: RwSections) -> RwResult<RwSectionHeader> {
+        let offset = self.stream.get_position();
+        let available = self.remaining();
+        if available < 12 {
+            return Err(RwParseError::TruncatedSection {
+                section_type: expected as u32,
+                offset,
+                size: 12,
+                available,
+            });
+        }
+        let header = self.read_section_header().map_err(|_| RwParseError::TruncatedSection {
+            section_type: expected as u32,
+            offset,
+            size: 12,
+            available,
+        })?;
+
+        if header.section_type != expected as u32 {
+            return Err(RwParseError::UnexpectedSection {
+                expected: expected as u32,
+                found: header.section_type,
+                offset,
+            });
+        }
+        if header.section_size as u64 > available - 12 {
+            return Err(RwParseError::TruncatedSection {
+                section_type: header.section_type,
+                offset,
+                size: header.section_size as u64 + 12,
+                available,
+            });
+        }
+
+        Ok(header)
+    }
+
+    /// Reads a whole little-endian section, header included, from the
+    /// current position of `reader` and leaves the reader at its end. The
+    /// size is checked against the remaining data before anything is
+    /// allocated, so a damaged header cannot cause a huge allocation.
+    pub fn read_section_from<R: Read + Seek>(reader: &mut R) -> RwResult<(RwSectionHeader, Vec<u8>)> {
+        RwFile::read_section_from_with_endianness(reader, Endianness::Little)
+    }
+
+    /// Like `read_section_from`, but reads the header with the given byte
+    /// order.
+    pub fn read_section_from_with_endianness<R: Read + Seek>(
+        reader: &mut R,
+        endianness: Endianness,
+    ) -> RwResult<(RwSectionHeader, Vec<u8>)> {
+        let offset = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let available = end.saturating_sub(offset);
+
+        let mut header_bytes = [0u8; 12];
+        if available < 12 {
+            return Err(RwParseError::TruncatedSection { section_type: 0, offset, size: 12, available });
+        }
+        reader.read_exact(&mut header_bytes)?;
+        let field = |index: usize| endianness.u32_from_bytes(header_bytes[index..index + 4].try_into().unwrap());
+        let header = RwSectionHeader {
+            section_type: field(0),
+            section_size: field(4),
+            version_number: field(8),
+        };
+
+        let size = 12 + header.section_size as u64;
+        if size > available {
+            return Err(RwParseError::TruncatedSection {
+                section_type: header.section_type,
+                offset,
+                size,
+                available,
+            });
+        }
+
+        let mut data = Vec::with_capacity(size as usize);
+        data.extend_from_slice(&header_bytes);
+        reader.by_ref().take(header.section_size as u64).read_to_end(&mut data)?;
+        Ok((header, data))
+    }
+
+    /// Starts a scope for the payload of `header`, which must have just been
+    /// read. Until the scope is left, `remaining` and `checked_capacity` stop
+    /// at the end of the section, which is clamped to the end of its parent.
+    /// Returns the depth to pass to `leave_section`.
+    pub fn enter_section(&mut self, header: &RwSectionHeader) -> usize {
+        self.enter_chunk(header.section_size as u64)
+    }
+
+    /// Like `enter_section`, but for a chunk of `size` bytes starting at the
+    /// position, for formats whose chunks have their own headers.
+    pub fn enter_chunk(&mut self, size: u64) -> usize {
+        let end = self.stream.get_position().saturating_add(size).min(self.section_end());
+        self.scopes.push(end);
+        self.scopes.len() - 1
+    }
+
+    /// Leaves the scope started at `depth`, along with any scope inside it
+    /// left open by an error, and moves to the end of its section. Returns
+    /// the number of bytes read past that end, which belong to the next
+    /// section and will be read again.
+    pub fn leave_section(&mut self, depth: usize) -> u64 {
+        let Some(&end) = self.scopes.get(depth) else {
+            return 0;
+        };
+        self.scopes.truncate(depth);
+        let overrun = self.stream.get_position().saturating_sub(end);
+        self.stream.set_position(end);
+        overrun
+    }
+
+    /// Returns the end offset of the innermost section being read, or the
+    /// size of the data outside of any section.
+    pub fn section_end(&self) -> u64 {
+        self.scopes.last().copied().unwrap_or_else(|| self.stream.get_size())
+    }
+
+    /// Returns the number of bytes left in the innermost section.
+    pub fn remaining(&self) -> u64 {
+        self.section_end().saturating_sub(self.stream.get_position())
+    }
+
+    /// Checks that `count` elements of at least `element_size` bytes each fit
+    /// in the rest of the innermost section, and returns the count as a capacity that is
+    /// safe to allocate.
+    pub fn checked_capacity(&self, what: &'static str, count: u64, element_size: u64) -> RwResult<usize> {
+        let available = self.remaining();
+        if count.saturating_mul(element_size) > available {
+            return Err(RwParseError::ExcessiveCount {
+                what,
+                count,
+                element_size,
+                offset: self.stream.get_position(),
+                available,
+            });
+        }
+        Ok(count as usize)
+    }
+
+    /// Returns `count` clamped to the number of elements of at least
+    /// `element_size` bytes that fit in the remaining data, for lists whose
+    /// reading stops at the first damaged element anyway.
+    pub fn bounded_capacity(&self, count: u64, element_size: u64) -> usize {
+        count.min(self.remaining() / element_size.max(1)) as usize
+    }
+
     pub fn get_stream(&mut self) -> &mut ByteStream<'a> {
         &mut self.stream
     }
+
+    /// Returns an iterator over the sections from the current position to
+    /// the end of the buffer, reading only their headers. Use
+    /// `RwSection::descend` to iterate over the children of a container.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rw_parser_rs::renderware::rw_file::RwFile;
+    /// use rw_parser_rs::renderware::rw_sections::RwSections;
+    /// use std::fs;
+    ///
+    /// let file_data = fs::read("path/to/your/model.dff").unwrap();
+    /// let file = RwFile::new(&file_data);
+    /// for section in file.sections() {
+    ///     println!("{:#x} version {:#x}", section.header.section_type, section.version());
+    ///     let has_skin = section
+    ///         .descend()
+    ///         .filter(|child| child.section() == Some(RwSections::RwGeometryList))
+    ///         .flat_map(|list| list.descend())
+    ///         .filter(|child| child.section() == Some(RwSections::RwGeometry))
+    ///         .flat_map(|geometry| geometry.descend())
+    ///         .filter(|child| child.section() == Some(RwSections::RwExtension))
+    ///         .any(|extension| extension.descend().any(|plugin| plugin.section() == Some(RwSections::RwSkin)));
+    ///     println!("Skinned: {}", has_skin);
+    /// }
+    /// ```
+    pub fn sections(&self) -> RwSectionIter<'a> {
+        let buffer = self.stream.buffer();
+        RwSectionIter {
+            buffer,
+            position: self.stream.get_position(),
+            end: buffer.len() as u64,
+            endianness: self.stream.endianness(),
+        }
+    }
+}
+
+/// A section found by `RwFile::sections` or `RwSection::descend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RwSection<'a> {
+    pub header: RwSectionHeader,
+    /// The offset of the section header from the start of the buffer.
+    pub offset: u64,
+    /// The size of the payload, clamped to the data of the parent, so it is
+    /// smaller than `header.section_size` for truncated sections.
+    pub payload_len: u64,
+    buffer: &'a [u8],
+    endianness: Endianness,
+}
+
+impl<'a> RwSection<'a> {
+    /// The known section type, if any.
+    pub fn section(&self) -> Option<RwSections> {
+        RwSections::from_u32(self.header.section_type)
+    }
+
+    /// The unpacked RenderWare version, such as `0x36003`.
+    pub fn version(&self) -> u32 {
+        unpack_version(self.header.version_number)
+    }
+
+    /// Returns the payload of the section.
+    pub fn payload(&self) -> &'a [u8] {
+        let start = self.offset as usize + 12;
+        &self.buffer[start..start + self.payload_len as usize]
+    }
+
+    /// Returns an iterator over the child sections. The payload is read as
+    /// a sequence of sections whatever the type, so this is only meaningful
+    /// for containers such as clumps, geometries and extensions.
+    pub fn descend(&self) -> RwSectionIter<'a> {
+        RwSectionIter {
+            buffer: self.buffer,
+            position: self.offset + 12,
+            end: self.offset + 12 + self.payload_len,
+            endianness: self.endianness,
+        }
+    }
+}
+
+/// An iterator over sibling sections, created by `RwFile::sections` and
+/// `RwSection::descend`. It stops at the end of its parent, at trailing
+/// bytes too short to hold a header and at zero padding, and yields a
+/// section extending past its parent with a clamped payload as its last
+/// item.
+#[derive(Debug, Clone)]
+pub struct RwSectionIter<'a> {
+    buffer: &'a [u8],
+    position: u64,
+    end: u64,
+    endianness: Endianness,
+}
+
+impl<'a> Iterator for RwSectionIter<'a> {
+    type Item = RwSection<'a>;
+
+    fn next(&mut self) -> Option<RwSection<'a>> {
+        if self.end.saturating_sub(self.position) < 12 {
+            return None;
+        }
+        let offset = self.position;
+        let field = |index: u64| {
+            let start = (offset + index) as usize;
+            let bytes: [u8; 4] = self.buffer[start..start + 4].try_into().unwrap();
+            self.endianness.u32_from_bytes(bytes)
+        };
+        let header = RwSectionHeader {
+            section_type: field(0),
+            section_size: field(4),
+            version_number: field(8),
+        };
+        if header.section_type == 0 && header.section_size == 0 {
+            self.position = self.end;
+            return None;
+        }
+
+        let payload_len = (header.section_size as u64).min(self.end - offset - 12);
+        self.position = offset + 12 + payload_len;
+        Some(RwSection {
+            header,
+            offset,
+            payload_len,
+            buffer: self.buffer,
+            endianness: self.endianness,
+        })
+    }
+}
+
+/// Writes RenderWare sections, filling in the size of each section header
+/// once its content has been written.
+#[derive(Debug, Default)]
+pub struct RwFileWriter {
+    stream: ByteWriter,
+    open_sections: Vec<u64>,
+}
+
+impl RwFileWriter {
+    pub fn new() -> Self {
+        RwFileWriter::default()
+    }
+
+    /// Writes a section header with a placeholder size. Everything written
+    /// until the matching `end_section` call, including nested sections,
+    /// becomes the section's payload.
+    pub fn begin_section(&mut self, section_type: u32, version_number: u32) {
+        self.stream.write_u32(section_type);
+        self.open_sections.push(self.stream.get_position());
+        self.stream.write_u32(0);
+        self.stream.write_u32(version_number);
+    }
+
+    /// Closes the innermost open section and writes its size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no section is open.
+    pub fn end_section(&mut self) {
+        let size_position = self.open_sections.pop().expect("end_section called without an open section");
+        let size = self.stream.get_position() - size_position - 8;
+        self.stream.patch_u32(size_position, size as u32);
+    }
+
+    /// Writes a complete section with the given payload.
+    pub fn write_section(&mut self, section_type: u32, version_number: u32, payload: &[u8]) {
+        self.stream.write_u32(section_type);
+        self.stream.write_u32(payload.len() as u32);
+        self.stream.write_u32(version_number);
+        self.stream.write(payload);
+    }
+
+    /// Returns the number of sections that have been begun but not ended.
+    pub fn depth(&self) -> usize {
+        self.open_sections.len()
+    }
+
+    pub fn get_stream(&mut self) -> &mut ByteWriter {
+        &mut self.stream
+    }
+
+    /// Returns the written data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a section is still open, as its size would be missing.
+    pub fn into_bytes(self) -> Vec<u8> {
+        assert!(self.open_sections.is_empty(), "{} sections were not ended", self.open_sections.len());
+        self.stream.into_inner()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,114 @@
+//! # Parse Reports
+//!
+//! Warnings collected while parsing a file that is readable but not quite
+//! well-formed, such as sections the parser does not know, plugins it skips
+//! or values that are unlikely to be intended. They are returned by the
+//! `parse_with_report` method of the DFF, TXD and IFP parsers, whose
+//! `ParseMode` decides whether such data is an error, a warning, or clamped
+//! and recovered from.
+
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_sections::RwSectionId;
+
+use serde::Serialize;
+
+/// How a parser reacts to data that is readable but inconsistent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum ParseMode {
+    /// Fails on any inconsistency, including the suspicious values that are
+    /// otherwise only reported. Meant for validators.
+    Strict,
+    /// Fails on data that cannot be read and reports suspicious values.
+    #[default]
+    Standard,
+    /// Clamps out of range values and stops at damaged data, returning what
+    /// was read so far. Meant for viewers of community-made files.
+    Lenient,
+}
+
+/// The kind of a `ParseWarning`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ParseWarningKind {
+    /// A section whose type is not known was skipped.
+    UnknownSection { section_type: u32 },
+    /// A known section or plugin was skipped without being interpreted.
+    SkippedPlugin { section_type: u32 },
+    /// A value was read as is but looks wrong, such as an empty mipmap.
+    SuspiciousValue,
+    /// Damaged data was clamped or dropped in `ParseMode::Lenient`.
+    Recovered,
+}
+
+/// A single warning, with the byte offset it refers to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    pub offset: u64,
+    pub message: String,
+}
+
+/// The warnings collected while parsing a file, in the order they were met.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ParseReport {
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl ParseReport {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Returns the warnings of sections the parser does not know.
+    pub fn unknown_sections(&self) -> impl Iterator<Item = &ParseWarning> {
+        self.warnings.iter().filter(|warning| matches!(warning.kind, ParseWarningKind::UnknownSection { .. }))
+    }
+
+    /// Returns the warnings of skipped plugins.
+    pub fn skipped_plugins(&self) -> impl Iterator<Item = &ParseWarning> {
+        self.warnings.iter().filter(|warning| matches!(warning.kind, ParseWarningKind::SkippedPlugin { .. }))
+    }
+
+    /// Returns the warnings of data recovered in lenient mode.
+    pub fn recoveries(&self) -> impl Iterator<Item = &ParseWarning> {
+        self.warnings.iter().filter(|warning| warning.kind == ParseWarningKind::Recovered)
+    }
+
+    /// Returns the warnings of suspicious values.
+    pub fn suspicious_values(&self) -> impl Iterator<Item = &ParseWarning> {
+        self.warnings.iter().filter(|warning| warning.kind == ParseWarningKind::SuspiciousValue)
+    }
+
+    pub(crate) fn warn(&mut self, kind: ParseWarningKind, offset: u64, message: impl Into<String>) {
+        self.warnings.push(ParseWarning {
+            kind,
+            offset,
+            message: message.into(),
+        });
+    }
+
+    /// Reports a suspicious value, which is an error in strict mode.
+    pub(crate) fn suspicious(&mut self, mode: ParseMode, offset: u64, message: impl Into<String>) -> RwResult<()> {
+        let message = message.into();
+        if mode == ParseMode::Strict {
+            return Err(RwParseError::InconsistentData { offset, message });
+        }
+        self.warn(ParseWarningKind::SuspiciousValue, offset, message);
+        Ok(())
+    }
+
+    /// Records data that was clamped or dropped in lenient mode.
+    pub(crate) fn recovered(&mut self, offset: u64, message: impl Into<String>) {
+        self.warn(ParseWarningKind::Recovered, offset, message);
+    }
+
+    /// Records a skipped section as unknown or as a skipped plugin depending
+    /// on whether its type is known.
+    pub(crate) fn skipped_section(&mut self, section_type: u32, offset: u64) {
+        let section = RwSectionId::from_u32(section_type);
+        let kind = match section {
+            RwSectionId::Known(_) => ParseWarningKind::SkippedPlugin { section_type },
+            _ => ParseWarningKind::UnknownSection { section_type },
+        };
+        self.warn(kind, offset, format!("Skipped {}", section));
+    }
+}
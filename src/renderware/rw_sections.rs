@@ -1,26 +1,470 @@
+//! # RenderWare Sections
+//!
+//! The ids of the sections (chunks) of RenderWare binary streams, covering
+//! the core sections, the Criterion toolkit and world plugins, and the
+//! Rockstar plugins of the GTA games.
+//!
+//! A section id packs a vendor id in its upper 24 bits and a per-vendor id
+//! in its lowest 8 bits. `RwSectionId` uses this to tell apart ids that are
+//! not listed in `RwSections` but come from a known vendor's range.
+
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[repr(u32)]
 pub enum RwSections {
+    // Core sections.
     RwStruct = 0x0001,
     RwString = 0x0002,
     RwExtension = 0x0003,
+    RwCamera = 0x0005,
     RwTexture = 0x0006,
     RwMaterial = 0x0007,
     RwMaterialList = 0x0008,
+    RwAtomicSector = 0x0009,
+    RwPlaneSector = 0x000A,
+    RwWorld = 0x000B,
+    RwSpline = 0x000C,
+    RwMatrix = 0x000D,
     RwFrameList = 0x000E,
     RwGeometry = 0x000F,
     RwClump = 0x0010,
+    RwLight = 0x0012,
+    RwUnicodeString = 0x0013,
     RwAtomic = 0x0014,
     RwTextureNative = 0x0015,
     RwTextureDictionary = 0x0016,
+    RwAnimationDatabase = 0x0017,
+    RwImage = 0x0018,
+    RwSkinAnimation = 0x0019,
     RwGeometryList = 0x001A,
-    RwSkin = 0x116,
-    RwAnim = 0x11E,
+    RwAnimAnimation = 0x001B,
+    RwTeam = 0x001C,
+    RwCrowd = 0x001D,
+    RwDeltaMorphAnimation = 0x001E,
+    RwRightToRender = 0x001F,
+    RwMultiTextureEffectNative = 0x0020,
+    RwMultiTextureEffectDictionary = 0x0021,
+    RwTeamDictionary = 0x0022,
+    RwPlatformIndependentTextureDictionary = 0x0023,
+    RwTableOfContents = 0x0024,
+    RwParticleStandardGlobalData = 0x0025,
+    RwAltPipe = 0x0026,
+    RwPlatformIndependentPeds = 0x0027,
+    RwPatchMesh = 0x0028,
+    RwChunkGroupStart = 0x0029,
+    RwChunkGroupEnd = 0x002A,
+    RwUvAnimationDictionary = 0x002B,
+    RwCollTree = 0x002C,
 
+    // Criterion toolkit plugins.
+    RwMetricsPLG = 0x0101,
+    RwSplinePLG = 0x0102,
+    RwStereoPLG = 0x0103,
+    RwVrmlPLG = 0x0104,
+    RwMorphPLG = 0x0105,
+    RwPvsPLG = 0x0106,
+    RwMemoryLeakPLG = 0x0107,
+    RwAnimationPLG = 0x0108,
+    RwGlossPLG = 0x0109,
+    RwLogoPLG = 0x010A,
+    RwMemoryInfoPLG = 0x010B,
+    RwRandomPLG = 0x010C,
+    RwPngImagePLG = 0x010D,
+    RwBonePLG = 0x010E,
+    RwVrmlAnimPLG = 0x010F,
+    RwSkyMipmapVal = 0x0110,
+    RwMrmPLG = 0x0111,
+    RwLodAtomicPLG = 0x0112,
+    RwMePLG = 0x0113,
+    RwLightmapPLG = 0x0114,
+    RwRefinePLG = 0x0115,
+    RwSkin = 0x0116,
+    RwLabelPLG = 0x0117,
+    RwParticlesPLG = 0x0118,
+    RwGeomTxPLG = 0x0119,
+    RwSynthCorePLG = 0x011A,
+    RwStqppPLG = 0x011B,
+    RwPartPpPLG = 0x011C,
+    RwCollisionPLG = 0x011D,
+    RwAnim = 0x011E,
+    RwUserDataPLG = 0x011F,
     RwMaterialEffectsPLG = 0x0120,
+    RwParticleSystemPLG = 0x0121,
+    RwDeltaMorphPLG = 0x0122,
+    RwPatchPLG = 0x0123,
+    RwTeamPLG = 0x0124,
+    RwCrowdPpPLG = 0x0125,
+    RwMipSplitPLG = 0x0126,
+    RwAnisotropyPLG = 0x0127,
+    RwGcnMaterialPLG = 0x0129,
+    RwGeometricPvsPLG = 0x012A,
+    RwXboxMaterialPLG = 0x012B,
+    RwMultiTexturePLG = 0x012C,
+    RwChainPLG = 0x012D,
+    RwToonPLG = 0x012E,
+    RwPTankPLG = 0x012F,
+    RwParticleStandardPLG = 0x0130,
+    RwPdsPLG = 0x0131,
+    RwPrtAdvPLG = 0x0132,
+    RwNormalMapPLG = 0x0133,
+    RwAdcPLG = 0x0134,
+    RwUvAnimationPLG = 0x0135,
+    RwCharacterSetPLG = 0x0180,
+    RwNohsWorldPLG = 0x0181,
+    RwImportUtilPLG = 0x0182,
+    RwSlerpPLG = 0x0183,
+    RwOptimPLG = 0x0184,
+    RwTlWorldPLG = 0x0185,
+    RwDatabasePLG = 0x0186,
+    RwRaytracePLG = 0x0187,
+    RwRayPLG = 0x0188,
+    RwLibraryPLG = 0x0189,
+    Rw2dPLG = 0x0190,
+    RwTileRenderPLG = 0x0191,
+    RwJpegImagePLG = 0x0192,
+    RwTgaImagePLG = 0x0193,
+    RwGifImagePLG = 0x0194,
+    RwQuatPLG = 0x0195,
+    RwSplinePvsPLG = 0x0196,
+    RwMipmapPLG = 0x0197,
+    RwMipmapKPLG = 0x0198,
+    Rw2dFont = 0x0199,
+    RwIntersectionPLG = 0x019A,
+    RwTiffImagePLG = 0x019B,
+    RwPickPLG = 0x019C,
+    RwBmpImagePLG = 0x019D,
+    RwRasImagePLG = 0x019E,
+    RwSkinFxPLG = 0x019F,
+    RwVcatPLG = 0x01A0,
+    Rw2dPath = 0x01A1,
+    Rw2dBrush = 0x01A2,
+    Rw2dObject = 0x01A3,
+    Rw2dShape = 0x01A4,
+    Rw2dScene = 0x01A5,
+    Rw2dPickRegion = 0x01A6,
+    Rw2dObjectString = 0x01A7,
+    Rw2dAnimationPLG = 0x01A8,
+    Rw2dAnimation = 0x01A9,
+    Rw2dKeyframe = 0x01B0,
+    Rw2dMaestro = 0x01B1,
+    RwBarycentric = 0x01B2,
+    RwPlatformIndependentTextureDictionaryTK = 0x01B3,
+    RwTocTK = 0x01B4,
+    RwTplTK = 0x01B5,
+    RwAltPipeTK = 0x01B6,
+    RwAnimationTK = 0x01B7,
+    RwSkinSplitTK = 0x01B8,
+    RwCompressedKeyTK = 0x01B9,
+    RwGeometryConditioningPLG = 0x01BA,
+    RwWingPLG = 0x01BB,
+    RwGenericPipelineTK = 0x01BC,
+    RwLightmapConversionTK = 0x01BD,
+    RwFilesystemPLG = 0x01BE,
+    RwDictionaryTK = 0x01BF,
+    RwUvAnimationLinear = 0x01C0,
+    RwUvAnimationParameter = 0x01C1,
+
+    // Criterion world plugins.
+    RwBinMeshPLG = 0x050E,
+    RwNativeDataPLG = 0x0510,
 
+    // ZModeler's lock against editing exported models.
+    RwZModelerLock = 0xF21E,
+
+    // Rockstar plugins.
+    RwPipelineSet = 0x0253F2F3,
+    RwTexDictionaryLink = 0x0253F2F5,
+    RwSpecularMaterial = 0x0253F2F6,
+    Rw2dEffect = 0x0253F2F8,
+    RwExtraVertColour = 0x0253F2F9,
+    RwCollisionModel = 0x0253F2FA,
+    RwGtaHAnim = 0x0253F2FB,
     RwReflectionMaterial = 0x0253F2FC,
+    RwBreakable = 0x0253F2FD,
     RwNodeName = 0x0253F2FE,
+}
+
+impl RwSections {
+    /// Returns the name of the section as used by RenderWare tools, such as
+    /// `"Bin Mesh PLG"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            RwSections::RwStruct => "Struct",
+            RwSections::RwString => "String",
+            RwSections::RwExtension => "Extension",
+            RwSections::RwCamera => "Camera",
+            RwSections::RwTexture => "Texture",
+            RwSections::RwMaterial => "Material",
+            RwSections::RwMaterialList => "Material List",
+            RwSections::RwAtomicSector => "Atomic Sector",
+            RwSections::RwPlaneSector => "Plane Sector",
+            RwSections::RwWorld => "World",
+            RwSections::RwSpline => "Spline",
+            RwSections::RwMatrix => "Matrix",
+            RwSections::RwFrameList => "Frame List",
+            RwSections::RwGeometry => "Geometry",
+            RwSections::RwClump => "Clump",
+            RwSections::RwLight => "Light",
+            RwSections::RwUnicodeString => "Unicode String",
+            RwSections::RwAtomic => "Atomic",
+            RwSections::RwTextureNative => "Texture Native",
+            RwSections::RwTextureDictionary => "Texture Dictionary",
+            RwSections::RwAnimationDatabase => "Animation Database",
+            RwSections::RwImage => "Image",
+            RwSections::RwSkinAnimation => "Skin Animation",
+            RwSections::RwGeometryList => "Geometry List",
+            RwSections::RwAnimAnimation => "Anim Animation",
+            RwSections::RwTeam => "Team",
+            RwSections::RwCrowd => "Crowd",
+            RwSections::RwDeltaMorphAnimation => "Delta Morph Animation",
+            RwSections::RwRightToRender => "Right To Render",
+            RwSections::RwMultiTextureEffectNative => "Multi Texture Effect Native",
+            RwSections::RwMultiTextureEffectDictionary => "Multi Texture Effect Dictionary",
+            RwSections::RwTeamDictionary => "Team Dictionary",
+            RwSections::RwPlatformIndependentTextureDictionary => "Platform Independent Texture Dictionary",
+            RwSections::RwTableOfContents => "Table of Contents",
+            RwSections::RwParticleStandardGlobalData => "Particle Standard Global Data",
+            RwSections::RwAltPipe => "AltPipe",
+            RwSections::RwPlatformIndependentPeds => "Platform Independent Peds",
+            RwSections::RwPatchMesh => "Patch Mesh",
+            RwSections::RwChunkGroupStart => "Chunk Group Start",
+            RwSections::RwChunkGroupEnd => "Chunk Group End",
+            RwSections::RwUvAnimationDictionary => "UV Animation Dictionary",
+            RwSections::RwCollTree => "Coll Tree",
+            RwSections::RwMetricsPLG => "Metrics PLG",
+            RwSections::RwSplinePLG => "Spline PLG",
+            RwSections::RwStereoPLG => "Stereo PLG",
+            RwSections::RwVrmlPLG => "VRML PLG",
+            RwSections::RwMorphPLG => "Morph PLG",
+            RwSections::RwPvsPLG => "PVS PLG",
+            RwSections::RwMemoryLeakPLG => "Memory Leak PLG",
+            RwSections::RwAnimationPLG => "Animation PLG",
+            RwSections::RwGlossPLG => "Gloss PLG",
+            RwSections::RwLogoPLG => "Logo PLG",
+            RwSections::RwMemoryInfoPLG => "Memory Info PLG",
+            RwSections::RwRandomPLG => "Random PLG",
+            RwSections::RwPngImagePLG => "PNG Image PLG",
+            RwSections::RwBonePLG => "Bone PLG",
+            RwSections::RwVrmlAnimPLG => "VRML Anim PLG",
+            RwSections::RwSkyMipmapVal => "Sky Mipmap Val",
+            RwSections::RwMrmPLG => "MRM PLG",
+            RwSections::RwLodAtomicPLG => "LOD Atomic PLG",
+            RwSections::RwMePLG => "ME PLG",
+            RwSections::RwLightmapPLG => "Lightmap PLG",
+            RwSections::RwRefinePLG => "Refine PLG",
+            RwSections::RwSkin => "Skin PLG",
+            RwSections::RwLabelPLG => "Label PLG",
+            RwSections::RwParticlesPLG => "Particles PLG",
+            RwSections::RwGeomTxPLG => "GeomTX PLG",
+            RwSections::RwSynthCorePLG => "Synth Core PLG",
+            RwSections::RwStqppPLG => "STQPP PLG",
+            RwSections::RwPartPpPLG => "Part PP PLG",
+            RwSections::RwCollisionPLG => "Collision PLG",
+            RwSections::RwAnim => "HAnim PLG",
+            RwSections::RwUserDataPLG => "User Data PLG",
+            RwSections::RwMaterialEffectsPLG => "Material Effects PLG",
+            RwSections::RwParticleSystemPLG => "Particle System PLG",
+            RwSections::RwDeltaMorphPLG => "Delta Morph PLG",
+            RwSections::RwPatchPLG => "Patch PLG",
+            RwSections::RwTeamPLG => "Team PLG",
+            RwSections::RwCrowdPpPLG => "Crowd PP PLG",
+            RwSections::RwMipSplitPLG => "Mip Split PLG",
+            RwSections::RwAnisotropyPLG => "Anisotropy PLG",
+            RwSections::RwGcnMaterialPLG => "GCN Material PLG",
+            RwSections::RwGeometricPvsPLG => "Geometric PVS PLG",
+            RwSections::RwXboxMaterialPLG => "Xbox Material PLG",
+            RwSections::RwMultiTexturePLG => "Multi Texture PLG",
+            RwSections::RwChainPLG => "Chain PLG",
+            RwSections::RwToonPLG => "Toon PLG",
+            RwSections::RwPTankPLG => "PTank PLG",
+            RwSections::RwParticleStandardPLG => "Particle Standard PLG",
+            RwSections::RwPdsPLG => "PDS PLG",
+            RwSections::RwPrtAdvPLG => "PrtAdv PLG",
+            RwSections::RwNormalMapPLG => "Normal Map PLG",
+            RwSections::RwAdcPLG => "ADC PLG",
+            RwSections::RwUvAnimationPLG => "UV Animation PLG",
+            RwSections::RwCharacterSetPLG => "Character Set PLG",
+            RwSections::RwNohsWorldPLG => "NOHS World PLG",
+            RwSections::RwImportUtilPLG => "Import Util PLG",
+            RwSections::RwSlerpPLG => "Slerp PLG",
+            RwSections::RwOptimPLG => "Optim PLG",
+            RwSections::RwTlWorldPLG => "TL World PLG",
+            RwSections::RwDatabasePLG => "Database PLG",
+            RwSections::RwRaytracePLG => "Raytrace PLG",
+            RwSections::RwRayPLG => "Ray PLG",
+            RwSections::RwLibraryPLG => "Library PLG",
+            RwSections::Rw2dPLG => "2D PLG",
+            RwSections::RwTileRenderPLG => "Tile Render PLG",
+            RwSections::RwJpegImagePLG => "JPEG Image PLG",
+            RwSections::RwTgaImagePLG => "TGA Image PLG",
+            RwSections::RwGifImagePLG => "GIF Image PLG",
+            RwSections::RwQuatPLG => "Quat PLG",
+            RwSections::RwSplinePvsPLG => "Spline PVS PLG",
+            RwSections::RwMipmapPLG => "Mipmap PLG",
+            RwSections::RwMipmapKPLG => "MipmapK PLG",
+            RwSections::Rw2dFont => "2D Font",
+            RwSections::RwIntersectionPLG => "Intersection PLG",
+            RwSections::RwTiffImagePLG => "TIFF Image PLG",
+            RwSections::RwPickPLG => "Pick PLG",
+            RwSections::RwBmpImagePLG => "BMP Image PLG",
+            RwSections::RwRasImagePLG => "RAS Image PLG",
+            RwSections::RwSkinFxPLG => "Skin FX PLG",
+            RwSections::RwVcatPLG => "VCAT PLG",
+            RwSections::Rw2dPath => "2D Path",
+            RwSections::Rw2dBrush => "2D Brush",
+            RwSections::Rw2dObject => "2D Object",
+            RwSections::Rw2dShape => "2D Shape",
+            RwSections::Rw2dScene => "2D Scene",
+            RwSections::Rw2dPickRegion => "2D Pick Region",
+            RwSections::Rw2dObjectString => "2D Object String",
+            RwSections::Rw2dAnimationPLG => "2D Animation PLG",
+            RwSections::Rw2dAnimation => "2D Animation",
+            RwSections::Rw2dKeyframe => "2D Keyframe",
+            RwSections::Rw2dMaestro => "2D Maestro",
+            RwSections::RwBarycentric => "Barycentric",
+            RwSections::RwPlatformIndependentTextureDictionaryTK => "Platform Independent Texture Dictionary TK",
+            RwSections::RwTocTK => "TOC TK",
+            RwSections::RwTplTK => "TPL TK",
+            RwSections::RwAltPipeTK => "AltPipe TK",
+            RwSections::RwAnimationTK => "Animation TK",
+            RwSections::RwSkinSplitTK => "Skin Split TK",
+            RwSections::RwCompressedKeyTK => "Compressed Key TK",
+            RwSections::RwGeometryConditioningPLG => "Geometry Conditioning PLG",
+            RwSections::RwWingPLG => "Wing PLG",
+            RwSections::RwGenericPipelineTK => "Generic Pipeline TK",
+            RwSections::RwLightmapConversionTK => "Lightmap Conversion TK",
+            RwSections::RwFilesystemPLG => "Filesystem PLG",
+            RwSections::RwDictionaryTK => "Dictionary TK",
+            RwSections::RwUvAnimationLinear => "UV Animation Linear",
+            RwSections::RwUvAnimationParameter => "UV Animation Parameter",
+            RwSections::RwBinMeshPLG => "Bin Mesh PLG",
+            RwSections::RwNativeDataPLG => "Native Data PLG",
+            RwSections::RwZModelerLock => "ZModeler Lock",
+            RwSections::RwPipelineSet => "Pipeline Set",
+            RwSections::RwTexDictionaryLink => "Texdictionary Link",
+            RwSections::RwSpecularMaterial => "Specular Material",
+            RwSections::Rw2dEffect => "2d Effect",
+            RwSections::RwExtraVertColour => "Extra Vert Colour",
+            RwSections::RwCollisionModel => "Collision Model",
+            RwSections::RwGtaHAnim => "GTA HAnim",
+            RwSections::RwReflectionMaterial => "Reflection Material",
+            RwSections::RwBreakable => "Breakable",
+            RwSections::RwNodeName => "Frame",
+        }
+    }
+
+    /// Returns the vendor that defined the section.
+    pub fn vendor(self) -> RwVendor {
+        RwVendor::from_section_type(self as u32)
+    }
+}
+
+impl fmt::Display for RwSections {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The vendor of a section, stored in the upper 24 bits of its id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RwVendor {
+    /// The RenderWare core library.
+    Core,
+    /// The Criterion toolkit plugins.
+    CriterionToolkit,
+    RedLineRacer,
+    Cslrd,
+    CriterionInternal,
+    /// The Criterion world plugins, such as the bin mesh.
+    CriterionWorld,
+    Beta,
+    CriterionRm,
+    CriterionRwa,
+    CriterionRwp,
+    /// Rockstar's plugins for the GTA games.
+    Rockstar,
+    /// A vendor without a documented id.
+    Other(u32),
+}
+
+impl RwVendor {
+    /// Returns the vendor of a section id.
+    pub fn from_section_type(section_type: u32) -> Self {
+        match section_type >> 8 {
+            0x00 => RwVendor::Core,
+            0x01 => RwVendor::CriterionToolkit,
+            0x02 => RwVendor::RedLineRacer,
+            0x03 => RwVendor::Cslrd,
+            0x04 => RwVendor::CriterionInternal,
+            0x05 => RwVendor::CriterionWorld,
+            0x06 => RwVendor::Beta,
+            0x07 => RwVendor::CriterionRm,
+            0x08 => RwVendor::CriterionRwa,
+            0x09 => RwVendor::CriterionRwp,
+            0x0253F2 => RwVendor::Rockstar,
+            vendor => RwVendor::Other(vendor),
+        }
+    }
+}
+
+/// A section id, looked up in `RwSections` and the vendor ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RwSectionId {
+    /// A section listed in `RwSections`.
+    Known(RwSections),
+    /// An unlisted plugin of a documented vendor.
+    Plugin { vendor: RwVendor, id: u32 },
+    /// An id outside every documented vendor range.
+    Unknown(u32),
+}
+
+impl RwSectionId {
+    pub fn from_u32(section_type: u32) -> Self {
+        if let Some(section) = RwSections::from_u32(section_type) {
+            return RwSectionId::Known(section);
+        }
+        match RwVendor::from_section_type(section_type) {
+            RwVendor::Other(_) => RwSectionId::Unknown(section_type),
+            vendor => RwSectionId::Plugin { vendor, id: section_type & 0xFF },
+        }
+    }
+
+    /// Returns the raw section id.
+    pub fn value(self) -> u32 {
+        match self {
+            RwSectionId::Known(section) => section as u32,
+            RwSectionId::Plugin { vendor, id } => vendor_id(vendor) << 8 | id,
+            RwSectionId::Unknown(section_type) => section_type,
+        }
+    }
+}
+
+fn vendor_id(vendor: RwVendor) -> u32 {
+    match vendor {
+        RwVendor::Core => 0x00,
+        RwVendor::CriterionToolkit => 0x01,
+        RwVendor::RedLineRacer => 0x02,
+        RwVendor::Cslrd => 0x03,
+        RwVendor::CriterionInternal => 0x04,
+        RwVendor::CriterionWorld => 0x05,
+        RwVendor::Beta => 0x06,
+        RwVendor::CriterionRm => 0x07,
+        RwVendor::CriterionRwa => 0x08,
+        RwVendor::CriterionRwp => 0x09,
+        RwVendor::Rockstar => 0x0253F2,
+        RwVendor::Other(vendor) => vendor,
+    }
+}
+
+impl fmt::Display for RwSectionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RwSectionId::Known(section) => write!(f, "{} ({:#x})", section.name(), self.value()),
+            RwSectionId::Plugin { vendor, .. } => write!(f, "{:?} plugin ({:#x})", vendor, self.value()),
+            RwSectionId::Unknown(section_type) => write!(f, "unknown section ({:#x})", section_type),
+        }
+    }
 }
\ No newline at end of file
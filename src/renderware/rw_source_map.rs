@@ -0,0 +1,114 @@
+//! # Source Maps
+//!
+//! The byte range each parsed structure was read from, for reverse
+//! engineering and for tools that hex-edit or binary-patch a file in place.
+//! They are returned by the `parse_with_source_map` method of the DFF, TXD
+//! and IFP parsers.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::rw_source_map::SourceItem;
+//!
+//! let buffer = std::fs::read("path/to/your/model.dff").unwrap();
+//! let (dff, source_map) = DffParser::new(&buffer).parse_with_source_map().unwrap();
+//! if let Some(span) = source_map.get(SourceItem::Material { geometry: 0, material: 0 }) {
+//!     println!("The first material is at {:#x}, {} bytes", span.offset, span.size);
+//! }
+//! ```
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+/// A byte range of the parsed data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SourceSpan {
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl SourceSpan {
+    /// Creates the span between two offsets.
+    pub fn between(start: u64, end: u64) -> Self {
+        SourceSpan {
+            offset: start,
+            size: end.saturating_sub(start),
+        }
+    }
+
+    pub fn end(&self) -> u64 {
+        self.offset + self.size
+    }
+
+    /// Returns the span as a range to index the parsed buffer with.
+    pub fn range(&self) -> Range<usize> {
+        self.offset as usize..self.end() as usize
+    }
+}
+
+/// A parsed structure, identified by its indices in the parsed file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum SourceItem {
+    /// A frame of the frame list, without its extension.
+    Frame(usize),
+    /// A geometry section, header included.
+    Geometry(usize),
+    /// A material section of a geometry, header included.
+    Material { geometry: usize, material: usize },
+    /// The texture section of a material, header included.
+    Texture { geometry: usize, material: usize },
+    /// An atomic section, header included, in the order of the file.
+    Atomic(usize),
+    /// A texture native section, header included.
+    TextureNative(usize),
+    /// An animation of an IFP package, from its name to its last keyframe.
+    Animation(usize),
+    /// A bone of an animation, from its name to its last keyframe.
+    Bone { animation: usize, bone: usize },
+}
+
+/// A structure and the span it was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SourceEntry {
+    pub item: SourceItem,
+    pub span: SourceSpan,
+}
+
+/// The spans of the structures of a parsed file, in the order they were read.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SourceMap {
+    pub entries: Vec<SourceEntry>,
+}
+
+impl SourceMap {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the span of `item`, if it was read.
+    pub fn get(&self, item: SourceItem) -> Option<SourceSpan> {
+        self.entries.iter().find(|entry| entry.item == item).map(|entry| entry.span)
+    }
+
+    /// Returns the innermost structure containing `offset`, to find what a
+    /// byte of the file belongs to.
+    pub fn item_at(&self, offset: u64) -> Option<&SourceEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.span.offset <= offset && offset < entry.span.end())
+            .min_by_key(|entry| entry.span.size)
+    }
+
+    pub(crate) fn record(&mut self, item: SourceItem, start: u64, end: u64) {
+        self.entries.push(SourceEntry {
+            item,
+            span: SourceSpan::between(start, end),
+        });
+    }
+}
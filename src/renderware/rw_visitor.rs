@@ -0,0 +1,248 @@
+//! # Section Visitor
+//!
+//! A callback interface over the sections of a RenderWare binary stream,
+//! for extracting a few values from many files without building the full
+//! parsed structures.
+//!
+//! ## Features
+//!
+//! - Walks every section depth-first, descending into containers detected
+//!   the same way as `RwChunkTree`.
+//! - Reads nested headers with the byte order of the `RwFile`.
+//! - Hands the payload of leaf sections to the visitor as a slice of the
+//!   input buffer, without copying it.
+//! - Lets the visitor skip the contents of a section or stop the walk.
+//! - Rejects sections nested deeper than `MAX_SECTION_DEPTH`, so crafted
+//!   files cannot exhaust the stack.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::rw_file::RwFile;
+//! use rw_parser_rs::renderware::rw_sections::RwSections;
+//! use rw_parser_rs::renderware::rw_visitor::{SectionContext, SectionVisitor, VisitAction};
+//! use std::fs;
+//!
+//! /// Collects the names of the textures referenced by materials.
+//! #[derive(Default)]
+//! struct TextureNames {
+//!     parents: Vec<u32>,
+//!     names: Vec<String>,
+//! }
+//!
+//! impl SectionVisitor for TextureNames {
+//!     fn enter_section(&mut self, section: &SectionContext) -> VisitAction {
+//!         self.parents.push(section.section_type);
+//!         VisitAction::Descend
+//!     }
+//!
+//!     fn on_payload(&mut self, section: &SectionContext, payload: &[u8]) {
+//!         let parent = self.parents.iter().rev().nth(1).copied();
+//!         if section.section_type == RwSections::RwString as u32 && parent == Some(RwSections::RwTexture as u32) {
+//!             let end = payload.iter().position(|&byte| byte == 0).unwrap_or(payload.len());
+//!             if end > 0 {
+//!                 self.names.push(String::from_utf8_lossy(&payload[..end]).to_string());
+//!             }
+//!         }
+//!     }
+//!
+//!     fn leave_section(&mut self, _section: &SectionContext) {
+//!         self.parents.pop();
+//!     }
+//! }
+//!
+//! let file_data = fs::read("path/to/your/model.dff").unwrap();
+//! let mut visitor = TextureNames::default();
+//! RwFile::new(&file_data).visit(&mut visitor).unwrap();
+//! println!("{:?}", visitor.names);
+//! ```
+
+use crate::renderware::rw_chunk_tree::RW_SECTION_HEADER_SIZE;
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_file::RwFile;
+use crate::renderware::rw_sections::RwSections;
+use crate::utils::byte_stream::Endianness;
+use crate::utils::rw_version::unpack_version;
+
+/// The deepest nesting the walk accepts. Real files nest fewer than ten
+/// levels deep, while a buffer of bare headers nested in each other could
+/// otherwise recurse once per 12 bytes.
+pub const MAX_SECTION_DEPTH: usize = 64;
+
+/// The header and position of a visited section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionContext {
+    pub section_type: u32,
+    /// The size of the payload, excluding the header.
+    pub size: u32,
+    /// The packed library id, as stored in the header.
+    pub version_number: u32,
+    /// The offset of the section header from the start of the buffer.
+    pub offset: u64,
+    /// The nesting depth, zero for top-level sections.
+    pub depth: usize,
+}
+
+impl SectionContext {
+    /// The offset of the payload from the start of the buffer.
+    pub fn data_offset(&self) -> u64 {
+        self.offset + RW_SECTION_HEADER_SIZE
+    }
+
+    /// The offset of the end of the section from the start of the buffer.
+    pub fn end_offset(&self) -> u64 {
+        self.data_offset() + self.size as u64
+    }
+}
+
+/// What the walk does after a section has been entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitAction {
+    /// Visits the children or payload of the section.
+    Descend,
+    /// Moves on to the next sibling without visiting the contents of the
+    /// section or calling `leave_section` for it.
+    Skip,
+    /// Ends the walk.
+    Stop,
+}
+
+/// Callbacks for the sections of a RenderWare binary stream, driven by
+/// `RwFile::visit`. Every method has an empty default.
+pub trait SectionVisitor {
+    /// Called when a section starts, before its contents.
+    fn enter_section(&mut self, _section: &SectionContext) -> VisitAction {
+        VisitAction::Descend
+    }
+
+    /// Called with the payload of sections that have no child sections.
+    fn on_payload(&mut self, _section: &SectionContext, _payload: &[u8]) {}
+
+    /// Called after the contents of a section entered with
+    /// `VisitAction::Descend`.
+    fn leave_section(&mut self, _section: &SectionContext) {}
+}
+
+impl<'a> RwFile<'a> {
+    /// Walks the sections from the current position to the end of the
+    /// buffer, reading every header with the byte order of the stream.
+    /// Trailing bytes too short to hold a section header, such as padding,
+    /// are ignored.
+    ///
+    /// # Returns
+    ///
+    /// A `RwParseError` if a top-level section extends past the end of the
+    /// buffer, or if sections are nested deeper than `MAX_SECTION_DEPTH`.
+    pub fn visit<V: SectionVisitor + ?Sized>(&mut self, visitor: &mut V) -> RwResult<()> {
+        let buffer = self.get_stream().buffer();
+        let endianness = self.get_stream().endianness();
+        let end = buffer.len() as u64;
+
+        while end.saturating_sub(self.get_stream().get_position()) >= RW_SECTION_HEADER_SIZE {
+            let offset = self.get_stream().get_position();
+            let header = self.read_section_header()?;
+            if header.section_type == 0 && header.section_size == 0 {
+                break;
+            }
+
+            let section = SectionContext {
+                section_type: header.section_type,
+                size: header.section_size,
+                version_number: header.version_number,
+                offset,
+                depth: 0,
+            };
+            if section.end_offset() > end {
+                return Err(RwParseError::TruncatedSection {
+                    section_type: section.section_type,
+                    offset,
+                    size: section.end_offset() - offset,
+                    available: end - offset,
+                });
+            }
+
+            self.get_stream().set_position(section.end_offset());
+            if !visit_section(buffer, endianness, &section, visitor)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Visits a section and its contents. Returns `false` if the visitor
+/// stopped the walk.
+fn visit_section<V: SectionVisitor + ?Sized>(
+    buffer: &[u8],
+    endianness: Endianness,
+    section: &SectionContext,
+    visitor: &mut V,
+) -> RwResult<bool> {
+    if section.depth > MAX_SECTION_DEPTH {
+        return Err(RwParseError::InconsistentData {
+            offset: section.offset,
+            message: format!("Section {:#x} is nested deeper than {} levels", section.section_type, MAX_SECTION_DEPTH),
+        });
+    }
+
+    match visitor.enter_section(section) {
+        VisitAction::Descend => {}
+        VisitAction::Skip => return Ok(true),
+        VisitAction::Stop => return Ok(false),
+    }
+
+    let payload = &buffer[section.data_offset() as usize..section.end_offset() as usize];
+    match child_sections(section, payload, endianness) {
+        Some(children) => {
+            for child in &children {
+                if !visit_section(buffer, endianness, child, visitor)? {
+                    return Ok(false);
+                }
+            }
+        }
+        None => visitor.on_payload(section, payload),
+    }
+
+    visitor.leave_section(section);
+    Ok(true)
+}
+
+/// Returns the child sections of a section if its payload is entirely made
+/// of valid sections. Struct and string payloads are always raw data.
+fn child_sections(section: &SectionContext, payload: &[u8], endianness: Endianness) -> Option<Vec<SectionContext>> {
+    let is_raw = section.section_type == RwSections::RwStruct as u32 || section.section_type == RwSections::RwString as u32;
+    if is_raw || payload.is_empty() {
+        return None;
+    }
+
+    let mut children = Vec::new();
+    let mut position = 0;
+    while position < payload.len() {
+        let header = payload.get(position..position + RW_SECTION_HEADER_SIZE as usize)?;
+        let field = |index: usize| endianness.u32_from_bytes(header[index * 4..index * 4 + 4].try_into().unwrap());
+        let (section_type, size, version_number) = (field(0), field(1), field(2));
+
+        let child_end = position + RW_SECTION_HEADER_SIZE as usize + size as usize;
+        if child_end > payload.len() || !is_plausible_version(version_number) {
+            return None;
+        }
+
+        children.push(SectionContext {
+            section_type,
+            size,
+            version_number,
+            offset: section.data_offset() + position as u64,
+            depth: section.depth + 1,
+        });
+        position = child_end;
+    }
+
+    Some(children)
+}
+
+/// Checks that a header version is a RenderWare 3 library id, either packed
+/// or in the pre-3.1 raw form.
+fn is_plausible_version(version_number: u32) -> bool {
+    (0x30000..0x40000).contains(&unpack_version(version_number)) || (0x300..0x400).contains(&version_number)
+}
@@ -1 +1,13 @@
-pub mod txd_parser;
\ No newline at end of file
+pub mod txd_parser;
+pub mod swizzle;
+#[cfg(feature = "fast-dxt")]
+pub mod dxt;
+pub mod txd_writer;
+pub mod txd_dds;
+pub mod txd_merge;
+pub mod txd_edit;
+pub mod txd_atlas;
+pub mod txd_audit;
+pub mod txd_stats;
+#[cfg(feature = "image")]
+pub mod txd_image;
\ No newline at end of file
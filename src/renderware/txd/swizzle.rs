@@ -0,0 +1,213 @@
+//! # Console Swizzling
+//!
+//! Standalone conversions between the linear raster layout and the swizzled
+//! layouts used by console texture natives. The parser applies these
+//! automatically; they are exposed for tools that work with raw raster data
+//! directly.
+//!
+//! - **PS2**: 8-bit and 4-bit palette indices stored in GS memory order, and
+//!   the CSM1 palette arrangement.
+//! - **Xbox**: Morton (Z-order) swizzling over whole pixels.
+//! - **PSP**: 16-byte by 8-row block tiling.
+//!
+//! ## Example
+//!
+//! ```
+//! use rw_parser_rs::renderware::txd::swizzle;
+//!
+//! let linear: Vec<u8> = (0..=255).collect();
+//! let swizzled = swizzle::swizzle_xbox(&linear, 16, 16, 1);
+//! assert_eq!(swizzle::unswizzle_xbox(&swizzled, 16, 16, 1), linear);
+//! ```
+
+/// Maps linear PS2 8-bit texel coordinates to their swizzled byte offset.
+fn ps2_8bit_offset(x: usize, y: usize, width: usize) -> usize {
+    let block_location = (y & !0x0F) * width + (x & !0x0F) * 2;
+    let swap_selector = (((y + 2) >> 2) & 0x01) * 4;
+    let pos_y = (((y & !3) >> 1) + (y & 1)) & 0x07;
+    let column_location = pos_y * width * 2 + ((x + swap_selector) & 0x07) * 4;
+    let byte_number = ((y >> 1) & 1) + ((x >> 2) & 2);
+    block_location + column_location + byte_number
+}
+
+/// Maps linear PS2 4-bit texel coordinates to their swizzled byte offset and
+/// whether the texel is stored in the high nibble.
+fn ps2_4bit_offset(x: usize, y: usize, width: usize, height: usize) -> (usize, bool) {
+    let pages_horizontal = width.div_ceil(128);
+    let pages_vertical = height.div_ceil(128);
+    let page_number = (y / 128) * pages_horizontal + x / 128;
+    let page_location = (page_number / pages_vertical) * 32 * height * 2 + (page_number % pages_vertical) * 64 * 4;
+    let (local_x, local_y) = (x & 0x7F, y & 0x7F);
+    let block_location = ((local_x & !0x1F) >> 1) * height + (local_y & !0x0F) * 2;
+    let swap_selector = (((y + 2) >> 2) & 0x01) * 4;
+    let pos_y = (((y & !3) >> 1) + (y & 1)) & 0x07;
+    let column_location = pos_y * height * 2 + ((x + swap_selector) & 0x07) * 4;
+    let byte_number = (x >> 3) & 3;
+    (page_location + block_location + column_location + byte_number, (y >> 1) & 1 != 0)
+}
+
+/// Converts swizzled PS2 8-bit palette indices to linear order.
+///
+/// Returns one index per texel. Texels outside `data` read as zero.
+pub fn unswizzle_ps2_8bit(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut indices = vec![0; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            indices[y * width + x] = data.get(ps2_8bit_offset(x, y, width)).copied().unwrap_or(0);
+        }
+    }
+    indices
+}
+
+/// Converts linear 8-bit palette indices to the swizzled PS2 layout.
+pub fn swizzle_ps2_8bit(indices: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut data = vec![0; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(byte) = data.get_mut(ps2_8bit_offset(x, y, width)) {
+                *byte = indices.get(y * width + x).copied().unwrap_or(0);
+            }
+        }
+    }
+    data
+}
+
+/// Converts swizzled PS2 4-bit palette indices to linear order.
+///
+/// Returns one index per texel, each in the range `0..16`.
+pub fn unswizzle_ps2_4bit(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut indices = vec![0; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let (offset, high) = ps2_4bit_offset(x, y, width, height);
+            let byte = data.get(offset).copied().unwrap_or(0);
+            indices[y * width + x] = if high { byte >> 4 } else { byte & 0x0F };
+        }
+    }
+    indices
+}
+
+/// Converts linear 4-bit palette indices, one per byte, to the swizzled PS2
+/// layout with two texels per byte.
+///
+/// The layout is only a one-to-one mapping for rasters at least 32 texels
+/// wide and 16 texels high; the game stores smaller 4-bit rasters unswizzled.
+pub fn swizzle_ps2_4bit(indices: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut data = vec![0; (width * height).div_ceil(2)];
+    for y in 0..height {
+        for x in 0..width {
+            let (offset, high) = ps2_4bit_offset(x, y, width, height);
+            let index = indices.get(y * width + x).copied().unwrap_or(0) & 0x0F;
+            if let Some(byte) = data.get_mut(offset) {
+                *byte = if high { (*byte & 0x0F) | index << 4 } else { (*byte & 0xF0) | index };
+            }
+        }
+    }
+    data
+}
+
+/// Reorders a 256-entry CSM1 palette of 4-byte colors into linear order.
+///
+/// The reordering swaps entries in groups of eight and is its own inverse,
+/// so the same function converts a linear palette back to CSM1.
+pub fn unclut_palette(palette: &[u8]) -> Vec<u8> {
+    let mut linear = vec![0; palette.len()];
+    for i in 0..palette.len() / 4 {
+        let swapped = (i & !0x18) | ((i & 0x08) << 1) | ((i & 0x10) >> 1);
+        linear[i * 4..i * 4 + 4].copy_from_slice(&palette[swapped * 4..swapped * 4 + 4]);
+    }
+    linear
+}
+
+/// Maps linear texel coordinates to their texel offset in an Xbox swizzled
+/// (Morton order) raster.
+pub fn xbox_swizzled_offset(x: usize, y: usize, width: usize, height: usize) -> usize {
+    let mut offset = 0;
+    let mut bit = 1;
+    let mut mask = 1;
+    while mask < width || mask < height {
+        if mask < width {
+            if x & mask != 0 {
+                offset |= bit;
+            }
+            bit <<= 1;
+        }
+        if mask < height {
+            if y & mask != 0 {
+                offset |= bit;
+            }
+            bit <<= 1;
+        }
+        mask <<= 1;
+    }
+    offset
+}
+
+/// Converts an Xbox swizzled raster with `bytes_per_pixel`-sized texels to
+/// linear order.
+pub fn unswizzle_xbox(data: &[u8], width: usize, height: usize, bytes_per_pixel: usize) -> Vec<u8> {
+    let mut linear = vec![0; width * height * bytes_per_pixel];
+    for y in 0..height {
+        for x in 0..width {
+            let source = xbox_swizzled_offset(x, y, width, height) * bytes_per_pixel;
+            let target = (y * width + x) * bytes_per_pixel;
+            if let Some(pixel) = data.get(source..source + bytes_per_pixel) {
+                linear[target..target + bytes_per_pixel].copy_from_slice(pixel);
+            }
+        }
+    }
+    linear
+}
+
+/// Converts a linear raster with `bytes_per_pixel`-sized texels to the Xbox
+/// swizzled layout.
+pub fn swizzle_xbox(data: &[u8], width: usize, height: usize, bytes_per_pixel: usize) -> Vec<u8> {
+    let mut swizzled = vec![0; width * height * bytes_per_pixel];
+    for y in 0..height {
+        for x in 0..width {
+            let source = (y * width + x) * bytes_per_pixel;
+            let target = xbox_swizzled_offset(x, y, width, height) * bytes_per_pixel;
+            if let (Some(pixel), Some(slot)) =
+                (data.get(source..source + bytes_per_pixel), swizzled.get_mut(target..target + bytes_per_pixel))
+            {
+                slot.copy_from_slice(pixel);
+            }
+        }
+    }
+    swizzled
+}
+
+/// Maps a linear byte position to its offset in a PSP swizzled raster made
+/// of 16-byte by 8-row blocks.
+fn psp_offset(column: usize, row: usize, row_bytes: usize) -> usize {
+    let blocks_per_row = row_bytes.div_ceil(16);
+    let block = (row / 8) * blocks_per_row + column / 16;
+    block * 128 + (row % 8) * 16 + column % 16
+}
+
+/// Converts a PSP swizzled raster to linear order. `depth` is the number of
+/// bits per texel.
+pub fn unswizzle_psp(data: &[u8], width: usize, height: usize, depth: usize) -> Vec<u8> {
+    let row_bytes = (width * depth).div_ceil(8);
+    let mut linear = vec![0; row_bytes * height];
+    for row in 0..height {
+        for column in 0..row_bytes {
+            linear[row * row_bytes + column] = data.get(psp_offset(column, row, row_bytes)).copied().unwrap_or(0);
+        }
+    }
+    linear
+}
+
+/// Converts a linear raster to the PSP swizzled layout. `depth` is the
+/// number of bits per texel.
+pub fn swizzle_psp(data: &[u8], width: usize, height: usize, depth: usize) -> Vec<u8> {
+    let row_bytes = (width * depth).div_ceil(8);
+    let blocks_per_row = row_bytes.div_ceil(16);
+    let mut swizzled = vec![0; blocks_per_row * 128 * height.div_ceil(8)];
+    for row in 0..height {
+        for column in 0..row_bytes {
+            swizzled[psp_offset(column, row, row_bytes)] = data.get(row * row_bytes + column).copied().unwrap_or(0);
+        }
+    }
+    swizzled
+}
@@ -0,0 +1,377 @@
+//! # Texture Atlases
+//!
+//! Packs the textures used by a model into a single texture and rewrites the
+//! model's texture coordinates to match, so that engines and web viewers can
+//! draw it with far fewer material switches.
+//!
+//! ## Features
+//!
+//! - Shelf packing into the smallest power-of-two atlas that fits, up to a
+//!   configurable size.
+//! - Padding around each texture filled with its edge pixels, so filtering
+//!   does not bleed neighbouring textures in.
+//! - UV remapping of the first texture channel, with vertices split where
+//!   textures of the same vertex end up in different regions.
+//! - Materials that become identical once they share the atlas are merged.
+//!
+//! The atlas can only hold one copy of each texture, so texture coordinates
+//! outside of `0..1`, which would tile the texture, are clamped to its edge.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::renderware::txd::txd_atlas::AtlasPacker;
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let mut dff = DffParser::new(&fs::read("path/to/lamppost.dff").unwrap()).parse().unwrap();
+//! let mut txd = TxdParser::new(&fs::read("path/to/lamppost.txd").unwrap()).parse().unwrap();
+//!
+//! let atlas = AtlasPacker::new().with_texture_name("lamppost_atlas").pack_model(&mut dff, &txd).unwrap();
+//! println!("{}x{} atlas with {} textures", atlas.texture.width, atlas.texture.height, atlas.regions.len());
+//! txd.texture_dictionary.texture_natives.push(atlas.texture);
+//! txd.texture_dictionary.rebuild_index();
+//! ```
+
+use super::txd_parser::{RwRasterEncoding, RwRasterFormatInfo, RwTextureNative, RwTxd};
+use crate::renderware::common::types::RwTextureCoordinate;
+use crate::renderware::dff::dff_parser::{RwDff, RwGeometry, RwMaterial};
+use crate::renderware::utils::image_format_enums::{D3dFormat, PlatformType, RasterFormat};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
+
+use serde::Serialize;
+
+/// The texture addressing mode that clamps coordinates to the edge.
+const ADDRESS_CLAMP: u8 = 3;
+/// The linear texture filtering mode.
+const FILTER_LINEAR: u8 = 2;
+
+/// The width and height of an atlas and the top-left corner of each
+/// rectangle in it.
+type Layout = (u32, u32, Vec<(u32, u32)>);
+
+/// Packs textures into atlases.
+pub struct AtlasPacker {
+    max_size: u32,
+    padding: u32,
+    texture_name: String,
+}
+
+/// Where a texture was placed in an atlas, in pixels, without padding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AtlasRegion {
+    pub texture_name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A packed atlas texture and the region of each texture in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureAtlas {
+    /// The atlas, as an uncompressed 32-bit D3D9 texture native.
+    pub texture: RwTextureNative,
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl Default for AtlasPacker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AtlasPacker {
+    /// Creates a packer for atlases of at most 2048x2048 pixels, with 2
+    /// pixels of padding, named `atlas`.
+    pub fn new() -> Self {
+        AtlasPacker {
+            max_size: 2048,
+            padding: 2,
+            texture_name: "atlas".to_string(),
+        }
+    }
+
+    /// Sets the largest width and height of the atlas, at most 4096.
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size.clamp(1, 4096);
+        self
+    }
+
+    /// Sets the number of pixels of padding around each texture.
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the texture name of the atlas, which is at most 31 characters
+    /// long in a TXD file.
+    pub fn with_texture_name(mut self, texture_name: &str) -> Self {
+        self.texture_name = texture_name.to_string();
+        self
+    }
+
+    /// Packs textures into an atlas. Textures with the same name, ignoring
+    /// case, are packed once.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the atlas or an `std::io::Error` if a texture
+    /// cannot be decoded or the textures do not fit in the largest atlas.
+    pub fn pack(&self, textures: &[&RwTextureNative]) -> Result<TextureAtlas> {
+        let mut sources: Vec<(&RwTextureNative, Vec<u8>)> = Vec::new();
+        for &texture in textures {
+            if sources.iter().any(|(known, _)| known.texture_name.eq_ignore_ascii_case(&texture.texture_name)) {
+                continue;
+            }
+            let (width, height) = (texture.width as usize, texture.height as usize);
+            let rgba = match texture.mipmaps.first().filter(|bitmap| bitmap.len() == width * height * 4) {
+                Some(bitmap) => bitmap.clone(),
+                None => texture.decode()?,
+            };
+            if width == 0 || height == 0 || rgba.len() != width * height * 4 {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!("Texture '{}' cannot be decoded", texture.texture_name),
+                ));
+            }
+            sources.push((texture, rgba));
+        }
+
+        let sizes: Vec<(u32, u32)> = sources
+            .iter()
+            .map(|(texture, _)| (texture.width as u32 + self.padding * 2, texture.height as u32 + self.padding * 2))
+            .collect();
+        let (width, height, positions) = self.layout(&sizes).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} textures do not fit in a {}x{} atlas", sources.len(), self.max_size, self.max_size),
+            )
+        })?;
+
+        let mut rgba = vec![0u8; width as usize * height as usize * 4];
+        let mut regions = Vec::with_capacity(sources.len());
+        for ((texture, pixels), (x, y)) in sources.iter().zip(positions) {
+            let (source_width, source_height) = (texture.width as i64, texture.height as i64);
+            let padding = self.padding as i64;
+            // Copy the texture with its padding, repeating the edge pixels
+            for row in -padding..source_height + padding {
+                let source_row = row.clamp(0, source_height - 1);
+                for column in -padding..source_width + padding {
+                    let source_column = column.clamp(0, source_width - 1);
+                    let source = ((source_row * source_width + source_column) * 4) as usize;
+                    let target_x = (x as i64 + padding + column) as usize;
+                    let target_y = (y as i64 + padding + row) as usize;
+                    let target = (target_y * width as usize + target_x) * 4;
+                    rgba[target..target + 4].copy_from_slice(&pixels[source..source + 4]);
+                }
+            }
+            regions.push(AtlasRegion {
+                texture_name: texture.texture_name.clone(),
+                x: x + self.padding,
+                y: y + self.padding,
+                width: texture.width as u32,
+                height: texture.height as u32,
+            });
+        }
+
+        let mut texture = self.atlas_texture();
+        texture.replace_pixels(width as u16, height as u16, &rgba)?;
+        Ok(TextureAtlas { texture, regions })
+    }
+
+    /// Packs the textures a model uses from a texture dictionary and rewrites
+    /// the model to use the atlas with `TextureAtlas::apply`. Textures missing
+    /// from the dictionary are left out and keep their materials.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the atlas or an `std::io::Error` if packing
+    /// fails, in which case the model is unchanged.
+    pub fn pack_model(&self, dff: &mut RwDff, txd: &RwTxd) -> Result<TextureAtlas> {
+        let geometries = dff.geometry_list.as_ref().map_or(&[][..], |list| &list.geometries[..]);
+        let textures: Vec<&RwTextureNative> = geometries
+            .iter()
+            .flat_map(|geometry| &geometry.material_list.material_data)
+            .filter_map(RwMaterial::texture_name)
+            .filter_map(|name| txd.texture_dictionary.get_by_name(name))
+            .collect();
+        let atlas = self.pack(&textures)?;
+        atlas.apply(dff);
+        Ok(atlas)
+    }
+
+    /// Finds the smallest power-of-two atlas holding rectangles of `sizes`,
+    /// returning its size and the top-left corner of each rectangle.
+    fn layout(&self, sizes: &[(u32, u32)]) -> Option<Layout> {
+        let mut order: Vec<usize> = (0..sizes.len()).collect();
+        order.sort_by_key(|&index| (std::cmp::Reverse(sizes[index].1), std::cmp::Reverse(sizes[index].0)));
+
+        let widest = sizes.iter().map(|size| size.0).max().unwrap_or(1);
+        let mut best: Option<Layout> = None;
+        let mut width = widest.next_power_of_two();
+        while width <= self.max_size {
+            let mut positions = vec![(0, 0); sizes.len()];
+            let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+            for &index in &order {
+                let (item_width, item_height) = sizes[index];
+                if x + item_width > width {
+                    y += shelf_height;
+                    x = 0;
+                    shelf_height = 0;
+                }
+                positions[index] = (x, y);
+                x += item_width;
+                shelf_height = shelf_height.max(item_height);
+            }
+            let height = (y + shelf_height).max(1).next_power_of_two();
+            let area = width as u64 * height as u64;
+            let smaller = best.as_ref().is_none_or(|(w, h, _)| area < *w as u64 * *h as u64);
+            if height <= self.max_size && smaller {
+                best = Some((width, height, positions));
+            }
+            width *= 2;
+        }
+        best
+    }
+
+    fn atlas_texture(&self) -> RwTextureNative {
+        let raster_format = (RasterFormat::Raster8888 as u32) << 8;
+        let d3d_format = D3dFormat::D3dfmtA8r8g8b8;
+        let d3d_format_bytes = (d3d_format as u32).to_le_bytes();
+        let d3d_format_length = d3d_format_bytes.iter().position(|&c| c == 0).unwrap_or(4);
+        RwTextureNative {
+            platform_id: PlatformType::D3d9 as u32,
+            filter_mode: FILTER_LINEAR,
+            u_addressing: ADDRESS_CLAMP,
+            v_addressing: ADDRESS_CLAMP,
+            texture_name: self.texture_name.clone(),
+            raster_format,
+            d3d_format: String::from_utf8_lossy(&d3d_format_bytes[..d3d_format_length]).to_string(),
+            depth: 32,
+            raster_type: 4,
+            raster_encoding: RwRasterEncoding::Uncompressed,
+            raster_format_info: RwRasterFormatInfo::from_raster_format(raster_format),
+            d3d_format_type: Some(d3d_format),
+            ..RwTextureNative::default()
+        }
+    }
+}
+
+impl TextureAtlas {
+    /// Returns the region of a texture, ignoring case.
+    pub fn region(&self, texture_name: &str) -> Option<&AtlasRegion> {
+        self.regions.iter().find(|region| region.texture_name.eq_ignore_ascii_case(texture_name))
+    }
+
+    /// Maps a texture coordinate of a texture into the atlas, clamping it to
+    /// the texture's region.
+    pub fn map_uv(&self, region: &AtlasRegion, uv: RwTextureCoordinate) -> RwTextureCoordinate {
+        let (width, height) = (self.texture.width.max(1) as f32, self.texture.height.max(1) as f32);
+        RwTextureCoordinate {
+            u: (region.x as f32 + uv.u.clamp(0.0, 1.0) * region.width as f32) / width,
+            v: (region.y as f32 + uv.v.clamp(0.0, 1.0) * region.height as f32) / height,
+        }
+    }
+
+    /// Rewrites every geometry of a model whose materials use textures of
+    /// the atlas: their first texture channel is mapped into the atlas and
+    /// their materials are switched to the atlas texture. Vertices shared by
+    /// textures of different regions are split, materials that become
+    /// identical are merged and the bin meshes are rebuilt.
+    pub fn apply(&self, dff: &mut RwDff) {
+        if let Some(geometry_list) = &mut dff.geometry_list {
+            for geometry in &mut geometry_list.geometries {
+                self.apply_geometry(geometry);
+            }
+        }
+    }
+
+    fn apply_geometry(&self, geometry: &mut RwGeometry) {
+        let material_regions: Vec<Option<usize>> = geometry
+            .material_list
+            .material_data
+            .iter()
+            .map(|material| {
+                let name = material.texture_name()?;
+                self.regions.iter().position(|region| region.texture_name.eq_ignore_ascii_case(name))
+            })
+            .collect();
+        if material_regions.iter().all(Option::is_none) || geometry.texture_mapping_information.is_empty() {
+            return;
+        }
+
+        // Give each vertex one region, duplicating it for every other
+        // region it is used with
+        let vertex_count = geometry.vertex_count();
+        let mut vertex_regions: Vec<Option<Option<usize>>> = vec![None; vertex_count];
+        let mut copies: HashMap<(usize, Option<usize>), usize> = HashMap::new();
+        for triangle_index in 0..geometry.triangle_information.len() {
+            let triangle = geometry.triangle_information[triangle_index];
+            let region = material_regions.get(triangle.material_id as usize).copied().flatten();
+            let mut corners = [triangle.vector.x as usize, triangle.vector.y as usize, triangle.vector.z as usize];
+            for corner in &mut corners {
+                if *corner >= vertex_count {
+                    continue;
+                }
+                match vertex_regions[*corner] {
+                    None => vertex_regions[*corner] = Some(region),
+                    Some(known) if known == region => {}
+                    Some(_) => {
+                        let original = *corner;
+                        *corner = *copies.entry((original, region)).or_insert_with(|| geometry.duplicate_vertex(original));
+                        if *corner >= vertex_regions.len() {
+                            vertex_regions.push(Some(region));
+                        }
+                    }
+                }
+            }
+            let vector = &mut geometry.triangle_information[triangle_index].vector;
+            (vector.x, vector.y, vector.z) = (corners[0] as f32, corners[1] as f32, corners[2] as f32);
+        }
+
+        let uvs = &mut geometry.texture_mapping_information[0];
+        for (uv, region) in uvs.iter_mut().zip(&vertex_regions) {
+            if let Some(Some(region)) = region {
+                *uv = self.map_uv(&self.regions[*region], *uv);
+            }
+        }
+
+        for (material, region) in geometry.material_list.material_data.iter_mut().zip(&material_regions) {
+            if let (Some(texture), Some(_)) = (&mut material.texture, region) {
+                texture.texture_name = self.texture.texture_name.clone();
+                texture.u_addressing = ADDRESS_CLAMP;
+                texture.v_addressing = ADDRESS_CLAMP;
+            }
+        }
+        merge_materials(geometry);
+        geometry.rebuild_bin_mesh();
+    }
+}
+
+/// Removes duplicate materials of a geometry, pointing its triangles at the
+/// first copy.
+fn merge_materials(geometry: &mut RwGeometry) {
+    let mut merged: Vec<RwMaterial> = Vec::new();
+    let remap: Vec<u16> = geometry
+        .material_list
+        .material_data
+        .iter()
+        .map(|material| match merged.iter().position(|known| known == material) {
+            Some(index) => index as u16,
+            None => {
+                merged.push(material.clone());
+                (merged.len() - 1) as u16
+            }
+        })
+        .collect();
+    for triangle in &mut geometry.triangle_information {
+        if let Some(&material_id) = remap.get(triangle.material_id as usize) {
+            triangle.material_id = material_id;
+        }
+    }
+    geometry.material_list.material_instance_count = merged.len() as u32;
+    geometry.material_list.material_data = merged;
+}
@@ -0,0 +1,165 @@
+//! # TXD Alpha Audit
+//!
+//! A validation pass that checks each texture's alpha flag and DXT variant
+//! against its actual pixel content. Textures flagged as opaque that contain
+//! transparent pixels (or the other way around) are the usual cause of
+//! transparency rendering bugs in San Andreas. The same analysis tells how a
+//! texture's transparency should be rendered, through `alpha_mode`.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let txd_data = fs::read("path/to/your.txd").unwrap();
+//! let txd = TxdParser::new(&txd_data).parse().unwrap();
+//!
+//! for issue in txd.audit_alpha() {
+//!     println!("{}: {:?}", issue.texture_name, issue.kind);
+//! }
+//! ```
+
+use super::txd_parser::{RwRasterEncoding, RwTextureNative, RwTxd};
+use crate::renderware::dff::dff_material::AlphaMode;
+use serde::Serialize;
+use std::borrow::Cow;
+
+/// A mismatch between a texture's header and its pixel content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AlphaAuditIssue {
+    pub texture_name: String,
+    pub kind: AlphaIssueKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AlphaIssueKind {
+    /// The texture has non-opaque pixels but its alpha flag is not set.
+    MissingAlphaFlag,
+    /// The alpha flag is set but every pixel is fully opaque.
+    UnusedAlphaFlag,
+    /// DXT1 only stores 1-bit alpha, but the texture has partially
+    /// transparent pixels. DXT3 or DXT5 should be used instead.
+    Dxt1WithGradientAlpha,
+    /// The texture is DXT3/DXT5 compressed but fully opaque, so DXT1 would
+    /// halve its size.
+    OpaqueDxtWithAlpha,
+}
+
+/// How a texture's alpha channel is actually used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AlphaUsage {
+    Opaque,
+    /// Every pixel is either fully opaque or fully transparent.
+    Binary,
+    Gradient,
+}
+
+impl RwTextureNative {
+    /// Checks the alpha flag and compression format against the pixel data.
+    ///
+    /// Uses the decoded bitmap when present and decodes the raster otherwise.
+    /// Textures that cannot be decoded produce no issues.
+    pub fn audit_alpha(&self) -> Vec<AlphaIssueKind> {
+        let Some(usage) = self.alpha_usage() else {
+            return Vec::new();
+        };
+
+        let mut issues = Vec::new();
+        match (self.alpha, usage) {
+            (false, AlphaUsage::Binary | AlphaUsage::Gradient) => issues.push(AlphaIssueKind::MissingAlphaFlag),
+            (true, AlphaUsage::Opaque) => issues.push(AlphaIssueKind::UnusedAlphaFlag),
+            _ => {}
+        }
+
+        match self.raster_encoding {
+            RwRasterEncoding::Dxt1 if usage == AlphaUsage::Gradient => {
+                issues.push(AlphaIssueKind::Dxt1WithGradientAlpha)
+            }
+            RwRasterEncoding::Dxt2 | RwRasterEncoding::Dxt3 | RwRasterEncoding::Dxt4 | RwRasterEncoding::Dxt5
+                if usage == AlphaUsage::Opaque =>
+            {
+                issues.push(AlphaIssueKind::OpaqueDxtWithAlpha)
+            }
+            _ => {}
+        }
+
+        issues
+    }
+
+    /// Classifies how the texture's transparency should be rendered.
+    ///
+    /// Textures whose alpha is all or nothing, apart from a few soft texels
+    /// left by compression or filtering along the edges, are cutouts to alpha
+    /// test. Textures with real translucency need blending. Textures that
+    /// cannot be decoded are judged by their alpha flag alone.
+    pub fn alpha_mode(&self) -> AlphaMode {
+        let Some(rgba) = self.rgba() else {
+            return if self.alpha { AlphaMode::Blend } else { AlphaMode::Opaque };
+        };
+        let (mut transparent, mut soft) = (0usize, 0usize);
+        for pixel in rgba.chunks_exact(4) {
+            match pixel[3] {
+                255 => {}
+                32..=223 => {
+                    transparent += 1;
+                    soft += 1;
+                }
+                _ => transparent += 1,
+            }
+        }
+        match transparent {
+            0 => AlphaMode::Opaque,
+            _ if soft * 10 < transparent => AlphaMode::Mask,
+            _ => AlphaMode::Blend,
+        }
+    }
+
+    fn alpha_usage(&self) -> Option<AlphaUsage> {
+        let mut usage = AlphaUsage::Opaque;
+        for pixel in self.rgba()?.chunks_exact(4) {
+            match pixel[3] {
+                255 => {}
+                0 => usage = AlphaUsage::Binary,
+                _ => return Some(AlphaUsage::Gradient),
+            }
+        }
+        Some(usage)
+    }
+
+    /// Returns the decoded bitmap when present, decoding the raster
+    /// otherwise, or `None` if the texture cannot be decoded.
+    fn rgba(&self) -> Option<Cow<'_, [u8]>> {
+        let pixel_count = self.width as usize * self.height as usize;
+        let rgba = match self.mipmaps.first() {
+            Some(bitmap) if !bitmap.is_empty() => Cow::Borrowed(&bitmap[..]),
+            _ => Cow::Owned(self.decode().ok()?),
+        };
+        if pixel_count == 0 || rgba.len() < pixel_count * 4 {
+            return None;
+        }
+        Some(match rgba {
+            Cow::Borrowed(bitmap) => Cow::Borrowed(&bitmap[..pixel_count * 4]),
+            Cow::Owned(mut bitmap) => {
+                bitmap.truncate(pixel_count * 4);
+                Cow::Owned(bitmap)
+            }
+        })
+    }
+}
+
+impl RwTxd {
+    /// Runs [`RwTextureNative::audit_alpha`] on every texture in the dictionary.
+    pub fn audit_alpha(&self) -> Vec<AlphaAuditIssue> {
+        self.texture_dictionary
+            .texture_natives
+            .iter()
+            .flat_map(|native| {
+                native.audit_alpha().into_iter().map(|kind| AlphaAuditIssue {
+                    texture_name: native.texture_name.clone(),
+                    kind,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,140 @@
+//! # Texture DDS Export
+//!
+//! Wraps texture natives into DirectDraw Surface (DDS) containers. BC1, BC2
+//! and BC3 rasters are written as-is together with their full mip chain, so
+//! no recompression loss occurs. Other formats fall back to an uncompressed
+//! RGBA8 surface of the first mipmap level.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/textures.txd").unwrap();
+//! let txd_data = TxdParser::new(&file_data).parse_metadata().unwrap();
+//!
+//! for texture in txd_data.texture_dictionary.iter() {
+//!     let dds = texture.to_dds().unwrap();
+//!     fs::write(format!("{}.dds", texture.texture_name), dds).unwrap();
+//! }
+//! ```
+
+use super::txd_parser::{RwRasterEncoding, RwTextureNative};
+use std::io::{Error, ErrorKind, Result};
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+impl RwTextureNative {
+    /// Encodes the texture as a DDS file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the DDS bytes or an `std::io::Error` if the
+    /// texture is neither DXT compressed nor decodable to RGBA.
+    pub fn to_dds(&self) -> Result<Vec<u8>> {
+        let fourcc = match self.raster_encoding {
+            RwRasterEncoding::Dxt1 => Some(b"DXT1"),
+            RwRasterEncoding::Dxt2 => Some(b"DXT2"),
+            RwRasterEncoding::Dxt3 => Some(b"DXT3"),
+            RwRasterEncoding::Dxt4 => Some(b"DXT4"),
+            RwRasterEncoding::Dxt5 => Some(b"DXT5"),
+            _ => None,
+        };
+
+        match fourcc {
+            Some(fourcc) if !self.raw_mipmaps.is_empty() => Ok(self.write_dds(Some(fourcc), &self.raw_mipmaps)),
+            _ => {
+                let rgba = match self.mipmaps.first() {
+                    Some(bitmap) if !bitmap.is_empty() => bitmap.clone(),
+                    _ => self.decode()?,
+                };
+                if rgba.is_empty() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Texture '{}' has no decodable pixels", self.texture_name),
+                    ));
+                }
+                Ok(self.write_dds(None, &[rgba]))
+            }
+        }
+    }
+
+    fn write_dds(&self, fourcc: Option<&[u8; 4]>, levels: &[Vec<u8>]) -> Vec<u8> {
+        let (width, height) = (self.width as u32, self.height as u32);
+        let mipmap_count = levels.len() as u32;
+
+        let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+        let pitch_or_linear_size = if fourcc.is_some() {
+            flags |= DDSD_LINEARSIZE;
+            levels[0].len() as u32
+        } else {
+            flags |= DDSD_PITCH;
+            width * 4
+        };
+        let mut caps = DDSCAPS_TEXTURE;
+        if mipmap_count > 1 {
+            flags |= DDSD_MIPMAPCOUNT;
+            caps |= DDSCAPS_MIPMAP | DDSCAPS_COMPLEX;
+        }
+
+        let mut output = Vec::with_capacity(128 + levels.iter().map(Vec::len).sum::<usize>());
+        let mut write_u32 = |value: u32| output.extend_from_slice(&value.to_le_bytes());
+
+        write_u32(u32::from_le_bytes(*b"DDS "));
+        write_u32(124);
+        write_u32(flags);
+        write_u32(height);
+        write_u32(width);
+        write_u32(pitch_or_linear_size);
+        write_u32(0); // Depth
+        write_u32(mipmap_count);
+        for _ in 0..11 {
+            write_u32(0); // Reserved
+        }
+
+        write_u32(32);
+        match fourcc {
+            Some(fourcc) => {
+                write_u32(DDPF_FOURCC);
+                write_u32(u32::from_le_bytes(*fourcc));
+                for _ in 0..5 {
+                    write_u32(0);
+                }
+            }
+            None => {
+                write_u32(DDPF_RGB | DDPF_ALPHAPIXELS);
+                write_u32(0);
+                write_u32(32);
+                write_u32(0x0000_00FF);
+                write_u32(0x0000_FF00);
+                write_u32(0x00FF_0000);
+                write_u32(0xFF00_0000);
+            }
+        }
+
+        write_u32(caps);
+        for _ in 0..4 {
+            write_u32(0); // Caps2, Caps3, Caps4, Reserved
+        }
+
+        for level in levels {
+            output.extend_from_slice(level);
+        }
+        output
+    }
+}
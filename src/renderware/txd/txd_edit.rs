@@ -0,0 +1,167 @@
+//! # Texture Editing
+//!
+//! Pixel replacement for parsed texture natives. New pixels are re-encoded
+//! into the texture's original raster format so the dictionary can be
+//! written back without changing how the game treats the texture.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/textures.txd").unwrap();
+//! let mut txd_data = TxdParser::new(&file_data).parse().unwrap();
+//!
+//! let rgba = vec![255u8; 64 * 64 * 4];
+//! txd_data.texture_dictionary.texture_natives[0].replace_pixels(64, 64, &rgba).unwrap();
+//! ```
+
+use super::txd_parser::{RwRasterEncoding, RwRasterFormatInfo, RwTextureNative};
+use super::txd_writer::{build_mipmap_chain, compress, generate_palette, nearest_palette_index};
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::utils::image_format_enums::{PaletteType, RasterFormat};
+use std::collections::HashMap;
+
+impl RwTextureNative {
+    /// Replaces the texture's pixels, re-encoding them into its raster format.
+    ///
+    /// DXT textures are recompressed, palettized textures get a freshly
+    /// generated palette and uncompressed textures are converted to their
+    /// pixel format. If the texture had mipmaps, a new chain is generated.
+    /// `mipmaps`, `raw_mipmaps`, `raw_palette`, the dimensions and the alpha
+    /// flag are all updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the new image.
+    /// * `height` - The height of the new image.
+    /// * `rgba` - The new pixels, 4 bytes per pixel in RGBA order.
+    ///
+    /// # Returns
+    ///
+    /// A `RwParseError::UnwritableData` if the pixel buffer does not match
+    /// the dimensions or the texture's raster encoding cannot be written.
+    pub fn replace_pixels(&mut self, width: u16, height: u16, rgba: &[u8]) -> RwResult<()> {
+        let (width_usize, height_usize) = (width as usize, height as usize);
+        if rgba.len() != width_usize * height_usize * 4 {
+            return Err(RwParseError::UnwritableData {
+                message: format!("Expected {} bytes of RGBA data, got {}", width_usize * height_usize * 4, rgba.len()),
+            });
+        }
+
+        let has_alpha = rgba.chunks_exact(4).any(|pixel| pixel[3] != 255);
+        let levels = if self.raw_mipmaps.len() > 1 || self.raster_format_info.mipmapped {
+            build_mipmap_chain(rgba, width_usize, height_usize)
+        } else {
+            vec![(rgba.to_vec(), width_usize, height_usize)]
+        };
+
+        let mut raster_format = self.raster_format;
+        let mut raw_palette = Vec::new();
+        let raw_mipmaps: Vec<Vec<u8>> = match self.raster_encoding {
+            RwRasterEncoding::Dxt1 => {
+                let pixel_format = if has_alpha { RasterFormat::Raster1555 } else { RasterFormat::Raster565 };
+                raster_format = (raster_format & !0x0F00) | (pixel_format as u32) << 8;
+                levels.iter().map(|(level, w, h)| compress(texpresso::Format::Bc1, level, *w, *h)).collect()
+            }
+            RwRasterEncoding::Dxt2 | RwRasterEncoding::Dxt3 => {
+                levels.iter().map(|(level, w, h)| compress(texpresso::Format::Bc2, level, *w, *h)).collect()
+            }
+            RwRasterEncoding::Dxt4 | RwRasterEncoding::Dxt5 => {
+                levels.iter().map(|(level, w, h)| compress(texpresso::Format::Bc3, level, *w, *h)).collect()
+            }
+            RwRasterEncoding::Palettized => {
+                // The same palette sizes the parser reads
+                let palette_size = match (self.raster_format_info.palette_type, self.depth) {
+                    (PaletteType::Palette8, _) => 256,
+                    (_, 4) => 16,
+                    _ => 32,
+                };
+                raw_palette = generate_palette(rgba, palette_size);
+                let mut cache = HashMap::new();
+                levels
+                    .iter()
+                    .map(|(level, _, _)| {
+                        let indices = level
+                            .chunks_exact(4)
+                            .map(|p| nearest_palette_index(&raw_palette, [p[0], p[1], p[2], p[3]], &mut cache));
+                        // 4-bit rasters pack two indices per byte, low nibble first,
+                        // while 8-bit rasters store one index per byte whatever the
+                        // palette size
+                        if self.depth == 4 {
+                            let indices: Vec<u8> = indices.collect();
+                            indices.chunks(2).map(|pair| pair[0] | pair.get(1).copied().unwrap_or(0) << 4).collect()
+                        } else {
+                            indices.collect()
+                        }
+                    })
+                    .collect()
+            }
+            RwRasterEncoding::Uncompressed => {
+                let pixel_format = self.raster_format_info.pixel_format;
+                levels.iter().map(|(level, _, _)| encode_uncompressed(level, pixel_format, self.depth)).collect::<RwResult<_>>()?
+            }
+            encoding => {
+                return Err(RwParseError::UnwritableData {
+                    message: format!("Cannot re-encode texture '{}' with {:?} rasters", self.texture_name, encoding),
+                });
+            }
+        };
+
+        if raw_mipmaps.len() > 1 {
+            raster_format |= 0x8000;
+        }
+
+        self.width = width;
+        self.height = height;
+        self.alpha = has_alpha;
+        self.mipmap_count = raw_mipmaps.len() as u8;
+        self.raster_format = raster_format;
+        self.raster_format_info = RwRasterFormatInfo::from_raster_format(raster_format);
+        self.raw_mipmaps = raw_mipmaps;
+        self.raw_palette = raw_palette;
+        self.mipmaps = vec![rgba.to_vec()];
+        Ok(())
+    }
+}
+
+fn encode_uncompressed(rgba: &[u8], pixel_format: Option<RasterFormat>, depth: u8) -> RwResult<Vec<u8>> {
+    let pixels = rgba.chunks_exact(4);
+    let pack16 = |r: u8, g: u8, b: u8, bits: (u32, u32, u32)| -> u16 {
+        let (rb, gb, bb) = bits;
+        ((r as u16 >> (8 - rb)) << (gb + bb)) | ((g as u16 >> (8 - gb)) << bb) | (b as u16 >> (8 - bb))
+    };
+
+    Ok(match (pixel_format, depth) {
+        (Some(RasterFormat::Raster8888), _) => pixels.flat_map(|p| [p[2], p[1], p[0], p[3]]).collect(),
+        (Some(RasterFormat::Raster888), 24) => pixels.flat_map(|p| [p[2], p[1], p[0]]).collect(),
+        (Some(RasterFormat::Raster888), _) => pixels.flat_map(|p| [p[2], p[1], p[0], 255]).collect(),
+        (Some(RasterFormat::Raster565), _) => {
+            pixels.flat_map(|p| pack16(p[0], p[1], p[2], (5, 6, 5)).to_le_bytes()).collect()
+        }
+        (Some(RasterFormat::Raster555), _) => {
+            pixels.flat_map(|p| pack16(p[0], p[1], p[2], (5, 5, 5)).to_le_bytes()).collect()
+        }
+        (Some(RasterFormat::Raster1555), _) => pixels
+            .flat_map(|p| (pack16(p[0], p[1], p[2], (5, 5, 5)) | if p[3] >= 128 { 0x8000 } else { 0 }).to_le_bytes())
+            .collect(),
+        (Some(RasterFormat::Raster4444), _) => pixels
+            .flat_map(|p| {
+                let value = (p[3] as u16 >> 4) << 12 | (p[0] as u16 >> 4) << 8 | (p[1] as u16 >> 4) << 4 | p[2] as u16 >> 4;
+                value.to_le_bytes()
+            })
+            .collect(),
+        (Some(RasterFormat::RasterLum), 16) => pixels.flat_map(|p| [luminance(p), p[3]]).collect(),
+        (Some(RasterFormat::RasterLum), _) => pixels.map(luminance).collect(),
+        (format, _) => {
+            return Err(RwParseError::UnwritableData {
+                message: format!("Cannot encode uncompressed {:?} rasters of depth {}", format, depth),
+            });
+        }
+    })
+}
+
+fn luminance(pixel: &[u8]) -> u8 {
+    ((pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000) as u8
+}
@@ -0,0 +1,81 @@
+//! # Texture Image Export
+//!
+//! Conversion helpers from `RwTextureNative` to the `image` crate, available
+//! with the `image` feature.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/textures.txd").unwrap();
+//! let txd_data = TxdParser::new(&file_data).parse().unwrap();
+//!
+//! for texture in txd_data.texture_dictionary.iter() {
+//!     let png = texture.to_png().unwrap();
+//!     fs::write(format!("{}.png", texture.texture_name), png).unwrap();
+//! }
+//! ```
+
+use super::txd_parser::RwTextureNative;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{DynamicImage, ExtendedColorType, ImageEncoder, RgbaImage};
+use std::io::{Error, ErrorKind, Result};
+
+impl RwTextureNative {
+    /// Returns the first mipmap level as an `image::RgbaImage`.
+    ///
+    /// Already decoded pixels in `mipmaps` are reused; otherwise the raster is
+    /// decoded on demand.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the image or an `std::io::Error` if the raster
+    /// format cannot be decoded.
+    pub fn to_image(&self) -> Result<RgbaImage> {
+        let rgba = match self.mipmaps.first() {
+            Some(bitmap) if !bitmap.is_empty() => bitmap.clone(),
+            _ => self.decode()?,
+        };
+
+        if rgba.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Texture '{}' has no decodable pixels", self.texture_name),
+            ));
+        }
+
+        RgbaImage::from_raw(self.width as u32, self.height as u32, rgba).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Texture '{}' bitmap does not match its dimensions", self.texture_name),
+            )
+        })
+    }
+
+    /// Encodes the first mipmap level as a PNG file.
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let image = self.to_image()?;
+        let mut output = Vec::new();
+        PngEncoder::new(&mut output)
+            .write_image(image.as_raw(), image.width(), image.height(), ExtendedColorType::Rgba8)
+            .map_err(Error::other)?;
+        Ok(output)
+    }
+
+    /// Encodes the first mipmap level as a JPEG file, discarding alpha.
+    ///
+    /// # Arguments
+    ///
+    /// * `quality` - The JPEG quality from 1 to 100.
+    pub fn to_jpeg(&self, quality: u8) -> Result<Vec<u8>> {
+        let image = DynamicImage::ImageRgba8(self.to_image()?).to_rgb8();
+        let mut output = Vec::new();
+        JpegEncoder::new_with_quality(&mut output, quality)
+            .write_image(image.as_raw(), image.width(), image.height(), ExtendedColorType::Rgb8)
+            .map_err(Error::other)?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,168 @@
+//! # TXD Merge and Diff
+//!
+//! Utilities for combining texture dictionaries and comparing them by texture
+//! name, format and pixel content. Names are compared case-insensitively, the
+//! same way the game resolves textures.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::txd::txd_merge::ConflictPolicy;
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let base_data = fs::read("path/to/base.txd").unwrap();
+//! let mod_data = fs::read("path/to/mod.txd").unwrap();
+//! let mut base = TxdParser::new(&base_data).parse().unwrap();
+//! let modded = TxdParser::new(&mod_data).parse().unwrap();
+//!
+//! let diff = base.diff(&modded);
+//! println!("{} added, {} changed", diff.added.len(), diff.changed.len());
+//!
+//! base.merge(&modded, ConflictPolicy::Replace).unwrap();
+//! ```
+
+use super::txd_parser::{RwTextureNative, RwTxd};
+use serde::Serialize;
+use std::io::{Error, ErrorKind, Result};
+
+/// Decides what happens when both dictionaries contain a texture with the
+/// same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the texture already present in the target dictionary.
+    KeepExisting,
+    /// Replace the existing texture with the incoming one.
+    Replace,
+    /// Abort the merge with an `AlreadyExists` error.
+    Fail,
+}
+
+/// The result of comparing two texture dictionaries.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TxdDiff {
+    /// Textures only present in the other dictionary.
+    pub added: Vec<String>,
+    /// Textures only present in this dictionary.
+    pub removed: Vec<String>,
+    /// Textures present in both dictionaries that differ.
+    pub changed: Vec<TextureChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextureChange {
+    pub name: String,
+    pub format_changed: bool,
+    pub dimensions_changed: bool,
+    pub pixels_changed: bool,
+}
+
+impl TxdDiff {
+    /// Returns `true` if the dictionaries contain the same textures.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl RwTextureNative {
+    /// Computes a stable 64-bit FNV-1a hash of the texture's pixel data.
+    ///
+    /// The undecoded first mipmap level and palette are hashed when present,
+    /// otherwise the decoded bitmap is used.
+    pub fn pixel_hash(&self) -> u64 {
+        let mut hash = 0xCBF2_9CE4_8422_2325u64;
+        let mut feed = |bytes: &[u8]| {
+            for &byte in bytes {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+            }
+        };
+
+        match self.raw_mipmaps.first() {
+            Some(level) => {
+                feed(level);
+                feed(&self.raw_palette);
+            }
+            None => feed(self.mipmaps.first().map_or(&[][..], Vec::as_slice)),
+        }
+        hash
+    }
+}
+
+impl RwTxd {
+    /// Merges the textures of `other` into this dictionary.
+    ///
+    /// Textures with new names are appended in order; name conflicts are
+    /// resolved according to `policy`.
+    ///
+    /// # Returns
+    ///
+    /// An `std::io::Error` of kind `AlreadyExists` if `policy` is
+    /// `ConflictPolicy::Fail` and a conflict is found. The dictionary is left
+    /// unchanged in that case.
+    pub fn merge(&mut self, other: &RwTxd, policy: ConflictPolicy) -> Result<()> {
+        let dictionary = &mut self.texture_dictionary;
+
+        let conflict = other.texture_dictionary.names().find(|name| dictionary.get_by_name(name).is_some());
+        if let (ConflictPolicy::Fail, Some(conflict)) = (policy, conflict) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("Texture '{}' exists in both dictionaries", conflict),
+            ));
+        }
+
+        for incoming in other.texture_dictionary.iter() {
+            let existing = dictionary
+                .texture_natives
+                .iter()
+                .position(|native| native.texture_name.eq_ignore_ascii_case(&incoming.texture_name));
+
+            match existing {
+                Some(index) if policy == ConflictPolicy::Replace => dictionary.texture_natives[index] = incoming.clone(),
+                Some(_) => {}
+                None => dictionary.texture_natives.push(incoming.clone()),
+            }
+        }
+
+        dictionary.texture_count = dictionary.texture_natives.len() as u16;
+        dictionary.rebuild_index();
+        Ok(())
+    }
+
+    /// Compares this dictionary against `other`.
+    ///
+    /// Textures are matched by name; matched pairs are compared by format,
+    /// dimensions and pixel hash.
+    pub fn diff(&self, other: &RwTxd) -> TxdDiff {
+        let ours = &self.texture_dictionary;
+        let theirs = &other.texture_dictionary;
+        let mut diff = TxdDiff::default();
+
+        for native in ours.iter() {
+            let Some(counterpart) = theirs.get_by_name(&native.texture_name) else {
+                diff.removed.push(native.texture_name.clone());
+                continue;
+            };
+
+            let change = TextureChange {
+                name: native.texture_name.clone(),
+                format_changed: native.platform_id != counterpart.platform_id
+                    || native.raster_format != counterpart.raster_format
+                    || native.d3d_format != counterpart.d3d_format
+                    || native.depth != counterpart.depth,
+                dimensions_changed: native.width != counterpart.width || native.height != counterpart.height,
+                pixels_changed: native.pixel_hash() != counterpart.pixel_hash(),
+            };
+            if change.format_changed || change.dimensions_changed || change.pixels_changed {
+                diff.changed.push(change);
+            }
+        }
+
+        diff.added = theirs
+            .names()
+            .filter(|name| ours.get_by_name(name).is_none())
+            .map(str::to_string)
+            .collect();
+        diff
+    }
+}
@@ -7,7 +7,18 @@
 //!
 //! - Parses texture metadata, including name, dimensions, and format.
 //! - Supports decompression of DXT1, DXT3, and DXT5 (BC1, BC2, BC3) textures.
+//! - Decodes palettized and uncompressed rasters, including LUM8 and A8L8.
 //! - Extracts mipmap levels for supported formats.
+//! - Keeps the undecoded mip chain for lossless pass-through of DXT blocks.
+//! - Borrows raw rasters from the input instead of copying them with
+//!   `parse_borrowed`.
+//! - Reads PS2 texture natives, unswizzling 4/8-bit rasters and CSM1 palettes.
+//! - Reads Xbox texture natives, including swizzled uncompressed rasters.
+//! - Reads mobile PowerVR texture natives, decoding PVRTC and ETC1 rasters
+//!   with the `pvrtc` and `etc1` features.
+//! - Reads big-endian GameCube texture natives and decodes the tiled GX
+//!   texture formats, including CMPR.
+//! - Decodes textures in parallel with the `parallel` feature.
 //! - Deserializes texture data into a structured `RwTxd` format.
 //!
 //! ## Example
@@ -23,30 +34,197 @@
 //! println!("Texture count: {}", txd_data.texture_dictionary.texture_count);
 //! ```
 
+use crate::renderware::rw_error::{catch_panic, RwParseError, RwResult};
 use crate::renderware::rw_file::RwFile;
-use crate::renderware::utils::image_format_enums::{PaletteType, PlatformType};
-use std::io::Result;
+use crate::utils::byte_stream::Endianness;
+use crate::renderware::rw_report::{ParseMode, ParseReport};
+use crate::renderware::rw_source_map::{SourceItem, SourceMap};
+use crate::renderware::rw_sections::RwSections;
+use super::swizzle::{unclut_palette, unswizzle_ps2_4bit, unswizzle_ps2_8bit, xbox_swizzled_offset};
+use crate::renderware::utils::image_format_enums::{D3dFormat, PaletteType, PlatformType, RasterFormat};
+use num::FromPrimitive;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+#[cfg(feature = "mmap")]
+use crate::utils::mapped_file::MappedFile;
+#[cfg(feature = "mmap")]
+use std::path::Path;
 use texpresso;
 
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize};
+
+const GL_COMPRESSED_RGB_PVRTC_4BPPV1: u32 = 0x8C00;
+const GL_COMPRESSED_RGB_PVRTC_2BPPV1: u32 = 0x8C01;
+const GL_COMPRESSED_RGBA_PVRTC_4BPPV1: u32 = 0x8C02;
+const GL_COMPRESSED_RGBA_PVRTC_2BPPV1: u32 = 0x8C03;
+const GL_ETC1_RGB8: u32 = 0x8D64;
+
+// GameCube GX texture formats
+const GX_TF_I4: u32 = 0x0;
+const GX_TF_I8: u32 = 0x1;
+const GX_TF_IA4: u32 = 0x2;
+const GX_TF_IA8: u32 = 0x3;
+const GX_TF_RGB565: u32 = 0x4;
+const GX_TF_RGB5A3: u32 = 0x5;
+const GX_TF_RGBA8: u32 = 0x6;
+const GX_TF_C4: u32 = 0x8;
+const GX_TF_C8: u32 = 0x9;
+const GX_TF_CMPR: u32 = 0xE;
+
+/// The most mipmap levels a PS2 raster can have, the base level and the six
+/// levels the GS MIPTBP registers address.
+const MAX_PS2_MIPMAP_LEVELS: usize = 7;
 
 /// Represents the top-level structure of a parsed TXD file.
 ///
 /// This struct contains the texture dictionary, which holds all the
 /// individual textures in the file.
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RwTxd {
     /// The texture dictionary containing all texture data.
     pub texture_dictionary: RwTextureDictionary,
+    /// The name of the parent dictionary declared in the dictionary's
+    /// extension, whose textures are shared with this one.
+    pub parent_name: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RwTextureDictionary {
     pub texture_count: u16,
     pub texture_natives: Vec<RwTextureNative>,
+    #[serde(skip)]
+    name_index: HashMap<String, usize>,
+}
+
+impl<'de> Deserialize<'de> for RwTextureDictionary {
+    /// Deserializes the textures and rebuilds the name index, which is not
+    /// serialized.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            texture_count: u16,
+            texture_natives: Vec<RwTextureNative>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        let mut dictionary = RwTextureDictionary {
+            texture_count: fields.texture_count,
+            texture_natives: fields.texture_natives,
+            name_index: HashMap::new(),
+        };
+        dictionary.rebuild_index();
+        Ok(dictionary)
+    }
+}
+
+impl PartialEq for RwTextureDictionary {
+    fn eq(&self, other: &Self) -> bool {
+        self.texture_count == other.texture_count && self.texture_natives == other.texture_natives
+    }
+}
+
+impl RwTextureDictionary {
+    /// Creates a dictionary from a list of textures and indexes them by name.
+    pub fn new(texture_natives: Vec<RwTextureNative>) -> Self {
+        let mut dictionary = RwTextureDictionary {
+            texture_count: texture_natives.len() as u16,
+            texture_natives,
+            name_index: HashMap::new(),
+        };
+        dictionary.rebuild_index();
+        dictionary
+    }
+
+    /// Rebuilds the name index after `texture_natives` has been modified.
+    pub fn rebuild_index(&mut self) {
+        self.name_index.clear();
+        for (index, native) in self.texture_natives.iter().enumerate() {
+            self.name_index.entry(native.texture_name.to_ascii_lowercase()).or_insert(index);
+        }
+    }
+
+    /// Looks up a texture by name, ignoring ASCII case like the game does.
+    ///
+    /// When several textures share a name, the first one is returned.
+    pub fn get_by_name(&self, name: &str) -> Option<&RwTextureNative> {
+        let indexed = self
+            .name_index
+            .get(&name.to_ascii_lowercase())
+            .and_then(|&index| self.texture_natives.get(index))
+            .filter(|native| native.texture_name.eq_ignore_ascii_case(name));
+
+        indexed.or_else(|| self.texture_natives.iter().find(|native| native.texture_name.eq_ignore_ascii_case(name)))
+    }
+
+    /// Returns an iterator over the textures in dictionary order.
+    pub fn iter(&self) -> std::slice::Iter<'_, RwTextureNative> {
+        self.texture_natives.iter()
+    }
+
+    /// Reports texture names that are used more than once, ignoring ASCII
+    /// case. The game only ever resolves the first of them.
+    pub fn name_collisions(&self) -> Vec<NameCollision> {
+        let mut collisions: Vec<NameCollision> = Vec::new();
+        for (index, native) in self.texture_natives.iter().enumerate() {
+            let first = self.name_index.get(&native.texture_name.to_ascii_lowercase()).copied();
+            if first.is_none_or(|first| first == index) {
+                continue;
+            }
+            match collisions.iter_mut().find(|collision| collision.name.eq_ignore_ascii_case(&native.texture_name)) {
+                Some(collision) => collision.indices.push(index),
+                None => collisions.push(NameCollision {
+                    name: self.texture_natives[first.unwrap_or(index)].texture_name.clone(),
+                    indices: vec![first.unwrap_or(index), index],
+                }),
+            }
+        }
+        collisions
+    }
+
+    /// Returns an iterator over the texture names in dictionary order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.texture_natives.iter().map(|native| native.texture_name.as_str())
+    }
+}
+
+/// A texture name shared by several textures in one dictionary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NameCollision {
+    /// The name as spelled by the first texture using it.
+    pub name: String,
+    /// Indices into `texture_natives` of every texture using the name.
+    pub indices: Vec<usize>,
+}
+
+/// Cleans up a texture name read from a fixed 32-byte field.
+///
+/// Control characters and non-ASCII bytes, which usually come from
+/// uninitialized memory in the original tools, are removed and surrounding
+/// whitespace is trimmed.
+pub fn sanitize_texture_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii() && !c.is_ascii_control())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn sanitize_native_names(native: &mut RwTextureNative) {
+    native.texture_name = sanitize_texture_name(&native.texture_name);
+    native.mask_name = sanitize_texture_name(&native.mask_name);
+}
+
+impl<'a> IntoIterator for &'a RwTextureDictionary {
+    type Item = &'a RwTextureNative;
+    type IntoIter = std::slice::Iter<'a, RwTextureNative>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RwTextureNative {
     pub platform_id: u32,
     pub filter_mode: u8,
@@ -66,6 +244,233 @@ pub struct RwTextureNative {
     pub auto_mip_maps: bool,
     pub compressed: bool,
     pub mipmaps: Vec<Vec<u8>>,
+    /// The undecoded mipmap levels exactly as stored in the file.
+    pub raw_mipmaps: Vec<Vec<u8>>,
+    /// The undecoded palette, if the raster is palettized.
+    pub raw_palette: Vec<u8>,
+    /// The encoding of `raw_mipmaps`.
+    pub raster_encoding: RwRasterEncoding,
+    /// Typed view of the packed `raster_format` field.
+    pub raster_format_info: RwRasterFormatInfo,
+    /// Typed view of `d3d_format`, when it names a known Direct3D format.
+    pub d3d_format_type: Option<D3dFormat>,
+    /// Plugin data from the texture's extension block.
+    pub extensions: RwTextureExtensions,
+}
+
+/// Plugin data attached to a texture native.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RwTextureExtensions {
+    /// Maximum anisotropic filtering level from the anisotropy plugin.
+    pub anisotropy: Option<u32>,
+    /// PS2 mipmap selection values from the sky mipmap plugin.
+    pub sky_mipmap: Option<RwSkyMipmapValue>,
+    /// Extensions this parser does not understand, kept for round-tripping.
+    pub unknown: Vec<RwUnknownExtension>,
+}
+
+/// The PS2 `K` and `L` values used by the GS to select mipmap levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RwSkyMipmapValue {
+    /// The LOD bias, stored as signed fixed point with four fractional bits.
+    pub k: f32,
+    /// The LOD calculation method, in the range `0..=3`.
+    pub l: u8,
+}
+
+impl RwSkyMipmapValue {
+    /// Unpacks the value as stored in the plugin chunk.
+    pub fn from_packed(value: u32) -> Self {
+        let k = ((value << 16) as i32 >> 20) as f32 / 16.0;
+        RwSkyMipmapValue { k, l: (value & 0x3) as u8 }
+    }
+
+    /// Packs the value back into its on-disk representation.
+    pub fn to_packed(self) -> u32 {
+        ((((self.k * 16.0) as i32) & 0xFFF) << 4) as u32 | (self.l & 0x3) as u32
+    }
+}
+
+/// A raw extension chunk that was not parsed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RwUnknownExtension {
+    pub section_type: u32,
+    pub data: Vec<u8>,
+}
+
+/// The decoded components of a packed RenderWare raster format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RwRasterFormatInfo {
+    /// The pixel format (bits `0x0F00`), if it is a known value.
+    pub pixel_format: Option<RasterFormat>,
+    /// The palette layout (bits `0x6000`).
+    pub palette_type: PaletteType,
+    /// Whether the raster carries a mipmap chain (`0x8000`).
+    pub mipmapped: bool,
+    /// Whether mipmaps are generated automatically (`0x1000`).
+    pub auto_mipmap: bool,
+    /// Whether platform-specific extended bits above `0xFFFF` are set.
+    pub extended: bool,
+}
+
+impl RwRasterFormatInfo {
+    /// Splits a packed raster format into its typed components.
+    pub fn from_raster_format(raster_format: u32) -> Self {
+        RwRasterFormatInfo {
+            pixel_format: FromPrimitive::from_u32((raster_format >> 8) & 0x0F),
+            palette_type: FromPrimitive::from_u32((raster_format >> 13) & 0b11).unwrap_or_default(),
+            mipmapped: (raster_format & 0x8000) != 0,
+            auto_mipmap: (raster_format & 0x1000) != 0,
+            extended: (raster_format & 0xFFFF_0000) != 0,
+        }
+    }
+}
+
+/// Describes how the bytes in `RwTextureNative::raw_mipmaps` are encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RwRasterEncoding {
+    /// No pixel data or an unrecognized encoding.
+    #[default]
+    Unknown,
+    /// BC1 blocks.
+    Dxt1,
+    /// BC2 blocks with premultiplied alpha.
+    Dxt2,
+    /// BC2 blocks.
+    Dxt3,
+    /// BC3 blocks with premultiplied alpha.
+    Dxt4,
+    /// BC3 blocks.
+    Dxt5,
+    /// Linear palette indices; colors are in `raw_palette`.
+    Palettized,
+    /// Linear uncompressed pixels described by `raster_format` and `depth`.
+    Uncompressed,
+    /// PS2 GS memory layout, optionally swizzled, with PS2 alpha range.
+    Ps2 { swizzled: bool },
+    /// Xbox Morton-order swizzled pixels or palette indices.
+    XboxSwizzled,
+    /// PVRTC 4 bits per pixel.
+    Pvrtc4,
+    /// PVRTC 2 bits per pixel.
+    Pvrtc2,
+    /// ETC1 blocks.
+    Etc1,
+    /// GameCube GX tiled texture data in the given GX texture format.
+    GameCube { format: u32 },
+}
+
+impl RwRasterEncoding {
+    fn d3d_format(self) -> Option<D3dFormat> {
+        match self {
+            RwRasterEncoding::Dxt1 => Some(D3dFormat::D3dDxt1),
+            RwRasterEncoding::Dxt2 => Some(D3dFormat::D3dDxt2),
+            RwRasterEncoding::Dxt3 => Some(D3dFormat::D3dDxt3),
+            RwRasterEncoding::Dxt4 => Some(D3dFormat::D3dDxt4),
+            RwRasterEncoding::Dxt5 => Some(D3dFormat::D3dDxt5),
+            _ => None,
+        }
+    }
+
+    fn is_dxt(self) -> bool {
+        matches!(
+            self,
+            RwRasterEncoding::Dxt1 | RwRasterEncoding::Dxt2 | RwRasterEncoding::Dxt3 | RwRasterEncoding::Dxt4 | RwRasterEncoding::Dxt5
+        )
+    }
+
+    fn from_dxt_name(name: &str) -> Self {
+        match name {
+            "DXT1" => RwRasterEncoding::Dxt1,
+            "DXT2" => RwRasterEncoding::Dxt2,
+            "DXT3" => RwRasterEncoding::Dxt3,
+            "DXT4" => RwRasterEncoding::Dxt4,
+            "DXT5" => RwRasterEncoding::Dxt5,
+            _ => RwRasterEncoding::Unknown,
+        }
+    }
+}
+
+impl RwTextureNative {
+    /// Returns the platform of `platform_id`, or `None` for unknown ids.
+    pub fn platform(&self) -> Option<PlatformType> {
+        PlatformType::from_id(self.platform_id)
+    }
+
+    /// Decodes the first mipmap level into an RGBA bitmap.
+    ///
+    /// This works on textures from both `TxdParser::parse` and
+    /// `TxdParser::parse_metadata`. Formats without a decoder yield an
+    /// empty bitmap.
+    pub fn decode(&self) -> RwResult<Vec<u8>> {
+        match self.raw_mipmaps.first() {
+            Some(data) => self.decode_raster(data, &self.raw_palette),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Decodes the first mipmap level of a raster returned by
+    /// `TxdParser::parse_borrowed` into an RGBA bitmap.
+    pub fn decode_view(&self, raster: &RwRasterView) -> RwResult<Vec<u8>> {
+        match raster.mipmaps.first() {
+            Some(data) => self.decode_raster(data, &raster.palette),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn decode_raster(&self, data: &[u8], palette: &[u8]) -> RwResult<Vec<u8>> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        // No encoding stores more than 4 pixels per byte, so a raster far
+        // smaller than its size comes from a damaged header. It yields an
+        // empty bitmap instead of allocating up to 16 GiB.
+        if width * height > data.len().max(16) * 8 {
+            return Ok(Vec::new());
+        }
+        // The DXT decoders slice whole blocks out of the raster, so a level
+        // shorter than its blocks yields an empty bitmap as well
+        if self.raster_encoding.is_dxt() && data.len() < mipmap_level_size(self.raster_encoding, width, height, self.depth) {
+            return Ok(Vec::new());
+        }
+
+        Ok(match self.raster_encoding {
+            RwRasterEncoding::Dxt1 => decode_dxt(texpresso::Format::Bc1, data, width, height),
+            RwRasterEncoding::Dxt2 | RwRasterEncoding::Dxt3 => decode_dxt(texpresso::Format::Bc2, data, width, height),
+            RwRasterEncoding::Dxt4 | RwRasterEncoding::Dxt5 => decode_dxt(texpresso::Format::Bc3, data, width, height),
+            RwRasterEncoding::Ps2 { swizzled } => {
+                decode_ps2_raster(data, palette, width, height, self.depth as u32, swizzled)
+            }
+            RwRasterEncoding::GameCube { format } => decode_gamecube_raster(data, palette, format, width, height),
+            RwRasterEncoding::XboxSwizzled => {
+                decode_xbox_raster(data, palette, self.raster_format, width, height, self.depth)
+            }
+            RwRasterEncoding::Pvrtc4 | RwRasterEncoding::Pvrtc2 | RwRasterEncoding::Etc1 => {
+                decode_mobile_raster(self.raster_encoding, data, width, height)
+            }
+            RwRasterEncoding::Palettized => decode_palettized_raster(data, palette, width, height, self.depth),
+            RwRasterEncoding::Uncompressed => decode_uncompressed_raster(data, self.uncompressed_format(), width, height),
+            RwRasterEncoding::Unknown => Vec::new(),
+        })
+    }
+}
+
+/// The undecoded mipmap levels and palette of a texture native, borrowed
+/// from the parsed buffer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RwRasterView<'a> {
+    pub mipmaps: Vec<Cow<'a, [u8]>>,
+    /// Owned instead of borrowed when the stored palette has to be
+    /// reordered, as for 8-bit PS2 rasters.
+    pub palette: Cow<'a, [u8]>,
+}
+
+/// A dictionary parsed by `TxdParser::parse_borrowed`.
+///
+/// The `mipmaps`, `raw_mipmaps` and `raw_palette` of its texture natives are
+/// empty. Their pixel data is in `rasters`, at the same index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RwTxdView<'a> {
+    pub txd: RwTxd,
+    pub rasters: Vec<RwRasterView<'a>>,
 }
 
 /// The main parser for TXD files.
@@ -74,6 +479,25 @@ pub struct RwTextureNative {
 /// deserialize the TXD texture data.
 pub struct TxdParser<'a> {
     file: RwFile<'a>,
+    decode_rasters: bool,
+    borrow_rasters: bool,
+    raster_views: Vec<RwRasterView<'a>>,
+    sanitize_names: bool,
+    mode: ParseMode,
+    diagnostics: Vec<TxdDiagnostic>,
+    report: ParseReport,
+    source_map: SourceMap,
+}
+
+/// A problem encountered while parsing a TXD in lenient mode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxdDiagnostic {
+    /// The index of the texture native that failed, or `None` if the error
+    /// was in the dictionary itself.
+    pub texture_index: Option<usize>,
+    /// The byte offset of the failing section in the file.
+    pub offset: u64,
+    pub message: String,
 }
 
 impl<'a> TxdParser<'a> {
@@ -85,9 +509,40 @@ impl<'a> TxdParser<'a> {
     pub fn new(buffer: &'a [u8]) -> Self {
         TxdParser {
             file: RwFile::new(buffer),
+            decode_rasters: true,
+            borrow_rasters: false,
+            raster_views: Vec::new(),
+            sanitize_names: false,
+            mode: ParseMode::Standard,
+            diagnostics: Vec::new(),
+            report: ParseReport::default(),
+            source_map: SourceMap::default(),
         }
     }
 
+    /// Enables cleaning up texture and mask names while parsing.
+    ///
+    /// See `sanitize_texture_name` for the rules applied.
+    pub fn with_name_sanitization(mut self, enabled: bool) -> Self {
+        self.sanitize_names = enabled;
+        self
+    }
+
+    /// Sets the byte order of the dictionary, for big-endian GameCube, Wii
+    /// and PS3 assets. GameCube natives in little-endian dictionaries are
+    /// detected without it.
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.file.get_stream().set_endianness(endianness);
+        self
+    }
+
+    /// Sets how inconsistent data is handled. Lenient mode behaves like
+    /// `parse_lenient`, with its diagnostics recorded in the `ParseReport`.
+    pub fn with_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
     /// Parses the entire TXD file buffer.
     ///
     // This method reads the root `TextureDictionary` section and all the
@@ -95,42 +550,420 @@ impl<'a> TxdParser<'a> {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the parsed `RwTxd` data or an `std::io::Error`
+    /// A `Result` containing the parsed `RwTxd` data or a `RwParseError`
     /// if a parsing error occurs.
-    pub fn parse(&mut self) -> Result<RwTxd> {
-        Ok(RwTxd {
-            texture_dictionary: self.read_texture_dictionary()?,
+    pub fn parse(&mut self) -> RwResult<RwTxd> {
+        self.decode_rasters = true;
+        self.read_txd()
+    }
+
+    /// Parses the TXD file buffer without decoding any pixel data.
+    ///
+    /// Texture names, dimensions and formats are filled in as usual, but
+    /// `mipmaps` is left empty. Call `RwTextureNative::decode` to decompress
+    /// individual textures when they are actually needed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwTxd` metadata or a `RwParseError`
+    /// if a parsing error occurs.
+    pub fn parse_metadata(&mut self) -> RwResult<RwTxd> {
+        self.decode_rasters = false;
+        self.read_txd()
+    }
+
+    /// Parses the TXD file buffer without copying or decoding any pixel data.
+    ///
+    /// The mipmap levels and palettes are returned as `RwRasterView`s that
+    /// borrow from the buffer, so batch tools scanning many dictionaries do
+    /// not hold a second copy of every raster. Decode a texture with
+    /// `RwTextureNative::decode_view`.
+    pub fn parse_borrowed(&mut self) -> RwResult<RwTxdView<'a>> {
+        self.decode_rasters = false;
+        self.borrow_rasters = true;
+        self.raster_views.clear();
+        let txd = self.read_txd();
+        self.borrow_rasters = false;
+        Ok(RwTxdView {
+            txd: txd?,
+            rasters: std::mem::take(&mut self.raster_views),
         })
     }
 
-    fn read_texture_dictionary(&mut self) -> Result<RwTextureDictionary> {
-        self.file.read_section_header()?; // Struct
-        self.file.read_section_header()?; // TextureDictionary
+    /// Parses the TXD file buffer, salvaging as much as possible from damaged
+    /// or truncated files.
+    ///
+    /// Texture natives that fail to parse are skipped when their section size
+    /// allows resynchronizing on the next one; otherwise parsing stops and the
+    /// textures read so far are returned. Each failure is reported as a
+    /// `TxdDiagnostic`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the recovered `RwTxd` and the diagnostics, or a
+    /// `RwParseError` if even the dictionary header cannot be read.
+    pub fn parse_lenient(&mut self) -> RwResult<(RwTxd, Vec<TxdDiagnostic>)> {
+        self.decode_rasters = true;
+        self.mode = ParseMode::Lenient;
+        let txd = self.read_txd()?;
+        Ok((txd, std::mem::take(&mut self.diagnostics)))
+    }
 
-        let texture_count = self.file.get_stream().read_u16()?;
-        self.file.get_stream().skip(2)?;
+    /// Parses the entire TXD file buffer like `parse`, also returning the
+    /// warnings met on the way: skipped plugins and empty or zero-sized
+    /// rasters.
+    pub fn parse_with_report(&mut self) -> RwResult<(RwTxd, ParseReport)> {
+        let txd = self.parse()?;
+        Ok((txd, std::mem::take(&mut self.report)))
+    }
+
+    /// Parses the entire TXD file buffer like `parse`, also returning where
+    /// each texture native was read from.
+    pub fn parse_with_source_map(&mut self) -> RwResult<(RwTxd, SourceMap)> {
+        let txd = self.parse()?;
+        Ok((txd, std::mem::take(&mut self.source_map)))
+    }
+
+    /// Parses TXD data from an untrusted source, such as an upload or a
+    /// fuzzer, in `ParseMode::Standard`. Counts that need more data than
+    /// remains are rejected before anything is allocated, and a panic is
+    /// returned as an error instead of unwinding into the caller. Rasters
+    /// are decoded like in `parse`.
+    pub fn parse_untrusted(data: &[u8]) -> RwResult<RwTxd> {
+        catch_panic(|| TxdParser::new(data).with_mode(ParseMode::Standard).parse())
+    }
+
+    /// Memory-maps the TXD file at `path` and parses it in place, without
+    /// reading it into a buffer first. Requires the `mmap` feature.
+    #[cfg(feature = "mmap")]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> RwResult<RwTxd> {
+        let file = MappedFile::open(path)?;
+        TxdParser::new(&file).parse()
+    }
+
+    /// Parses a TXD file from `reader`, starting at its current position,
+    /// and leaves the reader after the dictionary.
+    ///
+    /// Textures are read and decoded one section at a time, so the file is
+    /// never buffered as a whole on top of the parsed textures.
+    pub fn parse_from_reader<R: Read + Seek>(reader: &mut R) -> RwResult<RwTxd> {
+        TxdParser::parse_from_reader_with_endianness(reader, Endianness::Little)
+    }
+
+    /// Like `parse_from_reader`, but reads the dictionary in the given byte
+    /// order, such as a big-endian GameCube dictionary.
+    pub fn parse_from_reader_with_endianness<R: Read + Seek>(reader: &mut R, endianness: Endianness) -> RwResult<RwTxd> {
+        let start = reader.stream_position()?;
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header)?;
+        let field = |index: usize| endianness.u32_from_bytes(header[index..index + 4].try_into().unwrap());
+        let section_type = field(0);
+        if section_type != RwSections::RwTextureDictionary as u32 {
+            return Err(RwParseError::UnexpectedSection {
+                expected: RwSections::RwTextureDictionary as u32,
+                found: section_type,
+                offset: start,
+            });
+        }
+        let end = start + 12 + field(4) as u64;
+
+        let (_, info) = RwFile::read_section_from_with_endianness(reader, endianness)?;
+        let mut info_parser = TxdParser::new(&info).with_endianness(endianness);
+        info_parser.file.expect_section(RwSections::RwStruct)?;
+        let texture_count = info_parser.file.get_stream().read_u16()?;
 
         let mut texture_natives = Vec::with_capacity(texture_count as usize);
         for _ in 0..texture_count {
-            texture_natives.push(self.read_texture_native()?);
+            let (_, section) = RwFile::read_section_from_with_endianness(reader, endianness)?;
+            let mut native = TxdParser::new(&section).with_endianness(endianness).read_texture_native()?;
+            decode_into_mipmaps(&mut native)?;
+            texture_natives.push(native);
         }
-        
-        let size = self.file.read_section_header()?.section_size;
-        self.file.get_stream().skip(size as u64)?;
 
-        Ok(RwTextureDictionary {
-            texture_count,
-            texture_natives,
+        let parent_name = if reader.stream_position()? < end {
+            let (_, extension) = RwFile::read_section_from_with_endianness(reader, endianness)?;
+            TxdParser::new(&extension).with_endianness(endianness).read_texture_dictionary_extension()?
+        } else {
+            None
+        };
+
+        reader.seek(SeekFrom::Start(end))?;
+        Ok(RwTxd {
+            texture_dictionary: RwTextureDictionary::new(texture_natives),
+            parent_name,
         })
     }
 
-    fn read_texture_native(&mut self) -> Result<RwTextureNative> {
-        self.file.read_section_header()?; // Struct
-        self.file.read_section_header()?; // TextureNative
+    fn read_txd(&mut self) -> RwResult<RwTxd> {
+        self.report = ParseReport::default();
+        self.source_map = SourceMap::default();
+        self.diagnostics.clear();
+        let texture_dictionary = self.read_texture_dictionary()?;
+
+        let offset = self.file.get_stream().get_position();
+        if self.mode == ParseMode::Lenient && offset >= self.file.get_stream().get_size() {
+            return Ok(RwTxd { texture_dictionary, parent_name: None });
+        }
+
+        let parent_name = match self.read_texture_dictionary_extension() {
+            Err(error) if self.mode == ParseMode::Lenient => {
+                self.diagnose(None, offset, format!("Invalid dictionary extension: {}", error));
+                None
+            }
+            result => result?,
+        };
+
+        Ok(RwTxd { texture_dictionary, parent_name })
+    }
+
+    /// Decodes the textures one at a time, passing each one and its decoded
+    /// RGBA bitmap to `callback`.
+    ///
+    /// Only a single decoded bitmap is alive at any time, which keeps memory
+    /// use flat when extracting large dictionaries. The `mipmaps` field of
+    /// the texture passed to the callback is left empty. Iteration stops at
+    /// the first error returned by the parser or the callback.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+    /// use std::fs;
+    ///
+    /// let txd_data = fs::read("path/to/your.txd").unwrap();
+    /// TxdParser::new(&txd_data)
+    ///     .for_each_texture(|texture, rgba| {
+    ///         println!("{}: {} bytes", texture.texture_name, rgba.len());
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn for_each_texture<F>(&mut self, mut callback: F) -> RwResult<()>
+    where
+        F: FnMut(&RwTextureNative, &[u8]) -> RwResult<()>,
+    {
+        let texture_count = self.read_texture_dictionary_header()?;
+        for _ in 0..texture_count {
+            let mut native = self.read_texture_native()?;
+            if self.sanitize_names {
+                sanitize_native_names(&mut native);
+            }
+            let rgba = if native.mipmap_count > 0 { native.decode()? } else { Vec::new() };
+            callback(&native, &rgba)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the dictionary headers and returns the number of textures.
+    fn read_texture_dictionary_header(&mut self) -> RwResult<u16> {
+        if self.mode == ParseMode::Lenient {
+            // A truncated dictionary still has textures worth salvaging, so
+            // only its type is checked
+            let offset = self.file.get_stream().get_position();
+            let header = self.file.read_section_header()?;
+            if header.section_type != RwSections::RwTextureDictionary as u32 {
+                return Err(RwParseError::UnexpectedSection {
+                    expected: RwSections::RwTextureDictionary as u32,
+                    found: header.section_type,
+                    offset,
+                });
+            }
+        } else {
+            self.file.expect_section(RwSections::RwTextureDictionary)?;
+        }
+        self.file.expect_section(RwSections::RwStruct)?;
+
+        let texture_count = self.file.get_stream().read_u16()?;
+        self.file.get_stream().skip(2)?;
+        Ok(texture_count)
+    }
+
+    fn read_texture_dictionary(&mut self) -> RwResult<RwTextureDictionary> {
+        let texture_count = self.read_texture_dictionary_header()?;
+
+        let mut texture_natives = Vec::with_capacity(self.file.bounded_capacity(texture_count as u64, 12));
+        for index in 0..texture_count as usize {
+            let offset = self.file.get_stream().get_position();
+            match self.read_texture_native() {
+                Ok(native) => {
+                    let end = self.file.get_stream().get_position();
+                    self.source_map.record(SourceItem::TextureNative(index), offset, end);
+                    texture_natives.push(native);
+                }
+                Err(error) if self.mode == ParseMode::Lenient => {
+                    self.diagnose(Some(index), offset, error.to_string());
+                    if !self.skip_damaged_section(offset) {
+                        // Nothing after a truncated native can be trusted
+                        let size = self.file.get_stream().get_size();
+                        self.file.get_stream().set_position(size);
+                        break;
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        if self.sanitize_names {
+            texture_natives.iter_mut().for_each(sanitize_native_names);
+        }
+
+        if self.decode_rasters {
+            decode_texture_natives(&mut texture_natives)?;
+        }
+
+        Ok(RwTextureDictionary::new(texture_natives))
+    }
+
+    /// Records a problem recovered from in lenient mode.
+    fn diagnose(&mut self, texture_index: Option<usize>, offset: u64, message: String) {
+        self.report.recovered(offset, message.clone());
+        self.diagnostics.push(TxdDiagnostic { texture_index, offset, message });
+    }
+
+    /// Moves past the texture native starting at `offset` using its section
+    /// size. Returns `false` if the section extends past the end of the file.
+    fn skip_damaged_section(&mut self, offset: u64) -> bool {
+        self.file.get_stream().set_position(offset);
+        let Ok(header) = self.file.read_section_header() else {
+            return false;
+        };
+        let end = self.file.get_stream().get_position() + header.section_size as u64;
+        if header.section_type != RwSections::RwTextureNative as u32 || end > self.file.get_stream().get_size() {
+            return false;
+        }
+        self.file.get_stream().set_position(end);
+        true
+    }
+
+    /// Reads the dictionary's extension, returning the parent dictionary name
+    /// stored in a `String` chunk if there is one. Other plugins are skipped.
+    fn read_texture_dictionary_extension(&mut self) -> RwResult<Option<String>> {
+        let extension_size = self.file.expect_section(RwSections::RwExtension)?.section_size as u64;
+        let end = self.file.get_stream().get_position() + extension_size;
+
+        let mut parent_name = None;
+        while self.file.get_stream().get_position() < end {
+            let offset = self.file.get_stream().get_position();
+            let header = self.file.read_section_header()?;
+            let section_type: Option<RwSections> = FromPrimitive::from_u32(header.section_type);
+            match section_type {
+                Some(RwSections::RwString) => {
+                    let name = self.file.get_stream().read_string(header.section_size as usize)?;
+                    parent_name = Some(name).filter(|name| !name.is_empty());
+                }
+                _ => {
+                    self.report.skipped_section(header.section_type, offset);
+                    self.file.get_stream().skip(header.section_size as u64)?;
+                }
+            }
+        }
+
+        self.file.get_stream().set_position(end);
+        Ok(parent_name)
+    }
+
+    /// Reads a texture native's extension block, keeping unknown plugins as
+    /// raw data.
+    fn read_texture_native_extension(&mut self) -> RwResult<RwTextureExtensions> {
+        let extension_size = self.file.expect_section(RwSections::RwExtension)?.section_size as u64;
+        let end = self.file.get_stream().get_position() + extension_size;
+
+        let mut extensions = RwTextureExtensions::default();
+        while self.file.get_stream().get_position() < end {
+            let offset = self.file.get_stream().get_position();
+            let header = self.file.read_section_header()?;
+            let chunk_end = self.file.get_stream().get_position() + header.section_size as u64;
+            let section_type: Option<RwSections> = FromPrimitive::from_u32(header.section_type);
+            match section_type {
+                Some(RwSections::RwAnisotropyPLG) => {
+                    extensions.anisotropy = Some(self.file.get_stream().read_u32()?);
+                }
+                Some(RwSections::RwSkyMipmapVal) => {
+                    let value = self.file.get_stream().read_u32()?;
+                    extensions.sky_mipmap = Some(RwSkyMipmapValue::from_packed(value));
+                }
+                _ => {
+                    self.report.skipped_section(header.section_type, offset);
+                    extensions.unknown.push(RwUnknownExtension {
+                        section_type: header.section_type,
+                        data: self.file.get_stream().read(header.section_size as usize)?,
+                    });
+                }
+            }
+            self.file.get_stream().set_position(chunk_end);
+        }
+
+        self.file.get_stream().set_position(end);
+        Ok(extensions)
+    }
+
+    /// Reads a texture native, either copying its pixel data into the
+    /// native or keeping it borrowed in `raster_views`.
+    fn read_texture_native(&mut self) -> RwResult<RwTextureNative> {
+        let (mut native, raster) = self.read_texture_native_raster()?;
+        if self.borrow_rasters {
+            self.raster_views.push(raster);
+        } else {
+            native.raw_mipmaps = raster.mipmaps.into_iter().map(Cow::into_owned).collect();
+            native.raw_palette = raster.palette.into_owned();
+        }
+        Ok(native)
+    }
+
+    /// Reads a texture native within the scope of its section, so none of
+    /// its children can extend past it.
+    fn read_texture_native_raster(&mut self) -> RwResult<(RwTextureNative, RwRasterView<'a>)> {
+        let offset = self.file.get_stream().get_position();
+        let header = self.file.expect_section(RwSections::RwTextureNative)?;
+        let scope = self.file.enter_section(&header);
+        let result = self.read_texture_native_payload(offset);
+        let overrun = self.file.leave_section(scope);
+        let value = result?;
+        if overrun > 0 {
+            self.report.suspicious(
+                self.mode,
+                offset,
+                format!("Texture native section was read {} bytes past its end", overrun),
+            )?;
+        }
+
+        let (native, raster) = &value;
+        if let Some(level) = raster.mipmaps.first().filter(|_| native.raster_encoding.is_dxt()) {
+            let expected = mipmap_level_size(native.raster_encoding, native.width as usize, native.height as usize, native.depth);
+            if level.len() < expected {
+                self.report.suspicious(
+                    self.mode,
+                    offset,
+                    format!("Texture {:?} has {} of the {} bytes of its DXT blocks", native.texture_name, level.len(), expected),
+                )?;
+            }
+        }
+        Ok(value)
+    }
+
+    fn read_texture_native_payload(&mut self, offset: u64) -> RwResult<(RwTextureNative, RwRasterView<'a>)> {
+        self.file.expect_section(RwSections::RwStruct)?;
 
         let platform_id = self.file.get_stream().read_u32()?;
         let flags = self.file.get_stream().read_u32()?;
 
+        // GameCube natives keep their platform id big-endian even in
+        // little-endian dictionaries
+        let (big_endian_platform_id, big_endian_flags) = match self.file.get_stream().endianness() {
+            Endianness::Little => (platform_id.swap_bytes(), flags.swap_bytes()),
+            Endianness::Big => (platform_id, flags),
+        };
+        if big_endian_platform_id == PlatformType::GameCube.id() {
+            return self.read_gamecube_texture_native(big_endian_platform_id, big_endian_flags);
+        }
+        match PlatformType::from_id(platform_id) {
+            Some(PlatformType::D3d8 | PlatformType::D3d9) => {}
+            Some(PlatformType::Ps2) => return self.read_ps2_texture_native(platform_id, flags),
+            Some(PlatformType::Xbox) => return self.read_xbox_texture_native(platform_id, flags),
+            Some(PlatformType::MobilePvr) => return self.read_mobile_texture_native(platform_id, flags),
+            _ => return Err(RwParseError::UnsupportedPlatform { platform: platform_id, offset }),
+        }
+
         let filter_mode = (flags & 0xFF) as u8;
         let u_addressing = ((flags & 0xF00) >> 8) as u8;
         let v_addressing = ((flags & 0xF000) >> 12) as u8;
@@ -139,7 +972,7 @@ impl<'a> TxdParser<'a> {
         let mask_name = self.file.get_stream().read_string(32)?;
 
         let raster_format = self.file.get_stream().read_u32()?;
-        let d3d_format = self.file.get_stream().read_string(4)?;
+        let d3d_format_number = self.file.get_stream().read_u32()?;
         let width = self.file.get_stream().read_u16()?;
         let height = self.file.get_stream().read_u16()?;
         let depth = self.file.get_stream().read_u8()?;
@@ -147,44 +980,66 @@ impl<'a> TxdParser<'a> {
         let raster_type = self.file.get_stream().read_u8()?;
         let compression_flags = self.file.get_stream().read_u8()?;
 
-        let alpha = (compression_flags & (1 << 0)) != 0;
-        let cube_texture = (compression_flags & (1 << 1)) != 0;
-        let auto_mip_maps = (compression_flags & (1 << 2)) != 0;
-        let compressed = (compression_flags & (1 << 3)) != 0;
+        // D3D8 stores whether the raster has alpha in place of the D3D
+        // format, and the DXT type in place of the flags
+        let (d3d_format, alpha, cube_texture, auto_mip_maps, compressed) = if platform_id == PlatformType::D3d8.id() {
+            (String::new(), d3d_format_number != 0, false, false, compression_flags != 0)
+        } else {
+            let d3d_format_bytes = d3d_format_number.to_le_bytes();
+            let d3d_format_length = d3d_format_bytes.iter().position(|&c| c == 0).unwrap_or(4);
+            (
+                String::from_utf8_lossy(&d3d_format_bytes[..d3d_format_length]).to_string(),
+                (compression_flags & (1 << 0)) != 0,
+                (compression_flags & (1 << 1)) != 0,
+                (compression_flags & (1 << 2)) != 0,
+                (compression_flags & (1 << 3)) != 0,
+            )
+        };
 
-        let palette_type = (raster_format >> 13) & 0b11;
+        let raster_format_info = RwRasterFormatInfo::from_raster_format(raster_format);
 
-        let mut mipmaps = Vec::new();
-        let palette = if palette_type != PaletteType::PaletteNone as u32 {
-            self.read_palette(palette_type, depth)?
+        let palette = if raster_format_info.palette_type != PaletteType::PaletteNone {
+            self.read_palette(raster_format_info.palette_type, depth)?
         } else {
-            Vec::new()
+            &[]
         };
 
-        for i in 0..mipmap_count {
+        if width == 0 || height == 0 {
+            self.report.suspicious(
+                self.mode,
+                offset,
+                format!("Texture {:?} has a size of {}x{}", texture_name, width, height),
+            )?;
+        }
+
+        let mut raw_mipmaps = Vec::with_capacity(mipmap_count as usize);
+        for level in 0..mipmap_count {
             let raster_size = self.file.get_stream().read_u32()?;
-            let raster = self.file.get_stream().read(raster_size as usize)?;
-            
-            if i == 0 {
-                 let bitmap = if !palette.is_empty() {
-                    // Palette decoding is not implemented yet
-                    vec![]
-                } else if platform_id == PlatformType::D3d8 as u32 && compression_flags != 0 {
-                    self.get_bitmap_with_dxt(&format!("DXT{}", compression_flags), &raster, width, height)?
-                } else if platform_id == PlatformType::D3d9 as u32 && compressed {
-                    self.get_bitmap_with_dxt(&d3d_format, &raster, width, height)?
-                } else {
-                    // Raw RGBA decoding is not implemented yet
-                    vec![]
-                };
-                mipmaps.push(bitmap);
+            if raster_size == 0 {
+                let offset = self.file.get_stream().get_position() - 4;
+                self.report.suspicious(self.mode, offset, format!("Mipmap {} of texture {:?} is empty", level, texture_name))?;
             }
+            raw_mipmaps.push(Cow::Borrowed(self.file.get_stream().read_slice(raster_size as usize)?));
         }
+
+        let raster_encoding = if !palette.is_empty() {
+            RwRasterEncoding::Palettized
+        } else if platform_id == PlatformType::D3d8.id() && compression_flags != 0 {
+            RwRasterEncoding::from_dxt_name(&format!("DXT{}", compression_flags))
+        } else if platform_id == PlatformType::D3d9.id() && compressed {
+            RwRasterEncoding::from_dxt_name(&d3d_format)
+        } else {
+            RwRasterEncoding::Uncompressed
+        };
+        let d3d_format_type = if platform_id == PlatformType::D3d9.id() {
+            FromPrimitive::from_u32(d3d_format_number)
+        } else {
+            raster_encoding.d3d_format()
+        };
         
-        let size = self.file.read_section_header()?.section_size;
-        self.file.get_stream().skip(size as u64)?;
+        let extensions = self.read_texture_native_extension()?;
 
-        Ok(RwTextureNative {
+        Ok((RwTextureNative {
             platform_id,
             filter_mode,
             u_addressing,
@@ -202,28 +1057,791 @@ impl<'a> TxdParser<'a> {
             cube_texture,
             auto_mip_maps,
             compressed,
-            mipmaps,
+            mipmaps: Vec::new(),
+            raw_mipmaps: Vec::new(),
+            raw_palette: Vec::new(),
+            raster_encoding,
+            raster_format_info,
+            d3d_format_type,
+            extensions,
+        }, RwRasterView { mipmaps: raw_mipmaps, palette: Cow::Borrowed(palette) }))
+    }
+
+    fn read_ps2_texture_native(&mut self, platform_id: u32, flags: u32) -> RwResult<(RwTextureNative, RwRasterView<'a>)> {
+        let filter_mode = (flags & 0xFF) as u8;
+        let u_addressing = ((flags & 0xF00) >> 8) as u8;
+        let v_addressing = ((flags & 0xF000) >> 12) as u8;
+
+        let texture_name_size = self.file.expect_section(RwSections::RwString)?.section_size;
+        let texture_name = self.file.get_stream().read_string(texture_name_size as usize)?;
+        let mask_name_size = self.file.expect_section(RwSections::RwString)?.section_size;
+        let mask_name = self.file.get_stream().read_string(mask_name_size as usize)?;
+
+        self.file.expect_section(RwSections::RwStruct)?;
+        self.file.expect_section(RwSections::RwStruct)?; // Raster info
+
+        let width = self.file.get_stream().read_u32()?;
+        let height = self.file.get_stream().read_u32()?;
+        let depth = self.file.get_stream().read_u32()?;
+        let raster_format = self.file.get_stream().read_u32()?;
+        self.file.get_stream().skip(32)?; // TEX0, palette offset, TEX1, MIPTBP1, MIPTBP2
+        let pixel_size = self.file.get_stream().read_u32()?;
+        let palette_size = self.file.get_stream().read_u32()?;
+        self.file.get_stream().skip(8)?; // GPU aligned size, sky mipmap value
+
+        let has_header = (raster_format & 0x20000) != 0;
+        if !has_header && depth == 0 {
+            return Err(RwParseError::InconsistentData {
+                offset: self.file.get_stream().get_position(),
+                message: format!("PS2 texture {:?} has a depth of 0", texture_name),
+            });
+        }
+
+        self.file.expect_section(RwSections::RwStruct)?; // Raster data
+        let data_start = self.file.get_stream().get_position();
+        let pixel_end = data_start + pixel_size as u64;
+
+        let mut levels = Vec::new();
+        let mut swizzled = false;
+        let (mut level_width, mut level_height) = (width, height);
+        while self.file.get_stream().get_position() < pixel_end && levels.len() < MAX_PS2_MIPMAP_LEVELS {
+            let data_size = if has_header {
+                self.file.get_stream().skip(32)?; // GIF tags
+                let transfer_width = self.file.get_stream().read_u32()?;
+                let transfer_height = self.file.get_stream().read_u32()?;
+                self.file.get_stream().skip(24)?;
+                let size = self.file.get_stream().read_u32()? as u64 * 0x10;
+                self.file.get_stream().skip(12)?;
+                if levels.is_empty() {
+                    swizzled = transfer_width != width || transfer_height != height;
+                }
+                size
+            } else {
+                (level_width as u64 * level_height as u64 * depth as u64).div_ceil(8)
+            };
+
+            levels.push(Cow::Borrowed(self.file.get_stream().read_slice(data_size as usize)?));
+            level_width = (level_width / 2).max(1);
+            level_height = (level_height / 2).max(1);
+        }
+        self.file.get_stream().set_position(pixel_end);
+
+        let mut palette = Cow::Borrowed(&[][..]);
+        if raster_format & 0x6000 != 0 {
+            if has_header {
+                self.file.get_stream().skip(0x50)?;
+            }
+            let entries = if depth == 4 { 16 } else { 256 };
+            let stored = self.file.get_stream().read_slice(entries * 4)?;
+            palette = if entries == 256 { Cow::Owned(unclut_palette(stored)) } else { Cow::Borrowed(stored) };
+        }
+        self.file.get_stream().set_position(pixel_end + palette_size as u64);
+
+        let extensions = self.read_texture_native_extension()?;
+
+        let format = (raster_format & 0xF00) >> 8;
+        let alpha = format == RasterFormat::Raster1555 as u32
+            || format == RasterFormat::Raster4444 as u32
+            || format == RasterFormat::Raster8888 as u32;
+
+        Ok((RwTextureNative {
+            platform_id,
+            filter_mode,
+            u_addressing,
+            v_addressing,
+            texture_name,
+            mask_name,
+            raster_format,
+            d3d_format: String::new(),
+            width: width as u16,
+            height: height as u16,
+            depth: depth as u8,
+            mipmap_count: levels.len() as u8,
+            raster_type: (raster_format & 0xFF) as u8,
+            alpha,
+            cube_texture: false,
+            auto_mip_maps: (raster_format & 0x1000) != 0,
+            compressed: false,
+            mipmaps: Vec::new(),
+            raw_mipmaps: Vec::new(),
+            raw_palette: Vec::new(),
+            raster_encoding: RwRasterEncoding::Ps2 { swizzled },
+            raster_format_info: RwRasterFormatInfo::from_raster_format(raster_format),
+            d3d_format_type: None,
+            extensions,
+        }, RwRasterView { mipmaps: levels, palette }))
+    }
+
+    fn read_xbox_texture_native(&mut self, platform_id: u32, flags: u32) -> RwResult<(RwTextureNative, RwRasterView<'a>)> {
+        let filter_mode = (flags & 0xFF) as u8;
+        let u_addressing = ((flags & 0xF00) >> 8) as u8;
+        let v_addressing = ((flags & 0xF000) >> 12) as u8;
+
+        let texture_name = self.file.get_stream().read_string(32)?;
+        let mask_name = self.file.get_stream().read_string(32)?;
+
+        let raster_format = self.file.get_stream().read_u32()?;
+        let alpha = self.file.get_stream().read_u16()? != 0;
+        let cube_texture = self.file.get_stream().read_u16()? != 0;
+        let width = self.file.get_stream().read_u16()?;
+        let height = self.file.get_stream().read_u16()?;
+        let depth = self.file.get_stream().read_u8()?;
+        let mipmap_count = self.file.get_stream().read_u8()?;
+        let raster_type = self.file.get_stream().read_u8()?;
+        let dxt_format = self.file.get_stream().read_u8()?;
+        let total_size = self.file.get_stream().read_u32()?;
+
+        let palette = if raster_format & 0x2000 != 0 {
+            self.file.get_stream().read_slice(256 * 4)?
+        } else if raster_format & 0x4000 != 0 {
+            self.file.get_stream().read_slice(16 * 4)?
+        } else {
+            &[]
+        };
+
+        let raster = self.file.get_stream().read_slice(total_size as usize)?;
+
+        let d3d_format = match dxt_format {
+            0x0C => "DXT1",
+            0x0E => "DXT3",
+            0x0F => "DXT5",
+            _ => "",
+        }.to_string();
+        let compressed = !d3d_format.is_empty();
+
+        let raster_encoding = if compressed {
+            RwRasterEncoding::from_dxt_name(&d3d_format)
+        } else {
+            RwRasterEncoding::XboxSwizzled
+        };
+        let raw_mipmaps = split_mipmap_levels(raster, raster_encoding, width, height, depth, mipmap_count);
+
+        let extensions = self.read_texture_native_extension()?;
+
+        Ok((RwTextureNative {
+            platform_id,
+            filter_mode,
+            u_addressing,
+            v_addressing,
+            texture_name,
+            mask_name,
+            raster_format,
+            d3d_format,
+            width,
+            height,
+            depth,
+            mipmap_count,
+            raster_type,
+            alpha,
+            cube_texture,
+            auto_mip_maps: (raster_format & 0x1000) != 0,
+            compressed,
+            mipmaps: Vec::new(),
+            raw_mipmaps: Vec::new(),
+            raw_palette: Vec::new(),
+            raster_encoding,
+            raster_format_info: RwRasterFormatInfo::from_raster_format(raster_format),
+            d3d_format_type: raster_encoding.d3d_format(),
+            extensions,
+        }, RwRasterView { mipmaps: raw_mipmaps, palette: Cow::Borrowed(palette) }))
+    }
+
+    /// Reads a GameCube texture native. Unlike the chunk headers, every
+    /// field in the struct is stored big-endian, so the stream is switched
+    /// to big-endian for the struct and back for the extension that follows.
+    fn read_gamecube_texture_native(&mut self, platform_id: u32, flags: u32) -> RwResult<(RwTextureNative, RwRasterView<'a>)> {
+        let endianness = self.file.get_stream().endianness();
+        self.file.get_stream().set_endianness(Endianness::Big);
+        let raster = self.read_gamecube_raster(platform_id, flags);
+        self.file.get_stream().set_endianness(endianness);
+
+        let (mut native, view) = raster?;
+        native.extensions = self.read_texture_native_extension()?;
+        Ok((native, view))
+    }
+
+    fn read_gamecube_raster(&mut self, platform_id: u32, flags: u32) -> RwResult<(RwTextureNative, RwRasterView<'a>)> {
+        let filter_mode = (flags & 0xFF) as u8;
+        let u_addressing = ((flags & 0xF00) >> 8) as u8;
+        let v_addressing = ((flags & 0xF000) >> 12) as u8;
+
+        let texture_name = self.file.get_stream().read_string(32)?;
+        let mask_name = self.file.get_stream().read_string(32)?;
+
+        let raster_format = self.file.get_stream().read_u32()?;
+        let gx_format = self.file.get_stream().read_u32()?;
+        let width = self.file.get_stream().read_u16()?;
+        let height = self.file.get_stream().read_u16()?;
+        let depth = self.file.get_stream().read_u8()?;
+        let mipmap_count = self.file.get_stream().read_u8()?;
+        let raster_type = self.file.get_stream().read_u8()?;
+        let gx_flags = self.file.get_stream().read_u8()?;
+
+        // Palettes are always RGB5A3 entries
+        let palette = if raster_format & 0x2000 != 0 {
+            self.file.get_stream().read_slice(256 * 2)?
+        } else if raster_format & 0x4000 != 0 {
+            self.file.get_stream().read_slice(16 * 2)?
+        } else {
+            &[]
+        };
+
+        let total_size = self.file.get_stream().read_u32()?;
+        let raster = self.file.get_stream().read_slice(total_size as usize)?;
+
+        let raster_encoding = RwRasterEncoding::GameCube { format: gx_format };
+        let raw_mipmaps = split_mipmap_levels(raster, raster_encoding, width, height, depth, mipmap_count);
+
+        Ok((RwTextureNative {
+            platform_id,
+            filter_mode,
+            u_addressing,
+            v_addressing,
+            texture_name,
+            mask_name,
+            raster_format,
+            d3d_format: gx_format_name(gx_format).to_string(),
+            width,
+            height,
+            depth,
+            mipmap_count,
+            raster_type,
+            alpha: gx_flags & 1 != 0,
+            cube_texture: false,
+            auto_mip_maps: (raster_format & 0x1000) != 0,
+            compressed: gx_format == GX_TF_CMPR,
+            mipmaps: Vec::new(),
+            raw_mipmaps: Vec::new(),
+            raw_palette: Vec::new(),
+            raster_encoding,
+            raster_format_info: RwRasterFormatInfo::from_raster_format(raster_format),
+            d3d_format_type: None,
+            extensions: RwTextureExtensions::default(),
+        }, RwRasterView { mipmaps: raw_mipmaps, palette: Cow::Borrowed(palette) }))
+    }
+
+    fn read_mobile_texture_native(&mut self, platform_id: u32, flags: u32) -> RwResult<(RwTextureNative, RwRasterView<'a>)> {
+        let filter_mode = (flags & 0xFF) as u8;
+        let u_addressing = ((flags & 0xF00) >> 8) as u8;
+        let v_addressing = ((flags & 0xF000) >> 12) as u8;
+
+        let texture_name = self.file.get_stream().read_string(32)?;
+        let mask_name = self.file.get_stream().read_string(32)?;
+
+        let mipmap_count = self.file.get_stream().read_u8()?;
+        self.file.get_stream().skip(1)?; // Unknown
+        let alpha = self.file.get_stream().read_u8()? != 0;
+        self.file.get_stream().skip(1)?; // Padding
+        let width = self.file.get_stream().read_u16()?;
+        let height = self.file.get_stream().read_u16()?;
+        let internal_format = self.file.get_stream().read_u32()?;
+        let image_size = self.file.get_stream().read_u32()?;
+        self.file.get_stream().skip(4)?; // Unknown
+
+        let raster = self.file.get_stream().read_slice(image_size as usize)?;
+
+        let (d3d_format, depth, raster_encoding) = match internal_format {
+            GL_COMPRESSED_RGB_PVRTC_4BPPV1 | GL_COMPRESSED_RGBA_PVRTC_4BPPV1 => ("PVRTC4", 4, RwRasterEncoding::Pvrtc4),
+            GL_COMPRESSED_RGB_PVRTC_2BPPV1 | GL_COMPRESSED_RGBA_PVRTC_2BPPV1 => ("PVRTC2", 2, RwRasterEncoding::Pvrtc2),
+            GL_ETC1_RGB8 => ("ETC1", 4, RwRasterEncoding::Etc1),
+            _ => ("", 0, RwRasterEncoding::Unknown),
+        };
+        let raw_mipmaps = split_mipmap_levels(raster, raster_encoding, width, height, depth, mipmap_count);
+
+        let extensions = self.read_texture_native_extension()?;
+
+        Ok((RwTextureNative {
+            platform_id,
+            filter_mode,
+            u_addressing,
+            v_addressing,
+            texture_name,
+            mask_name,
+            raster_format: internal_format,
+            d3d_format: d3d_format.to_string(),
+            width,
+            height,
+            depth,
+            mipmap_count,
+            raster_type: 4,
+            alpha,
+            cube_texture: false,
+            auto_mip_maps: false,
+            compressed: !d3d_format.is_empty(),
+            mipmaps: Vec::new(),
+            raw_mipmaps: Vec::new(),
+            raw_palette: Vec::new(),
+            raster_encoding,
+            raster_format_info: RwRasterFormatInfo::default(),
+            d3d_format_type: None,
+            extensions,
+        }, RwRasterView { mipmaps: raw_mipmaps, palette: Cow::default() }))
+    }
+
+    fn read_palette(&mut self, palette_type: PaletteType, depth: u8) -> RwResult<&'a [u8]> {
+        let size = if palette_type == PaletteType::Palette8 { 1024 } else if depth == 4 { 64 } else { 128 };
+        Ok(self.file.get_stream().read_slice(size)?)
+    }
+}
+
+/// The pixel layouts of linear uncompressed D3D rasters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UncompressedFormat {
+    Bgra8888,
+    Bgrx8888,
+    Bgr888,
+    Rgb565,
+    Xrgb1555,
+    Argb1555,
+    Argb4444,
+    L8,
+    A8l8,
+}
+
+impl RwTextureNative {
+    /// Works out the pixel layout of an uncompressed raster, preferring the
+    /// D3D9 format and falling back to the raster format and depth.
+    fn uncompressed_format(&self) -> Option<UncompressedFormat> {
+        let from_d3d = match self.d3d_format_type {
+            Some(D3dFormat::D3dfmtA8r8g8b8) => Some(UncompressedFormat::Bgra8888),
+            Some(D3dFormat::D3dfmtX8r8g8b8) => Some(UncompressedFormat::Bgrx8888),
+            Some(D3dFormat::D3dfmtR5g6b5) => Some(UncompressedFormat::Rgb565),
+            Some(D3dFormat::D3dfmtX1r5g5b5) => Some(UncompressedFormat::Xrgb1555),
+            Some(D3dFormat::D3dfmtA1r5g5b5) => Some(UncompressedFormat::Argb1555),
+            Some(D3dFormat::D3dfmtA4r4g4b4) => Some(UncompressedFormat::Argb4444),
+            Some(D3dFormat::D3dfmtL8) => Some(UncompressedFormat::L8),
+            Some(D3dFormat::D3dfmtA8l8) => Some(UncompressedFormat::A8l8),
+            _ => None,
+        };
+
+        from_d3d.or(match (self.raster_format_info.pixel_format?, self.depth) {
+            (RasterFormat::Raster8888, _) => Some(UncompressedFormat::Bgra8888),
+            (RasterFormat::Raster888, 24) => Some(UncompressedFormat::Bgr888),
+            (RasterFormat::Raster888, _) => Some(UncompressedFormat::Bgrx8888),
+            (RasterFormat::Raster565, _) => Some(UncompressedFormat::Rgb565),
+            (RasterFormat::Raster555, _) => Some(UncompressedFormat::Xrgb1555),
+            (RasterFormat::Raster1555, _) => Some(UncompressedFormat::Argb1555),
+            (RasterFormat::Raster4444, _) => Some(UncompressedFormat::Argb4444),
+            (RasterFormat::RasterLum, 16) => Some(UncompressedFormat::A8l8),
+            (RasterFormat::RasterLum, _) => Some(UncompressedFormat::L8),
         })
     }
+}
+
+fn decode_into_mipmaps(native: &mut RwTextureNative) -> RwResult<()> {
+    if native.mipmap_count > 0 {
+        native.mipmaps = vec![native.decode()?];
+    }
+    Ok(())
+}
 
-    fn read_palette(&mut self, palette_type: u32, depth: u8) -> Result<Vec<u8>> {
-        let size = if palette_type == PaletteType::Palette8 as u32 { 1024 } else if depth == 4 { 64 } else { 128 };
-        self.file.get_stream().read(size)
+#[cfg(not(feature = "parallel"))]
+fn decode_texture_natives(natives: &mut [RwTextureNative]) -> RwResult<()> {
+    natives.iter_mut().try_for_each(decode_into_mipmaps)
+}
+
+/// Decodes every texture on the rayon thread pool.
+#[cfg(feature = "parallel")]
+fn decode_texture_natives(natives: &mut [RwTextureNative]) -> RwResult<()> {
+    use rayon::prelude::*;
+    natives.par_iter_mut().try_for_each(decode_into_mipmaps)
+}
+
+fn decode_dxt(format: texpresso::Format, raster: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut decoded = vec![0; width * height * 4];
+    #[cfg(feature = "fast-dxt")]
+    match format {
+        texpresso::Format::Bc1 => super::dxt::decode_bc1(raster, width, height, &mut decoded),
+        texpresso::Format::Bc2 => super::dxt::decode_bc2(raster, width, height, &mut decoded),
+        texpresso::Format::Bc3 => super::dxt::decode_bc3(raster, width, height, &mut decoded),
+        _ => format.decompress(raster, width, height, &mut decoded),
     }
+    #[cfg(not(feature = "fast-dxt"))]
+    format.decompress(raster, width, height, &mut decoded);
+    decoded
+}
 
-    fn get_bitmap_with_dxt(&self, dxt_type: &str, raster: &[u8], width: u16, height: u16) -> Result<Vec<u8>> {
-        let format = match dxt_type {
-            "DXT1" => texpresso::Format::Bc1,
-            "DXT2" => texpresso::Format::Bc2,
-            "DXT3" => texpresso::Format::Bc2,
-            "DXT4" => texpresso::Format::Bc3,
-            "DXT5" => texpresso::Format::Bc3,
-            _ => return Ok(Vec::new())
+/// Returns the byte size of a single mipmap level in the given encoding.
+fn mipmap_level_size(encoding: RwRasterEncoding, width: usize, height: usize, depth: u8) -> usize {
+    match encoding {
+        RwRasterEncoding::Dxt1 => width.div_ceil(4) * height.div_ceil(4) * 8,
+        RwRasterEncoding::Dxt2 | RwRasterEncoding::Dxt3 | RwRasterEncoding::Dxt4 | RwRasterEncoding::Dxt5 => {
+            width.div_ceil(4) * height.div_ceil(4) * 16
+        }
+        RwRasterEncoding::Pvrtc4 => width.max(8) * height.max(8) / 2,
+        RwRasterEncoding::Pvrtc2 => width.max(16) * height.max(8) / 4,
+        RwRasterEncoding::Etc1 => width.div_ceil(4) * height.div_ceil(4) * 8,
+        RwRasterEncoding::GameCube { format } => {
+            let (block_width, block_height, bits) = gx_block_layout(format);
+            width.next_multiple_of(block_width) * height.next_multiple_of(block_height) * bits / 8
+        }
+        _ => (width * height * depth as usize).div_ceil(8),
+    }
+}
+
+/// Splits a contiguous mip chain into individual levels. Any bytes left
+/// over after the expected levels are appended to the last one.
+fn split_mipmap_levels(data: &[u8], encoding: RwRasterEncoding, width: u16, height: u16, depth: u8, count: u8) -> Vec<Cow<'_, [u8]>> {
+    let mut starts = Vec::with_capacity(count as usize);
+    let (mut level_width, mut level_height) = (width as usize, height as usize);
+    let mut offset = 0;
+
+    for _ in 0..count.max(1) {
+        if offset >= data.len() {
+            break;
+        }
+        starts.push(offset);
+        offset = (offset + mipmap_level_size(encoding, level_width, level_height, depth)).min(data.len());
+        level_width = (level_width / 2).max(1);
+        level_height = (level_height / 2).max(1);
+    }
+
+    // Trailing bytes past the expected chain belong to the last level
+    let ends = starts.iter().skip(1).copied().chain(std::iter::once(data.len()));
+    starts.iter().zip(ends).map(|(&start, end)| Cow::Borrowed(&data[start..end])).collect()
+}
+
+/// Converts a PS2 alpha value (0..=0x80) to the standard 0..=255 range.
+fn ps2_alpha(alpha: u8) -> u8 {
+    ((alpha as u32 * 255) / 0x80).min(255) as u8
+}
+
+/// Decodes linear 4-bit or 8-bit palette indices using an RGBA palette.
+fn decode_palettized_raster(data: &[u8], palette: &[u8], width: usize, height: usize, depth: u8) -> Vec<u8> {
+    let pixel_count = width * height;
+    let indices: Vec<u8> = if depth == 4 {
+        data.iter().flat_map(|byte| [byte & 0x0F, byte >> 4]).take(pixel_count).collect()
+    } else {
+        data.iter().copied().take(pixel_count).collect()
+    };
+
+    let mut bitmap = Vec::with_capacity(pixel_count * 4);
+    for index in indices {
+        let offset = index as usize * 4;
+        bitmap.extend_from_slice(palette.get(offset..offset + 4).unwrap_or(&[0, 0, 0, 0]));
+    }
+    bitmap
+}
+
+/// Decodes a linear uncompressed D3D raster. Luminance formats replicate the
+/// luminance into the red, green and blue channels.
+fn decode_uncompressed_raster(data: &[u8], format: Option<UncompressedFormat>, width: usize, height: usize) -> Vec<u8> {
+    let Some(format) = format else {
+        return Vec::new();
+    };
+    let pixel_count = width * height;
+
+    let bytes_per_pixel = match format {
+        UncompressedFormat::Bgra8888 | UncompressedFormat::Bgrx8888 => 4,
+        UncompressedFormat::Bgr888 => 3,
+        UncompressedFormat::L8 => 1,
+        _ => 2,
+    };
+
+    let mut bitmap = Vec::with_capacity(pixel_count * 4);
+    for pixel in data.chunks_exact(bytes_per_pixel).take(pixel_count) {
+        let value = if bytes_per_pixel == 2 { u16::from_le_bytes([pixel[0], pixel[1]]) } else { 0 };
+        let rgba = match format {
+            UncompressedFormat::Bgra8888 => [pixel[2], pixel[1], pixel[0], pixel[3]],
+            UncompressedFormat::Bgrx8888 | UncompressedFormat::Bgr888 => [pixel[2], pixel[1], pixel[0], 255],
+            UncompressedFormat::Rgb565 => rgb565_to_rgba(value),
+            UncompressedFormat::Xrgb1555 => argb1555_to_rgba(value | 0x8000),
+            UncompressedFormat::Argb1555 => argb1555_to_rgba(value),
+            UncompressedFormat::Argb4444 => [
+                (((value >> 8) & 0xF) * 17) as u8,
+                (((value >> 4) & 0xF) * 17) as u8,
+                ((value & 0xF) * 17) as u8,
+                ((value >> 12) * 17) as u8,
+            ],
+            UncompressedFormat::L8 => [pixel[0], pixel[0], pixel[0], 255],
+            UncompressedFormat::A8l8 => [pixel[0], pixel[0], pixel[0], pixel[1]],
         };
+        bitmap.extend_from_slice(&rgba);
+    }
+    bitmap
+}
 
-        let mut decoded = vec![0; width as usize * height as usize * 4];
-        format.decompress(raster, width as usize, height as usize, &mut decoded);
-        
-        Ok(decoded)
+fn decode_ps2_raster(data: &[u8], palette: &[u8], width: usize, height: usize, depth: u32, swizzled: bool) -> Vec<u8> {
+    let pixel_count = width * height;
+
+    let indices = match depth {
+        8 if swizzled => unswizzle_ps2_8bit(data, width, height),
+        8 => data.to_vec(),
+        4 if swizzled => unswizzle_ps2_4bit(data, width, height),
+        4 => data.iter().flat_map(|byte| [byte & 0x0F, byte >> 4]).collect(),
+        _ => Vec::new(),
+    };
+
+    let mut bitmap = Vec::with_capacity(pixel_count * 4);
+    match depth {
+        4 | 8 => {
+            for i in 0..pixel_count {
+                let index = indices.get(i).copied().unwrap_or(0) as usize * 4;
+                let color = palette.get(index..index + 4).unwrap_or(&[0, 0, 0, 0]);
+                bitmap.extend_from_slice(&[color[0], color[1], color[2], ps2_alpha(color[3])]);
+            }
+        }
+        16 => {
+            for pixel in data.chunks_exact(2).take(pixel_count) {
+                let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+                bitmap.extend_from_slice(&[
+                    expand5(value & 0x1F),
+                    expand5((value >> 5) & 0x1F),
+                    expand5((value >> 10) & 0x1F),
+                    if value & 0x8000 != 0 { 255 } else { 0 },
+                ]);
+            }
+        }
+        32 => {
+            for pixel in data.chunks_exact(4).take(pixel_count) {
+                bitmap.extend_from_slice(&[pixel[0], pixel[1], pixel[2], ps2_alpha(pixel[3])]);
+            }
+        }
+        _ => {}
     }
-}
\ No newline at end of file
+    bitmap
+}
+
+fn decode_xbox_raster(data: &[u8], palette: &[u8], raster_format: u32, width: usize, height: usize, depth: u8) -> Vec<u8> {
+    let bytes_per_pixel = depth as usize / 8;
+    let format = (raster_format & 0xF00) >> 8;
+    let mut bitmap = Vec::with_capacity(width * height * 4);
+
+    for y in 0..height {
+        for x in 0..width {
+            let offset = xbox_swizzled_offset(x, y, width, height);
+
+            if !palette.is_empty() {
+                let index = if depth == 4 {
+                    let byte = data.get(offset / 2).copied().unwrap_or(0);
+                    if offset & 1 == 0 { byte & 0x0F } else { byte >> 4 }
+                } else {
+                    data.get(offset).copied().unwrap_or(0)
+                } as usize * 4;
+                let color = palette.get(index..index + 4).unwrap_or(&[0, 0, 0, 0]);
+                bitmap.extend_from_slice(&[color[2], color[1], color[0], color[3]]);
+                continue;
+            }
+
+            let start = offset * bytes_per_pixel;
+            let Some(pixel) = data.get(start..start + bytes_per_pixel) else {
+                bitmap.extend_from_slice(&[0, 0, 0, 0]);
+                continue;
+            };
+
+            let rgba = match bytes_per_pixel {
+                4 if format == RasterFormat::Raster888 as u32 => [pixel[2], pixel[1], pixel[0], 255],
+                4 => [pixel[2], pixel[1], pixel[0], pixel[3]],
+                2 => {
+                    let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+                    if format == RasterFormat::Raster565 as u32 {
+                        rgb565_to_rgba(value)
+                    } else if format == RasterFormat::Raster4444 as u32 {
+                        [(((value >> 8) & 0xF) * 17) as u8, (((value >> 4) & 0xF) * 17) as u8, ((value & 0xF) * 17) as u8, ((value >> 12) * 17) as u8]
+                    } else {
+                        argb1555_to_rgba(value)
+                    }
+                }
+                1 => [pixel[0], pixel[0], pixel[0], 255],
+                _ => [0, 0, 0, 0],
+            };
+            bitmap.extend_from_slice(&rgba);
+        }
+    }
+    bitmap
+}
+
+/// Decodes the first level of a PVRTC or ETC1 raster into RGBA.
+///
+/// Decoders are only available with the `pvrtc` and `etc1` features; without
+/// them an empty bitmap is returned.
+fn decode_mobile_raster(encoding: RwRasterEncoding, raster: &[u8], width: usize, height: usize) -> Vec<u8> {
+    type Decoder = fn(&[u8], usize, usize, &mut [u32]) -> std::result::Result<(), &'static str>;
+    let decoder: Option<Decoder> = match encoding {
+        #[cfg(feature = "pvrtc")]
+        RwRasterEncoding::Pvrtc4 => Some(texture2ddecoder::decode_pvrtc_4bpp),
+        #[cfg(feature = "pvrtc")]
+        RwRasterEncoding::Pvrtc2 => Some(texture2ddecoder::decode_pvrtc_2bpp),
+        #[cfg(feature = "etc1")]
+        RwRasterEncoding::Etc1 => Some(texture2ddecoder::decode_etc1),
+        _ => None,
+    };
+
+    let mut pixels = vec![0u32; width * height];
+    match decoder {
+        Some(decode) if decode(raster, width, height, &mut pixels).is_ok() => {}
+        _ => return Vec::new(),
+    }
+
+    pixels
+        .iter()
+        .flat_map(|pixel| {
+            let [b, g, r, a] = pixel.to_le_bytes();
+            [r, g, b, a]
+        })
+        .collect()
+}
+
+fn gx_format_name(format: u32) -> &'static str {
+    match format {
+        GX_TF_I4 => "I4",
+        GX_TF_I8 => "I8",
+        GX_TF_IA4 => "IA4",
+        GX_TF_IA8 => "IA8",
+        GX_TF_RGB565 => "RGB565",
+        GX_TF_RGB5A3 => "RGB5A3",
+        GX_TF_RGBA8 => "RGBA8",
+        GX_TF_C4 => "C4",
+        GX_TF_C8 => "C8",
+        GX_TF_CMPR => "CMPR",
+        _ => "",
+    }
+}
+
+/// Returns the tile width, tile height and bits per texel of a GX format.
+fn gx_block_layout(format: u32) -> (usize, usize, usize) {
+    match format {
+        GX_TF_I4 | GX_TF_C4 | GX_TF_CMPR => (8, 8, 4),
+        GX_TF_I8 | GX_TF_IA4 | GX_TF_C8 => (8, 4, 8),
+        GX_TF_RGBA8 => (4, 4, 32),
+        _ => (4, 4, 16),
+    }
+}
+
+/// Expands a 5-bit channel to 8 bits by replicating its top bits, so that
+/// full intensity stays 255.
+fn expand5(value: u16) -> u8 {
+    ((value << 3) | (value >> 2)) as u8
+}
+
+fn expand6(value: u16) -> u8 {
+    ((value << 2) | (value >> 4)) as u8
+}
+
+fn rgb565_to_rgba(value: u16) -> [u8; 4] {
+    [expand5(value >> 11), expand6((value >> 5) & 0x3F), expand5(value & 0x1F), 255]
+}
+
+fn argb1555_to_rgba(value: u16) -> [u8; 4] {
+    let alpha = if value & 0x8000 != 0 { 255 } else { 0 };
+    [expand5((value >> 10) & 0x1F), expand5((value >> 5) & 0x1F), expand5(value & 0x1F), alpha]
+}
+
+fn rgb5a3_to_rgba(value: u16) -> [u8; 4] {
+    if value & 0x8000 != 0 {
+        argb1555_to_rgba(value)
+    } else {
+        let alpha = ((value >> 12) & 0x7) * 255 / 7;
+        [(((value >> 8) & 0xF) * 17) as u8, (((value >> 4) & 0xF) * 17) as u8, ((value & 0xF) * 17) as u8, alpha as u8]
+    }
+}
+
+/// Decodes an 8-byte CMPR sub-block: a DXT1 block with big-endian colors
+/// and most-significant-bit-first indices.
+fn decode_cmpr_block(block: &[u8]) -> [[u8; 4]; 16] {
+    let color0 = u16::from_be_bytes([block[0], block[1]]);
+    let color1 = u16::from_be_bytes([block[2], block[3]]);
+    let (c0, c1) = (rgb565_to_rgba(color0), rgb565_to_rgba(color1));
+    let mix = |a: u8, b: u8, wa: u16, wb: u16| ((a as u16 * wa + b as u16 * wb) / (wa + wb)) as u8;
+
+    let palette = if color0 > color1 {
+        [
+            c0,
+            c1,
+            [mix(c0[0], c1[0], 2, 1), mix(c0[1], c1[1], 2, 1), mix(c0[2], c1[2], 2, 1), 255],
+            [mix(c0[0], c1[0], 1, 2), mix(c0[1], c1[1], 1, 2), mix(c0[2], c1[2], 1, 2), 255],
+        ]
+    } else {
+        [c0, c1, [mix(c0[0], c1[0], 1, 1), mix(c0[1], c1[1], 1, 1), mix(c0[2], c1[2], 1, 1), 255], [0, 0, 0, 0]]
+    };
+
+    let mut texels = [[0; 4]; 16];
+    for (row, &bits) in block[4..8].iter().enumerate() {
+        for column in 0..4 {
+            texels[row * 4 + column] = palette[((bits >> (6 - column * 2)) & 3) as usize];
+        }
+    }
+    texels
+}
+
+/// Decodes the first level of a tiled GameCube raster into RGBA.
+fn decode_gamecube_raster(data: &[u8], palette: &[u8], format: u32, width: usize, height: usize) -> Vec<u8> {
+    let (block_width, block_height, bits) = gx_block_layout(format);
+    let block_size = block_width * block_height * bits / 8;
+    let blocks_per_row = width.div_ceil(block_width);
+    let mut bitmap = vec![0; width * height * 4];
+
+    let palette_color = |index: usize| {
+        palette
+            .get(index * 2..index * 2 + 2)
+            .map_or([0, 0, 0, 0], |entry| rgb5a3_to_rgba(u16::from_be_bytes([entry[0], entry[1]])))
+    };
+
+    for (block_index, block) in data.chunks_exact(block_size).enumerate() {
+        let block_x = (block_index % blocks_per_row) * block_width;
+        let block_y = (block_index / blocks_per_row) * block_height;
+        if block_y >= height {
+            break;
+        }
+
+        let mut texels = vec![[0u8; 4]; block_width * block_height];
+        match format {
+            GX_TF_CMPR => {
+                for (sub_index, sub_block) in block.chunks_exact(8).enumerate() {
+                    let (sub_x, sub_y) = ((sub_index % 2) * 4, (sub_index / 2) * 4);
+                    for (texel_index, texel) in decode_cmpr_block(sub_block).into_iter().enumerate() {
+                        texels[(sub_y + texel_index / 4) * block_width + sub_x + texel_index % 4] = texel;
+                    }
+                }
+            }
+            GX_TF_RGBA8 => {
+                for (i, texel) in texels.iter_mut().enumerate() {
+                    let (ar, gb) = (&block[i * 2..i * 2 + 2], &block[32 + i * 2..32 + i * 2 + 2]);
+                    *texel = [ar[1], gb[0], gb[1], ar[0]];
+                }
+            }
+            GX_TF_I4 | GX_TF_C4 => {
+                for (i, texel) in texels.iter_mut().enumerate() {
+                    let byte = block[i / 2];
+                    let value = if i & 1 == 0 { byte >> 4 } else { byte & 0x0F };
+                    *texel = if format == GX_TF_I4 {
+                        [value * 17, value * 17, value * 17, value * 17]
+                    } else {
+                        palette_color(value as usize)
+                    };
+                }
+            }
+            GX_TF_I8 | GX_TF_IA4 | GX_TF_C8 => {
+                for (texel, &value) in texels.iter_mut().zip(block) {
+                    *texel = match format {
+                        GX_TF_I8 => [value, value, value, value],
+                        GX_TF_IA4 => {
+                            let intensity = (value & 0x0F) * 17;
+                            [intensity, intensity, intensity, (value >> 4) * 17]
+                        }
+                        _ => palette_color(value as usize),
+                    };
+                }
+            }
+            _ => {
+                for (texel, pair) in texels.iter_mut().zip(block.chunks_exact(2)) {
+                    let value = u16::from_be_bytes([pair[0], pair[1]]);
+                    *texel = match format {
+                        GX_TF_IA8 => [pair[1], pair[1], pair[1], pair[0]],
+                        GX_TF_RGB565 => rgb565_to_rgba(value),
+                        GX_TF_RGB5A3 => rgb5a3_to_rgba(value),
+                        _ => [0, 0, 0, 0],
+                    };
+                }
+            }
+        }
+
+        for (i, texel) in texels.iter().enumerate() {
+            let (x, y) = (block_x + i % block_width, block_y + i / block_width);
+            if x < width && y < height {
+                bitmap[(y * width + x) * 4..(y * width + x) * 4 + 4].copy_from_slice(texel);
+            }
+        }
+    }
+    bitmap
+}
@@ -0,0 +1,159 @@
+//! # TXD Statistics
+//!
+//! Summarizes the memory footprint of a texture dictionary: how much video
+//! memory each raster format takes, which textures are the largest, and which
+//! textures share identical pixel data.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use std::fs;
+//!
+//! let txd_data = fs::read("path/to/your.txd").unwrap();
+//! let txd = TxdParser::new(&txd_data).parse_metadata().unwrap();
+//!
+//! let stats = txd.stats();
+//! println!("{} textures, {} bytes", stats.texture_count, stats.total_bytes);
+//! for group in &stats.duplicates {
+//!     println!("identical: {:?}", group);
+//! }
+//! ```
+
+use super::txd_parser::{RwRasterEncoding, RwTextureNative, RwTxd};
+use crate::renderware::utils::image_format_enums::PaletteType;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// The number of entries reported in `TxdStats::largest`.
+const LARGEST_TEXTURE_COUNT: usize = 10;
+
+/// A memory usage summary of a texture dictionary.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TxdStats {
+    pub texture_count: usize,
+    /// Estimated video memory used by all textures, including mipmaps and
+    /// palettes.
+    pub total_bytes: usize,
+    /// Texture count and size per raster encoding, largest first.
+    pub formats: Vec<FormatStats>,
+    /// The largest textures by estimated size, largest first.
+    pub largest: Vec<TextureSize>,
+    pub compressed_count: usize,
+    pub palettized_count: usize,
+    pub uncompressed_count: usize,
+    /// Groups of texture names whose pixel data is identical.
+    pub duplicates: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormatStats {
+    pub encoding: RwRasterEncoding,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextureSize {
+    pub name: String,
+    pub width: u16,
+    pub height: u16,
+    pub bytes: usize,
+}
+
+impl RwTextureNative {
+    /// Estimates the video memory used by this texture.
+    ///
+    /// Uses the size of the stored mip chain and palette when available and
+    /// falls back to 32-bit pixels for textures without raw data.
+    pub fn memory_size(&self) -> usize {
+        if !self.raw_mipmaps.is_empty() {
+            return self.raw_mipmaps.iter().map(Vec::len).sum::<usize>() + self.raw_palette.len();
+        }
+
+        let base = self.width as usize * self.height as usize * 4;
+        if self.mipmap_count > 1 {
+            base * 4 / 3
+        } else {
+            base
+        }
+    }
+}
+
+impl RwTxd {
+    /// Computes memory and format statistics for the dictionary.
+    pub fn stats(&self) -> TxdStats {
+        let natives = &self.texture_dictionary.texture_natives;
+        let mut stats = TxdStats {
+            texture_count: natives.len(),
+            ..Default::default()
+        };
+
+        let mut formats: Vec<FormatStats> = Vec::new();
+        let mut hashes: HashMap<u64, Vec<String>> = HashMap::new();
+        let mut hash_order = Vec::new();
+
+        for native in natives {
+            let bytes = native.memory_size();
+            stats.total_bytes += bytes;
+
+            match formats.iter_mut().find(|format| format.encoding == native.raster_encoding) {
+                Some(format) => {
+                    format.count += 1;
+                    format.bytes += bytes;
+                }
+                None => formats.push(FormatStats {
+                    encoding: native.raster_encoding,
+                    count: 1,
+                    bytes,
+                }),
+            }
+
+            match native.raster_encoding {
+                RwRasterEncoding::Dxt1
+                | RwRasterEncoding::Dxt2
+                | RwRasterEncoding::Dxt3
+                | RwRasterEncoding::Dxt4
+                | RwRasterEncoding::Dxt5
+                | RwRasterEncoding::Pvrtc4
+                | RwRasterEncoding::Pvrtc2
+                | RwRasterEncoding::Etc1 => stats.compressed_count += 1,
+                RwRasterEncoding::GameCube { .. } if native.compressed => stats.compressed_count += 1,
+                RwRasterEncoding::Palettized => stats.palettized_count += 1,
+                _ if native.raster_format_info.palette_type != PaletteType::PaletteNone => {
+                    stats.palettized_count += 1
+                }
+                _ => stats.uncompressed_count += 1,
+            }
+
+            stats.largest.push(TextureSize {
+                name: native.texture_name.clone(),
+                width: native.width,
+                height: native.height,
+                bytes,
+            });
+
+            let hash = native.pixel_hash();
+            let group = hashes.entry(hash).or_default();
+            if group.is_empty() {
+                hash_order.push(hash);
+            }
+            group.push(native.texture_name.clone());
+        }
+
+        formats.sort_by_key(|format| Reverse(format.bytes));
+        stats.formats = formats;
+
+        stats.largest.sort_by_key(|texture| Reverse(texture.bytes));
+        stats.largest.truncate(LARGEST_TEXTURE_COUNT);
+
+        stats.duplicates = hash_order
+            .into_iter()
+            .filter_map(|hash| hashes.remove(&hash))
+            .filter(|group| group.len() > 1)
+            .collect();
+
+        stats
+    }
+}
@@ -0,0 +1,430 @@
+//! # TXD Writer
+//!
+//! A serializer that turns a parsed `RwTxd` back into a RenderWare texture
+//! dictionary for the Direct3D 8 and Direct3D 9 PC platforms.
+//!
+//! ## Features
+//!
+//! - Writes D3D8 and D3D9 texture natives from decoded RGBA bitmaps.
+//! - Re-compresses textures to DXT1, DXT3 or DXT5 (BC1, BC2, BC3).
+//! - Generates 256-color palettes for 8-bit palettized output.
+//! - Optionally builds full mipmap chains.
+//! - Passes original DXT, palettized and uncompressed rasters through unchanged.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::txd::txd_parser::TxdParser;
+//! use rw_parser_rs::renderware::txd::txd_writer::{TxdTextureFormat, TxdWriter};
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/textures.txd").unwrap();
+//! let txd_data = TxdParser::new(&file_data).parse().unwrap();
+//!
+//! let output = TxdWriter::new()
+//!     .with_format(TxdTextureFormat::Dxt5)
+//!     .with_mipmaps(true)
+//!     .write(&txd_data)
+//!     .unwrap();
+//! fs::write("path/to/your/output.txd", output).unwrap();
+//! ```
+
+use super::txd_parser::{RwRasterEncoding, RwTextureExtensions, RwTextureNative, RwTxd};
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_file::RwFileWriter;
+use crate::renderware::rw_sections::RwSections;
+use crate::renderware::utils::image_format_enums::{D3dFormat, PaletteType, PlatformType, RasterFormat};
+use crate::utils::byte_stream::ByteWriter;
+use std::collections::HashMap;
+
+/// The packed library id stamp for RenderWare 3.6.0.3 (San Andreas).
+const DEFAULT_VERSION: u32 = 0x1803FFFF;
+
+/// The raster encoding used for every texture written by a `TxdWriter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxdTextureFormat {
+    /// Uncompressed 32-bit BGRA.
+    Raw8888,
+    /// 8-bit indices into a generated 256-color palette.
+    Palette8,
+    /// BC1 compression with optional 1-bit alpha.
+    Dxt1,
+    /// BC2 compression with explicit 4-bit alpha.
+    Dxt3,
+    /// BC3 compression with interpolated alpha.
+    Dxt5,
+    /// Keeps each texture's original raster data and format where possible.
+    Preserve,
+}
+
+/// The main writer for TXD files.
+///
+/// This struct holds the output options and provides the `write` method to
+/// serialize an `RwTxd` into a texture dictionary buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct TxdWriter {
+    platform: PlatformType,
+    format: TxdTextureFormat,
+    generate_mipmaps: bool,
+    version_number: u32,
+}
+
+impl Default for TxdWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TxdWriter {
+    /// Creates a new `TxdWriter` producing uncompressed D3D9 textures for
+    /// RenderWare 3.6.0.3 without mipmaps.
+    pub fn new() -> Self {
+        TxdWriter {
+            platform: PlatformType::D3d9,
+            format: TxdTextureFormat::Raw8888,
+            generate_mipmaps: false,
+            version_number: DEFAULT_VERSION,
+        }
+    }
+
+    /// Sets the target platform. Only `D3d8` and `D3d9` are supported.
+    pub fn with_platform(mut self, platform: PlatformType) -> Self {
+        self.platform = platform;
+        self
+    }
+
+    /// Sets the raster encoding used for all textures.
+    pub fn with_format(mut self, format: TxdTextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Enables or disables generation of a full mipmap chain.
+    pub fn with_mipmaps(mut self, generate_mipmaps: bool) -> Self {
+        self.generate_mipmaps = generate_mipmaps;
+        self
+    }
+
+    /// Sets the packed RenderWare library id stamp written to every section.
+    pub fn with_version(mut self, version_number: u32) -> Self {
+        self.version_number = version_number;
+        self
+    }
+
+    /// Serializes the texture dictionary.
+    ///
+    /// Every texture must carry its decoded RGBA bitmap in `mipmaps[0]`.
+    ///
+    /// # Returns
+    ///
+    /// A `RwResult` containing the TXD file bytes, or an `UnwritableData`
+    /// error if the platform is unsupported or a texture has no decoded
+    /// pixels.
+    pub fn write(&self, txd: &RwTxd) -> RwResult<Vec<u8>> {
+        let device_id: u16 = match self.platform {
+            PlatformType::D3d8 => 1,
+            PlatformType::D3d9 => 2,
+            _ => return Err(RwParseError::UnwritableData { message: "TxdWriter only supports D3D8 and D3D9".to_string() }),
+        };
+
+        let natives = &txd.texture_dictionary.texture_natives;
+        let mut file = RwFileWriter::new();
+        self.begin_section(&mut file, RwSections::RwTextureDictionary);
+
+        self.begin_section(&mut file, RwSections::RwStruct);
+        file.get_stream().write_u16(natives.len() as u16);
+        file.get_stream().write_u16(device_id);
+        file.end_section();
+
+        for native in natives {
+            self.begin_section(&mut file, RwSections::RwTextureNative);
+            self.write_texture_native(&mut file, native)?;
+            file.end_section();
+        }
+
+        self.begin_section(&mut file, RwSections::RwExtension);
+        if let Some(parent_name) = &txd.parent_name {
+            self.begin_section(&mut file, RwSections::RwString);
+            file.get_stream().write_string(parent_name, (parent_name.len() + 4) & !3);
+            file.end_section();
+        }
+        file.end_section();
+
+        file.end_section();
+        Ok(file.into_bytes())
+    }
+
+    fn begin_section(&self, file: &mut RwFileWriter, section: RwSections) {
+        file.begin_section(section as u32, self.version_number);
+    }
+
+    fn write_texture_native(&self, file: &mut RwFileWriter, native: &RwTextureNative) -> RwResult<()> {
+        let encoded = if self.format == TxdTextureFormat::Preserve {
+            self.preserve_texture(native)?
+        } else {
+            self.encode_texture(native)?
+        };
+
+        self.begin_section(file, RwSections::RwStruct);
+        let stream = file.get_stream();
+        stream.write_u32(self.platform as u32);
+        let flags = native.filter_mode as u32 | (native.u_addressing as u32 & 0xF) << 8 | (native.v_addressing as u32 & 0xF) << 12;
+        stream.write_u32(flags);
+        write_name(stream, &native.texture_name);
+        write_name(stream, &native.mask_name);
+        stream.write_u32(encoded.raster_format);
+
+        let compression_flags = if self.platform == PlatformType::D3d8 {
+            stream.write_u32(encoded.has_alpha as u32);
+            encoded.dxt_type
+        } else {
+            stream.write_u32(encoded.d3d_format);
+            let mut flags = encoded.has_alpha as u8;
+            if native.cube_texture {
+                flags |= 1 << 1;
+            }
+            if native.auto_mip_maps {
+                flags |= 1 << 2;
+            }
+            if encoded.dxt_type != 0 {
+                flags |= 1 << 3;
+            }
+            flags
+        };
+
+        stream.write_u16(native.width);
+        stream.write_u16(native.height);
+        stream.write_u8(encoded.depth);
+        stream.write_u8(encoded.levels.len() as u8);
+        stream.write_u8(4); // Raster type: texture
+        stream.write_u8(compression_flags);
+        stream.write(&encoded.palette);
+
+        for level in &encoded.levels {
+            stream.write_u32(level.len() as u32);
+            stream.write(level);
+        }
+        file.end_section();
+
+        self.begin_section(file, RwSections::RwExtension);
+        self.write_texture_extensions(file, &native.extensions);
+        file.end_section();
+        Ok(())
+    }
+
+    fn write_texture_extensions(&self, file: &mut RwFileWriter, extensions: &RwTextureExtensions) {
+        if let Some(sky_mipmap) = extensions.sky_mipmap {
+            file.write_section(RwSections::RwSkyMipmapVal as u32, self.version_number, &sky_mipmap.to_packed().to_le_bytes());
+        }
+        if let Some(anisotropy) = extensions.anisotropy {
+            file.write_section(RwSections::RwAnisotropyPLG as u32, self.version_number, &anisotropy.to_le_bytes());
+        }
+        for extension in &extensions.unknown {
+            file.write_section(extension.section_type, self.version_number, &extension.data);
+        }
+    }
+
+    fn encode_texture(&self, native: &RwTextureNative) -> RwResult<EncodedTexture> {
+        let (width, height) = (native.width as usize, native.height as usize);
+        let rgba = native.mipmaps.first().filter(|bitmap| bitmap.len() == width * height * 4).ok_or_else(|| {
+            RwParseError::UnwritableData { message: format!("Texture '{}' has no decoded pixels", native.texture_name) }
+        })?;
+
+        let has_alpha = rgba.chunks_exact(4).any(|pixel| pixel[3] != 255);
+        let levels = if self.generate_mipmaps {
+            build_mipmap_chain(rgba, width, height)
+        } else {
+            vec![(rgba.clone(), width, height)]
+        };
+
+        let (raster, depth, d3d_format, dxt_type) = match self.format {
+            TxdTextureFormat::Raw8888 | TxdTextureFormat::Preserve => (RasterFormat::Raster8888, 32, D3dFormat::D3dfmtA8r8g8b8, 0),
+            TxdTextureFormat::Palette8 => (RasterFormat::Raster8888, 8, D3dFormat::D3dfmtP8, 0),
+            TxdTextureFormat::Dxt1 if has_alpha => (RasterFormat::Raster1555, 16, D3dFormat::D3dDxt1, 1),
+            TxdTextureFormat::Dxt1 => (RasterFormat::Raster565, 16, D3dFormat::D3dDxt1, 1),
+            TxdTextureFormat::Dxt3 => (RasterFormat::Raster4444, 16, D3dFormat::D3dDxt3, 3),
+            TxdTextureFormat::Dxt5 => (RasterFormat::Raster4444, 16, D3dFormat::D3dDxt5, 5),
+        };
+
+        let mut raster_format = (raster as u32) << 8;
+        if self.format == TxdTextureFormat::Palette8 {
+            raster_format |= (PaletteType::Palette8 as u32) << 13;
+        }
+        if levels.len() > 1 {
+            raster_format |= 0x8000;
+        }
+
+        let palette = if self.format == TxdTextureFormat::Palette8 {
+            generate_palette(rgba, 256)
+        } else {
+            Vec::new()
+        };
+
+        let mut index_cache = HashMap::new();
+        let levels = levels
+            .iter()
+            .map(|(level, level_width, level_height)| match self.format {
+                TxdTextureFormat::Raw8888 | TxdTextureFormat::Preserve => {
+                    level.chunks_exact(4).flat_map(|p| [p[2], p[1], p[0], p[3]]).collect()
+                }
+                TxdTextureFormat::Palette8 => level
+                    .chunks_exact(4)
+                    .map(|p| nearest_palette_index(&palette, [p[0], p[1], p[2], p[3]], &mut index_cache))
+                    .collect(),
+                TxdTextureFormat::Dxt1 => compress(texpresso::Format::Bc1, level, *level_width, *level_height),
+                TxdTextureFormat::Dxt3 => compress(texpresso::Format::Bc2, level, *level_width, *level_height),
+                TxdTextureFormat::Dxt5 => compress(texpresso::Format::Bc3, level, *level_width, *level_height),
+            })
+            .collect();
+
+        Ok(EncodedTexture {
+            raster_format,
+            d3d_format: d3d_format as u32,
+            depth,
+            dxt_type,
+            has_alpha,
+            palette,
+            levels,
+        })
+    }
+
+    /// Copies the texture's raw rasters unchanged. Textures whose rasters are
+    /// not in a Direct3D layout are re-encoded as uncompressed BGRA instead.
+    fn preserve_texture(&self, native: &RwTextureNative) -> RwResult<EncodedTexture> {
+        let (d3d_format, dxt_type) = match native.raster_encoding {
+            RwRasterEncoding::Dxt1 => (D3dFormat::D3dDxt1 as u32, 1),
+            RwRasterEncoding::Dxt2 => (D3dFormat::D3dDxt2 as u32, 2),
+            RwRasterEncoding::Dxt3 => (D3dFormat::D3dDxt3 as u32, 3),
+            RwRasterEncoding::Dxt4 => (D3dFormat::D3dDxt4 as u32, 4),
+            RwRasterEncoding::Dxt5 => (D3dFormat::D3dDxt5 as u32, 5),
+            RwRasterEncoding::Palettized | RwRasterEncoding::Uncompressed => {
+                let d3d_format = native.d3d_format_type.map_or(0, |format| format as u32);
+                (d3d_format, 0)
+            }
+            _ => return self.encode_texture(native),
+        };
+
+        Ok(EncodedTexture {
+            raster_format: native.raster_format,
+            d3d_format,
+            depth: native.depth,
+            dxt_type,
+            has_alpha: native.alpha,
+            palette: native.raw_palette.clone(),
+            levels: native.raw_mipmaps.clone(),
+        })
+    }
+}
+
+/// A texture's raster data in its final on-disk encoding.
+struct EncodedTexture {
+    raster_format: u32,
+    d3d_format: u32,
+    depth: u8,
+    dxt_type: u8,
+    has_alpha: bool,
+    palette: Vec<u8>,
+    levels: Vec<Vec<u8>>,
+}
+
+/// Writes a 32-byte texture name, keeping its last byte as a terminator.
+fn write_name(stream: &mut ByteWriter, name: &str) {
+    let bytes = name.as_bytes();
+    let length = bytes.len().min(31);
+    stream.write(&bytes[..length]);
+    stream.write(&[0; 32][length..]);
+}
+
+pub(crate) fn compress(format: texpresso::Format, rgba: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut output = vec![0; format.compressed_size(width, height)];
+    format.compress(rgba, width, height, texpresso::Params::default(), &mut output);
+    output
+}
+
+/// Builds a mipmap chain down to 1x1 using a 2x2 box filter.
+pub(crate) fn build_mipmap_chain(rgba: &[u8], width: usize, height: usize) -> Vec<(Vec<u8>, usize, usize)> {
+    let mut levels = vec![(rgba.to_vec(), width, height)];
+    let (mut current_width, mut current_height) = (width, height);
+
+    while current_width > 1 || current_height > 1 {
+        let (previous, previous_width, previous_height) = levels.last().unwrap();
+        let next_width = (current_width / 2).max(1);
+        let next_height = (current_height / 2).max(1);
+        let mut next = Vec::with_capacity(next_width * next_height * 4);
+
+        for y in 0..next_height {
+            for x in 0..next_width {
+                for channel in 0..4 {
+                    let mut sum = 0u32;
+                    for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let sx = (x * 2 + dx).min(previous_width - 1);
+                        let sy = (y * 2 + dy).min(previous_height - 1);
+                        sum += previous[(sy * previous_width + sx) * 4 + channel] as u32;
+                    }
+                    next.push((sum / 4) as u8);
+                }
+            }
+        }
+
+        levels.push((next, next_width, next_height));
+        current_width = next_width;
+        current_height = next_height;
+    }
+    levels
+}
+
+/// Generates an RGBA palette with `size` entries using median-cut quantization.
+pub(crate) fn generate_palette(rgba: &[u8], size: usize) -> Vec<u8> {
+    let mut colors: Vec<[u8; 4]> = rgba.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]).collect();
+    colors.sort_unstable();
+    colors.dedup();
+
+    let mut buckets = vec![colors];
+    while buckets.len() < size {
+        let widest = buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, bucket)| bucket.len() > 1)
+            .map(|(index, bucket)| {
+                let (channel, range) = (0..4)
+                    .map(|channel| {
+                        let min = bucket.iter().map(|c| c[channel]).min().unwrap_or(0);
+                        let max = bucket.iter().map(|c| c[channel]).max().unwrap_or(0);
+                        (channel, max - min)
+                    })
+                    .max_by_key(|(_, range)| *range)
+                    .unwrap_or((0, 0));
+                (index, channel, range)
+            })
+            .max_by_key(|(_, _, range)| *range);
+
+        let Some((index, channel, _)) = widest else { break };
+        let mut bucket = buckets.swap_remove(index);
+        bucket.sort_unstable_by_key(|c| c[channel]);
+        let upper = bucket.split_off(bucket.len() / 2);
+        buckets.push(bucket);
+        buckets.push(upper);
+    }
+
+    let mut palette = Vec::with_capacity(size * 4);
+    for bucket in &buckets {
+        for channel in 0..4 {
+            let sum: u32 = bucket.iter().map(|c| c[channel] as u32).sum();
+            palette.push((sum / bucket.len().max(1) as u32) as u8);
+        }
+    }
+    palette.resize(size * 4, 0);
+    palette
+}
+
+pub(crate) fn nearest_palette_index(palette: &[u8], color: [u8; 4], cache: &mut HashMap<[u8; 4], u8>) -> u8 {
+    *cache.entry(color).or_insert_with(|| {
+        palette
+            .chunks_exact(4)
+            .enumerate()
+            .min_by_key(|(_, entry)| {
+                entry.iter().zip(color.iter()).map(|(&a, &b)| (a as i32 - b as i32).pow(2)).sum::<i32>()
+            })
+            .map_or(0, |(index, _)| index as u8)
+    })
+}
@@ -1,17 +1,71 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use num_derive::FromPrimitive;
+use serde::{Deserialize, Serialize};
+
+/// The palette of a raster, from bits 13 and 14 of its raster format.
+///
+/// The discriminants are `(raster_format >> 13) & 3`: `rwRASTERFORMATPAL8`
+/// is `0x2000` and `rwRASTERFORMATPAL4` is `0x4000`, so 8-bit palettes come
+/// first. Swapping them would read 256-entry palettes as 16-entry ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromPrimitive, Serialize, Deserialize)]
 pub enum PaletteType {
+    #[default]
     PaletteNone = 0,
-    Palette4 = 1,
-    Palette8 = 2,
+    /// `rwRASTERFORMATPAL8`, a 256-entry palette.
+    Palette8 = 1,
+    /// `rwRASTERFORMATPAL4`, a 16-entry palette.
+    Palette4 = 2,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The platform a texture native was built for, from its platform id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PlatformType {
+    Xbox = 5,
+    GameCube = 6,
     D3d8 = 8,
     D3d9 = 9,
+    /// PowerVR compressed textures of the iOS releases.
+    MobilePvr = 10,
+    /// ATI compressed textures of the Android releases.
+    MobileAtc = 11,
+    /// Uncompressed textures of the mobile releases.
+    MobileUncompressed = 12,
+    /// The `PS2\0` four-character code.
+    Ps2 = 0x0032_5350,
+    /// The `PSP\0` four-character code.
+    Psp = 0x0050_5350,
+}
+
+impl PlatformType {
+    /// Maps a texture native platform id to its platform, or `None` for
+    /// unknown ids.
+    pub fn from_id(platform_id: u32) -> Option<PlatformType> {
+        match platform_id {
+            5 => Some(PlatformType::Xbox),
+            6 => Some(PlatformType::GameCube),
+            8 => Some(PlatformType::D3d8),
+            9 => Some(PlatformType::D3d9),
+            10 => Some(PlatformType::MobilePvr),
+            11 => Some(PlatformType::MobileAtc),
+            12 => Some(PlatformType::MobileUncompressed),
+            0x0032_5350 => Some(PlatformType::Ps2),
+            0x0050_5350 => Some(PlatformType::Psp),
+            _ => None,
+        }
+    }
+
+    /// Returns the platform id stored in texture natives.
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    /// Returns whether this parser can decode texture natives of the
+    /// platform.
+    pub fn is_supported(self) -> bool {
+        !matches!(self, PlatformType::MobileAtc | PlatformType::MobileUncompressed | PlatformType::Psp)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, Serialize, Deserialize)]
 pub enum RasterFormat {
     Raster1555 = 0x01,
     Raster565 = 0x02,
@@ -22,12 +76,21 @@ pub enum RasterFormat {
     Raster555 = 0x0A,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, Serialize, Deserialize)]
+#[repr(u32)]
 pub enum D3dFormat {
-    D3dfmtA8l8,
-    D3dDxt1,
-    D3dDxt2,
-    D3dDxt3,
-    D3dDxt4,
-    D3dDxt5,
-}
\ No newline at end of file
+    D3dfmtA8r8g8b8 = 21,
+    D3dfmtX8r8g8b8 = 22,
+    D3dfmtR5g6b5 = 23,
+    D3dfmtX1r5g5b5 = 24,
+    D3dfmtA1r5g5b5 = 25,
+    D3dfmtA4r4g4b4 = 26,
+    D3dfmtP8 = 41,
+    D3dfmtL8 = 50,
+    D3dfmtA8l8 = 51,
+    D3dDxt1 = 0x3154_5844,
+    D3dDxt2 = 0x3254_5844,
+    D3dDxt3 = 0x3354_5844,
+    D3dDxt4 = 0x3454_5844,
+    D3dDxt5 = 0x3554_5844,
+}
@@ -0,0 +1 @@
+pub mod world_parser;
\ No newline at end of file
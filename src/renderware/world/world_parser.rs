@@ -0,0 +1,396 @@
+//! # World Parser
+//!
+//! A parser for RenderWare world files (`.bsp`), which store the static
+//! geometry of a level as a BSP tree of plane sectors with atomic sectors
+//! at its leaves. GTA3, Vice City, and San Andreas do not use worlds, but
+//! many other RenderWare games do.
+//!
+//! ## Features
+//!
+//! - Parses the world header, its material list and the full sector tree.
+//! - Reads the vertices, normals, prelighting, texture coordinates and
+//!   triangles of each atomic sector, as well as its bin mesh.
+//! - Reuses the DFF material and mesh readers.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::world::world_parser::WorldParser;
+//! use std::fs;
+//!
+//! let file_data = fs::read("path/to/your/level.bsp").unwrap();
+//! let world = WorldParser::new(&file_data).parse().unwrap();
+//!
+//! println!("Atomic sector count: {}", world.atomic_sectors().len());
+//! ```
+
+use crate::renderware::common::types::{RwColor, RwTextureCoordinate, RwTriangle, RwVector3};
+use crate::renderware::dff::dff_parser::{DffParser, RwBinMesh, RwMaterialList};
+use crate::renderware::rw_error::{RwParseError, RwResult};
+use crate::renderware::rw_sections::RwSections;
+use crate::utils::rw_version::{unpack_version, version_string};
+
+use serde::Serialize;
+
+const WORLD_TEXTURED: u32 = 0x04;
+const WORLD_PRELIT: u32 = 0x08;
+const WORLD_NORMALS: u32 = 0x10;
+const WORLD_TEXTURED2: u32 = 0x80;
+
+/// The deepest plane sector nesting accepted, whatever the world header
+/// declares, so crafted trees cannot exhaust the stack.
+const MAX_SECTOR_DEPTH: u32 = 256;
+
+/// Represents a parsed RenderWare world.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwWorld {
+    /// The RenderWare version string (e.g., "3.6.0.3").
+    pub version: String,
+    pub version_number: u32,
+    /// The negated world origin.
+    pub inverse_origin: RwVector3,
+    pub triangle_count: u32,
+    pub vertex_count: u32,
+    pub plane_sector_count: u32,
+    pub atomic_sector_count: u32,
+    /// The world format flags, which use the geometry flag bits.
+    pub format_flags: u32,
+    /// The world bounds as `(min, max)`, when stored.
+    pub bounding_box: Option<(RwVector3, RwVector3)>,
+    pub material_list: RwMaterialList,
+    pub root: RwWorldSector,
+}
+
+/// A node of the world's BSP tree.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum RwWorldSector {
+    Plane(Box<RwPlaneSector>),
+    Atomic(RwAtomicSector),
+}
+
+/// An inner node of the BSP tree, splitting space along an axis.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwPlaneSector {
+    /// The split axis: 0 for X, 1 for Y and 2 for Z.
+    pub axis: u32,
+    pub value: f32,
+    /// The extents of the left and right children along the axis, which
+    /// may overlap the split value.
+    pub left_value: f32,
+    pub right_value: f32,
+    pub left: RwWorldSector,
+    pub right: RwWorldSector,
+}
+
+/// A leaf of the BSP tree, holding the geometry inside its bounds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RwAtomicSector {
+    /// The offset added to the material indices of the triangles.
+    pub material_list_window_base: u32,
+    pub min: RwVector3,
+    pub max: RwVector3,
+    pub vertices: Vec<RwVector3>,
+    pub normals: Vec<RwVector3>,
+    pub vertex_colors: Vec<RwColor>,
+    pub texture_coordinates: Vec<Vec<RwTextureCoordinate>>,
+    pub triangles: Vec<RwTriangle>,
+    pub bin_mesh: Option<RwBinMesh>,
+}
+
+impl RwWorld {
+    /// Returns the atomic sectors in tree order, left to right.
+    pub fn atomic_sectors(&self) -> Vec<&RwAtomicSector> {
+        let mut sectors = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(sector) = stack.pop() {
+            match sector {
+                RwWorldSector::Atomic(atomic) => sectors.push(atomic),
+                RwWorldSector::Plane(plane) => {
+                    stack.push(&plane.right);
+                    stack.push(&plane.left);
+                }
+            }
+        }
+        sectors
+    }
+}
+
+/// The main parser for world files.
+pub struct WorldParser<'a> {
+    parser: DffParser<'a>,
+}
+
+impl<'a> WorldParser<'a> {
+    /// Creates a new `WorldParser` instance with the given file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A byte slice containing the raw world file data.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        WorldParser {
+            parser: DffParser::new(buffer),
+        }
+    }
+
+    /// Parses the world section at the start of the buffer.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed `RwWorld` data or a `RwParseError`
+    /// if the buffer does not hold a world or a parsing error occurs.
+    pub fn parse(&mut self) -> RwResult<RwWorld> {
+        let header = self.parser.file().expect_section(RwSections::RwWorld)?;
+        let version_number = unpack_version(header.version_number);
+        let version = version_string(version_number).unwrap_or_default().to_string();
+
+        let struct_header = self.parser.file().expect_section(RwSections::RwStruct)?;
+        let struct_end = self.parser.file().get_stream().get_position() + struct_header.section_size as u64;
+        let stream = self.parser.file().get_stream();
+
+        let root_is_atomic_sector = stream.read_u32()? != 0;
+        let inverse_origin = self.read_vector3()?;
+        if version_number < 0x34000 {
+            self.parser.file().get_stream().skip(12)?; // ambient, specular, diffuse
+        }
+
+        let stream = self.parser.file().get_stream();
+        let triangle_count = stream.read_u32()?;
+        let vertex_count = stream.read_u32()?;
+        let plane_sector_count = stream.read_u32()?;
+        let atomic_sector_count = stream.read_u32()?;
+        let _collision_sector_size = stream.read_u32()?;
+        let format_flags = stream.read_u32()?;
+
+        let bounding_box = if struct_end.saturating_sub(self.parser.file().get_stream().get_position()) >= 24 {
+            let max = self.read_vector3()?;
+            let min = self.read_vector3()?;
+            Some((min, max))
+        } else {
+            None
+        };
+        self.parser.file().get_stream().set_position(struct_end);
+
+        let material_list = self.parser.read_material_list()?;
+
+        let max_depth = plane_sector_count.min(MAX_SECTOR_DEPTH);
+        let root_offset = self.parser.file().get_stream().get_position();
+        let root = self.read_sector(format_flags, 0, max_depth)?;
+        if root_is_atomic_sector != matches!(root, RwWorldSector::Atomic(_)) {
+            return Err(RwParseError::InconsistentData {
+                offset: root_offset,
+                message: format!(
+                    "World root sector is not the {} sector declared by the header",
+                    if root_is_atomic_sector { "atomic" } else { "plane" }
+                ),
+            });
+        }
+
+        Ok(RwWorld {
+            version,
+            version_number,
+            inverse_origin,
+            triangle_count,
+            vertex_count,
+            plane_sector_count,
+            atomic_sector_count,
+            format_flags,
+            bounding_box,
+            material_list,
+            root,
+        })
+    }
+
+    /// Reads the sector at `depth` plane sectors below the root. A tree of
+    /// `n` plane sectors is at most `n` deep, so deeper plane sectors than
+    /// `max_depth` are rejected.
+    fn read_sector(&mut self, format_flags: u32, depth: u32, max_depth: u32) -> RwResult<RwWorldSector> {
+        let offset = self.parser.file().get_stream().get_position();
+        let available = self.parser.file().remaining();
+        if available < 12 {
+            return Err(RwParseError::TruncatedSection { section_type: 0, offset, size: 12, available });
+        }
+        let section_type = self.parser.file().read_section_header()?.section_type;
+        self.parser.file().get_stream().set_position(offset);
+
+        match section_type {
+            section if section == RwSections::RwPlaneSector as u32 => {
+                if depth >= max_depth {
+                    return Err(RwParseError::InconsistentData {
+                        offset,
+                        message: format!("Plane sectors are nested deeper than {} levels", max_depth),
+                    });
+                }
+                self.parser.file().expect_section(RwSections::RwPlaneSector)?;
+                Ok(RwWorldSector::Plane(Box::new(self.read_plane_sector(format_flags, depth, max_depth)?)))
+            }
+            section if section == RwSections::RwAtomicSector as u32 => {
+                let header = self.parser.file().expect_section(RwSections::RwAtomicSector)?;
+                let end = self.parser.file().get_stream().get_position() + header.section_size as u64;
+                let sector = self.read_atomic_sector(format_flags, end)?;
+                self.parser.file().get_stream().set_position(end);
+                Ok(RwWorldSector::Atomic(sector))
+            }
+            section => Err(RwParseError::InconsistentData {
+                offset,
+                message: format!("Unexpected section 0x{:X} in world sector tree", section),
+            }),
+        }
+    }
+
+    fn read_plane_sector(&mut self, format_flags: u32, depth: u32, max_depth: u32) -> RwResult<RwPlaneSector> {
+        let struct_header = self.parser.file().expect_section(RwSections::RwStruct)?;
+        let struct_end = self.parser.file().get_stream().get_position() + struct_header.section_size as u64;
+        let stream = self.parser.file().get_stream();
+
+        // The axis is stored as the byte offset of the component in a vector
+        let axis = stream.read_u32()? / 4;
+        let value = stream.read_f32()?;
+        let _left_is_atomic = stream.read_u32()?;
+        let _right_is_atomic = stream.read_u32()?;
+        let left_value = stream.read_f32()?;
+        let right_value = stream.read_f32()?;
+        stream.set_position(struct_end);
+
+        let left = self.read_sector(format_flags, depth + 1, max_depth)?;
+        let right = self.read_sector(format_flags, depth + 1, max_depth)?;
+
+        Ok(RwPlaneSector {
+            axis,
+            value,
+            left_value,
+            right_value,
+            left,
+            right,
+        })
+    }
+
+    fn read_atomic_sector(&mut self, format_flags: u32, end: u64) -> RwResult<RwAtomicSector> {
+        self.parser.file().expect_section(RwSections::RwStruct)?;
+        let stream = self.parser.file().get_stream();
+        let material_list_window_base = stream.read_u32()?;
+        let triangle_count = stream.read_u32()?;
+        let vertex_count = stream.read_u32()?;
+        let max = self.read_vector3()?;
+        let min = self.read_vector3()?;
+        self.parser.file().get_stream().skip(8)?; // unused
+
+        let mut vertices = Vec::with_capacity(self.parser.file().checked_capacity("vertices", vertex_count as u64, 12)?);
+        for _ in 0..vertex_count {
+            vertices.push(self.read_vector3()?);
+        }
+
+        let mut normals = Vec::new();
+        if format_flags & WORLD_NORMALS != 0 {
+            normals.reserve(vertex_count as usize);
+            let stream = self.parser.file().get_stream();
+            for _ in 0..vertex_count {
+                // Normals are compressed to signed bytes
+                let x = stream.read_u8()? as i8 as f32 / 127.0;
+                let y = stream.read_u8()? as i8 as f32 / 127.0;
+                let z = stream.read_u8()? as i8 as f32 / 127.0;
+                stream.skip(1)?;
+                normals.push(RwVector3 { x, y, z });
+            }
+        }
+
+        let mut vertex_colors = Vec::new();
+        if format_flags & WORLD_PRELIT != 0 {
+            vertex_colors.reserve(vertex_count as usize);
+            let stream = self.parser.file().get_stream();
+            for _ in 0..vertex_count {
+                vertex_colors.push(RwColor {
+                    r: stream.read_u8()?,
+                    g: stream.read_u8()?,
+                    b: stream.read_u8()?,
+                    a: stream.read_u8()?,
+                });
+            }
+        }
+
+        let mut texture_coordinates = Vec::new();
+        for _ in 0..texture_coordinate_sets(format_flags) {
+            let stream = self.parser.file().get_stream();
+            let mut coordinates = Vec::with_capacity(vertex_count as usize);
+            for _ in 0..vertex_count {
+                coordinates.push(RwTextureCoordinate {
+                    u: stream.read_f32()?,
+                    v: stream.read_f32()?,
+                });
+            }
+            texture_coordinates.push(coordinates);
+        }
+
+        let mut triangles = Vec::with_capacity(self.parser.file().checked_capacity("triangles", triangle_count as u64, 8)?);
+        let stream = self.parser.file().get_stream();
+        for _ in 0..triangle_count {
+            let material_id = stream.read_u16()?;
+            let vertex1 = stream.read_u16()?;
+            let vertex2 = stream.read_u16()?;
+            let vertex3 = stream.read_u16()?;
+            triangles.push(RwTriangle {
+                vector: RwVector3 {
+                    x: vertex1 as f32,
+                    y: vertex2 as f32,
+                    z: vertex3 as f32,
+                },
+                material_id,
+            });
+        }
+
+        let bin_mesh = self.read_sector_extension(end)?;
+
+        Ok(RwAtomicSector {
+            material_list_window_base,
+            min,
+            max,
+            vertices,
+            normals,
+            vertex_colors,
+            texture_coordinates,
+            triangles,
+            bin_mesh,
+        })
+    }
+
+    fn read_vector3(&mut self) -> RwResult<RwVector3> {
+        let stream = self.parser.file().get_stream();
+        Ok(RwVector3 {
+            x: stream.read_f32()?,
+            y: stream.read_f32()?,
+            z: stream.read_f32()?,
+        })
+    }
+
+    /// Reads the bin mesh from an atomic sector's extension, if present.
+    fn read_sector_extension(&mut self, end: u64) -> RwResult<Option<RwBinMesh>> {
+        if self.parser.file().get_stream().get_position() + 12 > end {
+            return Ok(None);
+        }
+
+        let header = self.parser.file().read_section_header()?;
+        if header.section_type != RwSections::RwExtension as u32 {
+            return Ok(None);
+        }
+
+        let extension_end = self.parser.file().get_stream().get_position() + header.section_size as u64;
+        while self.parser.file().get_stream().get_position() + 12 <= extension_end {
+            let position = self.parser.file().get_stream().get_position();
+            let plugin = self.parser.file().read_section_header()?;
+            if plugin.section_type == RwSections::RwBinMeshPLG as u32 {
+                self.parser.file().get_stream().set_position(position);
+                return Ok(Some(self.parser.read_bin_mesh()?));
+            }
+            self.parser.file().get_stream().skip(plugin.section_size as u64)?;
+        }
+
+        Ok(None)
+    }
+}
+
+fn texture_coordinate_sets(format_flags: u32) -> u32 {
+    match (format_flags >> 16) & 0xFF {
+        0 if format_flags & WORLD_TEXTURED2 != 0 => 2,
+        0 if format_flags & WORLD_TEXTURED != 0 => 1,
+        sets => sets,
+    }
+}
@@ -0,0 +1,339 @@
+//! # Scene
+//!
+//! Aggregates the parsed models, texture dictionaries, collisions and
+//! placements of a map, with the references between them resolved, as a
+//! single starting point for converting whole maps.
+//!
+//! ## Features
+//!
+//! - Takes model definitions and texture dictionary parents from IDE files,
+//!   placements from text and binary IPL files, and collision models from
+//!   COL files.
+//! - Loads the DFF and the texture dictionary chain of every placed model
+//!   from an `AssetDatabase`.
+//! - Resolves each placement to its model, textures, collision and LOD
+//!   placement, with its world transform.
+//! - Splits the placements into square chunks of the map, for streaming or
+//!   exporting the world piece by piece.
+//!
+//! Lookups by name ignore case, as the games do.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::assets::asset_database::AssetDatabase;
+//! use rw_parser_rs::renderware::ide::ide_parser::IdeParser;
+//! use rw_parser_rs::renderware::img::img_archive::ImgArchive;
+//! use rw_parser_rs::renderware::ipl::ipl_parser::IplParser;
+//! use rw_parser_rs::scene::Scene;
+//! use std::fs;
+//!
+//! let mut scene = Scene::new();
+//! scene.add_ide(&IdeParser::new(&fs::read("path/to/vegepart.ide").unwrap()).parse().unwrap());
+//! scene.add_ipl(IplParser::new(&fs::read("path/to/countn2.ipl").unwrap()).parse().unwrap());
+//!
+//! let mut database = AssetDatabase::new();
+//! database.add_archive(ImgArchive::open_path("path/to/gta3.img").unwrap());
+//! scene.load_assets(&mut database).unwrap();
+//!
+//! for chunk in scene.chunks(500.0) {
+//!     println!("Chunk {}, {}: {} instances", chunk.x, chunk.y, chunk.instances.len());
+//! }
+//! ```
+
+use crate::renderware::assets::asset_database::{AssetDatabase, ModelDefinition};
+use crate::renderware::col::col_parser::{ColModel, RwCol};
+use crate::renderware::common::types::{RwMatrix4, RwQuaternion, RwVector3, RwVector4};
+use crate::renderware::dff::dff_parser::RwDff;
+use crate::renderware::ide::ide_parser::RwIde;
+use crate::renderware::ipl::ipl_parser::{IplInstance, RwIpl};
+use crate::renderware::txd::txd_parser::{RwTextureNative, RwTxd};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{Read, Result, Seek};
+
+use serde::Serialize;
+
+/// A defined model and its parsed DFF, once loaded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SceneModel {
+    pub definition: ModelDefinition,
+    pub dff: Option<RwDff>,
+}
+
+/// A placement, with its LOD resolved across all IPL files of the scene.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScenePlacement {
+    pub instance: IplInstance,
+    /// The index in `Scene::placements` of the lower detail placement shown
+    /// at a distance.
+    pub lod: Option<usize>,
+}
+
+/// A placement with everything it references.
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneInstance<'a> {
+    /// The index of the placement in `Scene::placements`.
+    pub index: usize,
+    pub placement: &'a ScenePlacement,
+    /// The model, if an IDE file of the scene defines it.
+    pub model: Option<&'a SceneModel>,
+    /// The texture dictionaries of the model, from its own to its farthest
+    /// parent, skipping the ones that are not loaded.
+    pub textures: Vec<&'a RwTxd>,
+    pub collision: Option<&'a ColModel>,
+    /// The transform from model space to world space.
+    pub transform: RwMatrix4,
+}
+
+/// The instances whose position falls within a square of the map.
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneChunk<'a> {
+    /// The column of the chunk, counted from X = 0.
+    pub x: i32,
+    /// The row of the chunk, counted from Y = 0.
+    pub y: i32,
+    pub instances: Vec<SceneInstance<'a>>,
+}
+
+/// Models, textures, collisions and placements of a map.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Scene {
+    /// The models by lowercase name.
+    models: HashMap<String, SceneModel>,
+    /// The lowercase model names by id.
+    model_ids: HashMap<i32, String>,
+    /// The lowercase parent of each lowercase texture dictionary name.
+    txd_parents: HashMap<String, String>,
+    /// The texture dictionaries by lowercase name.
+    textures: HashMap<String, RwTxd>,
+    /// The collision models by lowercase model name.
+    collisions: HashMap<String, ColModel>,
+    placements: Vec<ScenePlacement>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the model definitions and texture dictionary parents of an IDE
+    /// file. Later definitions of the same model take precedence.
+    pub fn add_ide(&mut self, ide: &RwIde) {
+        for definition in ModelDefinition::from_ide(ide) {
+            let key = definition.model_name.to_lowercase();
+            self.model_ids.insert(definition.id, key.clone());
+            let dff = self.models.remove(&key).and_then(|model| model.dff);
+            self.models.insert(key, SceneModel { definition, dff });
+        }
+        for link in &ide.texture_parents {
+            self.txd_parents.insert(link.txd_name.to_lowercase(), link.parent_name.to_lowercase());
+        }
+    }
+
+    /// Adds the placements of an IPL file, turning their LOD indices, which
+    /// count from the start of the file, into indices of the scene.
+    pub fn add_ipl(&mut self, ipl: RwIpl) {
+        let base = self.placements.len();
+        let count = ipl.instances.len();
+        self.placements.extend(ipl.instances.into_iter().map(|instance| {
+            let lod = usize::try_from(instance.lod).ok().filter(|&lod| lod < count).map(|lod| base + lod);
+            ScenePlacement { instance, lod }
+        }));
+    }
+
+    /// Adds the collision models of a COL file, matched to models by name.
+    pub fn add_col(&mut self, col: RwCol) {
+        for model in col.models {
+            self.collisions.insert(model.name.to_lowercase(), model);
+        }
+    }
+
+    /// Sets the parsed DFF of a model defined by an IDE file of the scene.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no IDE file defines the model, in which case the DFF is
+    /// dropped.
+    pub fn add_dff(&mut self, model_name: &str, dff: RwDff) -> bool {
+        match self.models.get_mut(&model_name.to_lowercase()) {
+            Some(model) => {
+                model.dff = Some(dff);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets a parsed texture dictionary by name.
+    pub fn add_txd(&mut self, txd_name: &str, txd: RwTxd) {
+        self.textures.insert(txd_name.to_lowercase(), txd);
+    }
+
+    /// Loads the DFF and the texture dictionary chain of every placed model
+    /// that is defined but not loaded yet. Files missing from the archives
+    /// are skipped.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is an `std::io::Error` if a file cannot be read or
+    /// parsed.
+    pub fn load_assets<R: Read + Seek>(&mut self, database: &mut AssetDatabase<R>) -> Result<()> {
+        let mut placed: Vec<String> = self
+            .placements
+            .iter()
+            .filter_map(|placement| self.model_key(&placement.instance).map(str::to_string))
+            .collect();
+        placed.sort_unstable();
+        placed.dedup();
+
+        for key in placed {
+            let Some(model) = self.models.get(&key) else {
+                continue;
+            };
+            let txd_name = model.definition.txd_name.clone();
+            if model.dff.is_none() {
+                let dff = database.read_dff(&model.definition.model_name)?;
+                if let Some(model) = self.models.get_mut(&key) {
+                    model.dff = dff;
+                }
+            }
+            for txd_name in self.txd_chain(&txd_name) {
+                if let Entry::Vacant(entry) = self.textures.entry(txd_name)
+                    && let Some(txd) = database.read_txd(entry.key())?
+                {
+                    entry.insert(txd);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn placements(&self) -> &[ScenePlacement] {
+        &self.placements
+    }
+
+    /// Returns a model by name.
+    pub fn model(&self, model_name: &str) -> Option<&SceneModel> {
+        self.models.get(&model_name.to_lowercase())
+    }
+
+    pub fn model_by_id(&self, id: i32) -> Option<&SceneModel> {
+        self.model_ids.get(&id).and_then(|key| self.models.get(key))
+    }
+
+    /// Returns a texture dictionary by name.
+    pub fn txd(&self, txd_name: &str) -> Option<&RwTxd> {
+        self.textures.get(&txd_name.to_lowercase())
+    }
+
+    /// Returns the collision model of a model.
+    pub fn collision(&self, model_name: &str) -> Option<&ColModel> {
+        self.collisions.get(&model_name.to_lowercase())
+    }
+
+    /// Returns the loaded texture dictionaries of a model, from its own to
+    /// its farthest parent.
+    pub fn model_textures(&self, model_name: &str) -> Vec<&RwTxd> {
+        let Some(model) = self.model(model_name) else {
+            return Vec::new();
+        };
+        self.txd_chain(&model.definition.txd_name).iter().filter_map(|name| self.textures.get(name)).collect()
+    }
+
+    /// Finds a texture of a model in its texture dictionary or one of its
+    /// parents, the way the game does.
+    pub fn find_texture(&self, model_name: &str, texture_name: &str) -> Option<&RwTextureNative> {
+        self.model_textures(model_name).into_iter().find_map(|txd| txd.texture_dictionary.get_by_name(texture_name))
+    }
+
+    /// Resolves a placement to its model, textures, collision and transform.
+    pub fn instance(&self, index: usize) -> Option<SceneInstance<'_>> {
+        let placement = self.placements.get(index)?;
+        let key = self.model_key(&placement.instance);
+        let model = key.and_then(|key| self.models.get(key));
+        Some(SceneInstance {
+            index,
+            placement,
+            model,
+            textures: model.map_or_else(Vec::new, |model| self.model_textures(&model.definition.model_name)),
+            collision: key.and_then(|key| self.collisions.get(key)),
+            transform: placement_transform(&placement.instance),
+        })
+    }
+
+    /// Returns the instances whose position falls within the chunk of the
+    /// given column and row, for chunks of `size` units.
+    pub fn instantiate_chunk(&self, x: i32, y: i32, size: f32) -> SceneChunk<'_> {
+        let instances = (0..self.placements.len())
+            .filter(|&index| chunk_of(&self.placements[index].instance, size) == (x, y))
+            .filter_map(|index| self.instance(index))
+            .collect();
+        SceneChunk { x, y, instances }
+    }
+
+    /// Splits every placement into chunks of `size` units, in order of row
+    /// then column. Only chunks with instances are returned.
+    pub fn chunks(&self, size: f32) -> Vec<SceneChunk<'_>> {
+        let mut chunks: HashMap<(i32, i32), Vec<SceneInstance<'_>>> = HashMap::new();
+        for index in 0..self.placements.len() {
+            let cell = chunk_of(&self.placements[index].instance, size);
+            if let Some(instance) = self.instance(index) {
+                chunks.entry(cell).or_default().push(instance);
+            }
+        }
+        let mut chunks: Vec<SceneChunk<'_>> = chunks.into_iter().map(|((x, y), instances)| SceneChunk { x, y, instances }).collect();
+        chunks.sort_by_key(|chunk| (chunk.y, chunk.x));
+        chunks
+    }
+
+    /// Returns the lowercase name of the model a placement refers to, by id
+    /// or, for text IPL files, by name.
+    fn model_key(&self, instance: &IplInstance) -> Option<&str> {
+        self.model_ids.get(&instance.id).map(String::as_str).or_else(|| {
+            let name = instance.model_name.as_ref()?.to_lowercase();
+            self.models.get_key_value(&name).map(|(key, _)| key.as_str())
+        })
+    }
+
+    /// Returns a texture dictionary name followed by its parents, lowercase.
+    fn txd_chain(&self, txd_name: &str) -> Vec<String> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut next = Some(txd_name.to_lowercase()).filter(|name| !name.is_empty());
+        while let Some(name) = next.take() {
+            // Guard against parent cycles
+            if chain.contains(&name) {
+                break;
+            }
+            next = self.txd_parents.get(&name).cloned();
+            chain.push(name);
+        }
+        chain
+    }
+}
+
+fn chunk_of(instance: &IplInstance, size: f32) -> (i32, i32) {
+    let size = size.max(f32::EPSILON);
+    ((instance.position.x / size).floor() as i32, (instance.position.y / size).floor() as i32)
+}
+
+/// Returns the world transform of a placement. The games store the inverse
+/// of the rotation, so it is conjugated here.
+fn placement_transform(instance: &IplInstance) -> RwMatrix4 {
+    let rotation = instance.rotation;
+    let matrix = RwQuaternion { x: -rotation.x, y: -rotation.y, z: -rotation.z, w: rotation.w }.to_matrix3();
+    let scale = instance.scale;
+    let (scale_x, scale_y, scale_z) = if scale.x == 0.0 && scale.y == 0.0 && scale.z == 0.0 { (1.0, 1.0, 1.0) } else { (scale.x, scale.y, scale.z) };
+    let row = |axis: RwVector3, factor: f32| RwVector4 {
+        x: axis.x * factor,
+        y: axis.y * factor,
+        z: axis.z * factor,
+        t: 0.0,
+    };
+    RwMatrix4 {
+        right: row(matrix.right, scale_x),
+        up: row(matrix.up, scale_y),
+        at: row(matrix.at, scale_z),
+        transform: RwVector4 { x: instance.position.x, y: instance.position.y, z: instance.position.z, t: 1.0 },
+    }
+}
@@ -1,52 +1,187 @@
-use std::io::{Cursor, Read, Seek, SeekFrom};
-use byteorder::{LittleEndian, ReadBytesExt};
+use crate::renderware::common::types::RwVector3;
+use std::io::{Cursor, Seek, SeekFrom};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+
+/// The byte order of multi-byte values. PC, Xbox and PS2 assets are
+/// little-endian, while GameCube, Wii and PS3 assets are big-endian.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Decodes a `u32` stored in this byte order.
+    pub fn u32_from_bytes(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    /// Encodes a `u32` in this byte order.
+    pub fn u32_to_bytes(self, value: u32) -> [u8; 4] {
+        match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        }
+    }
+}
 
 pub struct ByteStream<'a> {
     cursor: Cursor<&'a [u8]>,
+    endianness: Endianness,
 }
 
 impl<'a> ByteStream<'a> {
     pub fn new(stream: &'a [u8]) -> Self {
+        ByteStream::with_endianness(stream, Endianness::Little)
+    }
+
+    pub fn with_endianness(stream: &'a [u8], endianness: Endianness) -> Self {
         ByteStream {
             cursor: Cursor::new(stream),
+            endianness,
         }
     }
 
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Changes the byte order of the following reads, for files that mix
+    /// little-endian headers with big-endian payloads.
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
     pub fn read_u8(&mut self) -> std::io::Result<u8> {
         self.cursor.read_u8()
     }
 
     pub fn read_u16(&mut self) -> std::io::Result<u16> {
-        self.cursor.read_u16::<LittleEndian>()
+        match self.endianness {
+            Endianness::Little => self.cursor.read_u16::<LittleEndian>(),
+            Endianness::Big => self.cursor.read_u16::<BigEndian>(),
+        }
     }
 
     pub fn read_u32(&mut self) -> std::io::Result<u32> {
-        self.cursor.read_u32::<LittleEndian>()
+        match self.endianness {
+            Endianness::Little => self.cursor.read_u32::<LittleEndian>(),
+            Endianness::Big => self.cursor.read_u32::<BigEndian>(),
+        }
     }
 
     pub fn read_i16(&mut self) -> std::io::Result<i16> {
-        self.cursor.read_i16::<LittleEndian>()
+        match self.endianness {
+            Endianness::Little => self.cursor.read_i16::<LittleEndian>(),
+            Endianness::Big => self.cursor.read_i16::<BigEndian>(),
+        }
     }
 
     pub fn read_i32(&mut self) -> std::io::Result<i32> {
-        self.cursor.read_i32::<LittleEndian>()
+        match self.endianness {
+            Endianness::Little => self.cursor.read_i32::<LittleEndian>(),
+            Endianness::Big => self.cursor.read_i32::<BigEndian>(),
+        }
     }
 
     pub fn read_f32(&mut self) -> std::io::Result<f32> {
-        self.cursor.read_f32::<LittleEndian>()
+        match self.endianness {
+            Endianness::Little => self.cursor.read_f32::<LittleEndian>(),
+            Endianness::Big => self.cursor.read_f32::<BigEndian>(),
+        }
+    }
+
+    /// Fills `output` with floats, converting the whole range at once
+    /// instead of reading the values one by one.
+    pub fn read_f32_into(&mut self, output: &mut [f32]) -> std::io::Result<()> {
+        let bytes = self.read_elements(output.len(), 4)?;
+        match self.endianness {
+            Endianness::Little => LittleEndian::read_f32_into(bytes, output),
+            Endianness::Big => BigEndian::read_f32_into(bytes, output),
+        }
+        Ok(())
+    }
+
+    /// Reads `count` floats at once.
+    pub fn read_f32_slice(&mut self, count: usize) -> std::io::Result<Vec<f32>> {
+        let bytes = self.read_elements(count, 4)?;
+        let mut output = vec![0.0; count];
+        match self.endianness {
+            Endianness::Little => LittleEndian::read_f32_into(bytes, &mut output),
+            Endianness::Big => BigEndian::read_f32_into(bytes, &mut output),
+        }
+        Ok(output)
+    }
+
+    /// Reads `count` 16-bit integers at once.
+    pub fn read_u16_slice(&mut self, count: usize) -> std::io::Result<Vec<u16>> {
+        let bytes = self.read_elements(count, 2)?;
+        let mut output = vec![0; count];
+        match self.endianness {
+            Endianness::Little => LittleEndian::read_u16_into(bytes, &mut output),
+            Endianness::Big => BigEndian::read_u16_into(bytes, &mut output),
+        }
+        Ok(output)
     }
 
+    /// Reads `count` vectors of three floats at once.
+    pub fn read_vec3s(&mut self, count: usize) -> std::io::Result<Vec<RwVector3>> {
+        let bytes = self.read_elements(count, 12)?;
+        let read: fn(&[u8]) -> f32 = match self.endianness {
+            Endianness::Little => LittleEndian::read_f32,
+            Endianness::Big => BigEndian::read_f32,
+        };
+        Ok(bytes
+            .chunks_exact(12)
+            .map(|vector| RwVector3 {
+                x: read(&vector[0..4]),
+                y: read(&vector[4..8]),
+                z: read(&vector[8..12]),
+            })
+            .collect())
+    }
+
+    /// Borrows the bytes of `count` elements of `size` bytes, failing before
+    /// anything is allocated if they are not all there.
+    fn read_elements(&mut self, count: usize, size: usize) -> std::io::Result<&'a [u8]> {
+        let length = count.checked_mul(size).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")
+        })?;
+        self.read_slice(length)
+    }
+
+    /// Reads a zero-padded string field of `size` bytes. Fails before
+    /// allocating if the field runs past the end of the buffer.
     pub fn read_string(&mut self, size: usize) -> std::io::Result<String> {
-        let mut buf = vec![0; size];
-        self.cursor.read_exact(&mut buf)?;
+        let buf = self.read_slice(size)?;
         let pos = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
         Ok(String::from_utf8_lossy(&buf[..pos]).to_string())
     }
 
+    /// Copies `size` bytes. Fails before allocating if they are not all
+    /// there, so sizes read from the file cannot cause huge allocations.
     pub fn read(&mut self, size: usize) -> std::io::Result<Vec<u8>> {
-        let mut buf = vec![0; size];
-        self.cursor.read_exact(&mut buf)?;
-        Ok(buf)
+        Ok(self.read_slice(size)?.to_vec())
+    }
+
+    /// Reads `size` bytes without copying them, borrowing from the buffer.
+    pub fn read_slice(&mut self, size: usize) -> std::io::Result<&'a [u8]> {
+        let buffer: &'a [u8] = self.cursor.get_ref();
+        let start = self.cursor.position() as usize;
+        let end = start.checked_add(size).filter(|&end| end <= buffer.len()).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")
+        })?;
+        self.cursor.set_position(end as u64);
+        Ok(&buffer[start..end])
+    }
+
+    /// Returns the whole underlying buffer, regardless of the position.
+    pub fn buffer(&self) -> &'a [u8] {
+        self.cursor.get_ref()
     }
 
     pub fn get_size(&self) -> u64 {
@@ -57,6 +192,11 @@ impl<'a> ByteStream<'a> {
         self.cursor.position()
     }
 
+    /// Returns the number of bytes after the position.
+    pub fn remaining(&self) -> u64 {
+        self.get_size().saturating_sub(self.get_position())
+    }
+
     pub fn set_position(&mut self, position: u64) {
         self.cursor.set_position(position);
     }
@@ -64,4 +204,84 @@ impl<'a> ByteStream<'a> {
     pub fn skip(&mut self, size: u64) -> std::io::Result<u64> {
         self.cursor.seek(SeekFrom::Current(size as i64))
     }
+}
+
+/// The writable counterpart of `ByteStream`, which appends little-endian
+/// values to a growing buffer.
+#[derive(Debug, Clone, Default)]
+pub struct ByteWriter {
+    buffer: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        ByteWriter { buffer: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        ByteWriter {
+            buffer: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.buffer.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_i16(&mut self, value: i16) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes `text` into a field of `size` bytes, truncated or padded with
+    /// zeros as `read_string` expects.
+    pub fn write_string(&mut self, text: &str, size: usize) {
+        let bytes = text.as_bytes();
+        let length = bytes.len().min(size);
+        self.buffer.extend_from_slice(&bytes[..length]);
+        self.buffer.resize(self.buffer.len() + size - length, 0);
+    }
+
+    pub fn write(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Overwrites the `u32` at `position`, to fill in sizes and counts that
+    /// are only known after the data following them has been written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the four bytes at `position` have not been written yet.
+    pub fn patch_u32(&mut self, position: u64, value: u32) {
+        let position = position as usize;
+        self.buffer[position..position + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Returns the data written so far.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn get_position(&self) -> u64 {
+        self.buffer.len() as u64
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buffer
+    }
 }
\ No newline at end of file
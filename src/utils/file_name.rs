@@ -0,0 +1,62 @@
+//! # File Names
+//!
+//! Turns names read from game files, such as texture names, into file names
+//! that are safe to create inside an output directory.
+//!
+//! ## Example
+//!
+//! ```
+//! use rw_parser_rs::utils::file_name::{UniqueFileNames, sanitize_file_name};
+//!
+//! assert_eq!(sanitize_file_name("../../etc/passwd"), "______etc_passwd");
+//!
+//! let mut names = UniqueFileNames::new();
+//! assert_eq!(names.claim("wheel", "png"), "wheel.png");
+//! assert_eq!(names.claim("WHEEL", "png"), "WHEEL_2.png");
+//! ```
+
+use std::collections::HashSet;
+
+/// Returns `name` as a single path component. Path separators, drive
+/// prefixes, `..` and characters Windows does not allow in file names are
+/// replaced with `_`, so the result cannot point outside the directory it
+/// is joined to.
+pub fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows drops trailing dots and spaces, which could turn "a." into "a"
+    let sanitized = replaced.replace("..", "__").trim_end_matches(['.', ' ']).to_string();
+    if sanitized.is_empty() { "_".to_string() } else { sanitized }
+}
+
+/// Hands out sanitized file names that are unique within one directory,
+/// compared case-insensitively as on Windows and macOS.
+#[derive(Debug, Clone, Default)]
+pub struct UniqueFileNames {
+    used: HashSet<String>,
+}
+
+impl UniqueFileNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the sanitized `stem.extension`, with `_2`, `_3` and so on
+    /// appended to the stem if the name was already handed out.
+    pub fn claim(&mut self, stem: &str, extension: &str) -> String {
+        let stem = sanitize_file_name(stem);
+        let mut name = format!("{}.{}", stem, extension);
+        let mut suffix = 2;
+        while !self.used.insert(name.to_lowercase()) {
+            name = format!("{}_{}.{}", stem, suffix, extension);
+            suffix += 1;
+        }
+        name
+    }
+}
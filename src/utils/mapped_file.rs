@@ -0,0 +1,59 @@
+//! # Mapped Files
+//!
+//! Memory-mapped access to files on disk, so batch scans over a full game
+//! install can parse each file in place instead of reading and copying it
+//! first. Requires the `mmap` feature.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+//! use rw_parser_rs::utils::mapped_file::MappedFile;
+//!
+//! let file = MappedFile::open("path/to/your/model.dff").unwrap();
+//! let dff = DffParser::new(&file).parse().unwrap();
+//! ```
+
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// A read-only memory map of a whole file, which dereferences to its bytes.
+#[derive(Debug)]
+pub struct MappedFile {
+    map: Option<Mmap>,
+}
+
+impl MappedFile {
+    /// Maps the file at `path`. Empty files, which cannot be mapped, give an
+    /// empty buffer.
+    ///
+    /// The file must not be truncated or written by another process while it
+    /// is mapped; this is the usual contract of memory-mapped I/O.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Ok(MappedFile { map: None });
+        }
+        // SAFETY: the map is read-only, and game assets are not expected to
+        // change while they are scanned.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(MappedFile { map: Some(map) })
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or(&[])
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
@@ -1,2 +1,5 @@
 pub mod byte_stream;
+pub mod file_name;
+#[cfg(feature = "mmap")]
+pub mod mapped_file;
 pub mod rw_version;
\ No newline at end of file
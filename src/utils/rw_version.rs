@@ -1,39 +1,192 @@
-use std::collections::HashMap;
+//! # RenderWare Versions
+//!
+//! Decoding of the library id stamps in section headers, and a table of the
+//! known RenderWare 3.x releases with the games that shipped them.
+//!
+//! Since RenderWare 3.1.0.1, a stamp packs the version in its upper 16 bits
+//! and the build number in its lower 16 bits. Older stamps hold only the
+//! version, shifted right by 8 bits. Versions are written unpacked as
+//! `0x3MNRB`, such as `0x36003` for 3.6.0.3.
 
+/// The GTA games whose RenderWare versions are known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RwGame {
+    Gta3,
+    ViceCity,
+    SanAndreas,
+}
+
+/// A known RenderWare release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RwRelease {
+    /// The unpacked version, such as `0x36003`.
+    pub version: u32,
+    pub name: &'static str,
+    /// The games that shipped files of this version on some platform.
+    pub games: &'static [RwGame],
+    /// The games whose Android and iOS releases shipped files of this
+    /// version.
+    pub mobile_games: &'static [RwGame],
+}
+
+/// The known RenderWare 3.x releases, sorted by version.
+pub static RW_RELEASES: &[RwRelease] = &[
+    RwRelease { version: 0x30000, name: "RenderWare 3.0.0.0", games: &[], mobile_games: &[] },
+    RwRelease { version: 0x30002, name: "RenderWare 3.0.0.2", games: &[], mobile_games: &[] },
+    RwRelease { version: 0x31000, name: "RenderWare 3.1.0.0 (III on PS2)", games: &[RwGame::Gta3], mobile_games: &[] },
+    RwRelease { version: 0x31001, name: "RenderWare 3.1.0.1", games: &[], mobile_games: &[] },
+    RwRelease { version: 0x32000, name: "RenderWare 3.2.0.0 (III on PC)", games: &[RwGame::Gta3], mobile_games: &[] },
+    RwRelease { version: 0x33002, name: "RenderWare 3.3.0.2 (III on PC, VC on PS2)", games: &[RwGame::Gta3, RwGame::ViceCity], mobile_games: &[] },
+    RwRelease { version: 0x34001, name: "RenderWare 3.4.0.1", games: &[], mobile_games: &[] },
+    RwRelease { version: 0x34003, name: "RenderWare 3.4.0.3 (VC on PC)", games: &[RwGame::ViceCity], mobile_games: &[] },
+    RwRelease { version: 0x34005, name: "RenderWare 3.4.0.5 (III on PS2, VC on Android/PC)", games: &[RwGame::Gta3, RwGame::ViceCity], mobile_games: &[RwGame::ViceCity] },
+    RwRelease { version: 0x35000, name: "RenderWare 3.5.0.0 (III/VC on Xbox)", games: &[RwGame::Gta3, RwGame::ViceCity], mobile_games: &[] },
+    RwRelease { version: 0x35002, name: "RenderWare 3.5.0.2", games: &[], mobile_games: &[] },
+    RwRelease { version: 0x36000, name: "RenderWare 3.6.0.0", games: &[], mobile_games: &[] },
+    RwRelease { version: 0x36003, name: "RenderWare 3.6.0.3 (SA)", games: &[RwGame::SanAndreas], mobile_games: &[RwGame::SanAndreas] },
+    RwRelease { version: 0x37000, name: "RenderWare 3.7.0.0", games: &[], mobile_games: &[] },
+    RwRelease { version: 0x37002, name: "RenderWare 3.7.0.2", games: &[], mobile_games: &[] },
+];
+
+/// Returns the known release of an unpacked version.
+pub fn find_release(version: u32) -> Option<&'static RwRelease> {
+    RW_RELEASES
+        .binary_search_by_key(&version, |release| release.version)
+        .ok()
+        .map(|index| &RW_RELEASES[index])
+}
+
+/// Returns the name of an unpacked version, such as
+/// `"RenderWare 3.6.0.3 (SA)"`.
+pub fn version_string(version: u32) -> Option<&'static str> {
+    find_release(version).map(|release| release.name)
+}
+
+/// A decoded library id stamp.
+///
+/// The stamp packs the version, binary format revision and build number,
+/// and has no device bits: the platform a file was built for is only
+/// recorded by its native sections, such as the platform id of a texture
+/// native (`PlatformType`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct RwVersion {
-    versions: HashMap<u32, String>,
+    /// The unpacked version, such as `0x36003`.
+    pub version: u32,
+    /// The build number, or 0 for stamps older than 3.1.0.1.
+    pub build: u32,
 }
 
 impl RwVersion {
+    /// Returns an empty version. Known releases are in `RW_RELEASES`.
+    #[deprecated(note = "use `RwVersion::from_library_id` or construct the fields")]
     pub fn new() -> Self {
-        let mut versions = HashMap::new();
-        versions.insert(0x31000, "RenderWare 3.1.0.0 (III on PS2)".to_string());
-        versions.insert(0x32000, "RenderWare 3.2.0.0 (III on PC)".to_string());
-        versions.insert(0x33002, "RenderWare 3.3.0.2 (III on PC, VC on PS2)".to_string());
-        versions.insert(0x34003, "RenderWare 3.4.0.3 (VC on PC)".to_string());
-        versions.insert(0x34005, "RenderWare 3.4.0.5 (III on PS2, VC on Android/PC)".to_string());
-        versions.insert(0x35000, "RenderWare 3.5.0.0 (III/VC on Xbox)".to_string());
-        versions.insert(0x36003, "RenderWare 3.6.0.3 (SA)".to_string());
-        RwVersion { versions }
+        RwVersion::default()
     }
 
+    /// Returns the name of an unpacked version.
+    #[deprecated(note = "use `version_string`")]
     pub fn get_version_string(&self, version_number: u32) -> Option<String> {
-        self.versions.get(&version_number).cloned()
+        version_string(version_number).map(str::to_string)
+    }
+
+    /// Decodes the library id stamp of a section header.
+    pub fn from_library_id(library_id: u32) -> Self {
+        RwVersion {
+            version: unpack_version(library_id),
+            build: unpack_build(library_id),
+        }
+    }
+
+    /// Packs the version back into a library id stamp.
+    pub fn library_id(&self) -> u32 {
+        pack_version(self.version, self.build)
+    }
+
+    /// The major version, which is 3 for every supported file.
+    pub fn major(&self) -> u32 {
+        self.version >> 16 & 0xF
+    }
+
+    pub fn minor(&self) -> u32 {
+        self.version >> 12 & 0xF
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.version >> 8 & 0xF
+    }
+
+    /// The binary format revision, the last component of the version.
+    pub fn binary(&self) -> u32 {
+        self.version & 0x3F
+    }
+
+    /// Whether the stamp holds a build number, which RenderWare writes
+    /// since 3.1.0.1.
+    pub fn has_build(&self) -> bool {
+        self.version > 0x31000
+    }
+
+    pub fn release(&self) -> Option<&'static RwRelease> {
+        find_release(self.version)
+    }
+
+    /// Returns the name of the version, if it is a known release.
+    pub fn name(&self) -> Option<&'static str> {
+        version_string(self.version)
+    }
+
+    /// Whether GTA III shipped files of this version.
+    pub fn is_iii(&self) -> bool {
+        self.is_used_by(RwGame::Gta3)
+    }
+
+    /// Whether Vice City shipped files of this version.
+    pub fn is_vc(&self) -> bool {
+        self.is_used_by(RwGame::ViceCity)
+    }
+
+    /// Whether San Andreas shipped files of this version.
+    pub fn is_sa(&self) -> bool {
+        self.is_used_by(RwGame::SanAndreas)
+    }
+
+    /// Whether an Android or iOS release shipped files of this version.
+    pub fn is_mobile(&self) -> bool {
+        self.release().is_some_and(|release| !release.mobile_games.is_empty())
+    }
+
+    fn is_used_by(&self, game: RwGame) -> bool {
+        self.release().is_some_and(|release| release.games.contains(&game))
     }
 }
 
+/// Returns the unpacked version of a library id stamp.
+///
+/// Old stamps are shifted back into the `0x3MNRB` form, so a 3.1.0.0 stamp
+/// of `0x310` unpacks to `0x31000` and matches `RW_RELEASES`.
 pub fn unpack_version(version: u32) -> u32 {
     if version & 0xFFFF0000 != 0 {
-        (version >> 14 & 0x3FF00) + 0x30000 | (version >> 16 & 0x3F)
+        ((version >> 14 & 0x3FF00) + 0x30000) | (version >> 16 & 0x3F)
     } else {
-        version
+        version << 8
     }
 }
 
+/// Returns the build number of a library id stamp, or 0 for old stamps.
 pub fn unpack_build(version: u32) -> u32 {
     if version & 0xFFFF0000 != 0 {
         version & 0xFFFF
     } else {
         0
     }
-}
\ No newline at end of file
+}
+
+/// Packs an unpacked version and a build number into a library id stamp,
+/// in the old format for versions up to 3.1.0.0.
+pub fn pack_version(version: u32, build: u32) -> u32 {
+    if version <= 0x31000 {
+        version >> 8
+    } else {
+        ((version - 0x30000) & 0x3FF00) << 14 | (version & 0x3F) << 16 | (build & 0xFFFF)
+    }
+}
@@ -0,0 +1,54 @@
+//! # WebAssembly Bindings
+//!
+//! `wasm-bindgen` entry points for parsing RenderWare files in the browser,
+//! so web-based model viewers can use this crate in place of the TypeScript
+//! parser. Requires the `wasm` feature; build the module with `cargo rustc
+//! --release --target wasm32-unknown-unknown --features wasm --crate-type
+//! cdylib` and generate the JavaScript glue with `wasm-bindgen`.
+//!
+//! Each function takes the file bytes, such as a `Uint8Array`, and returns
+//! the parsed structure as a plain JavaScript object with the same field
+//! names as the JSON output. Files are parsed with `parse_untrusted`, and
+//! parse errors are thrown as JavaScript errors.
+//!
+//! ## Example
+//!
+//! ```js
+//! import init, { parse_dff } from "rw-parser-rs";
+//!
+//! await init();
+//! const response = await fetch("infernus.dff");
+//! const dff = parse_dff(new Uint8Array(await response.arrayBuffer()));
+//! console.log(dff.geometry_list.geometries.length);
+//! ```
+
+use crate::renderware::dff::dff_parser::DffParser;
+use crate::renderware::ifp::ifp_parser::IfpParser;
+use crate::renderware::rw_error::RwResult;
+use crate::renderware::txd::txd_parser::TxdParser;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+fn to_js<T: Serialize>(result: RwResult<T>) -> Result<JsValue, JsError> {
+    let value = result.map_err(|error| JsError::new(&error.to_string()))?;
+    serde_wasm_bindgen::to_value(&value).map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Parses a DFF file into an `RwDff` object.
+#[wasm_bindgen]
+pub fn parse_dff(bytes: &[u8]) -> Result<JsValue, JsError> {
+    to_js(DffParser::parse_untrusted(bytes))
+}
+
+/// Parses a TXD file into an `RwTxd` object, with every texture decoded to
+/// RGBA in the `mipmaps` of its texture native.
+#[wasm_bindgen]
+pub fn parse_txd(bytes: &[u8]) -> Result<JsValue, JsError> {
+    to_js(TxdParser::parse_untrusted(bytes))
+}
+
+/// Parses an IFP file into an `RwIfp` object.
+#[wasm_bindgen]
+pub fn parse_ifp(bytes: &[u8]) -> Result<JsValue, JsError> {
+    to_js(IfpParser::parse_untrusted(bytes))
+}
@@ -0,0 +1,131 @@
+//! Tests for the ANM parser on small standard and compressed animations.
+
+use rw_parser_rs::renderware::anm::anm_parser::{AnmKeyframeFormat, AnmParser, decompress_anm_float};
+use rw_parser_rs::renderware::ifp::ifp_parser::{IfpTimeBase, KeyframeType};
+use rw_parser_rs::renderware::rw_error::RwParseError;
+
+mod common;
+
+use common::{floats, section};
+
+fn animation(type_id: u32, keyframe_count: u32, duration: f32, keyframes: &[u8]) -> Vec<u8> {
+    let mut payload = 0x100u32.to_le_bytes().to_vec();
+    payload.extend(type_id.to_le_bytes());
+    payload.extend(keyframe_count.to_le_bytes());
+    payload.extend(0u32.to_le_bytes());
+    payload.extend(duration.to_le_bytes());
+    payload.extend(keyframes);
+    section(0x1B, &payload)
+}
+
+/// Writes a standard keyframe linked to the keyframe at index `previous`,
+/// which the file stores as a byte offset.
+fn standard_keyframe(time: f32, x: f32, previous: i32) -> Vec<u8> {
+    let mut keyframe = floats(&[time, 0.0, 0.0, 0.0, 1.0, x, 0.0, 0.0]);
+    let offset = if previous < 0 { previous } else { previous * 0x24 };
+    keyframe.extend(offset.to_le_bytes());
+    keyframe
+}
+
+#[test]
+fn standard_keyframes_are_split_into_bone_tracks() {
+    // Two bones with two keyframes each, then a third keyframe for bone 1
+    let keyframes = [
+        standard_keyframe(0.0, 1.0, -1),
+        standard_keyframe(0.0, 2.0, -1),
+        standard_keyframe(0.5, 3.0, 0),
+        standard_keyframe(0.5, 4.0, 1),
+        standard_keyframe(1.0, 5.0, 3),
+    ]
+    .concat();
+    let anm = AnmParser::new(&animation(1, 5, 1.0, &keyframes)).parse().unwrap();
+
+    assert_eq!(anm.keyframe_format, AnmKeyframeFormat::Standard);
+    assert_eq!((anm.animation_version, anm.duration), (0x100, 1.0));
+    assert_eq!(anm.bones.len(), 2);
+
+    let positions = |bone: usize| -> Vec<(f32, f32)> {
+        anm.bones[bone].keyframes.iter().map(|keyframe| (keyframe.time, keyframe.position.x)).collect()
+    };
+    assert_eq!(positions(0), vec![(0.0, 1.0), (0.5, 3.0)]);
+    assert_eq!(positions(1), vec![(0.0, 2.0), (0.5, 4.0), (1.0, 5.0)]);
+
+    let keyframe = &anm.bones[1].keyframes[2];
+    assert_eq!(keyframe.rotation.w, 1.0);
+    assert_eq!((keyframe.scale.x, keyframe.scale.y, keyframe.scale.z), (1.0, 1.0, 1.0));
+}
+
+#[test]
+fn compressed_floats_decode_to_their_values() {
+    assert_eq!(decompress_anm_float(0x0000), 0.0);
+    assert_eq!(decompress_anm_float(0x7800), 1.0);
+    assert_eq!(decompress_anm_float(0xF800), -1.0);
+    assert_eq!(decompress_anm_float(0x7C00), 1.5);
+    assert_eq!(decompress_anm_float(0x7000), 0.5);
+}
+
+#[test]
+fn compressed_translations_are_scaled_by_the_custom_data() {
+    let mut keyframes = 0.0f32.to_le_bytes().to_vec();
+    for component in [0x0000u16, 0x0000, 0x0000, 0x7800, 0x7000, 0x7800, 0x0000] {
+        keyframes.extend(component.to_le_bytes());
+    }
+    keyframes.extend((-1i32).to_le_bytes());
+    keyframes.extend(floats(&[1.0, -1.0, 0.0, 2.0, 3.0, 1.0]));
+
+    let anm = AnmParser::new(&animation(2, 1, 0.0, &keyframes)).parse().unwrap();
+
+    assert_eq!(anm.keyframe_format, AnmKeyframeFormat::Compressed);
+    let keyframe = &anm.bones[0].keyframes[0];
+    assert_eq!((keyframe.rotation.x, keyframe.rotation.w), (0.0, 1.0));
+    assert_eq!((keyframe.position.x, keyframe.position.y, keyframe.position.z), (2.0, 2.0, 0.0));
+}
+
+#[test]
+fn compressed_keyframes_are_linked_by_their_in_memory_size() {
+    let mut keyframes = Vec::new();
+    for (time, previous) in [(0.0f32, -1i32), (0.0, -1), (0.5, 0x18)] {
+        keyframes.extend(time.to_le_bytes());
+        for component in [0x0000u16, 0x0000, 0x0000, 0x7800, 0x0000, 0x0000, 0x0000] {
+            keyframes.extend(component.to_le_bytes());
+        }
+        keyframes.extend(previous.to_le_bytes());
+    }
+    keyframes.extend(floats(&[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]));
+
+    let anm = AnmParser::new(&animation(2, 3, 0.5, &keyframes)).parse().unwrap();
+
+    let track_lengths: Vec<usize> = anm.bones.iter().map(|bone| bone.keyframes.len()).collect();
+    assert_eq!(track_lengths, vec![1, 2]);
+}
+
+#[test]
+fn animations_convert_to_ifp_bones_by_index() {
+    let keyframes = [standard_keyframe(0.0, 1.0, -1), standard_keyframe(0.0, 2.0, -1)].concat();
+    let anm = AnmParser::new(&animation(1, 2, 0.0, &keyframes)).parse().unwrap();
+
+    let ifp = anm.to_ifp_animation("cutscene");
+
+    assert_eq!(ifp.name, "cutscene");
+    assert_eq!(ifp.time_base, IfpTimeBase::Seconds);
+    let bones: Vec<(i32, bool, KeyframeType)> =
+        ifp.bones.iter().map(|bone| (bone.bone_id, bone.use_bone_id, bone.keyframe_type)).collect();
+    assert_eq!(bones, vec![(0, true, KeyframeType::KRT0), (1, true, KeyframeType::KRT0)]);
+    assert_eq!(ifp.bones[1].keyframes[0].position.x, 2.0);
+}
+
+#[test]
+fn other_sections_and_keyframe_types_are_rejected() {
+    let error = AnmParser::new(&section(0x16, &[0; 20])).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::UnexpectedSection { expected: 0x1B, found: 0x16, offset: 0 }), "{}", error);
+
+    let error = AnmParser::new(&animation(3, 0, 0.0, &[])).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::InconsistentData { offset: 16, .. }), "{}", error);
+}
+
+#[test]
+fn truncated_keyframes_fail() {
+    let keyframes = standard_keyframe(0.0, 1.0, -1);
+    let error = AnmParser::new(&animation(1, 2, 0.0, &keyframes)).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::ExcessiveCount { count: 2, .. }), "{}", error);
+}
@@ -0,0 +1,134 @@
+//! Tests for resolving models across IDE, IPL and IMG files held in memory.
+
+use rw_parser_rs::renderware::assets::asset_database::{AssetDatabase, ModelKind};
+use rw_parser_rs::renderware::ide::ide_parser::{IdeObjectKind, IdeParser};
+use rw_parser_rs::renderware::img::img_archive::ImgArchive;
+use rw_parser_rs::renderware::img::img_writer::ImgWriter;
+use rw_parser_rs::renderware::ipl::ipl_parser::IplParser;
+use std::io::Cursor;
+
+mod common;
+
+use common::{build_dff, build_txd, floats};
+
+const IDE: &str = "objs
+615, veg_tree3, gta_tree_bevhills, 300, 0
+616, lamppost1, streetprops, 150, 0
+end
+peds
+7, male01, male01, CIVMALE, STAT_STREET_GUY, man, 03, 0, man, 0, 1, PED_TYPE_GEN, VOICE_GEN_MALE01, VOICE_GEN_MALE01
+end
+txdp
+gta_tree_bevhills, gta_trees
+gta_trees, generic_vegetation
+generic_vegetation, gta_tree_bevhills
+end
+";
+
+/// A binary IPL placing model 615, which stores no model names.
+fn binary_ipl() -> Vec<u8> {
+    let mut data = b"bnry".to_vec();
+    for count in [1i32, 0, 0, 0, 0, 0] {
+        data.extend(count.to_le_bytes());
+    }
+    for offset in [76u32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0] {
+        data.extend(offset.to_le_bytes());
+    }
+    data.extend(floats(&[100.0, 200.0, 10.0, 0.0, 0.0, 0.0, 1.0]));
+    for value in [615i32, 0, -1] {
+        data.extend(value.to_le_bytes());
+    }
+    data
+}
+
+fn database() -> AssetDatabase<Cursor<Vec<u8>>> {
+    let mut writer = ImgWriter::new();
+    writer.add("veg_tree3.dff", build_dff()).unwrap();
+    writer.add("gta_tree_bevhills.txd", build_txd()).unwrap();
+    let mut img = Vec::new();
+    writer.write_v2(&mut img).unwrap();
+
+    let mut database = AssetDatabase::new();
+    database.add_ide(IdeParser::new(IDE.as_bytes()).parse().unwrap());
+    database.add_ipl(
+        IplParser::new(b"inst\n615, veg_tree3, 0, 1, 2, 3, 0, 0, 0, 1, -1\n900, bollard, 0, 4, 5, 6, 0, 0, 0, 1, -1\nend\n")
+            .parse()
+            .unwrap(),
+    );
+    database.add_ipl(IplParser::new(&binary_ipl()).parse().unwrap());
+    database.add_archive(ImgArchive::open_v2(Cursor::new(img)).unwrap());
+    database
+}
+
+#[test]
+fn models_are_found_by_name_and_id() {
+    let database = database();
+
+    assert_eq!(database.definitions().len(), 3);
+    let tree = database.model("VEG_TREE3").unwrap();
+    assert_eq!((tree.kind, tree.id, tree.txd_name.as_str()), (ModelKind::Object(IdeObjectKind::Object), 615, "gta_tree_bevhills"));
+    assert_eq!(database.model_by_id(7).unwrap().kind, ModelKind::Ped);
+    assert!(database.model("bollard").is_none());
+}
+
+#[test]
+fn later_definitions_take_precedence() {
+    let mut database = database();
+    database.add_ide(IdeParser::new(b"objs\n615, veg_tree3, new_trees, 300, 0\nend\n").parse().unwrap());
+
+    assert_eq!(database.model("veg_tree3").unwrap().txd_name, "new_trees");
+    assert_eq!(database.model_by_id(615).unwrap().txd_name, "new_trees");
+}
+
+#[test]
+fn texture_chains_follow_parents_until_a_cycle() {
+    let database = database();
+
+    assert_eq!(database.txd_chain("veg_tree3"), vec!["gta_tree_bevhills", "gta_trees", "generic_vegetation"]);
+    assert_eq!(database.txd_chain("lamppost1"), vec!["streetprops"]);
+    assert!(database.txd_chain("bollard").is_empty());
+}
+
+#[test]
+fn placements_match_ids_across_text_and_binary_files() {
+    let database = database();
+
+    let placements = database.placements("veg_tree3");
+    let positions: Vec<f32> = placements.iter().map(|instance| instance.position.x).collect();
+    assert_eq!(positions, vec![1.0, 100.0]);
+
+    // Undefined models are matched by the names of text files
+    let bollards = database.placements("BOLLARD");
+    assert_eq!((bollards.len(), bollards[0].id), (1, 900));
+    assert!(database.placements("lamppost1").is_empty());
+}
+
+#[test]
+fn resolved_assets_load_their_files_from_the_archives() {
+    let mut database = database();
+
+    let asset = database.resolve("veg_tree3").unwrap().unwrap();
+    assert_eq!(asset.definition.id, 615);
+    assert!(asset.dff.unwrap().geometry_list.is_some());
+    assert_eq!(asset.txd.unwrap().texture_dictionary.texture_natives[0].texture_name, "wheel");
+    assert_eq!(asset.placements.len(), 2);
+
+    // Files missing from every archive resolve to nothing
+    let lamppost = database.resolve("lamppost1").unwrap().unwrap();
+    assert_eq!((lamppost.dff, lamppost.txd), (None, None));
+    assert!(database.resolve("bollard").unwrap().is_none());
+    assert!(database.read_file("missing.col").unwrap().is_none());
+}
+
+#[test]
+fn archives_are_searched_in_order() {
+    let mut writer = ImgWriter::new();
+    writer.add("veg_tree3.dff", b"not a model".to_vec()).unwrap();
+    let mut img = Vec::new();
+    writer.write_v2(&mut img).unwrap();
+
+    let mut database = database();
+    database.add_archive(ImgArchive::open_v2(Cursor::new(img)).unwrap());
+
+    assert!(database.read_dff("veg_tree3").unwrap().is_some());
+}
@@ -0,0 +1,65 @@
+//! Tests for batch scanning over directories written to the temporary
+//! directory.
+
+use rw_parser_rs::batch::{ScanOptions, ScanResult, scan};
+use std::fs;
+use std::path::PathBuf;
+
+/// Creates an empty directory unique to the test.
+fn scratch_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("rw-parser-batch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn sources(results: impl IntoIterator<Item = ScanResult>) -> Vec<String> {
+    let mut sources: Vec<String> = results
+        .into_iter()
+        .map(|scanned| scanned.source.path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    sources.sort();
+    sources
+}
+
+#[test]
+fn scans_matching_files_in_subdirectories() {
+    let directory = scratch_directory("walk");
+    fs::create_dir(directory.join("models")).unwrap();
+    fs::write(directory.join("car.dff"), b"not a model").unwrap();
+    fs::write(directory.join("models/car.txd"), b"not a dictionary").unwrap();
+    fs::write(directory.join("readme.txt"), b"skipped").unwrap();
+
+    let results: Vec<_> = scan([&directory], ScanOptions::new()).into_iter().collect();
+    assert!(results.iter().all(|scanned| scanned.result.is_err()));
+    assert_eq!(sources(results), vec!["car.dff", "car.txd"]);
+    assert_eq!(sources(scan([&directory], ScanOptions::new().with_recursive(false))), vec!["car.dff"]);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn symbolic_link_loops_are_walked_once() {
+    let directory = scratch_directory("loop");
+    fs::create_dir(directory.join("models")).unwrap();
+    fs::write(directory.join("models/car.dff"), b"not a model").unwrap();
+    std::os::unix::fs::symlink(&directory, directory.join("models/up")).unwrap();
+
+    assert_eq!(sources(scan([&directory], ScanOptions::new())), vec!["car.dff"]);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn dropping_the_receiver_stops_the_scan() {
+    let directory = scratch_directory("stop");
+    for index in 0..64 {
+        fs::write(directory.join(format!("{}.dff", index)), b"not a model").unwrap();
+    }
+
+    // The scan thread has to notice the closed channel instead of parsing
+    // every remaining file, which this only observes as not hanging
+    let receiver = scan([&directory], ScanOptions::new());
+    assert!(receiver.recv().is_ok());
+    drop(receiver);
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,125 @@
+//! Builders of small synthetic RenderWare files shared by the integration
+//! tests. Each test crate uses a different subset of them.
+
+#![allow(dead_code)]
+
+pub const VERSION: u32 = 0x1803FFFF;
+
+pub fn section(section_type: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(section_type.to_le_bytes());
+    data.extend((payload.len() as u32).to_le_bytes());
+    data.extend(VERSION.to_le_bytes());
+    data.extend(payload);
+    data
+}
+
+pub fn floats(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+pub fn fixed_string(text: &str, size: usize) -> Vec<u8> {
+    let mut data = text.as_bytes().to_vec();
+    data.resize(size, 0);
+    data
+}
+
+/// A texture section referencing `name`, without a mask.
+pub fn texture(name: &str) -> Vec<u8> {
+    let mut texture = section(0x01, &0x1106u32.to_le_bytes());
+    texture.extend(section(0x02, &fixed_string(name, (name.len() + 4) & !3)));
+    texture.extend(section(0x02, &fixed_string("", 4)));
+    texture.extend(section(0x03, &[]));
+    section(0x06, &texture)
+}
+
+/// A clump of one frame, one atomic and one textured triangle with
+/// prelit colors, normals and UVs.
+pub fn build_dff() -> Vec<u8> {
+    let mut frame = floats(&[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.5, -2.0, 3.25]);
+    frame.extend((-1i32).to_le_bytes());
+    frame.extend(0u32.to_le_bytes());
+    let mut frames = 1u32.to_le_bytes().to_vec();
+    frames.extend(frame);
+    let frame_list = section(0x0E, &section(0x01, &frames));
+
+    let mut material_struct = 0u32.to_le_bytes().to_vec();
+    material_struct.extend([200, 100, 50, 255]);
+    material_struct.extend(0u32.to_le_bytes());
+    material_struct.extend(1u32.to_le_bytes());
+    material_struct.extend(floats(&[1.0, 0.5, 0.25]));
+    let mut material = section(0x01, &material_struct);
+    material.extend(texture("body"));
+    material.extend(section(0x03, &[]));
+
+    let mut material_list_struct = 1u32.to_le_bytes().to_vec();
+    material_list_struct.extend((-1i32).to_le_bytes());
+    let mut material_list = section(0x01, &material_list_struct);
+    material_list.extend(section(0x07, &material));
+
+    let mut geometry_struct = Vec::new();
+    geometry_struct.extend((4u16 | 8 | 16 | 32).to_le_bytes());
+    geometry_struct.extend([1, 0]);
+    geometry_struct.extend(1u32.to_le_bytes()); // triangles
+    geometry_struct.extend(3u32.to_le_bytes()); // vertices
+    geometry_struct.extend(1u32.to_le_bytes()); // morph targets
+    geometry_struct.extend([255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255]);
+    geometry_struct.extend(floats(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0]));
+    for value in [1u16, 0, 0, 2] {
+        geometry_struct.extend(value.to_le_bytes());
+    }
+    geometry_struct.extend(floats(&[0.5, 0.5, 0.0, 1.0]));
+    geometry_struct.extend(1u32.to_le_bytes());
+    geometry_struct.extend(1u32.to_le_bytes());
+    geometry_struct.extend(floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]));
+    geometry_struct.extend(floats(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]));
+
+    let mut bin_mesh = 0u32.to_le_bytes().to_vec();
+    bin_mesh.extend(1u32.to_le_bytes());
+    bin_mesh.extend(3u32.to_le_bytes());
+    for value in [3u32, 0, 0, 1, 2] {
+        bin_mesh.extend(value.to_le_bytes());
+    }
+
+    let mut geometry = section(0x01, &geometry_struct);
+    geometry.extend(section(0x08, &material_list));
+    geometry.extend(section(0x03, &section(0x050E, &bin_mesh)));
+
+    let mut geometry_list = section(0x01, &1u32.to_le_bytes());
+    geometry_list.extend(section(0x0F, &geometry));
+
+    let mut atomic_struct = Vec::new();
+    for value in [0u32, 0, 5, 0] {
+        atomic_struct.extend(value.to_le_bytes());
+    }
+    let atomic = section(0x01, &atomic_struct);
+
+    let mut clump = section(0x01, &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    clump.extend(frame_list);
+    clump.extend(section(0x1A, &geometry_list));
+    clump.extend(section(0x14, &atomic));
+    section(0x10, &clump)
+}
+
+/// A D3D9 dictionary of one 2x2 uncompressed texture with a parent.
+pub fn build_txd() -> Vec<u8> {
+    let mut native_struct = 9u32.to_le_bytes().to_vec();
+    native_struct.extend(0x1102u32.to_le_bytes());
+    native_struct.extend(fixed_string("wheel", 32));
+    native_struct.extend(fixed_string("wheela", 32));
+    native_struct.extend(0x0500u32.to_le_bytes()); // 8888
+    native_struct.extend(21u32.to_le_bytes()); // D3DFMT_A8R8G8B8
+    native_struct.extend(2u16.to_le_bytes());
+    native_struct.extend(2u16.to_le_bytes());
+    native_struct.extend([32, 1, 4, 1]);
+    native_struct.extend(16u32.to_le_bytes());
+    native_struct.extend((0..16u8).map(|value| value * 16));
+
+    let mut native = section(0x01, &native_struct);
+    native.extend(section(0x03, &[]));
+
+    let mut dictionary = section(0x01, &[1, 0, 0, 0]);
+    dictionary.extend(section(0x15, &native));
+    dictionary.extend(section(0x03, &section(0x02, &fixed_string("parent", 8))));
+    section(0x16, &dictionary)
+}
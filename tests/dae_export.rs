@@ -0,0 +1,30 @@
+//! Tests for the Collada exporter.
+
+#![cfg(feature = "dae")]
+
+use rw_parser_rs::export::dae::DaeExporter;
+use rw_parser_rs::renderware::dff::dff_parser::{DffParser, RwDff};
+
+mod common;
+
+use common::build_dff;
+
+fn textured_dff(texture_name: &str) -> RwDff {
+    let mut dff = DffParser::new(&build_dff()).parse().unwrap();
+    let geometries = &mut dff.geometry_list.as_mut().unwrap().geometries;
+    geometries[0].material_list.material_data[0].texture.as_mut().unwrap().texture_name = texture_name.to_string();
+    dff
+}
+
+#[test]
+fn images_reference_the_texture_file() {
+    let dae = DaeExporter::new(&textured_dff("body")).to_dae();
+    assert!(dae.contains("<init_from>body.png</init_from>"), "{}", dae);
+}
+
+#[test]
+fn image_file_names_are_uri_escaped() {
+    let dae = DaeExporter::new(&textured_dff("dirt & rust#2")).to_dae();
+    assert!(dae.contains("<init_from>dirt%20%26%20rust%232.png</init_from>"), "{}", dae);
+    assert!(dae.contains("name=\"dirt &amp; rust#2\""), "{}", dae);
+}
@@ -0,0 +1,156 @@
+//! Tests for the gta.dat, water.dat and timecyc.dat parsers on small files
+//! of each game.
+
+use rw_parser_rs::renderware::common::types::RwColor;
+use rw_parser_rs::renderware::dat::gta_dat::{GtaDatEntryKind, GtaDatParser};
+use rw_parser_rs::renderware::dat::timecyc_dat::{RwTimecyc, TimecycParser, TimecycVersion};
+use rw_parser_rs::renderware::dat::water_dat::WaterDatParser;
+use std::io::ErrorKind;
+
+#[test]
+fn gta_dat_keeps_load_order_and_collision_levels() {
+    let gta_dat = GtaDatParser::new(
+        b"# load IDEs first
+IDE DATA\\MAPS\\generic.ide
+img models\\cutscene.img
+COLFILE 0 MODELS\\COLL\\WEAPONS.COL
+TEXDICTION MODELS\\MISC.TXD
+IPL DATA\\MAPS\\LA\\LAn.IPL   # downtown
+SPLASH loadsc0
+EXIT
+",
+    )
+    .parse()
+    .unwrap();
+
+    let kinds: Vec<&GtaDatEntryKind> = gta_dat.entries.iter().map(|entry| &entry.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            &GtaDatEntryKind::Ide,
+            &GtaDatEntryKind::Img,
+            &GtaDatEntryKind::ColFile,
+            &GtaDatEntryKind::TexDictionary,
+            &GtaDatEntryKind::Ipl,
+            &GtaDatEntryKind::Splash,
+            &GtaDatEntryKind::Other("EXIT".to_string()),
+        ]
+    );
+
+    let collision = &gta_dat.entries[2];
+    assert_eq!((collision.level, collision.path.as_str()), (Some(0), "MODELS\\COLL\\WEAPONS.COL"));
+    assert_eq!(gta_dat.entries[0].level, None);
+    assert_eq!(gta_dat.paths(&GtaDatEntryKind::Ipl), vec!["DATA\\MAPS\\LA\\LAn.IPL"]);
+}
+
+#[test]
+fn gta_dat_rejects_a_keyword_without_a_path() {
+    let error = GtaDatParser::new(b"IDE DATA\\default.ide\nCOLFILE 1\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn water_dat_reads_quads_triangles_and_rectangles() {
+    let water = WaterDatParser::new(
+        b"processed
+-2984.0 -2984.0 0.0 0.0 0.0 0.0 0.0  -2000.0 -2984.0 0.0 0.5 0.0 1.0 0.2  -2984.0 -2000.0 0.0 0.0 0.0 0.0 0.0  -2000.0 -2000.0 0.0 0.0 0.0 0.0 0.0  1
+100.0 100.0 5.0 0.0 0.0 0.0 0.0  200.0 100.0 5.0 0.0 0.0 0.0 0.0  100.0 200.0 5.0 0.0 0.0 0.0 0.0  0
+6.0, -100.0, -50.0, 100.0, 50.0
+",
+    )
+    .parse()
+    .unwrap();
+
+    assert_eq!(water.polygons.len(), 2);
+    let quad = &water.polygons[0];
+    assert!(quad.is_quad());
+    assert_eq!(quad.flags, 1);
+    let vertex = &quad.vertices[1];
+    assert_eq!((vertex.position.x, vertex.speed, vertex.unknown, vertex.wave_height), (-2000.0, [0.5, 0.0], 1.0, 0.2));
+
+    let triangle = &water.polygons[1];
+    assert!(!triangle.is_quad());
+    assert_eq!((triangle.vertices.len(), triangle.vertices[2].position.z), (3, 5.0));
+
+    let rectangle = &water.rectangles[0];
+    assert_eq!(
+        (rectangle.height, rectangle.min_x, rectangle.min_y, rectangle.max_x, rectangle.max_y),
+        (6.0, -100.0, -50.0, 100.0, 50.0)
+    );
+}
+
+#[test]
+fn water_dat_rejects_a_line_of_unknown_length() {
+    let error = WaterDatParser::new(b"1.0 2.0 3.0\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+/// A time cycle file of `lines` lines of `value_count` values, where value
+/// `i` of every line is `i` except for the first, which is the line index.
+fn timecyc(lines: usize, value_count: usize) -> RwTimecyc {
+    let mut text = String::from("// Amb_R Amb_G Amb_B ...\n");
+    for line in 0..lines {
+        let values: Vec<String> = (0..value_count)
+            .map(|index| if index == 0 { line } else { index })
+            .map(|value| value.to_string())
+            .collect();
+        text.push_str(&values.join(" "));
+        text.push('\n');
+    }
+    TimecycParser::new(text.as_bytes()).parse().unwrap()
+}
+
+#[test]
+fn gta3_time_cycles_have_24_hours_of_four_weathers() {
+    let timecyc = timecyc(4 * 24, 40);
+
+    assert_eq!(timecyc.version, TimecycVersion::Gta3);
+    assert_eq!(timecyc.entries.len(), 96);
+    let entry = timecyc.get(2, 13).unwrap();
+    assert_eq!((entry.weather, entry.hour, entry.ambient.r), (2, 13, 61));
+    assert_eq!(timecyc.weather_name(2), Some("RAINY"));
+
+    assert_eq!(entry.ambient_object, None);
+    assert_eq!(entry.sky_top, RwColor { r: 6, g: 7, b: 8, a: 255 });
+    assert_eq!((entry.sun_size, entry.far_clip, entry.light_on_ground), (18.0, 24.0, 26.0));
+    assert_eq!(entry.bottom_clouds.r, 33);
+    assert_eq!(entry.water, None);
+    assert_eq!(entry.values.len(), 40);
+}
+
+#[test]
+fn vice_city_time_cycles_have_seven_weathers() {
+    let timecyc = timecyc(7 * 24, 52);
+
+    assert_eq!(timecyc.version, TimecycVersion::ViceCity);
+    let entry = timecyc.get(6, 23).unwrap();
+    assert_eq!((entry.weather, entry.hour), (6, 23));
+    assert_eq!(entry.ambient_object, Some(RwColor { r: 3, g: 4, b: 5, a: 255 }));
+    assert_eq!(entry.directional.r, 12);
+    assert_eq!(entry.far_clip, 33.0);
+    assert_eq!(entry.water, Some(RwColor { r: 48, g: 49, b: 50, a: 51 }));
+}
+
+#[test]
+fn san_andreas_time_cycles_have_eight_key_hours() {
+    let timecyc = timecyc(23 * 8, 52);
+
+    assert_eq!(timecyc.version, TimecycVersion::SanAndreas);
+    assert_eq!(timecyc.weather_name(20), Some("UNDERWATER"));
+
+    // Hour 15 falls back to the noon line, the fifth of the weather
+    let entry = timecyc.get(2, 15).unwrap();
+    assert_eq!((entry.weather, entry.hour, entry.ambient.r), (2, 12, 20));
+    assert_eq!(entry.directional.r, 6);
+    assert_eq!(entry.sun_size, 21.0);
+    assert_eq!(entry.water, Some(RwColor { r: 36, g: 37, b: 38, a: 39 }));
+}
+
+#[test]
+fn time_cycles_with_too_few_values_are_rejected() {
+    let error = TimecycParser::new(b"1 2 3 4 5\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let error = TimecycParser::new(b"1 2 x\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
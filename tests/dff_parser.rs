@@ -0,0 +1,212 @@
+//! Tests for the DFF parser on hand-built clumps.
+//!
+//! The clumps start from the shared single-triangle fixture, with sections
+//! patched in through the chunk extractor.
+
+use rw_parser_rs::renderware::dff::dff_parser::{
+    DffParser, RwMaterial, RwSkin, RwSkinBoneRun, RwSkinRunRange, UserDataEntry, UserDataValues,
+};
+use rw_parser_rs::renderware::rw_chunk_extractor::{RwChunkExtractor, SectionPath};
+use rw_parser_rs::renderware::rw_error::RwParseError;
+use rw_parser_rs::renderware::rw_report::ParseWarningKind;
+use rw_parser_rs::renderware::rw_sections::RwSections;
+
+mod common;
+
+use common::{build_dff, floats, section, texture};
+
+fn material_extension_path() -> SectionPath {
+    SectionPath::new()
+        .child(RwSections::RwClump, 0)
+        .child(RwSections::RwGeometryList, 0)
+        .child(RwSections::RwGeometry, 0)
+        .child(RwSections::RwMaterialList, 0)
+        .child(RwSections::RwMaterial, 0)
+        .child(RwSections::RwExtension, 0)
+}
+
+/// Parses the fixture with `plugins` as the extension of its material.
+fn parse_material(plugins: &[u8]) -> (RwMaterial, Vec<ParseWarningKind>) {
+    let dff = RwChunkExtractor::new(&build_dff()).unwrap().inject(&material_extension_path(), plugins).unwrap();
+    let (mut parsed, report) = DffParser::new(&dff).parse_with_report().unwrap();
+    let material = parsed.geometry_list.as_mut().unwrap().geometries[0].material_list.material_data.remove(0);
+    (material, report.warnings.iter().map(|warning| warning.kind).collect())
+}
+
+#[test]
+fn normal_map_plugins_are_parsed() {
+    let mut normal_map = 3u32.to_le_bytes().to_vec();
+    normal_map.extend(texture("body_nm"));
+    normal_map.extend(floats(&[0.75]));
+    normal_map.extend(texture("reflection"));
+
+    let (material, warnings) = parse_material(&section(0x0133, &normal_map));
+    let normal_map = material.normal_map.unwrap();
+    assert_eq!(normal_map.normal_texture.unwrap().texture_name, "body_nm");
+    assert_eq!(normal_map.environment_texture.unwrap().texture_name, "reflection");
+    assert_eq!(normal_map.environment_coefficient, 0.75);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn plugins_after_unknown_ones_are_still_read() {
+    let mut plugins = section(0x0FFF_0001, &[1, 2, 3, 4, 5]);
+    plugins.extend(section(0x1F, &[0x02, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00]));
+
+    let (material, warnings) = parse_material(&plugins);
+    let right_to_render = material.right_to_render.unwrap();
+    assert_eq!((right_to_render.plugin_id, right_to_render.extra_data), (2, 7));
+    assert_eq!(warnings, vec![ParseWarningKind::UnknownSection { section_type: 0x0FFF_0001 }]);
+}
+
+fn i32s(values: &[i32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// A frame list of a root and a child, each with a node name and an HAnim
+/// plugin, and user data on the child only.
+fn frame_list_with_plugins() -> Vec<u8> {
+    let mut frames = 2u32.to_le_bytes().to_vec();
+    for parent in [-1, 0] {
+        frames.extend(floats(&[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]));
+        frames.extend(i32s(&[parent, 0]));
+    }
+    let mut frame_list = section(0x01, &frames);
+
+    let mut root = section(0x0253F2FE, b"root");
+    root.extend(section(0x011E, &i32s(&[0x100, 0, 1, 0, 36, 5, 0, 3])));
+    frame_list.extend(section(0x03, &root));
+
+    let mut user_data = i32s(&[1, 4]);
+    user_data.extend(b"lod\0");
+    user_data.extend(i32s(&[1, 1, 2]));
+    let mut child = section(0x0253F2FE, b"wheel");
+    child.extend(section(0x011E, &i32s(&[0x100, 5, 0])));
+    child.extend(section(0x011F, &user_data));
+    frame_list.extend(section(0x03, &child));
+    frame_list
+}
+
+#[test]
+fn frame_plugins_give_names_anim_nodes_and_user_data() {
+    let path = SectionPath::new().child(RwSections::RwClump, 0).child(RwSections::RwFrameList, 0);
+    let dff = RwChunkExtractor::new(&build_dff()).unwrap().inject(&path, &frame_list_with_plugins()).unwrap();
+    let dff = DffParser::new(&dff).parse().unwrap();
+
+    assert_eq!(dff.dummies, vec!["root", "wheel"]);
+    assert_eq!(dff.anim_nodes.iter().map(|node| node.bone_id).collect::<Vec<_>>(), vec![0, 5]);
+    assert_eq!(dff.anim_nodes[0].bones[0].bone_id, 5);
+    assert_eq!(dff.frame_name(1), Some("wheel"));
+    assert_eq!(dff.frame_bone_id(1), Some(5));
+
+    let frames = dff.frames();
+    assert!(frames[0].user_data.is_empty());
+    assert_eq!(frames[1].user_data, vec![UserDataEntry { name: "lod".to_string(), values: UserDataValues::Int(vec![2]) }]);
+}
+
+/// Overwrites the size of the string section holding `text`.
+fn patch_string_size(data: &mut [u8], text: &[u8], size: u32) {
+    let start = data.windows(text.len()).position(|window| window == text).unwrap();
+    data[start - 8..start - 4].copy_from_slice(&size.to_le_bytes());
+}
+
+#[test]
+fn string_sizes_larger_than_the_file_are_rejected() {
+    let mut dff = build_dff();
+    patch_string_size(&mut dff, b"body", 0xFFFF_FFF0);
+    assert!(DffParser::new(&dff).parse().is_err());
+    assert!(DffParser::parse_untrusted(&dff).is_err());
+}
+
+/// Parses the fixture with a skin plugin of two bones, with `used_bones`
+/// and then `tail` after the inverse bone matrices. Skins without used
+/// bones precede every matrix with a marker.
+fn parse_skin(used_bones: &[u8], tail: &[u8]) -> RwSkin {
+    let mut skin = vec![2, used_bones.len() as u8, 1, 0];
+    skin.extend(used_bones);
+    skin.extend([0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+    skin.extend(floats(&[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]));
+    for bone in 0..2 {
+        if used_bones.is_empty() {
+            skin.extend(0xDEADDEADu32.to_le_bytes());
+        }
+        skin.extend(floats(&[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, bone as f32, 0.0, 0.0, 1.0]));
+    }
+    skin.extend(tail);
+
+    let mut plugins = section(0x0116, &skin);
+    plugins.extend(section(0x050E, &i32s(&[0, 1, 3, 3, 0, 0, 1, 2])));
+    let path = SectionPath::new()
+        .child(RwSections::RwClump, 0)
+        .child(RwSections::RwGeometryList, 0)
+        .child(RwSections::RwGeometry, 0)
+        .child(RwSections::RwExtension, 0);
+    let dff = RwChunkExtractor::new(&build_dff()).unwrap().inject(&path, &plugins).unwrap();
+    DffParser::new(&dff).parse().unwrap().geometry_list.unwrap().geometries.remove(0).skin.unwrap()
+}
+
+#[test]
+fn skins_without_used_bones_skip_matrix_markers() {
+    // The fixture is a 3.6.0.3 file, so the layout cannot follow the version
+    let skin = parse_skin(&[], &[]);
+    assert_eq!(skin.used_bones, Vec::<u8>::new());
+    assert_eq!(skin.inverse_bone_matrices[1].transform.x, 1.0);
+    assert_eq!(skin.bone_vertex_indices[2], vec![1, 0, 0, 0]);
+    assert!(skin.split_data.is_none());
+}
+
+#[test]
+fn skins_with_used_bones_read_their_split_data() {
+    let mut split = i32s(&[2, 1, 2]);
+    split.extend([1, 0, 0, 2, 0, 1, 1, 1]);
+    let skin = parse_skin(&[0, 1], &split);
+    assert_eq!(skin.used_bones, vec![0, 1]);
+    assert_eq!(skin.inverse_bone_matrices[1].transform.x, 1.0);
+
+    let split_data = skin.split_data.unwrap();
+    assert_eq!(split_data.bone_limit, 2);
+    assert_eq!(split_data.bone_remap_indices, vec![1, 0]);
+    assert_eq!(split_data.mesh_bone_runs, vec![RwSkinRunRange { start: 0, count: 2 }]);
+    assert_eq!(
+        split_data.bone_runs,
+        vec![RwSkinBoneRun { first_bone: 0, bone_count: 1 }, RwSkinBoneRun { first_bone: 1, bone_count: 1 }]
+    );
+}
+
+#[test]
+fn skins_with_used_bones_and_no_split_meshes_keep_the_bone_limit() {
+    let split_data = parse_skin(&[0, 1], &i32s(&[0, 0, 0])).split_data.unwrap();
+    assert_eq!(split_data.bone_limit, 0);
+    assert!(split_data.bone_remap_indices.is_empty());
+}
+
+/// Sets the triangle and vertex counts of the fixture's geometry.
+fn with_geometry_counts(triangle_count: u32, vertex_count: u32) -> Vec<u8> {
+    let mut dff = build_dff();
+    let counts = [1u32, 3].map(u32::to_le_bytes).concat();
+    let start = dff.windows(8).position(|window| window == counts).unwrap();
+    dff[start..start + 4].copy_from_slice(&triangle_count.to_le_bytes());
+    dff[start + 4..start + 8].copy_from_slice(&vertex_count.to_le_bytes());
+    dff
+}
+
+#[test]
+fn geometry_counts_larger_than_the_struct_are_rejected() {
+    // Both counts overflow a 32-bit usize once multiplied by their sizes
+    let error = DffParser::new(&with_geometry_counts(1, 0x4000_0000)).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::ExcessiveCount { what: "vertex colors", .. }), "{}", error);
+
+    let error = DffParser::new(&with_geometry_counts(0x4000_0000, 3)).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::ExcessiveCount { what: "triangles", .. }), "{}", error);
+}
+
+#[test]
+fn atomics_referencing_missing_geometries_are_rejected() {
+    // The atomic struct ends the fixture: frame, geometry, flags and unused
+    let mut dff = build_dff();
+    let geometry_index = dff.len() - 12;
+    dff[geometry_index..geometry_index + 4].copy_from_slice(&20_000_000u32.to_le_bytes());
+
+    let error = DffParser::new(&dff).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::InconsistentData { .. }), "{}", error);
+}
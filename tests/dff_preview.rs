@@ -0,0 +1,68 @@
+//! Tests for the software preview renderer.
+//!
+//! The shared fixture is a single triangle in the XY plane, rendered from
+//! straight above so that its pixels are easy to predict.
+
+#![cfg(feature = "preview")]
+
+use rw_parser_rs::renderware::dff::dff_parser::{DffParser, RwDff};
+use rw_parser_rs::renderware::dff::dff_preview::PreviewRenderer;
+
+mod common;
+
+use common::build_dff;
+
+const SIZE: u32 = 32;
+
+fn renderer() -> PreviewRenderer {
+    PreviewRenderer::new(SIZE, SIZE).with_angles(0.0, 89.0).with_supersampling(1)
+}
+
+fn is_drawn(pixel: &[u8; 4]) -> bool {
+    pixel[3] == 255
+}
+
+#[test]
+fn draws_the_triangle_in_its_material_color() {
+    let dff = DffParser::new(&build_dff()).parse().unwrap();
+    let image = renderer().render(&dff, None);
+    assert_eq!(image.dimensions(), (SIZE, SIZE));
+
+    // Seen from above, the triangle covers the upper left half of the frame
+    let inside = image.get_pixel(12, 12).0;
+    assert!(is_drawn(&inside));
+    assert!(inside[0] > inside[1] && inside[1] > inside[2], "{:?} is not tinted by the material", inside);
+    assert!(!is_drawn(&image.get_pixel(20, 20).0));
+    assert_eq!(image.get_pixel(SIZE - 1, SIZE - 1).0, [0, 0, 0, 0]);
+}
+
+#[test]
+fn places_geometry_with_its_frame() {
+    let mut dff = DffParser::new(&build_dff()).parse().unwrap();
+    // Turning the frame half way around the Z axis mirrors the triangle
+    let matrix = &mut dff.frame_list.as_mut().unwrap().frames[0].rotation_matrix;
+    matrix.right.x = -1.0;
+    matrix.up.y = -1.0;
+
+    let image = renderer().render(&dff, None);
+    assert!(!is_drawn(&image.get_pixel(12, 12).0));
+    assert!(is_drawn(&image.get_pixel(20, 20).0));
+}
+
+#[test]
+fn models_without_geometry_render_the_background() {
+    let mut dff: RwDff = DffParser::new(&build_dff()).parse().unwrap();
+    dff.geometry_list = None;
+
+    let image = renderer().with_background([10, 20, 30, 255]).render(&dff, None);
+    assert!(image.pixels().all(|pixel| pixel.0 == [10, 20, 30, 255]));
+}
+
+#[test]
+fn encodes_png_files() {
+    let dff = DffParser::new(&build_dff()).parse().unwrap();
+    let png = PreviewRenderer::new(16, 8).render_png(&dff, None).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    // The IHDR chunk follows the signature with the width and height
+    assert_eq!(&png[16..24], &[0, 0, 0, 16, 0, 0, 0, 8]);
+}
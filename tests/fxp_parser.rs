@@ -0,0 +1,176 @@
+//! Tests for the FXP parser on a small particle project in the layout of
+//! the San Andreas `effects.fxp`.
+
+use rw_parser_rs::renderware::fxp::fxp_parser::{FxKeyframe, FxProperty, FxpParser, RwFxp};
+use std::io::ErrorKind;
+
+const PROJECT: &str = "FX_PROJECT_DATA:
+
+FX_SYSTEM_DATA:
+109
+
+FILENAME: X:\\SA\\FxTools\\Data\\effects\\gta_pc\\systems\\prt_blood.fxs
+NAME: prt_blood
+LENGTH: 1.000
+PLAYMODE: 2
+CULLDIST: 30.000
+BOUNDINGSPHERE: 0.000 0.500 1.000 2.500
+NUM_PRIMS: 1
+
+FX_PRIM_EMITTER_DATA:
+FX_PRIM_BASE_DATA:
+NAME: blood
+MATRIX: 1.000 0.000 0.000 0.000 1.000 0.000 0.000 0.000 1.000 0.000 0.000 0.250
+TEXTURE: blood_hazy
+TEXTURE2: NULL
+TEXTURE3: NULL
+TEXTURE4: NULL
+ALPHAON: 1
+SRCBLENDID: 4
+DSTBLENDID: 5
+
+NUM_INFOS: 2
+FX_INFO_EMRATE_DATA:
+TIMEMODEPRT: 1
+RATE:
+FX_INTERP_DATA:
+LOOPED: 0
+NUM_KEYS: 2
+TIME: 0.000
+VAL: 100.000
+TIME: 0.500
+VAL: 0.000
+
+FX_INFO_COLOUR_DATA:
+TIMEMODEPRT: 0
+RED:
+FX_INTERP_DATA:
+LOOPED: 1
+NUM_KEYS: 2
+TIME: 0.000
+VAL: 0.000
+TIME: 2.000
+VAL: 200.000
+GREEN:
+FX_INTERP_DATA:
+LOOPED: 0
+NUM_KEYS: 1
+TIME: 0.000
+VAL: 64.000
+
+FX_SYSTEM_DATA:
+109
+
+NAME: prt_smoke
+LENGTH: 4.000
+PLAYMODE: 0
+CULLDIST: 100.000
+BOUNDINGSPHERE: 0.000 0.000 0.000 0.000
+NUM_PRIMS: 1
+
+FX_PRIM_EMITTER_DATA:
+FX_PRIM_BASE_DATA:
+NAME: smoke
+MATRIX: 1.000 0.000 0.000 0.000 1.000 0.000 0.000 0.000 1.000 0.000 0.000 0.000
+TEXTURE: cloudmasked
+TEXTURE2: blood_hazy
+TEXTURE3: NULL
+TEXTURE4: NULL
+ALPHAON: 0
+SRCBLENDID: 4
+DSTBLENDID: 1
+NUM_INFOS: 0
+
+FX_PROJECT_DATA_END:
+";
+
+fn parse_project() -> RwFxp {
+    FxpParser::new(PROJECT.as_bytes()).parse().unwrap()
+}
+
+#[test]
+fn systems_have_their_settings_and_remaining_properties() {
+    let fxp = parse_project();
+
+    assert_eq!(fxp.systems.len(), 2);
+    let blood = fxp.system("PRT_BLOOD").unwrap();
+    assert_eq!((blood.version, blood.name.as_str()), (Some(109), "prt_blood"));
+    assert_eq!((blood.length, blood.play_mode, blood.cull_distance), (1.0, 2, 30.0));
+    assert_eq!(blood.bounding_sphere, [0.0, 0.5, 1.0, 2.5]);
+    assert_eq!(
+        blood.properties,
+        vec![FxProperty {
+            key: "FILENAME".to_string(),
+            value: "X:\\SA\\FxTools\\Data\\effects\\gta_pc\\systems\\prt_blood.fxs".to_string(),
+        }]
+    );
+    assert!(fxp.system("prt_fire").is_none());
+}
+
+#[test]
+fn primitives_have_their_matrix_blending_and_textures() {
+    let fxp = parse_project();
+
+    let primitive = &fxp.systems[0].primitives[0];
+    assert_eq!((primitive.kind.as_str(), primitive.name.as_str()), ("EMITTER", "blood"));
+    assert_eq!((primitive.matrix[0], primitive.matrix[4], primitive.matrix[11]), (1.0, 1.0, 0.25));
+    assert_eq!(primitive.textures, vec!["blood_hazy".to_string()]);
+    assert_eq!((primitive.alpha_on, primitive.src_blend, primitive.dst_blend), (true, 4, 5));
+    assert!(primitive.properties.is_empty());
+
+    assert_eq!(fxp.systems[1].primitives[0].textures, vec!["cloudmasked".to_string(), "blood_hazy".to_string()]);
+    assert_eq!(fxp.textures(), vec!["blood_hazy", "cloudmasked"]);
+}
+
+#[test]
+fn infos_keep_their_settings_and_interpolators() {
+    let fxp = parse_project();
+    let infos = &fxp.systems[0].primitives[0].infos;
+
+    let kinds: Vec<&str> = infos.iter().map(|info| info.kind.as_str()).collect();
+    assert_eq!(kinds, vec!["EMRATE", "COLOUR"]);
+    assert_eq!(infos[1].properties, vec![FxProperty { key: "TIMEMODEPRT".to_string(), value: "0".to_string() }]);
+
+    let rate = infos[0].interpolator("rate").unwrap();
+    assert!(!rate.looped);
+    assert_eq!(rate.keys, vec![FxKeyframe { time: 0.0, value: 100.0 }, FxKeyframe { time: 0.5, value: 0.0 }]);
+
+    let names: Vec<&str> = infos[1].interpolators.iter().map(|interpolator| interpolator.name.as_str()).collect();
+    assert_eq!(names, vec!["RED", "GREEN"]);
+    assert_eq!(infos[1].interpolator("GREEN").unwrap().keys.len(), 1);
+}
+
+#[test]
+fn interpolators_blend_keys_and_wrap_when_looped() {
+    let fxp = parse_project();
+    let infos = &fxp.systems[0].primitives[0].infos;
+
+    let rate = infos[0].interpolator("RATE").unwrap();
+    assert_eq!(rate.value_at(-1.0), Some(100.0));
+    assert_eq!(rate.value_at(0.25), Some(50.0));
+    assert_eq!(rate.value_at(3.0), Some(0.0));
+
+    let red = infos[1].interpolator("RED").unwrap();
+    assert_eq!(red.value_at(1.0), Some(100.0));
+    assert_eq!(red.value_at(3.0), Some(100.0));
+
+    assert_eq!(infos[1].interpolator("GREEN").unwrap().value_at(5.0), Some(64.0));
+}
+
+#[test]
+fn blocks_outside_of_their_parent_are_rejected() {
+    let error = FxpParser::new(b"FX_PROJECT_DATA:\nFX_INFO_EMRATE_DATA:\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let error = FxpParser::new(b"FX_PROJECT_DATA:\nFX_PRIM_EMITTER_DATA:\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn malformed_values_are_rejected() {
+    let error = FxpParser::new(b"FX_SYSTEM_DATA:\n109\nLENGTH: long\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let error = FxpParser::new(b"FX_SYSTEM_DATA:\nFX_PRIM_EMITTER_DATA:\nMATRIX: 1.0 0.0\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
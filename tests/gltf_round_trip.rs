@@ -0,0 +1,196 @@
+//! Tests for the glTF importer and exporter.
+//!
+//! The importer is fed small hand-written `.glb` files, and its output goes
+//! back through the exporter to check that both agree on the layout.
+
+#![cfg(feature = "gltf")]
+
+use rw_parser_rs::export::gltf::GltfExporter;
+use rw_parser_rs::import::gltf::GltfImporter;
+use rw_parser_rs::renderware::dff::dff_parser::RwSkin;
+use serde_json::{Value, json};
+
+fn floats(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn glb(mut root: Value, mut binary: Vec<u8>) -> Vec<u8> {
+    binary.resize(binary.len().next_multiple_of(4), 0);
+    root["asset"] = json!({ "version": "2.0" });
+    root["buffers"] = json!([{ "byteLength": binary.len() }]);
+    let mut json = serde_json::to_vec(&root).unwrap();
+    json.resize(json.len().next_multiple_of(4), b' ');
+
+    let mut data = Vec::new();
+    data.extend(b"glTF");
+    data.extend(2u32.to_le_bytes());
+    data.extend(((28 + json.len() + binary.len()) as u32).to_le_bytes());
+    data.extend((json.len() as u32).to_le_bytes());
+    data.extend(b"JSON");
+    data.extend(json);
+    data.extend((binary.len() as u32).to_le_bytes());
+    data.extend(b"BIN\0");
+    data.extend(binary);
+    data
+}
+
+/// A unit quad drawn as a triangle fan, under a parent node moved up along Y.
+fn quad() -> Vec<u8> {
+    let positions = floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0]);
+    let uvs = floats(&[0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
+    let mut binary = positions.clone();
+    binary.extend(&uvs);
+
+    let root = json!({
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [
+            { "name": "body", "translation": [0.0, 2.0, 0.0], "children": [1] },
+            { "name": "quad", "mesh": 0 },
+        ],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0, "TEXCOORD_0": 1 }, "mode": 6, "material": 0 }] }],
+        "materials": [{ "pbrMetallicRoughness": { "baseColorFactor": [1.0, 0.5, 0.0, 1.0], "baseColorTexture": { "index": 0 } } }],
+        "textures": [{ "source": 0, "sampler": 0 }],
+        "images": [{ "uri": "textures/panel.png" }],
+        "samplers": [{ "magFilter": 9729, "minFilter": 9987, "wrapS": 33071, "wrapT": 10497 }],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3" },
+            { "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC2" },
+        ],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": positions.len() },
+            { "buffer": 0, "byteOffset": positions.len(), "byteLength": uvs.len() },
+        ],
+    });
+    glb(root, binary)
+}
+
+#[test]
+fn imports_nodes_meshes_and_materials() {
+    let dff = GltfImporter::new(&quad()).import().unwrap();
+
+    let frames = dff.frames();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1].parent_frame, 0);
+    assert_eq!(dff.frame_name(1), Some("quad"));
+    // Y-up becomes Z-up
+    let offset = frames[0].coordinates_offset;
+    assert!(offset.x.abs() < 1e-6 && offset.y.abs() < 1e-6 && (offset.z - 2.0).abs() < 1e-6);
+
+    let geometry = &dff.geometry_list.as_ref().unwrap().geometries[0];
+    assert_eq!(dff.atomics, vec![1]);
+    assert_eq!(geometry.vertex_information.len(), 4);
+    assert_eq!(geometry.triangle_information.len(), 2);
+    assert_eq!(geometry.bin_mesh.meshes[0].index_count, 6);
+
+    let material = &geometry.material_list.material_data[0];
+    assert_eq!((material.color.r, material.color.g, material.color.b), (255, 128, 0));
+    let texture = material.texture.as_ref().unwrap();
+    assert_eq!(texture.texture_name, "panel");
+    assert_eq!((texture.texture_filtering, texture.u_addressing, texture.v_addressing), (6, 3, 1));
+}
+
+#[test]
+fn exported_model_imports_back() {
+    let dff = GltfImporter::new(&quad()).import().unwrap();
+    let glb = GltfExporter::new(&dff).to_glb().unwrap();
+    let imported = GltfImporter::new(&glb).import().unwrap();
+
+    let original = &dff.geometry_list.as_ref().unwrap().geometries[0];
+    let geometry = &imported.geometry_list.as_ref().unwrap().geometries[0];
+    assert_eq!(geometry.vertex_information, original.vertex_information);
+    assert_eq!(geometry.texture_mapping_information, original.texture_mapping_information);
+    assert_eq!(geometry.triangle_information, original.triangle_information);
+}
+
+#[test]
+fn rejects_skins_over_the_bone_limit() {
+    let root = json!({
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "children": [1, 2] }, { "name": "a" }, { "name": "b" }],
+        "skins": [{ "joints": [0, 1, 2] }],
+    });
+    let data = glb(root, Vec::new());
+
+    assert!(GltfImporter::new(&data).with_max_bones(2).import().is_err());
+    let dff = GltfImporter::new(&data).import().unwrap();
+    let bones = &dff.anim_nodes[0].bones;
+    assert_eq!(bones.iter().map(|bone| bone.flags).collect::<Vec<_>>(), vec![0, 3, 1]);
+}
+
+#[test]
+fn rejects_skinned_nodes_without_joints() {
+    let positions = floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    let length = positions.len();
+    let root = json!({
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0, "skin": 0 }],
+        "skins": [{ "joints": [] }],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }],
+        "accessors": [{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }],
+        "bufferViews": [{ "buffer": 0, "byteLength": length }],
+    });
+    assert!(GltfImporter::new(&glb(root, positions)).import().is_err());
+}
+
+#[test]
+fn rejects_accessors_with_bogus_sizes() {
+    let mesh = |accessor: Value| {
+        json!({
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [{ "mesh": 0 }],
+            "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }],
+            "accessors": [accessor],
+            "bufferViews": [{ "buffer": 0, "byteLength": 12, "byteStride": 12 }],
+        })
+    };
+
+    let overflowing = mesh(json!({ "bufferView": 0, "byteOffset": u64::MAX, "componentType": 5126, "count": 2, "type": "VEC3" }));
+    assert!(GltfImporter::new(&glb(overflowing, floats(&[0.0; 3]))).import().is_err());
+
+    let huge = mesh(json!({ "componentType": 5126, "count": u64::MAX / 2, "type": "VEC3" }));
+    assert!(GltfImporter::new(&glb(huge, Vec::new())).import().is_err());
+}
+
+/// Returns the JSON chunk of a `.glb` file.
+fn json_chunk(glb: &[u8]) -> Value {
+    let length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    serde_json::from_slice(&glb[20..20 + length]).unwrap()
+}
+
+fn skin(bone_count: u8) -> RwSkin {
+    RwSkin {
+        bone_count,
+        used_bone_count: bone_count,
+        max_weights_per_vertex: 1,
+        bone_vertex_indices: vec![vec![0, 0, 0, 0]; 4],
+        vertex_weights: vec![vec![1.0, 0.0, 0.0, 0.0]; 4],
+        inverse_bone_matrices: Vec::new(),
+        used_bones: (0..bone_count).collect(),
+        split_data: None,
+    }
+}
+
+#[test]
+fn skins_without_frames_are_not_exported() {
+    let mut dff = GltfImporter::new(&quad()).import().unwrap();
+    dff.frame_list = None;
+    dff.geometry_list.as_mut().unwrap().geometries[0].skin = Some(skin(2));
+    assert!(dff.skin_bone_frames(2).is_empty());
+
+    let root = json_chunk(&GltfExporter::new(&dff).to_glb().unwrap());
+    assert!(root.get("skins").is_none_or(|skins| skins.as_array().unwrap().is_empty()));
+    assert!(root["nodes"].as_array().unwrap().iter().all(|node| node.get("skin").is_none()));
+}
+
+#[test]
+fn skin_joints_fall_back_to_the_trailing_frames() {
+    let mut dff = GltfImporter::new(&quad()).import().unwrap();
+    let frame_count = dff.frames().len();
+    dff.anim_nodes.clear();
+    dff.geometry_list.as_mut().unwrap().geometries[0].skin = Some(skin(1));
+    assert_eq!(dff.skin_bone_frames(1), vec![frame_count - 1]);
+
+    let root = json_chunk(&GltfExporter::new(&dff).to_glb().unwrap());
+    assert_eq!(root["skins"][0]["joints"], json!([frame_count - 1]));
+}
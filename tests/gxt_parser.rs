@@ -0,0 +1,119 @@
+//! Tests for the GXT parser on hand-built tables of each game.
+
+use rw_parser_rs::renderware::gxt::gxt_parser::{gxt_key_hash, GxtParser, GxtVersion};
+
+mod common;
+
+use common::fixed_string;
+
+/// The text data of a table, with the offset of each string.
+fn text_data(texts: &[&str], bits_per_char: u16) -> (Vec<u8>, Vec<u32>) {
+    let mut data = Vec::new();
+    let mut offsets = Vec::new();
+    for text in texts {
+        offsets.push(data.len() as u32);
+        for code in text.chars().map(|character| character as u16).chain([0]) {
+            match bits_per_char {
+                8 => data.push(code as u8),
+                _ => data.extend(code.to_le_bytes()),
+            }
+        }
+    }
+    (data, offsets)
+}
+
+/// A GTA3/VC key block and text block, with keys stored by name.
+fn named_table(entries: &[(&str, &str)]) -> Vec<u8> {
+    let (texts, offsets) = text_data(&entries.iter().map(|(_, text)| *text).collect::<Vec<_>>(), 16);
+    let mut data = b"TKEY".to_vec();
+    data.extend((entries.len() as u32 * 12).to_le_bytes());
+    for ((key, _), offset) in entries.iter().zip(offsets) {
+        data.extend(offset.to_le_bytes());
+        data.extend(fixed_string(key, 8));
+    }
+    data.extend(b"TDAT");
+    data.extend((texts.len() as u32).to_le_bytes());
+    data.extend(texts);
+    data
+}
+
+/// A San Andreas key block and text block, with keys stored as hashes.
+fn hashed_table(entries: &[(&str, &str)]) -> Vec<u8> {
+    let (texts, offsets) = text_data(&entries.iter().map(|(_, text)| *text).collect::<Vec<_>>(), 8);
+    let mut data = b"TKEY".to_vec();
+    data.extend((entries.len() as u32 * 8).to_le_bytes());
+    for ((key, _), offset) in entries.iter().zip(offsets) {
+        data.extend(offset.to_le_bytes());
+        data.extend(gxt_key_hash(key).to_le_bytes());
+    }
+    data.extend(b"TDAT");
+    data.extend((texts.len() as u32).to_le_bytes());
+    data.extend(texts);
+    data
+}
+
+/// A `TABL` block starting at `start`, followed by its tables. Tables
+/// other than `MAIN` repeat their name before their keys.
+fn table_list(start: usize, tables: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let bodies_start = start + 8 + tables.len() * 12;
+    let mut data = b"TABL".to_vec();
+    data.extend((tables.len() as u32 * 12).to_le_bytes());
+    let mut bodies = Vec::new();
+    for (name, table) in tables {
+        data.extend(fixed_string(name, 8));
+        data.extend(((bodies_start + bodies.len()) as u32).to_le_bytes());
+        if *name != "MAIN" {
+            bodies.extend(fixed_string(name, 8));
+        }
+        bodies.extend(table);
+    }
+    data.extend(bodies);
+    data
+}
+
+#[test]
+fn key_hashes_are_the_uninverted_crc32_of_the_uppercase_key() {
+    assert_eq!(gxt_key_hash("FEM_OK"), 0x9F5E42AB);
+    assert_eq!(gxt_key_hash("fem_ok"), gxt_key_hash("FEM_OK"));
+}
+
+#[test]
+fn gta3_files_hold_a_single_main_table() {
+    let gxt = GxtParser::new(&named_table(&[("FEM_OK", "OK"), ("FEM_ACC", "Acción")])).parse().unwrap();
+    assert_eq!(gxt.version, GxtVersion::Gta3);
+    assert_eq!(gxt.tables.len(), 1);
+    assert_eq!(gxt.tables[0].name, "MAIN");
+    assert_eq!(gxt.tables[0].entries[0].key.as_deref(), Some("FEM_OK"));
+    assert_eq!(gxt.get("fem_acc"), Some("Acción"));
+}
+
+#[test]
+fn vice_city_files_hold_named_tables() {
+    let data = table_list(0, &[("MAIN", named_table(&[("FEM_OK", "OK")])), ("JOB1", named_table(&[("JOB1_A", "Go")]))]);
+    let gxt = GxtParser::new(&data).parse().unwrap();
+    assert_eq!(gxt.version, GxtVersion::ViceCity);
+    assert_eq!(gxt.tables.iter().map(|table| table.name.as_str()).collect::<Vec<_>>(), vec!["MAIN", "JOB1"]);
+    assert_eq!(gxt.table("job1").unwrap().get("JOB1_A"), Some("Go"));
+    assert_eq!(gxt.get("FEM_OK"), Some("OK"));
+}
+
+#[test]
+fn san_andreas_files_look_up_keys_by_hash() {
+    let mut data = 4u16.to_le_bytes().to_vec();
+    data.extend(8u16.to_le_bytes());
+    data.extend(table_list(4, &[("MAIN", hashed_table(&[("FEM_OK", "OK")])), ("INTRO", hashed_table(&[("INT_A", "Hey")]))]));
+
+    let gxt = GxtParser::new(&data).parse().unwrap();
+    assert_eq!(gxt.version, GxtVersion::SanAndreas);
+    assert_eq!(gxt.tables[0].entries[0].key, None);
+    assert_eq!(gxt.tables[0].entries[0].key_hash, 0x9F5E42AB);
+    assert_eq!(gxt.get("FEM_OK"), Some("OK"));
+    assert_eq!(gxt.table("INTRO").unwrap().get("int_a"), Some("Hey"));
+}
+
+#[test]
+fn table_lists_larger_than_the_file_are_rejected() {
+    let mut data = b"TABL".to_vec();
+    data.extend(u32::MAX.to_le_bytes());
+    assert!(GxtParser::new(&data).parse().is_err());
+}
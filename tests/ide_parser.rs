@@ -0,0 +1,94 @@
+//! Tests for the IDE parser on small definition files of each game.
+
+use rw_parser_rs::renderware::ide::ide_parser::{IdeObjectKind, IdeParser, RwIde};
+use std::io::ErrorKind;
+
+fn parse(text: &str) -> RwIde {
+    IdeParser::new(text.as_bytes()).parse().unwrap()
+}
+
+#[test]
+fn san_andreas_objects_have_one_draw_distance() {
+    let ide = parse(
+        "objs
+615, veg_tree3, gta_tree_bevhills, 300, 2097152
+end
+tobj
+620, neon_sign, signs, 150, 4, 20, 6
+end
+anim
+1532, gym_door, int_doors, gym_door_anim, 50, 0
+end
+",
+    );
+
+    let tree = &ide.objects[0];
+    assert_eq!((tree.kind, tree.id, tree.txd_name.as_str()), (IdeObjectKind::Object, 615, "gta_tree_bevhills"));
+    assert_eq!((tree.draw_distances.clone(), tree.flags), (vec![300.0], 2097152));
+
+    let sign = &ide.objects[1];
+    assert_eq!((sign.time_on, sign.time_off, sign.flags), (Some(20), Some(6), 4));
+    assert_eq!(ide.objects[2].animation.as_deref(), Some("gym_door_anim"));
+}
+
+#[test]
+fn vice_city_objects_store_a_mesh_count() {
+    let ide = parse(
+        "objs
+100, road_bit, roads, 2, 100, 300, 0x80
+end
+weap
+258, colt45, colt45, colt45, 1, 30
+end
+hier
+300, cutobj01, generic
+end
+",
+    );
+
+    assert_eq!(ide.objects[0].draw_distances, vec![100.0, 300.0]);
+    assert_eq!(ide.objects[0].flags, 0x80);
+    let weapon = &ide.objects[1];
+    assert_eq!((weapon.kind, weapon.animation.as_deref()), (IdeObjectKind::Weapon, Some("colt45")));
+    assert_eq!((weapon.draw_distances.clone(), weapon.flags), (vec![30.0], 0));
+    assert_eq!(ide.objects[2].kind, IdeObjectKind::Hierarchy);
+}
+
+#[test]
+fn peds_vehicles_and_texture_parents_are_parsed() {
+    let ide = parse(
+        "peds
+7, male01, male01, CIVMALE, STAT_STREET_GUY, man, 1FFFF, 0, man, 1, 4, PED_TYPE_GEN, VOICE_GEN_BBDYG1, VOICE_GEN_BBDYG2
+end
+cars
+411, infernus, infernus, car, INFERNUS, INFERNUS, null, richfamily, 10, 0, 0, -1, 0.7, 0.7, -1
+end
+txdp
+infernus, vehicle
+end
+2dfx
+1, 0, 0, 0
+end
+",
+    );
+
+    let ped = &ide.peds[0];
+    assert_eq!((ped.ped_type.as_str(), ped.anim_group.as_str(), ped.cars_can_drive), ("CIVMALE", "man", 0x1FFFF));
+    assert_eq!(ped.extra.len(), 7);
+
+    let vehicle = &ide.vehicles[0];
+    assert_eq!((vehicle.id, vehicle.vehicle_type.as_str(), vehicle.handling_id.as_str()), (411, "car", "INFERNUS"));
+    assert_eq!(vehicle.extra[0], "null");
+
+    assert_eq!(ide.txd_for_model("INFERNUS"), Some("infernus"));
+    assert_eq!(ide.txd_for_model("male01"), Some("male01"));
+    assert_eq!(ide.txd_parent("Infernus"), Some("vehicle"));
+    assert_eq!((ide.other[0].section.as_str(), ide.other[0].fields.len()), ("2dfx", 4));
+}
+
+#[test]
+fn malformed_lines_report_their_line_number() {
+    let error = IdeParser::new(b"objs\n615, tree, trees, 1, 2, 3, 4, 5\nend\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(error.to_string().contains("line 2"), "{}", error);
+}
@@ -0,0 +1,160 @@
+//! Tests for the IFP parser on hand-built `ANP3` and `ANPK` packages.
+//!
+//! Third-party tools store `ANP3` keyframe times either as absolute ticks or
+//! as deltas from the previous keyframe, and the parser has to tell them apart
+//! from the times alone.
+
+use rw_parser_rs::renderware::ifp::ifp_parser::{IfpParser, IfpTimeBase, RwIfpAnimation};
+use rw_parser_rs::renderware::rw_error::RwParseError;
+use rw_parser_rs::renderware::rw_report::ParseMode;
+
+mod common;
+
+use common::fixed_string;
+
+/// Builds a package of one animation with a KR00 bone per list of times.
+fn build_anp3(bone_times: &[&[i16]]) -> Vec<u8> {
+    let mut bones = Vec::new();
+    let mut keyframes_size = 0;
+    for (index, times) in bone_times.iter().enumerate() {
+        bones.extend(fixed_string(&format!("bone{}", index), 24));
+        bones.extend(3u32.to_le_bytes());
+        bones.extend((times.len() as u32).to_le_bytes());
+        bones.extend((index as i32).to_le_bytes());
+        for &time in *times {
+            bones.extend([0i16, 0, 0, 4096, time].iter().flat_map(|value| value.to_le_bytes()));
+            keyframes_size += 10;
+        }
+    }
+
+    let mut body = fixed_string("package", 24);
+    body.extend(1u32.to_le_bytes());
+    body.extend(fixed_string("walk", 24));
+    body.extend((bone_times.len() as u32).to_le_bytes());
+    body.extend((keyframes_size as u32).to_le_bytes());
+    body.extend(0u32.to_le_bytes());
+    body.extend(bones);
+
+    let mut data = b"ANP3".to_vec();
+    data.extend((body.len() as u32).to_le_bytes());
+    data.extend(body);
+    data
+}
+
+fn parse(bone_times: &[&[i16]]) -> RwIfpAnimation {
+    IfpParser::new(&build_anp3(bone_times)).parse().unwrap().animations.remove(0)
+}
+
+fn times(animation: &RwIfpAnimation, bone: usize) -> Vec<f32> {
+    animation.bones[bone].keyframes.iter().map(|keyframe| keyframe.time).collect()
+}
+
+#[test]
+fn ascending_times_are_absolute() {
+    let animation = parse(&[&[0, 2, 4, 6], &[0, 6]]);
+    assert_eq!(animation.original_time_base, IfpTimeBase::Ticks);
+    assert_eq!(times(&animation, 0), vec![0.0, 2.0, 4.0, 6.0]);
+}
+
+#[test]
+fn repeated_intervals_are_deltas() {
+    // Non-decreasing, but absolute times never repeat after the start
+    let animation = parse(&[&[0, 2, 2, 2]]);
+    assert_eq!(animation.original_time_base, IfpTimeBase::DeltaTicks);
+    assert_eq!(times(&animation, 0), vec![0.0, 2.0, 4.0, 6.0]);
+}
+
+#[test]
+fn one_delta_bone_decides_for_the_animation() {
+    // The first bone looks absolute on its own, the second cannot be
+    let animation = parse(&[&[0, 1, 2, 3], &[0, 3, 1, 2]]);
+    assert_eq!(animation.original_time_base, IfpTimeBase::DeltaTicks);
+    assert_eq!(times(&animation, 0), vec![0.0, 1.0, 3.0, 6.0]);
+    assert_eq!(times(&animation, 1), vec![0.0, 3.0, 4.0, 6.0]);
+}
+
+/// Builds an `ANPK` package of `animations` whose `INFO` block declares
+/// `info_size` bytes, followed by `body`.
+fn build_anpk(info_size: u32, animations: u32, body: &[u8]) -> Vec<u8> {
+    let mut data = b"ANPK".to_vec();
+    data.extend(0u32.to_le_bytes());
+    data.extend(b"INFO");
+    data.extend(info_size.to_le_bytes());
+    data.extend(animations.to_le_bytes());
+    data.extend(body);
+    data
+}
+
+#[test]
+fn info_blocks_too_small_for_their_count_are_rejected() {
+    // Parsed without `parse_untrusted`, so an underflow would fail the test
+    let error = IfpParser::new(&build_anpk(2, 1, &[])).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::InconsistentData { offset: 12, .. }), "{}", error);
+}
+
+#[test]
+fn name_sizes_larger_than_the_file_are_rejected() {
+    let mut body = fixed_string("package", 4);
+    body.extend(b"NAME");
+    body.extend(u32::MAX.to_le_bytes());
+    body.extend(b"walk");
+    assert!(IfpParser::new(&build_anpk(8, 1, &body)).parse().is_err());
+    assert!(IfpParser::parse_untrusted(&build_anpk(8, 1, &body)).is_err());
+}
+
+/// Builds an `ANPK` package with one animation of one bone with a `KR00`
+/// keyframe, declaring `bones_count` bones and a `CPAN` chunk of
+/// `cpan_size` bytes.
+fn build_anpk_animation(bones_count: u32, cpan_size: u32) -> Vec<u8> {
+    let mut anim = b"ANIM".to_vec();
+    anim.extend(44u32.to_le_bytes());
+    anim.extend(fixed_string("root", 28));
+    for value in [1u32, 0, 0, 0] {
+        anim.extend(value.to_le_bytes());
+    }
+    anim.extend(b"KR00");
+    anim.extend(20u32.to_le_bytes());
+    for value in [0.0f32, 0.0, 0.0, 1.0, 0.0] {
+        anim.extend(value.to_le_bytes());
+    }
+
+    let mut dgan = b"INFO".to_vec();
+    dgan.extend(8u32.to_le_bytes());
+    dgan.extend(bones_count.to_le_bytes());
+    dgan.extend(0u32.to_le_bytes());
+    dgan.extend(b"CPAN");
+    dgan.extend(cpan_size.to_le_bytes());
+    dgan.extend(&anim);
+
+    let mut body = fixed_string("pack", 4);
+    body.extend(b"NAME");
+    body.extend(4u32.to_le_bytes());
+    body.extend(b"walk");
+    body.extend(b"DGAN");
+    body.extend((dgan.len() as u32).to_le_bytes());
+    body.extend(dgan);
+    build_anpk(8, 1, &body)
+}
+
+#[test]
+fn anpk_chunks_are_read_within_their_sizes() {
+    let ifp = IfpParser::new(&build_anpk_animation(1, 80)).parse().unwrap();
+    assert_eq!(ifp.animations[0].bones[0].keyframes.len(), 1);
+
+    // The bone count is checked against the DGAN chunk, not the file
+    let mut data = build_anpk_animation(8, 80);
+    data.extend([0; 64]);
+    let error = IfpParser::new(&data).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::ExcessiveCount { what: "bones", .. }), "{}", error);
+}
+
+#[test]
+fn bones_read_past_their_cpan_chunk_are_suspicious() {
+    let data = build_anpk_animation(1, 76);
+
+    let (ifp, report) = IfpParser::new(&data).parse_with_report().unwrap();
+    assert_eq!(ifp.animations[0].bones[0].keyframes.len(), 1);
+    assert_eq!(report.suspicious_values().count(), 1);
+
+    assert!(IfpParser::new(&data).with_mode(ParseMode::Strict).parse().is_err());
+}
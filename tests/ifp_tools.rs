@@ -0,0 +1,307 @@
+//! Tests for the IFP tools on hand-built animations: retargeting onto a DFF
+//! frame hierarchy, root motion extraction, keyframe reduction and
+//! conversion between the `ANP3` and `ANPK` time bases.
+
+use rw_parser_rs::renderware::common::types::{RwQuaternion, RwVector3};
+use rw_parser_rs::renderware::dff::dff_parser::DffParser;
+use rw_parser_rs::renderware::ifp::ifp_parser::{
+    IfpTimeBase, IfpVersion, KeyframeType, RwIfp, RwIfpAnimation, RwIfpBone, RwIfpKeyframe,
+};
+
+mod common;
+
+use common::{floats, section};
+
+fn vector(x: f32, y: f32, z: f32) -> RwVector3 {
+    RwVector3 { x, y, z }
+}
+
+/// A rotation of `angle` radians around the Z axis.
+fn rotation_z(angle: f32) -> RwQuaternion {
+    RwQuaternion { x: 0.0, y: 0.0, z: (angle / 2.0).sin(), w: (angle / 2.0).cos() }
+}
+
+fn keyframe(time: f32, position: RwVector3, rotation: RwQuaternion) -> RwIfpKeyframe {
+    RwIfpKeyframe { time, position, rotation, scale: vector(1.0, 1.0, 1.0) }
+}
+
+fn bone(name: &str, bone_id: Option<i32>, keyframe_type: KeyframeType, keyframes: Vec<RwIfpKeyframe>) -> RwIfpBone {
+    RwIfpBone {
+        name: name.to_string(),
+        keyframe_type,
+        use_bone_id: bone_id.is_some(),
+        bone_id: bone_id.unwrap_or(0),
+        keyframes,
+        anpk_info: None,
+    }
+}
+
+fn animation(time_base: IfpTimeBase, bones: Vec<RwIfpBone>) -> RwIfpAnimation {
+    RwIfpAnimation { name: "walk".to_string(), bones, anpk_info: None, time_base, original_time_base: time_base }
+}
+
+/// A clump of an unnamed root frame and two named child frames with HAnim
+/// bone ids 1 and 2, offset along the Y axis.
+fn build_skeleton_dff() -> Vec<u8> {
+    let mut frames = 3u32.to_le_bytes().to_vec();
+    for (parent, offset) in [(-1i32, 0.0f32), (0, 1.0), (1, 0.5)] {
+        frames.extend(floats(&[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, offset, 0.0]));
+        frames.extend(parent.to_le_bytes());
+        frames.extend(0u32.to_le_bytes());
+    }
+    let mut frame_list = section(0x01, &frames);
+    frame_list.extend(section(0x03, &[]));
+    for (name, bone_id) in [("Pelvis", 1i32), ("Spine", 2)] {
+        let mut anim_node = 0x100u32.to_le_bytes().to_vec();
+        anim_node.extend(bone_id.to_le_bytes());
+        anim_node.extend(0i32.to_le_bytes());
+        let mut extension = section(0x0253F2FE, name.as_bytes());
+        extension.extend(section(0x011E, &anim_node));
+        frame_list.extend(section(0x03, &extension));
+    }
+
+    let mut clump = section(0x01, &[0; 12]);
+    clump.extend(section(0x0E, &frame_list));
+    clump.extend(section(0x1A, &section(0x01, &0u32.to_le_bytes())));
+    section(0x10, &clump)
+}
+
+#[test]
+fn retargeting_matches_bones_by_id_then_by_name() {
+    let dff = DffParser::new(&build_skeleton_dff()).parse().unwrap();
+    let quarter_turn = rotation_z(std::f32::consts::FRAC_PI_2);
+    let walk = animation(
+        IfpTimeBase::Seconds,
+        vec![
+            bone(
+                "hips",
+                Some(1),
+                KeyframeType::KRT0,
+                vec![keyframe(0.0, vector(0.0, 1.0, 0.0), RwQuaternion::IDENTITY), keyframe(1.0, vector(2.0, 1.0, 0.0), RwQuaternion::IDENTITY)],
+            ),
+            bone("SPINE", None, KeyframeType::KR00, vec![keyframe(0.0, vector(9.0, 9.0, 9.0), quarter_turn)]),
+            bone("Tail", None, KeyframeType::KR00, vec![keyframe(0.0, vector(0.0, 0.0, 0.0), RwQuaternion::IDENTITY)]),
+            bone("Pelvis", None, KeyframeType::KR00, vec![keyframe(0.0, vector(0.0, 0.0, 0.0), RwQuaternion::IDENTITY)]),
+        ],
+    );
+
+    let retargeted = walk.retarget(&dff, &[0.0, 0.5, 1.0]);
+
+    assert_eq!(retargeted.times, vec![0.0, 0.5, 1.0]);
+    assert_eq!(retargeted.tracks.len(), 3);
+    // The second bone for the pelvis frame and a bone of no frame are left out
+    assert_eq!(retargeted.unmatched_bones, vec!["Tail".to_string(), "Pelvis".to_string()]);
+
+    let root = &retargeted.tracks[0];
+    assert!(!root.animated);
+    assert_eq!((root.bone_id, root.name.as_deref()), (None, None));
+    assert!(root.transforms.iter().all(|transform| transform.position == vector(0.0, 0.0, 0.0)));
+
+    let pelvis = &retargeted.tracks[1];
+    assert!(pelvis.animated);
+    assert_eq!((pelvis.bone_id, pelvis.name.as_deref()), (Some(1), Some("Pelvis")));
+    let positions: Vec<f32> = pelvis.transforms.iter().map(|transform| transform.position.x).collect();
+    assert_eq!(positions, vec![0.0, 1.0, 2.0]);
+
+    // Rotation-only bones keep the bind translation of their frame
+    let spine = &retargeted.tracks[2];
+    assert!(spine.animated);
+    assert_eq!(spine.transforms[1].position, vector(0.0, 0.5, 0.0));
+    assert!(spine.transforms[1].rotation.angle_to(quarter_turn) < 1e-3);
+}
+
+fn moving_root() -> RwIfpAnimation {
+    animation(
+        IfpTimeBase::Seconds,
+        vec![
+            bone("Spine", Some(2), KeyframeType::KR00, vec![keyframe(0.0, vector(0.0, 0.0, 0.0), RwQuaternion::IDENTITY)]),
+            bone(
+                "Root",
+                Some(0),
+                KeyframeType::KRT0,
+                vec![
+                    keyframe(0.0, vector(1.0, 2.0, 1.0), RwQuaternion::IDENTITY),
+                    keyframe(0.5, vector(1.5, 4.0, 1.25), RwQuaternion::IDENTITY),
+                    keyframe(1.0, vector(2.0, 6.0, 1.0), RwQuaternion::IDENTITY),
+                ],
+            ),
+        ],
+    )
+}
+
+#[test]
+fn root_motion_is_relative_to_the_first_keyframe() {
+    let walk = moving_root();
+    assert_eq!(walk.root_bone_index(), Some(1));
+
+    let motion = walk.extract_root_motion().unwrap();
+
+    assert_eq!(motion.bone_name, "Root");
+    let translations: Vec<RwVector3> = motion.keyframes.iter().map(|keyframe| keyframe.translation).collect();
+    assert_eq!(translations, vec![vector(0.0, 0.0, 0.0), vector(0.5, 2.0, 0.25), vector(1.0, 4.0, 0.0)]);
+    assert_eq!(motion.keyframes[1].time, 0.5);
+}
+
+#[test]
+fn in_place_animations_keep_the_root_at_its_start() {
+    let mut walk = moving_root();
+    let motion = walk.make_in_place(false).unwrap();
+    assert_eq!(motion.keyframes[1].translation, vector(0.5, 2.0, 0.25));
+    assert!(walk.bones[1].keyframes.iter().all(|keyframe| keyframe.position == vector(1.0, 2.0, 1.0)));
+
+    // Vertical movement stays in the animation and out of the motion
+    let mut walk = moving_root();
+    let motion = walk.make_in_place(true).unwrap();
+    assert_eq!(motion.keyframes[1].translation, vector(0.5, 2.0, 0.0));
+    let heights: Vec<f32> = walk.bones[1].keyframes.iter().map(|keyframe| keyframe.position.z).collect();
+    assert_eq!(heights, vec![1.0, 1.25, 1.0]);
+    assert_eq!(walk.bones[1].keyframes[2].position.x, 1.0);
+}
+
+#[test]
+fn root_motion_needs_a_translated_root() {
+    let mut walk = moving_root();
+    walk.bones[1].keyframe_type = KeyframeType::KR00;
+    assert!(walk.extract_root_motion().is_none());
+    assert!(walk.make_in_place(false).is_none());
+
+    // Without a bone id 0 or a bone named root, the first bone is the root
+    let mut walk = moving_root();
+    walk.bones[1].bone_id = 3;
+    walk.bones[1].name = "Pelvis".to_string();
+    assert_eq!(walk.root_bone_index(), Some(0));
+}
+
+/// A bone moving along X at one unit per second and turning at a constant
+/// rate, with a bump of `bump` units along Y at the middle keyframe.
+fn steady_bone(bump: f32) -> RwIfpBone {
+    let keyframes = (0..=6)
+        .map(|index| {
+            let time = index as f32;
+            let y = if index == 3 { bump } else { 0.0 };
+            keyframe(time, vector(time, y, 0.0), rotation_z(time * 0.1))
+        })
+        .collect();
+    bone("Pelvis", Some(1), KeyframeType::KRT0, keyframes)
+}
+
+fn assert_within(original: &RwIfpBone, optimized: &RwIfpBone, tolerance: f32) {
+    for keyframe in &original.keyframes {
+        let pose = optimized.sample(keyframe.time);
+        let offset = ((pose.position.x - keyframe.position.x).powi(2) + (pose.position.y - keyframe.position.y).powi(2)).sqrt();
+        assert!(offset <= tolerance + 1e-5, "position off by {} at {}", offset, keyframe.time);
+        assert!(pose.rotation.angle_to(keyframe.rotation) <= tolerance + 1e-3);
+    }
+}
+
+#[test]
+fn linear_motion_reduces_to_its_end_keyframes() {
+    let original = steady_bone(0.0);
+    let mut walk = animation(IfpTimeBase::Seconds, vec![original.clone()]);
+
+    let report = walk.optimize(0.001);
+
+    assert_eq!((report.keyframes_before, report.keyframes_after), (7, 2));
+    assert!((report.reduction() - 5.0 / 7.0).abs() < 1e-6);
+    let times: Vec<f32> = walk.bones[0].keyframes.iter().map(|keyframe| keyframe.time).collect();
+    assert_eq!(times, vec![0.0, 6.0]);
+    assert_within(&original, &walk.bones[0], 0.001);
+}
+
+#[test]
+fn keyframes_outside_the_tolerance_are_kept() {
+    let original = steady_bone(0.05);
+
+    // A bump within the tolerance is smoothed away
+    let mut loose = animation(IfpTimeBase::Seconds, vec![original.clone()]);
+    assert_eq!(loose.optimize(0.1).keyframes_after, 2);
+    assert_within(&original, &loose.bones[0], 0.1);
+
+    // A bump above it keeps the keyframes needed to reproduce it
+    let mut strict = animation(IfpTimeBase::Seconds, vec![original.clone()]);
+    let report = strict.optimize(0.01);
+    assert!(report.keyframes_after > 2 && report.keyframes_after < 7);
+    assert!(strict.bones[0].keyframes.iter().any(|keyframe| keyframe.time == 3.0));
+    assert_within(&original, &strict.bones[0], 0.01);
+
+    let mut short = animation(IfpTimeBase::Seconds, vec![bone("Pelvis", Some(1), KeyframeType::KR00, original.keyframes[..2].to_vec())]);
+    assert_eq!(short.optimize(10.0).keyframes_after, 2);
+}
+
+fn package(version: IfpVersion, animation: RwIfpAnimation) -> RwIfp {
+    RwIfp { version, name: "ped".to_string(), animations: vec![animation] }
+}
+
+#[test]
+fn anp3_ticks_convert_to_anpk_seconds() {
+    let walk = animation(
+        IfpTimeBase::Ticks,
+        vec![bone(
+            "Pelvis",
+            Some(1),
+            KeyframeType::KRT0,
+            vec![keyframe(0.0, vector(0.5, 0.0, 0.0), RwQuaternion::IDENTITY), keyframe(30.0, vector(1.0, 0.0, 0.0), RwQuaternion::IDENTITY)],
+        )],
+    );
+
+    let converted = package(IfpVersion::ANP3, walk).convert(IfpVersion::ANPK).unwrap();
+
+    assert_eq!(converted.version, IfpVersion::ANPK);
+    let animation = &converted.animations[0];
+    assert_eq!(animation.time_base, IfpTimeBase::Seconds);
+    assert_eq!(animation.seconds_per_unit(), 1.0);
+    let times: Vec<f32> = animation.bones[0].keyframes.iter().map(|keyframe| keyframe.time).collect();
+    assert_eq!(times, vec![0.0, 0.5]);
+    assert_eq!(animation.bones[0].keyframes[1].position.x, 1.0);
+}
+
+#[test]
+fn anpk_seconds_convert_to_quantized_anp3_ticks() {
+    let walk = animation(
+        IfpTimeBase::Seconds,
+        vec![
+            bone(
+                "Pelvis",
+                None,
+                KeyframeType::KRTS,
+                vec![
+                    keyframe(0.0, vector(0.1, 0.0, 0.0), rotation_z(0.3)),
+                    RwIfpKeyframe { scale: vector(2.0, 2.0, 2.0), ..keyframe(0.51, vector(0.2, 0.0, 0.0), rotation_z(0.6)) },
+                ],
+            ),
+            bone("Spine", Some(2), KeyframeType::KR00, vec![keyframe(0.25, vector(3.0, 3.0, 3.0), RwQuaternion::IDENTITY)]),
+        ],
+    );
+
+    let converted = package(IfpVersion::ANPK, walk).convert(IfpVersion::ANP3).unwrap();
+
+    let animation = &converted.animations[0];
+    assert_eq!((animation.time_base, animation.anpk_info), (IfpTimeBase::Ticks, None));
+    assert_eq!(animation.seconds_per_unit(), 1.0 / 60.0);
+    assert_eq!(animation.duration(), 31.0);
+
+    // Name-only bones get their standard id and lose their scale
+    let pelvis = &animation.bones[0];
+    assert_eq!((pelvis.use_bone_id, pelvis.bone_id, pelvis.keyframe_type), (true, 1, KeyframeType::KRT0));
+    let keyframe = &pelvis.keyframes[1];
+    assert_eq!(keyframe.time, 31.0);
+    assert_eq!(keyframe.position.x, (0.2f32 * 1024.0).round() / 1024.0);
+    assert_eq!(keyframe.rotation.z, ((0.3f32).sin() * 4096.0).round() / 4096.0);
+    assert_eq!(keyframe.scale, vector(1.0, 1.0, 1.0));
+
+    // Rotation-only bones drop their translations
+    let spine = &animation.bones[1];
+    assert_eq!((spine.keyframes[0].time, spine.keyframes[0].position), (15.0, vector(0.0, 0.0, 0.0)));
+
+    // Converting back restores the times in seconds
+    let restored = converted.convert(IfpVersion::ANPK).unwrap();
+    assert_eq!(restored.animations[0].bones[0].keyframes[1].time, 31.0 * (1.0 / 60.0));
+}
+
+#[test]
+fn conversion_to_the_same_or_an_unknown_version() {
+    let ifp = package(IfpVersion::ANPK, moving_root());
+
+    assert_eq!(ifp.convert(IfpVersion::ANPK).unwrap(), ifp);
+    assert!(ifp.convert(IfpVersion::UNSUPPORTED).is_err());
+}
@@ -0,0 +1,75 @@
+//! Tests for the IMG reader on archives built with `ImgWriter`.
+
+use rw_parser_rs::renderware::img::img_archive::{ImgArchive, ImgVersion, IMG_SECTOR_SIZE};
+use rw_parser_rs::renderware::img::img_writer::ImgWriter;
+use std::io::{Cursor, ErrorKind};
+
+fn writer() -> ImgWriter {
+    let mut writer = ImgWriter::new();
+    writer.add("infernus.dff", vec![1; 3000]).unwrap();
+    writer.add("infernus.txd", vec![2; 10]).unwrap();
+    writer
+}
+
+fn v1_archive() -> ImgArchive<Cursor<Vec<u8>>> {
+    let (mut directory, mut data) = (Vec::new(), Vec::new());
+    writer().write_v1(&mut directory, &mut data).unwrap();
+    assert_eq!(directory.len(), 64);
+    ImgArchive::open_v1(&directory, Cursor::new(data)).unwrap()
+}
+
+fn v2_archive(data: Vec<u8>) -> ImgArchive<Cursor<Vec<u8>>> {
+    ImgArchive::open_v2(Cursor::new(data)).unwrap()
+}
+
+fn v2_data() -> Vec<u8> {
+    let mut data = Vec::new();
+    writer().write_v2(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn version_1_archives_find_and_read_entries() {
+    let mut archive = v1_archive();
+    assert_eq!(archive.version(), ImgVersion::V1);
+
+    let entry = archive.find("INFERNUS.DFF").unwrap().clone();
+    assert_eq!((entry.offset, entry.size), (0, 2));
+    assert_eq!(entry.extension().as_deref(), Some("dff"));
+    assert_eq!(archive.find("infernus.txd").unwrap().offset, 2);
+
+    let data = archive.read("infernus.dff").unwrap().unwrap();
+    assert_eq!(data.len() as u64, 2 * IMG_SECTOR_SIZE);
+    assert!(data[..3000].iter().all(|&byte| byte == 1) && data[3000..].iter().all(|&byte| byte == 0));
+    assert!(archive.read("missing.dff").unwrap().is_none());
+}
+
+#[test]
+fn version_2_archives_find_and_read_entries() {
+    let mut archive = v2_archive(v2_data());
+    assert_eq!(archive.version(), ImgVersion::V2);
+    assert_eq!(archive.entries().len(), 2);
+
+    // The table takes the first sector
+    assert_eq!(archive.find("infernus.dff").unwrap().offset, 1);
+    let data = archive.read("infernus.txd").unwrap().unwrap();
+    assert_eq!(&data[..10], &[2; 10]);
+}
+
+#[test]
+fn truncated_entries_are_rejected() {
+    let mut data = v2_data();
+    data.truncate(data.len() - 100);
+    let mut archive = v2_archive(data);
+    assert_eq!(archive.read("infernus.txd").unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert!(archive.read("infernus.dff").is_ok());
+}
+
+#[test]
+fn entries_larger_than_the_archive_are_rejected_before_reading() {
+    let mut directory = vec![0; 32];
+    directory[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    directory[8..13].copy_from_slice(b"a.dff");
+    let mut archive = ImgArchive::open_v1(&directory, Cursor::new(vec![0; 2048])).unwrap();
+    assert_eq!(archive.read("a.dff").unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
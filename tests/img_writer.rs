@@ -0,0 +1,116 @@
+//! Tests for editing IMG archives held in memory.
+
+use rw_parser_rs::renderware::img::img_archive::{ImgArchive, IMG_SECTOR_SIZE};
+use rw_parser_rs::renderware::img::img_writer::ImgWriter;
+use std::io::{Cursor, ErrorKind};
+
+type MemoryArchive = ImgArchive<Cursor<Vec<u8>>>;
+
+/// A version 2 archive of `count` one-sector entries named `0.dff`,
+/// `1.dff`, ..., each filled with its index.
+fn v2_archive(count: u8) -> MemoryArchive {
+    let mut writer = ImgWriter::new();
+    for index in 0..count {
+        writer.add(&format!("{}.dff", index), vec![index; 100]).unwrap();
+    }
+    let mut data = Vec::new();
+    writer.write_v2(&mut data).unwrap();
+    ImgArchive::open_v2(Cursor::new(data)).unwrap()
+}
+
+/// Reopens an edited archive from its bytes, as a game would.
+fn reopen(archive: MemoryArchive) -> MemoryArchive {
+    ImgArchive::open_v2(Cursor::new(archive.into_inner().into_inner())).unwrap()
+}
+
+fn read(archive: &mut MemoryArchive, name: &str) -> Vec<u8> {
+    archive.read(name).unwrap().unwrap()
+}
+
+#[test]
+fn appended_entries_are_read_back() {
+    let mut archive = v2_archive(2);
+    archive.append("new.col", &[7; 3000]).unwrap();
+    assert_eq!(archive.append("NEW.col", &[0]).unwrap_err().kind(), ErrorKind::AlreadyExists);
+
+    let mut archive = reopen(archive);
+    let entry = archive.find("new.col").unwrap().clone();
+    assert_eq!((entry.offset, entry.size), (3, 2));
+    assert_eq!(&read(&mut archive, "new.col")[..3000], &[7; 3000]);
+    assert_eq!(&read(&mut archive, "1.dff")[..100], &[1; 100]);
+}
+
+#[test]
+fn replacements_are_written_in_place_when_they_fit() {
+    let mut archive = v2_archive(2);
+    archive.replace("0.dff", &[9; 50]).unwrap();
+    archive.replace("1.dff", &[8; 5000]).unwrap();
+
+    let mut archive = reopen(archive);
+    assert_eq!(archive.find("0.dff").unwrap().offset, 1);
+    let moved = archive.find("1.dff").unwrap().clone();
+    assert_eq!((moved.offset, moved.size), (3, 3));
+    assert_eq!(&read(&mut archive, "0.dff")[..51], &[[9; 50].as_slice(), &[0]].concat());
+    assert_eq!(&read(&mut archive, "1.dff")[..5000], &[8; 5000]);
+}
+
+#[test]
+fn replacements_too_large_for_ver2_change_nothing() {
+    let archive = v2_archive(2);
+    let entries = archive.entries().to_vec();
+    let before = archive.into_inner().into_inner();
+
+    let mut archive = ImgArchive::open_v2(Cursor::new(before.clone())).unwrap();
+    let data = vec![0; (u16::MAX as u64 + 1) as usize * IMG_SECTOR_SIZE as usize];
+    assert_eq!(archive.replace("0.dff", &data).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(archive.append("2.dff", &data).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(archive.entries(), entries.as_slice());
+    assert_eq!(archive.into_inner().into_inner(), before);
+}
+
+#[test]
+fn removed_entries_are_dropped_and_defragmented_away() {
+    let mut archive = v2_archive(3);
+    assert_eq!(archive.remove("0.DFF").unwrap().name, "0.dff");
+    assert_eq!(archive.remove("0.dff").unwrap_err().kind(), ErrorKind::NotFound);
+
+    let size = archive.defragment().unwrap();
+    assert_eq!(size, 3 * IMG_SECTOR_SIZE);
+
+    let mut archive = reopen(archive);
+    assert!(archive.find("0.dff").is_none());
+    assert_eq!(archive.entries().iter().map(|entry| entry.offset).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(&read(&mut archive, "1.dff")[..100], &[1; 100]);
+    assert_eq!(&read(&mut archive, "2.dff")[..100], &[2; 100]);
+}
+
+#[test]
+fn growing_tables_move_the_entries_they_overlap() {
+    // 63 entries fill the first sector with the VER2 header
+    let mut archive = v2_archive(63);
+    archive.append("63.dff", &[63; 100]).unwrap();
+
+    let mut archive = reopen(archive);
+    assert_eq!(archive.entries().len(), 64);
+    assert!(archive.entries().iter().all(|entry| entry.offset >= 2));
+    for index in 0..64u8 {
+        assert_eq!(&read(&mut archive, &format!("{}.dff", index))[..100], &[index; 100]);
+    }
+}
+
+#[test]
+fn version_1_edits_update_the_directory() {
+    let mut writer = ImgWriter::new();
+    writer.add("a.txd", vec![1; 10]).unwrap();
+    let (mut directory, mut data) = (Vec::new(), Vec::new());
+    writer.write_v1(&mut directory, &mut data).unwrap();
+
+    let mut archive = ImgArchive::open_v1(&directory, Cursor::new(data)).unwrap();
+    archive.append("b.txd", &[2; 10]).unwrap();
+    let directory = archive.directory();
+    assert_eq!(directory.len(), 64);
+
+    let mut archive = ImgArchive::open_v1(&directory, Cursor::new(archive.into_inner().into_inner())).unwrap();
+    assert_eq!(archive.find("b.txd").unwrap().offset, 1);
+    assert_eq!(&read(&mut archive, "b.txd")[..10], &[2; 10]);
+}
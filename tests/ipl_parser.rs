@@ -0,0 +1,108 @@
+//! Tests for the IPL parser on small text and binary placement files.
+
+use rw_parser_rs::renderware::common::types::{RwQuaternion, RwVector3};
+use rw_parser_rs::renderware::ipl::ipl_parser::IplParser;
+use std::io::ErrorKind;
+
+mod common;
+
+use common::floats;
+
+const SAN_ANDREAS_IPL: &str = "# Placement of a small area
+inst
+1337, lamppost1, 0, 10.5, -20, 3, 0, 0, 0.6, 0.8, 2
+1338, lamppost1_lod, 0, 10.5, -20, 3, 0, 0, 0, 1, -1
+end
+zone
+SUNMA, 0, -100, -200, -50, 100, 200, 50, 1, SUNMA
+end
+occl
+100, 200, 0, 30, 40, 20, 90, 0, 0
+end
+pick
+22, 1, 2, 3
+end
+cull
+0, 0, 0, -1, -1, -1, 1, 1, 1, 0, 0
+end
+cars
+10, 20, 30, 90, 400, -1, -1, 0, 0, 0, 0, 0
+end
+";
+
+#[test]
+fn san_andreas_text_files_parse_every_section() {
+    let ipl = IplParser::new(SAN_ANDREAS_IPL.as_bytes()).parse().unwrap();
+    assert!(!ipl.binary);
+
+    assert_eq!(ipl.instances.len(), 2);
+    let lamppost = &ipl.instances[0];
+    assert_eq!((lamppost.id, lamppost.model_name.as_deref()), (1337, Some("lamppost1")));
+    assert_eq!(lamppost.position, RwVector3 { x: 10.5, y: -20.0, z: 3.0 });
+    assert_eq!(lamppost.rotation, RwQuaternion { x: 0.0, y: 0.0, z: 0.6, w: 0.8 });
+    assert_eq!((lamppost.lod, ipl.instances[1].lod), (2, -1));
+
+    assert_eq!(ipl.zones[0].name, "SUNMA");
+    assert_eq!(ipl.zones[0].max, RwVector3 { x: 100.0, y: 200.0, z: 50.0 });
+    assert_eq!(ipl.zones[0].text_key.as_deref(), Some("SUNMA"));
+    assert_eq!((ipl.occluders[0].rotation, ipl.occluders[0].extra_rotations), (90.0, [0.0, 0.0]));
+    assert_eq!(ipl.pickups[0].weapon_id, 22);
+    assert_eq!(ipl.culls[0].fields.len(), 11);
+    assert_eq!(ipl.other[0].section, "cars");
+    assert_eq!(ipl.other[0].fields[4], "400");
+}
+
+#[test]
+fn instance_layouts_follow_the_game() {
+    let gta3 = "inst\n100, road, 1, 2, 3, 2, 2, 2, 0, 0, 0, 1\nend\n";
+    let instance = IplParser::new(gta3.as_bytes()).parse().unwrap().instances.remove(0);
+    assert_eq!((instance.interior, instance.lod), (0, -1));
+    assert_eq!(instance.scale, RwVector3 { x: 2.0, y: 2.0, z: 2.0 });
+
+    let vice_city = "INST\n100, road, 5, 1, 2, 3, 1, 1, 1, 0, 0, 0, 1\nEND\n";
+    let instance = IplParser::new(vice_city.as_bytes()).parse().unwrap().instances.remove(0);
+    assert_eq!(instance.interior, 5);
+    assert_eq!(instance.position, RwVector3 { x: 1.0, y: 2.0, z: 3.0 });
+}
+
+#[test]
+fn malformed_lines_report_their_line_number() {
+    let error = IplParser::new(b"inst\n1, a, 0, x, 0, 0, 0, 0, 0, 1, -1\nend\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(error.to_string().contains("line 2"), "{}", error);
+}
+
+/// A binary IPL of one instance and one parked car.
+fn binary_ipl() -> Vec<u8> {
+    let mut data = b"bnry".to_vec();
+    for count in [1i32, 0, 0, 0, 1, 0] {
+        data.extend(count.to_le_bytes());
+    }
+    for offset in [76u32, 0, 0, 0, 0, 0, 0, 0, 116, 0, 0, 0] {
+        data.extend(offset.to_le_bytes());
+    }
+    data.extend(floats(&[1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 1.0]));
+    for value in [1337i32, 3, -1] {
+        data.extend(value.to_le_bytes());
+    }
+    data.extend(floats(&[10.0, 20.5, 30.0, 90.0]));
+    for value in [400i32, -1, -1, 1, 0, 0, 0, 0] {
+        data.extend(value.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn binary_files_keep_instances_and_parked_cars() {
+    let ipl = IplParser::new(&binary_ipl()).parse().unwrap();
+    assert!(ipl.binary);
+
+    let instance = &ipl.instances[0];
+    assert_eq!((instance.id, instance.interior, instance.lod), (1337, 3, -1));
+    assert_eq!(instance.model_name, None);
+    assert_eq!(instance.position, RwVector3 { x: 1.0, y: 2.0, z: 3.0 });
+
+    // Binary cars match the fields of the equivalent text line
+    let text = IplParser::new(b"cars\n10, 20.5, 30, 90, 400, -1, -1, 1, 0, 0, 0, 0\nend\n").parse().unwrap();
+    assert_eq!(ipl.other, text.other);
+}
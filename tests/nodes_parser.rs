@@ -0,0 +1,123 @@
+//! Tests for the path node parser on a small hand-built area.
+
+use rw_parser_rs::renderware::nodes::nodes_parser::{NodesParser, PathLink};
+
+/// A 28 byte path node at the given position in 1/8 units.
+fn node(position: [i16; 3], link_index: u16, node_id: u16, flags: u32) -> Vec<u8> {
+    let mut node = vec![0; 8];
+    for component in position {
+        node.extend(component.to_le_bytes());
+    }
+    node.extend(0u16.to_le_bytes());
+    node.extend(link_index.to_le_bytes());
+    node.extend(3u16.to_le_bytes());
+    node.extend(node_id.to_le_bytes());
+    node.extend([16, 1]);
+    node.extend(flags.to_le_bytes());
+    node
+}
+
+/// An area with two linked vehicle nodes, one pedestrian node linked to
+/// the second vehicle node, and one navi node between the vehicle nodes.
+fn build_nodes() -> Vec<u8> {
+    let mut data = Vec::new();
+    for count in [3u32, 2, 1, 1, 4] {
+        data.extend(count.to_le_bytes());
+    }
+
+    let vehicle_flags = 1 | (2 << 4) | (1 << 6) | (1 << 13) | (15 << 16);
+    data.extend(node([80, -16, 4], 0, 0, vehicle_flags));
+    data.extend(node([160, -16, 4], 1, 1, 2 | (1 << 8)));
+    data.extend(node([160, 16, 0], 3, 2, 1 | (1 << 7)));
+
+    data.extend(120i16.to_le_bytes());
+    data.extend((-16i16).to_le_bytes());
+    data.extend(3u16.to_le_bytes());
+    data.extend(0u16.to_le_bytes());
+    data.extend([100u8, -50i8 as u8]);
+    data.extend((16u32 | (2 << 8) | (1 << 11)).to_le_bytes());
+
+    for (area_id, node_id) in [(3u16, 1u16), (3, 0), (3, 2), (3, 1)] {
+        data.extend(area_id.to_le_bytes());
+        data.extend(node_id.to_le_bytes());
+    }
+    data.extend([0; 768]);
+    for navi_link in [(3u16 << 10) | 5, (3 << 10) | 5, 0, 0] {
+        data.extend(navi_link.to_le_bytes());
+    }
+    data.extend([10, 10, 5, 5]);
+    data.extend([0, 2, 1, 1]);
+    data
+}
+
+#[test]
+fn nodes_are_split_into_vehicle_and_ped_nodes() {
+    let nodes = NodesParser::new(&build_nodes()).parse().unwrap();
+
+    assert_eq!((nodes.vehicle_node_count, nodes.ped_node_count), (2, 1));
+    assert_eq!(nodes.vehicle_nodes().len(), 2);
+    assert_eq!(nodes.ped_nodes().len(), 1);
+
+    let node = &nodes.vehicle_nodes()[0];
+    assert_eq!((node.position.x, node.position.y, node.position.z), (10.0, -2.0, 0.5));
+    assert_eq!((node.area_id, node.node_id, node.width, node.flood_fill), (3, 0, 16, 1));
+    assert_eq!(nodes.ped_nodes()[0].node_id, 2);
+}
+
+#[test]
+fn node_flags_decode_into_traffic_settings() {
+    let nodes = NodesParser::new(&build_nodes()).parse().unwrap();
+
+    let highway = &nodes.nodes[0];
+    assert_eq!((highway.link_count(), highway.traffic_level(), highway.spawn_probability()), (1, 2, 15));
+    assert!(highway.has_roadblocks() && highway.is_highway());
+    assert!(!highway.is_water() && !highway.is_emergency_only());
+
+    let emergency = &nodes.nodes[1];
+    assert_eq!(emergency.link_count(), 2);
+    assert!(emergency.is_emergency_only() && !emergency.has_roadblocks());
+
+    assert!(nodes.nodes[2].is_water());
+}
+
+#[test]
+fn navi_nodes_have_their_direction_and_lanes() {
+    let nodes = NodesParser::new(&build_nodes()).parse().unwrap();
+
+    let navi = &nodes.navi_nodes[0];
+    assert_eq!(navi.position, [15.0, -2.0]);
+    assert_eq!((navi.area_id, navi.node_id), (3, 0));
+    assert_eq!(navi.direction, [1.0, -0.5]);
+    assert_eq!((navi.width(), navi.left_lanes(), navi.right_lanes()), (16, 2, 1));
+}
+
+#[test]
+fn links_combine_the_sections_after_the_filler() {
+    let nodes = NodesParser::new(&build_nodes()).parse().unwrap();
+
+    assert_eq!(
+        nodes.links_of(0),
+        &[PathLink { area_id: 3, node_id: 1, navi_area_id: 3, navi_node_id: 5, length: 10, intersection_flags: 0 }]
+    );
+
+    let neighbors: Vec<(u16, u8, u8)> =
+        nodes.links_of(1).iter().map(|link| (link.node_id, link.length, link.intersection_flags)).collect();
+    assert_eq!(neighbors, vec![(0, 10, 2), (2, 5, 1)]);
+    assert_eq!(nodes.links_of(1)[1].navi_area_id, 0);
+
+    assert_eq!(nodes.links_of(2)[0].node_id, 1);
+    assert!(nodes.links_of(3).is_empty());
+}
+
+#[test]
+fn truncated_files_fail() {
+    let data = build_nodes();
+
+    assert!(NodesParser::new(&data[..data.len() - 1]).parse().is_err());
+    assert!(NodesParser::new(&data[..100]).parse().is_err());
+
+    // A count larger than the file fails instead of allocating for it
+    let mut data = data;
+    data[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(NodesParser::new(&data).parse().is_err());
+}
@@ -0,0 +1,103 @@
+//! Tests for the RRR parser and writer on small hand-built path recordings.
+
+use rw_parser_rs::renderware::common::types::RwVector3;
+use rw_parser_rs::renderware::rrr::rrr_parser::{RRR_FRAME_SIZE, RrrParser, RwRrr};
+use rw_parser_rs::renderware::rrr::rrr_writer::RrrWriter;
+use std::io::ErrorKind;
+
+mod common;
+
+use common::floats;
+
+fn frame(time: u32, velocity: [i16; 3], orientation: [i8; 6], inputs: [u8; 4], position: [f32; 3]) -> Vec<u8> {
+    let mut frame = time.to_le_bytes().to_vec();
+    for component in velocity {
+        frame.extend(component.to_le_bytes());
+    }
+    frame.extend(orientation.map(|component| component as u8));
+    frame.extend(inputs);
+    frame.extend(floats(&position));
+    frame
+}
+
+fn recording() -> Vec<u8> {
+    [
+        frame(0, [0, 0, 0], [127, 0, 0, 0, 127, 0], [0, 0, 0, 0], [2488.5, -1666.0, 13.25]),
+        frame(500, [8192, -16383, 0], [0, -127, 0, 127, 0, 0], [-10i8 as u8, 100, 50, 1], [2490.0, -1660.0, 13.5]),
+    ]
+    .concat()
+}
+
+#[test]
+fn frames_decode_their_fixed_point_values() {
+    let rrr = RrrParser::new(&recording()).parse().unwrap();
+
+    assert_eq!(rrr.frames.len(), 2);
+    assert_eq!(rrr.duration(), 500);
+
+    let frame = &rrr.frames[1];
+    assert_eq!(frame.time, 500);
+    assert_eq!((frame.velocity.x, frame.velocity.y, frame.velocity.z), (8192.0 / 16383.5, -16383.0 / 16383.5, 0.0));
+    assert_eq!((frame.right.x, frame.right.y), (0.0, -1.0));
+    assert_eq!((frame.top.x, frame.top.y), (1.0, 0.0));
+    assert_eq!((frame.steering_angle, frame.gas_pedal, frame.brake_pedal, frame.handbrake), (-0.5, 1.0, 0.5, true));
+    assert_eq!((frame.position.x, frame.position.y, frame.position.z), (2490.0, -1660.0, 13.5));
+}
+
+#[test]
+fn the_up_vector_is_the_cross_product_of_right_and_top() {
+    let rrr = RrrParser::new(&recording()).parse().unwrap();
+
+    let up = rrr.frames[0].up();
+    assert_eq!((up.x, up.y, up.z), (0.0, 0.0, 1.0));
+
+    // A vehicle heading along +X with its right along -Y is still upright
+    let up = rrr.frames[1].up();
+    assert_eq!((up.x, up.y, up.z), (0.0, 0.0, 1.0));
+}
+
+#[test]
+fn empty_recordings_have_no_frames() {
+    let rrr = RrrParser::new(&[]).parse().unwrap();
+
+    assert!(rrr.frames.is_empty());
+    assert_eq!(rrr.duration(), 0);
+}
+
+#[test]
+fn partial_frames_are_rejected() {
+    let mut data = recording();
+    data.push(0);
+
+    let error = RrrParser::new(&data).parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn written_recordings_match_the_original_bytes() {
+    let data = recording();
+    let rrr = RrrParser::new(&data).parse().unwrap();
+
+    let written = RrrWriter::new().write(&rrr);
+
+    assert_eq!(written.len(), 2 * RRR_FRAME_SIZE);
+    assert_eq!(written, data);
+}
+
+#[test]
+fn out_of_range_values_are_clamped() {
+    let mut rrr = RrrParser::new(&recording()).parse().unwrap();
+    let frame = &mut rrr.frames[0];
+    frame.velocity = RwVector3 { x: 10.0, y: -10.0, z: 0.0 };
+    frame.right = RwVector3 { x: 2.0, y: 0.0, z: 0.0 };
+    frame.steering_angle = 10.0;
+    frame.gas_pedal = 3.0;
+
+    let reparsed: RwRrr = RrrParser::new(&RrrWriter::new().write(&rrr)).parse().unwrap();
+
+    let frame = &reparsed.frames[0];
+    assert_eq!((frame.velocity.x, frame.velocity.y), (32767.0 / 16383.5, -32768.0 / 16383.5));
+    assert_eq!(frame.right.x, 1.0);
+    assert_eq!(frame.steering_angle, 127.0 / 20.0);
+    assert_eq!(frame.gas_pedal, 2.55);
+}
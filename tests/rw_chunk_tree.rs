@@ -0,0 +1,96 @@
+//! Tests for the section tree and extractor on big-endian streams.
+
+use rw_parser_rs::renderware::rw_chunk_extractor::{RwChunkExtractor, SectionPath};
+use rw_parser_rs::renderware::rw_chunk_tree::RwChunkTree;
+use rw_parser_rs::renderware::rw_error::RwParseError;
+use rw_parser_rs::renderware::rw_sections::RwSections;
+use rw_parser_rs::renderware::rw_visitor::MAX_SECTION_DEPTH;
+use rw_parser_rs::utils::byte_stream::Endianness;
+
+mod common;
+
+use common::{section, VERSION};
+
+fn big_endian_section(section_type: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(section_type.to_be_bytes());
+    data.extend((payload.len() as u32).to_be_bytes());
+    data.extend(VERSION.to_be_bytes());
+    data.extend(payload);
+    data
+}
+
+/// A big-endian clump holding a struct and an extension with one plugin.
+fn big_endian_clump() -> Vec<u8> {
+    let mut children = big_endian_section(0x01, &[0, 0, 0, 1]);
+    children.extend(big_endian_section(0x03, &big_endian_section(0x0127, &[0, 0, 0, 4])));
+    big_endian_section(0x10, &children)
+}
+
+#[test]
+fn big_endian_trees_descend_into_containers() {
+    let tree = RwChunkTree::parse_with_endianness(&big_endian_clump(), Endianness::Big).unwrap();
+    assert_eq!(tree.chunks.len(), 1);
+
+    let clump = &tree.chunks[0];
+    assert_eq!(clump.section_type, 0x10);
+    assert_eq!(clump.children.iter().map(|chunk| chunk.section_type).collect::<Vec<_>>(), vec![0x01, 0x03]);
+    assert_eq!(clump.children[1].children[0].section_type, 0x0127);
+}
+
+#[test]
+fn big_endian_injection_writes_sizes_big_endian() {
+    let clump = big_endian_clump();
+    let path = SectionPath::new().child(RwSections::RwClump, 0).child(RwSections::RwStruct, 0);
+    let patched = RwChunkExtractor::with_endianness(&clump, Endianness::Big)
+        .unwrap()
+        .inject(&path, &[0, 0, 0, 1, 0, 0, 0, 2])
+        .unwrap();
+
+    assert_eq!(patched.len(), clump.len() + 4);
+    let tree = RwChunkTree::parse_with_endianness(&patched, Endianness::Big).unwrap();
+    assert_eq!(tree.chunks[0].size, clump.len() as u32 - 12 + 4);
+    assert_eq!(tree.chunks[0].children[0].size, 8);
+}
+
+#[test]
+fn missing_sections_are_reported_below_the_deepest_match() {
+    let clump = big_endian_clump();
+    let path = SectionPath::new().child(RwSections::RwClump, 0).child(RwSections::RwGeometryList, 0);
+    let error = RwChunkExtractor::with_endianness(&clump, Endianness::Big).unwrap().extract(&path).unwrap_err();
+    assert!(matches!(error, RwParseError::InconsistentData { offset: 12, .. }), "{}", error);
+}
+
+/// `levels` extensions, each holding only the next one. Built header by
+/// header, as nesting `section` calls would copy quadratically.
+fn nested_extensions(levels: usize) -> Vec<u8> {
+    let mut data = Vec::new();
+    for level in 0..levels {
+        data.extend(0x03u32.to_le_bytes());
+        data.extend(((levels - level - 1) as u32 * 12).to_le_bytes());
+        data.extend(VERSION.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn sections_nested_past_the_depth_limit_are_rejected() {
+    assert!(RwChunkTree::parse(&nested_extensions(MAX_SECTION_DEPTH + 1)).is_ok());
+    // Deep enough to overflow the stack if the walk recursed without a limit
+    let error = RwChunkTree::parse(&nested_extensions(200_000)).unwrap_err();
+    assert!(matches!(error, RwParseError::InconsistentData { .. }), "{}", error);
+}
+
+#[test]
+fn walk_and_find_visit_sections_in_file_order() {
+    let mut children = section(0x01, &[0, 0, 0, 1]);
+    children.extend(section(0x03, &section(0x0127, &[0, 0, 0, 4])));
+    let mut data = section(0x10, &children);
+    data.extend(section(0x03, &section(0x0127, &[])));
+
+    let tree = RwChunkTree::parse(&data).unwrap();
+    let walked: Vec<_> = tree.walk().iter().map(|(depth, chunk)| (*depth, chunk.section_type)).collect();
+    assert_eq!(walked, vec![(0, 0x10), (1, 0x01), (1, 0x03), (2, 0x0127), (0, 0x03), (1, 0x0127)]);
+    assert_eq!(tree.find(0x0127).unwrap().size, 4);
+    assert_eq!(tree.chunks[0].find(0x03).unwrap().offset, 28);
+}
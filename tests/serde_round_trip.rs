@@ -0,0 +1,124 @@
+//! JSON round-trip tests for the parsed DFF, TXD and IFP structures.
+//!
+//! Each test parses a small synthetic file, serializes it to JSON, loads it
+//! back and checks that nothing was lost. The key checks pin the field names
+//! of the JSON schema, which editing pipelines depend on.
+
+use rw_parser_rs::renderware::dff::dff_parser::{DffParser, RwDff};
+use rw_parser_rs::renderware::ifp::ifp_parser::{IfpParser, RwIfp};
+use rw_parser_rs::renderware::txd::txd_parser::{RwTxd, TxdParser};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+mod common;
+
+use common::{build_dff, build_txd, fixed_string, floats};
+
+fn build_ifp() -> Vec<u8> {
+    let mut bone = Vec::new();
+    bone.extend(b"ANIM");
+    bone.extend(44u32.to_le_bytes());
+    bone.extend(fixed_string("Root", 28));
+    bone.extend(2u32.to_le_bytes());
+    bone.extend([0u8; 8]);
+    bone.extend(3i32.to_le_bytes());
+    bone.extend(b"KRT0");
+    bone.extend(64u32.to_le_bytes());
+    bone.extend(floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]));
+    bone.extend(floats(&[0.0, 0.70710677, 0.0, 0.70710677, 1.5, 0.0, 0.25, 0.5]));
+    let mut cpan = b"CPAN".to_vec();
+    cpan.extend((bone.len() as u32).to_le_bytes());
+    cpan.extend(bone);
+
+    let mut info = b"INFO".to_vec();
+    info.extend(8u32.to_le_bytes());
+    info.extend(1u32.to_le_bytes());
+    info.extend(0u32.to_le_bytes());
+    let mut dgan = b"DGAN".to_vec();
+    dgan.extend(((info.len() + cpan.len()) as u32).to_le_bytes());
+    dgan.extend(info);
+    dgan.extend(cpan);
+
+    let mut animation = b"NAME".to_vec();
+    animation.extend(4u32.to_le_bytes());
+    animation.extend(b"walk");
+    animation.extend(dgan);
+
+    let mut body = b"INFO".to_vec();
+    body.extend(8u32.to_le_bytes());
+    body.extend(1u32.to_le_bytes());
+    body.extend(b"ped\0");
+    body.extend(animation);
+
+    let mut data = b"ANPK".to_vec();
+    data.extend((body.len() as u32).to_le_bytes());
+    data.extend(body);
+    data
+}
+
+/// Serializes `value`, loads it back and checks that both the value and
+/// its JSON survive unchanged. Returns the JSON for further checks.
+fn assert_round_trip<T>(value: &T) -> Value
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let json = serde_json::to_string(value).unwrap();
+    let loaded: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(&loaded, value);
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    serde_json::from_str(&json).unwrap()
+}
+
+fn keys(value: &Value) -> Vec<&str> {
+    let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    keys
+}
+
+#[test]
+fn dff_round_trip() {
+    let dff = DffParser::new(&build_dff()).parse().unwrap();
+    assert_eq!(dff.geometry_list.as_ref().unwrap().geometries.len(), 1);
+
+    let json = assert_round_trip::<RwDff>(&dff);
+    assert_eq!(
+        keys(&json),
+        ["anim_nodes", "atomic_pipelines", "atomics", "dummies", "frame_list", "geometry_list", "model_type", "version", "version_number"]
+    );
+    let material = &json["geometry_list"]["geometries"][0]["material_list"]["material_data"][0];
+    assert_eq!(keys(material), ["ambient", "color", "diffuse", "is_textured", "normal_map", "right_to_render", "specular", "texture"]);
+    assert_eq!(material["texture"]["texture_name"], "body");
+}
+
+#[test]
+fn txd_round_trip() {
+    let txd = TxdParser::new(&build_txd()).parse().unwrap();
+    assert_eq!(txd.parent_name.as_deref(), Some("parent"));
+
+    let json = assert_round_trip::<RwTxd>(&txd);
+    assert_eq!(keys(&json), ["parent_name", "texture_dictionary"]);
+    assert_eq!(keys(&json["texture_dictionary"]), ["texture_count", "texture_natives"]);
+    assert_eq!(json["texture_dictionary"]["texture_natives"][0]["texture_name"], "wheel");
+}
+
+#[test]
+fn txd_name_index_is_rebuilt() {
+    let txd = TxdParser::new(&build_txd()).parse().unwrap();
+    let json = serde_json::to_string(&txd).unwrap();
+    let loaded: RwTxd = serde_json::from_str(&json).unwrap();
+    assert!(loaded.texture_dictionary.get_by_name("WHEEL").is_some());
+}
+
+#[test]
+fn ifp_round_trip() {
+    let ifp = IfpParser::new(&build_ifp()).parse().unwrap();
+    assert_eq!(ifp.animations[0].bones[0].keyframes.len(), 2);
+
+    let json = assert_round_trip::<RwIfp>(&ifp);
+    assert_eq!(keys(&json), ["animations", "name", "version"]);
+    assert_eq!(json["version"], "ANPK");
+    let bone = &json["animations"][0]["bones"][0];
+    assert_eq!(keys(bone), ["anpk_info", "bone_id", "keyframe_type", "keyframes", "name", "use_bone_id"]);
+    assert_eq!(keys(&bone["keyframes"][1]), ["position", "rotation", "scale", "time"]);
+}
@@ -0,0 +1,67 @@
+//! Tests for replacing the pixels of texture natives.
+//!
+//! Replaced textures are written with their raw rasters and parsed back, so
+//! the re-encoding has to match the layout the parser decodes.
+
+use rw_parser_rs::renderware::rw_error::RwParseError;
+use rw_parser_rs::renderware::txd::txd_parser::{
+    RwRasterEncoding, RwRasterFormatInfo, RwTextureDictionary, RwTextureNative, RwTxd, TxdParser,
+};
+use rw_parser_rs::renderware::txd::txd_writer::{TxdTextureFormat, TxdWriter};
+use rw_parser_rs::renderware::utils::image_format_enums::D3dFormat;
+
+const PAL8: u32 = 0x2000;
+const PAL4: u32 = 0x4000;
+const RASTER_8888: u32 = 0x0500;
+
+fn palettized(palette_flag: u32, depth: u8) -> RwTextureNative {
+    let raster_format = palette_flag | RASTER_8888;
+    RwTextureNative {
+        texture_name: "sign".to_string(),
+        raster_format,
+        raster_format_info: RwRasterFormatInfo::from_raster_format(raster_format),
+        raster_encoding: RwRasterEncoding::Palettized,
+        d3d_format_type: Some(D3dFormat::D3dfmtP8),
+        depth,
+        ..Default::default()
+    }
+}
+
+/// An 8x8 image of eight opaque colors, few enough for any palette.
+fn image() -> Vec<u8> {
+    (0..64u8).flat_map(|pixel| [(pixel % 8) * 32, 255 - (pixel % 8) * 32, 128, 255]).collect()
+}
+
+fn round_trip(native: RwTextureNative) -> RwTextureNative {
+    let txd = RwTxd {
+        texture_dictionary: RwTextureDictionary::new(vec![native]),
+        parent_name: None,
+    };
+    let written = TxdWriter::new().with_format(TxdTextureFormat::Preserve).write(&txd).unwrap();
+    TxdParser::new(&written).parse().unwrap().texture_dictionary.texture_natives.remove(0)
+}
+
+#[test]
+fn replaced_palettized_pixels_round_trip() {
+    // Only 4-bit rasters pack two indices per byte
+    for (palette_flag, depth, raster_size) in [(PAL8, 8, 64), (PAL4, 4, 32), (PAL4, 8, 64)] {
+        let mut native = palettized(palette_flag, depth);
+        native.replace_pixels(8, 8, &image()).unwrap();
+        assert_eq!(native.raw_mipmaps[0].len(), raster_size, "PAL flag {:#x} at depth {}", palette_flag, depth);
+
+        let parsed = round_trip(native);
+        assert_eq!(parsed.raster_encoding, RwRasterEncoding::Palettized);
+        assert_eq!(parsed.mipmaps[0], image(), "PAL flag {:#x} at depth {}", palette_flag, depth);
+    }
+}
+
+#[test]
+fn mismatched_and_unencodable_pixels_are_unwritable() {
+    let error = palettized(PAL8, 8).replace_pixels(8, 4, &image()).unwrap_err();
+    assert!(matches!(error, RwParseError::UnwritableData { .. }), "{}", error);
+
+    let mut native = palettized(PAL8, 8);
+    native.raster_encoding = RwRasterEncoding::Unknown;
+    let error = native.replace_pixels(8, 8, &image()).unwrap_err();
+    assert!(matches!(error, RwParseError::UnwritableData { .. }), "{}", error);
+}
@@ -0,0 +1,300 @@
+//! Tests for the TXD parser on hand-built dictionaries.
+//!
+//! Each dictionary holds a single texture native, laid out section by section
+//! the way the game's platform-specific writers store it.
+
+use rw_parser_rs::renderware::rw_error::RwParseError;
+use rw_parser_rs::renderware::rw_report::ParseMode;
+use rw_parser_rs::renderware::txd::txd_parser::{RwRasterEncoding, RwRasterFormatInfo, TxdParser};
+use rw_parser_rs::renderware::txd::txd_writer::{TxdTextureFormat, TxdWriter};
+use rw_parser_rs::renderware::utils::image_format_enums::{PaletteType, PlatformType};
+use rw_parser_rs::utils::byte_stream::Endianness;
+use std::io::Cursor;
+
+mod common;
+
+use common::{build_txd, section};
+
+fn u32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Wraps the payload of a texture native into a dictionary of one texture.
+fn dictionary(native: &[u8]) -> Vec<u8> {
+    let mut children = section(0x01, &[1, 0, 0, 0]);
+    children.extend(section(0x15, native));
+    children.extend(section(0x03, &[]));
+    section(0x16, &children)
+}
+
+/// A PS2 texture native without GIF headers, whose levels are sized from
+/// the dimensions and depth alone.
+fn ps2_native(width: u32, height: u32, depth: u32, pixels: &[u8]) -> Vec<u8> {
+    let mut raster_info = u32s(&[width, height, depth, 0x0500]);
+    raster_info.extend([0; 32]);
+    raster_info.extend(u32s(&[pixels.len() as u32, 0, 0, 0]));
+
+    let mut raster = section(0x01, &raster_info);
+    raster.extend(section(0x01, pixels));
+
+    let mut native = section(0x01, &u32s(&[0x0032_5350, 0x1106]));
+    native.extend(section(0x02, b"ps2\0"));
+    native.extend(section(0x02, b"\0\0\0\0"));
+    native.extend(section(0x01, &raster));
+    native.extend(section(0x03, &[]));
+    native
+}
+
+#[test]
+fn ps2_rasters_without_depth_are_rejected() {
+    let txd = dictionary(&ps2_native(4, 4, 0, &[0; 16]));
+    assert!(TxdParser::new(&txd).parse().is_err());
+}
+
+#[test]
+fn ps2_levels_smaller_than_a_byte_still_advance() {
+    // A 4-bit 2x1 raster: a one byte base level and a half byte 1x1 level
+    let txd = dictionary(&ps2_native(2, 1, 4, &[0x21, 0x03]));
+    let native = TxdParser::new(&txd).parse().unwrap().texture_dictionary.texture_natives.remove(0);
+    assert!(matches!(native.raster_encoding, RwRasterEncoding::Ps2 { .. }));
+    assert_eq!(native.raw_mipmaps, vec![vec![0x21], vec![0x03]]);
+}
+
+#[test]
+fn ps2_16_bit_texels_expand_to_full_intensity() {
+    let txd = TxdParser::new(&dictionary(&ps2_native(2, 1, 16, &[0xFF; 4]))).parse().unwrap();
+    assert_eq!(txd.texture_dictionary.texture_natives[0].mipmaps[0], vec![255; 8]);
+}
+
+#[test]
+fn gamecube_natives_read_their_struct_big_endian_and_extensions_little_endian() {
+    let mut native_struct = Vec::new();
+    for value in [6u32, 0x1106] {
+        native_struct.extend(value.to_be_bytes());
+    }
+    native_struct.extend(common::fixed_string("grass", 32));
+    native_struct.extend(common::fixed_string("", 32));
+    native_struct.extend(0x0500u32.to_be_bytes());
+    native_struct.extend(0x1u32.to_be_bytes()); // GX_TF_I8
+    native_struct.extend(8u16.to_be_bytes());
+    native_struct.extend(4u16.to_be_bytes());
+    native_struct.extend([8, 1, 4, 0]);
+    native_struct.extend(32u32.to_be_bytes());
+    native_struct.extend([0x80; 32]);
+
+    let mut native = section(0x01, &native_struct);
+    native.extend(section(0x03, &section(0x0127, &u32s(&[4]))));
+
+    let native = TxdParser::new(&dictionary(&native)).parse().unwrap().texture_dictionary.texture_natives.remove(0);
+    assert_eq!(native.texture_name, "grass");
+    assert_eq!((native.width, native.height), (8, 4));
+    assert_eq!(native.d3d_format, "I8");
+    assert_eq!(native.extensions.anisotropy, Some(4));
+}
+
+/// An uncompressed 2x1 Xbox native of `raster_format` with every texel set
+/// to `texel`.
+fn xbox_native(raster_format: u32, texel: u16) -> Vec<u8> {
+    let mut native_struct = u32s(&[5, 0x1106]);
+    native_struct.extend(common::fixed_string("white", 32));
+    native_struct.extend(common::fixed_string("", 32));
+    native_struct.extend(u32s(&[raster_format]));
+    native_struct.extend([0; 4]);
+    native_struct.extend(2u16.to_le_bytes());
+    native_struct.extend(1u16.to_le_bytes());
+    native_struct.extend([16, 1, 4, 0]);
+    native_struct.extend(u32s(&[4]));
+    native_struct.extend([texel.to_le_bytes(), texel.to_le_bytes()].concat());
+
+    let mut native = section(0x01, &native_struct);
+    native.extend(section(0x03, &[]));
+    native
+}
+
+#[test]
+fn xbox_16_bit_texels_expand_to_full_intensity() {
+    for (raster_format, texel) in [(0x0200, 0xFFFF), (0x0100, 0xFFFF)] {
+        let txd = TxdParser::new(&dictionary(&xbox_native(raster_format, texel))).parse().unwrap();
+        assert_eq!(txd.texture_dictionary.texture_natives[0].mipmaps[0], vec![255; 8]);
+    }
+}
+
+/// A D3D8 native, which stores the alpha flag where D3D9 stores the D3D
+/// format and the DXT type where D3D9 stores its flags.
+fn d3d8_dxt3_native() -> Vec<u8> {
+    d3d8_dxt3_native_of_size(4, 4)
+}
+
+/// A D3D8 DXT3 native holding a single 16-byte block whatever its size.
+fn d3d8_dxt3_native_of_size(width: u16, height: u16) -> Vec<u8> {
+    let mut native_struct = u32s(&[8, 0x1106]);
+    native_struct.extend(common::fixed_string("glass", 32));
+    native_struct.extend(common::fixed_string("", 32));
+    native_struct.extend(u32s(&[0x0300, 1]));
+    native_struct.extend(width.to_le_bytes());
+    native_struct.extend(height.to_le_bytes());
+    native_struct.extend([16, 1, 4, 3]);
+    native_struct.extend(u32s(&[16]));
+    native_struct.extend([0x55; 16]);
+
+    let mut native = section(0x01, &native_struct);
+    native.extend(section(0x03, &[]));
+    native
+}
+
+#[test]
+fn d3d8_natives_read_alpha_and_dxt_type_from_their_own_fields() {
+    let txd = dictionary(&d3d8_dxt3_native());
+    let native = TxdParser::new(&txd).parse().unwrap().texture_dictionary.texture_natives.remove(0);
+    assert!(native.alpha);
+    assert!(native.compressed);
+    assert!(!native.cube_texture);
+    assert!(!native.auto_mip_maps);
+    assert_eq!(native.d3d_format, "");
+    assert!(matches!(native.raster_encoding, RwRasterEncoding::Dxt3));
+}
+
+#[test]
+fn dxt_levels_shorter_than_their_blocks_are_not_decoded() {
+    // An 8x8 DXT3 level needs four blocks, but only one is stored
+    let txd = dictionary(&d3d8_dxt3_native_of_size(8, 8));
+
+    let (parsed, report) = TxdParser::new(&txd).parse_with_report().unwrap();
+    assert!(parsed.texture_dictionary.texture_natives[0].mipmaps[0].is_empty());
+    assert_eq!(report.suspicious_values().count(), 1);
+
+    let error = TxdParser::new(&txd).with_mode(ParseMode::Strict).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::InconsistentData { .. }), "{}", error);
+}
+
+#[test]
+fn d3d8_dictionaries_keep_their_alpha_when_rewritten() {
+    let txd = TxdParser::new(&dictionary(&d3d8_dxt3_native())).parse().unwrap();
+    let written = TxdWriter::new()
+        .with_platform(PlatformType::D3d8)
+        .with_format(TxdTextureFormat::Preserve)
+        .write(&txd)
+        .unwrap();
+
+    let native = TxdParser::new(&written).parse().unwrap().texture_dictionary.texture_natives.remove(0);
+    assert!(native.alpha);
+    assert!(matches!(native.raster_encoding, RwRasterEncoding::Dxt3));
+    assert_eq!(native.raw_mipmaps, vec![vec![0x55; 16]]);
+}
+
+#[test]
+fn palette_types_follow_the_raster_format_bits() {
+    let palette_type = |raster_format| RwRasterFormatInfo::from_raster_format(raster_format).palette_type;
+    assert_eq!(palette_type(0x0500), PaletteType::PaletteNone);
+    assert_eq!(palette_type(0x2500), PaletteType::Palette8);
+    assert_eq!(palette_type(0x4500), PaletteType::Palette4);
+    assert_eq!(PaletteType::Palette8 as u32, 0x2000 >> 13);
+    assert_eq!(PaletteType::Palette4 as u32, 0x4000 >> 13);
+}
+
+#[test]
+fn pal8_natives_read_a_256_entry_palette() {
+    let mut native_struct = u32s(&[9, 0x1106]);
+    native_struct.extend(common::fixed_string("sign", 32));
+    native_struct.extend(common::fixed_string("", 32));
+    native_struct.extend(u32s(&[0x2500, 0]));
+    native_struct.extend(2u16.to_le_bytes());
+    native_struct.extend(1u16.to_le_bytes());
+    native_struct.extend([8, 1, 4, 0]);
+    native_struct.extend((0..=255u8).flat_map(|index| [index, index, index, 255]));
+    native_struct.extend(u32s(&[2]));
+    native_struct.extend([1, 255]);
+
+    let mut native = section(0x01, &native_struct);
+    native.extend(section(0x03, &[]));
+
+    let native = TxdParser::new(&dictionary(&native)).parse().unwrap().texture_dictionary.texture_natives.remove(0);
+    assert_eq!(native.raster_format_info.palette_type, PaletteType::Palette8);
+    assert_eq!(native.raw_palette.len(), 256 * 4);
+    assert_eq!(native.raw_mipmaps, vec![vec![1, 255]]);
+}
+
+#[test]
+fn string_sizes_larger_than_the_file_are_rejected() {
+    let mut txd = build_txd();
+    let start = txd.windows(6).position(|window| window == b"parent").unwrap();
+    txd[start - 8..start - 4].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+    assert!(TxdParser::new(&txd).parse().is_err());
+    assert!(TxdParser::parse_untrusted(&txd).is_err());
+}
+
+#[test]
+fn children_extending_past_their_texture_native_are_rejected() {
+    // Shrink the native so its struct ends past it, while still inside the
+    // file because the extension follows
+    let mut txd = build_txd();
+    let native_size = u32::from_le_bytes(txd[32..36].try_into().unwrap());
+    txd[32..36].copy_from_slice(&(native_size - 20).to_le_bytes());
+
+    let error = TxdParser::new(&txd).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::TruncatedSection { section_type: 0x01, offset: 40, .. }), "{}", error);
+}
+
+fn big_endian_section(section_type: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in [section_type, payload.len() as u32, common::VERSION] {
+        data.extend(value.to_be_bytes());
+    }
+    data.extend(payload);
+    data
+}
+
+#[test]
+fn readers_parse_big_endian_dictionaries() {
+    let mut native_struct = Vec::new();
+    for value in [6u32, 0x1106] {
+        native_struct.extend(value.to_be_bytes());
+    }
+    native_struct.extend(common::fixed_string("grass", 32));
+    native_struct.extend(common::fixed_string("", 32));
+    native_struct.extend(0x0500u32.to_be_bytes());
+    native_struct.extend(0x1u32.to_be_bytes()); // GX_TF_I8
+    native_struct.extend(8u16.to_be_bytes());
+    native_struct.extend(4u16.to_be_bytes());
+    native_struct.extend([8, 1, 4, 0]);
+    native_struct.extend(32u32.to_be_bytes());
+    native_struct.extend([0x80; 32]);
+
+    let mut native = big_endian_section(0x01, &native_struct);
+    native.extend(big_endian_section(0x03, &[]));
+    let mut children = big_endian_section(0x01, &[0, 1, 0, 0]);
+    children.extend(big_endian_section(0x15, &native));
+    children.extend(big_endian_section(0x03, &[]));
+    let mut data = big_endian_section(0x16, &children);
+    data.extend([0xAA; 4]);
+
+    let mut reader = Cursor::new(&data);
+    let txd = TxdParser::parse_from_reader_with_endianness(&mut reader, Endianness::Big).unwrap();
+    assert_eq!(reader.position(), data.len() as u64 - 4);
+    assert_eq!(txd, TxdParser::new(&data).with_endianness(Endianness::Big).parse().unwrap());
+    let native = &txd.texture_dictionary.texture_natives[0];
+    assert_eq!((native.texture_name.as_str(), native.width, native.height), ("grass", 8, 4));
+
+    assert!(TxdParser::parse_from_reader(&mut Cursor::new(&data)).is_err());
+}
+
+#[test]
+fn texture_callbacks_return_parse_errors() {
+    let txd = build_txd();
+
+    let mut decoded = Vec::new();
+    TxdParser::new(&txd)
+        .for_each_texture(|texture, rgba| {
+            decoded.push((texture.texture_name.clone(), rgba.to_vec()));
+            Ok(())
+        })
+        .unwrap();
+    let native = &TxdParser::new(&txd).parse().unwrap().texture_dictionary.texture_natives[0];
+    assert_eq!(decoded, vec![(native.texture_name.clone(), native.decode().unwrap())]);
+
+    // Errors of the callback stop the iteration and are returned unchanged
+    let error = TxdParser::new(&txd)
+        .for_each_texture(|_, _| Err(RwParseError::UnsupportedPlatform { platform: 0xFF, offset: 0 }))
+        .unwrap_err();
+    assert!(matches!(error, RwParseError::UnsupportedPlatform { platform: 0xFF, .. }));
+}
@@ -0,0 +1,126 @@
+//! Tests for the handling.cfg and carcols.dat parsers on small files of each
+//! game.
+
+use rw_parser_rs::renderware::common::types::RwColor;
+use rw_parser_rs::renderware::dat::carcols_dat::{CarColorCombination, CarcolsParser};
+use rw_parser_rs::renderware::dat::handling_cfg::{HandlingParser, HandlingVersion};
+use std::io::ErrorKind;
+
+const GTA3_LANDSTAL: &str =
+    "LANDSTAL 1700.0 2.5 5.0 2.2 0.0 0.0 -0.2 70 0.7 0.8 0.5 5 160.0 0.8 4 D 20.0 0.5 0 35.0 1.2 0.1 0.27 0.25 20000 0.25 -0.2 0.5 20 1 2";
+
+const VICE_CITY_LANDSTAL: &str =
+    "LANDSTAL 1700.0 2.5 5.0 2.2 0.0 0.0 -0.2 70 0.7 0.8 0.5 5 160.0 0.8 4 D 20.0 0.5 0 35.0 1.2 0.1 0.27 0.25 20000 0.25 -0.2 0.5 0.3 20 1 2";
+
+const SAN_ANDREAS_INFERNUS: &str = "INFERNUS 1400.0 2725.3 1.5 0.0 0.0 -0.25 70 0.70 0.80 0.50 5 240.0 30.0 10.0 4 P 11.0 0.51 0 30.0 1.2 0.19 0.0 0.25 -0.10 0.5 0.4 0.37 0.72 95000 40002004 C04000 1 1 1";
+
+#[test]
+fn gta3_vehicles_store_dimensions_and_the_value_before_the_limits() {
+    let handling = HandlingParser::new(format!("; comment\n{}\n", GTA3_LANDSTAL).as_bytes()).parse().unwrap();
+
+    assert_eq!(handling.version, HandlingVersion::Gta3);
+    let landstal = handling.get("landstal").unwrap();
+    assert_eq!(landstal.mass, 1700.0);
+    let dimensions = landstal.dimensions.unwrap();
+    assert_eq!((dimensions.x, dimensions.y, dimensions.z), (2.5, 5.0, 2.2));
+    assert_eq!((landstal.turn_mass, landstal.drag_multiplier, landstal.engine_inertia), (None, None, None));
+    assert_eq!(landstal.centre_of_mass.z, -0.2);
+    assert_eq!((landstal.gear_count, landstal.drive_type, landstal.engine_type), (5, '4', 'D'));
+    assert_eq!((landstal.seat_offset, landstal.collision_damage_multiplier, landstal.monetary_value), (0.27, 0.25, 20000));
+    assert_eq!((landstal.suspension_upper_limit, landstal.suspension_lower_limit), (0.25, -0.2));
+    assert_eq!(landstal.suspension_anti_dive, None);
+    assert_eq!((landstal.handling_flags, landstal.front_lights, landstal.rear_lights), (0x20, 1, 2));
+    assert_eq!((landstal.model_flags, landstal.anim_group), (None, None));
+}
+
+#[test]
+fn vice_city_vehicles_add_the_anti_dive_multiplier() {
+    let text = format!(
+        "{}\n%  PREDATOR 0.5 0.6\n! PCJ600 0.1 0.2 0.3\n$ SPARROW 1.0\n^ 1 2 3\n",
+        VICE_CITY_LANDSTAL
+    );
+    let handling = HandlingParser::new(text.as_bytes()).parse().unwrap();
+
+    assert_eq!(handling.version, HandlingVersion::ViceCity);
+    let landstal = &handling.vehicles[0];
+    assert_eq!((landstal.suspension_bias, landstal.suspension_anti_dive), (0.5, Some(0.3)));
+    assert_eq!(landstal.handling_flags, 0x20);
+
+    assert_eq!((handling.boats[0].id.as_str(), handling.boats[0].values.clone()), ("PREDATOR", vec!["0.5".to_string(), "0.6".to_string()]));
+    assert_eq!((handling.bikes[0].id.as_str(), handling.bikes[0].values.len()), ("PCJ600", 3));
+    assert_eq!(handling.flying[0].id, "SPARROW");
+    assert_eq!(handling.animations[0].id, "1");
+}
+
+#[test]
+fn san_andreas_vehicles_store_the_value_after_the_limits() {
+    let handling = HandlingParser::new(format!("{}\n#\n", SAN_ANDREAS_INFERNUS).as_bytes()).parse().unwrap();
+
+    assert_eq!(handling.version, HandlingVersion::SanAndreas);
+    let infernus = handling.get("INFERNUS").unwrap();
+    assert_eq!((infernus.turn_mass, infernus.drag_multiplier, infernus.dimensions), (Some(2725.3), Some(1.5), None));
+    assert_eq!((infernus.max_velocity, infernus.engine_acceleration, infernus.engine_inertia), (240.0, 30.0, Some(10.0)));
+    assert_eq!((infernus.drive_type, infernus.engine_type, infernus.abs), ('4', 'P', false));
+    assert_eq!(infernus.suspension_high_speed_damping, Some(0.0));
+    assert_eq!((infernus.suspension_upper_limit, infernus.suspension_lower_limit), (0.25, -0.10));
+    assert_eq!((infernus.suspension_bias, infernus.suspension_anti_dive), (0.5, Some(0.4)));
+    assert_eq!((infernus.seat_offset, infernus.collision_damage_multiplier, infernus.monetary_value), (0.37, 0.72, 95000));
+    assert_eq!((infernus.model_flags, infernus.handling_flags), (Some(0x40002004), 0xC04000));
+    assert_eq!((infernus.front_lights, infernus.rear_lights, infernus.anim_group), (1, 1, Some(1)));
+}
+
+#[test]
+fn handling_files_of_mixed_or_unknown_games_are_rejected() {
+    let mixed = format!("{}\n{}\n", GTA3_LANDSTAL, SAN_ANDREAS_INFERNUS);
+    let error = HandlingParser::new(mixed.as_bytes()).parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let error = HandlingParser::new(b"LANDSTAL 1700.0 2.5\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let error = HandlingParser::new(b"% PREDATOR 0.5\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn carcols_resolves_two_and_four_color_combinations() {
+    let carcols = CarcolsParser::new(
+        b"col
+0,0,0 # 0 black
+245,245,245 # 1 white
+42,119,161
+end
+car
+infernus, 0,1, 2,1
+end
+car4
+Stretch, 0,1,2,1
+end
+",
+    )
+    .parse()
+    .unwrap();
+
+    assert_eq!(carcols.palette.len(), 3);
+    assert_eq!(carcols.palette_color(2), Some(RwColor { r: 42, g: 119, b: 161, a: 255 }));
+    assert_eq!(carcols.palette_color(3), None);
+
+    let white = RwColor { r: 245, g: 245, b: 245, a: 255 };
+    let black = RwColor { r: 0, g: 0, b: 0, a: 255 };
+    let blue = carcols.palette[2];
+    assert_eq!(carcols.colors_for_model("INFERNUS"), vec![vec![black, white], vec![blue, white]]);
+
+    let stretch = &carcols.cars[1];
+    assert_eq!(
+        stretch.combinations,
+        vec![CarColorCombination { primary: 0, secondary: 1, tertiary: Some(2), quaternary: Some(1) }]
+    );
+    assert_eq!(stretch.combinations[0].indices(), vec![0, 1, 2, 1]);
+    assert_eq!(carcols.colors_for_model("stretch"), vec![vec![black, white, blue, white]]);
+}
+
+#[test]
+fn carcols_rejects_colors_out_of_range() {
+    let error = CarcolsParser::new(b"col\n0,0,300\nend\n").parse().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
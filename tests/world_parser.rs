@@ -0,0 +1,103 @@
+//! Tests for the world parser on hand-built BSP trees.
+
+use rw_parser_rs::renderware::common::types::RwVector3;
+use rw_parser_rs::renderware::rw_error::RwParseError;
+use rw_parser_rs::renderware::world::world_parser::{RwWorldSector, WorldParser};
+
+mod common;
+
+use common::{floats, section};
+
+fn u32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// An atomic sector holding one prelit triangle and its bin mesh.
+fn atomic_sector() -> Vec<u8> {
+    let mut sector_struct = u32s(&[0, 1, 3]); // window base, triangles, vertices
+    sector_struct.extend(floats(&[1.0, 1.0, 0.0, 0.0, 0.0, 0.0])); // max, min
+    sector_struct.extend(u32s(&[0, 0]));
+    sector_struct.extend(floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]));
+    sector_struct.extend([255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255]);
+    for value in [0u16, 0, 1, 2] {
+        sector_struct.extend(value.to_le_bytes());
+    }
+
+    let bin_mesh = section(0x050E, &u32s(&[0, 1, 3, 3, 0, 0, 1, 2]));
+    let mut sector = section(0x01, &sector_struct);
+    sector.extend(section(0x03, &bin_mesh));
+    section(0x09, &sector)
+}
+
+/// A plane sector splitting X at `value` between `left` and `right`.
+fn plane_sector(value: f32, left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut plane_struct = u32s(&[0]);
+    plane_struct.extend(floats(&[value]));
+    plane_struct.extend(u32s(&[left.starts_with(&[0x09]) as u32, right.starts_with(&[0x09]) as u32]));
+    plane_struct.extend(floats(&[value, value]));
+    let mut plane = section(0x01, &plane_struct);
+    plane.extend(left);
+    plane.extend(right);
+    section(0x0A, &plane)
+}
+
+/// A prelit world declaring `plane_sectors` plane sectors around `root`.
+fn world(plane_sectors: u32, root: &[u8]) -> Vec<u8> {
+    let mut world_struct = u32s(&[root.starts_with(&[0x09]) as u32]);
+    world_struct.extend(floats(&[0.0, 0.0, 0.0]));
+    world_struct.extend(u32s(&[1, 3, plane_sectors, plane_sectors + 1, 0, 0x08]));
+
+    let mut children = section(0x01, &world_struct);
+    children.extend(section(0x08, &section(0x01, &u32s(&[0]))));
+    children.extend(root);
+    section(0x0B, &children)
+}
+
+#[test]
+fn one_sector_worlds_keep_their_geometry() {
+    let world = WorldParser::new(&world(0, &atomic_sector())).parse().unwrap();
+    assert_eq!(world.version_number, 0x36003);
+    assert_eq!(world.triangle_count, 1);
+    assert!(world.bounding_box.is_none());
+
+    let RwWorldSector::Atomic(sector) = &world.root else {
+        panic!("expected an atomic root sector");
+    };
+    assert_eq!(sector.max, RwVector3 { x: 1.0, y: 1.0, z: 0.0 });
+    assert_eq!(sector.vertices[1], RwVector3 { x: 1.0, y: 0.0, z: 0.0 });
+    assert_eq!(sector.vertex_colors[2].b, 255);
+    assert!(sector.normals.is_empty() && sector.texture_coordinates.is_empty());
+    assert_eq!(sector.triangles.len(), 1);
+    assert_eq!(sector.triangles[0].vector, RwVector3 { x: 0.0, y: 1.0, z: 2.0 });
+
+    let bin_mesh = sector.bin_mesh.as_ref().unwrap();
+    assert_eq!(bin_mesh.mesh_count, 1);
+    assert_eq!(bin_mesh.meshes[0].indices, vec![0, 1, 2]);
+}
+
+#[test]
+fn plane_sectors_split_into_atomic_sectors() {
+    let world = WorldParser::new(&world(1, &plane_sector(0.5, &atomic_sector(), &atomic_sector()))).parse().unwrap();
+    let RwWorldSector::Plane(plane) = &world.root else {
+        panic!("expected a plane root sector");
+    };
+    assert_eq!(plane.value, 0.5);
+    assert_eq!(world.atomic_sectors().len(), 2);
+}
+
+#[test]
+fn plane_sectors_deeper_than_declared_are_rejected() {
+    let mut root = atomic_sector();
+    for _ in 0..3 {
+        root = plane_sector(0.5, &root, &atomic_sector());
+    }
+    assert!(WorldParser::new(&world(3, &root)).parse().is_ok());
+    let error = WorldParser::new(&world(2, &root)).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::InconsistentData { .. }), "{}", error);
+}
+
+#[test]
+fn other_sections_are_rejected() {
+    let error = WorldParser::new(&section(0x10, &[])).parse().unwrap_err();
+    assert!(matches!(error, RwParseError::UnexpectedSection { expected: 0x0B, found: 0x10, offset: 0 }), "{}", error);
+}
@@ -0,0 +1,233 @@
+//! Property-based round-trip tests for the writers.
+//!
+//! Each property generates random but valid structures, writes them, parses
+//! the output and checks that the parsed structure is the generated one, so
+//! that a writer and its parser cannot drift apart unnoticed. DFF and IFP
+//! have no writer yet; their properties belong here once they do.
+
+use proptest::prelude::*;
+use rw_parser_rs::renderware::col::col_parser::{
+    ColBounds, ColBox, ColFace, ColModel, ColParser, ColSphere, ColSurface, ColVersion, RwCol, COL_FLAG_NOT_EMPTY,
+    COL_FLAG_SHADOW_MESH,
+};
+use rw_parser_rs::renderware::col::col_writer::ColWriter;
+use rw_parser_rs::renderware::common::types::RwVector3;
+use rw_parser_rs::renderware::rw_error::RwParseError;
+use rw_parser_rs::renderware::txd::txd_parser::{RwTextureDictionary, RwTextureNative, RwTxd, TxdParser};
+use rw_parser_rs::renderware::txd::txd_writer::{TxdTextureFormat, TxdWriter};
+use rw_parser_rs::renderware::utils::image_format_enums::PlatformType;
+
+fn name(max_length: usize) -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(b"abcdefghijklmnopqrstuvwxyz0123456789_".to_vec()), 1..=max_length)
+        .prop_map(|bytes| String::from_utf8(bytes).unwrap())
+}
+
+/// Returns textures of power of two sizes with random RGBA pixels.
+fn texture() -> impl Strategy<Value = RwTextureNative> {
+    (2u32..=6, 2u32..=6)
+        .prop_flat_map(|(width, height)| {
+            let (width, height) = (1u16 << width, 1u16 << height);
+            (
+                name(31),
+                prop::option::of(name(31)),
+                0u8..=6,
+                (0u8..=4, 0u8..=4),
+                Just((width, height)),
+                prop::collection::vec(any::<u8>(), width as usize * height as usize * 4),
+            )
+        })
+        .prop_map(|(texture_name, mask_name, filter_mode, (u_addressing, v_addressing), (width, height), pixels)| {
+            RwTextureNative {
+                texture_name,
+                mask_name: mask_name.unwrap_or_default(),
+                filter_mode,
+                u_addressing,
+                v_addressing,
+                width,
+                height,
+                mipmaps: vec![pixels],
+                ..Default::default()
+            }
+        })
+}
+
+fn txd() -> impl Strategy<Value = RwTxd> {
+    (prop::collection::vec(texture(), 0..4), prop::option::of(name(23))).prop_map(|(texture_natives, parent_name)| {
+        RwTxd {
+            texture_dictionary: RwTextureDictionary::new(texture_natives),
+            parent_name,
+        }
+    })
+}
+
+fn txd_format() -> impl Strategy<Value = TxdTextureFormat> {
+    prop::sample::select(vec![
+        TxdTextureFormat::Raw8888,
+        TxdTextureFormat::Palette8,
+        TxdTextureFormat::Dxt1,
+        TxdTextureFormat::Dxt3,
+        TxdTextureFormat::Dxt5,
+    ])
+}
+
+/// Returns a coordinate that `COL2+` stores exactly, in 1/128 units.
+fn coordinate() -> impl Strategy<Value = f32> {
+    any::<i16>().prop_map(|value| value as f32 / 128.0)
+}
+
+fn vector() -> impl Strategy<Value = RwVector3> {
+    (coordinate(), coordinate(), coordinate()).prop_map(|(x, y, z)| RwVector3 { x, y, z })
+}
+
+fn surface() -> impl Strategy<Value = ColSurface> {
+    (any::<u8>(), any::<u8>(), any::<u8>(), any::<u8>()).prop_map(|(material, flag, brightness, light)| ColSurface {
+        material,
+        flag,
+        brightness,
+        light,
+    })
+}
+
+/// Returns a mesh whose faces reference every vertex, as `COL2+` only
+/// stores as many vertices as the faces use.
+fn mesh(version: ColVersion) -> impl Strategy<Value = (Vec<RwVector3>, Vec<ColFace>)> {
+    (1u32..32).prop_flat_map(move |vertex_count| {
+        let face = (0..vertex_count, 0..vertex_count, 0..vertex_count, surface()).prop_map(move |(a, b, c, surface)| {
+            let surface = match version {
+                ColVersion::COL1 => surface,
+                _ => ColSurface {
+                    material: surface.material,
+                    light: surface.light,
+                    ..Default::default()
+                },
+            };
+            ColFace { a, b, c, surface }
+        });
+        (prop::collection::vec(vector(), vertex_count as usize), prop::collection::vec(face, 1..16)).prop_map(
+            |(vertices, mut faces)| {
+                faces[0].c = vertices.len() as u32 - 1;
+                (vertices, faces)
+            },
+        )
+    })
+}
+
+fn col_model() -> impl Strategy<Value = ColModel> {
+    prop::sample::select(vec![ColVersion::COL1, ColVersion::COL2, ColVersion::COL3, ColVersion::COL4]).prop_flat_map(
+        |version| {
+            let sphere = (vector(), coordinate(), surface()).prop_map(|(center, radius, surface)| ColSphere {
+                center,
+                radius,
+                surface,
+            });
+            let col_box = (vector(), vector(), surface()).prop_map(|(min, max, surface)| ColBox { min, max, surface });
+            let shadow = match version {
+                ColVersion::COL1 | ColVersion::COL2 => Just(None).boxed(),
+                _ => prop::option::of(mesh(version)).boxed(),
+            };
+            (
+                Just(version),
+                name(21),
+                any::<u16>(),
+                (vector(), vector(), vector(), coordinate()),
+                prop::collection::vec(sphere, 0..4),
+                prop::collection::vec(col_box, 0..4),
+                prop::option::of(mesh(version)),
+                shadow,
+            )
+        },
+    )
+    .prop_map(|(version, name, model_id, (min, max, center, radius), spheres, boxes, mesh, shadow)| {
+        let (vertices, faces) = mesh.unwrap_or_default();
+        let (shadow_vertices, shadow_faces) = shadow.unwrap_or_default();
+        let mut flags = 0;
+        if version != ColVersion::COL1 {
+            if !spheres.is_empty() || !boxes.is_empty() || !faces.is_empty() {
+                flags |= COL_FLAG_NOT_EMPTY;
+            }
+            if !shadow_faces.is_empty() {
+                flags |= COL_FLAG_SHADOW_MESH;
+            }
+        }
+        ColModel {
+            version,
+            name,
+            model_id,
+            bounds: ColBounds { min, max, center, radius },
+            spheres,
+            boxes,
+            vertices,
+            faces,
+            face_groups: Vec::new(),
+            shadow_vertices,
+            shadow_faces,
+            flags,
+        }
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn txd_round_trip(
+        txd in txd(),
+        platform in prop::sample::select(vec![PlatformType::D3d8, PlatformType::D3d9]),
+        format in txd_format(),
+        mipmaps in any::<bool>(),
+    ) {
+        let writer = TxdWriter::new().with_platform(platform).with_format(format).with_mipmaps(mipmaps);
+        let written = TxdParser::new(&writer.write(&txd)?).parse()?;
+        prop_assert_eq!(&written.parent_name, &txd.parent_name);
+
+        let natives = &txd.texture_dictionary.texture_natives;
+        let written_natives = &written.texture_dictionary.texture_natives;
+        prop_assert_eq!(written_natives.len(), natives.len());
+        for (written, native) in written_natives.iter().zip(natives) {
+            prop_assert_eq!(&written.texture_name, &native.texture_name);
+            prop_assert_eq!(&written.mask_name, &native.mask_name);
+            prop_assert_eq!(written.filter_mode, native.filter_mode);
+            prop_assert_eq!((written.u_addressing, written.v_addressing), (native.u_addressing, native.v_addressing));
+            prop_assert_eq!((written.width, written.height), (native.width, native.height));
+            prop_assert_eq!(written.mipmaps[0].len(), native.mipmaps[0].len());
+            if format == TxdTextureFormat::Raw8888 {
+                prop_assert_eq!(&written.mipmaps[0], &native.mipmaps[0]);
+            }
+        }
+
+        // Writing a parsed dictionary as is must not change it
+        let preserved = TxdParser::new(&writer.with_format(TxdTextureFormat::Preserve).write(&written)?).parse()?;
+        prop_assert_eq!(preserved, written);
+    }
+
+    #[test]
+    fn col_round_trip(models in prop::collection::vec(col_model(), 1..4)) {
+        let col = RwCol { models };
+        let written = ColParser::new(&ColWriter::new().write(&col)?).parse()?;
+        prop_assert_eq!(written, col);
+    }
+}
+
+#[test]
+fn col2_rejects_vertices_out_of_range() {
+    let vertex = |x: f32| RwVector3 { x, y: 0.0, z: 0.0 };
+    let origin = vertex(0.0);
+    let model = |far: f32| ColModel {
+        version: ColVersion::COL3,
+        name: "far".to_string(),
+        model_id: 0,
+        bounds: ColBounds { min: origin, max: vertex(far), center: origin, radius: far },
+        spheres: Vec::new(),
+        boxes: Vec::new(),
+        vertices: vec![origin, vertex(1.0), vertex(far)],
+        faces: vec![ColFace { a: 0, b: 1, c: 2, surface: ColSurface::default() }],
+        face_groups: Vec::new(),
+        shadow_vertices: Vec::new(),
+        shadow_faces: Vec::new(),
+        flags: COL_FLAG_NOT_EMPTY,
+    };
+
+    assert!(ColWriter::new().write(&RwCol { models: vec![model(255.0)] }).is_ok());
+    let error = ColWriter::new().write(&RwCol { models: vec![model(300.0)] }).unwrap_err();
+    assert!(matches!(error, RwParseError::UnwritableData { .. }), "{}", error);
+}